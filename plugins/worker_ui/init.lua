@@ -252,6 +252,8 @@ local pause_when_restarted = true
 --   "EXITING"      	- we're shutting down
 local state = "IDLE"
 local start_load_args = {}
+local start_core_options = {}
+local restore_core_options = {}
 
 function is_polling_input_seq()
 	if current_poll_callback then
@@ -367,19 +369,27 @@ function emit_status(light, out)
 	-- abstractions to hide some differences between MAME 0.227 and
 	-- previous versions, similar to get_device_tag
 	local get_item_code, get_image_filename
-	local get_speed_percent, get_effective_frameskip, get_is_recording
+	local get_speed_percent, get_effective_frameskip, get_is_recording, get_window_size
 	if type(machine_video().speed_percent) == "function" then
 		get_item_code			= function(item) return item:code() end
 		get_image_filename		= function(image) return image:filename() end
 		get_speed_percent		= function() return machine_video():speed_percent() end
 		get_effective_frameskip	= function() return machine_video():effective_frameskip() end
 		get_is_recording		= function() return machine_video():is_recording() end
+		get_window_size			= function()
+			local target = machine():render():ui_target()
+			return target:width(), target:height()
+		end
 	else
 		get_item_code			= function(item) return item.code end
 		get_image_filename		= function(image) return image.filename end
 		get_speed_percent		= function() return machine_video().speed_percent end
 		get_effective_frameskip	= function() return machine_video().effective_frameskip end
 		get_is_recording		= function() return machine_video().is_recording end
+		get_window_size			= function()
+			local target = machine().render.ui_target
+			return target.width, target.height
+		end
 	end
 
 	-- we don't always want to send details
@@ -423,6 +433,11 @@ function emit_status(light, out)
 		emit("\t\tthrottled=\"" .. tostring(machine_video().throttled) .. "\"");
 		emit("\t\tthrottle_rate=\"" .. tostring(machine_video().throttle_rate) .. "\"");
 		emit("\t\tis_recording=\"" .. string_from_bool(get_is_recording()) .. "\"");
+		local window_width, window_height = get_window_size()
+		if window_width > 0 and window_height > 0 then
+			emit("\t\twindow_width=\"" .. tostring(window_width) .. "\"");
+			emit("\t\twindow_height=\"" .. tostring(window_height) .. "\"");
+		end
 		emit("\t/>");
 
 		-- <sound> (sound_manager)
@@ -657,8 +672,28 @@ function emit_status(light, out)
 	end
 end
 
+-- sets core options (e.g. - "-record"), returning the previous values so they can be restored
+function set_core_options(values)
+	local options
+	if type(manager.options) == "function" then
+		options = manager:options()
+	else
+		options = manager.options
+	end
+
+	local old_values = {}
+	for name,value in pairs(values) do
+		local entry = options.entries[name]
+		old_values[name] = entry:value()
+		entry:value(value)
+	end
+	return old_values
+end
+
 -- START command
 function command_start(args)
+	restore_core_options = set_core_options(start_core_options)
+	start_core_options = {}
 	emu.start(args[2])
 
 	-- prep initial load args
@@ -670,6 +705,47 @@ function command_start(args)
 	print("@INFO ### Starting emulation...")
 end
 
+-- INPUT_FILE command
+function command_input_file(args)
+	local option = args[2]:lower()
+	if option ~= "record" and option ~= "playback" then
+		print("@ERROR ### Unknown input file type '" .. args[2] .. "'")
+		return
+	end
+
+	-- MAME looks for input files relative to the input directory
+	local directory, filename = string.match(args[3], "^(.*)[/\\]([^/\\]+)$")
+	if filename then
+		start_core_options["input_directory"] = directory
+		start_core_options[option] = filename
+	else
+		start_core_options[option] = args[3]
+	end
+	print("@OK ### Input file '" .. args[3] .. "' will be used when emulation starts")
+end
+
+-- WAV_FILE command
+function command_wav_file(args)
+	start_core_options["wavwrite"] = args[2]
+	print("@OK ### Sound will be recorded to '" .. args[2] .. "' when emulation starts")
+end
+
+-- COMM_LINK command
+function command_comm_link(args)
+	start_core_options["comm_localhost"] = args[2]
+	start_core_options["comm_localport"] = args[3]
+	start_core_options["comm_remotehost"] = args[4]
+	start_core_options["comm_remoteport"] = args[5]
+	start_core_options["comm_framesync"] = (args[6] == "true")
+	print("@OK ### Network link to " .. args[4] .. ":" .. args[5] .. " will be used when emulation starts")
+end
+
+-- BIOS command
+function command_bios(args)
+	start_core_options["bios"] = args[2]
+	print("@OK ### BIOS '" .. args[2] .. "' will be used when emulation starts")
+end
+
 -- STOP command
 function command_stop(args)
 	if state == "ACTIVE" then
@@ -784,7 +860,8 @@ end
 
 -- PASTE command
 function command_paste(args)
-	machine_ioport():natkeyboard():paste(args[2])
+	local text = args[2]:gsub("\\n", "\n")
+	machine_ioport():natkeyboard():paste(text)
 	print("@OK ### Text inputted from clipboard")
 end
 
@@ -1163,6 +1240,10 @@ local commands =
 	["save_snapshot"]				= command_save_snapshot,
 	["begin_recording"]				= command_begin_recording,
 	["end_recording"]				= command_end_recording,
+	["input_file"]					= command_input_file,
+	["wav_file"]					= command_wav_file,
+	["comm_link"]					= command_comm_link,
+	["bios"]						= command_bios,
 	["load"]						= command_load,
 	["unload"]						= command_unload,
 	["create"]						= command_create,
@@ -1248,6 +1329,10 @@ function startplugin()
 			end
 			start_load_args = {}
 
+			-- input files only apply to the emulation they were started with
+			set_core_options(restore_core_options)
+			restore_core_options = {}
+
 			-- it is possible that loading an image will force a reset; we need to only
 			-- enter this block if we don't expect a reset
 			if not will_reset then
@@ -1,66 +1,225 @@
 use std::sync::Arc;
 
 use anyhow::Error;
-use itertools::Itertools;
 use muda::MenuId;
 use serde::Deserialize;
 use serde::Serialize;
-use strum::EnumProperty;
 
+use crate::appstate::InfoDbBuildProgress;
+use crate::collections::OrphanedEntry;
 use crate::dialogs::file::PathType;
+use crate::instance::LaunchRequest;
 use crate::prefs::BuiltinCollection;
+use crate::prefs::CloneDisplay;
+use crate::prefs::ColumnType;
+use crate::prefs::ItemAction;
+use crate::prefs::MachineKind;
 use crate::prefs::PrefsCollection;
+use crate::prefs::PrefsFilter;
 use crate::prefs::PrefsItem;
+use crate::prefs::PrefsNetplay;
 use crate::prefs::SortOrder;
+use crate::remote::RemoteCommand;
+use crate::renames::MachineRename;
+use crate::runtime::MameCommand;
+use crate::software::SoftwareSupport;
 use crate::status::Update;
 use crate::version::MameVersion;
 
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, EnumProperty)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub enum AppCommand {
 	// File menu
 	FileStop,
 	FilePause,
 	FileDevicesAndImages,
+	FileSaveScreenshot,
+	FileToggleRecordMovie,
+	FileToggleRecordInput,
+	FileToggleRecordAudio,
+	FilePlaybackInputDialog,
+	FileToggleRecordMacro,
+	FilePlayMacroDialog,
+	FileStopMacro,
 	FileResetSoft,
 	FileResetHard,
 	FileExit,
 
+	// View menu
+	ViewToggleGrid,
+	ViewGridColumns(u32),
+	ViewCloneDisplay(CloneDisplay),
+	ViewItemAction(ItemAction),
+	ViewToggleHiddenMachineKind(MachineKind),
+	ViewToggleColumn(ColumnType),
+	ViewResetColumnWidths,
+	ViewCommandPalette,
+	ViewLog,
+
 	// Options menu
 	OptionsThrottleRate(f32),
 	OptionsToggleWarp,
 	OptionsToggleSound,
-	#[strum(props(MinimumMame = "0.274"))]
 	OptionsClassic,
 
 	// Settings menu
 	SettingsPaths,
+	SettingsPathsFor(PathType),
+	SettingsAddMameExecutableDialog,
+	SettingsSwitchMameExecutable(String),
+	SettingsShortcuts,
+	SettingsRecording,
+	SettingsAppearance,
 	SettingsToggleBuiltinCollection(BuiltinCollection),
+	SettingsSyncDirectoryDialog,
+	SettingsSyncUrlDialog,
+	SettingsSyncNow,
+	SettingsStopSync,
 	SettingsReset,
+	SettingsNormalizeFoldersDialog,
+	SettingsNormalizeFolders,
+	SettingsOrphanedEntriesDialog,
+	SettingsRemoveOrphanedEntries(Vec<OrphanedEntry>),
+	SettingsScreenshotsToggleAsk,
+	SettingsScreenshotsTogglePerMachineDirectories,
+	SettingsScreenshotsTemplate(String),
+	SettingsRemoteControlToggleEnabled,
+	SettingsRemoteControlToggleAllowLan,
+	SettingsRemoteControlShowToken,
+	SettingsToggleDiscordRichPresence,
+	SettingsToggleMountAllSoftwareParts,
+	SettingsAttractModeToggleEnabled,
+	SettingsAttractModeToggleAutoLaunch,
 
 	// Help menu
 	HelpWebSite,
+	HelpCheckForMameUpdates,
+	HelpGenerateDiagnosticReport,
+	HelpLogLevelsDialog,
 	HelpAbout,
 
 	// MAME communication
 	MameSessionStarted,
 	MameSessionEnded,
+	MameSessionError(String),
 	MameStatusUpdate(Update),
 	MamePing,
+	DetachedSessionStatusUpdate {
+		session_id: usize,
+		update: Update,
+	},
+	DetachedSessionEnded {
+		session_id: usize,
+	},
 	ErrorMessageBox(String),
+	RemoteControl(RemoteCommand),
+	Launch(LaunchRequest),
 
 	// Other
 	RunMame {
 		machine_name: String,
 		initial_loads: Vec<(Arc<str>, Arc<str>)>,
 	},
+	RunMameUnsupportedSoftware {
+		machine_name: String,
+		initial_loads: Vec<(Arc<str>, Arc<str>)>,
+		software_description: String,
+		supported: SoftwareSupport,
+	},
+	PlaybackInput {
+		machine_name: String,
+		path: String,
+	},
+	RunMameDetached {
+		machine_name: String,
+		initial_loads: Vec<(Arc<str>, Arc<str>)>,
+	},
+	RunMameConfigure {
+		machine_name: String,
+		initial_loads: Vec<(Arc<str>, Arc<str>)>,
+	},
+	/// Runs software on a machine chosen from its context menu, remembering the choice
+	RunSoftwareOn {
+		software_list: String,
+		software: String,
+		machine_name: String,
+		command: Box<AppCommand>,
+	},
+	SessionSummaryDismiss,
+	NetplayDialog {
+		machine_name: String,
+	},
+	RunMameNetplay {
+		machine_name: String,
+		netplay: PrefsNetplay,
+	},
+	ReferenceSnapshotDialog {
+		machine_name: String,
+	},
+	ReferenceSnapshotUseLatest {
+		machine_name: String,
+	},
+	ReferenceSnapshotSet {
+		machine_name: String,
+		path: Option<String>,
+	},
+	ReferenceSnapshotCompare {
+		machine_name: String,
+	},
+	MachineDetailsDialog {
+		machine_name: String,
+	},
+	SoftwareDetailsDialog {
+		software_list: String,
+		software: String,
+	},
+	SoftwarePartsDialog {
+		machine_name: String,
+		software_list: String,
+		software: String,
+	},
+	MachineCaptureDirectoryDialog {
+		machine_name: String,
+	},
+	MachineCaptureDirectorySet {
+		machine_name: String,
+		directory: Option<String>,
+	},
+	FolderCaptureDirectoryDialog {
+		folder_name: String,
+	},
+	FolderCaptureDirectorySet {
+		folder_name: String,
+		directory: Option<String>,
+	},
+	FolderFamilyBiosSet {
+		folder_name: String,
+		parent_name: String,
+		bios: Option<String>,
+	},
+	SessionSwitch {
+		session_id: Option<usize>,
+	},
 	Browse(PrefsCollection),
+	ApplyMachineRenames(Vec<MachineRename>),
 	HistoryAdvance(isize),
 	SearchText(String),
 	ItemsSort(usize, SortOrder),
 	ItemsSelectedChanged,
+	ItemsAddFilter(PrefsFilter),
+	ItemsRemoveFilter(usize),
+	ItemsToggleCloneGroup {
+		machine_name: String,
+	},
+	ItemsSurpriseMe {
+		run: bool,
+	},
 	AddToExistingFolder(usize, Vec<PrefsItem>),
 	AddToNewFolder(String, Vec<PrefsItem>),
 	AddToNewFolderDialog(Vec<PrefsItem>),
+	CreateDesktopShortcut {
+		item: PrefsItem,
+		name: String,
+	},
 	RemoveFromFolder(String, Vec<PrefsItem>),
 	MoveCollection {
 		old_index: usize,
@@ -76,7 +235,23 @@ pub enum AppCommand {
 		index: usize,
 		new_name: String,
 	},
+	/// Nests a folder within another folder (by name), or moves it to the top level
+	MoveFolder {
+		index: usize,
+		parent: Option<String>,
+	},
+	ToggleFolderExpanded {
+		name: String,
+	},
+	/// Saves a folder collection (and the settings for its items) to a file that can be shared
+	ExportCollectionDialog {
+		index: usize,
+	},
+	ImportCollectionDialog,
 	ChoosePath(PathType),
+	FixMameExecutablePermissions,
+	InstallWorkerUiPlugin,
+	AdoptStandardPaths,
 	BookmarkCurrentCollection,
 	LoadImageDialog {
 		tag: String,
@@ -95,9 +270,14 @@ pub enum AppCommand {
 	InfoDbBuildLoad {
 		force_refresh: bool,
 	},
-	InfoDbBuildProgress {
-		machine_description: String,
+	InfoDbBuildFromListXmlDialog,
+	InfoDbBuildFromListXml {
+		path: String,
 	},
+	InfoDbDiffDialog,
+	InfoDbBuildProgress(InfoDbBuildProgress),
+	InfoDbBuildTogglePause,
+	InfoDbBuildStartQueued,
 	InfoDbBuildComplete,
 	InfoDbBuildCancel,
 }
@@ -105,12 +285,12 @@ pub enum AppCommand {
 const MENU_PREFIX: &str = "MENU_";
 
 impl AppCommand {
+	/// The oldest MAME that supports this command, taken from the `MameCommand` it issues
 	pub fn minimum_mame_version(&self) -> Option<MameVersion> {
-		let s = self.get_str("MinimumMame")?;
-		let Some((Ok(major), Ok(minor))) = s.split('.').map(|s| s.parse()).collect_tuple() else {
-			panic!("Failed to parse {s}");
-		};
-		Some(MameVersion::new(major, minor))
+		match self {
+			AppCommand::OptionsClassic => MameCommand::ClassicMenu.minimum_mame_version(),
+			_ => None,
+		}
 	}
 }
 
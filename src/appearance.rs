@@ -0,0 +1,42 @@
+//! Application wide font settings, propagated to each Slint window through the `Appearance` global
+use std::cell::RefCell;
+
+use slint::ComponentHandle;
+use slint::Global;
+
+use crate::prefs::PrefsAppearance;
+use crate::ui::Appearance;
+
+thread_local! {
+	static CURRENT_APPEARANCE: RefCell<PrefsAppearance> = RefCell::new(PrefsAppearance::default());
+}
+
+/// Applies an appearance to a window, and makes it the appearance that windows created later get
+pub fn set_appearance<C>(component: &C, appearance: &PrefsAppearance)
+where
+	C: ComponentHandle,
+	for<'a> Appearance<'a>: Global<'a, C>,
+{
+	CURRENT_APPEARANCE.set(appearance.clone());
+	apply_appearance(component, appearance);
+}
+
+/// Applies the current appearance to a newly created window
+pub fn apply_current_appearance<C>(component: &C)
+where
+	C: ComponentHandle,
+	for<'a> Appearance<'a>: Global<'a, C>,
+{
+	CURRENT_APPEARANCE.with_borrow(|appearance| apply_appearance(component, appearance));
+}
+
+/// Applies an appearance to a single window (e.g. - to preview it)
+pub fn apply_appearance<C>(component: &C, appearance: &PrefsAppearance)
+where
+	C: ComponentHandle,
+	for<'a> Appearance<'a>: Global<'a, C>,
+{
+	let global = component.global::<Appearance>();
+	global.set_font_family(appearance.font_family.as_deref().unwrap_or_default().into());
+	global.set_font_size(appearance.font_size.unwrap_or_default() as f32);
+}
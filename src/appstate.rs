@@ -1,20 +1,30 @@
 use std::cell::RefCell;
 use std::path::Path;
+use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use std::thread::sleep;
 use std::thread::spawn;
 use std::thread::JoinHandle;
 use std::time::Duration;
+use std::time::Instant;
 
 use anyhow::Result;
+use serde::Deserialize;
+use serde::Serialize;
 use slint::invoke_from_event_loop;
 use strum::EnumProperty;
 use throttle::Throttle;
+use tracing::event;
+use tracing::Level;
 
 use crate::appcommand::AppCommand;
+use crate::dialogs::file::PathType;
+use crate::dialogs::paths::detect_standard_paths;
 use crate::info::InfoDb;
+use crate::info::ListXmlProgress;
 use crate::prefs::PrefsPaths;
 use crate::runtime::args::preflight_checks_public;
 use crate::runtime::args::PreflightProblem;
@@ -22,10 +32,13 @@ use crate::status::Status;
 use crate::status::Update;
 use crate::threadlocalbubble::ThreadLocalBubble;
 
+const LOG: Level = Level::DEBUG;
+
 #[derive(Clone)]
 pub struct AppState {
 	pub info_db: Option<Rc<InfoDb>>,
 	phase: Phase,
+	background_build: Option<BackgroundBuild>,
 	shutting_down: bool,
 	callback: CommandCallback,
 }
@@ -40,7 +53,8 @@ enum Phase {
 	},
 	InfoDbBuilding {
 		job: Rc<RefCell<Option<InfoDbBuildJob>>>,
-		machine_description: Option<String>,
+		progress: Option<String>,
+		paused: bool,
 	},
 	Active {
 		status: Rc<Status>,
@@ -48,6 +62,27 @@ enum Phase {
 	Shutdown,
 }
 
+/// A rebuild of the InfoDb that happens while the existing InfoDb remains in use
+#[derive(Clone, Debug)]
+struct BackgroundBuild {
+	job: Rc<RefCell<Option<InfoDbBuildJob>>>,
+	progress: Option<String>,
+	/// Set if the build failed; the existing InfoDb stays in use
+	failure: Option<String>,
+	/// The new InfoDb, waiting for the running machine to stop before being swapped in
+	completed: Option<Rc<InfoDb>>,
+	/// Set if the build has not started yet because a machine is running
+	queued: Option<Rc<QueuedBuild>>,
+}
+
+/// A background build waiting for the running machine to stop, so as not to compete with it
+#[derive(Debug)]
+struct QueuedBuild {
+	prefs_path: Option<PathBuf>,
+	mame_executable_path: Option<String>,
+	source: InfoDbSource,
+}
+
 type CommandCallback = Rc<dyn Fn(AppCommand) + 'static>;
 
 #[derive(Debug)]
@@ -55,6 +90,7 @@ pub struct Report<'a> {
 	pub message: &'a Message,
 	pub submessage: Option<&'a str>,
 	pub button: Option<Button>,
+	pub secondary_button: Option<Button>,
 	pub issues: &'a [Message],
 }
 
@@ -64,9 +100,21 @@ pub struct Button {
 	pub command: AppCommand,
 }
 
+/// Progress of an InfoDb build, as reported by the build thread
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct InfoDbBuildProgress {
+	pub machine_description: String,
+	pub machines_processed: usize,
+	/// Estimated from the previous InfoDb, if we had one
+	pub machines_total: Option<usize>,
+	/// Time spent building, not counting time spent paused
+	pub elapsed: Duration,
+}
+
 #[derive(Debug)]
 struct InfoDbBuildJob {
 	cancelled: Arc<AtomicBool>,
+	paused: Arc<AtomicBool>,
 	join_handle: JoinHandle<Result<Option<InfoDb>>>,
 }
 
@@ -79,9 +127,21 @@ pub enum Message {
 	// progress messages
 	#[strum(to_string = "Building MAME machine info database...", props(Spinning = "true"))]
 	BuildingInfoDb,
+	#[strum(to_string = "Building MAME machine info database (paused)")]
+	BuildingInfoDbPaused,
+	#[strum(to_string = "Refreshing MAME machine info database...", props(Spinning = "true"))]
+	RefreshingInfoDb,
+	#[strum(to_string = "MAME machine info database will be updated when the running machine stops")]
+	InfoDbRefreshPending,
+	#[strum(to_string = "MAME machine info database will be refreshed when the running machine stops")]
+	InfoDbRefreshQueued,
 	#[strum(to_string = "Resetting MAME...", props(Spinning = "true"))]
 	MameResetting,
 
+	// safe mode
+	#[strum(to_string = "BletchMAME was started in safe mode")]
+	SafeMode,
+
 	// failure conditions
 	#[strum(to_string = "BletchMAME requires additional configuration in order to properly interface with MAME")]
 	InadequateMameSetup,
@@ -103,6 +163,8 @@ pub enum Message {
 	PluginsBootNotFound,
 	#[strum(to_string = "BletchMAME worker_ui plugin not found")]
 	WorkerUiPluginNotFound,
+	#[strum(to_string = "Standard MAME directories were found alongside the MAME executable")]
+	StandardPathsFound,
 }
 
 impl AppState {
@@ -117,6 +179,7 @@ impl AppState {
 				button: None,
 				issues: [].into(),
 			},
+			background_build: None,
 			shutting_down: false,
 			callback,
 		}
@@ -124,19 +187,35 @@ impl AppState {
 
 	/// Attempt to load a persisted InfoDB, or if unavailable trigger a rebuild
 	pub fn infodb_load(&self, prefs_path: Option<&Path>, paths: &PrefsPaths, force_refresh: bool) -> Option<Self> {
-		// try to load the InfoDb
+		// quick run of preflight
+		let problems = preflight_checks_public(paths.mame_executable.as_deref(), &paths.variables, &paths.plugins);
+
+		// if we're refreshing while active, rebuild in the background and keep using what we have
+		if force_refresh && problems.is_empty() && self.can_build_in_background() {
+			return self.start_background_build(prefs_path, paths, InfoDbSource::ChildProcess);
+		}
+		self.cancel_background_build();
+
+		// try to load the InfoDb; if it is missing or corrupt we rebuild it below
 		let info_db = paths
 			.mame_executable
 			.as_deref()
-			.and_then(|mame_executable_path| InfoDb::load(prefs_path, mame_executable_path).ok())
+			.and_then(|mame_executable_path| {
+				InfoDb::load(prefs_path, mame_executable_path)
+					.inspect_err(|e| event!(LOG, "infodb_load(): load failed; rebuilding: {e:?}"))
+					.ok()
+			})
 			.map(Rc::new);
 
-		// quick run of preflight
-		let problems = preflight_checks_public(paths.mame_executable.as_deref(), &paths.plugins);
-
 		// determine the new phase
 		let phase = if !problems.is_empty() {
-			let issues = problems.into_iter().map(Message::from).collect();
+			let standard_paths_found =
+				!detect_standard_paths(paths, |path_type, path| path_type.path_exists(path)).is_empty();
+			let issues = problems
+				.into_iter()
+				.map(Message::from)
+				.chain(standard_paths_found.then_some(Message::StandardPathsFound))
+				.collect();
 			Phase::Inactive {
 				message: Message::InadequateMameSetup,
 				submessage: None,
@@ -144,15 +223,20 @@ impl AppState {
 				issues,
 			}
 		} else if info_db.is_none() || force_refresh {
+			let source = InfoDbSource::ChildProcess;
+			let machines_total = info_db.as_ref().or(self.info_db.as_ref()).map(|x| x.machines().len());
 			let job = spawn_infodb_build_thread(
 				prefs_path,
-				paths.mame_executable.as_ref().unwrap(),
+				paths.mame_executable.as_deref(),
+				source,
+				machines_total,
 				self.callback.clone(),
 			);
 			let job = Rc::new(RefCell::new(Some(job)));
 			Phase::InfoDbBuilding {
 				job,
-				machine_description: None,
+				progress: None,
+				paused: false,
 			}
 		} else {
 			Phase::initial_active()
@@ -162,32 +246,235 @@ impl AppState {
 		let new_state = Self {
 			info_db,
 			phase,
+			background_build: None,
 			..self.clone()
 		};
 		Some(new_state)
 	}
 
-	pub fn infodb_build_progress(&self, machine_description: String) -> Option<Self> {
-		let Phase::InfoDbBuilding { job, .. } = &self.phase else {
+	/// Build the InfoDb from a previously saved `mame -listxml` file (presumably generated on another machine)
+	pub fn infodb_build_from_listxml(
+		&self,
+		prefs_path: Option<&Path>,
+		paths: &PrefsPaths,
+		listxml_path: PathBuf,
+	) -> Option<Self> {
+		// we can't start a build if we're already building
+		if matches!(self.phase, Phase::InfoDbBuilding { .. }) {
+			return None;
+		}
+
+		let source = InfoDbSource::ListXmlFile(listxml_path);
+		if self.can_build_in_background() {
+			return self.start_background_build(prefs_path, paths, source);
+		}
+		self.cancel_background_build();
+		let machines_total = self.info_db.as_ref().map(|x| x.machines().len());
+		let job = spawn_infodb_build_thread(
+			prefs_path,
+			paths.mame_executable.as_deref(),
+			source,
+			machines_total,
+			self.callback.clone(),
+		);
+		let job = Rc::new(RefCell::new(Some(job)));
+		let phase = Phase::InfoDbBuilding {
+			job,
+			progress: None,
+			paused: false,
+		};
+		let new_state = Self {
+			phase,
+			background_build: None,
+			..self.clone()
+		};
+		Some(new_state)
+	}
+
+	/// Enter safe mode; the InfoDb is not loaded until the user explicitly asks for it
+	pub fn safe_mode(&self) -> Option<Self> {
+		let button = Button {
+			text: "Load MAME Machine Info",
+			command: AppCommand::InfoDbBuildLoad { force_refresh: false },
+		};
+		let phase = Phase::Inactive {
+			message: Message::SafeMode,
+			submessage: Some("MAME will not be run without confirmation".to_string()),
+			button: Some(button),
+			issues: [].into(),
+		};
+		let new_state = Self { phase, ..self.clone() };
+		Some(new_state)
+	}
+
+	/// We can only rebuild in the background if there is an existing InfoDb in use
+	fn can_build_in_background(&self) -> bool {
+		self.info_db.is_some() && self.status().is_some()
+	}
+
+	fn start_background_build(
+		&self,
+		prefs_path: Option<&Path>,
+		paths: &PrefsPaths,
+		source: InfoDbSource,
+	) -> Option<Self> {
+		// only one build at a time (and a failed or pending build needs to be dismissed first)
+		if self.background_build.is_some() {
+			return None;
+		}
+
+		// building competes with a running machine for MAME and the CPU, so wait for it to stop
+		let queued = QueuedBuild {
+			prefs_path: prefs_path.map(|x| x.to_path_buf()),
+			mame_executable_path: paths.mame_executable.clone(),
+			source,
+		};
+		let background_build = BackgroundBuild {
+			job: Rc::new(RefCell::new(None)),
+			progress: None,
+			failure: None,
+			completed: None,
+			queued: Some(Rc::new(queued)),
+		};
+		let new_state = Self {
+			background_build: Some(background_build),
+			..self.clone()
+		};
+		Some(new_state.with_queued_background_build_started(false))
+	}
+
+	/// Starts a queued background build, if no machine is running (or we were told to go ahead regardless)
+	fn with_queued_background_build_started(mut self, force: bool) -> Self {
+		let is_running = self.status().is_none_or(|s| s.running.is_some());
+		let Some(background_build) = self.background_build.as_mut() else {
+			return self;
+		};
+		let Some(queued) = background_build.queued.as_ref().filter(|_| force || !is_running) else {
+			return self;
+		};
+
+		let machines_total = self.info_db.as_ref().map(|x| x.machines().len());
+		let job = spawn_infodb_build_thread(
+			queued.prefs_path.as_deref(),
+			queued.mame_executable_path.as_deref(),
+			queued.source.clone(),
+			machines_total,
+			self.callback.clone(),
+		);
+		background_build.job = Rc::new(RefCell::new(Some(job)));
+		background_build.queued = None;
+		self
+	}
+
+	/// Run a queued InfoDb refresh now, alongside the running machine
+	pub fn infodb_build_start_queued(&self) -> Option<Self> {
+		self.background_build.as_ref()?.queued.as_ref()?;
+		Some(self.clone().with_queued_background_build_started(true))
+	}
+
+	fn cancel_background_build(&self) {
+		let job = self.background_build.as_ref().and_then(|x| x.job.borrow_mut().take());
+		if let Some(job) = job {
+			// the build thread will wind down on its own; we don't need to wait for it
+			job.cancelled.store(true, Ordering::Relaxed);
+		}
+	}
+
+	/// Swaps in a completed background build, unless a machine is running
+	fn with_completed_background_build(mut self) -> Self {
+		let is_running = self.status().is_some_and(|s| s.running.is_some());
+		let completed = self.background_build.as_ref().and_then(|x| x.completed.clone());
+		if let (false, Some(info_db)) = (is_running, completed) {
+			self.info_db = Some(info_db);
+			self.background_build = None;
+		}
+		self
+	}
+
+	pub fn infodb_build_progress(&self, progress: &InfoDbBuildProgress) -> Option<Self> {
+		if let Some(background_build) = &self.background_build {
+			let background_build = BackgroundBuild {
+				progress: Some(progress.text()),
+				..background_build.clone()
+			};
+			let new_state = Self {
+				background_build: Some(background_build),
+				..self.clone()
+			};
+			return Some(new_state);
+		}
+
+		let Phase::InfoDbBuilding { job, paused, .. } = &self.phase else {
 			unreachable!()
 		};
 
 		let phase = Phase::InfoDbBuilding {
 			job: job.clone(),
-			machine_description: Some(machine_description),
+			progress: Some(progress.text()),
+			paused: *paused,
+		};
+		let new_state = Self { phase, ..self.clone() };
+		Some(new_state)
+	}
+
+	pub fn infodb_build_toggle_pause(&self) -> Option<Self> {
+		let Phase::InfoDbBuilding { job, progress, paused } = &self.phase else {
+			return None;
+		};
+
+		let paused = !paused;
+		job.borrow().as_ref()?.paused.store(paused, Ordering::Relaxed);
+		let phase = Phase::InfoDbBuilding {
+			job: job.clone(),
+			progress: progress.clone(),
+			paused,
 		};
 		let new_state = Self { phase, ..self.clone() };
 		Some(new_state)
 	}
 
 	pub fn infodb_build_complete(&self) -> Option<Self> {
+		if self.background_build.is_some() {
+			return self.background_build_complete();
+		}
 		self.internal_infodb_build_complete(false)
 	}
 
 	pub fn infodb_build_cancel(&self) -> Option<Self> {
+		if self.background_build.is_some() {
+			self.cancel_background_build();
+			let new_state = Self {
+				background_build: None,
+				..self.clone()
+			};
+			return Some(new_state);
+		}
 		self.internal_infodb_build_complete(true)
 	}
 
+	fn background_build_complete(&self) -> Option<Self> {
+		let background_build = self.background_build.as_ref().unwrap();
+		let job = background_build.job.borrow_mut().take()?;
+
+		// the old InfoDb stays in use if we failed or were cancelled
+		let background_build = match job.join_handle.join().unwrap() {
+			Ok(Some(info_db)) => Some(BackgroundBuild {
+				completed: Some(Rc::new(info_db)),
+				..background_build.clone()
+			}),
+			Ok(None) => None,
+			Err(e) => Some(BackgroundBuild {
+				failure: Some(e.to_string()),
+				..background_build.clone()
+			}),
+		};
+		let new_state = Self {
+			background_build,
+			..self.clone()
+		};
+		Some(new_state.with_completed_background_build())
+	}
+
 	fn internal_infodb_build_complete(&self, cancel: bool) -> Option<Self> {
 		// we expect to be in the process of building, and to be able to "take" the job
 		let Phase::InfoDbBuilding { job, .. } = &self.phase else {
@@ -247,7 +534,11 @@ impl AppState {
 		let status = Rc::new(self.status().unwrap().merge(update));
 		let phase = Phase::Active { status };
 		let new_state = Self { phase, ..self.clone() };
-		Some(new_state)
+		Some(
+			new_state
+				.with_completed_background_build()
+				.with_queued_background_build_started(false),
+		)
 	}
 
 	/// The MAME session ended; return a new state
@@ -264,7 +555,11 @@ impl AppState {
 					Phase::initial_active()
 				};
 				let new_state = Self { phase, ..self.clone() };
-				Some(new_state)
+				Some(
+					new_state
+						.with_completed_background_build()
+						.with_queued_background_build_started(false),
+				)
 			}
 			Phase::Shutdown => Some(self.clone()),
 		}
@@ -328,23 +623,31 @@ impl AppState {
 					message,
 					submessage: submessage.as_deref(),
 					button: button.clone(),
+					secondary_button: None,
 					issues,
 				};
 				Some(report)
 			}
 
-			Phase::InfoDbBuilding {
-				machine_description, ..
-			} => {
-				let message = &Message::BuildingInfoDb;
+			Phase::InfoDbBuilding { progress, paused, .. } => {
+				let (message, pause_text) = if *paused {
+					(&Message::BuildingInfoDbPaused, "Resume")
+				} else {
+					(&Message::BuildingInfoDb, "Pause")
+				};
 				let button = Button {
 					text: "Cancel",
 					command: AppCommand::InfoDbBuildCancel,
 				};
+				let secondary_button = Button {
+					text: pause_text,
+					command: AppCommand::InfoDbBuildTogglePause,
+				};
 				let report = Report {
 					message,
-					submessage: machine_description.as_deref(),
+					submessage: progress.as_deref(),
 					button: Some(button),
+					secondary_button: Some(secondary_button),
 					issues: &[],
 				};
 				Some(report)
@@ -360,6 +663,7 @@ impl AppState {
 					message,
 					submessage: None,
 					button: Some(button),
+					secondary_button: None,
 					issues: &[],
 				}
 			}),
@@ -369,6 +673,7 @@ impl AppState {
 					message: &Message::Blank,
 					submessage: None,
 					button: None,
+					secondary_button: None,
 					issues: &[],
 				};
 				Some(report)
@@ -376,9 +681,50 @@ impl AppState {
 		}
 	}
 
+	/// Reports on an InfoDb rebuild happening in the background, if any
+	pub fn background_report(&self) -> Option<Report<'_>> {
+		let background_build = self.background_build.as_ref()?;
+		let (message, submessage, button_text) = if let Some(failure) = &background_build.failure {
+			(&Message::InfoDbBuildFailure, Some(failure), "Dismiss")
+		} else if background_build.queued.is_some() {
+			(&Message::InfoDbRefreshQueued, None, "Cancel")
+		} else if background_build.completed.is_some() {
+			(&Message::InfoDbRefreshPending, None, "Discard")
+		} else {
+			(&Message::RefreshingInfoDb, background_build.progress.as_ref(), "Cancel")
+		};
+		let button = Button {
+			text: button_text,
+			command: AppCommand::InfoDbBuildCancel,
+		};
+		let secondary_button = background_build.queued.is_some().then_some(Button {
+			text: "Refresh Now",
+			command: AppCommand::InfoDbBuildStartQueued,
+		});
+		let report = Report {
+			message,
+			submessage: submessage.map(|x| x.as_str()),
+			button: Some(button),
+			secondary_button,
+			issues: &[],
+		};
+		Some(report)
+	}
+
 	pub fn is_shutdown(&self) -> bool {
 		matches!(self.phase, Phase::Shutdown)
 	}
+
+	/// Become active with an InfoDb that was loaded out of band; the test harness has no MAME to build one
+	#[cfg(feature = "test-harness")]
+	pub fn with_info_db(&self, info_db: Rc<InfoDb>) -> Option<Self> {
+		let new_state = Self {
+			info_db: Some(info_db),
+			phase: Phase::initial_active(),
+			..self.clone()
+		};
+		Some(new_state)
+	}
 }
 
 impl Phase {
@@ -388,6 +734,36 @@ impl Phase {
 	}
 }
 
+impl InfoDbBuildProgress {
+	/// Text for the report view; the machine being processed, then counts, throughput and (if we can) an ETA
+	pub fn text(&self) -> String {
+		let elapsed_secs = self.elapsed.as_secs_f64();
+		let rate = (elapsed_secs > 0.0).then(|| self.machines_processed as f64 / elapsed_secs);
+		let counts = match self.machines_total {
+			Some(total) => format!("{} of ~{} machines", self.machines_processed, total),
+			None => format!("{} machines", self.machines_processed),
+		};
+		let rate_text = rate.map(|rate| format!(" at {rate:.0}/s")).unwrap_or_default();
+		let eta_text = Option::zip(self.machines_total, rate.filter(|&rate| rate > 0.0))
+			.map(|(total, rate)| {
+				let remaining = total.saturating_sub(self.machines_processed) as f64 / rate;
+				let remaining = Duration::from_secs_f64(remaining);
+				format!(", about {} remaining", duration_text(remaining))
+			})
+			.unwrap_or_default();
+		format!("{}\n{counts}{rate_text}{eta_text}", self.machine_description)
+	}
+}
+
+fn duration_text(duration: Duration) -> String {
+	let secs = duration.as_secs();
+	if secs >= 60 {
+		format!("{}m {:02}s", secs / 60, secs % 60)
+	} else {
+		format!("{secs}s")
+	}
+}
+
 impl Message {
 	pub fn spinning(&self) -> bool {
 		match self.get_str("Spinning") {
@@ -396,6 +772,25 @@ impl Message {
 			_ => unreachable!(),
 		}
 	}
+
+	/// The action that can be taken to fix this issue, if any
+	pub fn fix(&self) -> Option<Button> {
+		let (text, command) = match self {
+			Message::NoMameExecutablePath | Message::NoMameExecutable => {
+				("Set Path...", AppCommand::SettingsPathsFor(PathType::MameExecutable))
+			}
+			Message::MameExecutableIsNotExecutable if cfg!(unix) => {
+				("Make Executable", AppCommand::FixMameExecutablePermissions)
+			}
+			Message::NoPluginsPaths | Message::PluginsBootNotFound => {
+				("Set Path...", AppCommand::SettingsPathsFor(PathType::Plugins))
+			}
+			Message::WorkerUiPluginNotFound => ("Install", AppCommand::InstallWorkerUiPlugin),
+			Message::StandardPathsFound => ("Use Standard Paths", AppCommand::AdoptStandardPaths),
+			_ => return None,
+		};
+		Some(Button { text, command })
+	}
 }
 
 impl From<PreflightProblem> for Message {
@@ -411,28 +806,57 @@ impl From<PreflightProblem> for Message {
 	}
 }
 
+/// Where the `-listxml` output used to build an InfoDb comes from
+#[derive(Clone, Debug)]
+enum InfoDbSource {
+	/// Invoke MAME with `-listxml`
+	ChildProcess,
+	/// A file with previously saved `-listxml` output
+	ListXmlFile(PathBuf),
+}
+
 fn spawn_infodb_build_thread(
 	prefs_path: Option<&Path>,
-	mame_executable_path: &str,
+	mame_executable_path: Option<&str>,
+	source: InfoDbSource,
+	machines_total: Option<usize>,
 	callback: CommandCallback,
 ) -> InfoDbBuildJob {
 	let prefs_path = prefs_path.map(|x| x.to_path_buf());
-	let mame_executable_path = mame_executable_path.to_string();
+	let mame_executable_path = mame_executable_path.map(|x| x.to_string());
 	let callback_bubble = ThreadLocalBubble::new(callback);
 	let cancelled = Arc::new(AtomicBool::from(false));
 	let cancelled_clone = cancelled.clone();
+	let paused = Arc::new(AtomicBool::from(false));
+	let paused_clone = paused.clone();
 	let join_handle = spawn(move || {
 		let prefs_path = prefs_path.as_deref();
-		infodb_build_thread_proc(prefs_path, &mame_executable_path, callback_bubble, cancelled_clone)
+		let mame_executable_path = mame_executable_path.as_deref();
+		infodb_build_thread_proc(
+			prefs_path,
+			mame_executable_path,
+			source,
+			machines_total,
+			callback_bubble,
+			cancelled_clone,
+			paused_clone,
+		)
 	});
-	InfoDbBuildJob { cancelled, join_handle }
+	InfoDbBuildJob {
+		cancelled,
+		paused,
+		join_handle,
+	}
 }
 
 fn infodb_build_thread_proc(
 	prefs_path: Option<&Path>,
-	mame_executable_path: &str,
+	mame_executable_path: Option<&str>,
+	source: InfoDbSource,
+	machines_total: Option<usize>,
 	callback_bubble: ThreadLocalBubble<CommandCallback>,
 	cancelled: Arc<AtomicBool>,
+	paused: Arc<AtomicBool>,
 ) -> Result<Option<InfoDb>> {
 	// progress messages need to be throttled
 	let mut throttle = Throttle::new(Duration::from_millis(100), 1);
@@ -454,11 +878,27 @@ fn infodb_build_thread_proc(
 
 	// prep a callback for progress
 	let invoke_command_clone = invoke_command.clone();
-	let callback = move |machine_description: &str| {
+	let start_time = Instant::now();
+	let mut paused_time = Duration::ZERO;
+	let callback = move |progress: ListXmlProgress<'_>| {
+		// if we're paused, wait until we're resumed (or cancelled)
+		if paused.load(Ordering::Relaxed) {
+			let pause_start_time = Instant::now();
+			while paused.load(Ordering::Relaxed) && !cancelled.load(Ordering::Relaxed) {
+				sleep(Duration::from_millis(50));
+			}
+			paused_time += pause_start_time.elapsed();
+		}
+
 		// do we need to update
 		if throttle.accept().is_ok() {
-			let machine_description = machine_description.to_string();
-			let command = AppCommand::InfoDbBuildProgress { machine_description };
+			let progress = InfoDbBuildProgress {
+				machine_description: progress.machine_description.to_string(),
+				machines_processed: progress.machines_processed,
+				machines_total,
+				elapsed: start_time.elapsed().saturating_sub(paused_time),
+			};
+			let command = AppCommand::InfoDbBuildProgress(progress);
 			invoke_command_clone(command);
 		}
 
@@ -466,11 +906,14 @@ fn infodb_build_thread_proc(
 		cancelled.load(Ordering::Relaxed)
 	};
 
-	// invoke MAME with `-listxml`
-	let result = InfoDb::from_child_process(mame_executable_path, callback);
+	// invoke MAME with `-listxml` (or read the output from a file)
+	let result = match source {
+		InfoDbSource::ChildProcess => InfoDb::from_child_process(mame_executable_path.unwrap(), callback),
+		InfoDbSource::ListXmlFile(path) => InfoDb::from_listxml_file(path, callback),
+	};
 
-	// save the InfoDb (if we got one)
-	if let Ok(Some(info_db)) = &result {
+	// save the InfoDb (if we got one, and we have a MAME executable to associate it with)
+	if let (Ok(Some(info_db)), Some(mame_executable_path)) = (&result, mame_executable_path) {
 		let _ = info_db.save(prefs_path, mame_executable_path);
 	}
 
@@ -480,3 +923,35 @@ fn infodb_build_thread_proc(
 	// and return the result
 	result
 }
+
+#[cfg(test)]
+mod test {
+	use std::time::Duration;
+
+	use test_case::test_case;
+
+	use super::InfoDbBuildProgress;
+
+	#[test_case(0, 0, None, 0, "Foo\n0 machines")]
+	#[test_case(1, 500, None, 2, "Foo\n500 machines at 250/s")]
+	#[test_case(2, 500, Some(1000), 0, "Foo\n500 of ~1000 machines")]
+	#[test_case(3, 500, Some(1000), 2, "Foo\n500 of ~1000 machines at 250/s, about 2s remaining")]
+	#[test_case(4, 1000, Some(40000), 4, "Foo\n1000 of ~40000 machines at 250/s, about 2m 36s remaining")]
+	#[test_case(5, 1200, Some(1000), 4, "Foo\n1200 of ~1000 machines at 300/s, about 0s remaining")]
+	pub fn infodb_build_progress_text(
+		_index: usize,
+		machines_processed: usize,
+		machines_total: Option<usize>,
+		elapsed_secs: u64,
+		expected: &str,
+	) {
+		let progress = InfoDbBuildProgress {
+			machine_description: "Foo".into(),
+			machines_processed,
+			machines_total,
+			elapsed: Duration::from_secs(elapsed_secs),
+		};
+		let actual = progress.text();
+		assert_eq!(expected, actual);
+	}
+}
@@ -1,10 +1,24 @@
+use std::any::Any;
 use std::borrow::Cow;
+use std::cell::Cell;
 use std::cell::RefCell;
+use std::future::ready;
 use std::iter::once;
+use std::path::Path;
 use std::path::PathBuf;
 use std::rc::Rc;
+use std::sync::Arc;
 use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
 
+use anyhow::Error;
+use anyhow::Result;
+use dirs::home_dir;
+use i_slint_core::items::PointerEvent;
+use i_slint_core::items::PointerEventKind;
+use itertools::Itertools;
 use muda::CheckMenuItem;
 use muda::IsMenuItem;
 use muda::Menu;
@@ -14,10 +28,12 @@ use muda::MenuItem;
 use muda::PredefinedMenuItem;
 use muda::Submenu;
 use slint::invoke_from_event_loop;
+use slint::platform::WindowEvent;
 use slint::quit_event_loop;
 use slint::spawn_local;
 use slint::CloseRequestResponse;
 use slint::ComponentHandle;
+use slint::Image;
 use slint::LogicalPosition;
 use slint::LogicalSize;
 use slint::Model;
@@ -26,56 +42,203 @@ use slint::SharedString;
 use slint::TableColumn;
 use slint::VecModel;
 use slint::Weak;
+use tokio::task::spawn_blocking;
 use tracing::event;
 use tracing::Level;
 
 use crate::appcommand::AppCommand;
+use crate::appearance::apply_appearance;
+use crate::appearance::set_appearance;
 use crate::appstate::AppState;
+use crate::attract::AttractAction;
+use crate::attract::AttractMode;
+use crate::bios::family_bios_assignment;
+use crate::catver::Catver;
 use crate::channel::Channel;
 use crate::childwindow::ChildWindow;
+use crate::collation::set_collation_language;
+use crate::collectionfile::CollectionFile;
 use crate::collections::add_items_to_existing_folder_collection;
 use crate::collections::add_items_to_new_folder_collection;
+use crate::collections::collection_tree_rows;
+use crate::collections::find_orphaned_entries;
+use crate::collections::folder_move_targets;
 use crate::collections::get_collection_name;
 use crate::collections::get_folder_collection_names;
 use crate::collections::get_folder_collections;
+use crate::collections::normalize_folder_collections;
 use crate::collections::remove_items_from_folder_collection;
+use crate::collections::remove_orphaned_entries;
+use crate::collections::reparent_folders;
+use crate::collections::set_folder_parent;
 use crate::collections::toggle_builtin_collection;
+use crate::collections::FolderNormalization;
+use crate::collections::OrphanedEntry;
+use crate::desktopshortcut::create_desktop_shortcut;
+use crate::details::list_roms;
+use crate::details::machine_details_text;
+use crate::details::machine_software_count;
+use crate::details::software_details_text;
 use crate::devimageconfig::DevicesImagesConfig;
+use crate::diagnostics::diagnostic_report;
+use crate::diagnostics::write_diagnostic_archive;
+use crate::diagnostics::LogFilter;
+use crate::diagnostics::LogRing;
+use crate::dialogs::appearance::dialog_appearance;
+use crate::dialogs::commandpalette::dialog_command_palette;
+use crate::dialogs::details::dialog_details;
 use crate::dialogs::devimages::dialog_devices_and_images;
+use crate::dialogs::file::collection_file_dialog;
+use crate::dialogs::file::diagnostic_report_file_dialog;
 use crate::dialogs::file::file_dialog;
+use crate::dialogs::file::inp_file_dialog;
+use crate::dialogs::file::listxml_file_dialog;
+use crate::dialogs::file::macro_file_dialog;
+use crate::dialogs::file::movie_file_dialog;
+use crate::dialogs::file::reference_snapshot_file_dialog;
+use crate::dialogs::file::save_collection_file_dialog;
+use crate::dialogs::file::save_macro_file_dialog;
+use crate::dialogs::file::screenshot_file_dialog;
+use crate::dialogs::file::sync_directory_dialog;
 use crate::dialogs::file::PathType;
 use crate::dialogs::image::dialog_load_image;
+use crate::dialogs::infodbdiff::dialog_infodb_diff;
+use crate::dialogs::loglevels::dialog_log_levels;
+use crate::dialogs::logviewer::dialog_log_viewer;
 use crate::dialogs::messagebox::dialog_message_box;
 use crate::dialogs::messagebox::OkCancel;
 use crate::dialogs::messagebox::OkOnly;
+use crate::dialogs::namecollection::dialog_capture_directory;
 use crate::dialogs::namecollection::dialog_new_collection;
 use crate::dialogs::namecollection::dialog_rename_collection;
+use crate::dialogs::namecollection::dialog_sync_url;
+use crate::dialogs::netplay::dialog_netplay;
+use crate::dialogs::orphans::dialog_orphaned_entries;
+use crate::dialogs::paths::add_standard_paths;
+use crate::dialogs::paths::detect_standard_paths;
 use crate::dialogs::paths::dialog_paths;
+use crate::dialogs::recording::dialog_recording_settings;
+use crate::dialogs::reset::dialog_reset_settings;
+use crate::dialogs::shortcuts::dialog_shortcuts;
 use crate::dialogs::socket::dialog_connect_to_socket;
+use crate::dialogs::softwareparts::dialog_software_parts;
+use crate::discord::DiscordPresence;
+use crate::discord::DISCORD_APPLICATION_ID;
+use crate::earlyexit::early_exit_message;
+use crate::earlyexit::Launch;
 use crate::guiutils::is_context_menu_event;
 use crate::guiutils::menuing::accel;
 use crate::guiutils::menuing::MenuExt;
 use crate::guiutils::menuing::MenuItemUpdate;
+use crate::guiutils::modal::dialog_geometry;
+use crate::guiutils::modal::set_dialog_geometry;
 use crate::guiutils::modal::Modal;
 use crate::guiutils::MenuingType;
 use crate::history::History;
+use crate::historyxml::find_links;
+use crate::historyxml::HistoryXml;
+use crate::i18n::apply_translations;
+use crate::i18n::available_languages;
+use crate::i18n::load_language;
+use crate::i18n::tr;
+use crate::i18n::tr_args;
+use crate::info::InfoDb;
+use crate::infodiff::InfoDbDiff;
+use crate::inp::inp_machine_name;
+use crate::instance::launch_args;
+use crate::instance::InstanceListener;
+use crate::instance::LaunchRequest;
+use crate::livepreview::LivePreview;
+use crate::macros::Macro;
+use crate::macros::MacroRecorder;
+use crate::mameupdate::install_release;
+use crate::mameupdate::is_update;
+use crate::mameupdate::latest_release;
 use crate::models::collectionsview::CollectionsViewModel;
+use crate::models::columns::column_provider;
+use crate::models::columns::column_providers;
+use crate::models::itemsgrid::find_snapshot;
+use crate::models::itemsgrid::load_snapshot_pixels;
+use crate::models::itemsgrid::ItemsGridModel;
+use crate::models::itemstable::column_alignment;
 use crate::models::itemstable::EmptyReason;
 use crate::models::itemstable::ItemsTableModel;
+use crate::platform::update_recent_items;
+use crate::platform::RecentItem;
 use crate::platform::WindowExt;
+use crate::prefs::prefs_filename;
+use crate::prefs::software_key;
 use crate::prefs::BuiltinCollection;
+use crate::prefs::CloneDisplay;
+use crate::prefs::ColumnAlignment;
+use crate::prefs::ItemAction;
+use crate::prefs::ItemsViewMode;
+use crate::prefs::MachineKind;
 use crate::prefs::Preferences;
+use crate::prefs::PrefsAppearance;
+use crate::prefs::PrefsCollection;
+use crate::prefs::PrefsColumn;
+use crate::prefs::PrefsItem;
+use crate::prefs::PrefsLaunchOutcome;
+use crate::prefs::PrefsPaths;
+use crate::prefs::PrefsSession;
 use crate::prefs::SortOrder;
+use crate::prefs::DEFAULT_GRID_COLUMNS;
+use crate::prefsync::shared_prefs_changed;
+use crate::prefsync::sync_prefs;
+use crate::prefsync::SyncOutcome;
+use crate::regions::Languages;
+use crate::remote::generate_token;
+use crate::remote::RemoteCommand;
+use crate::remote::RemoteServer;
+use crate::remote::DEFAULT_REMOTE_CONTROL_PORT;
+use crate::remote::STATUS_NOT_FOUND;
+use crate::remote::STATUS_SERVICE_UNAVAILABLE;
+use crate::renames::apply_machine_renames;
+use crate::renames::find_machine_renames;
+use crate::runtime::args::expand_path;
+#[cfg(unix)]
+use crate::runtime::args::make_executable;
+use crate::runtime::args::preflight_checks_public;
 use crate::runtime::controller::MameController;
+#[cfg(feature = "test-harness")]
+use crate::runtime::mock::MockMame;
+use crate::runtime::plugin::install_worker_ui;
+use crate::runtime::plugin::is_worker_ui_installed;
+use crate::runtime::plugin::managed_plugins_path;
+use crate::runtime::sessionlog::SessionLog;
+use crate::runtime::sessions::DetachedSession;
+use crate::runtime::sessions::DetachedSessions;
+use crate::runtime::sessions::SessionId;
 use crate::runtime::MameCommand;
 use crate::runtime::MameEvent;
 use crate::runtime::MameStderr;
 use crate::runtime::MameWindowing;
+use crate::screenshot::capture_directory_override;
+use crate::screenshot::next_screenshot_path;
+use crate::screenshot::DEFAULT_SCREENSHOT_TEMPLATE;
+use crate::screenshot::SCREENSHOT_TEMPLATES;
+use crate::selection::SelectionClick;
 use crate::selection::SelectionManager;
+use crate::sessionsummary::SessionStats;
+use crate::sessionsummary::SessionSummary;
+use crate::shortcuts::accelerator_for_command;
+use crate::shortcuts::bindable_commands;
+use crate::shortcuts::default_shortcuts;
+use crate::shortcuts::effective_shortcuts;
+use crate::snapcompare::compare_snapshot_files;
+use crate::snapcompare::find_latest_snapshot;
+use crate::snapcompare::SnapshotDifference;
+use crate::software::default_part_assignments;
+use crate::software::software_part_choices;
+use crate::software::software_part_loads;
+use crate::software::SoftwareListDispenser;
+use crate::software::SoftwareSupport;
 use crate::status::Status;
 use crate::threadlocalbubble::ThreadLocalBubble;
 use crate::ui::AboutDialog;
 use crate::ui::AppWindow;
+use crate::ui::HistoryDropdownEntry;
 use crate::ui::ReportIssue;
 
 const LOG_COMMANDS: Level = Level::DEBUG;
@@ -85,6 +248,37 @@ const LOG_PINGING: Level = Level::TRACE;
 const SOUND_ATTENUATION_OFF: i32 = -32;
 const SOUND_ATTENUATION_ON: i32 = 0;
 
+/// How long the summary of a session is shown after the machine stops
+const SESSION_SUMMARY_DURATION: Duration = Duration::from_secs(20);
+
+/// Two clicks on the same item within this long are a double click
+const DOUBLE_CLICK_DURATION: Duration = Duration::from_millis(500);
+
+/// How far the pointer has to move with the button held down before an item starts being dragged
+const DRAG_THRESHOLD: f32 = 5.0;
+
+/// How long a remote control request waits for the UI to act on it
+const REMOTE_REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How many entries in each direction the back/forward dropdown shows
+const HISTORY_DROPDOWN_COUNT: isize = 10;
+
+/// Something pressed that can be dragged onto a folder in the collections view
+#[derive(Clone, Copy, Debug)]
+struct Drag {
+	source: DragSource,
+	origin: LogicalPosition,
+	dragging: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum DragSource {
+	/// A row in the items table, which gets added to the folder
+	ItemsRow(usize),
+	/// A folder (by its index within the collections in preferences), which gets nested within the folder
+	Folder(usize),
+}
+
 /// Arguments to the application (derivative from the command line); almost all of this
 /// are power user features or diagnostics
 #[derive(Debug)]
@@ -92,6 +286,11 @@ pub struct AppArgs {
 	pub prefs_path: Option<PathBuf>,
 	pub mame_stderr: MameStderr,
 	pub menuing_type: MenuingType,
+	pub safe_mode: bool,
+	pub read_only_prefs: bool,
+	pub log_ring: LogRing,
+	pub log_filter: LogFilter,
+	pub launch_request: Option<LaunchRequest>,
 }
 
 struct AppModel {
@@ -101,8 +300,35 @@ struct AppModel {
 	preferences: RefCell<Preferences>,
 	state: RefCell<AppState>,
 	mame_controller: MameController,
+	mame_stderr: MameStderr,
+	log_ring: LogRing,
+	log_filter: LogFilter,
+	session_log: SessionLog,
+	launch: RefCell<Option<Launch>>,
+	session_stats: RefCell<Option<SessionStats>>,
+	session_summary: RefCell<Option<Rc<SessionSummary>>>,
+	configure_on_start: Cell<bool>,
+	items_last_click: Cell<Option<(Instant, usize)>>,
+	drag: Cell<Option<Drag>>,
+	record_input: Cell<bool>,
+	record_audio: Cell<bool>,
+	detached_sessions: DetachedSessions,
+	live_preview: LivePreview,
+	remote_server: RefCell<Option<RemoteServer>>,
+	instance_listener: RefCell<Option<InstanceListener>>,
+	pending_launch: RefCell<Option<LaunchRequest>>,
+	discord_presence: RefCell<Option<DiscordPresence>>,
+	attract_mode: AttractMode,
+	macro_recorder: MacroRecorder,
+	macro_playback: RefCell<Option<slint::JoinHandle<()>>>,
 	status_changed_channel: Channel<Status>,
+	software_list_progress_subscription: RefCell<Option<Box<dyn Any>>>,
+	history_xml: RefCell<Option<Arc<HistoryXml>>>,
 	child_window: ChildWindow,
+	safe_mode: bool,
+
+	/// Preference changes only last until we exit (e.g. - on a shared kiosk)
+	read_only_prefs: bool,
 }
 
 impl AppModel {
@@ -119,6 +345,15 @@ impl AppModel {
 		func(collections_model)
 	}
 
+	pub fn with_items_grid_model<T>(&self, func: impl FnOnce(&ItemsGridModel) -> T) -> T {
+		let items_grid_model = self.app_window().get_items_grid_model();
+		let items_grid_model = items_grid_model
+			.as_any()
+			.downcast_ref::<ItemsGridModel>()
+			.expect("with_items_grid_model(): downcast_ref::<ItemsGridModel>() failed");
+		func(items_grid_model)
+	}
+
 	pub fn with_items_table_model<T>(&self, func: impl FnOnce(&ItemsTableModel) -> T) -> T {
 		let items_model = self.app_window().get_items_model();
 		let items_model = items_model
@@ -135,6 +370,11 @@ impl AppModel {
 			let mut prefs = self.preferences.borrow_mut();
 			let old_prefs = prefs.clone();
 			func(&mut prefs);
+
+			// note changes that need to be synchronized (unless this is the synchronization itself)
+			if prefs.sync.is_enabled() && prefs.sync == old_prefs.sync && shared_prefs_changed(&old_prefs, &prefs) {
+				prefs.sync.dirty = true;
+			}
 			old_prefs
 		};
 
@@ -142,41 +382,160 @@ impl AppModel {
 		let prefs = self.preferences.borrow();
 
 		// save (ignore errors)
-		let _ = self.preferences.borrow().save();
+		if !self.read_only_prefs {
+			let _ = self.preferences.borrow().save();
+		}
 
 		// react to all of the possible changes
-		if prefs.collections != old_prefs.collections {
+		if prefs.collections != old_prefs.collections || prefs.collapsed_folders != old_prefs.collapsed_folders {
 			event!(LOG_PREFS, "modify_prefs(): prefs.collection changed");
 			let info_db = self.state.borrow().info_db.clone();
-			self.with_collections_view_model(|x| x.update(info_db, &prefs.collections));
+			self.with_collections_view_model(|x| x.update(info_db, &prefs.collections, &prefs.collapsed_folders));
 		}
 		if prefs.current_history_entry() != old_prefs.current_history_entry()
 			|| prefs.current_collection() != old_prefs.current_collection()
+			|| collection_tree_rows(&prefs.collections, &prefs.collapsed_folders)
+				!= collection_tree_rows(&old_prefs.collections, &old_prefs.collapsed_folders)
 		{
 			event!(LOG_PREFS, "modify_prefs(): current history_entry/collection] changed");
 			update_ui_for_current_history_item(self);
 		}
+		if prefs.launch_outcomes != old_prefs.launch_outcomes {
+			event!(LOG_PREFS, "modify_prefs(): launch_outcomes changed");
+			let launch_outcomes = prefs.launch_outcomes.clone();
+			self.with_items_table_model(|x| x.set_launch_outcomes(launch_outcomes));
+			update_ui_for_launch_outcome(self, &prefs);
+		}
+		if prefs.paths.mame_executable != old_prefs.paths.mame_executable
+			|| prefs.other_mame_executables != old_prefs.other_mame_executables
+		{
+			event!(
+				LOG_PREFS,
+				"modify_prefs(): mame_executable/other_mame_executables changed"
+			);
+			update_ui_for_mame_executables(self);
+		}
 		if prefs.items_columns != old_prefs.items_columns {
 			event!(LOG_PREFS, "modify_prefs(): items_columns changed");
+			let column_types = |columns: &[PrefsColumn]| columns.iter().map(|x| x.column_type).collect::<Vec<_>>();
+			if column_types(&prefs.items_columns) != column_types(&old_prefs.items_columns) {
+				update_ui_for_items_columns(self);
+				update_menus(self);
+			}
+			update_ui_for_items_column_alignments(self);
+			update_ui_for_sort_changes(self);
+		}
+		if prefs.clone_display != old_prefs.clone_display {
+			event!(LOG_PREFS, "modify_prefs(): clone_display changed");
+			self.with_items_table_model(|x| x.set_clone_display(prefs.clone_display));
+			update_menus(self);
+		}
+		if prefs.item_action != old_prefs.item_action {
+			event!(LOG_PREFS, "modify_prefs(): item_action changed");
+			update_menus(self);
+		}
+		if prefs.hidden_machine_kinds != old_prefs.hidden_machine_kinds {
+			event!(LOG_PREFS, "modify_prefs(): hidden_machine_kinds changed");
+			let hidden_machine_kinds = prefs.hidden_machine_kinds.clone();
+			self.with_items_table_model(|x| x.set_hidden_machine_kinds(hidden_machine_kinds));
+			update_menus(self);
+		}
+		if prefs.appearance != old_prefs.appearance {
+			event!(LOG_PREFS, "modify_prefs(): appearance changed");
+			set_appearance(&self.app_window(), &prefs.appearance);
+		}
+		if prefs.language != old_prefs.language {
+			// the menu bar was built with the old strings, and picks up the new language on the next start
+			event!(LOG_PREFS, "modify_prefs(): language changed");
+			if let Err(e) = load_language(prefs.prefs_path.as_deref(), prefs.language.as_deref()) {
+				event!(LOG_PREFS, "Failed to load translations: {e:?}");
+			}
+			set_collation_language(prefs.language.as_deref());
+			apply_translations(&self.app_window());
 			update_ui_for_sort_changes(self);
 		}
+		if prefs.screenshots != old_prefs.screenshots {
+			event!(LOG_PREFS, "modify_prefs(): screenshots changed");
+			update_menus(self);
+		}
+		if (&prefs.sync.directory, &prefs.sync.url) != (&old_prefs.sync.directory, &old_prefs.sync.url) {
+			event!(LOG_PREFS, "modify_prefs(): sync.directory/url changed");
+			update_menus(self);
+		}
+		if prefs.remote_control != old_prefs.remote_control {
+			event!(LOG_PREFS, "modify_prefs(): remote_control changed");
+			update_remote_server(self, &prefs);
+			update_menus(self);
+		}
+		if prefs.discord_rich_presence != old_prefs.discord_rich_presence {
+			event!(LOG_PREFS, "modify_prefs(): discord_rich_presence changed");
+			update_discord_presence(self, &prefs);
+			update_menus(self);
+		}
+		if prefs.mount_first_software_part_only != old_prefs.mount_first_software_part_only {
+			event!(LOG_PREFS, "modify_prefs(): mount_first_software_part_only changed");
+			let mount_all_software_parts = !prefs.mount_first_software_part_only;
+			self.with_items_table_model(|x| x.set_mount_all_software_parts(mount_all_software_parts));
+			update_menus(self);
+		}
+		if prefs.software_machines != old_prefs.software_machines {
+			event!(LOG_PREFS, "modify_prefs(): software_machines changed");
+			let software_machines = prefs.software_machines.clone();
+			self.with_items_table_model(|x| x.set_software_machines(software_machines));
+		}
+		if prefs.recent_machines != old_prefs.recent_machines
+			|| prefs.last_session.is_some() != old_prefs.last_session.is_some()
+		{
+			event!(LOG_PREFS, "modify_prefs(): recent_machines changed");
+			update_os_recent_items(self, &prefs);
+		}
+		if prefs.shortcuts != old_prefs.shortcuts {
+			event!(LOG_PREFS, "modify_prefs(): shortcuts changed");
+			update_menu_accelerators(self);
+		}
+		if prefs.items_view_mode != old_prefs.items_view_mode
+			|| prefs.items_grid_columns != old_prefs.items_grid_columns
+		{
+			event!(LOG_PREFS, "modify_prefs(): items_view_mode/items_grid_columns changed");
+			update_ui_for_items_view_mode(self);
+		}
 		if prefs.paths != old_prefs.paths {
 			if self.mame_controller.has_session() {
 				self.mame_controller.issue_command(MameCommand::Exit);
 			}
-			if prefs.paths.mame_executable != old_prefs.paths.mame_executable {
-				event!(LOG_PREFS, "modify_prefs(): paths.mame_executable changed");
+			if prefs.paths.mame_executable != old_prefs.paths.mame_executable
+				|| prefs.paths.plugins != old_prefs.paths.plugins
+			{
+				// either of these can change the outcome of preflight checks
+				event!(LOG_PREFS, "modify_prefs(): paths.mame_executable/paths.plugins changed");
 				self.infodb_load(false);
 			}
 			if prefs.paths.software_lists != old_prefs.paths.software_lists {
 				event!(LOG_PREFS, "modify_prefs(): paths.software_lists changed");
 				software_paths_updated(self);
 			}
+			if prefs.paths.snapshots != old_prefs.paths.snapshots {
+				event!(LOG_PREFS, "modify_prefs(): paths.snapshots changed");
+				let snapshot_paths = snapshot_paths(&prefs);
+				self.with_items_grid_model(|x| x.set_snapshot_paths(snapshot_paths));
+			}
+			if prefs.paths.history != old_prefs.paths.history {
+				event!(LOG_PREFS, "modify_prefs(): paths.history changed");
+				load_history_xml(self);
+			}
+			if prefs.paths.category != old_prefs.paths.category {
+				event!(LOG_PREFS, "modify_prefs(): paths.category changed");
+				load_catver(self);
+			}
+			if prefs.paths.languages != old_prefs.paths.languages {
+				event!(LOG_PREFS, "modify_prefs(): paths.languages changed");
+				load_languages(self);
+			}
 		}
 	}
 
 	pub fn update_state(self: &Rc<Self>, callback: impl FnOnce(&AppState) -> Option<AppState>) {
-		let (info_db_changed, active_changed) = {
+		let (info_db_changed, active_changed, is_shutdown, old_info_db) = {
 			// invoke the callback to get the new state
 			let mut state = self.state.borrow_mut();
 			let Some(mut new_state) = callback(&state) else { return };
@@ -190,10 +549,7 @@ impl AppModel {
 			let active_changed = state.status().is_some() != new_state.status().is_some();
 
 			// are we shut down?
-			if new_state.is_shutdown() {
-				update_prefs(self);
-				quit_event_loop().unwrap()
-			}
+			let is_shutdown = new_state.is_shutdown();
 
 			// do we have an InfoDb mismatch?
 			if new_state.has_infodb_mismatch() {
@@ -205,10 +561,21 @@ impl AppModel {
 			}
 
 			// commit the state and return the changes
-			*state = new_state;
-			(info_db_changed, active_changed)
+			let old_info_db = std::mem::replace(&mut *state, new_state).info_db;
+			(info_db_changed, active_changed, is_shutdown, old_info_db)
 		};
 
+		// shutting down; this happens after the state is committed, because synchronizing preferences can change
+		// collections (which needs to borrow the state)
+		if is_shutdown {
+			update_prefs(self);
+			if let Err(e) = sync_prefs_now(self) {
+				event!(LOG_PREFS, "Error synchronizing preferences: {e:?}");
+			}
+			update_prefs(self);
+			quit_event_loop().unwrap()
+		}
+
 		// InfoDb changed?
 		if info_db_changed {
 			let info_db = self.state.borrow().info_db.clone();
@@ -219,8 +586,11 @@ impl AppModel {
 			self.with_collections_view_model(|collections_model| {
 				let prefs = self.preferences.borrow();
 				let info_db = info_db.clone();
-				collections_model.update(info_db, &prefs.collections);
+				collections_model.update(info_db, &prefs.collections, &prefs.collapsed_folders);
 			});
+			if let Some(info_db) = info_db {
+				check_machine_renames(self, old_info_db.as_deref(), &info_db);
+			}
 		}
 
 		// did the activation state change?
@@ -260,7 +630,7 @@ impl AppModel {
 			app_window.set_report_message(
 				report
 					.as_ref()
-					.map(|r| r.message.to_string())
+					.map(|r| tr(&r.message.to_string()))
 					.unwrap_or_default()
 					.into(),
 			);
@@ -277,7 +647,15 @@ impl AppModel {
 				report
 					.as_ref()
 					.and_then(|r| r.button.as_ref())
-					.map(|b| b.text)
+					.map(|b| tr(b.text))
+					.unwrap_or_default()
+					.into(),
+			);
+			app_window.set_report_secondary_button_text(
+				report
+					.as_ref()
+					.and_then(|r| r.secondary_button.as_ref())
+					.map(|b| tr(b.text))
 					.unwrap_or_default()
 					.into(),
 			);
@@ -286,16 +664,57 @@ impl AppModel {
 				.unwrap_or_default()
 				.iter()
 				.map(|issue| {
-					let text = issue.to_string().into();
-					ReportIssue {
-						text,
-						button_text: "".into(),
-					}
+					let text = tr(&issue.to_string()).into();
+					let button_text = issue.fix().map(|b| tr(b.text)).unwrap_or_default().into();
+					ReportIssue { text, button_text }
 				})
 				.collect::<Vec<_>>();
 			let issues = VecModel::from(issues);
 			let issues = ModelRc::new(issues);
 			app_window.set_report_issues(issues);
+
+			// background report (e.g. - refreshing the InfoDb while the old one remains in use)
+			let background_report = state.background_report();
+			app_window.set_background_report_message(
+				background_report
+					.as_ref()
+					.map(|r| tr(&r.message.to_string()))
+					.unwrap_or_default()
+					.into(),
+			);
+			app_window.set_background_report_submessage(
+				background_report
+					.as_ref()
+					.and_then(|r| r.submessage)
+					.unwrap_or_default()
+					.into(),
+			);
+			app_window.set_background_report_button_text(
+				background_report
+					.as_ref()
+					.and_then(|r| r.button.as_ref())
+					.map(|b| tr(b.text))
+					.unwrap_or_default()
+					.into(),
+			);
+			app_window.set_background_report_secondary_button_text(
+				background_report
+					.as_ref()
+					.and_then(|r| r.secondary_button.as_ref())
+					.map(|b| tr(b.text))
+					.unwrap_or_default()
+					.into(),
+			);
+		}
+
+		// Discord Rich Presence
+		publish_discord_presence(self);
+
+		// launch requests that arrived before we were ready to run anything
+		if is_ready_to_launch(self) {
+			if let Some(request) = self.pending_launch.take() {
+				handle_command(self, AppCommand::Launch(request));
+			}
 		}
 
 		// menus
@@ -325,41 +744,85 @@ impl AppModel {
 }
 
 pub fn create(args: AppArgs) -> AppWindow {
+	let (app_window, _) = create_model(args, MameController::new);
+	app_window
+}
+
+fn create_model(
+	args: AppArgs,
+	mame_controller: impl FnOnce(MameStderr, SessionLog) -> MameController,
+) -> (AppWindow, Rc<AppModel>) {
 	let app_window = AppWindow::new().unwrap();
 
 	// child window for MAME to attach to
 	let child_window =
 		ChildWindow::new(app_window.window()).unwrap_or_else(|e| panic!("Failed to create child window: {e:?}"));
 
-	// create the menu bar
-	let menu_bar = create_menu_bar();
-
 	// get preferences
 	let prefs_path = args.prefs_path;
-	let preferences = Preferences::load(prefs_path.as_ref())
-		.ok()
-		.flatten()
-		.unwrap_or_else(|| Preferences::fresh(prefs_path));
+	let preferences = Preferences::load(prefs_path.as_ref()).ok().flatten();
+	let is_fresh_prefs = preferences.is_none();
+	let preferences = preferences.unwrap_or_else(|| Preferences::fresh(prefs_path));
+
+	// load translations (this needs to happen before we create any UI strings)
+	if let Err(e) = load_language(preferences.prefs_path.as_deref(), preferences.language.as_deref()) {
+		event!(LOG_PREFS, "Failed to load translations: {e:?}");
+	}
+	set_collation_language(preferences.language.as_deref());
+	apply_translations(&app_window);
+
+	// create the menu bar
+	let menu_bar = create_menu_bar();
 
-	// update window preferences
-	if let Some(window_size) = &preferences.window_size {
+	// update window preferences (safe mode sticks with the default window size)
+	if let Some(window_size) = preferences.window_size.as_ref().filter(|_| !args.safe_mode) {
 		let physical_size = LogicalSize::from(*window_size).to_physical(app_window.window().scale_factor());
 		app_window.window().set_size(physical_size);
 	}
+	if !args.safe_mode {
+		set_dialog_geometry(preferences.dialog_geometry.clone());
+		set_appearance(&app_window, &preferences.appearance);
+	}
 
 	// create a bogus state for now
 	let state = AppState::new(|_| {});
 
 	// create the model
+	let session_log = SessionLog::default();
 	let model = AppModel {
 		menu_bar,
 		menuing_type: args.menuing_type,
 		app_window_weak: app_window.as_weak(),
 		preferences: RefCell::new(preferences),
 		state: RefCell::new(state),
-		mame_controller: MameController::new(args.mame_stderr),
+		mame_controller: mame_controller(args.mame_stderr, session_log.clone()),
+		mame_stderr: args.mame_stderr,
+		log_ring: args.log_ring,
+		log_filter: args.log_filter,
+		session_log,
+		launch: RefCell::new(None),
+		session_stats: RefCell::new(None),
+		session_summary: RefCell::new(None),
+		configure_on_start: Cell::new(false),
+		items_last_click: Cell::new(None),
+		drag: Cell::new(None),
+		record_input: Cell::new(false),
+		record_audio: Cell::new(false),
+		detached_sessions: DetachedSessions::default(),
+		live_preview: LivePreview::default(),
+		remote_server: RefCell::new(None),
+		instance_listener: RefCell::new(None),
+		pending_launch: RefCell::new(args.launch_request),
+		discord_presence: RefCell::new(None),
+		attract_mode: AttractMode::default(),
+		macro_recorder: MacroRecorder::default(),
+		macro_playback: RefCell::new(None),
 		status_changed_channel: Channel::default(),
+		software_list_progress_subscription: RefCell::new(None),
+		history_xml: RefCell::new(None),
 		child_window,
+		safe_mode: args.safe_mode,
+		read_only_prefs: args.read_only_prefs,
 	};
 	let model = Rc::new(model);
 
@@ -379,6 +842,7 @@ pub fn create(args: AppArgs) -> AppWindow {
 			app_window.on_menubar_sub_menu_selected(move |entry| model_clone.menu_bar.slint_menu_entries(Some(&entry)));
 			let model_clone = model.clone();
 			app_window.on_menu_entry_activated(move |entry| {
+				note_activity(&model_clone);
 				let id = MenuId::from(&entry.id);
 				if let Ok(command) = AppCommand::try_from(&id) {
 					handle_command(&model_clone, command);
@@ -395,8 +859,8 @@ pub fn create(args: AppArgs) -> AppWindow {
 			let model = bubble.unwrap();
 			let command = match event {
 				MameEvent::SessionStarted => AppCommand::MameSessionStarted,
-				MameEvent::SessionEnded => AppCommand::MameSessionEnded,
-				MameEvent::Error(e) => AppCommand::ErrorMessageBox(format!("{e:?}")),
+				MameEvent::SessionEnded(_) => AppCommand::MameSessionEnded,
+				MameEvent::Error(e) => AppCommand::MameSessionError(format!("{e:?}")),
 				MameEvent::StatusUpdate(update) => AppCommand::MameStatusUpdate(update),
 			};
 			handle_command(&model, command);
@@ -408,6 +872,33 @@ pub fn create(args: AppArgs) -> AppWindow {
 	let fut = ping_callback(Rc::downgrade(&model));
 	spawn_local(fut).unwrap();
 
+	// start the remote control server, if enabled
+	update_remote_server(&model, &model.preferences.borrow());
+
+	// listen for launch requests from other instances (e.g. - from the taskbar jump list); this needs to write into
+	// the preferences directory
+	let prefs_path = model.preferences.borrow().prefs_path.clone();
+	if let Some(prefs_path) = prefs_path.filter(|_| !model.read_only_prefs) {
+		let bubble = ThreadLocalBubble::new(model.clone());
+		let callback = move |request| {
+			let bubble = bubble.clone();
+			invoke_from_event_loop(move || {
+				let model = bubble.unwrap();
+				handle_command(&model, AppCommand::Launch(request));
+			})
+			.unwrap();
+		};
+		match InstanceListener::start(&prefs_path, callback) {
+			Ok(listener) => {
+				model.instance_listener.replace(Some(listener));
+			}
+			Err(e) => event!(LOG_PREFS, "Could not listen for launch requests: {e:?}"),
+		}
+	}
+
+	// and Discord Rich Presence
+	update_discord_presence(&model, &model.preferences.borrow());
+
 	// set up the collections view model
 	let collections_view_model = CollectionsViewModel::new(app_window.as_weak());
 	let collections_view_model = Rc::new(collections_view_model);
@@ -437,13 +928,65 @@ pub fn create(args: AppArgs) -> AppWindow {
 			empty_callback,
 		)
 	};
+	items_model.set_launch_outcomes(model.preferences.borrow().launch_outcomes.clone());
+	items_model.set_clone_display(model.preferences.borrow().clone_display);
+	items_model.set_hidden_machine_kinds(model.preferences.borrow().hidden_machine_kinds.clone());
+	items_model.set_mount_all_software_parts(!model.preferences.borrow().mount_first_software_part_only);
+	items_model.set_software_machines(model.preferences.borrow().software_machines.clone());
 	let items_model_clone = items_model.clone();
 	app_window.set_items_model(ModelRc::new(items_model_clone));
 
+	// report progress while software lists are loading for "All Software"
+	let app_window_weak = app_window.as_weak();
+	let subscription = items_model.software_list_progress_channel().subscribe(move |progress| {
+		let progress = progress
+			.map(|x| {
+				let args: &[(&str, &dyn Display)] = &[("loaded", &x.loaded), ("total", &x.total)];
+				tr_args("Loading software lists {loaded}/{total}", args)
+			})
+			.unwrap_or_default();
+		app_window_weak.unwrap().set_software_list_progress(progress.into());
+	});
+	model
+		.software_list_progress_subscription
+		.replace(Some(Box::new(subscription)));
+
+	// set up the items grid model, which piggybacks on the items model
+	let items_grid_model = ItemsGridModel::new(items_model.clone(), snapshot_paths(&model.preferences.borrow()));
+	let items_grid_model_weak = Rc::downgrade(&items_grid_model);
+	items_model.set_items_map_changed_callback(move || {
+		if let Some(items_grid_model) = items_grid_model_weak.upgrade() {
+			items_grid_model.items_map_changed();
+		}
+	});
+	app_window.set_items_grid_model(ModelRc::from(items_grid_model));
+
+	// load history.xml, the category file and languages.ini (if we have them) in the background
+	load_history_xml(&model);
+	load_catver(&model);
+	load_languages(&model);
+	let model_clone = model.clone();
+	app_window.on_items_grid_clicked(move |index| {
+		note_activity(&model_clone);
+		model_clone.app_window().invoke_items_view_select(index);
+		handle_command(&model_clone, AppCommand::ItemsSelectedChanged);
+	});
+	let model_clone = model.clone();
+	app_window.on_items_row_activated(move |index| {
+		note_activity(&model_clone);
+		items_row_activated(&model_clone, index.try_into().unwrap());
+	});
+	let model_clone = model.clone();
+	app_window.on_items_type_ahead(move |text| {
+		note_activity(&model_clone);
+		model_clone.with_items_table_model(|x| x.type_ahead(&text))
+	});
+
 	// bind collection selection changes to the items view model
 	let collections_view_model_clone = collections_view_model.clone();
 	let model_clone = model.clone();
 	app_window.on_collections_view_selected(move |index| {
+		note_activity(&model_clone);
 		let index = index.try_into().unwrap();
 		if let Some(collection) = collections_view_model_clone.get(index) {
 			let collection = Rc::unwrap_or_clone(collection);
@@ -458,6 +1001,14 @@ pub fn create(args: AppArgs) -> AppWindow {
 		let delta = delta.try_into().unwrap();
 		handle_command(&model_clone, AppCommand::HistoryAdvance(delta));
 	});
+	let model_clone = model.clone();
+	app_window.on_history_dropdown_clicked(move || update_history_dropdown(&model_clone));
+
+	// set up "surprise me" button
+	let model_clone = model.clone();
+	app_window.on_surprise_me_clicked(move || {
+		handle_command(&model_clone, AppCommand::ItemsSurpriseMe { run: false });
+	});
 
 	// set up bookmark collection button
 	let model_clone = model.clone();
@@ -465,24 +1016,12 @@ pub fn create(args: AppArgs) -> AppWindow {
 		handle_command(&model_clone, AppCommand::BookmarkCurrentCollection);
 	});
 
-	// set up items columns
-	let items_columns = model
-		.preferences
-		.borrow()
-		.items_columns
-		.iter()
-		.map(|column| {
-			let mut table_column = TableColumn::default();
-			table_column.title = format!("{}", column.column_type).into();
-			table_column.horizontal_stretch = 1.0;
-			table_column.width = column.width;
-			table_column
-		})
-		.collect::<Vec<_>>();
-	let items_columns = VecModel::from(items_columns);
-	let items_columns = Rc::new(items_columns);
-	let items_columns = ModelRc::from(items_columns);
-	app_window.set_items_columns(items_columns);
+	// set up items columns; fresh preferences get widths that suit the window
+	if is_fresh_prefs {
+		reset_items_column_widths(&model);
+	}
+	update_ui_for_items_columns(&model);
+	update_ui_for_items_column_alignments(&model);
 
 	// set up items filter
 	let model_clone = model.clone();
@@ -495,6 +1034,7 @@ pub fn create(args: AppArgs) -> AppWindow {
 	});
 	let model_clone = model.clone();
 	app_window.on_items_search_text_changed(move |search| {
+		note_activity(&model_clone);
 		let command = AppCommand::SearchText(search.into());
 		handle_command(&model_clone, command);
 	});
@@ -503,9 +1043,23 @@ pub fn create(args: AppArgs) -> AppWindow {
 	));
 	let model_clone = model.clone();
 	app_window.on_items_current_row_changed(move || {
+		note_activity(&model_clone);
+		model_clone.with_items_table_model(|x| x.current_row_changed());
 		let command = AppCommand::ItemsSelectedChanged;
 		handle_command(&model_clone, command);
 	});
+	let model_clone = model.clone();
+	app_window.on_items_filter_chip_removed(move |index| {
+		let command = AppCommand::ItemsRemoveFilter(index.try_into().unwrap());
+		handle_command(&model_clone, command);
+	});
+	let model_clone = model.clone();
+	app_window.on_is_empty_fix_clicked(move || {
+		handle_command(&model_clone, AppCommand::AdoptStandardPaths);
+	});
+	app_window.on_history_link_clicked(|url| {
+		let _ = open::that(url.as_str());
+	});
 
 	// set up menu handler
 	let packet = ThreadLocalBubble::new(model.clone());
@@ -514,6 +1068,7 @@ pub fn create(args: AppArgs) -> AppWindow {
 			let packet = packet.clone();
 			invoke_from_event_loop(move || {
 				let model = packet.unwrap();
+				note_activity(&model);
 				handle_command(&model, command);
 			})
 			.unwrap();
@@ -528,12 +1083,44 @@ pub fn create(args: AppArgs) -> AppWindow {
 		CloseRequestResponse::KeepWindowShown
 	});
 
+	// dismissing attract mode
+	let model_clone = model.clone();
+	app_window.on_attract_dismissed(move || {
+		note_activity(&model_clone);
+	});
+
+	// MAME executable switcher
+	let model_clone = model.clone();
+	app_window.on_mame_executable_selected(move |index| {
+		let index = usize::try_from(index).unwrap();
+		let mame_executable = model_clone.preferences.borrow().mame_executables()[index].to_string();
+		handle_command(&model_clone, AppCommand::SettingsSwitchMameExecutable(mame_executable));
+	});
+
+	// session switcher
+	let model_clone = model.clone();
+	app_window.on_session_selected(move |index| {
+		let index = usize::try_from(index).unwrap();
+		let session_id = index
+			.checked_sub(1)
+			.and_then(|index| model_clone.detached_sessions.entries().get(index).map(|(id, _)| *id));
+		handle_command(&model_clone, AppCommand::SessionSwitch { session_id });
+	});
+
 	// collections popup menus
 	let model_clone = model.clone();
 	app_window.on_collections_row_pointer_event(move |index, evt, position| {
+		note_activity(&model_clone);
+		let folder_index = usize::try_from(index)
+			.ok()
+			.and_then(|row| model_clone.with_collections_view_model(|x| x.folder_index(row)));
+		drag_pointer_event(&model_clone, folder_index.map(DragSource::Folder), &evt, position);
 		if is_context_menu_event(&evt) {
 			let index = usize::try_from(index).ok();
-			if let Some(popup_menu) = model_clone.with_collections_view_model(|x| x.context_commands(index)) {
+			let capture_directories = model_clone.preferences.borrow().folder_capture_directories.clone();
+			if let Some(popup_menu) =
+				model_clone.with_collections_view_model(|x| x.context_commands(index, &capture_directories))
+			{
 				model_clone.show_popup_menu(popup_menu, position);
 			}
 		}
@@ -542,18 +1129,39 @@ pub fn create(args: AppArgs) -> AppWindow {
 	// items popup menus
 	let model_clone = model.clone();
 	app_window.on_items_row_pointer_event(move |index, evt, position| {
-		if is_context_menu_event(&evt) {
+		note_activity(&model_clone);
+		let source = usize::try_from(index).ok().map(DragSource::ItemsRow);
+		drag_pointer_event(&model_clone, source, &evt, position);
+		if let Some(click) = SelectionClick::from_pointer_event(&evt) {
+			let index = usize::try_from(index).unwrap();
+			model_clone.with_items_table_model(|x| x.row_clicked(index, click));
+			if click != SelectionClick::Replace {
+				handle_command(&model_clone, AppCommand::ItemsSelectedChanged);
+			} else {
+				items_clicked(&model_clone, index);
+			}
+		} else if is_context_menu_event(&evt) {
 			let index = usize::try_from(index).unwrap();
 			let folder_info = get_folder_collections(&model_clone.preferences.borrow().collections);
+			let recent_input_files = model_clone.preferences.borrow().recent_input_files.clone();
+			let reference_snapshots = model_clone.preferences.borrow().reference_snapshots.clone();
+			let capture_directories = model_clone.preferences.borrow().machine_capture_directories.clone();
 			let has_mame_initialized = model_clone
 				.state
 				.borrow()
 				.status()
 				.map(|s| s.has_initialized)
 				.unwrap_or_default();
-			if let Some(popup_menu) =
-				model_clone.with_items_table_model(|x| x.context_commands(index, &folder_info, has_mame_initialized))
-			{
+			if let Some(popup_menu) = model_clone.with_items_table_model(|x| {
+				x.context_commands(
+					index,
+					&folder_info,
+					has_mame_initialized,
+					&recent_input_files,
+					&reference_snapshots,
+					&capture_directories,
+				)
+			}) {
 				model_clone.show_popup_menu(popup_menu, position);
 			}
 		}
@@ -568,6 +1176,71 @@ pub fn create(args: AppArgs) -> AppWindow {
 		};
 		handle_command(&model_clone, command);
 	});
+	let model_clone = model.clone();
+	app_window.on_report_secondary_button_clicked(move || {
+		let command = {
+			let state = model_clone.state.borrow();
+			state.report().unwrap().secondary_button.unwrap().command
+		};
+		handle_command(&model_clone, command);
+	});
+
+	// background report button
+	let model_clone = model.clone();
+	app_window.on_background_report_button_clicked(move || {
+		let command = {
+			let state = model_clone.state.borrow();
+			state.background_report().unwrap().button.unwrap().command
+		};
+		handle_command(&model_clone, command);
+	});
+	let model_clone = model.clone();
+	app_window.on_background_report_secondary_button_clicked(move || {
+		let command = {
+			let state = model_clone.state.borrow();
+			state.background_report().unwrap().secondary_button.unwrap().command
+		};
+		handle_command(&model_clone, command);
+	});
+
+	// session summary buttons
+	let model_clone = model.clone();
+	app_window.on_session_summary_play_again_clicked(move || {
+		let summary = model_clone.session_summary.borrow().clone();
+		if let Some(summary) = summary {
+			let command = AppCommand::RunMame {
+				machine_name: summary.machine_name.clone(),
+				initial_loads: summary.initial_loads.clone(),
+			};
+			handle_command(&model_clone, command);
+		}
+	});
+	let model_clone = model.clone();
+	app_window.on_session_summary_configure_clicked(move || {
+		let summary = model_clone.session_summary.borrow().clone();
+		if let Some(summary) = summary {
+			let command = AppCommand::RunMameConfigure {
+				machine_name: summary.machine_name.clone(),
+				initial_loads: summary.initial_loads.clone(),
+			};
+			handle_command(&model_clone, command);
+		}
+	});
+	let model_clone = model.clone();
+	app_window.on_session_summary_dismissed(move || {
+		handle_command(&model_clone, AppCommand::SessionSummaryDismiss);
+	});
+
+	// issue "fix" buttons
+	let model_clone = model.clone();
+	app_window.on_issue_button_clicked(move |index| {
+		let index = usize::try_from(index).unwrap();
+		let command = {
+			let state = model_clone.state.borrow();
+			state.report().unwrap().issues[index].fix().unwrap().command
+		};
+		handle_command(&model_clone, command);
+	});
 
 	// now create the "real initial" state, now that we have a model to work with
 	let model_weak = Rc::downgrade(&model);
@@ -577,15 +1250,64 @@ pub fn create(args: AppArgs) -> AppWindow {
 	});
 	model.update_state(|_| Some(state));
 
-	// and load the InfoDb and update the state
-	model.infodb_load(false);
+	// if we've installed the worker_ui plugin, make sure it is current
+	if let Some(prefs_path) = model.preferences.borrow().prefs_path.as_deref() {
+		if is_worker_ui_installed(prefs_path) {
+			if let Err(e) = install_worker_ui(prefs_path) {
+				event!(LOG_PREFS, "Error updating worker_ui plugin: {e:?}");
+			}
+		}
+	}
+
+	// pick up preferences changed on other computers
+	if let Err(e) = sync_prefs_now(&model) {
+		event!(LOG_PREFS, "Error synchronizing preferences: {e:?}");
+	}
+
+	// and load the InfoDb and update the state (unless we're in safe mode)
+	if model.safe_mode {
+		model.update_state(AppState::safe_mode);
+	} else {
+		model.infodb_load(false);
+	}
 
 	// initial updates
+	update_menu_accelerators(&model);
+	update_ui_for_items_view_mode(&model);
+	update_ui_for_sessions(&model);
+	update_ui_for_mame_executables(&model);
 	update_ui_for_current_history_item(&model);
 	update_items_model_for_columns_and_search(&model);
 
 	// and we're done!
-	app_window
+	(app_window, model)
+}
+
+/// The application running against a mock MAME, for end to end tests (see `harness.rs`)
+#[cfg(feature = "test-harness")]
+pub struct HarnessApp {
+	_app_window: AppWindow,
+	model: Rc<AppModel>,
+}
+
+#[cfg(feature = "test-harness")]
+impl HarnessApp {
+	pub fn new(args: AppArgs, mock: Arc<MockMame>, info_db: Rc<InfoDb>) -> Self {
+		let (app_window, model) = create_model(args, |_, log| MameController::new_mock(mock, log));
+		model.update_state(|state| state.with_info_db(info_db));
+		Self {
+			_app_window: app_window,
+			model,
+		}
+	}
+
+	pub fn handle_command(&self, command: AppCommand) {
+		handle_command(&self.model, command);
+	}
+
+	pub fn status(&self) -> Option<Status> {
+		active_session_status(&self.model)
+	}
 }
 
 fn create_menu_bar() -> Menu {
@@ -597,113 +1319,377 @@ fn create_menu_bar() -> Menu {
 		.iter()
 		.map(|x| {
 			let id = AppCommand::SettingsToggleBuiltinCollection(*x);
-			MenuItem::with_id(id, format!("{}", x), true, None)
+			MenuItem::with_id(id, tr(&format!("{}", x)), true, None)
 		})
 		.collect::<Vec<_>>();
 	let toggle_builtin_menu_items = to_menu_item_ref_vec(&toggle_builtin_menu_items);
 
+	let toggle_column_menu_items = column_providers()
+		.into_iter()
+		.map(|x| {
+			let id = AppCommand::ViewToggleColumn(x.column_type);
+			CheckMenuItem::with_id(id, tr(x.title), true, false, None)
+		})
+		.collect::<Vec<_>>();
+	let reset_column_widths_menu_item =
+		MenuItem::with_id(AppCommand::ViewResetColumnWidths, tr("Reset Column Widths"), true, None);
+	let columns_separator = PredefinedMenuItem::separator();
+	let mut toggle_column_menu_items = to_menu_item_ref_vec(&toggle_column_menu_items);
+	toggle_column_menu_items.push(&columns_separator);
+	toggle_column_menu_items.push(&reset_column_widths_menu_item);
+
+	let screenshot_template_menu_items = SCREENSHOT_TEMPLATES
+		.iter()
+		.map(|(template, text)| {
+			let id = AppCommand::SettingsScreenshotsTemplate(template.to_string());
+			CheckMenuItem::with_id(id, tr(text), true, false, None)
+		})
+		.collect::<Vec<_>>();
+	let screenshot_template_menu_items = to_menu_item_ref_vec(&screenshot_template_menu_items);
+	let screenshots_menu = Submenu::with_items(
+		tr("Screenshots"),
+		true,
+		&[
+			&CheckMenuItem::with_id(
+				AppCommand::SettingsScreenshotsToggleAsk,
+				tr("Ask Every Time"),
+				true,
+				false,
+				None,
+			),
+			&CheckMenuItem::with_id(
+				AppCommand::SettingsScreenshotsTogglePerMachineDirectories,
+				tr("Save Into Per-Machine Folders"),
+				true,
+				false,
+				None,
+			),
+			&PredefinedMenuItem::separator(),
+			&Submenu::with_items(tr("Naming"), true, &screenshot_template_menu_items).unwrap(),
+		],
+	)
+	.unwrap();
+	let sync_menu = Submenu::with_items(
+		tr("Sync Preferences"),
+		true,
+		&[
+			&MenuItem::with_id(
+				AppCommand::SettingsSyncDirectoryDialog,
+				tr("Choose Sync Directory..."),
+				true,
+				None,
+			),
+			&MenuItem::with_id(AppCommand::SettingsSyncUrlDialog, tr("Sync With WebDAV..."), true, None),
+			&MenuItem::with_id(AppCommand::SettingsSyncNow, tr("Sync Now"), false, None),
+			&MenuItem::with_id(AppCommand::SettingsStopSync, tr("Stop Syncing"), false, None),
+		],
+	)
+	.unwrap();
+	let remote_control_menu = Submenu::with_items(
+		tr("Remote Control"),
+		true,
+		&[
+			&CheckMenuItem::with_id(
+				AppCommand::SettingsRemoteControlToggleEnabled,
+				tr("Enable Remote Control"),
+				true,
+				false,
+				None,
+			),
+			&CheckMenuItem::with_id(
+				AppCommand::SettingsRemoteControlToggleAllowLan,
+				tr("Allow Connections From Other Computers"),
+				true,
+				false,
+				None,
+			),
+			&MenuItem::with_id(
+				AppCommand::SettingsRemoteControlShowToken,
+				tr("Show Access Token..."),
+				false,
+				None,
+			),
+		],
+	)
+	.unwrap();
+
+	let attract_mode_menu = Submenu::with_items(
+		tr("Attract Mode"),
+		true,
+		&[
+			&CheckMenuItem::with_id(
+				AppCommand::SettingsAttractModeToggleEnabled,
+				tr("Show Snapshots When Idle"),
+				true,
+				false,
+				None,
+			),
+			&CheckMenuItem::with_id(
+				AppCommand::SettingsAttractModeToggleAutoLaunch,
+				tr("Run Random Machines"),
+				true,
+				false,
+				None,
+			),
+		],
+	)
+	.unwrap();
+
 	#[rustfmt::skip]
 	let menu_bar = Menu::with_items(&[
 		&Submenu::with_items(
-			"File",
+			tr("File"),
 			true,
 			&[
-				&MenuItem::with_id(AppCommand::FileStop, "Stop", false, None),
-				&CheckMenuItem::with_id(AppCommand::FilePause, "Pause", false, false, accel("Pause")),
+				&MenuItem::with_id(AppCommand::FileStop, tr("Stop"), false, None),
+				&CheckMenuItem::with_id(AppCommand::FilePause, tr("Pause"), false, false, None),
+				&PredefinedMenuItem::separator(),
+				&MenuItem::with_id(AppCommand::FileDevicesAndImages,tr("Devices and Images..."), false, None),
+				&PredefinedMenuItem::separator(),
+				&MenuItem::with_id(AppCommand::ItemsSurpriseMe { run: true }, tr("Run Random Machine"), false, None),
+				&PredefinedMenuItem::separator(),
+				&CheckMenuItem::with_id(AppCommand::FileToggleRecordInput, tr("Record Input"), true, false, None),
+				&CheckMenuItem::with_id(AppCommand::FileToggleRecordAudio, tr("Record Audio"), true, false, None),
+				&MenuItem::with_id(AppCommand::FilePlaybackInputDialog, tr("Play Back Input..."), false, None),
 				&PredefinedMenuItem::separator(),
-				&MenuItem::with_id(AppCommand::FileDevicesAndImages,"Devices and Images...", false, None),
+				&CheckMenuItem::with_id(AppCommand::FileToggleRecordMacro, tr("Record Macro"), false, false, None),
+				&MenuItem::with_id(AppCommand::FilePlayMacroDialog, tr("Play Macro..."), false, None),
+				&MenuItem::with_id(AppCommand::FileStopMacro, tr("Stop Macro"), false, None),
 				&PredefinedMenuItem::separator(),
-				&MenuItem::new("Quick Load State", false, accel("F7")),
-				&MenuItem::new("Quick Save State", false, accel("Shift+F7")),
-				&MenuItem::new("Load State...", false, accel("Ctrl+F7")),
-				&MenuItem::new("Save State...", false, accel("Ctrl+Shift+F7")),
+				&MenuItem::new(tr("Quick Load State"), false, accel("F7")),
+				&MenuItem::new(tr("Quick Save State"), false, accel("Shift+F7")),
+				&MenuItem::new(tr("Load State..."), false, accel("Ctrl+F7")),
+				&MenuItem::new(tr("Save State..."), false, accel("Ctrl+Shift+F7")),
+				&MenuItem::with_id(AppCommand::FileSaveScreenshot, tr("Save Screenshot..."), false, accel("F12")),
+				&CheckMenuItem::with_id(AppCommand::FileToggleRecordMovie, tr("Record Movie"), false, false, accel("Shift+F12")),
 				&PredefinedMenuItem::separator(),
-				&MenuItem::new("Debugger...", false, None),
+				&MenuItem::new(tr("Debugger..."), false, None),
 				&Submenu::with_items(
-					"Reset",
+					tr("Reset"),
 					true,
 					&[
-						&MenuItem::with_id(AppCommand::FileResetSoft, "Soft Reset", false, None),
-						&MenuItem::with_id(AppCommand::FileResetHard,"Hard Reset", false, None),
+						&MenuItem::with_id(AppCommand::FileResetSoft, tr("Soft Reset"), false, None),
+						&MenuItem::with_id(AppCommand::FileResetHard,tr("Hard Reset"), false, None),
 					],
 				)
 				.unwrap(),
-				&MenuItem::with_id(AppCommand::FileExit, "Exit", true, accel("Ctrl+Alt+X")),
+				&MenuItem::with_id(AppCommand::FileExit, tr("Exit"), true, None),
 			],
 		)
 		.unwrap(),
 		&Submenu::with_items(
-			"Options",
+			tr("View"),
 			true,
 			&[
+				&MenuItem::with_id(AppCommand::ViewCommandPalette, tr("Command Palette..."), true, None),
+				&MenuItem::with_id(AppCommand::ViewLog, tr("MAME Log..."), true, None),
+				&PredefinedMenuItem::separator(),
+				&CheckMenuItem::with_id(AppCommand::ViewToggleGrid, tr("Grid View"), true, false, None),
 				&Submenu::with_items(
-					"Throttle",
+					tr("Grid Columns"),
 					true,
 					&[
-						&CheckMenuItem::with_id(AppCommand::OptionsThrottleRate(10.0), "1000%", false, false, None),
-						&CheckMenuItem::with_id(AppCommand::OptionsThrottleRate(5.0), "500%", false, false, None),
-						&CheckMenuItem::with_id(AppCommand::OptionsThrottleRate(2.0), "200%", false, false, None),
-						&CheckMenuItem::with_id(AppCommand::OptionsThrottleRate(1.0), "100%", false, false, None),
-						&CheckMenuItem::with_id(AppCommand::OptionsThrottleRate(0.5), "50%", false, false, None),
-						&CheckMenuItem::with_id(AppCommand::OptionsThrottleRate(0.2), "20%", false, false, None),
-						&CheckMenuItem::with_id(AppCommand::OptionsThrottleRate(0.1), "10%", false, false, None),
-						&PredefinedMenuItem::separator(),
-						&MenuItem::new("Increase Speed", false, accel("F9")),
-						&MenuItem::new("Decrease Speed", false, accel("F8")),
-						&CheckMenuItem::with_id(AppCommand::OptionsToggleWarp, "Warp mode", false, false, accel("F10")),
+						&CheckMenuItem::with_id(AppCommand::ViewGridColumns(3), "3", true, false, None),
+						&CheckMenuItem::with_id(AppCommand::ViewGridColumns(4), "4", true, false, None),
+						&CheckMenuItem::with_id(AppCommand::ViewGridColumns(5), "5", true, false, None),
+						&CheckMenuItem::with_id(AppCommand::ViewGridColumns(6), "6", true, false, None),
+						&CheckMenuItem::with_id(AppCommand::ViewGridColumns(8), "8", true, false, None),
 					],
 				)
 				.unwrap(),
 				&Submenu::with_items(
-					"Frame Skip",
-					false,
-					&[
-						&MenuItem::new("Auto", false, None),
-						&MenuItem::new("0", false, None),
-						&MenuItem::new("1", false, None),
-						&MenuItem::new("2", false, None),
-						&MenuItem::new("3", false, None),
-						&MenuItem::new("4", false, None),
-						&MenuItem::new("5", false, None),
-						&MenuItem::new("6", false, None),
-						&MenuItem::new("7", false, None),
-						&MenuItem::new("8", false, None),
-						&MenuItem::new("9", false, None),
-						&MenuItem::new("10", false, None),
-					],
+					tr("Columns"),
+					true,
+					&toggle_column_menu_items,
 				)
 				.unwrap(),
-				&MenuItem::new("Full Screen", false, accel("F11")),
-				&CheckMenuItem::with_id(AppCommand::OptionsToggleSound, "Sound", false, false,None),
-				&MenuItem::new("Cheats...", false, None),
-				&MenuItem::with_id(AppCommand::OptionsClassic,"Classic MAME Menu", false, None),
+				&Submenu::with_items(
+					tr("Clones"),
+					true,
+					&[
+						&CheckMenuItem::with_id(
+							AppCommand::ViewCloneDisplay(CloneDisplay::Show),
+							tr("Show Clones"),
+							true,
+							false,
+							None,
+						),
+						&CheckMenuItem::with_id(
+							AppCommand::ViewCloneDisplay(CloneDisplay::Hide),
+							tr("Hide Clones"),
+							true,
+							false,
+							None,
+						),
+						&CheckMenuItem::with_id(
+							AppCommand::ViewCloneDisplay(CloneDisplay::Group),
+							tr("Group Clones Under Parents"),
+							true,
+							false,
+							None,
+						),
+					],
+				)
+				.unwrap(),
+				&Submenu::with_items(
+					tr("Double Click"),
+					true,
+					&[
+						&CheckMenuItem::with_id(AppCommand::ViewItemAction(ItemAction::Run), tr("Run"), true, false, None),
+						&CheckMenuItem::with_id(
+							AppCommand::ViewItemAction(ItemAction::Configure),
+							tr("Configure"),
+							true,
+							false,
+							None,
+						),
+						&CheckMenuItem::with_id(
+							AppCommand::ViewItemAction(ItemAction::BrowseSoftware),
+							tr("Browse Software"),
+							true,
+							false,
+							None,
+						),
+						&CheckMenuItem::with_id(
+							AppCommand::ViewItemAction(ItemAction::ShowDetails),
+							tr("Show Details"),
+							true,
+							false,
+							None,
+						),
+					],
+				)
+				.unwrap(),
+				&Submenu::with_items(
+					tr("All Systems"),
+					true,
+					&[
+						&CheckMenuItem::with_id(
+							AppCommand::ViewToggleHiddenMachineKind(MachineKind::Bios),
+							tr("Hide BIOS Sets"),
+							true,
+							false,
+							None,
+						),
+						&CheckMenuItem::with_id(
+							AppCommand::ViewToggleHiddenMachineKind(MachineKind::Device),
+							tr("Hide Devices"),
+							true,
+							false,
+							None,
+						),
+						&CheckMenuItem::with_id(
+							AppCommand::ViewToggleHiddenMachineKind(MachineKind::Mechanical),
+							tr("Hide Mechanical Machines"),
+							true,
+							false,
+							None,
+						),
+					],
+				)
+				.unwrap(),
+			],
+		)
+		.unwrap(),
+		&Submenu::with_items(
+			tr("Options"),
+			true,
+			&[
+				&Submenu::with_items(
+					tr("Throttle"),
+					true,
+					&[
+						&CheckMenuItem::with_id(AppCommand::OptionsThrottleRate(10.0), "1000%", false, false, None),
+						&CheckMenuItem::with_id(AppCommand::OptionsThrottleRate(5.0), "500%", false, false, None),
+						&CheckMenuItem::with_id(AppCommand::OptionsThrottleRate(2.0), "200%", false, false, None),
+						&CheckMenuItem::with_id(AppCommand::OptionsThrottleRate(1.0), "100%", false, false, None),
+						&CheckMenuItem::with_id(AppCommand::OptionsThrottleRate(0.5), "50%", false, false, None),
+						&CheckMenuItem::with_id(AppCommand::OptionsThrottleRate(0.2), "20%", false, false, None),
+						&CheckMenuItem::with_id(AppCommand::OptionsThrottleRate(0.1), "10%", false, false, None),
+						&PredefinedMenuItem::separator(),
+						&MenuItem::new(tr("Increase Speed"), false, accel("F9")),
+						&MenuItem::new(tr("Decrease Speed"), false, accel("F8")),
+						&CheckMenuItem::with_id(AppCommand::OptionsToggleWarp, tr("Warp mode"), false, false, None),
+					],
+				)
+				.unwrap(),
+				&Submenu::with_items(
+					tr("Frame Skip"),
+					false,
+					&[
+						&MenuItem::new(tr("Auto"), false, None),
+						&MenuItem::new("0", false, None),
+						&MenuItem::new("1", false, None),
+						&MenuItem::new("2", false, None),
+						&MenuItem::new("3", false, None),
+						&MenuItem::new("4", false, None),
+						&MenuItem::new("5", false, None),
+						&MenuItem::new("6", false, None),
+						&MenuItem::new("7", false, None),
+						&MenuItem::new("8", false, None),
+						&MenuItem::new("9", false, None),
+						&MenuItem::new("10", false, None),
+					],
+				)
+				.unwrap(),
+				&MenuItem::new(tr("Full Screen"), false, accel("F11")),
+				&CheckMenuItem::with_id(AppCommand::OptionsToggleSound, tr("Sound"), false, false,None),
+				&MenuItem::new(tr("Cheats..."), false, None),
+				&MenuItem::with_id(AppCommand::OptionsClassic,tr("Classic MAME Menu"), false, None),
 			],
 		)
 		.unwrap(),
 		&Submenu::with_items(
-			"Settings",
+			tr("Settings"),
 			true,
 			&[
-				&MenuItem::new("Joysticks and Controllers...", false, None),
-				&MenuItem::new("Keyboard...", false, None),
-				&MenuItem::new("Miscellaneous Input...", false, None),
-				&MenuItem::new("Configuration...", false, None),
-				&MenuItem::new("DIP Switches...", false, None),
+				&MenuItem::new(tr("Joysticks and Controllers..."), false, None),
+				&MenuItem::new(tr("Keyboard..."), false, None),
+				&MenuItem::new(tr("Miscellaneous Input..."), false, None),
+				&MenuItem::new(tr("Configuration..."), false, None),
+				&MenuItem::new(tr("DIP Switches..."), false, None),
 				&PredefinedMenuItem::separator(),
-				&MenuItem::with_id(AppCommand::SettingsPaths, "Paths...", true, None),
-				&Submenu::with_items("Builtin Collections", true, &toggle_builtin_menu_items).unwrap(),
-				&MenuItem::with_id(AppCommand::SettingsReset, "Reset Settings To Default", true, None),
-				&MenuItem::new("Import MAME INI...", false, None),
+				&MenuItem::with_id(AppCommand::SettingsPaths, tr("Paths..."), true, None),
+				&MenuItem::with_id(AppCommand::SettingsAddMameExecutableDialog, tr("Add MAME Executable..."), true, None),
+				&MenuItem::with_id(AppCommand::SettingsShortcuts, tr("Keyboard Shortcuts..."), true, None),
+				&MenuItem::with_id(AppCommand::SettingsRecording, tr("Recording..."), true, None),
+				&MenuItem::with_id(AppCommand::SettingsAppearance, tr("Appearance..."), true, None),
+				&Submenu::with_items(tr("Builtin Collections"), true, &toggle_builtin_menu_items).unwrap(),
+				&screenshots_menu,
+				&remote_control_menu,
+				&CheckMenuItem::with_id(AppCommand::SettingsToggleDiscordRichPresence, tr("Show Running Machine On Discord"), true, false, None),
+				&CheckMenuItem::with_id(AppCommand::SettingsToggleMountAllSoftwareParts, tr("Mount All Parts Of Software"), true, false, None),
+				&attract_mode_menu,
+				&MenuItem::with_id(AppCommand::SettingsNormalizeFoldersDialog, tr("Normalize Folder Names..."), true, None),
+				&MenuItem::with_id(AppCommand::SettingsOrphanedEntriesDialog, tr("Clean Up Missing Items..."), false, None),
+				&sync_menu,
+				&MenuItem::with_id(AppCommand::SettingsReset, tr("Reset Settings To Default..."), true, None),
+				&MenuItem::new(tr("Import MAME INI..."), false, None),
 			],
 		)
 		.unwrap(),
 		&Submenu::with_items(
-			"Help",
+			tr("Help"),
 			true,
 			&[
-				&MenuItem::with_id(AppCommand::InfoDbBuildLoad { force_refresh: true }, "Refresh MAME machine info...", false, None),
-				&MenuItem::with_id(AppCommand::HelpWebSite, "BletchMAME web site...", true, None),
-				&MenuItem::with_id(AppCommand::HelpAbout, "About...", true, None),
+				&MenuItem::with_id(AppCommand::InfoDbBuildLoad { force_refresh: true }, tr("Refresh MAME machine info..."), false, None),
+				&MenuItem::with_id(AppCommand::InfoDbBuildFromListXmlDialog, tr("Build database from listxml file..."), false, None),
+				&MenuItem::with_id(AppCommand::InfoDbDiffDialog, tr("Changes since previous MAME machine info..."), false, None),
+				&MenuItem::with_id(AppCommand::HelpWebSite, tr("BletchMAME web site..."), true, None),
+				&MenuItem::with_id(
+					AppCommand::HelpCheckForMameUpdates,
+					tr("Check for MAME Updates..."),
+					false,
+					None,
+				),
+				&MenuItem::with_id(
+					AppCommand::HelpGenerateDiagnosticReport,
+					tr("Generate Diagnostic Report..."),
+					true,
+					None,
+				),
+				&MenuItem::with_id(AppCommand::HelpLogLevelsDialog, tr("Log Levels..."), true, None),
+				&MenuItem::with_id(AppCommand::HelpAbout, tr("About..."), true, None),
 			],
 		)
 		.unwrap(),
@@ -717,20 +1703,20 @@ fn handle_command(model: &Rc<AppModel>, command: AppCommand) {
 	event!(LOG_COMMANDS, "handle_command(): command={:?}", &command);
 	match command {
 		AppCommand::FileStop => {
-			model.mame_controller.issue_command(MameCommand::Stop);
+			if model.detached_sessions.active().is_none() {
+				model.launch.take();
+			}
+			issue_command_to_active_session(model, MameCommand::Stop);
 		}
 		AppCommand::FilePause => {
-			let is_paused = model
-				.state
-				.borrow()
-				.status()
-				.and_then(|s| s.running.as_ref())
+			let is_paused = active_session_status(model)
+				.and_then(|s| s.running)
 				.map(|r| r.is_paused)
 				.unwrap_or_default();
 			if is_paused {
-				model.mame_controller.issue_command(MameCommand::Resume);
+				issue_command_to_active_session(model, MameCommand::Resume);
 			} else {
-				model.mame_controller.issue_command(MameCommand::Pause);
+				issue_command_to_active_session(model, MameCommand::Pause);
 			}
 		}
 		AppCommand::FileDevicesAndImages => {
@@ -749,39 +1735,153 @@ fn handle_command(model: &Rc<AppModel>, command: AppCommand) {
 			);
 			spawn_local(fut).unwrap();
 		}
+		AppCommand::FileSaveScreenshot => {
+			save_screenshot(model);
+		}
+		AppCommand::FileToggleRecordMovie => {
+			toggle_record_movie(model);
+		}
+		AppCommand::FileToggleRecordInput => {
+			model.record_input.set(!model.record_input.get());
+			update_menus(model);
+		}
+		AppCommand::FileToggleRecordAudio => {
+			model.record_audio.set(!model.record_audio.get());
+			update_menus(model);
+		}
+		AppCommand::FilePlaybackInputDialog => {
+			let directory = input_files_directory(model);
+			if let Some(path) = inp_file_dialog(&model.app_window(), directory.as_deref()) {
+				match std::fs::File::open(&path)
+					.map_err(Error::new)
+					.and_then(inp_machine_name)
+				{
+					Ok(machine_name) => handle_command(model, AppCommand::PlaybackInput { machine_name, path }),
+					Err(e) => handle_command(model, AppCommand::ErrorMessageBox(format!("{e:?}"))),
+				}
+			}
+		}
+		AppCommand::FileToggleRecordMacro => {
+			if let Some(mac) = model.macro_recorder.stop() {
+				if let Some(path) = save_macro_file_dialog(&model.app_window()) {
+					let result = std::fs::File::create(&path)
+						.map_err(Error::new)
+						.and_then(|file| mac.save(file));
+					if let Err(e) = result {
+						handle_command(model, AppCommand::ErrorMessageBox(format!("{e:?}")));
+					}
+				}
+			} else {
+				model.macro_recorder.start();
+			}
+			update_menus(model);
+		}
+		AppCommand::FilePlayMacroDialog => {
+			if let Some(path) = macro_file_dialog(&model.app_window()) {
+				match std::fs::File::open(&path).map_err(Error::new).and_then(Macro::load) {
+					Ok(mac) => play_macro(model, mac),
+					Err(e) => handle_command(model, AppCommand::ErrorMessageBox(format!("{e:?}"))),
+				}
+			}
+		}
+		AppCommand::FileStopMacro => {
+			if let Some(playback) = model.macro_playback.take() {
+				playback.abort();
+			}
+			update_menus(model);
+		}
 		AppCommand::FileResetSoft => {
-			model.mame_controller.issue_command(MameCommand::SoftReset);
+			issue_command_to_active_session(model, MameCommand::SoftReset);
 		}
 		AppCommand::FileResetHard => {
-			model.mame_controller.issue_command(MameCommand::HardReset);
+			issue_command_to_active_session(model, MameCommand::HardReset);
 		}
 		AppCommand::FileExit => {
+			model.launch.take();
+			model.session_stats.take();
 			if model.mame_controller.has_session() {
 				model.mame_controller.issue_command(MameCommand::Exit);
 			}
+			model.detached_sessions.shutdown_all();
 			model.update_state(AppState::shutdown);
 		}
+		AppCommand::ViewLog => {
+			dialog_log_viewer(model.session_log.clone());
+		}
+		AppCommand::ViewCommandPalette => {
+			let commands = model.menu_bar.enabled_commands();
+			let parent = model.app_window().as_weak();
+			let model_clone = model.clone();
+			let fut = async move {
+				if let Some(command) = dialog_command_palette(parent, commands)
+					.await
+					.and_then(|id| AppCommand::try_from(&id).ok())
+				{
+					handle_command(&model_clone, command);
+				}
+			};
+			spawn_local(fut).unwrap();
+		}
+		AppCommand::ViewToggleGrid => {
+			model.modify_prefs(|prefs| {
+				prefs.items_view_mode = match prefs.items_view_mode {
+					ItemsViewMode::Table => ItemsViewMode::Grid,
+					ItemsViewMode::Grid => ItemsViewMode::Table,
+				};
+			});
+		}
+		AppCommand::ViewGridColumns(columns) => {
+			model.modify_prefs(|prefs| {
+				prefs.items_grid_columns = (columns != DEFAULT_GRID_COLUMNS).then_some(columns);
+			});
+		}
+		AppCommand::ViewCloneDisplay(clone_display) => {
+			model.modify_prefs(|prefs| {
+				prefs.clone_display = clone_display;
+			});
+		}
+		AppCommand::ViewItemAction(item_action) => {
+			model.modify_prefs(|prefs| {
+				prefs.item_action = item_action;
+			});
+		}
+		AppCommand::ViewToggleHiddenMachineKind(machine_kind) => {
+			model.modify_prefs(|prefs| {
+				if !prefs.hidden_machine_kinds.remove(&machine_kind) {
+					prefs.hidden_machine_kinds.insert(machine_kind);
+				}
+			});
+		}
+		AppCommand::ViewToggleColumn(column_type) => {
+			let items_columns = model.app_window().get_items_columns();
+			model.modify_prefs(|prefs| {
+				// pick up any column resizing before the columns get rebuilt
+				for (index, column) in prefs.items_columns.iter_mut().enumerate() {
+					if let Some(data) = items_columns.row_data(index) {
+						column.width = data.width;
+					}
+				}
+				prefs.toggle_items_column(column_type);
+			});
+		}
+		AppCommand::ViewResetColumnWidths => {
+			reset_items_column_widths(model);
+			update_ui_for_items_columns(model);
+			update_ui_for_sort_changes(model);
+		}
 		AppCommand::OptionsThrottleRate(throttle) => {
-			model.mame_controller.issue_command(MameCommand::ThrottleRate(throttle));
+			issue_command_to_active_session(model, MameCommand::ThrottleRate(throttle));
 		}
 		AppCommand::OptionsToggleWarp => {
-			let is_throttled = model
-				.state
-				.borrow()
-				.status()
-				.and_then(|s| s.running.as_ref())
+			let is_throttled = active_session_status(model)
+				.and_then(|s| s.running)
 				.map(|r| r.is_throttled)
 				.unwrap_or_default();
-			model
-				.mame_controller
-				.issue_command(MameCommand::Throttled(!is_throttled));
+			issue_command_to_active_session(model, MameCommand::Throttled(!is_throttled));
 		}
 		AppCommand::OptionsToggleSound => {
-			if let Some(sound_attenuation) = model
-				.state
-				.borrow()
-				.status()
-				.and_then(|s| s.running.as_ref())
+			if let Some(sound_attenuation) = active_session_status(model)
+				.and_then(|s| s.running)
 				.map(|r| r.sound_attenuation)
 			{
 				let is_sound_enabled = sound_attenuation > SOUND_ATTENUATION_OFF;
@@ -790,176 +1890,965 @@ fn handle_command(model: &Rc<AppModel>, command: AppCommand) {
 				} else {
 					SOUND_ATTENUATION_ON
 				};
-				model
-					.mame_controller
-					.issue_command(MameCommand::SetAttenuation(new_attenuation));
+				issue_command_to_active_session(model, MameCommand::SetAttenuation(new_attenuation));
 			}
 		}
 		AppCommand::OptionsClassic => {
-			model.mame_controller.issue_command(MameCommand::ClassicMenu);
+			issue_command_to_active_session(model, MameCommand::ClassicMenu);
 		}
 		AppCommand::SettingsPaths => {
-			let fut = show_paths_dialog(model.clone());
+			let fut = show_paths_dialog(model.clone(), PathType::default());
+			spawn_local(fut).unwrap();
+		}
+		AppCommand::SettingsPathsFor(path_type) => {
+			let fut = show_paths_dialog(model.clone(), path_type);
 			spawn_local(fut).unwrap();
 		}
+		AppCommand::SettingsAddMameExecutableDialog => {
+			if let Some(mame_executable) = file_dialog(&model.app_window(), PathType::MameExecutable, None) {
+				handle_command(model, AppCommand::SettingsSwitchMameExecutable(mame_executable));
+			}
+		}
+		AppCommand::SettingsSwitchMameExecutable(mame_executable) => model.modify_prefs(|prefs| {
+			prefs.switch_mame_executable(mame_executable);
+		}),
 		AppCommand::SettingsToggleBuiltinCollection(col) => {
 			model.modify_prefs(|prefs| {
 				toggle_builtin_collection(&mut prefs.collections, col);
 			});
 		}
-		AppCommand::SettingsReset => model.modify_prefs(|prefs| {
-			let prefs_path = prefs.prefs_path.take();
-			*prefs = Preferences::fresh(prefs_path);
-		}),
-		AppCommand::HelpWebSite => {
-			let _ = open::that("https://www.bletchmame.org");
-		}
-		AppCommand::HelpAbout => {
-			let modal = Modal::new(&model.app_window(), || AboutDialog::new().unwrap());
-			modal.launch();
-		}
-		AppCommand::MameSessionStarted => {
-			// do nothing
-		}
-		AppCommand::MameSessionEnded => {
-			model.update_state(AppState::session_ended);
-		}
-		AppCommand::MameStatusUpdate(update) => {
-			model.update_state(|state| state.status_update(update));
-		}
-		AppCommand::MamePing => {
-			model.mame_controller.issue_command(MameCommand::Ping);
-		}
-		AppCommand::ErrorMessageBox(message) => {
+		AppCommand::SettingsShortcuts => {
+			let shortcuts = effective_shortcuts(&model.preferences.borrow());
 			let parent = model.app_window().as_weak();
+			let model_clone = model.clone();
 			let fut = async move {
-				dialog_message_box::<OkOnly>(parent, "Error", message).await;
+				if let Some(new_shortcuts) = dialog_shortcuts(parent, shortcuts).await {
+					model_clone.modify_prefs(|prefs| {
+						prefs.shortcuts = (new_shortcuts != default_shortcuts()).then_some(new_shortcuts);
+					});
+				}
 			};
 			spawn_local(fut).unwrap();
 		}
-		AppCommand::RunMame {
-			machine_name,
-			initial_loads,
-		} => {
-			let initial_loads = initial_loads
-				.iter()
-				.map(|(dev, arg)| (dev.as_ref(), arg.as_ref()))
-				.collect::<Vec<_>>();
-
-			let command = MameCommand::Start {
-				machine_name: &machine_name,
-				initial_loads: initial_loads.as_slice(),
+		AppCommand::SettingsRecording => {
+			let recording = model.preferences.borrow().recording.clone();
+			let parent = model.app_window().as_weak();
+			let model_clone = model.clone();
+			let fut = async move {
+				if let Some(recording) = dialog_recording_settings(parent, recording).await {
+					model_clone.modify_prefs(|prefs| prefs.recording = recording);
+				}
 			};
-			model.mame_controller.issue_command(command);
-		}
-		AppCommand::Browse(collection) => {
-			let collection = Rc::new(collection);
-			model.modify_prefs(|prefs| {
-				prefs.history_push(collection);
-			});
-		}
-		AppCommand::HistoryAdvance(delta) => {
-			model.modify_prefs(|prefs| prefs.history_advance(delta));
-		}
-		AppCommand::SearchText(search) => {
-			model.modify_prefs(|prefs| {
-				// modify the search text
-				let current_entry = prefs.current_history_entry_mut();
-				current_entry.sort_suppressed = !search.is_empty();
-				current_entry.search = search;
-			});
+			spawn_local(fut).unwrap();
 		}
-		AppCommand::ItemsSort(column_index, order) => {
-			model.modify_prefs(|prefs| {
-				for (index, column) in prefs.items_columns.iter_mut().enumerate() {
-					column.sort = (index == column_index).then_some(order);
+		AppCommand::SettingsAppearance => {
+			let (appearance, language, languages) = {
+				let prefs = model.preferences.borrow();
+				let languages = available_languages(prefs.prefs_path.as_deref());
+				(prefs.appearance.clone(), prefs.language.clone(), languages)
+			};
+			let parent = model.app_window().as_weak();
+			let app_window_weak = model.app_window().as_weak();
+			let preview = move |x: &PrefsAppearance| apply_appearance(&app_window_weak.unwrap(), x);
+			let model_clone = model.clone();
+			let fut = async move {
+				match dialog_appearance(parent, appearance.clone(), language, languages, preview).await {
+					Some((appearance, language)) => model_clone.modify_prefs(|prefs| {
+						prefs.appearance = appearance;
+						prefs.language = language;
+					}),
+					None => apply_appearance(&model_clone.app_window(), &appearance),
 				}
-				prefs.current_history_entry_mut().sort_suppressed = false;
-			});
-		}
-		AppCommand::ItemsSelectedChanged => {
-			let selection = model.with_items_table_model(|x| x.current_selection());
-			model.modify_prefs(|prefs| {
-				prefs.current_history_entry_mut().selection = selection;
-			});
-		}
-		AppCommand::AddToExistingFolder(folder_index, new_items) => {
-			model.modify_prefs(|prefs| {
-				add_items_to_existing_folder_collection(&mut prefs.collections, folder_index, new_items);
-			});
-		}
-		AppCommand::AddToNewFolder(name, items) => {
-			model.modify_prefs(|prefs| {
-				add_items_to_new_folder_collection(&mut prefs.collections, name, items);
-			});
+			};
+			spawn_local(fut).unwrap();
 		}
-		AppCommand::AddToNewFolderDialog(items) => {
-			let existing_names = get_folder_collection_names(&model.preferences.borrow().collections);
+		AppCommand::SettingsNormalizeFoldersDialog => {
+			let (_, changes) = normalize_folder_collections(&model.preferences.borrow().collections);
 			let parent = model.app_window().as_weak();
 			let model_clone = model.clone();
 			let fut = async move {
-				if let Some(name) = dialog_new_collection(parent, existing_names).await {
-					let command = AppCommand::AddToNewFolder(name, items);
-					handle_command(&model_clone, command);
+				if changes.is_empty() {
+					let message = tr("Folder names are already normalized");
+					dialog_message_box::<OkOnly>(parent, "Normalize Folder Names", message).await;
+				} else {
+					let changes = changes.iter().map(|x| x.to_string()).join("\n");
+					let message = tr_args(
+						"The following changes will be made:\n\n{changes}",
+						&[("changes", &changes)],
+					);
+					if dialog_message_box::<OkCancel>(parent, "Normalize Folder Names", message).await == OkCancel::Ok {
+						handle_command(&model_clone, AppCommand::SettingsNormalizeFolders);
+					}
 				}
 			};
 			spawn_local(fut).unwrap();
 		}
-		AppCommand::RemoveFromFolder(name, items) => {
-			model.modify_prefs(|prefs| {
-				remove_items_from_folder_collection(&mut prefs.collections, name, &items);
-			});
-		}
-		AppCommand::MoveCollection { old_index, new_index } => {
-			model.modify_prefs(|prefs| {
-				// detach the collection we're moving
-				let collection = prefs.collections.remove(old_index);
+		AppCommand::SettingsNormalizeFolders => model.modify_prefs(|prefs| {
+			let (new_collections, changes) = normalize_folder_collections(&prefs.collections);
+			prefs.collections = new_collections;
 
-				if let Some(new_index) = new_index {
-					// and readd it
-					prefs.collections.insert(new_index, collection);
-				} else {
-					// the collection is being removed; we need to remove any entries that
-					// might be referenced
-					prefs.purge_stray_entries();
+			// history entries refer to folders by name, so they need to follow along
+			for change in changes {
+				if let FolderNormalization::Rename { old_name, new_name }
+				| FolderNormalization::Merge { old_name, new_name } = change
+				{
+					prefs.rename_folder_entries(&old_name, &new_name);
+					if let Some(directory) = prefs.folder_capture_directories.remove(&old_name) {
+						prefs.folder_capture_directories.entry(new_name).or_insert(directory);
+					}
 				}
-			});
-		}
-		AppCommand::DeleteCollectionDialog { index } => {
+			}
+			prefs.purge_stray_entries();
+		}),
+		AppCommand::SettingsOrphanedEntriesDialog => {
+			let Some(orphans) = orphaned_entries(model) else {
+				return;
+			};
 			let parent = model.app_window().as_weak();
 			let model_clone = model.clone();
-			let old_name = get_collection_name(&model.preferences.borrow().collections, index).to_string();
 			let fut = async move {
-				let message = format!("Are you sure you want to delete \"{}\"", old_name);
-				if dialog_message_box::<OkCancel>(parent, "Delete", message).await == OkCancel::Ok {
-					let command = AppCommand::MoveCollection {
-						old_index: index,
-						new_index: None,
-					};
-					handle_command(&model_clone, command);
+				if orphans.is_empty() {
+					let message = tr("No folders or collections refer to missing machines or software");
+					dialog_message_box::<OkOnly>(parent, "Clean Up Missing Items", message).await;
+				} else if let Some(orphans) = dialog_orphaned_entries(parent, orphans).await {
+					handle_command(&model_clone, AppCommand::SettingsRemoveOrphanedEntries(orphans));
 				}
 			};
 			spawn_local(fut).unwrap();
 		}
-		AppCommand::RenameCollectionDialog { index } => {
-			let existing_names = get_folder_collection_names(&model.preferences.borrow().collections);
+		AppCommand::SettingsRemoveOrphanedEntries(orphans) => model.modify_prefs(|prefs| {
+			prefs.collections = remove_orphaned_entries(&prefs.collections, &orphans);
+			prefs.purge_stray_entries();
+		}),
+		AppCommand::SettingsScreenshotsToggleAsk => model.modify_prefs(|prefs| {
+			prefs.screenshots.ask = !prefs.screenshots.ask;
+		}),
+		AppCommand::SettingsScreenshotsTogglePerMachineDirectories => model.modify_prefs(|prefs| {
+			prefs.screenshots.per_machine_directories = !prefs.screenshots.per_machine_directories;
+		}),
+		AppCommand::SettingsScreenshotsTemplate(template) => model.modify_prefs(|prefs| {
+			prefs.screenshots.template = (template != DEFAULT_SCREENSHOT_TEMPLATE).then_some(template);
+		}),
+		AppCommand::SettingsSyncDirectoryDialog => {
+			if let Some(directory) = sync_directory_dialog(&model.app_window()) {
+				// anything here that differs from what is already in the directory gets merged
+				model.modify_prefs(|prefs| {
+					prefs.sync.directory = Some(directory);
+					prefs.sync.url = None;
+					prefs.sync.last_synced = None;
+					prefs.sync.dirty = true;
+				});
+				handle_command(model, AppCommand::SettingsSyncNow);
+			}
+		}
+		AppCommand::SettingsSyncUrlDialog => {
+			let url = model.preferences.borrow().sync.url.clone();
 			let parent = model.app_window().as_weak();
 			let model_clone = model.clone();
-			let old_name = get_collection_name(&model.preferences.borrow().collections, index).to_string();
 			let fut = async move {
-				if let Some(new_name) = dialog_rename_collection(parent, existing_names, old_name).await {
-					let command = AppCommand::RenameCollection { index, new_name };
-					handle_command(&model_clone, command);
+				if let Some(url) = dialog_sync_url(parent, url).await {
+					// as with directories, anything that differs from what is already there gets merged
+					model_clone.modify_prefs(|prefs| {
+						prefs.sync.directory = None;
+						prefs.sync.url = Some(url);
+						prefs.sync.last_synced = None;
+						prefs.sync.dirty = true;
+					});
+					handle_command(&model_clone, AppCommand::SettingsSyncNow);
+				}
+			};
+			spawn_local(fut).unwrap();
+		}
+		AppCommand::SettingsSyncNow => {
+			let message = match sync_prefs_now(model) {
+				Ok(outcome) => outcome.to_string(),
+				Err(e) => format!("{e:?}"),
+			};
+			let parent = model.app_window().as_weak();
+			let fut = async move {
+				dialog_message_box::<OkOnly>(parent, "Sync Preferences", message).await;
+			};
+			spawn_local(fut).unwrap();
+		}
+		AppCommand::SettingsStopSync => model.modify_prefs(|prefs| {
+			prefs.sync = Default::default();
+		}),
+		AppCommand::SettingsRemoteControlToggleEnabled => model.modify_prefs(|prefs| {
+			prefs.remote_control.enabled = !prefs.remote_control.enabled;
+		}),
+		AppCommand::SettingsRemoteControlToggleAllowLan => {
+			let mut is_new_token = false;
+			model.modify_prefs(|prefs| {
+				let remote_control = &mut prefs.remote_control;
+				remote_control.allow_lan = !remote_control.allow_lan;
+				if remote_control.allow_lan && remote_control.token.is_none() {
+					remote_control.token = Some(generate_token());
+					is_new_token = true;
+				}
+			});
+
+			// other computers are going to need the token, so show it the first time around
+			if is_new_token {
+				handle_command(model, AppCommand::SettingsRemoteControlShowToken);
+			}
+		}
+		AppCommand::SettingsRemoteControlShowToken => {
+			let token = model.preferences.borrow().remote_control.token.clone();
+			if let Some(token) = token {
+				let parent = model.app_window().as_weak();
+				let message = tr_args(
+					"Other computers have to send this header with each request:\n\nAuthorization: Bearer {token}",
+					&[("token", &token)],
+				);
+				let fut = async move {
+					dialog_message_box::<OkOnly>(parent, "Remote Control", message).await;
+				};
+				spawn_local(fut).unwrap();
+			}
+		}
+		AppCommand::SettingsToggleDiscordRichPresence => model.modify_prefs(|prefs| {
+			prefs.discord_rich_presence = !prefs.discord_rich_presence;
+		}),
+		AppCommand::SettingsToggleMountAllSoftwareParts => model.modify_prefs(|prefs| {
+			prefs.mount_first_software_part_only = !prefs.mount_first_software_part_only;
+		}),
+		AppCommand::SettingsAttractModeToggleEnabled => model.modify_prefs(|prefs| {
+			prefs.attract_mode.enabled = !prefs.attract_mode.enabled;
+		}),
+		AppCommand::SettingsAttractModeToggleAutoLaunch => model.modify_prefs(|prefs| {
+			prefs.attract_mode.auto_launch = !prefs.attract_mode.auto_launch;
+		}),
+		AppCommand::SettingsReset => {
+			let prefs = model.preferences.borrow().clone();
+			let parent = model.app_window().as_weak();
+			let model_clone = model.clone();
+			let fut = async move {
+				if let Some(options) = dialog_reset_settings(parent, prefs).await {
+					model_clone.modify_prefs(|prefs| *prefs = prefs.fresh_keeping(options));
+				}
+			};
+			spawn_local(fut).unwrap();
+		}
+		AppCommand::HelpWebSite => {
+			let _ = open::that("https://www.bletchmame.org");
+		}
+		AppCommand::HelpCheckForMameUpdates => {
+			let Some(prefs_path) = model.preferences.borrow().prefs_path.clone() else {
+				return;
+			};
+			let current = model.state.borrow().info_db.as_ref().map(|x| x.build().clone());
+			let parent = model.app_window().as_weak();
+			let model_clone = model.clone();
+			let fut = async move {
+				let release = match spawn_blocking(latest_release).await.unwrap_or_else(|e| Err(e.into())) {
+					Ok(Some(release)) => release,
+					Ok(None) => {
+						let message = tr("The MAME project does not publish builds for this platform");
+						dialog_message_box::<OkOnly>(parent, "MAME Updates", message).await;
+						return;
+					}
+					Err(e) => {
+						handle_command(&model_clone, AppCommand::ErrorMessageBox(format!("{e:?}")));
+						return;
+					}
+				};
+				if !is_update(&release, current.as_ref()) {
+					let message = tr_args("You have the latest MAME ({version})", &[("version", &release.version)]);
+					dialog_message_box::<OkOnly>(parent, "MAME Updates", message).await;
+					return;
+				}
+
+				let message = tr_args(
+					"MAME {version} is available. Download and install it?",
+					&[("version", &release.version)],
+				);
+				if dialog_message_box::<OkCancel>(parent, "MAME Updates", message).await != OkCancel::Ok {
+					return;
+				}
+				let install = spawn_blocking(move || install_release(&release, &prefs_path));
+				match install.await.unwrap_or_else(|e| Err(e.into())) {
+					Ok(mame_executable) => {
+						// switching executables rebuilds the InfoDb
+						let mame_executable = mame_executable.to_string_lossy().into_owned();
+						handle_command(&model_clone, AppCommand::SettingsSwitchMameExecutable(mame_executable));
+					}
+					Err(e) => handle_command(&model_clone, AppCommand::ErrorMessageBox(format!("{e:?}"))),
+				}
+			};
+			spawn_local(fut).unwrap();
+		}
+		AppCommand::HelpGenerateDiagnosticReport => {
+			if let Some(path) = diagnostic_report_file_dialog(&model.app_window()) {
+				if let Err(e) = generate_diagnostic_report(model, &path) {
+					handle_command(model, AppCommand::ErrorMessageBox(format!("{e:?}")));
+				}
+			}
+		}
+		AppCommand::HelpLogLevelsDialog => {
+			let levels = model.log_filter.levels();
+			let parent = model.app_window().as_weak();
+			let model_clone = model.clone();
+			let fut = async move {
+				if let Some(levels) = dialog_log_levels(parent, levels).await {
+					if let Err(e) = model_clone.log_filter.set_levels(levels) {
+						handle_command(&model_clone, AppCommand::ErrorMessageBox(format!("{e:?}")));
+					}
+				}
+			};
+			spawn_local(fut).unwrap();
+		}
+		AppCommand::HelpAbout => {
+			let modal = Modal::new(&model.app_window(), || AboutDialog::new().unwrap());
+			let dialog_weak = modal.dialog().as_weak();
+			modal.dialog().on_close_clicked(move || {
+				dialog_weak
+					.unwrap()
+					.window()
+					.dispatch_event(WindowEvent::CloseRequested);
+			});
+			modal.launch();
+		}
+		AppCommand::MameSessionStarted => {
+			// do nothing
+		}
+		AppCommand::MameSessionEnded => {
+			model.update_state(AppState::session_ended);
+			let early_exit = check_launch(model, None, true);
+			update_session_stats(model, true, early_exit);
+		}
+		AppCommand::MameSessionError(message) => {
+			if !check_launch(model, Some(&message), true) {
+				handle_command(model, AppCommand::ErrorMessageBox(message));
+			}
+		}
+		AppCommand::MameStatusUpdate(update) => {
+			model.update_state(|state| state.status_update(update));
+			let early_exit = check_launch(model, None, false);
+			update_session_stats(model, false, early_exit);
+		}
+		AppCommand::MamePing => {
+			model.mame_controller.issue_command(MameCommand::Ping);
+		}
+		AppCommand::DetachedSessionStatusUpdate { session_id, update } => {
+			// detached sessions only live as long as the machine they were started for
+			if model.detached_sessions.status_update(session_id, update) {
+				remember_machine_window_size(model, session_id);
+				update_menus(model);
+			} else {
+				end_detached_session(model, session_id);
+			}
+		}
+		AppCommand::DetachedSessionEnded { session_id } => {
+			end_detached_session(model, session_id);
+		}
+		AppCommand::ErrorMessageBox(message) => {
+			let parent = model.app_window().as_weak();
+			let fut = async move {
+				dialog_message_box::<OkOnly>(parent, "Error", message).await;
+			};
+			spawn_local(fut).unwrap();
+		}
+		AppCommand::RemoteControl(remote_command) => match remote_command {
+			RemoteCommand::Pause => issue_command_to_active_session(model, MameCommand::Pause),
+			RemoteCommand::Resume => issue_command_to_active_session(model, MameCommand::Resume),
+			RemoteCommand::Stop => handle_command(model, AppCommand::FileStop),
+			RemoteCommand::SoftReset => issue_command_to_active_session(model, MameCommand::SoftReset),
+			RemoteCommand::HardReset => issue_command_to_active_session(model, MameCommand::HardReset),
+			RemoteCommand::LoadState(path) => issue_command_to_active_session(model, MameCommand::StateLoad(&path)),
+			RemoteCommand::Run(machine_name) => {
+				let command = AppCommand::RunMame {
+					machine_name,
+					initial_loads: Vec::new(),
+				};
+				handle_command(model, command);
+			}
+		},
+		AppCommand::Launch(request) => {
+			if is_ready_to_launch(model) {
+				let command = match request {
+					LaunchRequest::RunMachine(machine_name) => Some(AppCommand::RunMame {
+						machine_name,
+						initial_loads: Vec::new(),
+					}),
+					LaunchRequest::ResumeLastSession => {
+						model
+							.preferences
+							.borrow()
+							.last_session
+							.clone()
+							.map(|session| AppCommand::RunMame {
+								machine_name: session.machine_name,
+								initial_loads: session.initial_loads,
+							})
+					}
+				};
+				if let Some(command) = command {
+					handle_command(model, command);
+				}
+			} else {
+				// we are still starting up; this will be picked up by `update_state()`
+				model.pending_launch.replace(Some(request));
+			}
+		}
+		AppCommand::RunMame {
+			machine_name,
+			initial_loads,
+		} => {
+			run_mame_with_confirmation(model, machine_name, initial_loads, run_mame);
+		}
+		AppCommand::RunMameUnsupportedSoftware {
+			machine_name,
+			initial_loads,
+			software_description,
+			supported,
+		} => {
+			let parent = model.app_window().as_weak();
+			let model_clone = model.clone();
+			let fut = async move {
+				let args: &[(&str, &dyn Display)] = &[("software", &software_description), ("supported", &supported)];
+				let message = tr_args(
+					"\"{software}\" is marked as \"{supported}\" in its software list, and may not work correctly.  Run it anyway?",
+					args,
+				);
+				if dialog_message_box::<OkCancel>(parent, "Unsupported Software", message).await == OkCancel::Ok {
+					let command = AppCommand::RunMame {
+						machine_name,
+						initial_loads,
+					};
+					handle_command(&model_clone, command);
+				}
+			};
+			spawn_local(fut).unwrap();
+		}
+		AppCommand::RunSoftwareOn {
+			software_list,
+			software,
+			machine_name,
+			command,
+		} => {
+			let key = software_key(&software_list, &software);
+			model.modify_prefs(|prefs| {
+				prefs.software_machines.insert(key, machine_name);
+			});
+			handle_command(model, *command);
+		}
+		AppCommand::RunMameConfigure {
+			machine_name,
+			initial_loads,
+		} => {
+			// the devices and images dialog needs a running machine, so open it once we start
+			model.configure_on_start.set(true);
+			run_mame_with_confirmation(model, machine_name, initial_loads, run_mame);
+		}
+		AppCommand::SessionSummaryDismiss => {
+			set_session_summary(model, None);
+		}
+		AppCommand::PlaybackInput { machine_name, path } => {
+			let is_known = model
+				.state
+				.borrow()
+				.info_db
+				.as_ref()
+				.is_some_and(|info_db| info_db.machines().find(&machine_name).is_some());
+			if is_known {
+				let run = move |model: &Rc<AppModel>, machine_name: &str, initial_loads: &[(Arc<str>, Arc<str>)]| {
+					run_mame_with_input(model, machine_name, initial_loads, Some(&path));
+				};
+				run_mame_with_confirmation(model, machine_name, Vec::new(), run);
+			} else {
+				let message = tr_args(
+					"This input file was recorded with \"{machine}\", which is not available",
+					&[("machine", &machine_name)],
+				);
+				handle_command(model, AppCommand::ErrorMessageBox(message));
+			}
+		}
+		AppCommand::RunMameDetached {
+			machine_name,
+			initial_loads,
+		} => {
+			run_mame_with_confirmation(model, machine_name, initial_loads, run_mame_detached);
+		}
+		AppCommand::NetplayDialog { machine_name } => {
+			let machine_desc = model
+				.state
+				.borrow()
+				.info_db
+				.as_ref()
+				.and_then(|info_db| info_db.machines().find(&machine_name))
+				.map(|machine| machine.description().to_string())
+				.unwrap_or_else(|| machine_name.clone());
+			let presets = model.preferences.borrow().netplay_presets.clone();
+			let parent = model.app_window().as_weak();
+			let model_clone = model.clone();
+			let fut = async move {
+				if let Some(netplay) = dialog_netplay(parent, &machine_desc, presets).await {
+					handle_command(&model_clone, AppCommand::RunMameNetplay { machine_name, netplay });
+				}
+			};
+			spawn_local(fut).unwrap();
+		}
+		AppCommand::RunMameNetplay { machine_name, netplay } => {
+			model.modify_prefs(|prefs| prefs.add_netplay_preset(netplay.clone()));
+			let run = move |model: &Rc<AppModel>, machine_name: &str, initial_loads: &[(Arc<str>, Arc<str>)]| {
+				// the link needs to be specified before the machine starts
+				model.mame_controller.issue_command(MameCommand::CommLink(&netplay));
+				run_mame(model, machine_name, initial_loads);
+			};
+			run_mame_with_confirmation(model, machine_name, Vec::new(), run);
+		}
+		AppCommand::ReferenceSnapshotDialog { machine_name } => {
+			if let Some(path) = reference_snapshot_file_dialog(&model.app_window()) {
+				let path = Some(path);
+				handle_command(model, AppCommand::ReferenceSnapshotSet { machine_name, path });
+			}
+		}
+		AppCommand::ReferenceSnapshotUseLatest { machine_name } => {
+			let snapshot_paths = snapshot_paths(&model.preferences.borrow());
+			if let Some(path) = find_latest_snapshot(&snapshot_paths, &machine_name) {
+				let path = path.into_os_string().into_string().ok();
+				handle_command(model, AppCommand::ReferenceSnapshotSet { machine_name, path });
+			} else {
+				let message = tr_args(
+					"No snapshots of \"{machine}\" were found",
+					&[("machine", &machine_name)],
+				);
+				handle_command(model, AppCommand::ErrorMessageBox(message));
+			}
+		}
+		AppCommand::ReferenceSnapshotSet { machine_name, path } => model.modify_prefs(|prefs| {
+			if let Some(path) = path {
+				prefs.reference_snapshots.insert(machine_name, path);
+			} else {
+				prefs.reference_snapshots.remove(&machine_name);
+			}
+		}),
+		AppCommand::ReferenceSnapshotCompare { machine_name } => {
+			compare_reference_snapshot(model, &machine_name);
+		}
+		AppCommand::MachineDetailsDialog { machine_name } => {
+			let Some(info_db) = model.state.borrow().info_db.clone() else {
+				return;
+			};
+			let Some(machine) = info_db.machines().find(&machine_name) else {
+				return;
+			};
+			let machine_description = machine.description().to_string();
+			let software_list_paths = model.preferences.borrow().paths.software_lists.clone();
+			let mut dispenser = SoftwareListDispenser::new(&info_db, &software_list_paths);
+			let software_count = machine_software_count(machine, &mut dispenser);
+			let details_text = machine_details_text(machine, software_count);
+			let mame_executable = model.preferences.borrow().paths.mame_executable.clone();
+			let roms_text = async move {
+				let Some(mame_executable) = mame_executable else {
+					return "MAME is not configured\n".to_string();
+				};
+				let result = spawn_blocking(move || list_roms(&mame_executable, &machine_name)).await;
+				let text = match result.unwrap_or_else(|e| Err(e.into())) {
+					Ok(text) => text,
+					Err(e) => format!("{e}\n"),
+				};
+				format!("\nROMs:\n{text}")
+			};
+			let parent = model.app_window().as_weak();
+			let fut = async move {
+				dialog_details(parent, &machine_description, details_text, roms_text).await;
+			};
+			spawn_local(fut).unwrap();
+		}
+		AppCommand::SoftwareDetailsDialog {
+			software_list,
+			software,
+		} => {
+			let Some(info_db) = model.state.borrow().info_db.clone() else {
+				return;
+			};
+			let software_list_paths = model.preferences.borrow().paths.software_lists.clone();
+			let mut dispenser = SoftwareListDispenser::new(&info_db, &software_list_paths);
+			let Ok((_, software_list)) = dispenser.get(&software_list) else {
+				return;
+			};
+			let Some(software) = software_list.software.iter().find(|x| x.name.as_ref() == software) else {
+				return;
+			};
+			let description = software.description.to_string();
+			let details_text = software_details_text(&info_db, &software_list, software);
+			let parent = model.app_window().as_weak();
+			let fut = async move {
+				dialog_details(parent, &description, details_text, ready(String::new())).await;
+			};
+			spawn_local(fut).unwrap();
+		}
+		AppCommand::SoftwarePartsDialog {
+			machine_name,
+			software_list,
+			software,
+		} => {
+			let Some(info_db) = model.state.borrow().info_db.clone() else {
+				return;
+			};
+			let Some(machine) = info_db.machines().find(&machine_name) else {
+				return;
+			};
+			let software_list_paths = model.preferences.borrow().paths.software_lists.clone();
+			let mut dispenser = SoftwareListDispenser::new(&info_db, &software_list_paths);
+			let Ok((_, software_list)) = dispenser.get(&software_list) else {
+				return;
+			};
+			let Some(software) = software_list.software.iter().find(|x| x.name.as_ref() == software) else {
+				return;
+			};
+			let software = software.clone();
+
+			// each device gets a choice of "(none)" or any of the parts it can load
+			let choices = software_part_choices(machine, &software);
+			let assignments = default_part_assignments(&choices, software.parts.len());
+			let rows = choices
+				.iter()
+				.zip(&assignments)
+				.map(|((tag, part_indexes), assignment)| {
+					let part_names = part_indexes.iter().map(|&index| software.parts[index].display_name());
+					let part_names = once("(none)".to_string()).chain(part_names).collect();
+					let selected = assignment
+						.and_then(|assignment| part_indexes.iter().position(|&index| index == assignment))
+						.map_or(0, |position| position + 1);
+					(tag.to_string(), part_names, selected)
+				})
+				.collect::<Vec<_>>();
+
+			let parent = model.app_window().as_weak();
+			let model_clone = model.clone();
+			let fut = async move {
+				let Some(selections) = dialog_software_parts(parent, &software.description, rows).await else {
+					return;
+				};
+				let assignments = choices
+					.iter()
+					.zip(selections)
+					.map(|((_, part_indexes), selection)| part_indexes.get(selection.checked_sub(1)?).copied())
+					.collect::<Vec<_>>();
+				let initial_loads = software_part_loads(&software, &choices, &assignments);
+				let command = if software.supported == SoftwareSupport::Yes {
+					AppCommand::RunMame {
+						machine_name,
+						initial_loads,
+					}
+				} else {
+					AppCommand::RunMameUnsupportedSoftware {
+						machine_name,
+						initial_loads,
+						software_description: software.description.to_string(),
+						supported: software.supported,
+					}
+				};
+				handle_command(&model_clone, command);
+			};
+			spawn_local(fut).unwrap();
+		}
+		AppCommand::MachineCaptureDirectoryDialog { machine_name } => {
+			let directory = model
+				.preferences
+				.borrow()
+				.machine_capture_directories
+				.get(&machine_name)
+				.cloned();
+			capture_directory_dialog(model, machine_name.clone(), directory, |directory| {
+				AppCommand::MachineCaptureDirectorySet {
+					machine_name,
+					directory,
+				}
+			});
+		}
+		AppCommand::MachineCaptureDirectorySet {
+			machine_name,
+			directory,
+		} => model.modify_prefs(|prefs| {
+			if let Some(directory) = directory {
+				prefs.machine_capture_directories.insert(machine_name, directory);
+			} else {
+				prefs.machine_capture_directories.remove(&machine_name);
+			}
+		}),
+		AppCommand::FolderCaptureDirectoryDialog { folder_name } => {
+			let directory = model
+				.preferences
+				.borrow()
+				.folder_capture_directories
+				.get(&folder_name)
+				.cloned();
+			capture_directory_dialog(model, folder_name.clone(), directory, |directory| {
+				AppCommand::FolderCaptureDirectorySet { folder_name, directory }
+			});
+		}
+		AppCommand::FolderCaptureDirectorySet { folder_name, directory } => model.modify_prefs(|prefs| {
+			if let Some(directory) = directory {
+				prefs.folder_capture_directories.insert(folder_name, directory);
+			} else {
+				prefs.folder_capture_directories.remove(&folder_name);
+			}
+		}),
+		AppCommand::FolderFamilyBiosSet {
+			folder_name,
+			parent_name,
+			bios,
+		} => {
+			let assignment = {
+				let state = model.state.borrow();
+				let Some(info_db) = state.info_db.as_ref() else {
+					return;
+				};
+				let prefs = model.preferences.borrow();
+				let items = prefs.collections.iter().find_map(|col| match col.as_ref() {
+					PrefsCollection::Folder { name, items, .. } if *name == folder_name => Some(items.as_slice()),
+					_ => None,
+				});
+				family_bios_assignment(info_db, items.unwrap_or_default(), &parent_name, bios.as_deref())
+			};
+			model.modify_prefs(|prefs| {
+				for machine_name in assignment.compatible {
+					if let Some(bios) = &bios {
+						prefs.machine_bios_sets.insert(machine_name, bios.clone());
+					} else {
+						prefs.machine_bios_sets.remove(&machine_name);
+					}
+				}
+			});
+			if !assignment.incompatible.is_empty() {
+				let machine_names = assignment.incompatible.join(", ");
+				let message = tr_args(
+					"These machines do not offer this BIOS and were left unchanged: {machines}",
+					&[("machines", &machine_names)],
+				);
+				handle_command(model, AppCommand::ErrorMessageBox(message));
+			}
+		}
+		AppCommand::SessionSwitch { session_id } => {
+			model.detached_sessions.set_active(session_id);
+			update_ui_for_sessions(model);
+			update_menus(model);
+		}
+		AppCommand::Browse(collection) => {
+			let collection = Rc::new(collection);
+			model.modify_prefs(|prefs| {
+				prefs.history_push(collection);
+			});
+		}
+		AppCommand::ApplyMachineRenames(renames) => model.modify_prefs(|prefs| {
+			apply_machine_renames(prefs, &renames);
+		}),
+		AppCommand::HistoryAdvance(delta) => {
+			model.modify_prefs(|prefs| prefs.history_advance(delta));
+		}
+		AppCommand::SearchText(search) => {
+			model.modify_prefs(|prefs| {
+				// modify the search text
+				let current_entry = prefs.current_history_entry_mut();
+				current_entry.sort_suppressed = !search.is_empty();
+				current_entry.search = search;
+			});
+		}
+		AppCommand::ItemsSort(column_index, order) => {
+			model.modify_prefs(|prefs| {
+				for (index, column) in prefs.items_columns.iter_mut().enumerate() {
+					column.sort = (index == column_index).then_some(order);
+				}
+				prefs.current_history_entry_mut().sort_suppressed = false;
+			});
+		}
+		AppCommand::ItemsSelectedChanged => {
+			let selection = model.with_items_table_model(|x| x.current_selection());
+			model.modify_prefs(|prefs| {
+				prefs.current_history_entry_mut().selection = selection;
+			});
+		}
+		AppCommand::ItemsAddFilter(filter) => {
+			model.modify_prefs(|prefs| {
+				let current_entry = prefs.current_history_entry_mut();
+				if !current_entry.filters.contains(&filter) {
+					current_entry.filters.push(filter);
+				}
+			});
+		}
+		AppCommand::ItemsRemoveFilter(index) => {
+			model.modify_prefs(|prefs| {
+				let current_entry = prefs.current_history_entry_mut();
+				if index < current_entry.filters.len() {
+					current_entry.filters.remove(index);
+				}
+			});
+		}
+		AppCommand::ItemsToggleCloneGroup { machine_name } => {
+			model.with_items_table_model(|x| x.toggle_clone_group(&machine_name));
+		}
+		AppCommand::ItemsSurpriseMe { run } => {
+			let Some((row, machine_name)) = model.with_items_table_model(|x| x.random_runnable_machine()) else {
+				return;
+			};
+			let has_mame_initialized = model
+				.state
+				.borrow()
+				.status()
+				.is_some_and(|s| s.has_initialized && s.running.is_none());
+
+			if run && has_mame_initialized {
+				let command = AppCommand::RunMame {
+					machine_name,
+					initial_loads: vec![],
+				};
+				handle_command(model, command);
+			} else {
+				model.app_window().invoke_items_view_select(row.try_into().unwrap());
+				handle_command(model, AppCommand::ItemsSelectedChanged);
+			}
+		}
+		AppCommand::AddToExistingFolder(folder_index, new_items) => {
+			model.modify_prefs(|prefs| {
+				add_items_to_existing_folder_collection(&mut prefs.collections, folder_index, new_items);
+			});
+		}
+		AppCommand::AddToNewFolder(name, items) => {
+			model.modify_prefs(|prefs| {
+				add_items_to_new_folder_collection(&mut prefs.collections, name, items);
+			});
+		}
+		AppCommand::AddToNewFolderDialog(items) => {
+			let existing_names = get_folder_collection_names(&model.preferences.borrow().collections);
+			let parent = model.app_window().as_weak();
+			let model_clone = model.clone();
+			let fut = async move {
+				if let Some(name) = dialog_new_collection(parent, existing_names).await {
+					let command = AppCommand::AddToNewFolder(name, items);
+					handle_command(&model_clone, command);
+				}
+			};
+			spawn_local(fut).unwrap();
+		}
+		AppCommand::RemoveFromFolder(name, items) => {
+			model.modify_prefs(|prefs| {
+				remove_items_from_folder_collection(&mut prefs.collections, name, &items);
+			});
+		}
+		AppCommand::CreateDesktopShortcut { item, name } => {
+			let (prefs_path, icon) = {
+				let prefs = model.preferences.borrow();
+				(prefs.prefs_path.clone(), find_snapshot(&snapshot_paths(&prefs), &item))
+			};
+			match create_desktop_shortcut(&name, &item, prefs_path.as_deref(), icon.as_deref()) {
+				Ok(path) => {
+					let parent = model.app_window().as_weak();
+					let message = tr_args("Created \"{path}\"", &[("path", &path.display())]);
+					let fut = async move {
+						dialog_message_box::<OkOnly>(parent, "Create Desktop Shortcut", message).await;
+					};
+					spawn_local(fut).unwrap();
+				}
+				Err(e) => handle_command(model, AppCommand::ErrorMessageBox(format!("{e:?}"))),
+			}
+		}
+		AppCommand::MoveCollection { old_index, new_index } => {
+			model.modify_prefs(|prefs| {
+				// detach the collection we're moving
+				let collection = prefs.collections.remove(old_index);
+
+				if let Some(new_index) = new_index {
+					// and readd it
+					prefs.collections.insert(new_index, collection);
+				} else {
+					// the collection is being removed; any folders nested within it move up a level
+					if let PrefsCollection::Folder { name, parent, .. } = collection.as_ref() {
+						reparent_folders(&mut prefs.collections, name, parent.as_deref());
+						prefs.collapsed_folders.remove(name);
+					}
+
+					// we need to remove any entries that might be referenced
+					prefs.purge_stray_entries();
+				}
+			});
+		}
+		AppCommand::DeleteCollectionDialog { index } => {
+			let parent = model.app_window().as_weak();
+			let model_clone = model.clone();
+			let old_name = get_collection_name(&model.preferences.borrow().collections, index).to_string();
+			let fut = async move {
+				let message = tr_args("Are you sure you want to delete \"{name}\"", &[("name", &old_name)]);
+				if dialog_message_box::<OkCancel>(parent, "Delete", message).await == OkCancel::Ok {
+					let command = AppCommand::MoveCollection {
+						old_index: index,
+						new_index: None,
+					};
+					handle_command(&model_clone, command);
+				}
+			};
+			spawn_local(fut).unwrap();
+		}
+		AppCommand::RenameCollectionDialog { index } => {
+			let existing_names = get_folder_collection_names(&model.preferences.borrow().collections);
+			let parent = model.app_window().as_weak();
+			let model_clone = model.clone();
+			let old_name = get_collection_name(&model.preferences.borrow().collections, index).to_string();
+			let fut = async move {
+				if let Some(new_name) = dialog_rename_collection(parent, existing_names, old_name).await {
+					let command = AppCommand::RenameCollection { index, new_name };
+					handle_command(&model_clone, command);
 				}
 			};
 			spawn_local(fut).unwrap();
 		}
 		AppCommand::RenameCollection { index, new_name } => model.modify_prefs(|prefs| {
+			let old_name = get_collection_name(&prefs.collections, index).to_string();
+			if let Some(directory) = prefs.folder_capture_directories.remove(&old_name) {
+				prefs.folder_capture_directories.insert(new_name.clone(), directory);
+			}
+			if prefs.collapsed_folders.remove(&old_name) {
+				prefs.collapsed_folders.insert(new_name.clone());
+			}
 			prefs.rename_folder(index, new_name);
 		}),
+		AppCommand::MoveFolder { index, parent } => model.modify_prefs(|prefs| {
+			set_folder_parent(&mut prefs.collections, index, parent);
+		}),
+		AppCommand::ToggleFolderExpanded { name } => model.modify_prefs(|prefs| {
+			if !prefs.collapsed_folders.remove(&name) {
+				prefs.collapsed_folders.insert(name);
+			}
+		}),
+		AppCommand::ExportCollectionDialog { index } => {
+			let file = CollectionFile::export(&model.preferences.borrow(), index);
+			let path = file
+				.as_ref()
+				.and_then(|file| save_collection_file_dialog(&model.app_window(), &file.name));
+			if let (Some(file), Some(path)) = (file, path) {
+				let result = std::fs::File::create(&path)
+					.map_err(Error::new)
+					.and_then(|writer| file.save(writer));
+				if let Err(e) = result {
+					handle_command(model, AppCommand::ErrorMessageBox(format!("{e:?}")));
+				}
+			}
+		}
+		AppCommand::ImportCollectionDialog => {
+			if let Some(path) = collection_file_dialog(&model.app_window()) {
+				let result = std::fs::File::open(&path)
+					.map_err(Error::new)
+					.and_then(CollectionFile::load);
+				match result {
+					Ok(file) => model.modify_prefs(|prefs| file.import(prefs)),
+					Err(e) => handle_command(model, AppCommand::ErrorMessageBox(format!("{e:?}"))),
+				}
+			}
+		}
 		AppCommand::ChoosePath(path_type) => {
 			choose_path(model, path_type);
 		}
+		AppCommand::FixMameExecutablePermissions => {
+			fix_mame_executable_permissions(model);
+		}
+		AppCommand::InstallWorkerUiPlugin => {
+			install_worker_ui_plugin(model);
+		}
+		AppCommand::AdoptStandardPaths => {
+			adopt_standard_paths(model);
+		}
 		AppCommand::BookmarkCurrentCollection => {
 			let (collection, _) = model.preferences.borrow().current_collection();
 			model.modify_prefs(|prefs| {
@@ -984,12 +2873,14 @@ fn handle_command(model: &Rc<AppModel>, command: AppCommand) {
 		}
 		AppCommand::LoadImage { tag, filename } => {
 			let loads = [(tag.as_str(), filename.as_str())];
-			model.mame_controller.issue_command(MameCommand::LoadImage(&loads));
+			let command = MameCommand::LoadImage(&loads);
+			model.macro_recorder.record(&command);
+			model.mame_controller.issue_command(command);
 		}
 		AppCommand::UnloadImage { tag } => {
-			model
-				.mame_controller
-				.issue_command(MameCommand::UnloadImage(tag.as_str()));
+			let command = MameCommand::UnloadImage(tag.as_str());
+			model.macro_recorder.record(&command);
+			model.mame_controller.issue_command(command);
 		}
 		AppCommand::ConnectToSocketDialog { tag } => {
 			let model_clone = model.clone();
@@ -1008,21 +2899,765 @@ fn handle_command(model: &Rc<AppModel>, command: AppCommand) {
 				.iter()
 				.map(|(slot, opt)| (slot.as_str(), opt.as_deref().unwrap_or_default()))
 				.collect::<Vec<_>>();
-			model.mame_controller.issue_command(MameCommand::ChangeSlots(&changes));
+			let command = MameCommand::ChangeSlots(&changes);
+			model.macro_recorder.record(&command);
+			model.mame_controller.issue_command(command);
 		}
 		AppCommand::InfoDbBuildLoad { force_refresh } => model.infodb_load(force_refresh),
-		AppCommand::InfoDbBuildProgress { machine_description } => {
-			model.update_state(|state| state.infodb_build_progress(machine_description))
+		AppCommand::InfoDbBuildFromListXmlDialog => {
+			if let Some(path) = listxml_file_dialog(&model.app_window()) {
+				handle_command(model, AppCommand::InfoDbBuildFromListXml { path });
+			}
+		}
+		AppCommand::InfoDbBuildFromListXml { path } => model.update_state(|state| {
+			let preferences = model.preferences.borrow();
+			let prefs_path = preferences.prefs_path.as_deref();
+			state.infodb_build_from_listxml(prefs_path, &preferences.paths, path.into())
+		}),
+		AppCommand::InfoDbDiffDialog => {
+			let Some(info_db) = model.state.borrow().info_db.clone() else {
+				return;
+			};
+			let previous_info_db = {
+				let prefs = model.preferences.borrow();
+				let mame_executable_path = prefs.paths.mame_executable.as_deref().unwrap_or_default();
+				InfoDb::load_previous(prefs.prefs_path.as_deref(), mame_executable_path)
+			};
+			let previous_info_db = match previous_info_db {
+				Ok(x) => x,
+				Err(e) => {
+					let message = tr_args(
+						"No previous MAME machine info is available\n\n{error}",
+						&[("error", &format!("{e:?}"))],
+					);
+					handle_command(model, AppCommand::ErrorMessageBox(message));
+					return;
+				}
+			};
+			let diff = InfoDbDiff::new(&previous_info_db, &info_db);
+			let parent = model.app_window().as_weak();
+			let fut = async move {
+				dialog_infodb_diff(parent, &diff).await;
+			};
+			spawn_local(fut).unwrap();
 		}
+		AppCommand::InfoDbBuildProgress(progress) => model.update_state(|state| state.infodb_build_progress(&progress)),
+		AppCommand::InfoDbBuildTogglePause => model.update_state(AppState::infodb_build_toggle_pause),
+		AppCommand::InfoDbBuildStartQueued => model.update_state(AppState::infodb_build_start_queued),
 		AppCommand::InfoDbBuildComplete => model.update_state(AppState::infodb_build_complete),
 		AppCommand::InfoDbBuildCancel => model.update_state(AppState::infodb_build_cancel),
 	};
 }
 
-async fn show_paths_dialog(model: Rc<AppModel>) {
+fn run_mame_with_confirmation(
+	model: &Rc<AppModel>,
+	machine_name: String,
+	initial_loads: Vec<(Arc<str>, Arc<str>)>,
+	run: impl FnOnce(&Rc<AppModel>, &str, &[(Arc<str>, Arc<str>)]) + 'static,
+) {
+	if model.safe_mode {
+		// in safe mode, we confirm before running anything
+		let parent = model.app_window().as_weak();
+		let model_clone = model.clone();
+		let fut = async move {
+			let message = tr_args(
+				"BletchMAME is in safe mode; are you sure you want to run \"{machine}\"?",
+				&[("machine", &machine_name)],
+			);
+			if dialog_message_box::<OkCancel>(parent, "Safe Mode", message).await == OkCancel::Ok {
+				run(&model_clone, &machine_name, &initial_loads);
+			}
+		};
+		spawn_local(fut).unwrap();
+	} else {
+		run(model, &machine_name, &initial_loads);
+	}
+}
+
+fn run_mame(model: &Rc<AppModel>, machine_name: &str, initial_loads: &[(Arc<str>, Arc<str>)]) {
+	run_mame_with_input(model, machine_name, initial_loads, None);
+}
+
+/// starts a machine in the main session, optionally playing back an input file; if we are not playing back we might
+/// be recording
+fn run_mame_with_input(
+	model: &Rc<AppModel>,
+	machine_name: &str,
+	initial_loads: &[(Arc<str>, Arc<str>)],
+	playback_path: Option<&str>,
+) {
+	let last_session = PrefsSession {
+		machine_name: machine_name.to_string(),
+		initial_loads: initial_loads.to_vec(),
+	};
+	let initial_loads = initial_loads
+		.iter()
+		.map(|(dev, arg)| (dev.as_ref(), arg.as_ref()))
+		.collect::<Vec<_>>();
+
+	// input files need to be specified before the machine starts
+	let input_file = if let Some(playback_path) = playback_path {
+		model
+			.mame_controller
+			.issue_command(MameCommand::PlaybackInput(playback_path));
+		Some(playback_path.to_string())
+	} else if model.record_input.get() {
+		let record_path = new_input_file_path(model, machine_name);
+		if let Some(record_path) = &record_path {
+			model
+				.mame_controller
+				.issue_command(MameCommand::RecordInput(record_path));
+		}
+		record_path
+	} else {
+		None
+	};
+	model.modify_prefs(|prefs| {
+		prefs.add_recent_machine(machine_name.to_string());
+		prefs.last_session = Some(last_session);
+		if let Some(input_file) = input_file {
+			prefs.add_recent_input_file(machine_name.to_string(), input_file);
+		}
+	});
+
+	// the BIOS is a core option, so it too needs to be specified before the machine starts
+	let bios = model.preferences.borrow().machine_bios_sets.get(machine_name).cloned();
+	if let Some(bios) = bios {
+		model.mame_controller.issue_command(MameCommand::Bios(&bios));
+	}
+
+	// likewise, MAME only records audio if told to before the machine starts
+	if model.record_audio.get() {
+		if let Some(wav_path) = new_recording_path(model, machine_name, "wav") {
+			model.mame_controller.issue_command(MameCommand::RecordAudio(&wav_path));
+		}
+	}
+
+	let command = MameCommand::Start {
+		machine_name,
+		initial_loads: initial_loads.as_slice(),
+	};
+	model.mame_controller.issue_command(command);
+	model.launch.replace(Some(Launch::new(machine_name)));
+
+	let initial_loads = initial_loads
+		.iter()
+		.map(|(dev, arg)| (Arc::from(*dev), Arc::from(*arg)))
+		.collect();
+	model
+		.session_stats
+		.replace(Some(SessionStats::new(machine_name, initial_loads)));
+	set_session_summary(model, None);
+}
+
+/// begins or ends recording a movie of the active session
+fn toggle_record_movie(model: &AppModel) {
+	let Some(running) = active_session_status(model).and_then(|status| status.running) else {
+		return;
+	};
+	if running.is_recording {
+		issue_command_to_active_session(model, MameCommand::EndRecording);
+		return;
+	}
+
+	// movies go into the capture directory; if there is none we have to ask
+	let movie_format = model.preferences.borrow().recording.movie_format;
+	let extension = movie_format.extension();
+	let path = new_recording_path(model, &running.machine_name, extension).or_else(|| {
+		let directory = capture_directory(&model.preferences.borrow(), &running.machine_name).map(PathBuf::from);
+		movie_file_dialog(&model.app_window(), movie_format, directory.as_deref())
+	});
+	if let Some(path) = path {
+		issue_command_to_active_session(model, MameCommand::BeginRecording(&path, movie_format));
+	}
+}
+
+/// chooses a path in the capture directory for a new movie or audio recording
+fn new_recording_path(model: &AppModel, machine_name: &str, extension: &str) -> Option<String> {
+	let directory = capture_directory(&model.preferences.borrow(), machine_name)?;
+	std::fs::create_dir_all(&directory).ok()?;
+	let timestamp = SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.unwrap_or_default()
+		.as_secs();
+	let path = Path::new(&directory).join(format!("{machine_name}-{timestamp}.{extension}"));
+	path.into_os_string().into_string().ok()
+}
+
+/// saves a screenshot of the active session, either prompting for a filename or naming it automatically
+fn save_screenshot(model: &AppModel) {
+	let Some(machine_name) = active_session_status(model)
+		.and_then(|status| status.running)
+		.map(|running| running.machine_name)
+	else {
+		return;
+	};
+
+	// screenshots go into the capture directory; overrides are taken literally rather than getting per-machine
+	// subdirectories, because they can use "{machine}" for that
+	let prefs = model.preferences.borrow();
+	let directory_override = machine_capture_directory(&prefs, &machine_name);
+	let per_machine_directories = prefs.screenshots.per_machine_directories && directory_override.is_none();
+	let directory = directory_override
+		.or_else(|| snapshot_paths(&prefs).into_iter().next())
+		.map(PathBuf::from);
+	let screenshots = prefs.screenshots.clone();
+	drop(prefs);
+
+	let path = match directory {
+		Some(directory) if !screenshots.ask => {
+			let template = screenshots.template.as_deref().unwrap_or(DEFAULT_SCREENSHOT_TEMPLATE);
+			let path = next_screenshot_path(
+				&directory,
+				template,
+				per_machine_directories,
+				&machine_name,
+				SystemTime::now(),
+			);
+			if let Some(parent) = path.parent() {
+				let _ = std::fs::create_dir_all(parent);
+			}
+			path.into_os_string().into_string().ok()
+		}
+		directory => {
+			let file_name = format!("{machine_name}.png");
+			screenshot_file_dialog(&model.app_window(), directory.as_deref(), Some(&file_name))
+		}
+	};
+	if let Some(path) = path {
+		issue_command_to_active_session(model, MameCommand::SaveSnapshot(&path));
+		if model.detached_sessions.active().is_none() {
+			if let Some(session_stats) = model.session_stats.borrow_mut().as_mut() {
+				session_stats.note_screenshot();
+			}
+		}
+	}
+}
+
+/// compares the latest snapshot of a machine against its reference snapshot, and reports the result
+fn compare_reference_snapshot(model: &Rc<AppModel>, machine_name: &str) {
+	let prefs = model.preferences.borrow();
+	let snapshot_paths = snapshot_paths(&prefs);
+	let reference = prefs.reference_snapshots.get(machine_name).cloned();
+	drop(prefs);
+	let Some(reference) = reference else {
+		return;
+	};
+	let Some(snapshot) = find_latest_snapshot(&snapshot_paths, machine_name) else {
+		let message = tr_args(
+			"No snapshots of \"{machine}\" were found",
+			&[("machine", &machine_name)],
+		);
+		handle_command(model, AppCommand::ErrorMessageBox(message));
+		return;
+	};
+
+	let difference = match compare_snapshot_files(&snapshot, Path::new(&reference)) {
+		Ok(difference) => difference,
+		Err(e) => {
+			handle_command(model, AppCommand::ErrorMessageBox(format!("{e:?}")));
+			return;
+		}
+	};
+	let summary = match difference {
+		SnapshotDifference::SizeMismatch((width, height), (ref_width, ref_height)) => {
+			let args: &[(&str, &dyn Display)] = &[
+				("width", &width),
+				("height", &height),
+				("ref_width", &ref_width),
+				("ref_height", &ref_height),
+			];
+			tr_args(
+				"The snapshot is {width}x{height}, but the reference is {ref_width}x{ref_height}",
+				args,
+			)
+		}
+		SnapshotDifference::Pixels(percent) => tr_args(
+			"{percent}% of pixels differ from the reference",
+			&[("percent", &format!("{percent:.2}"))],
+		),
+	};
+	let verdict = if difference.is_changed() {
+		tr("The output of this machine has CHANGED")
+	} else {
+		tr("The output of this machine is unchanged")
+	};
+	let args: &[(&str, &dyn Display)] = &[
+		("verdict", &verdict),
+		("summary", &summary),
+		("snapshot", &snapshot.display()),
+		("reference", &reference),
+	];
+	let message = tr_args(
+		"{verdict}\n\n{summary}\n\nSnapshot: {snapshot}\nReference: {reference}",
+		args,
+	);
+
+	let parent = model.app_window().as_weak();
+	let fut = async move {
+		dialog_message_box::<OkOnly>(parent, "Compare Snapshot", message).await;
+	};
+	spawn_local(fut).unwrap();
+}
+
+/// the directory in which we record input files
+fn input_files_directory(model: &AppModel) -> Option<PathBuf> {
+	prefs_filename(model.preferences.borrow().prefs_path.as_ref(), Some("inp")).ok()
+}
+
+fn new_input_file_path(model: &AppModel, machine_name: &str) -> Option<String> {
+	let directory = input_files_directory(model)?;
+	std::fs::create_dir_all(&directory).ok()?;
+	let timestamp = SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.unwrap_or_default()
+		.as_secs();
+	let path = directory.join(format!("{machine_name}-{timestamp}.inp"));
+	path.into_os_string().into_string().ok()
+}
+
+/// watches a machine that was just started, so that if it exits immediately we can tell the user why instead of
+/// silently returning to the browser; returns true if an early exit was reported
+fn check_launch(model: &Rc<AppModel>, output: Option<&str>, session_ended: bool) -> bool {
+	let mut launch = model.launch.borrow_mut();
+	let Some(current_launch) = launch.as_mut() else {
+		return false;
+	};
+
+	// is the machine still running?
+	let running = model.state.borrow().status().and_then(|status| status.running.clone());
+	if !session_ended {
+		match running {
+			Some(running) => {
+				current_launch.set_running(running);
+				return false;
+			}
+			None if !current_launch.has_run() => return false,
+			None => {}
+		}
+	}
+
+	// the machine is gone; was it too soon?
+	let current_launch = launch.take().unwrap();
+	drop(launch);
+	if !current_launch.is_early_exit(Instant::now()) {
+		record_launch_outcome(model, current_launch.machine_name(), None);
+		return false;
+	}
+
+	let preflight_problems = {
+		let prefs = model.preferences.borrow();
+		let paths = &prefs.paths;
+		preflight_checks_public(paths.mame_executable.as_deref(), &paths.variables, &paths.plugins)
+	};
+	let causes = current_launch.likely_causes(output, &preflight_problems);
+	let outcome = if let Some(problem) = preflight_problems.first() {
+		PrefsLaunchOutcome::PreflightFailure {
+			reason: problem.to_string(),
+		}
+	} else {
+		PrefsLaunchOutcome::Crashed {
+			reason: causes.first().cloned(),
+		}
+	};
+	record_launch_outcome(model, current_launch.machine_name(), Some(outcome));
+	let machine_description = machine_description(model, current_launch.machine_name());
+	let message = early_exit_message(&machine_description, output, &causes);
+
+	let parent = model.app_window().as_weak();
+	let fut = async move {
+		dialog_message_box::<OkOnly>(parent, "Machine Exited", message).await;
+	};
+	spawn_local(fut).unwrap();
+	true
+}
+
+/// keeps track of the running machine, and summarizes the session once it stops
+fn update_session_stats(model: &Rc<AppModel>, session_ended: bool, early_exit: bool) {
+	let is_running = !session_ended
+		&& model
+			.state
+			.borrow()
+			.status()
+			.is_some_and(|status| status.running.is_some());
+
+	let mut session_stats = model.session_stats.borrow_mut();
+	let Some(current_stats) = session_stats.as_mut() else {
+		return;
+	};
+	if is_running {
+		current_stats.set_running();
+		drop(session_stats);
+		if model.configure_on_start.take() {
+			handle_command(model, AppCommand::FileDevicesAndImages);
+		}
+		return;
+	}
+	if !session_ended && !current_stats.has_run() {
+		return;
+	}
+
+	// the machine has stopped; early exits get their own message
+	let current_stats = session_stats.take().unwrap();
+	drop(session_stats);
+	model.configure_on_start.set(false);
+	if early_exit || !current_stats.has_run() {
+		return;
+	}
+	let state_dir = expand_path("$(MAMEPATH)", &model.preferences.borrow().paths)
+		.map(|mame_path| PathBuf::from(mame_path).join("sta"));
+	let summary = current_stats.finish(Instant::now(), state_dir.as_deref());
+	set_session_summary(model, Some(summary));
+}
+
+/// shows (or hides) the summary of the last session; it is dismissed automatically after a while
+fn set_session_summary(model: &Rc<AppModel>, summary: Option<SessionSummary>) {
+	let summary = summary.map(Rc::new);
+	let text = summary
+		.as_ref()
+		.map(|summary| summary.text(&machine_description(model, &summary.machine_name)))
+		.unwrap_or_default();
+	model.app_window().set_session_summary_text(text.into());
+	model.session_summary.replace(summary.clone());
+
+	if let Some(summary) = summary {
+		let model_weak = Rc::downgrade(model);
+		let fut = async move {
+			tokio::time::sleep(SESSION_SUMMARY_DURATION).await;
+			let Some(model) = model_weak.upgrade() else {
+				return;
+			};
+			let is_current = model
+				.session_summary
+				.borrow()
+				.as_ref()
+				.is_some_and(|x| Rc::ptr_eq(x, &summary));
+			if is_current {
+				set_session_summary(&model, None);
+			}
+		};
+		spawn_local(fut).unwrap();
+	}
+}
+
+/// remembers how the last launch of a machine went; `None` means it succeeded
+fn record_launch_outcome(model: &Rc<AppModel>, machine_name: &str, outcome: Option<PrefsLaunchOutcome>) {
+	model.modify_prefs(|prefs| {
+		if let Some(outcome) = outcome {
+			prefs.launch_outcomes.insert(machine_name.to_string(), outcome);
+		} else {
+			prefs.launch_outcomes.remove(machine_name);
+		}
+	});
+}
+
+fn machine_description(model: &AppModel, machine_name: &str) -> String {
+	model
+		.state
+		.borrow()
+		.info_db
+		.as_ref()
+		.and_then(|info_db| info_db.machines().find(machine_name))
+		.map(|machine| machine.description().to_string())
+		.unwrap_or_else(|| machine_name.to_string())
+}
+
+/// starts a machine in a new MAME process with its own window, running alongside the main session
+fn run_mame_detached(model: &Rc<AppModel>, machine_name: &str, initial_loads: &[(Arc<str>, Arc<str>)]) {
+	let session_id = model.detached_sessions.allocate_id();
+	let controller = MameController::new(model.mame_stderr, model.session_log.clone());
+
+	// events from detached sessions are funneled back into commands, much like the main session
+	let bubble = ThreadLocalBubble::new(model.clone());
+	controller.set_event_callback(move |event| {
+		let bubble = bubble.clone();
+		invoke_from_event_loop(move || {
+			let model = bubble.unwrap();
+			let command = match event {
+				MameEvent::SessionStarted => return,
+				MameEvent::SessionEnded(_) => AppCommand::DetachedSessionEnded { session_id },
+				MameEvent::Error(e) => AppCommand::ErrorMessageBox(format!("{e:?}")),
+				MameEvent::StatusUpdate(update) => AppCommand::DetachedSessionStatusUpdate { session_id, update },
+			};
+			handle_command(&model, command);
+		})
+		.unwrap();
+	});
+
+	// start up MAME, restoring the window size from the last time this machine was run
+	let mame_windowing = model
+		.preferences
+		.borrow()
+		.machine_window_sizes
+		.get(machine_name)
+		.map_or(MameWindowing::Windowed, |&(width, height)| {
+			MameWindowing::WindowedSized(width, height)
+		});
+	controller.reset(Some(&model.preferences.borrow().paths), &mame_windowing);
+	if !controller.has_session() {
+		let message = tr("Unable to start MAME");
+		handle_command(model, AppCommand::ErrorMessageBox(message));
+		return;
+	}
+
+	// and start the machine
+	let initial_loads = initial_loads
+		.iter()
+		.map(|(dev, arg)| (dev.as_ref(), arg.as_ref()))
+		.collect::<Vec<_>>();
+	let bios = model.preferences.borrow().machine_bios_sets.get(machine_name).cloned();
+	if let Some(bios) = bios {
+		controller.issue_command(MameCommand::Bios(&bios));
+	}
+	controller.issue_command(MameCommand::Start {
+		machine_name,
+		initial_loads: initial_loads.as_slice(),
+	});
+
+	let machine_description = machine_description(model, machine_name);
+	let session = DetachedSession {
+		machine_description,
+		controller,
+		status: Status::default(),
+	};
+	model.detached_sessions.insert(session_id, session);
+	update_ui_for_sessions(model);
+	update_menus(model);
+}
+
+fn generate_diagnostic_report(model: &AppModel, path: &str) -> Result<()> {
+	let home_dir = home_dir();
+	let report = {
+		let prefs = model.preferences.borrow();
+		let state = model.state.borrow();
+		let log_lines = model.log_ring.lines();
+		diagnostic_report(
+			&prefs,
+			state.info_db.as_deref(),
+			state.status(),
+			&log_lines,
+			home_dir.as_deref(),
+		)?
+	};
+	let session_log = model.session_log.entries();
+	let status_xml = model.session_log.last_status_xml();
+	let file = std::fs::File::create(path).map_err(|e| Error::new(e).context("Error writing diagnostic report"))?;
+	write_diagnostic_archive(file, &report, &session_log, status_xml.as_deref(), home_dir.as_deref())
+		.map_err(|e| e.context("Error writing diagnostic report"))?;
+	Ok(())
+}
+
+fn remember_machine_window_size(model: &Rc<AppModel>, session_id: SessionId) {
+	let Some(running) = model
+		.detached_sessions
+		.status(session_id)
+		.and_then(|status| status.running)
+	else {
+		return;
+	};
+	let Some(window_size) = running.window_size else {
+		return;
+	};
+	let machine_name = running.machine_name;
+	let prefs = model.preferences.borrow();
+	if prefs.machine_window_sizes.get(&machine_name) != Some(&window_size) {
+		drop(prefs);
+		model.modify_prefs(|prefs| {
+			prefs.machine_window_sizes.insert(machine_name, window_size);
+		});
+	}
+}
+
+fn end_detached_session(model: &AppModel, session_id: SessionId) {
+	if let Some(session) = model.detached_sessions.remove(session_id) {
+		session.controller.reset(None, &MameWindowing::Windowed);
+	}
+	update_ui_for_sessions(model);
+	update_menus(model);
+}
+
+/// the items table does not report double clicks, so we identify them ourselves
+fn items_clicked(model: &Rc<AppModel>, index: usize) {
+	let now = Instant::now();
+	let last_click = model.items_last_click.replace(Some((now, index)));
+	let is_double_click = last_click
+		.is_some_and(|(last_time, last_index)| last_index == index && now - last_time <= DOUBLE_CLICK_DURATION);
+	if is_double_click {
+		model.items_last_click.set(None);
+		items_row_activated(model, index);
+	}
+}
+
+/// an item was double clicked (or Enter was pressed on it); what happens is up to `Preferences::item_action`
+fn items_row_activated(model: &Rc<AppModel>, index: usize) {
+	let item_action = model.preferences.borrow().item_action;
+	let has_mame_initialized = model
+		.state
+		.borrow()
+		.status()
+		.map(|s| s.has_initialized)
+		.unwrap_or_default();
+	let command = model.with_items_table_model(|x| x.action_command(index, item_action, has_mame_initialized));
+	if let Some(command) = command {
+		handle_command(model, command);
+	}
+}
+
+/// follows the pointer after an item or folder is pressed, dropping it onto a folder in the collections view if it is
+/// dragged there (items are added like `AppCommand::AddToExistingFolder`, folders are nested like
+/// `AppCommand::MoveFolder`)
+fn drag_pointer_event(model: &Rc<AppModel>, source: Option<DragSource>, evt: &PointerEvent, position: LogicalPosition) {
+	let app_window = model.app_window();
+	match (evt.kind, model.drag.get()) {
+		(PointerEventKind::Down, _) => {
+			let drag = (SelectionClick::from_pointer_event(evt) == Some(SelectionClick::Replace))
+				.then_some(source)
+				.flatten()
+				.map(|source| Drag {
+					source,
+					origin: position,
+					dragging: false,
+				});
+			model.drag.set(drag);
+		}
+		(PointerEventKind::Move, Some(mut drag)) => {
+			let distance = (position.x - drag.origin.x).hypot(position.y - drag.origin.y);
+			if !drag.dragging && distance >= DRAG_THRESHOLD {
+				let description = match drag.source {
+					DragSource::ItemsRow(row) => model.with_items_table_model(|x| x.row_item(row)).map(|x| x.1),
+					DragSource::Folder(folder_index) => {
+						let prefs = model.preferences.borrow();
+						prefs.collections.get(folder_index).and_then(|x| match x.as_ref() {
+							PrefsCollection::Folder { name, .. } => Some(name.clone()),
+							_ => None,
+						})
+					}
+				};
+				let Some(description) = description else {
+					return;
+				};
+				drag.dragging = true;
+				app_window.set_items_drag_text(description.into());
+				app_window.set_items_drag_active(true);
+			}
+			if drag.dragging {
+				let drop_index = drop_folder_index(model, drag.source, position)
+					.and_then(|x| model.with_collections_view_model(|m| m.row(x)))
+					.and_then(|x| i32::try_from(x).ok())
+					.unwrap_or(-1);
+				app_window.set_items_drag_position(position);
+				app_window.set_collections_drop_index(drop_index);
+			}
+			model.drag.set(Some(drag));
+		}
+		(PointerEventKind::Up | PointerEventKind::Cancel, Some(drag)) => {
+			model.drag.set(None);
+			app_window.set_items_drag_active(false);
+			app_window.set_collections_drop_index(-1);
+			let folder_index = (drag.dragging && evt.kind == PointerEventKind::Up)
+				.then(|| drop_folder_index(model, drag.source, position))
+				.flatten();
+			let Some(folder_index) = folder_index else {
+				return;
+			};
+			let command = match drag.source {
+				DragSource::ItemsRow(row) => model
+					.with_items_table_model(|x| x.row_item(row))
+					.map(|(item, _)| AppCommand::AddToExistingFolder(folder_index, vec![item])),
+				DragSource::Folder(index) => {
+					let prefs = model.preferences.borrow();
+					match prefs.collections[folder_index].as_ref() {
+						PrefsCollection::Folder { name, .. } => Some(AppCommand::MoveFolder {
+							index,
+							parent: Some(name.clone()),
+						}),
+						_ => None,
+					}
+				}
+			};
+			if let Some(command) = command {
+				handle_command(model, command);
+			}
+		}
+		_ => {}
+	}
+}
+
+/// the index of the folder collection under the pointer that `source` can be dropped onto, if any
+fn drop_folder_index(model: &AppModel, source: DragSource, position: LogicalPosition) -> Option<usize> {
+	let row = model.app_window().invoke_collections_row_at(position);
+	let row = usize::try_from(row).ok()?;
+	let folder_index = model.with_collections_view_model(|x| x.folder_index(row))?;
+	match source {
+		DragSource::ItemsRow(_) => Some(folder_index),
+		DragSource::Folder(index) => {
+			let prefs = model.preferences.borrow();
+			let PrefsCollection::Folder { name, .. } = prefs.collections[folder_index].as_ref() else {
+				return None;
+			};
+			folder_move_targets(&prefs.collections, index)
+				.contains(name)
+				.then_some(folder_index)
+		}
+	}
+}
+
+/// issues a command to the active session, which is either a detached session or the main session
+fn issue_command_to_active_session(model: &AppModel, command: MameCommand<'_>) {
+	// commands the running MAME does not understand (e.g. - in a macro recorded against a newer MAME) are dropped
+	if let Some(minimum) = command.minimum_mame_version() {
+		let build = active_session_status(model).and_then(|status| status.build);
+		if !build.is_some_and(|build| build >= minimum) {
+			event!(
+				LOG_COMMANDS,
+				"issue_command_to_active_session(): {command:?} requires MAME {minimum}"
+			);
+			return;
+		}
+	}
+
+	model.macro_recorder.record(&command);
+	match model.detached_sessions.active() {
+		Some(session_id) => model.detached_sessions.issue_command(session_id, command),
+		None => model.mame_controller.issue_command(command),
+	}
+}
+
+/// plays back a frontend macro against the active session, stopping early if the session stops running
+fn play_macro(model: &Rc<AppModel>, mac: Macro) {
+	let model_clone = model.clone();
+	let fut = async move {
+		for step in mac.steps {
+			tokio::time::sleep(step.delay()).await;
+			let is_running = active_session_status(&model_clone).is_some_and(|s| s.running.is_some());
+			if !is_running {
+				break;
+			}
+			step.command
+				.with_mame_command(|command| issue_command_to_active_session(&model_clone, command));
+		}
+		model_clone.macro_playback.take();
+		update_menus(&model_clone);
+	};
+	let playback = spawn_local(fut).unwrap();
+	if let Some(old_playback) = model.macro_playback.replace(Some(playback)) {
+		old_playback.abort();
+	}
+	update_menus(model);
+}
+
+/// the status of the active session, which is either a detached session or the main session
+fn active_session_status(model: &AppModel) -> Option<Status> {
+	match model.detached_sessions.active() {
+		Some(_) => model.detached_sessions.active_status(),
+		None => model.state.borrow().status().cloned(),
+	}
+}
+
+async fn show_paths_dialog(model: Rc<AppModel>, path_type: PathType) {
 	let parent = model.app_window_weak.clone();
 	let paths = model.preferences.borrow().paths.clone();
-	if let Some(new_paths) = dialog_paths(parent, paths).await {
+	if let Some(new_paths) = dialog_paths(parent, paths, path_type).await {
 		model.modify_prefs(|prefs| prefs.paths = new_paths.into());
 	}
 }
@@ -1030,13 +3665,49 @@ async fn show_paths_dialog(model: Rc<AppModel>) {
 fn update_menus(model: &AppModel) {
 	// calculate properties
 	let state = model.state.borrow();
-	let running_status = state
+	let main_status = state
 		.status()
 		.map(Cow::Borrowed)
 		.unwrap_or_else(|| Cow::Owned(Status::default()));
+	let running_status = model
+		.detached_sessions
+		.active_status()
+		.map(Cow::Owned)
+		.unwrap_or_else(|| main_status.clone());
+	let is_detached_active = model.detached_sessions.active().is_some();
 	let has_mame_executable = model.preferences.borrow().paths.mame_executable.is_some();
+	let has_info_db = model.state.borrow().info_db.is_some();
+	let is_grid_view = model.preferences.borrow().items_view_mode == ItemsViewMode::Grid;
+	let grid_columns = grid_columns(&model.preferences.borrow());
+	let clone_display = model.preferences.borrow().clone_display;
+	let item_action = model.preferences.borrow().item_action;
+	let hidden_machine_kinds = model.preferences.borrow().hidden_machine_kinds.clone();
+	let screenshots = model.preferences.borrow().screenshots.clone();
+	let screenshot_template = screenshots.template.as_deref().unwrap_or(DEFAULT_SCREENSHOT_TEMPLATE);
+	let remote_control = model.preferences.borrow().remote_control.clone();
+	let is_syncing = model.preferences.borrow().sync.is_enabled();
+	let can_install_mame = model.preferences.borrow().prefs_path.is_some() && !model.read_only_prefs;
+	let discord_rich_presence = model.preferences.borrow().discord_rich_presence;
+	let mount_first_software_part_only = model.preferences.borrow().mount_first_software_part_only;
+	let attract_mode = model.preferences.borrow().attract_mode.clone();
+	let is_recording_macro = model.macro_recorder.is_recording();
+	let is_playing_macro = model.macro_playback.borrow().is_some();
+	let column_types = model
+		.preferences
+		.borrow()
+		.items_columns
+		.iter()
+		.map(|x| x.column_type)
+		.collect::<Vec<_>>();
 	let is_running = running_status.running.is_some();
+	let is_main_running = main_status.running.is_some();
+	let is_ready = main_status.has_initialized && !is_main_running;
 	let is_paused = running_status.running.as_ref().map(|r| r.is_paused).unwrap_or_default();
+	let is_recording = running_status
+		.running
+		.as_ref()
+		.map(|r| r.is_recording)
+		.unwrap_or_default();
 	let is_throttled = running_status
 		.running
 		.as_ref()
@@ -1054,9 +3725,52 @@ fn update_menus(model: &AppModel) {
 		let command = AppCommand::try_from(id);
 		let (enabled, checked) = match command {
 			Ok(AppCommand::InfoDbBuildLoad { .. }) => (Some(has_mame_executable), None),
+			Ok(AppCommand::InfoDbBuildFromListXmlDialog) => (Some(!is_main_running), None),
+			Ok(AppCommand::InfoDbDiffDialog) => (Some(has_info_db && has_mame_executable), None),
+			Ok(AppCommand::ViewToggleGrid) => (None, Some(is_grid_view)),
+			Ok(AppCommand::ViewGridColumns(x)) => (None, Some(x == grid_columns)),
+			Ok(AppCommand::ViewCloneDisplay(x)) => (None, Some(x == clone_display)),
+			Ok(AppCommand::ViewItemAction(x)) => (None, Some(x == item_action)),
+			Ok(AppCommand::ViewToggleHiddenMachineKind(x)) => (None, Some(hidden_machine_kinds.contains(&x))),
+			Ok(AppCommand::ViewToggleColumn(x)) => (None, Some(column_types.contains(&x))),
 			Ok(AppCommand::FileStop) => (Some(is_running), None),
+			Ok(AppCommand::HelpCheckForMameUpdates) => (Some(can_install_mame), None),
+			Ok(AppCommand::SettingsOrphanedEntriesDialog) => (Some(has_info_db), None),
+			Ok(AppCommand::ItemsSurpriseMe { .. }) => (Some(is_ready), None),
 			Ok(AppCommand::FilePause) => (Some(is_running), Some(is_paused)),
-			Ok(AppCommand::FileDevicesAndImages) => (Some(is_running), None),
+			Ok(AppCommand::FileDevicesAndImages) => (Some(is_running && !is_detached_active), None),
+			Ok(AppCommand::FileSaveScreenshot) => (Some(is_running), None),
+			Ok(AppCommand::FileToggleRecordMovie) => (Some(is_running), Some(is_recording)),
+			Ok(AppCommand::FileToggleRecordAudio) => (None, Some(model.record_audio.get())),
+			Ok(AppCommand::SettingsScreenshotsToggleAsk) => (None, Some(screenshots.ask)),
+			Ok(AppCommand::SettingsScreenshotsTogglePerMachineDirectories) => {
+				(Some(!screenshots.ask), Some(screenshots.per_machine_directories))
+			}
+			Ok(AppCommand::SettingsScreenshotsTemplate(x)) => (Some(!screenshots.ask), Some(x == screenshot_template)),
+			Ok(AppCommand::SettingsSyncNow | AppCommand::SettingsStopSync) => (Some(is_syncing), None),
+			Ok(AppCommand::SettingsRemoteControlToggleEnabled) => (None, Some(remote_control.enabled)),
+			Ok(AppCommand::SettingsRemoteControlToggleAllowLan) => {
+				(Some(remote_control.enabled), Some(remote_control.allow_lan))
+			}
+			Ok(AppCommand::SettingsRemoteControlShowToken) => {
+				(Some(remote_control.allow_lan && remote_control.token.is_some()), None)
+			}
+			Ok(AppCommand::SettingsToggleDiscordRichPresence) => {
+				(Some(DISCORD_APPLICATION_ID.is_some()), Some(discord_rich_presence))
+			}
+			Ok(AppCommand::SettingsToggleMountAllSoftwareParts) => (None, Some(!mount_first_software_part_only)),
+			Ok(AppCommand::SettingsAttractModeToggleEnabled) => (None, Some(attract_mode.enabled)),
+			Ok(AppCommand::SettingsAttractModeToggleAutoLaunch) => {
+				(Some(attract_mode.enabled), Some(attract_mode.auto_launch))
+			}
+			Ok(AppCommand::FileToggleRecordInput) => (None, Some(model.record_input.get())),
+			Ok(AppCommand::FilePlaybackInputDialog) => (Some(is_ready), None),
+			Ok(AppCommand::FileToggleRecordMacro) => (
+				Some((is_running && !is_playing_macro) || is_recording_macro),
+				Some(is_recording_macro),
+			),
+			Ok(AppCommand::FilePlayMacroDialog) => (Some(is_running && !is_recording_macro), None),
+			Ok(AppCommand::FileStopMacro) => (Some(is_playing_macro), None),
 			Ok(AppCommand::FileResetSoft) => (Some(is_running), None),
 			Ok(AppCommand::FileResetHard) => (Some(is_running), None),
 			Ok(AppCommand::OptionsThrottleRate(x)) => (Some(is_running), Some(Some(x) == throttle_rate)),
@@ -1091,9 +3805,24 @@ fn update_ui_for_current_history_item(model: &AppModel) {
 	// update search text bar
 	app_window.set_items_search_text(SharedString::from(&search));
 
+	// update the filter chips under it
+	let filter_chips = prefs
+		.current_history_entry()
+		.filters
+		.iter()
+		.map(|filter| SharedString::from(filter.description()))
+		.collect::<Vec<_>>();
+	app_window.set_items_filter_chips(ModelRc::new(VecModel::from(filter_chips)));
+
 	// identify the currently selected collection
 	let (collection, collection_index) = prefs.current_collection();
-	let collection_index = collection_index.and_then(|x| i32::try_from(x).ok()).unwrap_or(-1);
+	let collection_index = model
+		.with_collections_view_model(|x| match collection_index {
+			Some(collection_index) => x.row(collection_index),
+			None => x.category_index(&collection),
+		})
+		.and_then(|x| i32::try_from(x).ok())
+		.unwrap_or(-1);
 
 	// update current collection text
 	let current_collection_desc = model
@@ -1123,11 +3852,354 @@ fn update_ui_for_current_history_item(model: &AppModel) {
 	model.with_items_table_model(|items_model| {
 		items_model.set_current_collection(collection, search, &prefs.current_history_entry().selection);
 	});
+	update_ui_for_launch_outcome(model, &prefs);
+	update_ui_for_history_text(model, &prefs);
 
 	drop(prefs);
 	update_ui_for_sort_changes(model);
 }
 
+/// shows why the last launch of the selected machine failed, if it did
+fn update_ui_for_launch_outcome(model: &AppModel, prefs: &Preferences) {
+	let note = prefs
+		.current_history_entry()
+		.selection
+		.iter()
+		.find_map(|item| match item {
+			PrefsItem::Machine { machine_name } => prefs.launch_outcomes.get(machine_name),
+			_ => None,
+		})
+		.map(|outcome| outcome.description())
+		.unwrap_or_default();
+	model.app_window().set_items_launch_note(note.into());
+}
+
+/// shows the history.xml notes (and any links within them) for the selected item
+fn update_ui_for_history_text(model: &AppModel, prefs: &Preferences) {
+	let history_xml = model.history_xml.borrow();
+	let text = history_xml
+		.as_deref()
+		.zip(prefs.current_history_entry().selection.first())
+		.and_then(|(history_xml, item)| match item {
+			PrefsItem::Machine { machine_name } => history_xml.machine_text(machine_name),
+			PrefsItem::Software {
+				software_list,
+				software,
+			} => history_xml.software_text(software_list, software),
+		})
+		.unwrap_or_default();
+	let links = find_links(text).into_iter().map(SharedString::from).collect::<Vec<_>>();
+
+	let app_window = model.app_window();
+	app_window.set_history_text(text.into());
+	app_window.set_history_links(ModelRc::new(VecModel::from(links)));
+}
+
+/// (re)loads history.xml from the configured path on a worker thread
+fn load_history_xml(model: &Rc<AppModel>) {
+	let (requested, path) = {
+		let prefs = model.preferences.borrow();
+		let requested = prefs.paths.history.clone();
+		let path = requested.as_deref().and_then(|path| expand_path(path, &prefs.paths));
+		(requested, path)
+	};
+	model.history_xml.replace(None);
+	update_ui_for_history_text(model, &model.preferences.borrow());
+
+	let Some(path) = path else {
+		return;
+	};
+	let model_weak = Rc::downgrade(model);
+	let fut = async move {
+		let history_xml = spawn_blocking(move || HistoryXml::load(path)).await.unwrap();
+		let history_xml = match history_xml {
+			Ok(history_xml) => history_xml,
+			Err(e) => {
+				event!(LOG_PREFS, "load_history_xml(): failed: {e:?}");
+				return;
+			}
+		};
+
+		// ignore this load if the path changed while we were working
+		let model = model_weak
+			.upgrade()
+			.filter(|x| x.preferences.borrow().paths.history == requested);
+		if let Some(model) = model {
+			model.history_xml.replace(Some(Arc::new(history_xml)));
+			update_ui_for_history_text(&model, &model.preferences.borrow());
+		}
+	};
+	spawn_local(fut).unwrap();
+}
+
+/// (re)loads the category file (catver.ini) from the configured path on a worker thread
+fn load_catver(model: &Rc<AppModel>) {
+	load_external_file(model, |paths| &paths.category, Catver::load, set_catver);
+}
+
+/// (re)loads languages.ini from the configured path on a worker thread
+fn load_languages(model: &Rc<AppModel>) {
+	load_external_file(model, |paths| &paths.languages, Languages::load, set_languages);
+}
+
+/// loads a file with supplemental machine information on a worker thread, applying the result unless the
+/// configured path changed while we were working
+fn load_external_file<T>(
+	model: &Rc<AppModel>,
+	path_func: fn(&PrefsPaths) -> &Option<String>,
+	load_func: fn(String) -> Result<T>,
+	apply_func: fn(&Rc<AppModel>, Option<Arc<T>>),
+) where
+	T: Send + Sync + 'static,
+{
+	let (requested, path) = {
+		let prefs = model.preferences.borrow();
+		let requested = path_func(&prefs.paths).clone();
+		let path = requested.as_deref().and_then(|path| expand_path(path, &prefs.paths));
+		(requested, path)
+	};
+	apply_func(model, None);
+
+	let Some(path) = path else {
+		return;
+	};
+	let model_weak = Rc::downgrade(model);
+	let fut = async move {
+		let result = spawn_blocking(move || load_func(path)).await.unwrap();
+		let result = match result {
+			Ok(result) => result,
+			Err(e) => {
+				event!(LOG_PREFS, "load_external_file(): failed: {e:?}");
+				return;
+			}
+		};
+
+		let model = model_weak
+			.upgrade()
+			.filter(|x| *path_func(&x.preferences.borrow().paths) == requested);
+		if let Some(model) = model {
+			apply_func(&model, Some(Arc::new(result)));
+		}
+	};
+	spawn_local(fut).unwrap();
+}
+
+fn set_catver(model: &Rc<AppModel>, catver: Option<Arc<Catver>>) {
+	let genres = catver.as_deref().map(|x| x.genres()).unwrap_or_default();
+	model.with_collections_view_model(|x| x.set_categories(&genres));
+	model.with_items_table_model(|x| x.set_catver(catver));
+}
+
+fn set_languages(model: &Rc<AppModel>, languages: Option<Arc<Languages>>) {
+	model.with_items_table_model(|x| x.set_languages(languages));
+}
+
+/// rebuilds the menu accelerators from the keyboard shortcuts in the preferences
+fn update_menu_accelerators(model: &AppModel) {
+	let shortcuts = effective_shortcuts(&model.preferences.borrow());
+	let bindable_commands = bindable_commands();
+	model.menu_bar.update_accelerators(|id| {
+		let command = AppCommand::try_from(id).ok()?;
+		bindable_commands
+			.iter()
+			.any(|(x, _)| *x == command)
+			.then(|| accelerator_for_command(&shortcuts, &command))
+	});
+}
+
+/// updates the MAME executable switcher to reflect the registered MAME executables
+fn update_ui_for_mame_executables(model: &AppModel) {
+	let prefs = model.preferences.borrow();
+	let mame_executables = prefs.mame_executables();
+	let index = mame_executables
+		.iter()
+		.position(|x| Some(*x) == prefs.paths.mame_executable.as_deref())
+		.unwrap_or_default();
+	let names = mame_executables
+		.iter()
+		.map(|x| SharedString::from(*x))
+		.collect::<Vec<_>>();
+
+	let app_window = model.app_window();
+	app_window.set_mame_executable_names(ModelRc::new(VecModel::from(names)));
+	app_window.set_mame_executable_index(index.try_into().unwrap());
+}
+
+/// updates the session switcher to reflect the detached sessions
+fn update_ui_for_sessions(model: &AppModel) {
+	let entries = model.detached_sessions.entries();
+	let session_names = once(tr("Main Session"))
+		.chain(entries.iter().map(|(_, desc)| desc.clone()))
+		.map(SharedString::from)
+		.collect::<Vec<_>>();
+	let session_index = model
+		.detached_sessions
+		.active()
+		.and_then(|session_id| entries.iter().position(|(id, _)| *id == session_id))
+		.map(|index| index + 1)
+		.unwrap_or_default();
+
+	let app_window = model.app_window();
+	app_window.set_session_names(ModelRc::new(VecModel::from(session_names)));
+	app_window.set_session_index(session_index.try_into().unwrap());
+}
+
+/// sizes the items columns in proportion to the width of the items table
+fn reset_items_column_widths(model: &Rc<AppModel>) {
+	// before the window is shown the table might not be laid out, in which case the window width will have to do
+	let app_window = model.app_window();
+	let window = app_window.window();
+	let window_width = window.size().to_logical(window.scale_factor()).width;
+	let width = [app_window.get_items_view_width(), window_width]
+		.into_iter()
+		.find(|&x| x > 0.0);
+	if let Some(width) = width {
+		model.modify_prefs(|prefs| prefs.reset_items_column_widths(width));
+	}
+}
+
+fn update_ui_for_items_columns(model: &AppModel) {
+	let items_columns = model
+		.preferences
+		.borrow()
+		.items_columns
+		.iter()
+		.map(|column| {
+			let mut table_column = TableColumn::default();
+			table_column.title = tr(column_provider(column.column_type).title).into();
+			table_column.horizontal_stretch = 1.0;
+			table_column.width = column.width;
+			table_column
+		})
+		.collect::<Vec<_>>();
+	let items_columns = VecModel::from(items_columns);
+	let items_columns = Rc::new(items_columns);
+	let items_columns = ModelRc::from(items_columns);
+	model.app_window().set_items_columns(items_columns);
+}
+
+/// updates the alignment of each items column; the items table applies these to the cells
+fn update_ui_for_items_column_alignments(model: &AppModel) {
+	let right_aligned = model
+		.preferences
+		.borrow()
+		.items_columns
+		.iter()
+		.map(|column| column_alignment(column) == ColumnAlignment::Right)
+		.collect::<Vec<_>>();
+	let right_aligned = ModelRc::new(VecModel::from(right_aligned));
+	model.app_window().set_items_columns_right_aligned(right_aligned);
+}
+
+fn update_ui_for_items_view_mode(model: &AppModel) {
+	let app_window = model.app_window();
+	let prefs = model.preferences.borrow();
+	app_window.set_items_grid_visible(prefs.items_view_mode == ItemsViewMode::Grid);
+	app_window.set_items_grid_columns(grid_columns(&prefs).try_into().unwrap());
+	drop(prefs);
+	update_menus(model);
+}
+
+/// fills in the back/forward dropdown; previews of the selection in each history entry are found and decoded on a
+/// worker thread, and filled in as they arrive
+fn update_history_dropdown(model: &AppModel) {
+	let prefs = model.preferences.borrow();
+	let info_db = model.state.borrow().info_db.clone();
+
+	// forward entries (furthest first), followed by back entries (nearest first)
+	let forward = (1..=HISTORY_DROPDOWN_COUNT).rev();
+	let back = (1..=HISTORY_DROPDOWN_COUNT).map(|x| -x);
+	let (entries, items): (Vec<_>, Vec<_>) = forward
+		.chain(back)
+		.filter_map(|delta| {
+			let history_entry = prefs.history_entry_at(delta)?;
+			let text = info_db
+				.as_deref()
+				.map(|info_db| history_entry.collection.description(info_db))
+				.unwrap_or_default();
+			let entry = HistoryDropdownEntry {
+				text: text.as_ref().into(),
+				preview: Image::default(),
+				delta: delta.try_into().unwrap(),
+			};
+			Some((entry, history_entry.selection.first().cloned()))
+		})
+		.unzip();
+	let entries = Rc::new(VecModel::from(entries));
+	model
+		.app_window()
+		.set_history_dropdown_entries(ModelRc::from(entries.clone()));
+
+	let snapshot_paths = snapshot_paths(&prefs);
+	let fut = async move {
+		let previews = spawn_blocking(move || {
+			items
+				.iter()
+				.map(|item| {
+					let path = find_snapshot(&snapshot_paths, item.as_ref()?)?;
+					load_snapshot_pixels(&path)
+				})
+				.collect::<Vec<_>>()
+		})
+		.await
+		.unwrap_or_default();
+		for (index, pixels) in previews.into_iter().enumerate() {
+			if let (Some(pixels), Some(mut entry)) = (pixels, entries.row_data(index)) {
+				entry.preview = Image::from_rgba8(pixels);
+				entries.set_row_data(index, entry);
+			}
+		}
+	};
+	spawn_local(fut).unwrap();
+}
+
+fn grid_columns(prefs: &Preferences) -> u32 {
+	prefs.items_grid_columns.unwrap_or(DEFAULT_GRID_COLUMNS)
+}
+
+/// prompts for the capture directory of a machine or folder, and issues the command `set_command` makes from it
+fn capture_directory_dialog(
+	model: &Rc<AppModel>,
+	subject: String,
+	directory: Option<String>,
+	set_command: impl FnOnce(Option<String>) -> AppCommand + 'static,
+) {
+	let parent = model.app_window().as_weak();
+	let model_clone = model.clone();
+	let fut = async move {
+		if let Some(directory) = dialog_capture_directory(parent, &subject, directory).await {
+			handle_command(&model_clone, set_command(Some(directory)));
+		}
+	};
+	spawn_local(fut).unwrap();
+}
+
+/// the directory that screenshots and recordings of a machine go into; the first snapshot path unless overridden
+fn capture_directory(prefs: &Preferences, machine_name: &str) -> Option<String> {
+	machine_capture_directory(prefs, machine_name).or_else(|| snapshot_paths(prefs).into_iter().next())
+}
+
+/// the capture directory override for a machine (or a folder containing it), if any
+fn machine_capture_directory(prefs: &Preferences, machine_name: &str) -> Option<String> {
+	let directory = capture_directory_override(
+		&prefs.machine_capture_directories,
+		&prefs.folder_capture_directories,
+		&prefs.collections,
+		machine_name,
+		SystemTime::now(),
+	)?;
+	expand_path(&directory, &prefs.paths)
+}
+
+fn snapshot_paths(prefs: &Preferences) -> Vec<String> {
+	prefs
+		.paths
+		.snapshots
+		.iter()
+		.filter_map(|path| expand_path(path, &prefs.paths))
+		.collect()
+}
+
 fn update_ui_for_sort_changes(model: &AppModel) {
 	let app_window = model.app_window();
 	let prefs = model.preferences.borrow();
@@ -1153,7 +4225,12 @@ fn update_items_model_for_columns_and_search(model: &AppModel) {
 	model.with_items_table_model(move |x| {
 		let prefs = model.preferences.borrow();
 		let entry = prefs.current_history_entry();
-		x.set_columns_and_search(&prefs.items_columns, &entry.search, entry.sort_suppressed);
+		x.set_columns_and_search(
+			&prefs.items_columns,
+			&entry.search,
+			&entry.filters,
+			entry.sort_suppressed,
+		);
 	});
 }
 
@@ -1164,6 +4241,9 @@ fn update_prefs(model: &Rc<AppModel>) {
 		let logical_size = physical_size.to_logical(model.app_window().window().scale_factor());
 		prefs.window_size = Some(logical_size.into());
 
+		// update dialog geometry
+		prefs.dialog_geometry.extend(dialog_geometry());
+
 		let items_columns = model.app_window().get_items_columns();
 		for (index, column) in prefs.items_columns.iter_mut().enumerate() {
 			if let Some(data) = items_columns.row_data(index) {
@@ -1176,15 +4256,38 @@ fn update_prefs(model: &Rc<AppModel>) {
 	});
 }
 
+/// synchronizes preferences with the sync directory or WebDAV collection (if any); this is a no-op with read-only preferences
+fn sync_prefs_now(model: &Rc<AppModel>) -> Result<SyncOutcome> {
+	if model.read_only_prefs {
+		return Ok(SyncOutcome::UpToDate);
+	}
+	let (prefs, outcome) = sync_prefs(&model.preferences.borrow())?;
+	event!(LOG_PREFS, "sync_prefs_now(): outcome={outcome:?}");
+	if outcome != SyncOutcome::UpToDate {
+		model.modify_prefs(|x| *x = prefs);
+	}
+	Ok(outcome)
+}
+
 fn update_empty_reason(model: &AppModel, empty_reason: Option<EmptyReason>) {
 	let app_window = model.app_window();
-	let reason_string = empty_reason.map(|x| format!("{x}")).unwrap_or_default().into();
+	let reason_string = empty_reason.map(|x| tr(&format!("{x}"))).unwrap_or_default().into();
 	app_window.set_is_empty_reason(reason_string);
+
+	// when software lists are missing but a `hash/` directory sits alongside MAME, offer to use it
+	let can_fix = matches!(empty_reason, Some(EmptyReason::NoSoftwareLists)) && {
+		let prefs = model.preferences.borrow();
+		detect_standard_paths(&prefs.paths, |path_type, path| path_type.path_exists(path))
+			.iter()
+			.any(|(path_type, _)| *path_type == PathType::SoftwareLists)
+	};
+	let fix_text = can_fix.then(|| tr("Use Standard Paths")).unwrap_or_default();
+	app_window.set_is_empty_fix_text(fix_text.into());
 }
 
 fn choose_path(model: &Rc<AppModel>, path_type: PathType) {
 	// open the file dialog
-	let Some(path) = file_dialog(&model.app_window(), path_type) else {
+	let Some(path) = file_dialog(&model.app_window(), path_type, None) else {
 		return;
 	};
 
@@ -1196,6 +4299,57 @@ fn choose_path(model: &Rc<AppModel>, path_type: PathType) {
 	});
 }
 
+#[cfg(unix)]
+fn fix_mame_executable_permissions(model: &Rc<AppModel>) {
+	let mame_executable = model.preferences.borrow().paths.mame_executable.clone();
+	let Some(mame_executable) = mame_executable else {
+		return;
+	};
+	match make_executable(&mame_executable) {
+		Ok(()) => model.infodb_load(false),
+		Err(e) => handle_command(model, AppCommand::ErrorMessageBox(format!("{e:?}"))),
+	}
+}
+
+#[cfg(not(unix))]
+fn fix_mame_executable_permissions(_model: &Rc<AppModel>) {
+	// there is no notion of an executable bit here; `Message::fix()` does not offer this
+}
+
+fn install_worker_ui_plugin(model: &Rc<AppModel>) {
+	let Some(prefs_path) = model.preferences.borrow().prefs_path.clone() else {
+		let message = tr("Cannot install the worker_ui plugin without a preferences directory");
+		handle_command(model, AppCommand::ErrorMessageBox(message));
+		return;
+	};
+	if let Err(e) = install_worker_ui(&prefs_path) {
+		handle_command(model, AppCommand::ErrorMessageBox(format!("{e:?}")));
+		return;
+	}
+
+	// ensure the managed plugins directory is one of our plugins paths; changing the paths will rerun preflight
+	let plugins_path = managed_plugins_path(&prefs_path).to_string_lossy().into_owned();
+	if model.preferences.borrow().paths.plugins.contains(&plugins_path) {
+		model.infodb_load(false);
+	} else {
+		model.modify_prefs(|prefs| {
+			let mut paths = (*prefs.paths).clone();
+			paths.plugins.push(plugins_path);
+			prefs.paths = paths.into();
+		});
+	}
+}
+
+/// Adopts the standard directories (`hash/`, `roms/` etc) found alongside the MAME executable
+fn adopt_standard_paths(model: &Rc<AppModel>) {
+	model.modify_prefs(|prefs| {
+		let mut paths = (*prefs.paths).clone();
+		let detected = detect_standard_paths(&paths, |path_type, path| path_type.path_exists(path));
+		add_standard_paths(&mut paths, &detected);
+		prefs.paths = paths.into();
+	});
+}
+
 fn software_paths_updated(model: &AppModel) {
 	let software_list_paths = model.preferences.borrow().paths.software_lists.clone();
 	model.with_items_table_model(|x| x.set_software_list_paths(software_list_paths));
@@ -1207,6 +4361,261 @@ fn items_set_sorting(model: &Rc<AppModel>, column: i32, order: SortOrder) {
 	handle_command(model, command);
 }
 
+/// offers to follow machines that MAME appears to have renamed since `old_info_db`
+fn check_machine_renames(model: &Rc<AppModel>, old_info_db: Option<&InfoDb>, new_info_db: &InfoDb) {
+	let renames = find_machine_renames(&model.preferences.borrow(), old_info_db, new_info_db);
+	if renames.is_empty() {
+		return;
+	}
+	event!(LOG_PREFS, "check_machine_renames(): renames={:?}", renames);
+
+	let parent = model.app_window().as_weak();
+	let model_clone = model.clone();
+	let fut = async move {
+		let renames_text = renames.iter().map(|x| x.to_string()).join("\n");
+		let message = tr_args(
+			"MAME appears to have renamed the following machines:\n\n{renames}\n\nUpdate folders and history to use the new names?",
+			&[("renames", &renames_text)],
+		);
+		if dialog_message_box::<OkCancel>(parent, "Renamed Machines", message).await == OkCancel::Ok {
+			handle_command(&model_clone, AppCommand::ApplyMachineRenames(renames));
+		}
+	};
+	spawn_local(fut).unwrap();
+}
+
+/// finds folder items and collections referring to machines or software missing from the InfoDb
+fn orphaned_entries(model: &AppModel) -> Option<Vec<OrphanedEntry>> {
+	let info_db = model.state.borrow().info_db.clone()?;
+	let prefs = model.preferences.borrow();
+	let dispenser = RefCell::new(SoftwareListDispenser::new(&info_db, &prefs.paths.software_lists));
+	let is_known = |item: &PrefsItem| match item {
+		PrefsItem::Machine { machine_name } => info_db.machines().find(machine_name).is_some(),
+		PrefsItem::Software {
+			software_list,
+			software,
+		} => {
+			// software lists that fail to load are a problem with paths, not with the item
+			info_db.software_lists().find(software_list).is_some()
+				&& dispenser
+					.borrow_mut()
+					.get(software_list)
+					.is_none_or(|(_, list)| list.software.iter().any(|x| x.name.as_ref() == software.as_str()))
+		}
+	};
+	Some(find_orphaned_entries(&prefs.collections, is_known))
+}
+
+/// starts or stops the remote control server to match preferences
+fn update_remote_server(model: &Rc<AppModel>, prefs: &Preferences) {
+	// stop any existing server first, so that we can rebind the same port
+	model.remote_server.take();
+
+	let remote_control = &prefs.remote_control;
+	if remote_control.enabled {
+		let port = remote_control.port.unwrap_or(DEFAULT_REMOTE_CONTROL_PORT);
+		let bubble = ThreadLocalBubble::new(model.clone());
+		let callback = move |remote_command| {
+			let bubble = bubble.clone();
+			let (reply_tx, reply_rx) = std::sync::mpsc::channel();
+			invoke_from_event_loop(move || {
+				let model = bubble.unwrap();
+				let reply = remote_command_problem(&model, &remote_command).map_or(Ok(()), Err);
+				if reply.is_ok() {
+					handle_command(&model, AppCommand::RemoteControl(remote_command));
+				}
+				let _ = reply_tx.send(reply);
+			})
+			.unwrap();
+			reply_rx
+				.recv_timeout(REMOTE_REPLY_TIMEOUT)
+				.unwrap_or(Err(STATUS_SERVICE_UNAVAILABLE))
+		};
+		let lan_token = remote_control.token.clone().filter(|_| remote_control.allow_lan);
+		match RemoteServer::start(port, lan_token, callback) {
+			Ok(server) => {
+				model.remote_server.replace(Some(server));
+			}
+			Err(e) => {
+				let args: &[(&str, &dyn Display)] = &[("port", &port), ("error", &e)];
+				let message = tr_args("Could not start remote control on port {port}: {error}", args);
+				handle_command(model, AppCommand::ErrorMessageBox(message));
+			}
+		}
+	}
+}
+
+/// remote requests come from outside, so machines have to be known and save states have to be real files; returns the
+/// HTTP status to refuse the request with
+fn remote_command_problem(model: &AppModel, remote_command: &RemoteCommand) -> Option<&'static str> {
+	let is_valid = match remote_command {
+		RemoteCommand::Run(machine_name) => model
+			.state
+			.borrow()
+			.info_db
+			.as_ref()
+			.is_some_and(|info_db| info_db.machines().find(machine_name).is_some()),
+		RemoteCommand::LoadState(path) => {
+			let path = Path::new(path);
+			path.is_absolute() && path.is_file()
+		}
+		_ => true,
+	};
+	(!is_valid).then_some(STATUS_NOT_FOUND)
+}
+
+/// starts or stops Discord Rich Presence to match preferences
+fn update_discord_presence(model: &AppModel, prefs: &Preferences) {
+	let application_id = DISCORD_APPLICATION_ID.filter(|_| prefs.discord_rich_presence);
+	if application_id.is_some() != model.discord_presence.borrow().is_some() {
+		model
+			.discord_presence
+			.replace(application_id.map(DiscordPresence::start));
+		publish_discord_presence(model);
+	}
+}
+
+/// reports the running machine (if any) to Discord Rich Presence
+fn publish_discord_presence(model: &AppModel) {
+	let mut discord_presence = model.discord_presence.borrow_mut();
+	let Some(discord_presence) = discord_presence.as_mut() else {
+		return;
+	};
+	let state = model.state.borrow();
+	let running = state.status().and_then(|s| s.running.as_ref());
+	let machine_description = running
+		.and_then(|r| state.info_db.as_ref()?.machines().find(&r.machine_name))
+		.map(|m| m.description());
+	discord_presence.update(Option::zip(machine_description, running.map(|r| r.is_paused)));
+}
+
+/// launch requests need the InfoDb (to know machines) and MAME (to run them)
+fn is_ready_to_launch(model: &AppModel) -> bool {
+	let state = model.state.borrow();
+	state.info_db.is_some() && state.status().is_some_and(|s| s.has_initialized)
+}
+
+/// offers recently run machines through the OS (the taskbar jump list on Windows, XDG recent files elsewhere); the
+/// entries launch machines through the running instance (see `instance.rs`)
+fn update_os_recent_items(model: &AppModel, prefs: &Preferences) {
+	let Ok(program) = std::env::current_exe() else {
+		return;
+	};
+	let program = program.to_string_lossy();
+	let info_db = model.state.borrow().info_db.clone();
+	let items = prefs
+		.recent_machines
+		.iter()
+		.map(|machine_name| {
+			let title = info_db
+				.as_ref()
+				.and_then(|info_db| info_db.machines().find(machine_name))
+				.map(|machine| machine.description().to_string())
+				.unwrap_or_else(|| machine_name.clone());
+			let request = LaunchRequest::RunMachine(machine_name.clone());
+			let args = launch_args(&request, prefs.prefs_path.as_deref());
+			RecentItem {
+				title,
+				program: program.to_string(),
+				args,
+			}
+		})
+		.collect::<Vec<_>>();
+	let tasks = prefs
+		.last_session
+		.iter()
+		.map(|_| RecentItem {
+			title: tr("Resume Last Session"),
+			program: program.to_string(),
+			args: launch_args(&LaunchRequest::ResumeLastSession, prefs.prefs_path.as_deref()),
+		})
+		.collect::<Vec<_>>();
+	if let Err(e) = update_recent_items(&items, &tasks) {
+		event!(LOG_PREFS, "update_os_recent_items(): error={e:?}");
+	}
+}
+
+/// records user activity, dismissing attract mode if it is showing
+fn note_activity(model: &AppModel) {
+	if model.attract_mode.note_activity(Instant::now()) {
+		hide_attract_mode(model);
+	}
+}
+
+/// starts, advances or stops attract mode; invoked periodically
+fn update_attract_mode(model: &Rc<AppModel>) {
+	// playing a machine counts as activity
+	let is_running = model.state.borrow().status().is_some_and(|s| s.running.is_some());
+	if is_running {
+		note_activity(model);
+		return;
+	}
+
+	let prefs = model.preferences.borrow().attract_mode.clone();
+	match model.attract_mode.tick(Instant::now(), &prefs) {
+		AttractAction::None => {}
+		AttractAction::Start => {
+			event!(LOG_COMMANDS, "update_attract_mode(): starting attract mode");
+			model.app_window().window().set_fullscreen(true);
+			model.app_window().set_attract_visible(true);
+			show_attract_slide(model);
+		}
+		AttractAction::NextSlide => show_attract_slide(model),
+		AttractAction::Stop => hide_attract_mode(model),
+		AttractAction::Launch => {
+			hide_attract_mode(model);
+			handle_command(model, AppCommand::ItemsSurpriseMe { run: true });
+		}
+	}
+}
+
+/// shows the snapshot of a random item in the current collection; items without snapshots are skipped if possible
+fn show_attract_slide(model: &AppModel) {
+	const ATTEMPTS: usize = 10;
+	let snapshot_paths = snapshot_paths(&model.preferences.borrow());
+	let (description, image) = (0..ATTEMPTS)
+		.filter_map(|_| model.with_items_table_model(|x| x.random_item()))
+		.map(|(item, description)| {
+			let image = find_snapshot(&snapshot_paths, &item).and_then(|path| Image::load_from_path(&path).ok());
+			(description, image)
+		})
+		.find_or_last(|(_, image)| image.is_some())
+		.unwrap_or_default();
+
+	let app_window = model.app_window();
+	app_window.set_attract_caption(description.into());
+	app_window.set_attract_image(image.unwrap_or_default());
+}
+
+fn hide_attract_mode(model: &AppModel) {
+	let app_window = model.app_window();
+	if app_window.get_attract_visible() {
+		app_window.set_attract_visible(false);
+		app_window.set_attract_image(Image::default());
+		app_window.window().set_fullscreen(false);
+	}
+}
+
+/// shows the last snapshot of the active detached session, and asks for another one
+fn update_live_preview(model: &AppModel) {
+	let Some(session_id) = model.detached_sessions.active() else {
+		model.live_preview.reset();
+		model.app_window().set_live_preview(Image::default());
+		return;
+	};
+
+	if let Some(image) = model.live_preview.load_image() {
+		model.app_window().set_live_preview(image);
+	}
+	if model.detached_sessions.is_idle(session_id) {
+		if let Some(path) = model.live_preview.next_path() {
+			model
+				.detached_sessions
+				.issue_command(session_id, MameCommand::SaveSnapshot(&path));
+		}
+	}
+}
+
 async fn ping_callback(model_weak: std::rc::Weak<AppModel>) {
 	// we really should be turning the timer on and off depending on what is running
 	while let Some(model) = model_weak.upgrade() {
@@ -1227,6 +4636,9 @@ async fn ping_callback(model_weak: std::rc::Weak<AppModel>) {
 		if is_running && model.mame_controller.is_queue_empty() {
 			handle_command(&model, AppCommand::MamePing);
 		}
+		model.detached_sessions.ping();
+		update_live_preview(&model);
+		update_attract_mode(&model);
 		drop(model);
 		tokio::time::sleep(Duration::from_secs(1)).await;
 	}
@@ -0,0 +1,138 @@
+//! Attract mode, for arcade cabinets; after a period without user activity, snapshots of random items in the current
+//! collection are shown full screen, and optionally a random machine is eventually run
+use std::cell::Cell;
+use std::time::Duration;
+use std::time::Instant;
+
+use crate::prefs::PrefsAttractMode;
+
+/// How long without activity before attract mode begins
+const IDLE_TIME: Duration = Duration::from_secs(5 * 60);
+
+/// How long each snapshot is shown
+const SLIDE_INTERVAL: Duration = Duration::from_secs(8);
+
+/// How long attract mode shows snapshots before running a random machine (if enabled)
+const LAUNCH_TIME: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttractAction {
+	None,
+	Start,
+	NextSlide,
+	Launch,
+	Stop,
+}
+
+pub struct AttractMode {
+	last_activity: Cell<Instant>,
+	active: Cell<Option<AttractActive>>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct AttractActive {
+	since: Instant,
+	last_slide: Instant,
+}
+
+impl AttractMode {
+	/// Records user activity; returns true if attract mode was showing, and should now be dismissed
+	pub fn note_activity(&self, now: Instant) -> bool {
+		self.last_activity.set(now);
+		self.active.take().is_some()
+	}
+
+	/// Invoked periodically to determine what (if anything) attract mode should do next
+	pub fn tick(&self, now: Instant, prefs: &PrefsAttractMode) -> AttractAction {
+		let Some(active) = self.active.get() else {
+			let is_idle = now.duration_since(self.last_activity.get()) >= IDLE_TIME;
+			if !prefs.enabled || !is_idle {
+				return AttractAction::None;
+			}
+			let active = AttractActive {
+				since: now,
+				last_slide: now,
+			};
+			self.active.set(Some(active));
+			return AttractAction::Start;
+		};
+
+		if !prefs.enabled {
+			self.note_activity(now);
+			AttractAction::Stop
+		} else if prefs.auto_launch && now.duration_since(active.since) >= LAUNCH_TIME {
+			self.note_activity(now);
+			AttractAction::Launch
+		} else if now.duration_since(active.last_slide) >= SLIDE_INTERVAL {
+			let active = AttractActive {
+				last_slide: now,
+				..active
+			};
+			self.active.set(Some(active));
+			AttractAction::NextSlide
+		} else {
+			AttractAction::None
+		}
+	}
+}
+
+impl Default for AttractMode {
+	fn default() -> Self {
+		Self {
+			last_activity: Cell::new(Instant::now()),
+			active: Cell::new(None),
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::time::Duration;
+	use std::time::Instant;
+
+	use test_case::test_case;
+
+	use super::AttractAction;
+	use super::AttractMode;
+	use crate::prefs::PrefsAttractMode;
+
+	#[test_case(0, false, false, &[(60, AttractAction::None), (600, AttractAction::None)])]
+	#[test_case(1, true, false, &[(60, AttractAction::None), (300, AttractAction::Start), (304, AttractAction::None), (308, AttractAction::NextSlide), (1500, AttractAction::NextSlide)])]
+	#[test_case(2, true, true, &[(300, AttractAction::Start), (308, AttractAction::NextSlide), (900, AttractAction::Launch), (901, AttractAction::None)])]
+	pub fn tick(_index: usize, enabled: bool, auto_launch: bool, expected: &[(u64, AttractAction)]) {
+		let attract_mode = AttractMode::default();
+		let start = Instant::now();
+		attract_mode.note_activity(start);
+		let prefs = PrefsAttractMode { enabled, auto_launch };
+
+		let actual = expected
+			.iter()
+			.map(|(secs, _)| {
+				let action = attract_mode.tick(start + Duration::from_secs(*secs), &prefs);
+				(*secs, action)
+			})
+			.collect::<Vec<_>>();
+		assert_eq!(expected, actual.as_slice());
+	}
+
+	#[test]
+	pub fn note_activity() {
+		let attract_mode = AttractMode::default();
+		let start = Instant::now();
+		let prefs = PrefsAttractMode {
+			enabled: true,
+			auto_launch: false,
+		};
+		attract_mode.note_activity(start);
+		assert_eq!(
+			AttractAction::Start,
+			attract_mode.tick(start + Duration::from_secs(300), &prefs)
+		);
+		assert!(attract_mode.note_activity(start + Duration::from_secs(301)));
+		assert!(!attract_mode.note_activity(start + Duration::from_secs(302)));
+		assert_eq!(
+			AttractAction::None,
+			attract_mode.tick(start + Duration::from_secs(303), &prefs)
+		);
+	}
+}
@@ -0,0 +1,396 @@
+//! A stand-in for MAME, for developing BletchMAME without a MAME install
+//!
+//! It answers `-listxml` and speaks enough of the `worker_ui` plugin protocol (see `plugins/worker_ui/init.lua`)
+//! to start, pause, load images into and stop emulations.  To use it, build with `--features fake-mame` and set the
+//! MAME executable path to the resulting `fake-mame` binary.  Preflight still expects the plugins path to contain
+//! `boot.lua` and the `worker_ui` plugin, but neither is read.
+//!
+//! The machines are those in the CoCo test data; set `FAKE_MAME_LISTXML` to the path of a `-listxml` dump to use
+//! another set.
+use std::borrow::Cow;
+use std::io::BufRead;
+use std::io::Write;
+
+use anyhow::Error;
+use anyhow::Result;
+use quick_xml::escape::escape;
+use quick_xml::events::BytesStart;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+
+const LISTXML: &str = include_str!("../info/test_data/listxml_coco.xml");
+const APP_VERSION: &str = "0.273";
+
+fn main() -> Result<()> {
+	let listxml = match std::env::var_os("FAKE_MAME_LISTXML") {
+		Some(path) => Cow::Owned(std::fs::read_to_string(path)?),
+		None => Cow::Borrowed(LISTXML),
+	};
+	let mut stdout = std::io::stdout().lock();
+
+	if std::env::args().any(|arg| arg == "-listxml") {
+		write!(stdout, "{listxml}")?;
+		return Ok(());
+	}
+
+	let mut session = FakeSession::new(&listxml)?;
+	session.startup(&mut stdout)?;
+	stdout.flush()?;
+	for line in std::io::stdin().lock().lines() {
+		let keep_going = session.command(&line?, &mut stdout)?;
+		stdout.flush()?;
+		if !keep_going {
+			break;
+		}
+	}
+	Ok(())
+}
+
+struct FakeSession<'a> {
+	listxml: &'a str,
+	build: String,
+	running: Option<Running>,
+}
+
+struct Running {
+	machine_name: String,
+	description: String,
+	images: Vec<Image>,
+	is_paused: bool,
+	is_throttled: bool,
+	throttle_rate: f32,
+	is_recording: bool,
+	attenuation: i32,
+}
+
+struct Image {
+	tag: String,
+	instance_name: String,
+	filename: Option<String>,
+}
+
+impl<'a> FakeSession<'a> {
+	pub fn new(listxml: &'a str) -> Result<Self> {
+		let build = listxml_build(listxml)?.ok_or_else(|| Error::msg("Cannot find build in listxml"))?;
+		let result = Self {
+			listxml,
+			build,
+			running: None,
+		};
+		Ok(result)
+	}
+
+	pub fn startup(&self, out: &mut impl Write) -> Result<()> {
+		writeln!(out, "@INFO ### Worker UI plugin starting")?;
+		self.write_status(out, "Idle; no emulation running; ready for commands")
+	}
+
+	/// Handles a single command, returning false when it is time to exit
+	pub fn command(&mut self, line: &str, out: &mut impl Write) -> Result<bool> {
+		let args = quoted_string_split(line);
+		let Some(verb) = args.first() else {
+			return Ok(true);
+		};
+		let verb = verb.to_ascii_uppercase();
+		let args = &args[1..];
+
+		match (verb.as_str(), self.running.as_mut()) {
+			("EXIT", _) => {
+				writeln!(out, "@OK ### Exit scheduled")?;
+				return Ok(false);
+			}
+			("PING", _) => self.write_status(out, "Ping... pong...")?,
+			("START", _) => {
+				let Some(machine_name) = args.first() else {
+					writeln!(out, "@ERROR ### START requires a machine name")?;
+					return Ok(true);
+				};
+				let Some((description, devices)) = machine_devices(self.listxml, machine_name)? else {
+					writeln!(out, "@ERROR ### Unknown machine '{machine_name}'")?;
+					return Ok(true);
+				};
+				let images = devices
+					.into_iter()
+					.map(|(tag, instance_name)| Image {
+						tag: format!(":{tag}"),
+						instance_name,
+						filename: None,
+					})
+					.collect();
+				let mut running = Running {
+					machine_name: machine_name.clone(),
+					description,
+					images,
+					is_paused: false,
+					is_throttled: true,
+					throttle_rate: 1.0,
+					is_recording: false,
+					attenuation: 0,
+				};
+				for pair in args[1..].chunks(2) {
+					if let [tag, filename] = pair {
+						if let Some(image) = running.find_image(tag) {
+							image.filename = Some(filename.clone());
+						}
+					}
+				}
+				self.running = Some(running);
+				writeln!(out, "@INFO ### Starting emulation...")?;
+				self.write_status(out, "Emulation reset")?;
+			}
+			("STOP", None) => writeln!(out, "@OK ### No running emulation")?,
+			("STOP", Some(_)) => {
+				self.running = None;
+				writeln!(out, "@INFO ### Stopping emulation")?;
+				writeln!(out, "@INFO ### Session is stopping")?;
+				self.write_status(out, "Idle; no emulation running; ready for commands")?;
+			}
+			(_, None) => writeln!(out, "@ERROR ### No running emulation")?,
+			("PAUSE", Some(running)) => {
+				running.is_paused = true;
+				self.write_status(out, "Paused")?;
+			}
+			("RESUME", Some(running)) => {
+				running.is_paused = false;
+				self.write_status(out, "Resumed")?;
+			}
+			("SOFT_RESET" | "HARD_RESET", Some(_)) => {
+				writeln!(out, "@INFO ### Reset scheduled")?;
+				self.write_status(out, "Emulation reset")?;
+			}
+			("THROTTLED", Some(running)) => {
+				running.is_throttled = args.first().is_some_and(|x| x != "0" && x != "false");
+				self.write_status(out, "Throttled set")?;
+			}
+			("THROTTLE_RATE", Some(running)) => {
+				running.throttle_rate = args.first().and_then(|x| x.parse().ok()).unwrap_or(1.0);
+				self.write_status(out, "Throttle rate set")?;
+			}
+			("SET_ATTENUATION", Some(running)) => {
+				running.attenuation = args.first().and_then(|x| x.parse().ok()).unwrap_or_default();
+				self.write_status(out, "Attenuation set")?;
+			}
+			("BEGIN_RECORDING" | "END_RECORDING", Some(running)) => {
+				running.is_recording = verb == "BEGIN_RECORDING";
+				self.write_status(out, "Recording toggled")?;
+			}
+			("LOAD", Some(running)) => {
+				for pair in args.chunks(2) {
+					let [tag, filename] = pair else {
+						writeln!(out, "@ERROR ### LOAD requires tag/filename pairs")?;
+						return Ok(true);
+					};
+					let Some(image) = running.find_image(tag) else {
+						writeln!(out, "@ERROR ### Cannot find device '{tag}'")?;
+						return Ok(true);
+					};
+					image.filename = Some(filename.clone());
+				}
+				self.write_status(out, "Images loaded")?;
+			}
+			("UNLOAD", Some(running)) => {
+				let Some(image) = args.first().and_then(|tag| running.find_image(tag)) else {
+					writeln!(out, "@ERROR ### Cannot find device")?;
+					return Ok(true);
+				};
+				image.filename = None;
+				self.write_status(out, "Image unloaded")?;
+			}
+			(
+				"INPUT_FILE" | "WAV_FILE" | "COMM_LINK" | "STATE_LOAD" | "STATE_SAVE" | "PASTE" | "CLASSIC_MENU"
+				| "CHANGE_SLOTS" | "SAVE_SNAPSHOT",
+				Some(_),
+			) => writeln!(out, "@OK ### {verb} ignored by fake MAME")?,
+			(_, Some(_)) => writeln!(out, "@ERROR ### Unrecognized command '{verb}'")?,
+		}
+		Ok(true)
+	}
+
+	fn write_status(&self, out: &mut impl Write, message: &str) -> Result<()> {
+		writeln!(out, "@OK STATUS ### {message}")?;
+		let build = escape(&self.build);
+		let Some(running) = &self.running else {
+			writeln!(
+				out,
+				"<status app_name=\"mame\" app_version=\"{APP_VERSION}\" app_build=\"{build}\" romname=\"\"/>"
+			)?;
+			return Ok(());
+		};
+
+		writeln!(
+			out,
+			"<status app_name=\"mame\" app_version=\"{APP_VERSION}\" app_build=\"{build}\""
+		)?;
+		writeln!(
+			out,
+			"\tgamename=\"{}\" romname=\"{}\" softname=\"\" time=\"0.0\" pid=\"{}\"",
+			escape(&running.description),
+			escape(&running.machine_name),
+			std::process::id()
+		)?;
+		writeln!(
+			out,
+			"\tpolling_input_seq=\"false\" natural_keyboard_in_use=\"false\" paused=\"{}\" startup_text=\"\"",
+			running.is_paused
+		)?;
+		writeln!(
+			out,
+			"\tdebugger_present=\"0\" show_profiler=\"false\" has_input_using_mouse=\"false\""
+		)?;
+		writeln!(out, "\thas_mouse_enabled_problem=\"false\">")?;
+		writeln!(
+			out,
+			"\t<video speed_percent=\"1.0\" frameskip=\"0\" effective_frameskip=\"0\" throttled=\"{}\" throttle_rate=\"{}\" is_recording=\"{}\"/>",
+			running.is_throttled,
+			running.throttle_rate,
+			u8::from(running.is_recording)
+		)?;
+		writeln!(out, "\t<sound attenuation=\"{}\"/>", running.attenuation)?;
+		writeln!(out, "\t<images>")?;
+		for image in &running.images {
+			let filename = image.filename.as_deref().map(escape).unwrap_or_default();
+			writeln!(
+				out,
+				"\t\t<image tag=\"{}\" filename=\"{}\">",
+				escape(&image.tag),
+				filename
+			)?;
+			writeln!(
+				out,
+				"\t\t\t<details instance_name=\"{}\" is_readable=\"1\" is_writeable=\"1\" is_creatable=\"1\" must_be_loaded=\"0\"/>",
+				escape(&image.instance_name)
+			)?;
+			writeln!(out, "\t\t</image>")?;
+		}
+		writeln!(out, "\t</images>")?;
+		writeln!(out, "</status>")?;
+		Ok(())
+	}
+}
+
+impl Running {
+	fn find_image(&mut self, tag: &str) -> Option<&mut Image> {
+		let tag = tag.strip_prefix(':').unwrap_or(tag);
+		self.images.iter_mut().find(|image| image.tag[1..] == *tag)
+	}
+}
+
+/// Splits a command line the way `quoted_string_split` in the `worker_ui` plugin does
+fn quoted_string_split(text: &str) -> Vec<String> {
+	let mut result = Vec::new();
+	let mut chars = text.chars().peekable();
+	loop {
+		while chars.next_if(|ch| ch.is_whitespace()).is_some() {}
+		let Some(first) = chars.next() else {
+			break;
+		};
+		let arg = if first == '\'' || first == '"' {
+			chars.by_ref().take_while(|&ch| ch != first).collect()
+		} else {
+			let mut arg = first.to_string();
+			while let Some(ch) = chars.next_if(|ch| !ch.is_whitespace()) {
+				arg.push(ch);
+			}
+			arg
+		};
+		result.push(arg);
+	}
+	result
+}
+
+fn listxml_build(listxml: &str) -> Result<Option<String>> {
+	let mut reader = Reader::from_str(listxml);
+	loop {
+		match reader.read_event()? {
+			Event::Start(evt) | Event::Empty(evt) if evt.name().as_ref() == b"mame" => {
+				return attribute(&evt, "build");
+			}
+			Event::Eof => return Ok(None),
+			_ => {}
+		}
+	}
+}
+
+/// Finds the description and image devices (tag and instance name) of a machine
+fn machine_devices(listxml: &str, machine_name: &str) -> Result<Option<(String, Vec<(String, String)>)>> {
+	let mut reader = Reader::from_str(listxml);
+	let mut in_machine = false;
+	let mut in_description = false;
+	let mut description = String::new();
+	let mut devices = Vec::new();
+	let mut device_tag = None;
+	loop {
+		match reader.read_event()? {
+			Event::Start(evt) if evt.name().as_ref() == b"machine" => {
+				in_machine = attribute(&evt, "name")?.as_deref() == Some(machine_name);
+			}
+			Event::End(evt) if in_machine && evt.name().as_ref() == b"machine" => {
+				return Ok(Some((description, devices)));
+			}
+			Event::Start(evt) if in_machine && evt.name().as_ref() == b"description" => in_description = true,
+			Event::End(evt) if in_machine && evt.name().as_ref() == b"description" => in_description = false,
+			Event::Text(text) if in_description => description.push_str(&text.unescape()?),
+			Event::Start(evt) if in_machine && evt.name().as_ref() == b"device" => {
+				device_tag = attribute(&evt, "tag")?;
+			}
+			Event::Start(evt) | Event::Empty(evt) if in_machine && evt.name().as_ref() == b"instance" => {
+				if let (Some(tag), Some(name)) = (device_tag.take(), attribute(&evt, "name")?) {
+					devices.push((tag, name));
+				}
+			}
+			Event::Eof => return Ok(None),
+			_ => {}
+		}
+	}
+}
+
+fn attribute(evt: &BytesStart<'_>, name: &str) -> Result<Option<String>> {
+	let value = evt
+		.try_get_attribute(name)?
+		.map(|x| x.unescape_value().map(|x| x.into_owned()))
+		.transpose()?;
+	Ok(value)
+}
+
+#[cfg(test)]
+mod test {
+	use test_case::test_case;
+
+	use super::FakeSession;
+	use super::LISTXML;
+
+	#[test_case(0, "", &[])]
+	#[test_case(1, "PING", &["PING"])]
+	#[test_case(2, "  LOAD :ext  'foo bar.ccc' ", &["LOAD", ":ext", "foo bar.ccc"])]
+	#[test_case(3, "PASTE \"it's\"", &["PASTE", "it's"])]
+	pub fn quoted_string_split(_index: usize, text: &str, expected: &[&str]) {
+		let actual = super::quoted_string_split(text);
+		assert_eq!(expected, actual.as_slice());
+	}
+
+	#[test]
+	pub fn machine_devices() {
+		let (description, devices) = super::machine_devices(LISTXML, "coco2b").unwrap().unwrap();
+		assert_eq!("Color Computer 2B", description);
+		assert!(devices.contains(&("ext".to_string(), "cartridge".to_string())));
+	}
+
+	#[test]
+	pub fn session() {
+		let mut session = FakeSession::new(LISTXML).unwrap();
+		let mut out = Vec::new();
+		let commands = ["START coco2b ext foo.ccc", "PAUSE", "UNLOAD :ext", "STOP", "EXIT"];
+		let results = commands
+			.into_iter()
+			.map(|command| session.command(command, &mut out).unwrap())
+			.collect::<Vec<_>>();
+		assert_eq!([true, true, true, true, false], results.as_slice());
+
+		let out = String::from_utf8(out).unwrap();
+		assert!(out.contains("romname=\"coco2b\""));
+		assert!(out.contains("paused=\"true\""));
+		assert!(out.contains("filename=\"foo.ccc\""));
+		assert!(out.contains("@OK ### Exit scheduled"));
+		assert!(!out.contains("@ERROR"));
+	}
+}
@@ -0,0 +1,77 @@
+use crate::info::InfoDb;
+use crate::info::Machine;
+use crate::info::View;
+use crate::prefs::PrefsItem;
+
+/// How a BIOS set applies to the machines of a family (a parent and its clones) within a folder
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct FamilyBiosAssignment {
+	/// Machines that offer the BIOS set
+	pub compatible: Vec<String>,
+
+	/// Machines that do not offer the BIOS set, and are left alone
+	pub incompatible: Vec<String>,
+}
+
+/// The name of the parent of the family this machine belongs to
+pub fn family_parent_name<'a>(machine: &Machine<'a>) -> &'a str {
+	machine.clone_of().map(|x| x.name()).unwrap_or_else(|| machine.name())
+}
+
+/// Finds the machines within `items` that belong to the family of `parent_name`, and sorts them by whether they offer
+/// the `bios` BIOS set; restoring the default BIOS (`None`) is compatible with every machine
+pub fn family_bios_assignment(
+	info_db: &InfoDb,
+	items: &[PrefsItem],
+	parent_name: &str,
+	bios: Option<&str>,
+) -> FamilyBiosAssignment {
+	let mut result = FamilyBiosAssignment::default();
+	let machines = items.iter().filter_map(|item| match item {
+		PrefsItem::Machine { machine_name } => info_db.machines().find(machine_name),
+		PrefsItem::Software { .. } => None,
+	});
+	for machine in machines.filter(|machine| family_parent_name(machine) == parent_name) {
+		let is_compatible = bios.is_none_or(|bios| machine.bios_sets().iter().any(|x| x.name() == bios));
+		let list = if is_compatible {
+			&mut result.compatible
+		} else {
+			&mut result.incompatible
+		};
+		list.push(machine.name().to_string());
+	}
+	result
+}
+
+#[cfg(test)]
+mod test {
+	use test_case::test_case;
+
+	use crate::info::InfoDb;
+	use crate::prefs::PrefsItem;
+
+	#[test_case(0, &["coco", "cocoh", "coco2b", "coco3"], "coco", Some("b11e10"), &["coco", "cocoh"], &["coco2b", "coco3"])]
+	#[test_case(1, &["coco", "cocoh", "coco2b", "coco3"], "coco", None, &["coco", "cocoh", "coco2b", "coco3"], &[])]
+	#[test_case(2, &["cocoh", "coco2b"], "coco", Some("nonexistent"), &[], &["cocoh", "coco2b"])]
+	#[test_case(3, &["coco", "cocoh"], "coco3", Some("b11e10"), &[], &[])]
+	fn family_bios_assignment(
+		_index: usize,
+		machine_names: &[&str],
+		parent_name: &str,
+		bios: Option<&str>,
+		expected_compatible: &[&str],
+		expected_incompatible: &[&str],
+	) {
+		let xml = include_str!("info/test_data/listxml_coco.xml");
+		let info_db = InfoDb::from_listxml_output(xml.as_bytes(), |_| false).unwrap().unwrap();
+		let items = machine_names
+			.iter()
+			.map(|machine_name| PrefsItem::Machine {
+				machine_name: machine_name.to_string(),
+			})
+			.collect::<Vec<_>>();
+		let actual = super::family_bios_assignment(&info_db, &items, parent_name, bios);
+		assert_eq!(expected_compatible, actual.compatible.as_slice());
+		assert_eq!(expected_incompatible, actual.incompatible.as_slice());
+	}
+}
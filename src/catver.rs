@@ -0,0 +1,148 @@
+//! Support for category files, which assign a genre (e.g. - "Shooter / Flying Vertical") to each machine
+//!
+//! Two flavors are understood: `catver.ini` (a `[Category]` section of `machine=category` lines) and
+//! folder style `category.ini` files (one section per category, listing machine names)
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::fs::read_to_string;
+use std::path::Path;
+
+use anyhow::Result;
+
+/// Sections of folder style files that hold settings rather than machines
+const FOLDER_SETTINGS_SECTIONS: &[&str] = &["FOLDER_SETTINGS", "ROOT_FOLDER"];
+
+#[derive(Debug, Default)]
+pub struct Catver {
+	categories: Vec<String>,
+	machines: HashMap<String, usize>,
+}
+
+impl Catver {
+	pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+		let text = read_to_string(path)?;
+		Ok(Self::from_text(&text))
+	}
+
+	pub fn from_text(text: &str) -> Self {
+		let mut result = Self::default();
+		for (section, line) in ini_entries(text) {
+			match section {
+				"Category" => {
+					if let Some((machine_name, category)) = line.split_once('=') {
+						result.insert(machine_name.trim(), category.trim());
+					}
+				}
+				"VerAdded" => {}
+				category => result.insert(line, category),
+			}
+		}
+		result
+	}
+
+	fn insert(&mut self, machine_name: &str, category: &str) {
+		if machine_name.is_empty() || category.is_empty() {
+			return;
+		}
+		let index = self.categories.iter().position(|x| x == category).unwrap_or_else(|| {
+			self.categories.push(category.to_string());
+			self.categories.len() - 1
+		});
+		self.machines.insert(machine_name.to_string(), index);
+	}
+
+	pub fn machine_category(&self, machine_name: &str) -> Option<&str> {
+		let index = *self.machines.get(machine_name)?;
+		Some(self.categories[index].as_str())
+	}
+
+	/// The distinct top level genres (e.g. - "Shooter", "Puzzle"), sorted
+	pub fn genres(&self) -> Vec<&str> {
+		self.categories
+			.iter()
+			.map(|x| category_genre(x))
+			.collect::<BTreeSet<_>>()
+			.into_iter()
+			.collect()
+	}
+}
+
+/// The `(section, line)` pairs of an INI file, skipping comments and the settings sections of folder style files
+pub fn ini_entries(text: &str) -> Vec<(&str, &str)> {
+	let mut section = None;
+	text.lines()
+		.map(str::trim)
+		.filter(|line| !line.is_empty() && !line.starts_with(';'))
+		.filter_map(|line| {
+			if let Some(name) = line.strip_prefix('[').and_then(|x| x.strip_suffix(']')) {
+				section = Some(name.trim());
+				None
+			} else {
+				section
+					.filter(|section| !FOLDER_SETTINGS_SECTIONS.contains(section))
+					.map(|section| (section, line))
+			}
+		})
+		.collect()
+}
+
+/// The top level genre of a category; "Shooter / Flying Vertical * Mature *" becomes "Shooter"
+pub fn category_genre(category: &str) -> &str {
+	let genre = category.split_once('/').map(|(genre, _)| genre).unwrap_or(category);
+	let genre = genre.split_once('*').map(|(genre, _)| genre).unwrap_or(genre);
+	genre.trim()
+}
+
+#[cfg(test)]
+mod test {
+	use test_case::test_case;
+
+	use super::Catver;
+
+	const CATVER_INI: &str = ";; catver.ini ;;
+[Category]
+pacman=Maze / Collect
+galaga=Shooter / Flying Vertical
+1942=Shooter / Flying Vertical
+tetris=Puzzle / Drop * Mature *
+
+[VerAdded]
+pacman=0.30
+";
+
+	const CATEGORY_INI: &str = "[FOLDER_SETTINGS]
+RootFolderIcon mame
+SubFolderIcon folder
+
+[ROOT_FOLDER]
+
+[Maze / Collect]
+pacman
+
+[Shooter / Flying Vertical]
+galaga
+1942
+";
+
+	#[test_case(0, CATVER_INI, "pacman", Some("Maze / Collect"))]
+	#[test_case(1, CATVER_INI, "1942", Some("Shooter / Flying Vertical"))]
+	#[test_case(2, CATVER_INI, "tetris", Some("Puzzle / Drop * Mature *"))]
+	#[test_case(3, CATVER_INI, "dkong", None)]
+	#[test_case(4, CATEGORY_INI, "pacman", Some("Maze / Collect"))]
+	#[test_case(5, CATEGORY_INI, "galaga", Some("Shooter / Flying Vertical"))]
+	#[test_case(6, CATEGORY_INI, "RootFolderIcon mame", None)]
+	pub fn machine_category(_index: usize, text: &str, machine_name: &str, expected: Option<&str>) {
+		let catver = Catver::from_text(text);
+		let actual = catver.machine_category(machine_name);
+		assert_eq!(expected, actual);
+	}
+
+	#[test_case(0, CATVER_INI, &["Maze", "Puzzle", "Shooter"])]
+	#[test_case(1, CATEGORY_INI, &["Maze", "Shooter"])]
+	#[test_case(2, "", &[])]
+	pub fn genres(_index: usize, text: &str, expected: &[&str]) {
+		let catver = Catver::from_text(text);
+		let actual = catver.genres();
+		assert_eq!(expected, actual.as_slice());
+	}
+}
@@ -19,7 +19,9 @@ impl ChildWindow {
 	pub fn new(parent: &Window) -> Result<Self> {
 		// access the raw hande for the parent - if we can't access the so-called "handle text", we
 		// can't use the window and we return a bogus child window
-		let raw_window_handle = parent.window_handle().window_handle()?.as_raw();
+		let Ok(raw_window_handle) = parent.window_handle().window_handle().map(|x| x.as_raw()) else {
+			return Ok(Self(None));
+		};
 		if handle_text(&raw_window_handle).is_none() {
 			return Ok(Self(None));
 		}
@@ -0,0 +1,151 @@
+//! Locale aware text comparison, used when sorting and searching items
+//!
+//! With the `icu` feature, sorting follows the collation rules of the UI language (e.g. - accented titles sort
+//! alongside their unaccented counterparts) and searching ignores accents as well as case.  Without it, sorting and
+//! searching simply ignore case.
+use std::cmp::Ordering;
+
+use tracing::event;
+use tracing::Level;
+
+const LOG: Level = Level::DEBUG;
+
+/// Text that orders according to the current collation
+#[derive(Clone, Debug)]
+pub struct CollatedText<S>(pub S);
+
+impl<S: AsRef<str>> Ord for CollatedText<S> {
+	fn cmp(&self, other: &Self) -> Ordering {
+		compare_text(self.0.as_ref(), other.0.as_ref())
+	}
+}
+
+impl<S: AsRef<str>> PartialOrd for CollatedText<S> {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl<S: AsRef<str>> PartialEq for CollatedText<S> {
+	fn eq(&self, other: &Self) -> bool {
+		self.cmp(other) == Ordering::Equal
+	}
+}
+
+impl<S: AsRef<str>> Eq for CollatedText<S> {}
+
+/// Sets the locale used for collation (e.g. - "de"); `None` for the root collation
+pub fn set_collation_language(language: Option<&str>) {
+	event!(LOG, "set_collation_language(): language={:?}", language);
+	imp::set_collation_language(language);
+}
+
+/// Compares two strings according to the current collation
+pub fn compare_text(a: &str, b: &str) -> Ordering {
+	imp::compare_text(a, b)
+}
+
+/// Folds text for searching, so that text differing only by case (and with the `icu` feature, accents) matches
+pub fn fold_text(text: &str) -> String {
+	imp::fold_text(text)
+}
+
+#[cfg(feature = "icu")]
+mod imp {
+	use std::cell::RefCell;
+	use std::cmp::Ordering;
+
+	use icu::casemap::CaseMapper;
+	use icu::collator::Collator;
+	use icu::collator::CollatorOptions;
+	use icu::locid::Locale;
+	use icu::normalizer::DecomposingNormalizer;
+	use icu::properties::maps::general_category;
+	use icu::properties::GeneralCategory;
+	use unicase::UniCase;
+
+	thread_local! {
+		static COLLATOR: RefCell<Option<Collator>> = RefCell::new(new_collator(None));
+	}
+
+	fn new_collator(language: Option<&str>) -> Option<Collator> {
+		let locale = language.and_then(|x| x.parse::<Locale>().ok()).unwrap_or(Locale::UND);
+		Collator::try_new(&(&locale).into(), CollatorOptions::new()).ok()
+	}
+
+	pub fn set_collation_language(language: Option<&str>) {
+		COLLATOR.set(new_collator(language));
+	}
+
+	pub fn compare_text(a: &str, b: &str) -> Ordering {
+		COLLATOR.with_borrow(|collator| match collator {
+			Some(collator) => collator.compare(a, b),
+			None => UniCase::new(a).cmp(&UniCase::new(b)),
+		})
+	}
+
+	pub fn fold_text(text: &str) -> String {
+		let text = CaseMapper::new().fold_string(text);
+		let text = DecomposingNormalizer::new_nfd().normalize(&text);
+		let general_category = general_category();
+		text.chars()
+			.filter(|&ch| general_category.get(ch) != GeneralCategory::NonspacingMark)
+			.collect()
+	}
+}
+
+#[cfg(not(feature = "icu"))]
+mod imp {
+	use std::cmp::Ordering;
+
+	use unicase::UniCase;
+
+	pub fn set_collation_language(_language: Option<&str>) {}
+
+	pub fn compare_text(a: &str, b: &str) -> Ordering {
+		UniCase::new(a).cmp(&UniCase::new(b))
+	}
+
+	pub fn fold_text(text: &str) -> String {
+		text.to_lowercase()
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::cmp::Ordering;
+
+	use test_case::test_case;
+
+	#[test_case(0, "Pac-Man", "Pac-Man", Ordering::Equal)]
+	#[test_case(1, "Asteroids", "Battlezone", Ordering::Less)]
+	#[test_case(2, "asteroids", "Battlezone", Ordering::Less)]
+	#[test_case(3, "zaxxon", "Xevious", Ordering::Greater)]
+	pub fn compare_text(_index: usize, a: &str, b: &str, expected: Ordering) {
+		let actual = super::compare_text(a, b);
+		assert_eq!(expected, actual);
+	}
+
+	#[test_case(0, "Pac-Man", "pac-man")]
+	#[test_case(1, "ZAXXON", "zaxxon")]
+	pub fn fold_text(_index: usize, text: &str, expected: &str) {
+		let actual = super::fold_text(text);
+		assert_eq!(expected, actual);
+	}
+
+	#[cfg(feature = "icu")]
+	#[test_case(0, "Éclair", "eclair")]
+	#[test_case(1, "Straße", "strasse")]
+	pub fn fold_text_icu(_index: usize, text: &str, expected: &str) {
+		let actual = super::fold_text(text);
+		assert_eq!(expected, actual);
+	}
+
+	#[cfg(feature = "icu")]
+	#[test]
+	pub fn accented_ordering() {
+		let mut titles = vec!["Zoo", "Éclair", "Eagle", "Ecstasy"];
+		titles.sort_by(|a, b| super::compare_text(a, b));
+		assert_eq!(vec!["Eagle", "Éclair", "Ecstasy", "Zoo"], titles);
+	}
+}
@@ -0,0 +1,196 @@
+//! Folder collections saved to standalone files, so that curated sets can be shared between installations
+use std::io::Read;
+use std::io::Write;
+
+use anyhow::Error;
+use anyhow::Result;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::collections::add_items_to_existing_folder_collection;
+use crate::collections::add_items_to_new_folder_collection;
+use crate::prefs::software_key;
+use crate::prefs::Preferences;
+use crate::prefs::PrefsCollection;
+use crate::prefs::PrefsItem;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectionFile {
+	pub name: String,
+	pub items: Vec<CollectionFileItem>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectionFileItem {
+	#[serde(flatten)]
+	pub item: PrefsItem,
+
+	/// The BIOS set a machine is started with (see `Preferences::machine_bios_sets`)
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub bios: Option<String>,
+
+	/// The machine software is run on (see `Preferences::software_machines`)
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub run_on: Option<String>,
+}
+
+impl CollectionFile {
+	/// Captures a folder collection, along with the settings in preferences for each of its items
+	pub fn export(prefs: &Preferences, folder_index: usize) -> Option<Self> {
+		let PrefsCollection::Folder { name, items, .. } = prefs.collections.get(folder_index)?.as_ref() else {
+			return None;
+		};
+		let items = items
+			.iter()
+			.map(|item| {
+				let (bios, run_on) = match item {
+					PrefsItem::Machine { machine_name } => (prefs.machine_bios_sets.get(machine_name).cloned(), None),
+					PrefsItem::Software {
+						software_list,
+						software,
+					} => {
+						let key = software_key(software_list, software);
+						(None, prefs.software_machines.get(&key).cloned())
+					}
+				};
+				CollectionFileItem {
+					item: item.clone(),
+					bios,
+					run_on,
+				}
+			})
+			.collect();
+		let name = name.clone();
+		Some(Self { name, items })
+	}
+
+	/// Adds this collection to preferences; items are added to an existing folder with the same name if there is
+	/// one, and settings already in preferences are not overridden
+	pub fn import(self, prefs: &mut Preferences) {
+		let mut items = Vec::with_capacity(self.items.len());
+		for file_item in self.items {
+			match &file_item.item {
+				PrefsItem::Machine { machine_name } => {
+					if let Some(bios) = file_item.bios {
+						prefs.machine_bios_sets.entry(machine_name.clone()).or_insert(bios);
+					}
+				}
+				PrefsItem::Software {
+					software_list,
+					software,
+				} => {
+					if let Some(run_on) = file_item.run_on {
+						let key = software_key(software_list, software);
+						prefs.software_machines.entry(key).or_insert(run_on);
+					}
+				}
+			}
+			items.push(file_item.item);
+		}
+
+		let folder_index = prefs
+			.collections
+			.iter()
+			.position(|x| matches!(x.as_ref(), PrefsCollection::Folder { name, .. } if *name == self.name));
+		if let Some(folder_index) = folder_index {
+			add_items_to_existing_folder_collection(&mut prefs.collections, folder_index, items);
+		} else {
+			add_items_to_new_folder_collection(&mut prefs.collections, self.name, items);
+		}
+	}
+
+	pub fn load(reader: impl Read) -> Result<Self> {
+		serde_json::from_reader(reader).map_err(|e| Error::new(e).context("Error reading collection file"))
+	}
+
+	pub fn save(&self, writer: impl Write) -> Result<()> {
+		serde_json::to_writer_pretty(writer, self).map_err(|e| Error::new(e).context("Error writing collection file"))
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::rc::Rc;
+
+	use crate::prefs::Preferences;
+	use crate::prefs::PrefsCollection;
+	use crate::prefs::PrefsItem;
+
+	use super::CollectionFile;
+
+	fn prefs_with_folder(name: &str, items: Vec<PrefsItem>) -> Preferences {
+		let mut prefs = Preferences::fresh(None);
+		let folder = PrefsCollection::Folder {
+			name: name.into(),
+			parent: None,
+			items,
+		};
+		prefs.collections.push(Rc::new(folder));
+		prefs
+	}
+
+	fn machine(machine_name: &str) -> PrefsItem {
+		let machine_name = machine_name.into();
+		PrefsItem::Machine { machine_name }
+	}
+
+	fn software(software_list: &str, software: &str) -> PrefsItem {
+		let software_list = software_list.into();
+		let software = software.into();
+		PrefsItem::Software {
+			software_list,
+			software,
+		}
+	}
+
+	#[test]
+	fn export_save_load_import() {
+		let items = vec![machine("coco2b"), software("coco_cart", "mega")];
+		let mut prefs = prefs_with_folder("Favorites", items.clone());
+		prefs.machine_bios_sets.insert("coco2b".into(), "orig".into());
+		prefs.software_machines.insert("coco_cart:mega".into(), "coco3".into());
+		let folder_index = prefs.collections.len() - 1;
+		let file = CollectionFile::export(&prefs, folder_index).unwrap();
+
+		let mut buf = Vec::new();
+		file.save(&mut buf).unwrap();
+		let file = CollectionFile::load(buf.as_slice()).unwrap();
+
+		let mut other_prefs = Preferences::fresh(None);
+		let key = "coco_cart:mega".to_string();
+		other_prefs.software_machines.insert(key, "coco2".into());
+		file.import(&mut other_prefs);
+
+		let expected = PrefsCollection::Folder {
+			name: "Favorites".into(),
+			parent: None,
+			items,
+		};
+		assert_eq!(Some(&expected), other_prefs.collections.last().map(|x| x.as_ref()));
+		let bios = other_prefs.machine_bios_sets.get("coco2b");
+		assert_eq!(Some("orig"), bios.map(|x| x.as_str()));
+		let run_on = other_prefs.software_machines.get("coco_cart:mega");
+		assert_eq!(Some("coco2"), run_on.map(|x| x.as_str()));
+	}
+
+	#[test]
+	fn import_into_existing_folder() {
+		let mut prefs = prefs_with_folder("Favorites", vec![machine("coco2b"), machine("coco3")]);
+		let file = CollectionFile::export(&prefs, prefs.collections.len() - 1).unwrap();
+		prefs.collections.pop();
+
+		let mut other_prefs = prefs_with_folder("Favorites", vec![machine("coco3"), machine("mc10")]);
+		let collections_len = other_prefs.collections.len();
+		file.import(&mut other_prefs);
+
+		let expected = PrefsCollection::Folder {
+			name: "Favorites".into(),
+			parent: None,
+			items: vec![machine("coco3"), machine("mc10"), machine("coco2b")],
+		};
+		assert_eq!(collections_len, other_prefs.collections.len());
+		assert_eq!(Some(&expected), other_prefs.collections.last().map(|x| x.as_ref()));
+	}
+}
@@ -1,6 +1,14 @@
 use std::borrow::Cow;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
 use std::rc::Rc;
 
+use itertools::Itertools;
+use serde::Deserialize;
+use serde::Serialize;
+use unicase::UniCase;
+
+use crate::prefs::ram_range_description;
 use crate::prefs::BuiltinCollection;
 use crate::prefs::PrefsCollection;
 use crate::prefs::PrefsItem;
@@ -29,7 +37,8 @@ pub fn add_items_to_new_folder_collection(
 	name: String,
 	items: Vec<PrefsItem>,
 ) {
-	let col = PrefsCollection::Folder { name, items };
+	let parent = None;
+	let col = PrefsCollection::Folder { name, parent, items };
 	let col = Rc::new(col);
 	collections.push(col);
 }
@@ -55,12 +64,12 @@ pub fn remove_items_from_folder_collection(
 	folder_name: String,
 	items: &[PrefsItem],
 ) {
-	let (index, old_items) = collections
+	let (index, parent, old_items) = collections
 		.iter()
 		.enumerate()
 		.filter_map(|(index, col)| {
-			if let PrefsCollection::Folder { name, items } = col.as_ref() {
-				(name == &folder_name).then_some((index, items))
+			if let PrefsCollection::Folder { name, parent, items } = col.as_ref() {
+				(name == &folder_name).then_some((index, parent, items))
 			} else {
 				None
 			}
@@ -76,6 +85,7 @@ pub fn remove_items_from_folder_collection(
 
 	let new_collection = PrefsCollection::Folder {
 		name: folder_name,
+		parent: parent.clone(),
 		items: new_items,
 	};
 	collections[index] = Rc::new(new_collection);
@@ -96,5 +106,452 @@ pub fn get_collection_name(collections: &[Rc<PrefsCollection>], index: usize) ->
 		PrefsCollection::Folder { name, .. } => Cow::Borrowed(name),
 		PrefsCollection::Builtin(x) => Cow::Owned(format!("{}", x)),
 		PrefsCollection::MachineSoftware { machine_name } => Cow::Borrowed(machine_name),
+		PrefsCollection::MachinesWithCpu { cpu } => Cow::Borrowed(cpu),
+		PrefsCollection::MachinesWithRam { minimum, maximum } => Cow::Owned(ram_range_description(*minimum, *maximum)),
+		PrefsCollection::Category { category } => Cow::Borrowed(category),
+	}
+}
+
+/// A single change proposed by `normalize_folder_collections()`
+#[derive(Clone, Debug, PartialEq, Eq, strum_macros::Display)]
+pub enum FolderNormalization {
+	#[strum(to_string = "Rename \"{old_name}\" to \"{new_name}\"")]
+	Rename { old_name: String, new_name: String },
+	#[strum(to_string = "Merge \"{old_name}\" into \"{new_name}\"")]
+	Merge { old_name: String, new_name: String },
+	#[strum(to_string = "Sort folders by name")]
+	Sort,
+}
+
+/// A row in the collections view, which shows folders nested beneath their parents
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CollectionRow {
+	pub index: usize,
+	pub depth: usize,
+	pub has_children: bool,
+}
+
+/// Lays out collections as a tree; folders nested within collapsed folders are left out, and folders whose parent
+/// is missing (or that are nested within themselves) are shown at the top level
+pub fn collection_tree_rows(collections: &[Rc<PrefsCollection>], collapsed: &BTreeSet<String>) -> Vec<CollectionRow> {
+	fn visit(
+		collections: &[Rc<PrefsCollection>],
+		collapsed: &BTreeSet<String>,
+		parents: &[Option<usize>],
+		index: usize,
+		depth: usize,
+		rows: &mut Vec<CollectionRow>,
+	) {
+		let children = (0..parents.len())
+			.filter(|&x| parents[x] == Some(index))
+			.collect::<Vec<_>>();
+		let has_children = !children.is_empty();
+		rows.push(CollectionRow {
+			index,
+			depth,
+			has_children,
+		});
+		let is_collapsed = folder_name(&collections[index]).is_some_and(|x| collapsed.contains(x));
+		if !is_collapsed {
+			for child in children {
+				visit(collections, collapsed, parents, child, depth + 1, rows);
+			}
+		}
+	}
+
+	let parents = folder_parents(collections);
+	let mut rows = Vec::new();
+	for index in (0..collections.len()).filter(|&x| parents[x].is_none()) {
+		visit(collections, collapsed, &parents, index, 0, &mut rows);
+	}
+	rows
+}
+
+/// The folders (by name) that a folder can be moved into; this excludes the folder itself and those nested within it
+pub fn folder_move_targets(collections: &[Rc<PrefsCollection>], folder_index: usize) -> Vec<String> {
+	let parents = folder_parents(collections);
+	let is_within_folder = |mut index: usize| loop {
+		if index == folder_index {
+			break true;
+		}
+		let Some(parent) = parents[index] else {
+			break false;
+		};
+		index = parent;
+	};
+	collections
+		.iter()
+		.enumerate()
+		.filter(|&(index, _)| !is_within_folder(index))
+		.filter_map(|(_, collection)| folder_name(collection).map(str::to_string))
+		.collect()
+}
+
+/// Nests a folder within another folder, or moves it to the top level if `parent` is `None`
+pub fn set_folder_parent(collections: &mut [Rc<PrefsCollection>], folder_index: usize, parent: Option<String>) {
+	let PrefsCollection::Folder { name, items, .. } = collections[folder_index].as_ref() else {
+		panic!("Expected PrefsCollection::Folder");
+	};
+	let name = name.clone();
+	let items = items.clone();
+	collections[folder_index] = Rc::new(PrefsCollection::Folder { name, parent, items });
+}
+
+/// Points folders nested within `old_parent` to `new_parent` (e.g. - when `old_parent` is renamed or deleted)
+pub fn reparent_folders(collections: &mut [Rc<PrefsCollection>], old_parent: &str, new_parent: Option<&str>) {
+	for collection in collections.iter_mut() {
+		if let PrefsCollection::Folder { name, parent, items } = collection.as_ref() {
+			if parent.as_deref() == Some(old_parent) {
+				let name = name.clone();
+				let parent = new_parent.map(str::to_string);
+				let items = items.clone();
+				*collection = Rc::new(PrefsCollection::Folder { name, parent, items });
+			}
+		}
+	}
+}
+
+/// The index of the folder that each collection is nested within, ignoring parents that are missing or circular
+fn folder_parents(collections: &[Rc<PrefsCollection>]) -> Vec<Option<usize>> {
+	let direct_parent = |index: usize| {
+		let PrefsCollection::Folder {
+			parent: Some(parent), ..
+		} = collections[index].as_ref()
+		else {
+			return None;
+		};
+		collections.iter().position(|x| folder_name(x) == Some(parent.as_str()))
+	};
+	(0..collections.len())
+		.map(|index| {
+			let parent = direct_parent(index)?;
+			let mut ancestor = Some(parent);
+			for _ in 0..collections.len() {
+				match ancestor {
+					Some(x) if x == index => return None,
+					Some(x) => ancestor = direct_parent(x),
+					None => break,
+				}
+			}
+			Some(parent)
+		})
+		.collect()
+}
+
+fn folder_name(collection: &PrefsCollection) -> Option<&str> {
+	match collection {
+		PrefsCollection::Folder { name, .. } => Some(name),
+		_ => None,
+	}
+}
+
+/// Normalizes folder names (trimming and collapsing whitespace), merges folders whose names only differ by case
+/// or whitespace, and sorts folders by name; non-folder collections keep their positions
+pub fn normalize_folder_collections(
+	collections: &[Rc<PrefsCollection>],
+) -> (Vec<Rc<PrefsCollection>>, Vec<FolderNormalization>) {
+	let mut changes = Vec::new();
+
+	// group the folders by their normalized names, merging as we go
+	let mut folders: Vec<(String, Option<String>, Vec<PrefsItem>)> = Vec::new();
+	let mut new_names = HashMap::new();
+	for (old_name, parent, items) in collections.iter().filter_map(|col| match col.as_ref() {
+		PrefsCollection::Folder { name, parent, items } => Some((name, parent, items)),
+		_ => None,
+	}) {
+		let new_name = old_name.split_whitespace().join(" ");
+		if let Some((existing_name, _, existing_items)) = folders
+			.iter_mut()
+			.find(|(existing_name, _, _)| UniCase::new(existing_name.as_str()) == UniCase::new(new_name.as_str()))
+		{
+			let change = FolderNormalization::Merge {
+				old_name: old_name.clone(),
+				new_name: existing_name.clone(),
+			};
+			changes.push(change);
+			new_names.insert(old_name.clone(), existing_name.clone());
+			let new_items = items
+				.iter()
+				.filter(|x| !existing_items.contains(x))
+				.cloned()
+				.collect::<Vec<_>>();
+			existing_items.extend(new_items);
+		} else {
+			if &new_name != old_name {
+				let change = FolderNormalization::Rename {
+					old_name: old_name.clone(),
+					new_name: new_name.clone(),
+				};
+				changes.push(change);
+			}
+			new_names.insert(old_name.clone(), new_name.clone());
+			folders.push((new_name, parent.clone(), items.clone()));
+		}
+	}
+
+	// nested folders follow their parents through renames and merges
+	for (name, parent, _) in folders.iter_mut() {
+		let new_parent = parent.take().map(|x| new_names.get(&x).cloned().unwrap_or(x));
+		*parent = new_parent.filter(|x| x != name);
+	}
+
+	// sort the folders
+	let is_sorted = folders.is_sorted_by_key(|(name, _, _)| UniCase::new(name.clone()));
+	if !is_sorted {
+		folders.sort_by_key(|(name, _, _)| UniCase::new(name.clone()));
+		changes.push(FolderNormalization::Sort);
+	}
+
+	// and put the folders back into the slots previously occupied by folders
+	let mut folders = folders.into_iter();
+	let new_collections = collections
+		.iter()
+		.filter_map(|col| {
+			if matches!(col.as_ref(), PrefsCollection::Folder { .. }) {
+				folders
+					.next()
+					.map(|(name, parent, items)| Rc::new(PrefsCollection::Folder { name, parent, items }))
+			} else {
+				Some(col.clone())
+			}
+		})
+		.collect::<Vec<_>>();
+	(new_collections, changes)
+}
+
+/// A folder item or collection referring to a machine or software that MAME no longer knows about
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, strum_macros::Display)]
+#[serde(rename_all = "camelCase")]
+pub enum OrphanedEntry {
+	#[strum(to_string = "{item} in folder \"{folder_name}\"")]
+	FolderItem { folder_name: String, item: PrefsItem },
+	#[strum(to_string = "Software collection for machine \"{machine_name}\"")]
+	MachineSoftware { machine_name: String },
+}
+
+/// Finds folder items and collections for which `is_known` fails; these are hidden from the items view, but
+/// otherwise linger in preferences
+pub fn find_orphaned_entries(
+	collections: &[Rc<PrefsCollection>],
+	is_known: impl Fn(&PrefsItem) -> bool,
+) -> Vec<OrphanedEntry> {
+	collections
+		.iter()
+		.flat_map(|collection| match collection.as_ref() {
+			PrefsCollection::Folder { name, items, .. } => items
+				.iter()
+				.filter(|item| !is_known(item))
+				.map(|item| OrphanedEntry::FolderItem {
+					folder_name: name.clone(),
+					item: item.clone(),
+				})
+				.collect::<Vec<_>>(),
+			PrefsCollection::MachineSoftware { machine_name } => {
+				let item = PrefsItem::Machine {
+					machine_name: machine_name.clone(),
+				};
+				(!is_known(&item))
+					.then(|| OrphanedEntry::MachineSoftware {
+						machine_name: machine_name.clone(),
+					})
+					.into_iter()
+					.collect()
+			}
+			_ => Vec::new(),
+		})
+		.collect()
+}
+
+/// Removes entries previously found by `find_orphaned_entries()`
+pub fn remove_orphaned_entries(
+	collections: &[Rc<PrefsCollection>],
+	orphans: &[OrphanedEntry],
+) -> Vec<Rc<PrefsCollection>> {
+	collections
+		.iter()
+		.filter_map(|collection| match collection.as_ref() {
+			PrefsCollection::Folder { name, parent, items } => {
+				let is_orphan = |item: &PrefsItem| {
+					orphans.iter().any(|orphan| match orphan {
+						OrphanedEntry::FolderItem { folder_name, item: x } => folder_name == name && x == item,
+						_ => false,
+					})
+				};
+				let items = items.iter().filter(|item| !is_orphan(item)).cloned().collect();
+				let name = name.clone();
+				let parent = parent.clone();
+				Some(Rc::new(PrefsCollection::Folder { name, parent, items }))
+			}
+			PrefsCollection::MachineSoftware { machine_name } => {
+				let orphan = OrphanedEntry::MachineSoftware {
+					machine_name: machine_name.clone(),
+				};
+				(!orphans.contains(&orphan)).then(|| collection.clone())
+			}
+			_ => Some(collection.clone()),
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod test {
+	use std::collections::BTreeSet;
+	use std::rc::Rc;
+
+	use test_case::test_case;
+
+	use crate::prefs::BuiltinCollection;
+	use crate::prefs::PrefsCollection;
+	use crate::prefs::PrefsItem;
+
+	use super::CollectionRow;
+	use super::FolderNormalization;
+
+	fn folder(name: &str, machines: &[&str]) -> Rc<PrefsCollection> {
+		let items = machines
+			.iter()
+			.map(|x| PrefsItem::Machine {
+				machine_name: x.to_string(),
+			})
+			.collect();
+		Rc::new(PrefsCollection::Folder {
+			name: name.to_string(),
+			parent: None,
+			items,
+		})
+	}
+
+	/// A builtin collection followed by folders, each with an optional parent
+	fn nested_folders(folders: &[(&str, Option<&str>)]) -> Vec<Rc<PrefsCollection>> {
+		let folders = folders.iter().map(|(name, parent)| {
+			Rc::new(PrefsCollection::Folder {
+				name: name.to_string(),
+				parent: parent.map(str::to_string),
+				items: Vec::new(),
+			})
+		});
+		[Rc::new(PrefsCollection::Builtin(BuiltinCollection::All))]
+			.into_iter()
+			.chain(folders)
+			.collect()
+	}
+
+	#[test_case(0, &[("A", None), ("B", None)], &[], &[(0, 0, false), (1, 0, false), (2, 0, false)])]
+	#[test_case(1, &[("A", None), ("B", Some("A"))], &[], &[(0, 0, false), (1, 0, true), (2, 1, false)])]
+	#[test_case(2, &[("B", Some("A")), ("A", None)], &[], &[(0, 0, false), (2, 0, true), (1, 1, false)])]
+	#[test_case(3, &[("A", None), ("B", Some("A")), ("C", Some("B"))], &["B"], &[(0, 0, false), (1, 0, true), (2, 1, true)])]
+	#[test_case(4, &[("A", None), ("B", Some("A"))], &["A"], &[(0, 0, false), (1, 0, true)])]
+	#[test_case(5, &[("A", Some("Missing"))], &[], &[(0, 0, false), (1, 0, false)])]
+	#[test_case(6, &[("A", Some("B")), ("B", Some("A"))], &[], &[(0, 0, false), (1, 0, false), (2, 0, false)])]
+	fn collection_tree_rows(
+		_index: usize,
+		folders: &[(&str, Option<&str>)],
+		collapsed: &[&str],
+		expected: &[(usize, usize, bool)],
+	) {
+		let collections = nested_folders(folders);
+		let collapsed = collapsed.iter().map(|x| x.to_string()).collect::<BTreeSet<_>>();
+		let actual = super::collection_tree_rows(&collections, &collapsed);
+		let expected = expected
+			.iter()
+			.map(|&(index, depth, has_children)| CollectionRow {
+				index,
+				depth,
+				has_children,
+			})
+			.collect::<Vec<_>>();
+		assert_eq!(expected, actual);
+	}
+
+	#[test_case(0, &[("A", None), ("B", None)], 1, &["B"])]
+	#[test_case(1, &[("A", None), ("B", Some("A")), ("C", Some("B"))], 1, &[])]
+	#[test_case(2, &[("A", None), ("B", Some("A")), ("C", Some("B"))], 3, &["A", "B"])]
+	#[test_case(3, &[("A", None), ("B", Some("A")), ("C", None)], 2, &["A", "C"])]
+	fn folder_move_targets(_index: usize, folders: &[(&str, Option<&str>)], folder_index: usize, expected: &[&str]) {
+		let collections = nested_folders(folders);
+		let actual = super::folder_move_targets(&collections, folder_index);
+		assert_eq!(expected, actual.as_slice());
+	}
+
+	#[test]
+	fn normalize_folder_collections_nested() {
+		let input = nested_folders(&[
+			("Beta", None),
+			(" Alpha ", None),
+			("Gamma", Some(" Alpha ")),
+			("alpha", None),
+			("Delta", Some("alpha")),
+		]);
+		let (actual, _) = super::normalize_folder_collections(&input);
+		let expected = nested_folders(&[
+			("Alpha", None),
+			("Beta", None),
+			("Delta", Some("Alpha")),
+			("Gamma", Some("Alpha")),
+		]);
+		assert_eq!(expected, actual);
+	}
+
+	#[test_case(0, &[("Alpha", &["coco"]), ("Beta", &["coco2"])], &[("Alpha", &["coco"]), ("Beta", &["coco2"])], 0)]
+	#[test_case(1, &[(" Alpha  Games ", &["coco"])], &[("Alpha Games", &["coco"])], 1)]
+	#[test_case(2, &[("Alpha", &["coco"]), ("alpha ", &["coco", "coco2"])], &[("Alpha", &["coco", "coco2"])], 1)]
+	#[test_case(3, &[("Beta", &[]), ("Alpha", &[])], &[("Alpha", &[]), ("Beta", &[])], 1)]
+	fn normalize_folder_collections(
+		_index: usize,
+		input: &[(&str, &[&str])],
+		expected: &[(&str, &[&str])],
+		expected_change_count: usize,
+	) {
+		let builtin = Rc::new(PrefsCollection::Builtin(BuiltinCollection::All));
+		let input = [builtin.clone()]
+			.into_iter()
+			.chain(input.iter().map(|(name, machines)| folder(name, machines)))
+			.collect::<Vec<_>>();
+		let expected = [builtin]
+			.into_iter()
+			.chain(expected.iter().map(|(name, machines)| folder(name, machines)))
+			.collect::<Vec<_>>();
+
+		let (actual, changes) = super::normalize_folder_collections(&input);
+		assert_eq!((expected, expected_change_count), (actual, changes.len()));
+	}
+
+	#[test]
+	fn orphaned_entries() {
+		let software_collection = |machine_name: &str| {
+			Rc::new(PrefsCollection::MachineSoftware {
+				machine_name: machine_name.to_string(),
+			})
+		};
+		let collections = [
+			folder("Alpha", &["coco", "gone", "coco2"]),
+			software_collection("coco"),
+			software_collection("gone"),
+		];
+		let is_known =
+			|item: &PrefsItem| !matches!(item, PrefsItem::Machine { machine_name } if machine_name == "gone");
+
+		let orphans = super::find_orphaned_entries(&collections, is_known);
+		let orphans_text = orphans.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+		assert_eq!(
+			vec![
+				"Machine \"gone\" in folder \"Alpha\"",
+				"Software collection for machine \"gone\""
+			],
+			orphans_text
+		);
+
+		let actual = super::remove_orphaned_entries(&collections, &orphans);
+		let expected = vec![folder("Alpha", &["coco", "coco2"]), software_collection("coco")];
+		assert_eq!(expected, actual);
+	}
+
+	#[test]
+	fn folder_normalization_display() {
+		let change = FolderNormalization::Merge {
+			old_name: "alpha".into(),
+			new_name: "Alpha".into(),
+		};
+		assert_eq!("Merge \"alpha\" into \"Alpha\"", change.to_string());
 	}
 }
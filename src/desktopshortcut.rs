@@ -0,0 +1,192 @@
+//! Desktop shortcuts that launch items through the command line run mode (see `headless.rs`)
+use std::borrow::Cow;
+use std::env::current_exe;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+
+use anyhow::Error;
+use anyhow::Result;
+use itertools::Itertools;
+use tracing::event;
+use tracing::Level;
+
+use crate::platform::CommandExt;
+use crate::prefs::PrefsItem;
+
+const LOG: Level = Level::DEBUG;
+
+/// Creates a shortcut on the desktop that runs `item`; returns the path of the new shortcut
+pub fn create_desktop_shortcut(
+	name: &str,
+	item: &PrefsItem,
+	prefs_path: Option<&Path>,
+	icon: Option<&Path>,
+) -> Result<PathBuf> {
+	let desktop_dir = dirs::desktop_dir().ok_or_else(|| Error::msg("Could not find the desktop directory"))?;
+	let program = current_exe()?;
+	let program = program.to_string_lossy();
+	let args = shortcut_args(item, prefs_path);
+	let file_name = shortcut_file_name(name);
+	event!(LOG, "create_desktop_shortcut(): file_name={file_name:?} args={args:?}");
+
+	let path = if cfg!(target_os = "windows") {
+		// .lnk files are binary; let the shell write it for us
+		let path = desktop_dir.join(format!("{file_name}.lnk"));
+		let script = powershell_script(&path.to_string_lossy(), &program, &args);
+		let status = Command::new("powershell")
+			.args(["-NoProfile", "-NonInteractive", "-Command", &script])
+			.create_no_window(true)
+			.status()?;
+		if !status.success() {
+			let message = format!("PowerShell failed to create the shortcut ({status})");
+			return Err(Error::msg(message));
+		}
+		path
+	} else if cfg!(target_os = "macos") {
+		let path = desktop_dir.join(format!("{file_name}.command"));
+		std::fs::write(&path, command_script(&program, &args))?;
+		path
+	} else {
+		let path = desktop_dir.join(format!("{file_name}.desktop"));
+		std::fs::write(&path, desktop_entry(name, &program, &args, icon))?;
+		path
+	};
+
+	// shell scripts and (on some desktops) desktop entries need to be executable
+	#[cfg(unix)]
+	crate::runtime::args::make_executable(&path)?;
+
+	Ok(path)
+}
+
+/// The command line arguments to run `item`
+pub fn shortcut_args(item: &PrefsItem, prefs_path: Option<&Path>) -> Vec<String> {
+	let prefs_args = prefs_path
+		.into_iter()
+		.flat_map(|path| ["--prefs-path".to_string(), path.to_string_lossy().into_owned()]);
+	let run_args = match item {
+		PrefsItem::Machine { machine_name } => ["--run".to_string(), machine_name.clone()],
+		PrefsItem::Software {
+			software_list,
+			software,
+		} => ["--run-software".to_string(), format!("{software_list}:{software}")],
+	};
+	prefs_args.chain(run_args).collect()
+}
+
+/// Strips characters that are not allowed in file names on any of our platforms
+pub fn shortcut_file_name(name: &str) -> String {
+	let name = name
+		.chars()
+		.map(|c| {
+			if "/\\:*?\"<>|".contains(c) || c.is_control() {
+				'_'
+			} else {
+				c
+			}
+		})
+		.collect::<String>();
+	name.trim().trim_end_matches('.').to_string()
+}
+
+/// A freedesktop.org desktop entry
+pub fn desktop_entry(name: &str, program: &str, args: &[String], icon: Option<&Path>) -> String {
+	let exec = [program]
+		.into_iter()
+		.chain(args.iter().map(|x| x.as_str()))
+		.map(desktop_exec_arg)
+		.join(" ");
+	let icon = icon
+		.map(|x| format!("Icon={}\n", x.to_string_lossy()))
+		.unwrap_or_default();
+	format!("[Desktop Entry]\nType=Application\nName={name}\nExec={exec}\n{icon}Terminal=false\n")
+}
+
+/// Quotes an argument for the `Exec` key of a desktop entry
+fn desktop_exec_arg(arg: &str) -> Cow<'_, str> {
+	if arg.chars().any(|c| " \t\"'\\><~|&;$*?#()`=%".contains(c)) {
+		let arg = arg
+			.chars()
+			.flat_map(|c| match c {
+				'"' | '`' | '$' | '\\' => vec!['\\', c],
+				'%' => vec!['%', '%'],
+				c => vec![c],
+			})
+			.collect::<String>();
+		Cow::Owned(format!("\"{arg}\""))
+	} else {
+		Cow::Borrowed(arg)
+	}
+}
+
+/// A shell script that macOS Finder will run when double clicked
+fn command_script(program: &str, args: &[String]) -> String {
+	let command = [program]
+		.into_iter()
+		.chain(args.iter().map(|x| x.as_str()))
+		.map(|x| format!("'{}'", x.replace('\'', "'\\''")))
+		.join(" ");
+	format!("#!/bin/sh\nexec {command}\n")
+}
+
+/// A PowerShell script that creates a Windows shortcut; the icon is extracted from our own executable
+fn powershell_script(path: &str, program: &str, args: &[String]) -> String {
+	let quote = |s: &str| format!("'{}'", s.replace('\'', "''"));
+	let args = args
+		.iter()
+		.map(|x| if x.contains(' ') { format!("\"{x}\"") } else { x.clone() })
+		.join(" ");
+	format!(
+		"$s = (New-Object -ComObject WScript.Shell).CreateShortcut({}); $s.TargetPath = {}; $s.Arguments = {}; $s.IconLocation = {}; $s.Save()",
+		quote(path),
+		quote(program),
+		quote(&args),
+		quote(&format!("{program},0"))
+	)
+}
+
+#[cfg(test)]
+mod test {
+	use std::path::Path;
+
+	use test_case::test_case;
+
+	use crate::prefs::PrefsItem;
+
+	#[test]
+	pub fn shortcut_args() {
+		let item = PrefsItem::Software {
+			software_list: "coco_cart".into(),
+			software: "mpak".into(),
+		};
+		let actual = super::shortcut_args(&item, Some(Path::new("/prefs")));
+		assert_eq!(
+			vec!["--prefs-path", "/prefs", "--run-software", "coco_cart:mpak"],
+			actual
+		);
+	}
+
+	#[test_case(0, "Color Computer 2B", "Color Computer 2B")]
+	#[test_case(1, "Pac-Man (Midway) ", "Pac-Man (Midway)")]
+	#[test_case(2, "Disk: A/B?", "Disk_ A_B_")]
+	pub fn shortcut_file_name(_index: usize, name: &str, expected: &str) {
+		let actual = super::shortcut_file_name(name);
+		assert_eq!(expected, actual);
+	}
+
+	#[test_case(0, "coco2b", "coco2b")]
+	#[test_case(1, "/my games/bletchmame", "\"/my games/bletchmame\"")]
+	#[test_case(2, "100% \"real\"", "\"100%% \\\"real\\\"\"")]
+	pub fn desktop_exec_arg(_index: usize, arg: &str, expected: &str) {
+		let actual = super::desktop_exec_arg(arg);
+		assert_eq!(expected, actual);
+	}
+
+	#[test]
+	pub fn command_script() {
+		let args = ["--run".to_string(), "it's".to_string()];
+		let actual = super::command_script("/apps/bletchmame", &args);
+		assert_eq!("#!/bin/sh\nexec '/apps/bletchmame' '--run' 'it'\\''s'\n", actual);
+	}
+}
@@ -0,0 +1,247 @@
+use std::fmt::Write;
+use std::process::Command;
+use std::process::Stdio;
+use std::sync::Arc;
+
+use anyhow::Error;
+use anyhow::Result;
+
+use crate::info::ChipType;
+use crate::info::InfoDb;
+use crate::info::Machine;
+use crate::info::SoftwareListStatus;
+use crate::info::View;
+use crate::models::columns::clock_text;
+use crate::models::columns::requirements_text;
+use crate::platform::CommandExt;
+use crate::prefs::ram_text;
+use crate::software::Software;
+use crate::software::SoftwareList;
+use crate::software::SoftwareListDispenser;
+
+/// A plain text report of what the InfoDB knows about a machine, suitable for showing to the user or copying to the
+/// clipboard; `software_count` is the number of titles in the machine's software lists, if they could be loaded
+pub fn machine_details_text(machine: Machine<'_>, software_count: Option<usize>) -> String {
+	let mut text = format!("{} ({})\n", machine.description(), machine.name());
+	writeln!(text, "Manufacturer: {}", machine.manufacturer()).unwrap();
+	writeln!(text, "Year: {}", machine.year()).unwrap();
+	writeln!(text, "Source File: {}", machine.source_file()).unwrap();
+	if let Some(clone_of) = machine.clone_of() {
+		writeln!(text, "Clone Of: {} ({})", clone_of.description(), clone_of.name()).unwrap();
+	}
+	let clone_count = machine.clone_count();
+	if clone_count > 0 {
+		writeln!(text, "Clones: {clone_count}").unwrap();
+	}
+	let software_list_count = machine.machine_software_lists().len();
+	if software_list_count > 0 {
+		let software_count = software_count.map(|x| format!("{x} titles in ")).unwrap_or_default();
+		writeln!(text, "Software: {software_count}{software_list_count} lists").unwrap();
+	}
+	let requirements = requirements_text(machine);
+	if !requirements.is_empty() {
+		writeln!(text, "Requires: {requirements}").unwrap();
+	}
+
+	let mut section = |title, lines| write_section(&mut text, title, lines);
+	let chips = machine.chips();
+	let chip_lines = chips.iter().map(|chip| {
+		let chip_type = match chip.chip_type() {
+			ChipType::Cpu => "CPU",
+			ChipType::Audio => "Audio",
+		};
+		let clock = (chip.clock() > 0).then(|| format!(" @ {}", clock_text(chip.clock())));
+		let clock = clock.unwrap_or_default();
+		format!("{chip_type} \"{}\": {}{clock}", chip.tag(), chip.name())
+	});
+	section("Chips", chip_lines.collect());
+	let bios_sets = machine.bios_sets();
+	let bios_set_lines = bios_sets.iter().map(|bios_set| {
+		let default = if bios_set.is_default() { " (default)" } else { "" };
+		format!("{}: {}{default}", bios_set.name(), bios_set.description())
+	});
+	section("BIOS Sets", bios_set_lines.collect());
+	let slots = machine.slots();
+	let slot_lines = slots.iter().map(|slot| {
+		let options = slot.options();
+		let default_option = slot.default_option_index().and_then(|index| options.get(index));
+		let default_option = default_option.map(|x| x.name()).unwrap_or("(empty)");
+		format!("{}: {default_option} ({} options)", slot.name(), options.len())
+	});
+	section("Slots", slot_lines.collect());
+	let ram_options = machine.ram_options();
+	let ram_option_lines = ram_options.iter().map(|ram_option| {
+		let default = if ram_option.is_default() { " (default)" } else { "" };
+		format!("{}{default}", ram_text(ram_option.size()))
+	});
+	section("RAM Options", ram_option_lines.collect());
+	let machine_software_lists = machine.machine_software_lists();
+	let software_list_lines = machine_software_lists.iter().map(|x| {
+		let status = match x.status() {
+			SoftwareListStatus::Original => "original",
+			SoftwareListStatus::Compatible => "compatible",
+		};
+		let filter = (!x.filter().is_empty()).then(|| format!(", filter \"{}\"", x.filter()));
+		let filter = filter.unwrap_or_default();
+		format!("{} ({status}{filter})", x.software_list().name())
+	});
+	section("Software Lists", software_list_lines.collect());
+	text
+}
+
+/// The number of titles in a machine's software lists that the machine can run; lists that cannot be loaded are
+/// skipped, and `None` is returned if none of them could be
+pub fn machine_software_count(machine: Machine<'_>, dispenser: &mut SoftwareListDispenser<'_>) -> Option<usize> {
+	let machine_software_lists = machine.machine_software_lists();
+	let counts = machine_software_lists.iter().filter_map(|x| {
+		let (_, software_list) = dispenser.get(x.software_list().name()).ok()?;
+		let software = software_list.software.iter();
+		Some(software.filter(|software| software.is_compatible(x.filter())).count())
+	});
+	counts.reduce(|a, b| a + b)
+}
+
+/// Like `machine_details_text()`, but for software; this includes the machines that can run the software
+pub fn software_details_text(info_db: &InfoDb, software_list: &SoftwareList, software: &Software) -> String {
+	let mut text = format!("{} ({}:{})\n", software.description, software_list.name, software.name);
+	writeln!(text, "Publisher: {}", software.publisher).unwrap();
+	writeln!(text, "Year: {}", software.year).unwrap();
+	writeln!(text, "Software List: {}", software_list.description).unwrap();
+	writeln!(text, "Support: {}", software.supported).unwrap();
+	if let Some(source) = &software_list.source {
+		writeln!(text, "Source: {}", source.display()).unwrap();
+	}
+
+	let mut section = |title, lines| write_section(&mut text, title, lines);
+	let pair_lines = |pairs: &[(Arc<str>, Arc<str>)]| {
+		pairs
+			.iter()
+			.map(|(name, value)| format!("{name}: {value}"))
+			.collect::<Vec<_>>()
+	};
+	section("Information", pair_lines(&software.infos));
+	section("Shared Features", pair_lines(&software.shared_features));
+	let part_lines = software.parts.iter().map(|part| {
+		let features = part
+			.features
+			.iter()
+			.map(|(name, value)| format!("\n    {name}: {value}"));
+		format!("{}: {}{}", part.name, part.interface, features.collect::<String>())
+	});
+	section("Parts", part_lines.collect());
+	let shadowed_lines = software_list.shadowed.iter().map(|x| x.display().to_string());
+	section("Shadowed Hash Files", shadowed_lines.collect());
+
+	// which machines can run this software depends on the filters on their software lists
+	let mut machine_lines = Vec::new();
+	if let Some(info_software_list) = info_db.software_lists().find(&software_list.name) {
+		let originals = info_software_list.original_for_machines();
+		let compatibles = info_software_list.compatible_for_machines();
+		let originals = originals.iter().map(|x| (x, "original"));
+		let compatibles = compatibles.iter().map(|x| (x, "compatible"));
+		for (machine, status) in originals.chain(compatibles) {
+			let machine_software_list = machine
+				.machine_software_lists()
+				.iter()
+				.find(|x| x.software_list().name() == software_list.name.as_ref());
+			if machine_software_list.is_some_and(|x| software.is_compatible(x.filter())) {
+				machine_lines.push(format!("{}: {} ({status})", machine.name(), machine.description()));
+			}
+		}
+	}
+	section("Machines", machine_lines);
+	text
+}
+
+fn write_section(text: &mut String, title: &str, lines: Vec<String>) {
+	if !lines.is_empty() {
+		writeln!(text, "\n{title} ({}):", lines.len()).unwrap();
+		for line in lines {
+			writeln!(text, "  {line}").unwrap();
+		}
+	}
+}
+
+/// ROMs are not recorded in the InfoDB, so we ask MAME to list them (along with their hashes)
+pub fn list_roms(mame_executable_path: &str, machine_name: &str) -> Result<String> {
+	let output = Command::new(mame_executable_path)
+		.arg("-listroms")
+		.arg(machine_name)
+		.stdout(Stdio::piped())
+		.stderr(Stdio::null())
+		.create_no_window(true)
+		.output()?;
+	if !output.status.success() {
+		let message = format!("MAME -listroms failed ({})", output.status);
+		return Err(Error::msg(message));
+	}
+	Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod test {
+	use test_case::test_case;
+
+	use crate::info::InfoDb;
+	use crate::software::SoftwareList;
+
+	#[test_case(0, include_str!("info/test_data/listxml_coco.xml"), "coco2b", "Color Computer 2B (coco2b)\n")]
+	#[test_case(1, include_str!("info/test_data/listxml_coco.xml"), "coco2b", "Clone Of: Color Computer 1/2 (coco)\n")]
+	#[test_case(2, include_str!("info/test_data/listxml_coco.xml"), "coco2b", "  CPU \"maincpu\": Motorola MC6809E @ 894.886 kHz\n")]
+	#[test_case(3, include_str!("info/test_data/listxml_coco.xml"), "coco2b", "  coco_flop (original, filter \"COCO\")\n")]
+	#[test_case(4, include_str!("info/test_data/listxml_coco.xml"), "coco", "  b12e11: Color BASIC v1.2 / Extended Color BASIC v1.1 (default)\n")]
+	#[test_case(5, include_str!("info/test_data/listxml_coco.xml"), "coco", "\nRAM Options (4):\n  4K\n  16K\n  32K\n  64K (default)\n")]
+	#[test_case(6, include_str!("info/test_data/listxml_fake.xml"), "fake", "  ext:fdcv11:wd17xx:1: qd (1 options)\n")]
+	#[test_case(7, include_str!("info/test_data/listxml_fake.xml"), "fake", "Requires: CHD\n")]
+	#[test_case(8, include_str!("info/test_data/listxml_coco.xml"), "coco", "Clones: 6\n")]
+	#[test_case(9, include_str!("info/test_data/listxml_coco.xml"), "coco2b", "Software: 42 titles in 3 lists\n")]
+	pub fn machine_details_text(_index: usize, xml: &str, machine_name: &str, expected: &str) {
+		let info_db = InfoDb::from_listxml_output(xml.as_bytes(), |_| false).unwrap().unwrap();
+		let machine = info_db.machines().find(machine_name).unwrap();
+		let actual = super::machine_details_text(machine, Some(42));
+		assert!(actual.contains(expected), "{actual}");
+	}
+
+	#[test_case(0, "clowns", "Clowns & Balloons (coco_cart:clowns)\n")]
+	#[test_case(1, "clowns", "\nInformation (3):\n  developer: Datasoft\n  author: Steve Bjork\n  serial: 26-3087\n")]
+	#[test_case(2, "clowns", "\nParts (1):\n  cart: coco_cart\n")]
+	#[test_case(3, "clowns", "  coco2b: Color Computer 2B (original)\n")]
+	#[test_case(4, "amazing", "  compatibility: COCO3\n")]
+	#[test_case(5, "amazing", "  coco3: Color Computer 3 (NTSC) (original)\n")]
+	pub fn software_details_text(_index: usize, software_name: &str, expected: &str) {
+		let xml = include_str!("info/test_data/listxml_coco.xml");
+		let info_db = InfoDb::from_listxml_output(xml.as_bytes(), |_| false).unwrap().unwrap();
+		let xml = include_str!("software/test_data/softlist_coco_cart.xml");
+		let software_list = SoftwareList::from_reader(xml.as_bytes()).unwrap();
+		let software = software_list.software.iter().find(|x| x.name.as_ref() == software_name);
+		let actual = super::software_details_text(&info_db, &software_list, software.unwrap());
+		assert!(actual.contains(expected), "{actual}");
+	}
+
+	#[test]
+	pub fn software_details_text_source() {
+		let xml = include_str!("info/test_data/listxml_coco.xml");
+		let info_db = InfoDb::from_listxml_output(xml.as_bytes(), |_| false).unwrap().unwrap();
+		let xml = include_str!("software/test_data/softlist_coco_cart.xml");
+		let mut software_list = SoftwareList::from_reader(xml.as_bytes()).unwrap();
+		software_list.source = Some("/mame/hash/coco_cart.xml".into());
+		software_list.shadowed = vec!["/other/hash/coco_cart.xml".into()];
+		let software = software_list.software.iter().find(|x| x.name.as_ref() == "clowns");
+		let actual = super::software_details_text(&info_db, &software_list, software.unwrap());
+		let expected = "Source: /mame/hash/coco_cart.xml\n";
+		assert!(actual.contains(expected), "{actual}");
+		let expected = "\nShadowed Hash Files (1):\n  /other/hash/coco_cart.xml\n";
+		assert!(actual.contains(expected), "{actual}");
+	}
+
+	#[test]
+	pub fn software_details_text_excludes_incompatible() {
+		let xml = include_str!("info/test_data/listxml_coco.xml");
+		let info_db = InfoDb::from_listxml_output(xml.as_bytes(), |_| false).unwrap().unwrap();
+		let xml = include_str!("software/test_data/softlist_coco_cart.xml");
+		let software_list = SoftwareList::from_reader(xml.as_bytes()).unwrap();
+		let software = software_list.software.iter().find(|x| x.name.as_ref() == "amazing");
+		let actual = super::software_details_text(&info_db, &software_list, software.unwrap());
+		assert!(!actual.contains("  coco2b:"), "{actual}");
+	}
+}
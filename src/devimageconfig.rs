@@ -1,7 +1,9 @@
 use std::borrow::Cow;
+use std::collections::HashSet;
 use std::iter::once;
 use std::rc::Rc;
 
+use crate::collation::fold_text;
 use crate::info::InfoDb;
 use crate::info::View;
 use crate::mconfig::MachineConfig;
@@ -44,6 +46,7 @@ pub struct Entry<'a> {
 	pub tag: &'a str,
 	pub subtag: &'a str,
 	pub indent: usize,
+	pub has_children: bool,
 	pub details: EntryDetails<'a>,
 }
 
@@ -134,15 +137,60 @@ impl DevicesImagesConfig {
 			}
 		};
 
+		let has_children = core.entries.iter().any(|x| is_beneath(&x.tag, &internal_entry.tag));
 		let entry = Entry {
 			tag: &internal_entry.tag,
 			subtag: &internal_entry.tag[internal_entry.subtag_start..],
 			indent: internal_entry.indent,
+			has_children,
 			details,
 		};
 		Some(entry)
 	}
 
+	/// Indexes of the entries to show in the slot tree; entries beneath collapsed slots are hidden, unless searching,
+	/// in which case entries matching the search are shown along with the slots above them
+	pub fn visible_entries(&self, collapsed: &HashSet<String>, search: &str) -> Vec<usize> {
+		let entries = self.core.as_ref().map(|x| x.entries.as_ref()).unwrap_or_default();
+		if search.is_empty() {
+			(0..entries.len())
+				.filter(|&index| {
+					let tag = &entries[index].tag;
+					!collapsed.iter().any(|collapsed_tag| is_beneath(tag, collapsed_tag))
+				})
+				.collect()
+		} else {
+			let search = fold_text(search);
+			let matches = (0..entries.len())
+				.filter(|&index| self.is_search_match(index, &search))
+				.collect::<Vec<_>>();
+			(0..entries.len())
+				.filter(|&index| {
+					matches
+						.iter()
+						.any(|&m| m == index || is_beneath(&entries[m].tag, &entries[index].tag))
+				})
+				.collect()
+		}
+	}
+
+	/// Slots match on their name and the names and descriptions of their options (e.g. - searching for "disk" finds
+	/// slots that can hold a disk controller); images match on their name and filename
+	fn is_search_match(&self, index: usize, folded_search: &str) -> bool {
+		let entry = self.entry(index).unwrap();
+		let texts = match &entry.details {
+			EntryDetails::Slot { options, .. } => options
+				.iter()
+				.flat_map(|x| [x.name.as_deref(), x.description.as_deref()])
+				.flatten()
+				.collect::<Vec<_>>(),
+			EntryDetails::Image { filename } => filename.iter().copied().collect(),
+		};
+		once(entry.subtag)
+			.chain(texts)
+			.any(|text| fold_text(text).contains(folded_search))
+	}
+
 	pub fn update_status(&self, status: &Status) -> Self {
 		// note that this logic won't error; this is because we expect the InfoDB and Status data
 		// to be in harmony; we really need a status validation step
@@ -348,6 +396,11 @@ fn internal_entry_image_from_status(
 	}
 }
 
+/// Whether `tag` is somewhere beneath `ancestor_tag` (e.g. - "ext:fdc:wd17xx:0" is beneath "ext")
+fn is_beneath(tag: &str, ancestor_tag: &str) -> bool {
+	tag.strip_prefix(ancestor_tag).is_some_and(|x| x.starts_with(':'))
+}
+
 fn identify_changed_rows(a: &[InternalEntry], b: &[InternalEntry]) -> Option<Vec<usize>> {
 	(a.len() == b.len()).then(|| {
 		a.iter()
@@ -362,6 +415,7 @@ fn identify_changed_rows(a: &[InternalEntry], b: &[InternalEntry]) -> Option<Vec
 
 #[cfg(test)]
 mod test {
+	use std::collections::HashSet;
 	use std::rc::Rc;
 
 	use test_case::test_case;
@@ -414,4 +468,33 @@ mod test {
 		// smoke test!
 		smoke_test_config(new_config);
 	}
+
+	#[test_case(0, &[], "", |_| true)]
+	#[test_case(1, &["ext"], "", |tag| !tag.starts_with("ext:"))]
+	#[test_case(2, &["ext"], "rs_printer", |tag| tag == "rs232")]
+	#[test_case(3, &["ext"], "525QD", |tag| tag != "rs232")]
+	#[test_case(4, &[], "xyzzy", |_| false)]
+	fn visible_entries(_index: usize, collapsed: &[&str], search: &str, expected: fn(&str) -> bool) {
+		// build the InfoDB
+		let info_xml = include_str!("info/test_data/listxml_coco.xml");
+		let info_db = InfoDb::from_listxml_output(info_xml.as_bytes(), |_| false)
+			.unwrap()
+			.unwrap();
+		let info_db = Rc::new(info_db);
+
+		// create the config and identify what is visible
+		let config = DevicesImagesConfig::with_machine_name(info_db, Some("coco2b"));
+		let collapsed = collapsed.iter().map(|x| x.to_string()).collect::<HashSet<_>>();
+		let actual = config
+			.visible_entries(&collapsed, search)
+			.into_iter()
+			.map(|index| config.entry(index).unwrap().tag.to_string())
+			.collect::<Vec<_>>();
+
+		let expected = (0..config.entry_count())
+			.map(|index| config.entry(index).unwrap().tag.to_string())
+			.filter(|tag| expected(tag))
+			.collect::<Vec<_>>();
+		assert_eq!(expected, actual);
+	}
 }
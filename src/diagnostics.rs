@@ -1,12 +1,346 @@
+use std::collections::VecDeque;
+use std::fmt::Write as _;
 use std::fs::File;
 use std::io::BufReader;
+use std::io::Seek;
+use std::io::Write;
 use std::path::Path;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use anyhow::Result;
+use tracing::Level;
+use tracing_subscriber::reload;
+use tracing_subscriber::EnvFilter;
+use tracing_subscriber::Registry;
+use zip::write::SimpleFileOptions;
+use zip::CompressionMethod;
+use zip::ZipWriter;
 
 use crate::info::InfoDb;
+use crate::info::View;
+use crate::prefs::Preferences;
+use crate::runtime::sessionlog::SessionLogEntry;
+use crate::runtime::sessionlog::SessionLogSource;
+use crate::software::shadowed_software_lists;
+use crate::status::Status;
+use crate::status::Update;
+
+/// The number of log lines retained for diagnostic reports
+const LOG_RING_CAPACITY: usize = 1000;
+
+/// Modules whose log levels can be changed at runtime, with their display names
+pub const LOG_TARGETS: &[(&str, &str)] = &[
+	("MAME Info Database", "bletchmame::info"),
+	("MAME Sessions", "bletchmame::runtime::session"),
+	("Items Table", "bletchmame::models::itemstable"),
+];
 
 pub fn info_db_from_xml_file(path: impl AsRef<Path>) {
-	let file = File::open(path).unwrap();
-	let mut reader = BufReader::new(file);
-	let _ = InfoDb::from_listxml_output(&mut reader, |_| false).unwrap().unwrap();
+	let _ = InfoDb::from_listxml_file(path, |_| false).unwrap().unwrap();
 	println!("Success");
 }
+
+/// Strictly parses a status XML file captured from the worker_ui plugin, to find anything the parser does not expect
+pub fn status_from_xml_file(path: impl AsRef<Path>) {
+	let file = BufReader::new(File::open(path).unwrap());
+	let _ = Update::parse_strict(file).unwrap();
+	println!("Success");
+}
+
+/// Retains the most recent lines of log output, so they can be included in diagnostic reports
+#[derive(Clone, Default)]
+pub struct LogRing(Arc<Mutex<LogRingInner>>);
+
+#[derive(Default)]
+struct LogRingInner {
+	lines: VecDeque<String>,
+	partial: String,
+}
+
+impl LogRing {
+	pub fn lines(&self) -> Vec<String> {
+		self.0.lock().unwrap().lines.iter().cloned().collect()
+	}
+}
+
+impl Write for LogRing {
+	fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+		let mut inner = self.0.lock().unwrap();
+		inner.partial.push_str(&String::from_utf8_lossy(buf));
+		while let Some(idx) = inner.partial.find('\n') {
+			let line = inner.partial.drain(..=idx).collect::<String>();
+			let line = strip_ansi_escapes(line.trim_end());
+			if inner.lines.len() >= LOG_RING_CAPACITY {
+				inner.lines.pop_front();
+			}
+			inner.lines.push_back(line);
+		}
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> std::io::Result<()> {
+		Ok(())
+	}
+}
+
+/// Controls which log output is emitted; the levels of the modules in `LOG_TARGETS` can be changed while running
+#[derive(Clone)]
+pub struct LogFilter {
+	handle: reload::Handle<EnvFilter, Registry>,
+	default_level: Level,
+	levels: Arc<Mutex<Vec<Option<Level>>>>,
+}
+
+impl LogFilter {
+	/// Creates the filter, along with the layer to install into the subscriber
+	pub fn new(default_level: Level) -> (Self, reload::Layer<EnvFilter, Registry>) {
+		let levels = vec![None; LOG_TARGETS.len()];
+		let (layer, handle) = reload::Layer::new(env_filter(default_level, &levels));
+		let log_filter = Self {
+			handle,
+			default_level,
+			levels: Arc::new(Mutex::new(levels)),
+		};
+		(log_filter, layer)
+	}
+
+	/// The level for each of `LOG_TARGETS`; `None` when using the default level
+	pub fn levels(&self) -> Vec<Option<Level>> {
+		self.levels.lock().unwrap().clone()
+	}
+
+	pub fn set_levels(&self, levels: Vec<Option<Level>>) -> Result<()> {
+		self.handle.reload(env_filter(self.default_level, &levels))?;
+		*self.levels.lock().unwrap() = levels;
+		Ok(())
+	}
+}
+
+impl Default for LogFilter {
+	fn default() -> Self {
+		Self::new(Level::INFO).0
+	}
+}
+
+fn env_filter(default_level: Level, levels: &[Option<Level>]) -> EnvFilter {
+	EnvFilter::new(filter_directives(default_level, levels))
+}
+
+fn filter_directives(default_level: Level, levels: &[Option<Level>]) -> String {
+	let mut directives = default_level.as_str().to_lowercase();
+	for ((_, target), level) in LOG_TARGETS.iter().zip(levels) {
+		if let Some(level) = level {
+			write!(directives, ",{target}={}", level.as_str().to_lowercase()).unwrap();
+		}
+	}
+	directives
+}
+
+/// Assembles a plain text report suitable for attaching to bug reports
+pub fn diagnostic_report(
+	prefs: &Preferences,
+	info_db: Option<&InfoDb>,
+	status: Option<&Status>,
+	log_lines: &[String],
+	home_dir: Option<&Path>,
+) -> Result<String> {
+	let prefs_json = serde_json::to_string_pretty(prefs)?;
+	let prefs_json = sanitize_home_dir(&prefs_json, home_dir);
+
+	let mut report = String::new();
+	writeln!(report, "BletchMAME Diagnostic Report")?;
+	writeln!(report, "BletchMAME Version: {}", env!("CARGO_PKG_VERSION"))?;
+	writeln!(report, "Platform: {} {}", std::env::consts::OS, std::env::consts::ARCH)?;
+	writeln!(report)?;
+
+	writeln!(report, "[MAME]")?;
+	if let Some(info_db) = info_db {
+		writeln!(report, "InfoDb Build: {}", info_db.build())?;
+		writeln!(report, "InfoDb Machines: {}", info_db.machines().len())?;
+	} else {
+		writeln!(report, "InfoDb: (none)")?;
+	}
+	if let Some(build) = status.and_then(|status| status.build.as_ref()) {
+		writeln!(report, "Running Build: {build}")?;
+	}
+	if let Some(running) = status.and_then(|status| status.running.as_ref()) {
+		writeln!(report, "Running Machine: {}", running.machine_name)?;
+	}
+	writeln!(report)?;
+
+	let shadowed = shadowed_software_lists(&prefs.paths.software_lists);
+	if !shadowed.is_empty() {
+		writeln!(report, "[Software Lists]")?;
+		for (name, files) in shadowed {
+			let files = files.iter().map(|x| x.display().to_string()).collect::<Vec<_>>();
+			let files = sanitize_home_dir(&files.join(", "), home_dir);
+			writeln!(report, "Shadowed: {name} ({files})")?;
+		}
+		writeln!(report)?;
+	}
+
+	writeln!(report, "[Preferences]")?;
+	writeln!(report, "{prefs_json}")?;
+	writeln!(report)?;
+
+	writeln!(report, "[Log]")?;
+	for line in log_lines {
+		writeln!(report, "{}", sanitize_home_dir(line, home_dir))?;
+	}
+	Ok(report)
+}
+
+/// Packages a diagnostic report as a ZIP, along with the recent `worker_ui` traffic and the last status XML
+pub fn write_diagnostic_archive(
+	writer: impl Write + Seek,
+	report: &str,
+	session_log: &[SessionLogEntry],
+	status_xml: Option<&str>,
+	home_dir: Option<&Path>,
+) -> Result<()> {
+	let traffic = session_log
+		.iter()
+		.filter_map(|entry| match entry.source {
+			SessionLogSource::Command => Some(format!("> {}\n", entry.text)),
+			SessionLogSource::Response => Some(format!("< {}\n", entry.text)),
+			SessionLogSource::Stderr => None,
+		})
+		.collect::<String>();
+	let files = [
+		("report.txt", Some(report.to_string())),
+		("worker_ui.log", Some(sanitize_home_dir(&traffic, home_dir))),
+		("status.xml", status_xml.map(|x| sanitize_home_dir(x, home_dir))),
+	];
+
+	let mut zip = ZipWriter::new(writer);
+	let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+	for (name, text) in files {
+		if let Some(text) = text {
+			zip.start_file(name, options)?;
+			zip.write_all(text.as_bytes())?;
+		}
+	}
+	zip.finish()?;
+	Ok(())
+}
+
+/// Replaces the user's home directory (which often contains their name) with a placeholder
+fn sanitize_home_dir(text: &str, home_dir: Option<&Path>) -> String {
+	let Some(home_dir) = home_dir.and_then(|x| x.to_str()).filter(|x| !x.is_empty()) else {
+		return text.to_string();
+	};
+
+	// the home directory may appear either raw, or escaped within JSON strings
+	let escaped = serde_json::to_string(home_dir).unwrap();
+	let escaped = &escaped[1..escaped.len() - 1];
+	text.replace(escaped, "~").replace(home_dir, "~")
+}
+
+fn strip_ansi_escapes(s: &str) -> String {
+	let mut result = String::with_capacity(s.len());
+	let mut chars = s.chars();
+	while let Some(c) = chars.next() {
+		if c == '\x1b' {
+			for c in chars.by_ref() {
+				if c.is_ascii_alphabetic() {
+					break;
+				}
+			}
+		} else {
+			result.push(c);
+		}
+	}
+	result
+}
+
+#[cfg(test)]
+mod test {
+	use std::io::Cursor;
+	use std::io::Read;
+	use std::io::Write;
+	use std::path::Path;
+
+	use test_case::test_case;
+	use tracing::Level;
+	use zip::ZipArchive;
+
+	use super::LogRing;
+	use crate::runtime::sessionlog::SessionLogEntry;
+	use crate::runtime::sessionlog::SessionLogSource;
+
+	#[test_case(0, "foo", "foo")]
+	#[test_case(1, "\x1b[2mfoo\x1b[0m bar", "foo bar")]
+	#[test_case(2, "\x1b[32m INFO\x1b[0m hello", " INFO hello")]
+	pub fn strip_ansi_escapes(_index: usize, s: &str, expected: &str) {
+		let actual = super::strip_ansi_escapes(s);
+		assert_eq!(expected, actual);
+	}
+
+	#[test_case(0, "/home/alice/mame", Some("/home/alice"), "~/mame")]
+	#[test_case(1, "/home/alice/mame", None, "/home/alice/mame")]
+	#[test_case(2, "\"C:\\\\Users\\\\alice\\\\mame\"", Some("C:\\Users\\alice"), "\"~\\\\mame\"")]
+	pub fn sanitize_home_dir(_index: usize, text: &str, home_dir: Option<&str>, expected: &str) {
+		let actual = super::sanitize_home_dir(text, home_dir.map(Path::new));
+		assert_eq!(expected, actual);
+	}
+
+	#[test_case(0, Level::INFO, &[None, None, None], "info")]
+	#[test_case(1, Level::WARN, &[None, Some(Level::TRACE), None], "warn,bletchmame::runtime::session=trace")]
+	#[test_case(2, Level::INFO, &[Some(Level::DEBUG), None, Some(Level::ERROR)], "info,bletchmame::info=debug,bletchmame::models::itemstable=error")]
+	pub fn filter_directives(_index: usize, default_level: Level, levels: &[Option<Level>], expected: &str) {
+		let actual = super::filter_directives(default_level, levels);
+		assert_eq!(expected, actual);
+	}
+
+	#[test]
+	pub fn log_ring() {
+		let mut log_ring = LogRing::default();
+		for i in 0..1005 {
+			write!(log_ring, "line {i}\npartial").unwrap();
+			writeln!(log_ring, " end").unwrap();
+		}
+		let lines = log_ring.lines();
+		assert_eq!(super::LOG_RING_CAPACITY, lines.len());
+		assert_eq!("partial end", lines.last().unwrap());
+	}
+
+	#[test_case(0, None, &["report.txt", "worker_ui.log"], "> PING\n< @OK\n")]
+	#[test_case(1, Some("<status/>"), &["report.txt", "worker_ui.log", "status.xml"], "> PING\n< @OK\n")]
+	pub fn write_diagnostic_archive(
+		_index: usize,
+		status_xml: Option<&str>,
+		expected_names: &[&str],
+		expected_traffic: &str,
+	) {
+		let session_log = [
+			(SessionLogSource::Stderr, "Starting MAME"),
+			(SessionLogSource::Command, "PING"),
+			(SessionLogSource::Response, "@OK"),
+		];
+		let session_log = session_log
+			.into_iter()
+			.map(|(source, text)| SessionLogEntry {
+				source,
+				text: text.to_string(),
+			})
+			.collect::<Vec<_>>();
+		let mut buffer = Cursor::new(Vec::new());
+		super::write_diagnostic_archive(&mut buffer, "report", &session_log, status_xml, None).unwrap();
+
+		let mut archive = ZipArchive::new(buffer).unwrap();
+		let mut actual_names = archive.file_names().map(str::to_string).collect::<Vec<_>>();
+		let mut expected_names = expected_names.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+		actual_names.sort();
+		expected_names.sort();
+		assert_eq!(expected_names, actual_names);
+
+		let mut actual_traffic = String::new();
+		archive
+			.by_name("worker_ui.log")
+			.unwrap()
+			.read_to_string(&mut actual_traffic)
+			.unwrap();
+		assert_eq!(expected_traffic, actual_traffic);
+	}
+}
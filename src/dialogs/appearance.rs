@@ -0,0 +1,91 @@
+use slint::CloseRequestResponse;
+use slint::ComponentHandle;
+use slint::ModelRc;
+use slint::SharedString;
+use slint::VecModel;
+use slint::Weak;
+
+use crate::dialogs::SingleResult;
+use crate::guiutils::modal::Modal;
+use crate::i18n::tr;
+use crate::prefs::PrefsAppearance;
+use crate::ui::AppearanceDialog;
+
+/// Edits the application font and UI language; `preview` is invoked whenever the font settings are changed so that they
+/// can be seen live
+pub async fn dialog_appearance(
+	parent: Weak<impl ComponentHandle + 'static>,
+	appearance: PrefsAppearance,
+	language: Option<String>,
+	languages: Vec<String>,
+	preview: impl Fn(&PrefsAppearance) + 'static,
+) -> Option<(PrefsAppearance, Option<String>)> {
+	// prepare the dialog
+	let modal = Modal::new(&parent.unwrap(), || AppearanceDialog::new().unwrap());
+	let single_result = SingleResult::default();
+
+	// set up defaults
+	let font_family = appearance.font_family.unwrap_or_default();
+	let font_size = appearance.font_size.unwrap_or_default();
+	modal.dialog().set_font_family(font_family.into());
+	modal.dialog().set_font_size(font_size.try_into().unwrap_or_default());
+
+	// set up the languages; the first entry is the untranslated English
+	let language_names = [tr("English")]
+		.into_iter()
+		.chain(languages.iter().cloned())
+		.map(SharedString::from)
+		.collect::<Vec<_>>();
+	let language_index = language
+		.and_then(|language| languages.iter().position(|x| *x == language))
+		.map(|x| x + 1)
+		.unwrap_or_default();
+	modal
+		.dialog()
+		.set_language_names(ModelRc::new(VecModel::from(language_names)));
+	modal.dialog().set_language_index(language_index.try_into().unwrap());
+
+	// set up the changed handler
+	let dialog_weak = modal.dialog().as_weak();
+	modal.dialog().on_changed(move || {
+		preview(&get_appearance(&dialog_weak.unwrap()));
+	});
+
+	// set up the "ok" button
+	let signaller = single_result.signaller();
+	let dialog_weak = modal.dialog().as_weak();
+	modal.dialog().on_ok_clicked(move || {
+		let dialog = dialog_weak.unwrap();
+		let appearance = get_appearance(&dialog);
+		let language = usize::try_from(dialog.get_language_index())
+			.ok()
+			.and_then(|index| index.checked_sub(1))
+			.and_then(|index| languages.get(index).cloned());
+		signaller.signal(Some((appearance, language)));
+	});
+
+	// set up the "cancel" button
+	let signaller = single_result.signaller();
+	modal.dialog().on_cancel_clicked(move || {
+		signaller.signal(None);
+	});
+
+	// set up the close handler
+	let signaller = single_result.signaller();
+	modal.window().on_close_requested(move || {
+		signaller.signal(None);
+		CloseRequestResponse::KeepWindowShown
+	});
+
+	// present the modal dialog
+	modal.run(async { single_result.wait().await }).await
+}
+
+fn get_appearance(dialog: &AppearanceDialog) -> PrefsAppearance {
+	let font_family = dialog.get_font_family().trim().to_string();
+	let font_size = u32::try_from(dialog.get_font_size()).unwrap_or_default();
+	PrefsAppearance {
+		font_family: (!font_family.is_empty()).then_some(font_family),
+		font_size: (font_size > 0).then_some(font_size),
+	}
+}
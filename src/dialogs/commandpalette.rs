@@ -0,0 +1,127 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use muda::MenuId;
+use slint::CloseRequestResponse;
+use slint::ComponentHandle;
+use slint::ModelRc;
+use slint::StandardListViewItem;
+use slint::VecModel;
+use slint::Weak;
+use unicase::UniCase;
+
+use crate::dialogs::SingleResult;
+use crate::guiutils::modal::Modal;
+use crate::ui::CommandPaletteDialog;
+
+pub async fn dialog_command_palette(
+	parent: Weak<impl ComponentHandle + 'static>,
+	commands: Vec<(String, MenuId)>,
+) -> Option<MenuId> {
+	// prepare the dialog
+	let modal = Modal::new(&parent.unwrap(), || CommandPaletteDialog::new().unwrap());
+	let single_result = SingleResult::default();
+	let commands = Rc::new(commands);
+	let filtered = Rc::new(RefCell::new(Vec::new()));
+	update_filter(modal.dialog(), &commands, &mut filtered.borrow_mut(), "");
+
+	// set up the search handler
+	let dialog_weak = modal.dialog().as_weak();
+	let commands_clone = commands.clone();
+	let filtered_clone = filtered.clone();
+	modal.dialog().on_search_text_changed(move |search| {
+		update_filter(
+			&dialog_weak.unwrap(),
+			&commands_clone,
+			&mut filtered_clone.borrow_mut(),
+			&search,
+		);
+	});
+
+	// set up the accepted handler
+	let signaller = single_result.signaller();
+	let dialog_weak = modal.dialog().as_weak();
+	let filtered_clone = filtered.clone();
+	modal.dialog().on_accepted(move || {
+		let index = dialog_weak.unwrap().get_current_index();
+		let index = usize::try_from(index).ok();
+		let command_index = index.and_then(|index| filtered_clone.borrow().get(index).copied());
+		signaller.signal(command_index);
+	});
+
+	// set up the cancelled handler
+	let signaller = single_result.signaller();
+	modal.dialog().on_cancelled(move || {
+		signaller.signal(None);
+	});
+
+	// set up the close handler
+	let signaller = single_result.signaller();
+	modal.window().on_close_requested(move || {
+		signaller.signal(None);
+		CloseRequestResponse::KeepWindowShown
+	});
+
+	// present the modal dialog
+	let command_index = modal.run(async { single_result.wait().await }).await?;
+	Some(commands[command_index].1.clone())
+}
+
+fn update_filter(
+	dialog: &CommandPaletteDialog,
+	commands: &[(String, MenuId)],
+	filtered: &mut Vec<usize>,
+	search: &str,
+) {
+	let mut scored = commands
+		.iter()
+		.enumerate()
+		.filter_map(|(index, (text, _))| fuzzy_score(search, text).map(|score| (score, index)))
+		.collect::<Vec<_>>();
+	scored.sort_by_key(|(score, index)| (*score, *index));
+	*filtered = scored.into_iter().map(|(_, index)| index).collect();
+
+	let entries = filtered
+		.iter()
+		.map(|&index| StandardListViewItem::from(commands[index].0.as_str()))
+		.collect::<Vec<_>>();
+	let entries = VecModel::from(entries);
+	dialog.set_entries(ModelRc::new(entries));
+	dialog.set_current_index(if filtered.is_empty() { -1 } else { 0 });
+}
+
+/// Case-insensitive subsequence match; lower scores are better matches (contiguous and earlier), and `None`
+/// indicates no match at all
+fn fuzzy_score(pattern: &str, text: &str) -> Option<usize> {
+	let mut score = 0;
+	let mut last_position = None;
+	let mut text_chars = text.char_indices();
+	for pattern_char in pattern.chars().filter(|ch| !ch.is_whitespace()) {
+		let pattern_char = UniCase::new(pattern_char.to_string());
+		let (position, _) = text_chars
+			.by_ref()
+			.find(|(_, ch)| UniCase::new(ch.to_string()) == pattern_char)?;
+		score += match last_position {
+			Some(last_position) => position - last_position - 1,
+			None => position,
+		};
+		last_position = Some(position);
+	}
+	Some(score)
+}
+
+#[cfg(test)]
+mod test {
+	use test_case::test_case;
+
+	#[test_case(0, "", "File > Stop", Some(0))]
+	#[test_case(1, "file", "File > Stop", Some(0))]
+	#[test_case(2, "stop", "File > Stop", Some(7))]
+	#[test_case(3, "fs", "File > Stop", Some(6))]
+	#[test_case(4, "xyz", "File > Stop", None)]
+	#[test_case(5, "pots", "File > Stop", None)]
+	pub fn fuzzy_score(_index: usize, pattern: &str, text: &str, expected: Option<usize>) {
+		let actual = super::fuzzy_score(pattern, text);
+		assert_eq!(expected, actual);
+	}
+}
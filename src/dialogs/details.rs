@@ -0,0 +1,51 @@
+use std::future::Future;
+
+use slint::spawn_local;
+use slint::CloseRequestResponse;
+use slint::ComponentHandle;
+use slint::Weak;
+
+use crate::dialogs::SingleResult;
+use crate::guiutils::modal::Modal;
+use crate::ui::DetailsDialog;
+
+/// Shows details about a machine or software; some details (e.g. - the ROM listing) come from MAME and can take a
+/// moment, so `more_text` is filled in when ready
+pub async fn dialog_details(
+	parent: Weak<impl ComponentHandle + 'static>,
+	subject: &str,
+	details_text: String,
+	more_text: impl Future<Output = String> + 'static,
+) {
+	// prepare the dialog
+	let modal = Modal::new(&parent.unwrap(), || DetailsDialog::new().unwrap());
+	let single_result = SingleResult::default();
+	modal.dialog().set_subject(subject.into());
+	modal.dialog().set_details_text(details_text.into());
+
+	// fill in the rest once we have it
+	let dialog_weak = modal.dialog().as_weak();
+	let fut = async move {
+		let more_text = more_text.await;
+		if let Some(dialog) = dialog_weak.upgrade() {
+			dialog.set_more_text(more_text.into());
+		}
+	};
+	spawn_local(fut).unwrap();
+
+	// set up the "close" button
+	let signaller = single_result.signaller();
+	modal.dialog().on_close_clicked(move || {
+		signaller.signal(());
+	});
+
+	// set up the close handler
+	let signaller = single_result.signaller();
+	modal.window().on_close_requested(move || {
+		signaller.signal(());
+		CloseRequestResponse::KeepWindowShown
+	});
+
+	// present the modal dialog
+	modal.run(async { single_result.wait().await }).await
+}
@@ -1,6 +1,7 @@
 use std::any::Any;
 use std::borrow::Cow;
 use std::cell::RefCell;
+use std::collections::HashSet;
 use std::path::Path;
 
 use slint::CloseRequestResponse;
@@ -23,6 +24,7 @@ use crate::guiutils::menuing::MenuDesc;
 use crate::guiutils::menuing::MenuExt;
 use crate::guiutils::modal::Modal;
 use crate::guiutils::MenuingType;
+use crate::i18n::tr;
 use crate::platform::WindowExt;
 use crate::status::Status;
 use crate::ui::DeviceAndImageEntry;
@@ -41,8 +43,12 @@ pub async fn dialog_devices_and_images(
 
 	// set up the model
 	let none_string = SharedString::from("<<none>>");
+	let rows = diconfig.visible_entries(&HashSet::new(), "");
 	let model = DevicesAndImagesModel {
 		diconfig: RefCell::new(diconfig),
+		collapsed: RefCell::new(HashSet::new()),
+		search: RefCell::new(String::new()),
+		rows: RefCell::new(rows),
 		dialog_weak: modal.dialog().as_weak(),
 		menuing_type,
 		none_string: none_string.clone(),
@@ -76,23 +82,32 @@ pub async fn dialog_devices_and_images(
 
 	// set up callbacks
 	let model_clone = model.clone();
-	modal
-		.dialog()
-		.on_entry_option_changed(move |entry_index, new_option_name| {
-			let entry_index = entry_index.try_into().unwrap();
-			let new_option_name = (!new_option_name.is_empty()).then_some(new_option_name.as_str());
-			let model = DevicesAndImagesModel::get_model(&model_clone);
-			model.change_diconfig(|diconfig| {
-				let tag = diconfig.entry(entry_index).unwrap().tag;
-				Some(diconfig.set_slot_option(tag, new_option_name))
-			});
+	modal.dialog().on_entry_option_changed(move |row, new_option_name| {
+		let new_option_name = (!new_option_name.is_empty()).then_some(new_option_name.as_str());
+		let model = DevicesAndImagesModel::get_model(&model_clone);
+		let entry_index = model.entry_index(row.try_into().unwrap());
+		model.change_diconfig(|diconfig| {
+			let tag = diconfig.entry(entry_index).unwrap().tag;
+			Some(diconfig.set_slot_option(tag, new_option_name))
 		});
+	});
 	let model_clone = model.clone();
-	modal.dialog().on_entry_button_clicked(move |entry_index, point| {
+	modal.dialog().on_entry_button_clicked(move |row, point| {
 		let model = DevicesAndImagesModel::get_model(&model_clone);
-		let entry_index = entry_index.try_into().unwrap();
+		let entry_index = model.entry_index(row.try_into().unwrap());
 		entry_popup_menu(model, entry_index, point);
 	});
+	let model_clone = model.clone();
+	modal.dialog().on_entry_expand_clicked(move |row| {
+		let model = DevicesAndImagesModel::get_model(&model_clone);
+		model.toggle_expanded(model.entry_index(row.try_into().unwrap()));
+	});
+	let model_clone = model.clone();
+	modal.dialog().on_search_changed(move |search| {
+		let model = DevicesAndImagesModel::get_model(&model_clone);
+		model.search.replace(search.into());
+		model.update_rows();
+	});
 
 	// subscribe to status changes
 	let model_clone = model.clone();
@@ -134,11 +149,11 @@ fn entry_popup_menu(model: &DevicesAndImagesModel, entry_index: usize, point: Lo
 			command.into()
 		});
 		[
-			MenuDesc::Item("Create Image...".into(), None),
-			MenuDesc::Item("Load Image...".into(), load_command),
-			MenuDesc::Item("Load Software List Part...".into(), None),
-			MenuDesc::Item("Connect To Socket...".into(), connect_socket_command),
-			MenuDesc::Item("Unload".into(), unload_command),
+			MenuDesc::Item(tr("Create Image..."), None),
+			MenuDesc::Item(tr("Load Image..."), load_command),
+			MenuDesc::Item(tr("Load Software List Part..."), None),
+			MenuDesc::Item(tr("Connect To Socket..."), connect_socket_command),
+			MenuDesc::Item(tr("Unload"), unload_command),
 		]
 	});
 	let popup_menu = MenuDesc::make_popup_menu(menu_items);
@@ -157,6 +172,14 @@ fn entry_popup_menu(model: &DevicesAndImagesModel, entry_index: usize, point: Lo
 
 struct DevicesAndImagesModel {
 	diconfig: RefCell<DevicesImagesConfig>,
+
+	/// Tags of slots whose children are hidden
+	collapsed: RefCell<HashSet<String>>,
+	search: RefCell<String>,
+
+	/// The entry shown on each row
+	rows: RefCell<Vec<usize>>,
+
 	dialog_weak: Weak<DevicesAndImagesDialog>,
 	menuing_type: MenuingType,
 	none_string: SharedString,
@@ -178,16 +201,47 @@ impl DevicesAndImagesModel {
 			}
 		};
 
-		// notify row changes (if any)
-		if let Some(range) = range {
-			for row in range {
-				self.notify.row_changed(row);
+		// notify row changes (if any); these are in terms of entries, which map to rows unless the tree changed shape
+		let rows = self.visible_entries();
+		if let Some(range) = range.filter(|_| rows == *self.rows.borrow()) {
+			for entry_index in range {
+				if let Some(row) = rows.iter().position(|&x| x == entry_index) {
+					self.notify.row_changed(row);
+				}
 			}
 		} else {
+			self.rows.replace(rows);
 			self.notify.reset();
 		}
 	}
 
+	pub fn entry_index(&self, row: usize) -> usize {
+		self.rows.borrow()[row]
+	}
+
+	pub fn toggle_expanded(&self, entry_index: usize) {
+		let tag = self.with_diconfig(|diconfig| diconfig.entry(entry_index).unwrap().tag.to_string());
+		{
+			let mut collapsed = self.collapsed.borrow_mut();
+			if !collapsed.remove(&tag) {
+				collapsed.insert(tag);
+			}
+		}
+		self.update_rows();
+	}
+
+	fn update_rows(&self) {
+		let rows = self.visible_entries();
+		self.rows.replace(rows);
+		self.notify.reset();
+	}
+
+	fn visible_entries(&self) -> Vec<usize> {
+		let collapsed = self.collapsed.borrow();
+		let search = self.search.borrow();
+		self.with_diconfig(|diconfig| diconfig.visible_entries(&collapsed, &search))
+	}
+
 	pub fn with_diconfig<R>(&self, callback: impl FnOnce(&DevicesImagesConfig) -> R) -> R {
 		let diconfig = self.diconfig.borrow();
 		callback(&diconfig)
@@ -202,17 +256,20 @@ impl Model for DevicesAndImagesModel {
 	type Data = DeviceAndImageEntry;
 
 	fn row_count(&self) -> usize {
-		self.diconfig.borrow().entry_count()
+		self.rows.borrow().len()
 	}
 
 	fn row_data(&self, row: usize) -> Option<Self::Data> {
 		// retrieve the entry
+		let entry_index = *self.rows.borrow().get(row)?;
 		let diconfig = self.diconfig.borrow();
-		let entry = diconfig.entry(row)?;
+		let entry = diconfig.entry(entry_index)?;
 
-		// basic indent and display tag stuff
+		// basic indent and display tag stuff; while searching everything is expanded
 		let display_tag = SharedString::from(entry.subtag);
 		let indent = entry.indent.try_into().unwrap();
+		let has_children = entry.has_children;
+		let expanded = !self.search.borrow().is_empty() || !self.collapsed.borrow().contains(entry.tag);
 
 		// now figure out the slot/image-specific details
 		let (options, current_option_index, filename) = match entry.details {
@@ -257,6 +314,8 @@ impl Model for DevicesAndImagesModel {
 		let result = DeviceAndImageEntry {
 			display_tag,
 			indent,
+			has_children,
+			expanded,
 			option_names,
 			option_descriptions,
 			current_option_index,
@@ -1,3 +1,5 @@
+use std::fs::read_dir;
+use std::fs::File;
 use std::path::Path;
 
 use derive_enum_all_values::AllValues;
@@ -7,6 +9,7 @@ use serde::Deserialize;
 use serde::Serialize;
 use slint::ComponentHandle;
 
+use crate::prefs::MovieFormat;
 use crate::prefs::PrefsPaths;
 
 const EXE_EXTENSION: &str = if cfg!(target_os = "windows") { "exe" } else { "" };
@@ -22,14 +25,28 @@ pub enum PathType {
 	Roms,
 	#[strum(to_string = "Samples")]
 	Samples,
+	#[strum(to_string = "Artwork")]
+	Artwork,
+	#[strum(to_string = "Cheats")]
+	Cheats,
 	#[strum(to_string = "Software Lists")]
 	SoftwareLists,
 	#[strum(to_string = "Plugins")]
 	Plugins,
+	#[strum(to_string = "Snapshots")]
+	Snapshots,
 	#[strum(to_string = "MAME Configs")]
 	Cfg,
 	#[strum(to_string = "NVRAM")]
 	Nvram,
+	#[strum(to_string = "MAME INIs")]
+	Ini,
+	#[strum(to_string = "History File")]
+	History,
+	#[strum(to_string = "Category File")]
+	Category,
+	#[strum(to_string = "Languages File")]
+	Languages,
 }
 
 impl PathType {
@@ -46,17 +63,83 @@ impl PathType {
 				name: "MAME Executable",
 				extension: EXE_EXTENSION,
 			},
-			Self::Roms | Self::Samples | Self::SoftwareLists | Self::Plugins | Self::Cfg | Self::Nvram => PickType::Dir,
+			Self::History => PickType::File {
+				name: "History File",
+				extension: "xml",
+			},
+			Self::Category => PickType::File {
+				name: "Category File",
+				extension: "ini",
+			},
+			Self::Languages => PickType::File {
+				name: "Languages File",
+				extension: "ini",
+			},
+			Self::Roms
+			| Self::Samples
+			| Self::Artwork
+			| Self::Cheats
+			| Self::SoftwareLists
+			| Self::Plugins
+			| Self::Snapshots
+			| Self::Cfg
+			| Self::Nvram
+			| Self::Ini => PickType::Dir,
+		}
+	}
+
+	/// The directory conventionally found alongside the MAME executable for this path type, if any
+	pub fn standard_directory(&self) -> Option<&'static str> {
+		match self {
+			Self::MameExecutable | Self::History | Self::Category | Self::Languages => None,
+			Self::Roms => Some("roms"),
+			Self::Samples => Some("samples"),
+			Self::Artwork => Some("artwork"),
+			Self::Cheats => Some("cheat"),
+			Self::SoftwareLists => Some("hash"),
+			Self::Plugins => Some("plugins"),
+			Self::Snapshots => Some("snap"),
+			Self::Cfg => Some("cfg"),
+			Self::Nvram => Some("nvram"),
+			// MAME looks for `mame.ini` in its own directory too, so `ini/` alone would be a poor substitute
+			Self::Ini => None,
 		}
 	}
 
 	pub fn path_exists(&self, path: impl AsRef<Path>) -> bool {
-		std::fs::metadata(path)
-			.map(|metadata| match self.pick_type() {
-				PickType::File { .. } => metadata.is_file(),
-				PickType::Dir => metadata.is_dir(),
-			})
-			.unwrap_or_default()
+		self.path_problem(path).is_none()
+	}
+
+	/// Checks that a path exists, is the right kind of thing (a file or a directory) and can be read
+	pub fn path_problem(&self, path: impl AsRef<Path>) -> Option<PathProblem> {
+		let path = path.as_ref();
+		let Ok(metadata) = std::fs::metadata(path) else {
+			return Some(PathProblem::NotFound);
+		};
+		match self.pick_type() {
+			PickType::File { .. } if !metadata.is_file() => Some(PathProblem::NotAFile),
+			PickType::File { .. } if File::open(path).is_err() => Some(PathProblem::NotReadable),
+			PickType::Dir if !metadata.is_dir() => Some(PathProblem::NotADirectory),
+			PickType::Dir if read_dir(path).is_err() => Some(PathProblem::NotReadable),
+			_ => None,
+		}
+	}
+
+	/// The option in MAME's own `mame.ini` corresponding to this path type, if any
+	pub fn mame_ini_option(&self) -> Option<&'static str> {
+		match self {
+			Self::MameExecutable | Self::History | Self::Category | Self::Languages => None,
+			Self::Roms => Some("rompath"),
+			Self::Samples => Some("samplepath"),
+			Self::Artwork => Some("artpath"),
+			Self::Cheats => Some("cheatpath"),
+			Self::SoftwareLists => Some("hashpath"),
+			Self::Plugins => Some("pluginspath"),
+			Self::Snapshots => Some("snapshot_directory"),
+			Self::Cfg => Some("cfg_directory"),
+			Self::Nvram => Some("nvram_directory"),
+			Self::Ini => Some("inipath"),
+		}
 	}
 
 	pub fn load_from_prefs_paths(prefs_paths: &PrefsPaths, path_type: PathType) -> Vec<&String> {
@@ -89,14 +172,35 @@ impl PathType {
 			),
 			PathType::Roms => ((|x| &x.roms), PathsStore::Multiple(|x| &mut x.roms)),
 			PathType::Samples => ((|x| &x.samples), PathsStore::Multiple(|x| &mut x.samples)),
+			PathType::Artwork => ((|x| &x.artwork), PathsStore::Multiple(|x| &mut x.artwork)),
+			PathType::Cheats => ((|x| &x.cheats), PathsStore::Multiple(|x| &mut x.cheats)),
 			PathType::SoftwareLists => ((|x| &x.software_lists), PathsStore::Multiple(|x| &mut x.software_lists)),
 			PathType::Plugins => ((|x| &x.plugins), PathsStore::Multiple(|x| &mut x.plugins)),
+			PathType::Snapshots => ((|x| &x.snapshots), PathsStore::Multiple(|x| &mut x.snapshots)),
 			PathType::Cfg => ((|x| x.cfg.as_slice()), PathsStore::Single(|x| &mut x.cfg)),
 			PathType::Nvram => ((|x| x.nvram.as_slice()), PathsStore::Single(|x| &mut x.nvram)),
+			PathType::Ini => ((|x| &x.ini), PathsStore::Multiple(|x| &mut x.ini)),
+			PathType::History => ((|x| x.history.as_slice()), PathsStore::Single(|x| &mut x.history)),
+			PathType::Category => ((|x| x.category.as_slice()), PathsStore::Single(|x| &mut x.category)),
+			PathType::Languages => ((|x| x.languages.as_slice()), PathsStore::Single(|x| &mut x.languages)),
 		}
 	}
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, strum_macros::Display)]
+pub enum PathProblem {
+	#[strum(to_string = "Not found")]
+	NotFound,
+	#[strum(to_string = "Not a file")]
+	NotAFile,
+	#[strum(to_string = "Not a directory")]
+	NotADirectory,
+	#[strum(to_string = "Cannot be read")]
+	NotReadable,
+	#[strum(to_string = "Unknown path variable")]
+	UnknownVariable,
+}
+
 #[derive(Debug)]
 enum PathsStore {
 	Single(fn(&mut PrefsPaths) -> &mut Option<String>),
@@ -111,8 +215,123 @@ enum PickType {
 	Dir,
 }
 
-pub fn file_dialog(_parent: &impl ComponentHandle, path_type: PathType) -> Option<String> {
-	let dialog = FileDialog::new();
+/// Prompts for a file containing saved `mame -listxml` output
+pub fn listxml_file_dialog(_parent: &impl ComponentHandle) -> Option<String> {
+	let path = FileDialog::new()
+		.add_filter("MAME -listxml Output", &["xml"])
+		.pick_file()?;
+	path.into_os_string().into_string().ok()
+}
+
+/// Prompts for a MAME input recording to play back
+pub fn inp_file_dialog(_parent: &impl ComponentHandle, directory: Option<&Path>) -> Option<String> {
+	let mut dialog = FileDialog::new().add_filter("MAME Input Files", &["inp"]);
+	if let Some(directory) = directory {
+		dialog = dialog.set_directory(directory);
+	}
+	let path = dialog.pick_file()?;
+	path.into_os_string().into_string().ok()
+}
+
+/// Prompts for an image to use as a machine's reference snapshot
+pub fn reference_snapshot_file_dialog(_parent: &impl ComponentHandle) -> Option<String> {
+	let path = FileDialog::new().add_filter("PNG Images", &["png"]).pick_file()?;
+	path.into_os_string().into_string().ok()
+}
+
+/// Prompts for where to save a screenshot
+pub fn screenshot_file_dialog(
+	_parent: &impl ComponentHandle,
+	directory: Option<&Path>,
+	file_name: Option<&str>,
+) -> Option<String> {
+	let mut dialog = FileDialog::new().add_filter("PNG Images", &["png"]);
+	if let Some(directory) = directory {
+		dialog = dialog.set_directory(directory);
+	}
+	if let Some(file_name) = file_name {
+		dialog = dialog.set_file_name(file_name);
+	}
+	let path = dialog.save_file()?;
+	path.into_os_string().into_string().ok()
+}
+
+/// Prompts for where to save a movie
+pub fn movie_file_dialog(
+	_parent: &impl ComponentHandle,
+	movie_format: MovieFormat,
+	directory: Option<&Path>,
+) -> Option<String> {
+	let name = format!("{movie_format} Movies");
+	let mut dialog = FileDialog::new().add_filter(name, &[movie_format.extension()]);
+	if let Some(directory) = directory {
+		dialog = dialog.set_directory(directory);
+	}
+	let path = dialog.save_file()?;
+	path.into_os_string().into_string().ok()
+}
+
+/// Prompts for a frontend macro to play back
+pub fn macro_file_dialog(_parent: &impl ComponentHandle) -> Option<String> {
+	let path = FileDialog::new().add_filter("Macro Files", &["json"]).pick_file()?;
+	path.into_os_string().into_string().ok()
+}
+
+/// Prompts for where to save a recorded frontend macro
+pub fn save_macro_file_dialog(_parent: &impl ComponentHandle) -> Option<String> {
+	let path = FileDialog::new()
+		.add_filter("Macro Files", &["json"])
+		.set_file_name("Macro.json")
+		.save_file()?;
+	path.into_os_string().into_string().ok()
+}
+
+/// Prompts for a shared folder collection to import
+pub fn collection_file_dialog(_parent: &impl ComponentHandle) -> Option<String> {
+	let path = FileDialog::new()
+		.add_filter("Collection Files", &["json"])
+		.pick_file()?;
+	path.into_os_string().into_string().ok()
+}
+
+/// Prompts for where to export a folder collection
+pub fn save_collection_file_dialog(_parent: &impl ComponentHandle, name: &str) -> Option<String> {
+	let path = FileDialog::new()
+		.add_filter("Collection Files", &["json"])
+		.set_file_name(format!("{name}.json"))
+		.save_file()?;
+	path.into_os_string().into_string().ok()
+}
+
+/// Prompts for where to export orphaned folder items and collections
+pub fn orphans_file_dialog(_parent: &impl ComponentHandle) -> Option<String> {
+	let path = FileDialog::new()
+		.add_filter("JSON Files", &["json"])
+		.set_file_name("BletchMAME Missing Items.json")
+		.save_file()?;
+	path.into_os_string().into_string().ok()
+}
+
+/// Prompts for a directory (e.g. - a Dropbox folder) to synchronize preferences through
+pub fn sync_directory_dialog(_parent: &impl ComponentHandle) -> Option<String> {
+	let path = FileDialog::new().pick_folder()?;
+	path.into_os_string().into_string().ok()
+}
+
+/// Prompts for where to save a diagnostic report
+pub fn diagnostic_report_file_dialog(_parent: &impl ComponentHandle) -> Option<String> {
+	let path = FileDialog::new()
+		.add_filter("ZIP Files", &["zip"])
+		.set_file_name("BletchMAME Diagnostic Report.zip")
+		.save_file()?;
+	path.into_os_string().into_string().ok()
+}
+
+pub fn file_dialog(_parent: &impl ComponentHandle, path_type: PathType, directory: Option<&Path>) -> Option<String> {
+	let mut dialog = FileDialog::new();
+	if let Some(directory) = directory {
+		dialog = dialog.set_directory(directory);
+	}
 	let path = match path_type.pick_type() {
 		PickType::File { name, extension } => dialog.add_filter(name, &[extension]).pick_file(),
 		PickType::Dir => dialog.pick_folder(),
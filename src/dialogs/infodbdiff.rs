@@ -0,0 +1,32 @@
+use slint::CloseRequestResponse;
+use slint::ComponentHandle;
+use slint::Weak;
+
+use crate::dialogs::SingleResult;
+use crate::guiutils::modal::Modal;
+use crate::infodiff::InfoDbDiff;
+use crate::ui::InfoDbDiffDialog;
+
+/// Shows what changed between the previous InfoDb and the current one
+pub async fn dialog_infodb_diff(parent: Weak<impl ComponentHandle + 'static>, diff: &InfoDbDiff) {
+	// prepare the dialog
+	let modal = Modal::new(&parent.unwrap(), || InfoDbDiffDialog::new().unwrap());
+	let single_result = SingleResult::default();
+	modal.dialog().set_diff_text(diff.text().into());
+
+	// set up the "close" button
+	let signaller = single_result.signaller();
+	modal.dialog().on_close_clicked(move || {
+		signaller.signal(());
+	});
+
+	// set up the close handler
+	let signaller = single_result.signaller();
+	modal.window().on_close_requested(move || {
+		signaller.signal(());
+		CloseRequestResponse::KeepWindowShown
+	});
+
+	// present the modal dialog
+	modal.run(async { single_result.wait().await }).await
+}
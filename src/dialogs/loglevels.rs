@@ -0,0 +1,98 @@
+use std::rc::Rc;
+
+use slint::CloseRequestResponse;
+use slint::ComponentHandle;
+use slint::Model;
+use slint::ModelRc;
+use slint::VecModel;
+use slint::Weak;
+use tracing::Level;
+
+use crate::diagnostics::LOG_TARGETS;
+use crate::dialogs::SingleResult;
+use crate::guiutils::modal::Modal;
+use crate::i18n::tr;
+use crate::ui::LogLevelRow;
+use crate::ui::LogLevelsDialog;
+
+/// The choices in each combo box; `None` uses the default level
+const LEVEL_CHOICES: &[(Option<Level>, &str)] = &[
+	(None, "Default"),
+	(Some(Level::ERROR), "Error"),
+	(Some(Level::WARN), "Warning"),
+	(Some(Level::INFO), "Info"),
+	(Some(Level::DEBUG), "Debug"),
+	(Some(Level::TRACE), "Trace"),
+];
+
+/// Lets the user change the log level of each of `LOG_TARGETS`
+pub async fn dialog_log_levels(
+	parent: Weak<impl ComponentHandle + 'static>,
+	levels: Vec<Option<Level>>,
+) -> Option<Vec<Option<Level>>> {
+	// prepare the dialog
+	let modal = Modal::new(&parent.unwrap(), || LogLevelsDialog::new().unwrap());
+	let single_result = SingleResult::default();
+
+	// set up the rows
+	let level_names = LEVEL_CHOICES
+		.iter()
+		.map(|(_, name)| tr(name).into())
+		.collect::<Vec<_>>();
+	modal
+		.dialog()
+		.set_level_names(ModelRc::new(VecModel::from(level_names)));
+	let rows = LOG_TARGETS
+		.iter()
+		.zip(levels)
+		.map(|((name, _), level)| {
+			let level_index = LEVEL_CHOICES.iter().position(|(x, _)| *x == level).unwrap_or_default();
+			LogLevelRow {
+				name: tr(name).into(),
+				level_index: level_index.try_into().unwrap(),
+			}
+		})
+		.collect::<Vec<_>>();
+	let rows = Rc::new(VecModel::from(rows));
+	modal.dialog().set_rows(ModelRc::from(rows.clone()));
+
+	// set up the level changed handler
+	let rows_clone = rows.clone();
+	modal.dialog().on_level_changed(move |index, level_index| {
+		let index = usize::try_from(index).unwrap();
+		let row = LogLevelRow {
+			level_index,
+			..rows_clone.row_data(index).unwrap()
+		};
+		rows_clone.set_row_data(index, row);
+	});
+
+	// set up the "ok" button
+	let signaller = single_result.signaller();
+	modal.dialog().on_ok_clicked(move || {
+		let levels = rows
+			.iter()
+			.map(|row| {
+				let level_index = usize::try_from(row.level_index).unwrap_or_default();
+				LEVEL_CHOICES.get(level_index).and_then(|(level, _)| *level)
+			})
+			.collect();
+		signaller.signal(Some(levels));
+	});
+
+	// set up the "cancel" button
+	let signaller = single_result.signaller();
+	modal.dialog().on_cancel_clicked(move || {
+		signaller.signal(None);
+	});
+
+	// set up the close handler
+	let signaller = single_result.signaller();
+	modal.window().on_close_requested(move || {
+		signaller.signal(None);
+		CloseRequestResponse::KeepWindowShown
+	});
+
+	// present the modal dialog
+	modal.run(async { single_result.wait().await }).await
+}
@@ -0,0 +1,119 @@
+use std::time::Duration;
+
+use slint::spawn_local;
+use slint::CloseRequestResponse;
+use slint::ComponentHandle;
+use slint::Weak;
+
+use crate::appearance::apply_current_appearance;
+use crate::i18n::apply_translations;
+use crate::runtime::sessionlog::SessionLog;
+use crate::runtime::sessionlog::SessionLogEntry;
+use crate::runtime::sessionlog::SessionLogSource;
+use crate::ui::LogViewerDialog;
+
+/// How often the log viewer checks for new entries
+const REFRESH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Shows the log viewer; unlike most dialogs this is not modal, so that the log can be watched while using MAME
+pub fn dialog_log_viewer(log: SessionLog) {
+	let dialog = LogViewerDialog::new().unwrap();
+	apply_current_appearance(&dialog);
+	apply_translations(&dialog);
+
+	// set up the changed handler (when the filter changes)
+	let dialog_weak = dialog.as_weak();
+	let log_clone = log.clone();
+	dialog.on_changed(move || {
+		update_log_text(&dialog_weak.unwrap(), &log_clone);
+	});
+
+	// set up the clear handler
+	let dialog_weak = dialog.as_weak();
+	let log_clone = log.clone();
+	dialog.on_clear_clicked(move || {
+		log_clone.clear();
+		update_log_text(&dialog_weak.unwrap(), &log_clone);
+	});
+
+	// set up the close handlers
+	let dialog_weak = dialog.as_weak();
+	dialog.on_close_clicked(move || {
+		dialog_weak.unwrap().hide().unwrap();
+	});
+	dialog.window().on_close_requested(|| CloseRequestResponse::HideWindow);
+
+	// show the dialog, and keep it up to date
+	update_log_text(&dialog, &log);
+	dialog.show().unwrap();
+	let fut = refresh_log_viewer(dialog.as_weak(), log);
+	spawn_local(fut).unwrap();
+}
+
+async fn refresh_log_viewer(dialog_weak: Weak<LogViewerDialog>, log: SessionLog) {
+	let mut generation = log.generation();
+	while let Some(dialog) = dialog_weak.upgrade().filter(|dialog| dialog.window().is_visible()) {
+		if log.generation() != generation {
+			generation = log.generation();
+			update_log_text(&dialog, &log);
+		}
+		drop(dialog);
+		tokio::time::sleep(REFRESH_INTERVAL).await;
+	}
+}
+
+fn update_log_text(dialog: &LogViewerDialog, log: &SessionLog) {
+	let text = log_text(
+		&log.entries(),
+		dialog.get_show_stderr(),
+		dialog.get_show_traffic(),
+		&dialog.get_filter_text(),
+	);
+	dialog.set_log_text(text.into());
+}
+
+fn log_text(entries: &[SessionLogEntry], show_stderr: bool, show_traffic: bool, filter: &str) -> String {
+	let filter = filter.to_lowercase();
+	entries
+		.iter()
+		.filter_map(|entry| {
+			let prefix = match entry.source {
+				SessionLogSource::Stderr => show_stderr.then_some(""),
+				SessionLogSource::Command => show_traffic.then_some("> "),
+				SessionLogSource::Response => show_traffic.then_some("< "),
+			}?;
+			let matches = filter.is_empty() || entry.text.to_lowercase().contains(&filter);
+			matches.then(|| format!("{prefix}{}\n", entry.text))
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod test {
+	use test_case::test_case;
+
+	use crate::runtime::sessionlog::SessionLogEntry;
+	use crate::runtime::sessionlog::SessionLogSource;
+
+	#[test_case(0, true, true, "", "> START coco2b\n< @OK STATUS\nWarning: foo\n")]
+	#[test_case(1, false, true, "", "> START coco2b\n< @OK STATUS\n")]
+	#[test_case(2, true, false, "", "Warning: foo\n")]
+	#[test_case(3, true, true, "COCO", "> START coco2b\n")]
+	#[test_case(4, false, false, "", "")]
+	pub fn log_text(_index: usize, show_stderr: bool, show_traffic: bool, filter: &str, expected: &str) {
+		let entries = [
+			(SessionLogSource::Command, "START coco2b"),
+			(SessionLogSource::Response, "@OK STATUS"),
+			(SessionLogSource::Stderr, "Warning: foo"),
+		]
+		.into_iter()
+		.map(|(source, text)| SessionLogEntry {
+			source,
+			text: text.to_string(),
+		})
+		.collect::<Vec<_>>();
+
+		let actual = super::log_text(&entries, show_stderr, show_traffic, filter);
+		assert_eq!(expected, actual);
+	}
+}
@@ -11,6 +11,7 @@ use slint::Weak;
 
 use crate::dialogs::SingleResult;
 use crate::guiutils::modal::Modal;
+use crate::i18n::tr;
 use crate::ui::MessageBoxDialog;
 
 pub trait MessageBoxDefaults {
@@ -65,19 +66,19 @@ impl MessageBoxDefaults for OkCancel {
 
 pub async fn dialog_message_box<T>(
 	parent: Weak<impl ComponentHandle + 'static>,
-	title: impl Into<SharedString>,
+	title: impl AsRef<str>,
 	message: impl Into<SharedString>,
 ) -> T
 where
 	T: Display + MessageBoxDefaults + PartialEq + Clone + 'static,
 {
 	// normalization
-	let title = title.into();
+	let title = tr(title.as_ref()).into();
 	let message = message.into();
 
 	// get the values
 	let values = T::all_values();
-	let value_texts = values.iter().map(|x| format!("{}", x).into()).collect::<Vec<_>>();
+	let value_texts = values.iter().map(|x| tr(&format!("{}", x)).into()).collect::<Vec<_>>();
 
 	// determine accept/abort indexes
 	let accept_index = values.iter().position(|x| *x == T::accept()).unwrap();
@@ -94,7 +95,7 @@ async fn internal_dialog_message_box(
 	title: SharedString,
 	message: SharedString,
 	value_texts: Vec<SharedString>,
-	_accept_index: usize,
+	accept_index: usize,
 	abort_index: usize,
 ) -> usize {
 	// prepare the dialog
@@ -109,6 +110,8 @@ async fn internal_dialog_message_box(
 	modal.dialog().set_title_text(title);
 	modal.dialog().set_message_text(message);
 	modal.dialog().set_button_texts(value_texts);
+	modal.dialog().set_accept_index(accept_index.try_into().unwrap());
+	modal.dialog().set_abort_index(abort_index.try_into().unwrap());
 
 	// set button callbacks
 	let signaller = single_result.signaller();
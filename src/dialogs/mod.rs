@@ -3,13 +3,25 @@ use std::rc::Rc;
 
 use tokio::sync::Notify;
 
+pub mod appearance;
+pub mod commandpalette;
+pub mod details;
 pub mod devimages;
 pub mod file;
 pub mod image;
+pub mod infodbdiff;
+pub mod loglevels;
+pub mod logviewer;
 pub mod messagebox;
 pub mod namecollection;
+pub mod netplay;
+pub mod orphans;
 pub mod paths;
+pub mod recording;
+pub mod reset;
+pub mod shortcuts;
 pub mod socket;
+pub mod softwareparts;
 
 struct SingleResult<T>(Rc<(Notify, RefCell<Option<T>>)>);
 
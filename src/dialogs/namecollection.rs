@@ -8,21 +8,35 @@ use slint::Weak;
 
 use crate::dialogs::SingleResult;
 use crate::guiutils::modal::Modal;
+use crate::i18n::tr;
+use crate::i18n::tr_args;
 use crate::ui::NameCollectionDialog;
 
 async fn dialog_name_collection(
 	parent: Weak<impl ComponentHandle + 'static>,
-	title: impl Into<SharedString>,
+	title: impl AsRef<str>,
 	existing_names: Vec<String>,
 	default_name: impl Into<SharedString>,
+) -> Option<String> {
+	let is_good = move |new_name: &str| is_good_new_name(&existing_names, new_name);
+	dialog_enter_text(parent, title, default_name, is_good).await
+}
+
+async fn dialog_enter_text(
+	parent: Weak<impl ComponentHandle + 'static>,
+	title: impl AsRef<str>,
+	default_text: impl Into<SharedString>,
+	is_good: impl Fn(&str) -> bool + 'static,
 ) -> Option<String> {
 	// prepare the dialog
 	let modal = Modal::new(&parent.unwrap(), || NameCollectionDialog::new().unwrap());
 	let single_result = SingleResult::default();
 
-	// set the title and default name
-	modal.dialog().set_title_text(title.into());
-	modal.dialog().invoke_set_text(default_name.into());
+	// set the title and default text
+	let default_text = default_text.into();
+	modal.dialog().set_title_text(tr(title.as_ref()).into());
+	modal.dialog().set_ok_enabled(is_good(&default_text));
+	modal.dialog().invoke_set_text(default_text);
 
 	// set up the "ok" button
 	let signaller = single_result.signaller();
@@ -45,10 +59,10 @@ async fn dialog_name_collection(
 		CloseRequestResponse::KeepWindowShown
 	});
 
-	// we want the "ok" button to be disabled when bad names are proposed
+	// we want the "ok" button to be disabled when bad text is proposed
 	let dialog_weak = modal.dialog().as_weak();
-	modal.dialog().on_text_edited(move |new_name| {
-		let ok_enabled = is_good_new_name(&existing_names, &new_name);
+	modal.dialog().on_text_edited(move |new_text| {
+		let ok_enabled = is_good(&new_text);
 		dialog_weak.unwrap().set_ok_enabled(ok_enabled);
 	});
 
@@ -89,10 +103,27 @@ pub async fn dialog_rename_collection(
 	existing_names: Vec<String>,
 	old_name: String,
 ) -> Option<String> {
-	let title = format!("Rename Folder \"{}\"", old_name);
+	let title = tr_args("Rename Folder \"{name}\"", &[("name", &old_name)]);
 	dialog_name_collection(parent, title, existing_names, old_name).await
 }
 
+/// Prompts for the directory that captures of a machine or folder are saved into; tokens like "{machine}" are allowed
+pub async fn dialog_capture_directory(
+	parent: Weak<impl ComponentHandle + 'static>,
+	subject: &str,
+	directory: Option<String>,
+) -> Option<String> {
+	let title = tr_args("Capture Directory For \"{subject}\"", &[("subject", &subject)]);
+	let is_good = |directory: &str| !directory.trim().is_empty();
+	dialog_enter_text(parent, title, directory.unwrap_or_default(), is_good).await
+}
+
+/// Prompts for the WebDAV collection that preferences are synchronized through
+pub async fn dialog_sync_url(parent: Weak<impl ComponentHandle + 'static>, url: Option<String>) -> Option<String> {
+	let is_good = |url: &str| url.starts_with("https://") || url.starts_with("http://");
+	dialog_enter_text(parent, "WebDAV Sync URL", url.unwrap_or_default(), is_good).await
+}
+
 #[cfg(test)]
 mod test {
 	use test_case::test_case;
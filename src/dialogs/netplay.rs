@@ -0,0 +1,107 @@
+use slint::CloseRequestResponse;
+use slint::ComponentHandle;
+use slint::ModelRc;
+use slint::SharedString;
+use slint::VecModel;
+use slint::Weak;
+
+use crate::dialogs::SingleResult;
+use crate::guiutils::modal::Modal;
+use crate::prefs::PrefsNetplay;
+use crate::ui::NetplayDialog;
+
+/// Prompts for the network settings to link a machine with another instance of MAME; presets are recently used
+/// settings, most recent first
+pub async fn dialog_netplay(
+	parent: Weak<impl ComponentHandle + 'static>,
+	machine_desc: &str,
+	presets: Vec<PrefsNetplay>,
+) -> Option<PrefsNetplay> {
+	// prepare the dialog
+	let modal = Modal::new(&parent.unwrap(), || NetplayDialog::new().unwrap());
+	let single_result = SingleResult::default();
+	modal.dialog().set_machine_desc(machine_desc.into());
+
+	// start out with the most recent settings
+	let initial = presets.first().cloned().unwrap_or_default();
+
+	// set up the presets combo box
+	let preset_descs = presets
+		.iter()
+		.map(|x| SharedString::from(x.description()))
+		.collect::<Vec<_>>();
+	modal.dialog().set_presets(ModelRc::new(VecModel::from(preset_descs)));
+	let dialog_weak = modal.dialog().as_weak();
+	modal.dialog().on_preset_selected(move || {
+		let dialog = dialog_weak.unwrap();
+		if let Some(preset) = usize::try_from(dialog.get_preset_index())
+			.ok()
+			.and_then(|index| presets.get(index))
+		{
+			set_fields(&dialog, preset);
+		}
+	});
+
+	// set up the accepted handler (when "Run" is clicked)
+	let signaller = single_result.signaller();
+	let dialog_weak = modal.dialog().as_weak();
+	modal.dialog().on_accepted(move || {
+		if let Some(result) = get_results(&dialog_weak.unwrap()) {
+			signaller.signal(Some(result));
+		}
+	});
+
+	// set up the cancelled handler (when "Cancel" is clicked)
+	let signaller = single_result.signaller();
+	modal.dialog().on_cancelled(move || {
+		signaller.signal(None);
+	});
+
+	// set up the changed handler
+	let dialog_weak = modal.dialog().as_weak();
+	modal.dialog().on_changed(move || {
+		update_can_accept(&dialog_weak.unwrap());
+	});
+
+	// set up the close handler
+	let signaller = single_result.signaller();
+	modal.window().on_close_requested(move || {
+		signaller.signal(None);
+		CloseRequestResponse::KeepWindowShown
+	});
+
+	// set up defaults
+	set_fields(modal.dialog(), &initial);
+
+	// present the modal dialog
+	modal.run(async { single_result.wait().await }).await
+}
+
+fn set_fields(dialog: &NetplayDialog, netplay: &PrefsNetplay) {
+	dialog.set_local_host_text(netplay.local_host.as_str().into());
+	dialog.set_local_port_text(netplay.local_port.to_string().into());
+	dialog.set_remote_host_text(netplay.remote_host.as_str().into());
+	dialog.set_remote_port_text(netplay.remote_port.to_string().into());
+	dialog.set_frame_sync(netplay.frame_sync);
+	update_can_accept(dialog);
+}
+
+fn update_can_accept(dialog: &NetplayDialog) {
+	let is_enabled = get_results(dialog).is_some();
+	dialog.set_can_accept(is_enabled);
+}
+
+fn get_results(dialog: &NetplayDialog) -> Option<PrefsNetplay> {
+	let local_host = dialog.get_local_host_text().trim().to_string();
+	let remote_host = dialog.get_remote_host_text().trim().to_string();
+	let local_port = dialog.get_local_port_text().parse().ok()?;
+	let remote_port = dialog.get_remote_port_text().parse().ok()?;
+	let is_valid = hostname_validator::is_valid(&local_host) && hostname_validator::is_valid(&remote_host);
+	is_valid.then(|| PrefsNetplay {
+		local_host,
+		local_port,
+		remote_host,
+		remote_port,
+		frame_sync: dialog.get_frame_sync(),
+	})
+}
@@ -0,0 +1,109 @@
+use std::rc::Rc;
+
+use anyhow::Error;
+use slint::spawn_local;
+use slint::CloseRequestResponse;
+use slint::ComponentHandle;
+use slint::Model;
+use slint::ModelRc;
+use slint::VecModel;
+use slint::Weak;
+
+use crate::collections::OrphanedEntry;
+use crate::dialogs::file::orphans_file_dialog;
+use crate::dialogs::messagebox::dialog_message_box;
+use crate::dialogs::messagebox::OkOnly;
+use crate::dialogs::SingleResult;
+use crate::guiutils::modal::Modal;
+use crate::ui::OrphanedEntriesDialog;
+use crate::ui::OrphanedEntryRow;
+
+/// Lets the user review orphaned folder items and collections; returns the ones to delete
+pub async fn dialog_orphaned_entries(
+	parent: Weak<impl ComponentHandle + 'static>,
+	orphans: Vec<OrphanedEntry>,
+) -> Option<Vec<OrphanedEntry>> {
+	// prepare the dialog
+	let modal = Modal::new(&parent.unwrap(), || OrphanedEntriesDialog::new().unwrap());
+	let single_result = SingleResult::default();
+
+	// set up the entries model; everything starts out checked
+	let rows = orphans
+		.iter()
+		.map(|orphan| OrphanedEntryRow {
+			description: orphan.to_string().into(),
+			checked: true,
+		})
+		.collect::<Vec<_>>();
+	let rows = Rc::new(VecModel::from(rows));
+	modal.dialog().set_entries(ModelRc::from(rows.clone()));
+	modal.dialog().set_any_checked(!orphans.is_empty());
+	let orphans = Rc::new(orphans);
+
+	// set up the toggle handler
+	let dialog_weak = modal.dialog().as_weak();
+	let rows_clone = rows.clone();
+	modal.dialog().on_entry_toggled(move |index, checked| {
+		let index = usize::try_from(index).unwrap();
+		let mut row = rows_clone.row_data(index).unwrap();
+		row.checked = checked;
+		rows_clone.set_row_data(index, row);
+		let any_checked = rows_clone.iter().any(|x| x.checked);
+		dialog_weak.unwrap().set_any_checked(any_checked);
+	});
+
+	// set up the "export" button
+	let dialog_weak = modal.dialog().as_weak();
+	let rows_clone = rows.clone();
+	let orphans_clone = orphans.clone();
+	modal.dialog().on_export_clicked(move || {
+		let dialog = dialog_weak.unwrap();
+		let Some(path) = orphans_file_dialog(&dialog) else {
+			return;
+		};
+		let checked = checked_orphans(&orphans_clone, &rows_clone);
+		let result = serde_json::to_string_pretty(&checked)
+			.map_err(Error::from)
+			.and_then(|json| std::fs::write(&path, json).map_err(Error::from));
+		if let Err(e) = result {
+			let parent = dialog.as_weak();
+			let fut = async move {
+				dialog_message_box::<OkOnly>(parent, "Export", format!("{e:?}")).await;
+			};
+			spawn_local(fut).unwrap();
+		}
+	});
+
+	// set up the "delete" button
+	let signaller = single_result.signaller();
+	let orphans_clone = orphans.clone();
+	modal.dialog().on_delete_clicked(move || {
+		let checked = checked_orphans(&orphans_clone, &rows);
+		signaller.signal(Some(checked));
+	});
+
+	// set up the "cancel" button
+	let signaller = single_result.signaller();
+	modal.dialog().on_cancel_clicked(move || {
+		signaller.signal(None);
+	});
+
+	// set up the close handler
+	let signaller = single_result.signaller();
+	modal.window().on_close_requested(move || {
+		signaller.signal(None);
+		CloseRequestResponse::KeepWindowShown
+	});
+
+	// present the modal dialog
+	modal.run(async { single_result.wait().await }).await
+}
+
+fn checked_orphans(orphans: &[OrphanedEntry], rows: &VecModel<OrphanedEntryRow>) -> Vec<OrphanedEntry> {
+	orphans
+		.iter()
+		.zip(rows.iter())
+		.filter(|(_, row)| row.checked)
+		.map(|(orphan, _)| orphan.clone())
+		.collect()
+}
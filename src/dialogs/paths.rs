@@ -2,8 +2,14 @@ use std::any::Any;
 use std::cell::RefCell;
 use std::default::Default;
 use std::fmt::Debug;
+use std::iter::once;
+use std::path::Path;
+use std::path::PathBuf;
+use std::path::MAIN_SEPARATOR;
 use std::rc::Rc;
 
+use itertools::Itertools;
+use slint::spawn_local;
 use slint::CloseRequestResponse;
 use slint::ComponentHandle;
 use slint::Model;
@@ -11,15 +17,24 @@ use slint::ModelNotify;
 use slint::ModelRc;
 use slint::ModelTracker;
 use slint::SharedString;
+use slint::StandardListViewItem;
 use slint::VecModel;
 use slint::Weak;
 
 use crate::dialogs::file::file_dialog;
+use crate::dialogs::file::PathProblem;
 use crate::dialogs::file::PathType;
+use crate::dialogs::messagebox::dialog_message_box;
+use crate::dialogs::messagebox::OkCancel;
 use crate::dialogs::SingleResult;
 use crate::guiutils::modal::Modal;
+use crate::i18n::tr;
+use crate::i18n::tr_args;
 use crate::icon::Icon;
+use crate::mameini::MameIni;
 use crate::prefs::PrefsPaths;
+use crate::runtime::args::expand_path;
+use crate::runtime::args::is_valid_variable_name;
 use crate::ui::MagicListViewItem;
 use crate::ui::PathsDialog;
 
@@ -38,7 +53,11 @@ impl Debug for State {
 	}
 }
 
-pub async fn dialog_paths(parent: Weak<impl ComponentHandle + 'static>, paths: Rc<PrefsPaths>) -> Option<PrefsPaths> {
+pub async fn dialog_paths(
+	parent: Weak<impl ComponentHandle + 'static>,
+	paths: Rc<PrefsPaths>,
+	initial_path_type: PathType,
+) -> Option<PrefsPaths> {
 	// prepare the dialog
 	let modal = Modal::new(&parent.unwrap(), || PathsDialog::new().unwrap());
 	let single_result = SingleResult::default();
@@ -57,6 +76,13 @@ pub async fn dialog_paths(parent: Weak<impl ComponentHandle + 'static>, paths: R
 	let path_labels = VecModel::from(path_labels);
 	let path_labels = ModelRc::new(path_labels);
 	modal.dialog().set_path_labels(path_labels);
+	let path_label_index = PathType::all_values()
+		.iter()
+		.position(|x| *x == initial_path_type)
+		.unwrap_or_default();
+	modal
+		.dialog()
+		.set_path_label_index(path_label_index.try_into().unwrap());
 
 	// set up the "ok" button
 	let signaller = single_result.signaller();
@@ -74,8 +100,12 @@ pub async fn dialog_paths(parent: Weak<impl ComponentHandle + 'static>, paths: R
 	let state_clone = state.clone();
 	modal.dialog().on_browse_clicked(move || {
 		let dialog = state_clone.dialog_weak.unwrap();
-		browse_clicked(&dialog);
+		let directory = browse_directory(&state_clone.paths.borrow(), path_type(&dialog));
+		browse_clicked(&dialog, directory.as_deref());
 		model_contents_changed(&state_clone);
+		if path_type(&dialog) == PathType::MameExecutable {
+			offer_standard_paths(state_clone.clone());
+		}
 	});
 
 	// set up the "delete" button
@@ -95,9 +125,13 @@ pub async fn dialog_paths(parent: Weak<impl ComponentHandle + 'static>, paths: R
 
 	// ensure paths entries are updated
 	let state_clone = state.clone();
-	let model: PathEntriesModel = PathEntriesModel::new(modal.dialog().as_weak(), move || {
-		model_contents_changed(&state_clone);
-	});
+	let changed_func = move || model_contents_changed(&state_clone);
+	let state_clone = state.clone();
+	let validate_func = move |path: &str| {
+		let dialog = state_clone.dialog_weak.unwrap();
+		validate_path(path_type(&dialog), path, &state_clone.paths.borrow())
+	};
+	let model = PathEntriesModel::new(modal.dialog().as_weak(), changed_func, validate_func);
 	let model = ModelRc::from(Rc::new(model));
 	modal.dialog().set_path_entries(model);
 	let state_clone = state.clone();
@@ -114,11 +148,47 @@ pub async fn dialog_paths(parent: Weak<impl ComponentHandle + 'static>, paths: R
 		update_buttons(&dialog);
 	});
 
+	// set up path variables
+	let state_clone = state.clone();
+	modal.dialog().on_variable_selected(move |index| {
+		let dialog = state_clone.dialog_weak.unwrap();
+		let paths = state_clone.paths.borrow();
+		let index = usize::try_from(index).ok();
+		if let Some((name, value)) = index.and_then(|index| paths.variables.iter().nth(index)) {
+			dialog.set_variable_name(name.into());
+			dialog.set_variable_value(value.into());
+		}
+		update_variable_buttons(&dialog, &paths);
+	});
+	let state_clone = state.clone();
+	modal.dialog().on_variable_edited(move || {
+		let dialog = state_clone.dialog_weak.unwrap();
+		update_variable_buttons(&dialog, &state_clone.paths.borrow());
+	});
+	let state_clone = state.clone();
+	modal.dialog().on_variable_set_clicked(move || {
+		let dialog = state_clone.dialog_weak.unwrap();
+		let name = dialog.get_variable_name().to_string();
+		let value = dialog.get_variable_value().to_string();
+		state_clone.paths.borrow_mut().variables.insert(name, value);
+		variables_changed(&state_clone);
+	});
+	let state_clone = state.clone();
+	modal.dialog().on_variable_remove_clicked(move || {
+		let dialog = state_clone.dialog_weak.unwrap();
+		let name = dialog.get_variable_name();
+		state_clone.paths.borrow_mut().variables.remove(name.as_str());
+		dialog.set_variable_name(SharedString::default());
+		dialog.set_variable_value(SharedString::default());
+		variables_changed(&state_clone);
+	});
+	update_variables(modal.dialog(), &state.paths.borrow());
+
 	// present the modal dialog
 	let accepted = modal.run(async { single_result.wait().await }).await;
 
 	// if the user hit "ok", return
-	accepted.then(|| Rc::try_unwrap(state).unwrap().paths.into_inner())
+	accepted.then(|| state.paths.borrow().clone())
 }
 
 fn path_type(dialog: &PathsDialog) -> PathType {
@@ -138,9 +208,9 @@ fn update_paths_entries(dialog: &PathsDialog, paths: &PrefsPaths) {
 	let paths_entries = path_entries
 		.into_iter()
 		.map(|path| {
-			let exists = path_type.path_exists(path);
+			let problem = validate_path(path_type, path, paths);
 			let path = SharedString::from(path);
-			(path, exists)
+			(path, problem)
 		})
 		.collect::<Vec<_>>();
 
@@ -149,9 +219,35 @@ fn update_paths_entries(dialog: &PathsDialog, paths: &PrefsPaths) {
 	model.update(paths_entries, path_type.is_multi());
 }
 
-fn browse_clicked(dialog: &PathsDialog) {
+/// Checks a path entry as it would be seen by MAME, after path variables are expanded
+fn validate_path(path_type: PathType, path: &str, paths: &PrefsPaths) -> Option<PathProblem> {
+	let Some(path) = expand_path(path, paths) else {
+		return Some(PathProblem::UnknownVariable);
+	};
+	path_type.path_problem(path)
+}
+
+/// Where "Browse..." should start; MAME's own `mame.ini` settings are preferred, followed by the standard directory
+/// alongside the MAME executable
+fn browse_directory(paths: &PrefsPaths, path_type: PathType) -> Option<PathBuf> {
+	let mame_executable = expand_path(paths.mame_executable.as_deref()?, paths)?;
+	let mame_executable = Path::new(&mame_executable);
+	let mame_dir = mame_executable.parent()?;
+	let from_ini = path_type
+		.mame_ini_option()
+		.zip(MameIni::load(mame_executable))
+		.and_then(|(option, ini)| ini.first_path(option, mame_executable));
+	let standard = path_type.standard_directory().map(|x| mame_dir.join(x));
+	[from_ini, standard]
+		.into_iter()
+		.flatten()
+		.find(|x| x.is_dir())
+		.or_else(|| Some(mame_dir.to_path_buf()))
+}
+
+fn browse_clicked(dialog: &PathsDialog, directory: Option<&Path>) {
 	let path_type = path_type(dialog);
-	let Some(path) = file_dialog(dialog, path_type) else {
+	let Some(path) = file_dialog(dialog, path_type, directory) else {
 		return;
 	};
 	let Ok(row) = usize::try_from(dialog.get_path_entry_index()) else {
@@ -159,7 +255,7 @@ fn browse_clicked(dialog: &PathsDialog) {
 	};
 	let model = dialog.get_path_entries();
 	let model = model.as_any().downcast_ref::<PathEntriesModel>().unwrap();
-	model.set_entry(row, &path, true);
+	model.set_entry(row, &path);
 }
 
 fn delete_clicked(dialog: &PathsDialog) {
@@ -193,6 +289,108 @@ fn model_contents_changed(state: &State) {
 	dialog.set_ok_enabled(*paths != **original_paths);
 }
 
+fn update_variables(dialog: &PathsDialog, paths: &PrefsPaths) {
+	let entries = paths
+		.variables
+		.iter()
+		.map(|(name, value)| StandardListViewItem::from(format!("$({name}) = {value}").as_str()))
+		.collect::<Vec<_>>();
+	dialog.set_variable_entries(ModelRc::new(VecModel::from(entries)));
+	update_variable_buttons(dialog, paths);
+}
+
+fn update_variable_buttons(dialog: &PathsDialog, paths: &PrefsPaths) {
+	let name = dialog.get_variable_name();
+	let value = dialog.get_variable_value();
+	let is_changed = paths.variables.get(name.as_str()).map(String::as_str) != Some(value.as_str());
+	dialog.set_variable_set_enabled(is_valid_variable_name(&name) && !value.is_empty() && is_changed);
+	dialog.set_variable_remove_enabled(paths.variables.contains_key(name.as_str()));
+}
+
+/// Path variables were set or removed; paths using them might now exist (or not)
+fn variables_changed(state: &State) {
+	let dialog = state.dialog_weak.unwrap();
+	let paths = state.paths.borrow();
+	update_variables(&dialog, &paths);
+	update_paths_entries(&dialog, &paths);
+	dialog.set_ok_enabled(*paths != *state.original_paths);
+}
+
+/// After the MAME executable is chosen, offer to use the standard directories found alongside it
+fn offer_standard_paths(state: Rc<State>) {
+	let detected = detect_standard_paths(&state.paths.borrow(), |path_type, path| path_type.path_exists(path));
+	if detected.is_empty() {
+		return;
+	}
+
+	let directories = detected
+		.iter()
+		.map(|(path_type, _)| {
+			format!(
+				"{}: {}",
+				tr(&path_type.to_string()),
+				path_type.standard_directory().unwrap()
+			)
+		})
+		.join("\n");
+	let message = tr_args(
+		"The following directories were found alongside the MAME executable:\n\n{directories}\n\nWould you like to use them?",
+		&[("directories", &directories)],
+	);
+	let parent = state.dialog_weak.clone();
+	let fut = async move {
+		if dialog_message_box::<OkCancel>(parent, "Paths", message).await == OkCancel::Ok {
+			apply_standard_paths(&state, &detected);
+		}
+	};
+	spawn_local(fut).unwrap();
+}
+
+/// Identifies standard directories alongside the MAME executable (`hash/`, `roms/` etc) that are not yet
+/// configured; paths are expressed relative to `$(MAMEPATH)`
+pub fn detect_standard_paths(
+	paths: &PrefsPaths,
+	path_exists: impl Fn(PathType, &str) -> bool,
+) -> Vec<(PathType, String)> {
+	PathType::all_values()
+		.iter()
+		.filter_map(|&path_type| {
+			let path = format!("$(MAMEPATH){MAIN_SEPARATOR}{}", path_type.standard_directory()?);
+			let expanded = expand_path(&path, paths)?;
+			let existing = PathType::load_from_prefs_paths(paths, path_type);
+			let already_configured = if path_type.is_multi() {
+				existing
+					.iter()
+					.any(|x| expand_path(x, paths).as_ref() == Some(&expanded))
+			} else {
+				!existing.is_empty()
+			};
+			(!already_configured && path_exists(path_type, &expanded)).then_some((path_type, path))
+		})
+		.collect()
+}
+
+/// Appends paths found by `detect_standard_paths()` to the configured paths
+pub fn add_standard_paths(paths: &mut PrefsPaths, detected: &[(PathType, String)]) {
+	for (path_type, path) in detected {
+		let entries = PathType::load_from_prefs_paths(paths, *path_type)
+			.into_iter()
+			.cloned()
+			.chain(once(path.clone()))
+			.collect::<Vec<_>>();
+		PathType::store_in_prefs_paths(paths, *path_type, entries.into_iter());
+	}
+}
+
+fn apply_standard_paths(state: &State, detected: &[(PathType, String)]) {
+	let mut paths = state.paths.borrow_mut();
+	add_standard_paths(&mut paths, detected);
+
+	let dialog = state.dialog_weak.unwrap();
+	update_paths_entries(&dialog, &paths);
+	dialog.set_ok_enabled(*paths != *state.original_paths);
+}
+
 fn assign_if_changed<T>(target: &mut T, source: T) -> bool
 where
 	T: PartialEq,
@@ -204,27 +402,36 @@ where
 	changed
 }
 
+type PathEntry = (SharedString, Option<PathProblem>);
+
 struct PathEntriesModel {
 	dialog_weak: Weak<PathsDialog>,
 	changed_func: Box<dyn Fn() + 'static>,
-	data: RefCell<(Vec<(SharedString, bool)>, bool)>,
+	validate_func: Box<dyn Fn(&str) -> Option<PathProblem> + 'static>,
+	data: RefCell<(Vec<PathEntry>, bool)>,
 	notify: ModelNotify,
 }
 
 impl PathEntriesModel {
-	pub fn new(dialog_weak: Weak<PathsDialog>, changed_func: impl Fn() + 'static) -> Self {
+	pub fn new(
+		dialog_weak: Weak<PathsDialog>,
+		changed_func: impl Fn() + 'static,
+		validate_func: impl Fn(&str) -> Option<PathProblem> + 'static,
+	) -> Self {
 		let changed_func = Box::new(changed_func) as Box<dyn Fn() + 'static>;
+		let validate_func = Box::new(validate_func) as Box<dyn Fn(&str) -> Option<PathProblem> + 'static>;
 		let data = RefCell::new((Vec::new(), false));
 		let notify = ModelNotify::default();
 		Self {
 			dialog_weak,
 			changed_func,
+			validate_func,
 			data,
 			notify,
 		}
 	}
 
-	pub fn update(&self, items: Vec<(SharedString, bool)>, is_multi: bool) {
+	pub fn update(&self, items: Vec<PathEntry>, is_multi: bool) {
 		self.data.replace((items, is_multi));
 		self.notify.reset();
 	}
@@ -250,8 +457,10 @@ impl PathEntriesModel {
 		data.0.iter().map(|(s, _)| s.clone()).collect()
 	}
 
-	pub fn set_entry(&self, row: usize, text: impl Into<SharedString>, exists: bool) {
-		let new_value = (text.into(), exists);
+	pub fn set_entry(&self, row: usize, text: impl Into<SharedString>) {
+		let text = text.into();
+		let problem = (self.validate_func)(&text);
+		let new_value = (text, problem);
 		let changed = if self.append_row_index() == Some(row) {
 			self.data.borrow_mut().0.push(new_value);
 			self.notify.row_added(row, 1);
@@ -268,14 +477,15 @@ impl PathEntriesModel {
 		}
 	}
 
-	fn make_entry(&self, text: impl Into<SharedString>, exists: bool) -> MagicListViewItem {
-		let prefix_icon = if exists { Icon::Clear } else { Icon::Blank };
+	fn make_entry(&self, text: impl Into<SharedString>, problem: Option<PathProblem>) -> MagicListViewItem {
+		let prefix_icon = if problem.is_none() { Icon::Clear } else { Icon::Blank };
 		let prefix_icon = prefix_icon.slint_icon(&self.dialog_weak.unwrap());
 		let text = text.into();
+		let supporting_text = problem.map(|x| x.to_string().into()).unwrap_or_default();
 		MagicListViewItem {
 			prefix_icon,
 			text,
-			supporting_text: Default::default(),
+			supporting_text,
 		}
 	}
 }
@@ -289,17 +499,17 @@ impl Model for PathEntriesModel {
 	}
 
 	fn row_data(&self, row: usize) -> Option<Self::Data> {
-		let (text, exists) = if self.append_row_index() == Some(row) {
-			("<          >".into(), true)
+		let (text, problem) = if self.append_row_index() == Some(row) {
+			("<          >".into(), None)
 		} else {
 			self.data.borrow().0.get(row)?.clone()
 		};
-		let data = self.make_entry(text, exists);
+		let data = self.make_entry(text, problem);
 		Some(data)
 	}
 
 	fn set_row_data(&self, row: usize, data: Self::Data) {
-		self.set_entry(row, data.text, false);
+		self.set_entry(row, data.text);
 	}
 
 	fn model_tracker(&self) -> &dyn ModelTracker {
@@ -310,3 +520,36 @@ impl Model for PathEntriesModel {
 		self
 	}
 }
+
+#[cfg(test)]
+mod test {
+	use std::path::MAIN_SEPARATOR;
+
+	use test_case::test_case;
+
+	use crate::dialogs::file::PathType;
+	use crate::prefs::PrefsPaths;
+
+	#[test_case(0, &[], &[])]
+	#[test_case(1, &["hash", "roms"], &[PathType::Roms, PathType::SoftwareLists])]
+	#[test_case(2, &["snap", "cfg", "nvram"], &[PathType::Snapshots, PathType::Nvram])]
+	#[test_case(3, &["plugins", "samples"], &[PathType::Samples])]
+	#[test_case(4, &["artwork", "samples"], &[PathType::Samples, PathType::Artwork])]
+	pub fn detect_standard_paths(_index: usize, existing_dirs: &[&str], expected: &[PathType]) {
+		let paths = PrefsPaths {
+			mame_executable: Some(format!("{MAIN_SEPARATOR}mame{MAIN_SEPARATOR}mame")),
+			plugins: vec![format!("$(MAMEPATH){MAIN_SEPARATOR}plugins")],
+			cfg: Some("/somewhere/else".to_string()),
+			..Default::default()
+		};
+		let existing_dirs = existing_dirs
+			.iter()
+			.map(|dir| format!("{MAIN_SEPARATOR}mame{MAIN_SEPARATOR}{dir}"))
+			.collect::<Vec<_>>();
+		let actual = super::detect_standard_paths(&paths, |_, path| existing_dirs.iter().any(|x| x == path))
+			.into_iter()
+			.map(|(path_type, _)| path_type)
+			.collect::<Vec<_>>();
+		assert_eq!(expected, actual.as_slice());
+	}
+}
@@ -0,0 +1,82 @@
+use slint::CloseRequestResponse;
+use slint::ComponentHandle;
+use slint::ModelRc;
+use slint::VecModel;
+use slint::Weak;
+
+use crate::dialogs::SingleResult;
+use crate::guiutils::modal::Modal;
+use crate::i18n::tr;
+use crate::prefs::MovieFormat;
+use crate::prefs::PrefsRecording;
+use crate::ui::RecordingSettingsDialog;
+
+pub async fn dialog_recording_settings(
+	parent: Weak<impl ComponentHandle + 'static>,
+	recording: PrefsRecording,
+) -> Option<PrefsRecording> {
+	// prepare the dialog
+	let modal = Modal::new(&parent.unwrap(), || RecordingSettingsDialog::new().unwrap());
+	let single_result = SingleResult::default();
+
+	// set up the "movie formats" combo box
+	let movie_formats = MovieFormat::all_values()
+		.iter()
+		.map(|x| format!("{}", *x).into())
+		.collect::<Vec<_>>();
+	let movie_formats = VecModel::from(movie_formats);
+	modal.dialog().set_movie_formats(ModelRc::new(movie_formats));
+	let movie_format_index = MovieFormat::all_values()
+		.iter()
+		.position(|x| *x == recording.movie_format)
+		.unwrap_or_default();
+	modal
+		.dialog()
+		.set_movie_format_index(movie_format_index.try_into().unwrap());
+	update_movie_format_notes(modal.dialog());
+
+	// set up the movie format changed handler
+	let dialog_weak = modal.dialog().as_weak();
+	modal.dialog().on_movie_format_changed(move || {
+		update_movie_format_notes(&dialog_weak.unwrap());
+	});
+
+	// set up the "ok" button
+	let signaller = single_result.signaller();
+	let dialog_weak = modal.dialog().as_weak();
+	modal.dialog().on_ok_clicked(move || {
+		let movie_format = selected_movie_format(&dialog_weak.unwrap());
+		let recording = PrefsRecording { movie_format };
+		signaller.signal(Some(recording));
+	});
+
+	// set up the "cancel" button
+	let signaller = single_result.signaller();
+	modal.dialog().on_cancel_clicked(move || {
+		signaller.signal(None);
+	});
+
+	// set up the close handler
+	let signaller = single_result.signaller();
+	modal.window().on_close_requested(move || {
+		signaller.signal(None);
+		CloseRequestResponse::KeepWindowShown
+	});
+
+	// present the modal dialog
+	modal.run(async { single_result.wait().await }).await
+}
+
+fn selected_movie_format(dialog: &RecordingSettingsDialog) -> MovieFormat {
+	let index = usize::try_from(dialog.get_movie_format_index()).unwrap_or_default();
+	MovieFormat::all_values().get(index).copied().unwrap_or_default()
+}
+
+fn update_movie_format_notes(dialog: &RecordingSettingsDialog) {
+	let notes = if selected_movie_format(dialog).has_sound() {
+		"AVI movies are uncompressed and include sound; expect large files."
+	} else {
+		"MNG movies are video only; use Record Audio to capture sound separately."
+	};
+	dialog.set_movie_format_notes(tr(notes).into());
+}
@@ -0,0 +1,64 @@
+use slint::CloseRequestResponse;
+use slint::ComponentHandle;
+use slint::Weak;
+
+use crate::dialogs::SingleResult;
+use crate::guiutils::modal::Modal;
+use crate::prefs::Preferences;
+use crate::prefs::ResetOptions;
+use crate::ui::ResetSettingsDialog;
+
+/// Asks the user to confirm resetting settings, previewing what will be lost and letting them keep paths and/or
+/// collections
+pub async fn dialog_reset_settings(
+	parent: Weak<impl ComponentHandle + 'static>,
+	prefs: Preferences,
+) -> Option<ResetOptions> {
+	// prepare the dialog
+	let modal = Modal::new(&parent.unwrap(), || ResetSettingsDialog::new().unwrap());
+	let single_result = SingleResult::default();
+
+	// the preview of what will be lost follows the check boxes
+	let update_losses = {
+		let dialog_weak = modal.dialog().as_weak();
+		move || {
+			let dialog = dialog_weak.unwrap();
+			let losses = prefs.reset_losses(options(&dialog));
+			let losses = losses.iter().map(|x| format!("\u{2022} {x}\n")).collect::<String>();
+			dialog.set_losses_text(losses.into());
+		}
+	};
+	update_losses();
+	modal.dialog().on_options_changed(update_losses);
+
+	// set up the "reset" button
+	let signaller = single_result.signaller();
+	let dialog_weak = modal.dialog().as_weak();
+	modal.dialog().on_reset_clicked(move || {
+		let options = options(&dialog_weak.unwrap());
+		signaller.signal(Some(options));
+	});
+
+	// set up the "cancel" button
+	let signaller = single_result.signaller();
+	modal.dialog().on_cancel_clicked(move || {
+		signaller.signal(None);
+	});
+
+	// set up the close handler
+	let signaller = single_result.signaller();
+	modal.window().on_close_requested(move || {
+		signaller.signal(None);
+		CloseRequestResponse::KeepWindowShown
+	});
+
+	// present the modal dialog
+	modal.run(async { single_result.wait().await }).await
+}
+
+fn options(dialog: &ResetSettingsDialog) -> ResetOptions {
+	ResetOptions {
+		keep_paths: dialog.get_keep_paths(),
+		keep_collections: dialog.get_keep_collections(),
+	}
+}
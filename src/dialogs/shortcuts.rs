@@ -0,0 +1,123 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use slint::CloseRequestResponse;
+use slint::ComponentHandle;
+use slint::Model;
+use slint::ModelRc;
+use slint::VecModel;
+use slint::Weak;
+
+use crate::appcommand::AppCommand;
+use crate::dialogs::SingleResult;
+use crate::guiutils::modal::Modal;
+use crate::i18n::tr;
+use crate::shortcuts::bindable_commands;
+use crate::shortcuts::shortcut_problems;
+use crate::ui::ShortcutEntry;
+use crate::ui::ShortcutsDialog;
+
+pub async fn dialog_shortcuts(
+	parent: Weak<impl ComponentHandle + 'static>,
+	shortcuts: BTreeMap<String, AppCommand>,
+) -> Option<BTreeMap<String, AppCommand>> {
+	// prepare the dialog
+	let modal = Modal::new(&parent.unwrap(), || ShortcutsDialog::new().unwrap());
+	let single_result = SingleResult::default();
+
+	// build our working list of bindings; one per bindable command
+	let commands = bindable_commands();
+	let accelerators = commands
+		.iter()
+		.map(|(command, _)| {
+			shortcuts
+				.iter()
+				.find(|(_, x)| *x == command)
+				.map(|(accel, _)| accel.clone())
+				.unwrap_or_default()
+		})
+		.collect::<Vec<_>>();
+	let accelerators = Rc::new(RefCell::new(accelerators));
+	let commands = Rc::new(commands);
+
+	// set up the entries model
+	let entries = Rc::new(VecModel::from(vec![ShortcutEntry::default(); commands.len()]));
+	modal.dialog().set_entries(ModelRc::from(entries.clone()));
+	update_entries(modal.dialog(), &entries, &commands, &accelerators.borrow());
+
+	// set up the edit handler
+	let dialog_weak = modal.dialog().as_weak();
+	let commands_clone = commands.clone();
+	let accelerators_clone = accelerators.clone();
+	modal.dialog().on_accelerator_edited(move |index, text| {
+		let index = usize::try_from(index).unwrap();
+		accelerators_clone.borrow_mut()[index] = text.trim().to_string();
+		update_entries(
+			&dialog_weak.unwrap(),
+			&entries,
+			&commands_clone,
+			&accelerators_clone.borrow(),
+		);
+	});
+
+	// set up the "ok" button
+	let signaller = single_result.signaller();
+	modal.dialog().on_ok_clicked(move || {
+		signaller.signal(true);
+	});
+
+	// set up the "cancel" button
+	let signaller = single_result.signaller();
+	modal.dialog().on_cancel_clicked(move || {
+		signaller.signal(false);
+	});
+
+	// set up the close handler
+	let signaller = single_result.signaller();
+	modal.window().on_close_requested(move || {
+		signaller.signal(false);
+		CloseRequestResponse::KeepWindowShown
+	});
+
+	// present the modal dialog
+	let accepted = modal.run(async { single_result.wait().await }).await;
+
+	// and return the results
+	accepted.then(|| {
+		commands
+			.iter()
+			.zip(accelerators.borrow().iter())
+			.filter(|(_, accel)| !accel.is_empty())
+			.map(|((command, _), accel)| (accel.clone(), command.clone()))
+			.collect()
+	})
+}
+
+fn update_entries(
+	dialog: &ShortcutsDialog,
+	entries: &VecModel<ShortcutEntry>,
+	commands: &[(AppCommand, &'static str)],
+	accelerators: &[String],
+) {
+	let shortcuts = commands
+		.iter()
+		.zip(accelerators)
+		.map(|((_, description), accel)| (accel.as_str(), *description))
+		.collect::<Vec<_>>();
+	let problems = shortcut_problems(&shortcuts);
+	let ok_enabled = problems.iter().all(Option::is_none);
+
+	// only touch rows that actually changed, so that we don't disrupt the line edit being typed into
+	for (index, ((accel, description), problem)) in shortcuts.iter().zip(problems).enumerate() {
+		let entry = ShortcutEntry {
+			description: tr(description).into(),
+			accelerator: (*accel).into(),
+			problem: problem.unwrap_or_default().into(),
+		};
+		if entries.row_data(index).as_ref() != Some(&entry) {
+			entries.set_row_data(index, entry);
+		}
+	}
+	dialog.set_ok_enabled(ok_enabled);
+}
@@ -0,0 +1,79 @@
+use std::rc::Rc;
+
+use slint::CloseRequestResponse;
+use slint::ComponentHandle;
+use slint::Model;
+use slint::ModelRc;
+use slint::SharedString;
+use slint::VecModel;
+use slint::Weak;
+
+use crate::dialogs::SingleResult;
+use crate::guiutils::modal::Modal;
+use crate::ui::SoftwarePartRow;
+use crate::ui::SoftwarePartsDialog;
+
+/// Lets the user choose which part of multi-part software goes into each device; each row is a device name, the
+/// choices for that device and the initially selected choice, and the result is the selected choice for each row
+pub async fn dialog_software_parts(
+	parent: Weak<impl ComponentHandle + 'static>,
+	software_description: &str,
+	rows: Vec<(String, Vec<String>, usize)>,
+) -> Option<Vec<usize>> {
+	// prepare the dialog
+	let modal = Modal::new(&parent.unwrap(), || SoftwarePartsDialog::new().unwrap());
+	let single_result = SingleResult::default();
+	modal.dialog().set_software_description(software_description.into());
+
+	// set up the rows
+	let rows = rows
+		.into_iter()
+		.map(|(device, choices, choice_index)| {
+			let choices = choices.into_iter().map(SharedString::from).collect::<Vec<_>>();
+			SoftwarePartRow {
+				device: device.into(),
+				choices: ModelRc::new(VecModel::from(choices)),
+				choice_index: choice_index.try_into().unwrap(),
+			}
+		})
+		.collect::<Vec<_>>();
+	let rows = Rc::new(VecModel::from(rows));
+	modal.dialog().set_rows(ModelRc::from(rows.clone()));
+
+	// set up the choice changed handler
+	let rows_clone = rows.clone();
+	modal.dialog().on_choice_changed(move |index, choice_index| {
+		let index = usize::try_from(index).unwrap();
+		let row = SoftwarePartRow {
+			choice_index,
+			..rows_clone.row_data(index).unwrap()
+		};
+		rows_clone.set_row_data(index, row);
+	});
+
+	// set up the "run" button
+	let signaller = single_result.signaller();
+	modal.dialog().on_ok_clicked(move || {
+		let choices = rows
+			.iter()
+			.map(|row| usize::try_from(row.choice_index).unwrap_or_default())
+			.collect();
+		signaller.signal(Some(choices));
+	});
+
+	// set up the "cancel" button
+	let signaller = single_result.signaller();
+	modal.dialog().on_cancel_clicked(move || {
+		signaller.signal(None);
+	});
+
+	// set up the close handler
+	let signaller = single_result.signaller();
+	modal.window().on_close_requested(move || {
+		signaller.signal(None);
+		CloseRequestResponse::KeepWindowShown
+	});
+
+	// present the modal dialog
+	modal.run(async { single_result.wait().await }).await
+}
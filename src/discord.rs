@@ -0,0 +1,160 @@
+//! Optional Discord Rich Presence, showing the running machine and how long it has been played
+//!
+//! Discord is reached over its local IPC socket on a background thread, so a missing or unresponsive Discord client
+//! never holds up the UI; if Discord is not running, updates are dropped and connecting is retried on the next one.
+//!
+//! Discord identifies the application by an ID from its developer portal, supplied at build time through the
+//! `BLETCHMAME_DISCORD_APPLICATION_ID` environment variable; builds without one cannot enable Rich Presence.
+use std::sync::mpsc::channel;
+use std::sync::mpsc::Receiver;
+use std::sync::mpsc::Sender;
+use std::thread::spawn;
+use std::thread::JoinHandle;
+use std::time::SystemTime;
+
+use anyhow::Error;
+use anyhow::Result;
+use discord_rich_presence::activity::Activity;
+use discord_rich_presence::activity::Timestamps;
+use discord_rich_presence::DiscordIpc;
+use discord_rich_presence::DiscordIpcClient;
+use tracing::event;
+use tracing::Level;
+
+const LOG: Level = Level::DEBUG;
+
+pub const DISCORD_APPLICATION_ID: Option<&str> = option_env!("BLETCHMAME_DISCORD_APPLICATION_ID");
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Presence {
+	machine_description: String,
+	is_paused: bool,
+
+	/// When play started, in seconds since the Unix epoch
+	start: i64,
+}
+
+pub struct DiscordPresence {
+	sender: Option<Sender<Option<Presence>>>,
+	handle: Option<JoinHandle<()>>,
+	current: Option<Presence>,
+}
+
+impl DiscordPresence {
+	pub fn start(application_id: &'static str) -> Self {
+		let (sender, receiver) = channel();
+		let handle = spawn(move || presence_thread(application_id, receiver));
+		Self {
+			sender: Some(sender),
+			handle: Some(handle),
+			current: None,
+		}
+	}
+
+	/// Reports the running machine's description and whether it is paused, or `None` when nothing is running
+	pub fn update(&mut self, running: Option<(&str, bool)>) {
+		let now = SystemTime::now()
+			.duration_since(SystemTime::UNIX_EPOCH)
+			.map(|x| x.as_secs() as i64)
+			.unwrap_or_default();
+		let presence = next_presence(self.current.as_ref(), running, now);
+		if presence != self.current {
+			event!(LOG, "DiscordPresence::update(): presence={:?}", presence);
+			self.current = presence.clone();
+			if let Some(sender) = &self.sender {
+				let _ = sender.send(presence);
+			}
+		}
+	}
+}
+
+impl Drop for DiscordPresence {
+	fn drop(&mut self) {
+		// closing the channel tells the thread to clear the presence and disconnect
+		self.sender.take();
+		if let Some(handle) = self.handle.take() {
+			let _ = handle.join();
+		}
+	}
+}
+
+/// The elapsed play time carries over as long as the same machine keeps running
+fn next_presence(current: Option<&Presence>, running: Option<(&str, bool)>, now: i64) -> Option<Presence> {
+	let (machine_description, is_paused) = running?;
+	let start = current
+		.filter(|x| x.machine_description == machine_description)
+		.map(|x| x.start)
+		.unwrap_or(now);
+	let presence = Presence {
+		machine_description: machine_description.to_string(),
+		is_paused,
+		start,
+	};
+	Some(presence)
+}
+
+fn presence_thread(application_id: &str, receiver: Receiver<Option<Presence>>) {
+	let mut client = None;
+	while let Ok(presence) = receiver.recv() {
+		// only the latest presence matters
+		let presence = receiver.try_iter().last().unwrap_or(presence);
+		if let Err(e) = publish(application_id, &mut client, presence.as_ref()) {
+			event!(LOG, "DiscordPresence: could not publish: {e:?}");
+			client = None;
+		}
+	}
+
+	if let Some(mut client) = client {
+		let _ = client.clear_activity();
+		let _ = client.close();
+	}
+}
+
+fn publish(application_id: &str, client: &mut Option<DiscordIpcClient>, presence: Option<&Presence>) -> Result<()> {
+	if client.is_none() {
+		let mut new_client = DiscordIpcClient::new(application_id).map_err(|e| Error::msg(e.to_string()))?;
+		new_client.connect().map_err(|e| Error::msg(e.to_string()))?;
+		*client = Some(new_client);
+	}
+	let client = client.as_mut().unwrap();
+
+	let result = if let Some(presence) = presence {
+		let state = if presence.is_paused { "Paused" } else { "Playing" };
+		let activity = Activity::new()
+			.details(&presence.machine_description)
+			.state(state)
+			.timestamps(Timestamps::new().start(presence.start));
+		client.set_activity(activity)
+	} else {
+		client.clear_activity()
+	};
+	result.map_err(|e| Error::msg(e.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+	use test_case::test_case;
+
+	use super::Presence;
+
+	#[test_case(0, None, None, None)]
+	#[test_case(1, None, Some(("Pac-Man", false)), Some(("Pac-Man", false, 1000)))]
+	#[test_case(2, Some(("Pac-Man", false, 500)), Some(("Pac-Man", true)), Some(("Pac-Man", true, 500)))]
+	#[test_case(3, Some(("Pac-Man", false, 500)), Some(("Galaga", false)), Some(("Galaga", false, 1000)))]
+	#[test_case(4, Some(("Pac-Man", false, 500)), None, None)]
+	pub fn next_presence(
+		_index: usize,
+		current: Option<(&str, bool, i64)>,
+		running: Option<(&str, bool)>,
+		expected: Option<(&str, bool, i64)>,
+	) {
+		let presence = |(machine_description, is_paused, start): (&str, bool, i64)| Presence {
+			machine_description: machine_description.to_string(),
+			is_paused,
+			start,
+		};
+		let current = current.map(presence);
+		let actual = super::next_presence(current.as_ref(), running, 1000);
+		assert_eq!(expected.map(presence), actual);
+	}
+}
@@ -0,0 +1,158 @@
+//! Detection of machines that exit shortly after being started, which usually means that they could not be run
+use std::time::Duration;
+use std::time::Instant;
+
+use crate::runtime::args::PreflightProblem;
+use crate::status::Running;
+
+/// Machines that exit within this amount of time of being started are considered to have failed
+const EARLY_EXIT_THRESHOLD: Duration = Duration::from_secs(10);
+
+/// How many lines of MAME's output to show
+const OUTPUT_TAIL_LINES: usize = 15;
+
+/// A machine that has been started by the user, and is being watched for an early exit
+#[derive(Debug)]
+pub struct Launch {
+	machine_name: String,
+	started_at: Instant,
+	last_running: Option<Running>,
+}
+
+impl Launch {
+	pub fn new(machine_name: impl Into<String>) -> Self {
+		Self {
+			machine_name: machine_name.into(),
+			started_at: Instant::now(),
+			last_running: None,
+		}
+	}
+
+	pub fn machine_name(&self) -> &str {
+		&self.machine_name
+	}
+
+	/// Notes the most recent status of the machine, once it is running
+	pub fn set_running(&mut self, running: Running) {
+		self.last_running = Some(running);
+	}
+
+	pub fn has_run(&self) -> bool {
+		self.last_running.is_some()
+	}
+
+	pub fn is_early_exit(&self, now: Instant) -> bool {
+		now.saturating_duration_since(self.started_at) < EARLY_EXIT_THRESHOLD
+	}
+
+	/// Our best guesses as to why the machine exited
+	pub fn likely_causes(&self, output: Option<&str>, preflight_problems: &[PreflightProblem]) -> Vec<String> {
+		let missing_files = output
+			.is_some_and(|x| x.to_lowercase().contains("required files are missing"))
+			.then(|| "ROM files for this machine are missing or incorrect; check the ROM paths".to_string());
+
+		let missing_images = self
+			.last_running
+			.iter()
+			.flat_map(|running| running.images.iter())
+			.filter(|image| image.details.must_be_loaded && image.filename.is_none())
+			.map(|image| format!("The \"{}\" device requires media to be loaded", image.tag));
+
+		let preflight = preflight_problems.iter().map(|problem| problem.to_string());
+
+		missing_files
+			.into_iter()
+			.chain(missing_images)
+			.chain(preflight)
+			.collect()
+	}
+}
+
+/// The text of the message shown to the user when a machine exits early
+pub fn early_exit_message(machine_description: &str, output: Option<&str>, causes: &[String]) -> String {
+	let mut message = format!("\"{machine_description}\" exited shortly after starting.\n\n");
+	if causes.is_empty() {
+		message.push_str("No likely cause could be determined.\n");
+	} else {
+		message.push_str("Likely causes:\n");
+		for cause in causes {
+			message.push_str(&format!("\u{2022} {cause}\n"));
+		}
+	}
+
+	if let Some(output) = output
+		.map(|x| tail_lines(x, OUTPUT_TAIL_LINES))
+		.filter(|x| !x.is_empty())
+	{
+		message.push_str("\nMAME output:\n");
+		message.push_str(output);
+	}
+	message
+}
+
+fn tail_lines(text: &str, count: usize) -> &str {
+	let text = text.trim_end();
+	let start = text
+		.rmatch_indices('\n')
+		.nth(count.saturating_sub(1))
+		.map(|(idx, _)| idx + 1)
+		.unwrap_or_default();
+	&text[start..]
+}
+
+#[cfg(test)]
+mod test {
+	use std::sync::Arc;
+
+	use test_case::test_case;
+
+	use crate::runtime::args::PreflightProblem;
+	use crate::status::Image;
+	use crate::status::ImageDetails;
+	use crate::status::Running;
+
+	use super::Launch;
+
+	#[test_case(0, "", 2, "")]
+	#[test_case(1, "alpha", 2, "alpha")]
+	#[test_case(2, "alpha\nbravo\ncharlie\n", 2, "bravo\ncharlie")]
+	#[test_case(3, "alpha\nbravo\ncharlie", 5, "alpha\nbravo\ncharlie")]
+	pub fn tail_lines(_index: usize, text: &str, count: usize, expected: &str) {
+		let actual = super::tail_lines(text, count);
+		assert_eq!(expected, actual);
+	}
+
+	#[test_case(0, None, None, &[], &[])]
+	#[test_case(1, Some("Required files are missing, the machine cannot be run."), None, &[], &["ROM files for this machine are missing or incorrect; check the ROM paths"])]
+	#[test_case(2, None, Some(("ext:fdc:wd17xx:0", None)), &[], &["The \"ext:fdc:wd17xx:0\" device requires media to be loaded"])]
+	#[test_case(3, None, Some(("ext:fdc:wd17xx:0", Some("foo.dsk"))), &[], &[])]
+	#[test_case(4, None, None, &[PreflightProblem::WorkerUiPluginNotFound], &["BletchMAME worker_ui plugin not found"])]
+	pub fn likely_causes(
+		_index: usize,
+		output: Option<&str>,
+		mandatory_image: Option<(&str, Option<&str>)>,
+		preflight_problems: &[PreflightProblem],
+		expected: &[&str],
+	) {
+		let mut launch = Launch::new("coco2b");
+		if let Some((tag, filename)) = mandatory_image {
+			let image = Image {
+				tag: tag.to_string(),
+				filename: filename.map(str::to_string),
+				details: ImageDetails {
+					must_be_loaded: true,
+					..Default::default()
+				},
+			};
+			let running = Running {
+				machine_name: "coco2b".to_string(),
+				images: Arc::from([image]),
+				..Default::default()
+			};
+			launch.set_running(running);
+		}
+
+		let actual = launch.likely_causes(output, preflight_problems);
+		assert_eq!(expected, actual.as_slice());
+	}
+}
@@ -18,6 +18,13 @@ use slint::VecModel;
 
 /// Helper function to declare accelerators
 pub fn accel(text: &str) -> Option<Accelerator> {
+	let result = parse_accel(text);
+	assert!(result.is_some(), "Unknown accelerator {text}");
+	result
+}
+
+/// Parses accelerator text (e.g. - "Ctrl+Shift+F7"), returning `None` if the text is not recognized
+pub fn parse_accel(text: &str) -> Option<Accelerator> {
 	fn strip_modifier<'a>(
 		text: &'a str,
 		mods: Option<Modifiers>,
@@ -35,14 +42,62 @@ pub fn accel(text: &str) -> Option<Accelerator> {
 	let (text, mods) = strip_modifier(text, mods, "Alt+", Modifiers::ALT);
 
 	let key = match text {
+		"A" => Code::KeyA,
+		"B" => Code::KeyB,
+		"C" => Code::KeyC,
+		"D" => Code::KeyD,
+		"E" => Code::KeyE,
+		"F" => Code::KeyF,
+		"G" => Code::KeyG,
+		"H" => Code::KeyH,
+		"I" => Code::KeyI,
+		"J" => Code::KeyJ,
+		"K" => Code::KeyK,
+		"L" => Code::KeyL,
+		"M" => Code::KeyM,
+		"N" => Code::KeyN,
+		"O" => Code::KeyO,
+		"P" => Code::KeyP,
+		"Q" => Code::KeyQ,
+		"R" => Code::KeyR,
+		"S" => Code::KeyS,
+		"T" => Code::KeyT,
+		"U" => Code::KeyU,
+		"V" => Code::KeyV,
+		"W" => Code::KeyW,
 		"X" => Code::KeyX,
+		"Y" => Code::KeyY,
+		"Z" => Code::KeyZ,
+		"0" => Code::Digit0,
+		"1" => Code::Digit1,
+		"2" => Code::Digit2,
+		"3" => Code::Digit3,
+		"4" => Code::Digit4,
+		"5" => Code::Digit5,
+		"6" => Code::Digit6,
+		"7" => Code::Digit7,
+		"8" => Code::Digit8,
+		"9" => Code::Digit9,
+		"F1" => Code::F1,
+		"F2" => Code::F2,
+		"F3" => Code::F3,
+		"F4" => Code::F4,
+		"F5" => Code::F5,
+		"F6" => Code::F6,
 		"F7" => Code::F7,
 		"F8" => Code::F8,
 		"F9" => Code::F9,
 		"F10" => Code::F10,
 		"F11" => Code::F11,
+		"F12" => Code::F12,
 		"Pause" => Code::Pause,
-		x => panic!("Unknown accelerator {x}"),
+		"Insert" => Code::Insert,
+		"Delete" => Code::Delete,
+		"Home" => Code::Home,
+		"End" => Code::End,
+		"PageUp" => Code::PageUp,
+		"PageDown" => Code::PageDown,
+		_ => return None,
 	};
 	Some(Accelerator::new(mods, key))
 }
@@ -56,9 +111,11 @@ pub struct MenuItemUpdate {
 /// Extension for muda menus
 pub trait MenuExt {
 	fn update(&self, callback: impl Fn(&MenuId) -> MenuItemUpdate);
+	fn update_accelerators(&self, callback: impl Fn(&MenuId) -> Option<Option<Accelerator>>);
 	fn slint_menu_entries(&self, sub_menu: Option<&SlintMenuEntry>) -> ModelRc<SlintMenuEntry>;
 	fn is_natively_supported() -> bool;
 	fn visit<B, C>(&self, init: C, func: impl Fn(C, &MenuItemKind) -> ControlFlow<B, C>) -> ControlFlow<B, C>;
+	fn enabled_commands(&self) -> Vec<(String, MenuId)>;
 }
 
 impl MenuExt for Menu {
@@ -93,6 +150,27 @@ impl MenuExt for Menu {
 		});
 	}
 
+	fn update_accelerators(&self, callback: impl Fn(&MenuId) -> Option<Option<Accelerator>>) {
+		self.visit((), |_, item| {
+			match item {
+				MenuItemKind::MenuItem(menu_item) => {
+					if let Some(accelerator) = callback(menu_item.id()) {
+						menu_item.set_accelerator(accelerator).unwrap();
+					}
+				}
+				MenuItemKind::Check(menu_item) => {
+					if let Some(accelerator) = callback(menu_item.id()) {
+						menu_item.set_accelerator(accelerator).unwrap();
+					}
+				}
+				_ => {
+					// do nothing
+				}
+			};
+			ControlFlow::<Infallible>::Continue(())
+		});
+	}
+
 	fn slint_menu_entries(&self, sub_menu: Option<&SlintMenuEntry>) -> ModelRc<SlintMenuEntry> {
 		// find the menu items we want to return
 		let items = if let Some(sub_menu) = sub_menu {
@@ -130,6 +208,29 @@ impl MenuExt for Menu {
 	fn visit<B, C>(&self, init: C, func: impl Fn(C, &MenuItemKind) -> ControlFlow<B, C>) -> ControlFlow<B, C> {
 		visit_menu_items(&self.items(), init, &func)
 	}
+
+	fn enabled_commands(&self) -> Vec<(String, MenuId)> {
+		let mut results = Vec::new();
+		collect_enabled_commands(&self.items(), "", &mut results);
+		results
+	}
+}
+
+/// Collects enabled menu items along with their full path (e.g. - "File > Stop")
+fn collect_enabled_commands(items: &[MenuItemKind], prefix: &str, results: &mut Vec<(String, MenuId)>) {
+	for item in items {
+		let (text, id) = match item {
+			MenuItemKind::MenuItem(menu_item) if menu_item.is_enabled() => (menu_item.text(), menu_item.id().clone()),
+			MenuItemKind::Check(menu_item) if menu_item.is_enabled() => (menu_item.text(), menu_item.id().clone()),
+			MenuItemKind::Submenu(sub_menu) if sub_menu.is_enabled() => {
+				let prefix = format!("{prefix}{} > ", sub_menu.text());
+				collect_enabled_commands(&sub_menu.items(), &prefix, results);
+				continue;
+			}
+			_ => continue,
+		};
+		results.push((format!("{prefix}{text}"), id));
+	}
 }
 
 fn visit_menu_items<B, C>(
@@ -219,4 +320,13 @@ mod test {
 		let actual = super::accel(text);
 		assert_eq!(Some(expected), actual);
 	}
+
+	#[test_case(0, "F12", Some(Accelerator::new(None, Code::F12)))]
+	#[test_case(1, "Ctrl+Shift+1", Some(Accelerator::new(Some(Modifiers::CONTROL | Modifiers::SHIFT), Code::Digit1)))]
+	#[test_case(2, "Ctrl+", None)]
+	#[test_case(3, "Bogus", None)]
+	pub fn parse_accel(_index: usize, text: &str, expected: Option<Accelerator>) {
+		let actual = super::parse_accel(text);
+		assert_eq!(expected, actual);
+	}
 }
@@ -1,14 +1,38 @@
+use std::any::type_name;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::future::Future;
 use std::rc::Rc;
 
 use slint::CloseRequestResponse;
 use slint::ComponentHandle;
+use slint::Global;
+use slint::LogicalSize;
 use slint::Window;
 use winit::window::WindowAttributes;
 
+use crate::appearance::apply_current_appearance;
 use crate::guiutils::hook::with_attributes_hook;
+use crate::i18n::apply_translations;
 use crate::platform::WindowAttributesExt;
 use crate::platform::WindowExt;
+use crate::prefs::PrefsDialogGeometry;
+use crate::ui::Appearance;
+use crate::ui::I18n;
+
+thread_local! {
+	static DIALOG_GEOMETRY: RefCell<BTreeMap<String, PrefsDialogGeometry>> = const { RefCell::new(BTreeMap::new()) };
+}
+
+/// Sets the geometry that dialogs are restored to when created, keyed by dialog type
+pub fn set_dialog_geometry(geometry: BTreeMap<String, PrefsDialogGeometry>) {
+	DIALOG_GEOMETRY.set(geometry);
+}
+
+/// The last known geometry of dialogs, keyed by dialog type
+pub fn dialog_geometry() -> BTreeMap<String, PrefsDialogGeometry> {
+	DIALOG_GEOMETRY.with_borrow(|geometry| geometry.clone())
+}
 
 pub struct Modal<D> {
 	reenable_parent: Rc<dyn Fn() + 'static>,
@@ -18,19 +42,29 @@ pub struct Modal<D> {
 impl<D> Modal<D>
 where
 	D: ComponentHandle + 'static,
+	for<'a> Appearance<'a>: Global<'a, D>,
+	for<'a> I18n<'a>: Global<'a, D>,
 {
 	pub fn new(parent: &(impl ComponentHandle + 'static), func: impl FnOnce() -> D) -> Self {
 		// disable the parent
 		parent.window().set_enabled_for_modal(false);
 
+		// were we here before?
+		let geometry = DIALOG_GEOMETRY.with_borrow(|geometry| geometry.get(dialog_key::<D>()).copied());
+
 		// set up a hook
 		let parent_weak = parent.as_weak();
 		let hook = move |window_attributes| {
-			set_window_attributes_for_modal_parent(window_attributes, parent_weak.unwrap().window())
+			set_window_attributes_for_modal_parent(window_attributes, parent_weak.unwrap().window(), geometry)
 		};
 
 		// invoke the func
 		let dialog = with_attributes_hook(func, hook);
+		apply_current_appearance(&dialog);
+		apply_translations(&dialog);
+		if let Some(geometry) = geometry {
+			dialog.window().set_size(LogicalSize::from(geometry.size));
+		}
 
 		// set up a bogus callback because the default callback won't do the right thing
 		dialog
@@ -60,7 +94,9 @@ where
 	pub fn launch(self) {
 		// stow a callback to reenable the parent here
 		let reenable_parent_clone = self.reenable_parent.clone();
+		let dialog_weak = self.dialog.as_weak();
 		self.window().on_close_requested(move || {
+			remember_geometry::<D>(dialog_weak.unwrap().window());
 			reenable_parent_clone();
 			CloseRequestResponse::HideWindow
 		});
@@ -76,7 +112,8 @@ where
 		// run the function
 		let result = fut.await;
 
-		// before we hide the dialog, reenable the parent
+		// before we hide the dialog, remember where it was and reenable the parent
+		remember_geometry::<D>(self.window());
 		(self.reenable_parent)();
 
 		// hide the dialog
@@ -90,11 +127,19 @@ where
 fn set_window_attributes_for_modal_parent(
 	mut window_attributes: WindowAttributes,
 	parent: &Window,
+	geometry: Option<PrefsDialogGeometry>,
 ) -> WindowAttributes {
-	let parent_position = parent.position();
-	let position = winit::dpi::PhysicalPosition {
-		x: parent_position.x + 64,
-		y: parent_position.y + 64,
+	let position = if let Some(geometry) = geometry {
+		winit::dpi::PhysicalPosition {
+			x: geometry.x,
+			y: geometry.y,
+		}
+	} else {
+		let parent_position = parent.position();
+		winit::dpi::PhysicalPosition {
+			x: parent_position.x + 64,
+			y: parent_position.y + 64,
+		}
 	};
 	window_attributes = window_attributes.with_owner_window(parent);
 	window_attributes.position = Some(position.into());
@@ -104,3 +149,30 @@ fn set_window_attributes_for_modal_parent(
 fn reenable_modal_parent(parent: &impl ComponentHandle) {
 	parent.window().set_enabled_for_modal(true);
 }
+
+fn remember_geometry<D>(window: &Window) {
+	let position = window.position();
+	let size = window.size().to_logical(window.scale_factor());
+	let geometry = PrefsDialogGeometry {
+		x: position.x,
+		y: position.y,
+		size: size.into(),
+	};
+	DIALOG_GEOMETRY.with_borrow_mut(|x| x.insert(dialog_key::<D>().to_string(), geometry));
+}
+
+/// The key geometry is stored under; the unqualified name of the dialog type (e.g. - "PathsDialog")
+fn dialog_key<D>() -> &'static str {
+	let name = type_name::<D>();
+	name.rsplit("::").next().unwrap_or(name)
+}
+
+#[cfg(test)]
+mod test {
+	struct FakeDialog;
+
+	#[test]
+	pub fn dialog_key() {
+		assert_eq!("FakeDialog", super::dialog_key::<FakeDialog>());
+	}
+}
@@ -0,0 +1,152 @@
+//! Test harness that drives the UI end to end against a mock MAME (see `runtime/mock.rs`) on Slint's testing
+//! backend, so that flows like launching, pausing and loading images can be tested without MAME or a display
+#![cfg_attr(not(test), allow(dead_code))]
+use std::future::Future;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use slint::quit_event_loop;
+use slint::run_event_loop;
+use slint::spawn_local;
+
+use crate::appwindow::AppArgs;
+use crate::appwindow::HarnessApp;
+use crate::diagnostics::LogFilter;
+use crate::diagnostics::LogRing;
+use crate::guiutils::MenuingType;
+use crate::info::InfoDb;
+use crate::runtime::mock::MockMame;
+use crate::runtime::MameStderr;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Runs `scenario` against the application; the Slint platform can only be set once, so this can only be invoked
+/// once per process
+pub fn run_harness<F, Fut>(prefs_path: PathBuf, mock: Arc<MockMame>, info_db: InfoDb, scenario: F)
+where
+	F: FnOnce(Rc<HarnessApp>) -> Fut,
+	Fut: Future<Output = ()> + 'static,
+{
+	i_slint_backend_testing::init_integration_test_with_system_time();
+	let tokio_runtime = tokio::runtime::Builder::new_multi_thread()
+		.enable_time()
+		.build()
+		.unwrap();
+	let _guard = tokio_runtime.enter();
+
+	let args = AppArgs {
+		prefs_path: Some(prefs_path),
+		mame_stderr: MameStderr::Inherit,
+		menuing_type: MenuingType::Slint,
+		safe_mode: false,
+		read_only_prefs: false,
+		log_ring: LogRing::default(),
+		log_filter: LogFilter::default(),
+		launch_request: None,
+	};
+	let app = Rc::new(HarnessApp::new(args, mock, Rc::new(info_db)));
+	let fut = scenario(app.clone());
+	let fut = async move {
+		fut.await;
+		quit_event_loop().unwrap();
+	};
+	spawn_local(fut).unwrap();
+	run_event_loop().unwrap();
+}
+
+/// Waits for `predicate` to hold, while letting the event loop process events from the session
+pub async fn wait_until(app: &HarnessApp, what: &str, predicate: impl Fn(&HarnessApp) -> bool) {
+	let deadline = Instant::now() + TIMEOUT;
+	while !predicate(app) {
+		assert!(Instant::now() < deadline, "Timed out waiting for {what}");
+		tokio::time::sleep(Duration::from_millis(10)).await;
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::sync::Arc;
+
+	use tempdir::TempDir;
+
+	use crate::appcommand::AppCommand;
+	use crate::info::InfoDb;
+	use crate::runtime::mock::MockMame;
+
+	use super::run_harness;
+	use super::wait_until;
+
+	const STATUS_IDLE: &str = include_str!("status/test_data/status_mame0270_1.xml");
+	const STATUS_PAUSED: &str = include_str!("status/test_data/status_mame0270_coco2b_1.xml");
+	const STATUS_RUNNING: &str = include_str!("status/test_data/status_mame0270_coco2b_2.xml");
+	const STATUS_IMAGES: &str = include_str!("status/test_data/status_mame0270_coco2b_4.xml");
+
+	#[test]
+	pub fn launch_pause_images_and_stop() {
+		let prefs_dir = TempDir::new("bletchmame_harness").unwrap();
+		let info_xml = include_str!("info/test_data/listxml_coco.xml");
+		let info_db = InfoDb::from_listxml_output(info_xml.as_bytes(), |_| false)
+			.unwrap()
+			.unwrap();
+		let mock = MockMame::new(STATUS_IDLE)
+			.respond_to("START", STATUS_PAUSED)
+			.respond_to("RESUME", STATUS_RUNNING)
+			.respond_to("LOAD", STATUS_IMAGES)
+			.respond_to("STOP", STATUS_IDLE);
+		let mock = Arc::new(mock);
+		let mock_clone = mock.clone();
+
+		run_harness(prefs_dir.path().to_path_buf(), mock, info_db, |app| async move {
+			let running = |app: &super::HarnessApp| app.status().and_then(|s| s.running);
+			wait_until(&app, "MAME to initialize", |app| {
+				app.status().is_some_and(|s| s.has_initialized)
+			})
+			.await;
+
+			// launch
+			app.handle_command(AppCommand::RunMame {
+				machine_name: "coco2b".into(),
+				initial_loads: Vec::new(),
+			});
+			wait_until(&app, "coco2b to start", |app| {
+				running(app).is_some_and(|r| r.machine_name == "coco2b" && r.is_paused)
+			})
+			.await;
+
+			// pause (which resumes, because the machine starts paused)
+			app.handle_command(AppCommand::FilePause);
+			wait_until(&app, "coco2b to resume", |app| {
+				running(app).is_some_and(|r| !r.is_paused)
+			})
+			.await;
+
+			// images
+			app.handle_command(AppCommand::LoadImage {
+				tag: "ext:fdc:wd17xx:0".into(),
+				filename: "/disks/foo.dsk".into(),
+			});
+			wait_until(&app, "the image to load", |_| {
+				mock_clone
+					.commands()
+					.iter()
+					.any(|x| x.starts_with("LOAD") && x.contains("/disks/foo.dsk"))
+			})
+			.await;
+
+			// stop
+			app.handle_command(AppCommand::FileStop);
+			wait_until(&app, "coco2b to stop", |app| running(app).is_none()).await;
+
+			let commands = mock_clone.commands();
+			let verbs = commands
+				.iter()
+				.filter_map(|x| x.split_whitespace().next())
+				.filter(|x| *x != "PING")
+				.collect::<Vec<_>>();
+			assert_eq!(vec!["START", "RESUME", "LOAD", "STOP"], verbs);
+		});
+	}
+}
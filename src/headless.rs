@@ -0,0 +1,253 @@
+//! Running a machine or folder item straight from the command line without the GUI; useful for shortcuts and
+//! launcher integration
+use std::path::Path;
+use std::sync::mpsc::channel;
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Error;
+use anyhow::Result;
+use tracing::event;
+use tracing::Level;
+
+use crate::info::InfoDb;
+use crate::info::View;
+use crate::models::columns::rank_software_machines;
+use crate::prefs::software_key;
+use crate::prefs::Preferences;
+use crate::prefs::PrefsCollection;
+use crate::prefs::PrefsItem;
+use crate::runtime::args::MameArgumentsSource;
+use crate::runtime::controller::MameController;
+use crate::runtime::sessionlog::SessionLog;
+use crate::runtime::MameCommand;
+use crate::runtime::MameEvent;
+use crate::runtime::MameStderr;
+use crate::runtime::MameWindowing;
+use crate::software::software_initial_loads;
+use crate::software::SoftwareListDispenser;
+use crate::status::Status;
+
+const LOG: Level = Level::DEBUG;
+
+const PING_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RunTarget {
+	Item(PrefsItem),
+	FolderItem { folder_name: String, index: usize },
+}
+
+#[derive(thiserror::Error, Debug)]
+enum ThisError {
+	#[error("No preferences found; run BletchMAME normally first to set it up")]
+	NoPreferences,
+	#[error("No MAME executable path specified")]
+	NoMameExecutablePath,
+	#[error("No folder named \"{0}\"")]
+	UnknownFolder(String),
+	#[error("Folder \"{0}\" has no item #{1}")]
+	UnknownFolderItem(String, usize),
+	#[error("Unknown software \"{0}:{1}\"")]
+	UnknownSoftware(String, String),
+	#[error("No machine can run software \"{0}:{1}\"")]
+	NoMachineForSoftware(String, String),
+}
+
+/// Parses the argument to `--run-item` (e.g. - "My Games:3"); folder names may themselves contain colons
+pub fn parse_run_item(s: &str) -> Result<(String, usize)> {
+	let (folder_name, index) = s
+		.rsplit_once(':')
+		.ok_or_else(|| Error::msg("Expected <folder>:<index>"))?;
+	Ok((folder_name.to_string(), index.parse()?))
+}
+
+/// Parses the argument to `--run-software` (e.g. - "coco_cart:mpak")
+pub fn parse_run_software(s: &str) -> Result<(String, String)> {
+	let (software_list, software) = s
+		.split_once(':')
+		.ok_or_else(|| Error::msg("Expected <software list>:<software>"))?;
+	Ok((software_list.to_string(), software.to_string()))
+}
+
+/// Runs the target in MAME, returning MAME's exit code once it exits
+pub fn run_headless(
+	prefs_path: Option<&Path>,
+	read_only_prefs: bool,
+	mame_stderr: MameStderr,
+	target: &RunTarget,
+) -> Result<i32> {
+	let prefs = Preferences::load(prefs_path)?.ok_or(ThisError::NoPreferences)?;
+	let (machine_name, initial_loads) = resolve_target(&prefs, prefs_path, read_only_prefs, target)?;
+	event!(
+		LOG,
+		"run_headless(): machine_name={machine_name:?} initial_loads={initial_loads:?}"
+	);
+
+	// preflight; the controller would otherwise quietly refuse to start MAME
+	let mame_windowing = prefs
+		.machine_window_sizes
+		.get(&machine_name)
+		.map_or(MameWindowing::Windowed, |&(width, height)| {
+			MameWindowing::WindowedSized(width, height)
+		});
+	MameArgumentsSource::new(&prefs.paths, &mame_windowing)?.preflight()?;
+
+	// start MAME, funneling its events to us
+	let (sender, receiver) = channel();
+	let controller = MameController::new(mame_stderr, SessionLog::default());
+	controller.set_event_callback(move |event| {
+		let _ = sender.send(event);
+	});
+	controller.reset(Some(&prefs.paths), &mame_windowing);
+
+	// the BIOS is a core option, so it needs to be specified before the machine starts
+	if let Some(bios) = prefs.machine_bios_sets.get(&machine_name) {
+		controller.issue_command(MameCommand::Bios(bios));
+	}
+	let initial_loads = initial_loads
+		.iter()
+		.map(|(dev, arg)| (dev.as_ref(), arg.as_ref()))
+		.collect::<Vec<_>>();
+	controller.issue_command(MameCommand::Start {
+		machine_name: &machine_name,
+		initial_loads: &initial_loads,
+	});
+
+	// and wait for the machine to stop, pinging much like the GUI would
+	let mut status = Status::default();
+	let mut exit_status = None;
+	loop {
+		match receiver.recv_timeout(PING_INTERVAL) {
+			Ok(MameEvent::StatusUpdate(update)) => {
+				let was_running = status.running.is_some();
+				status = status.merge(update);
+				if was_running && status.running.is_none() {
+					controller.issue_command(MameCommand::Exit);
+				}
+			}
+			Ok(MameEvent::SessionStarted) => {}
+			Ok(MameEvent::SessionEnded(x)) => {
+				exit_status = x;
+				break;
+			}
+			Err(RecvTimeoutError::Disconnected) => break,
+			Ok(MameEvent::Error(e)) => return Err(e),
+			Err(RecvTimeoutError::Timeout) => {
+				if status.running.is_some() && controller.is_queue_empty() {
+					controller.issue_command(MameCommand::Ping);
+				}
+			}
+		}
+	}
+	controller.reset(None, &mame_windowing);
+
+	// MAME being killed by a signal has no exit code, but is hardly a success
+	let exit_code = exit_status.map_or(0, |x| x.code().unwrap_or(1));
+	event!(LOG, "run_headless(): exit_status={exit_status:?} exit_code={exit_code}");
+	Ok(exit_code)
+}
+
+/// Identifies the machine to run, and any images to load
+fn resolve_target(
+	prefs: &Preferences,
+	prefs_path: Option<&Path>,
+	read_only_prefs: bool,
+	target: &RunTarget,
+) -> Result<(String, Vec<(Arc<str>, Arc<str>)>)> {
+	let item = match target {
+		RunTarget::Item(item) => item.clone(),
+		RunTarget::FolderItem { folder_name, index } => prefs
+			.collections
+			.iter()
+			.find_map(|collection| match collection.as_ref() {
+				PrefsCollection::Folder { name, items, .. } if name == folder_name => Some(items),
+				_ => None,
+			})
+			.ok_or_else(|| ThisError::UnknownFolder(folder_name.clone()))?
+			.get(*index)
+			.ok_or_else(|| ThisError::UnknownFolderItem(folder_name.clone(), *index))?
+			.clone(),
+	};
+
+	match item {
+		PrefsItem::Machine { machine_name } => Ok((machine_name, Vec::new())),
+		PrefsItem::Software {
+			software_list,
+			software,
+		} => {
+			// software needs the InfoDb to find a machine (and devices) to run it with
+			let mame_executable_path = prefs
+				.paths
+				.mame_executable
+				.as_deref()
+				.ok_or(ThisError::NoMameExecutablePath)?;
+			let info_db = load_or_rebuild_info_db(prefs_path, read_only_prefs, mame_executable_path)?;
+			let mut dispenser = SoftwareListDispenser::new(&info_db, &prefs.paths.software_lists);
+			let (info_list, list) = dispenser.get(&software_list)?;
+			let unknown_software = || ThisError::UnknownSoftware(software_list.clone(), software.clone());
+			let software_obj = list
+				.software
+				.iter()
+				.find(|x| x.name.as_ref() == software.as_str())
+				.ok_or_else(unknown_software)?;
+
+			// prefer the machine this software was last run on, just like the GUI
+			let machines = Iterator::chain(
+				info_list.original_for_machines().iter(),
+				info_list.compatible_for_machines().iter(),
+			);
+			let preferred_machine = prefs.software_machines.get(&software_key(&software_list, &software));
+			rank_software_machines(machines, preferred_machine.map(String::as_str))
+				.into_iter()
+				.find_map(|machine| {
+					let initial_loads =
+						software_initial_loads(machine, software_obj, !prefs.mount_first_software_part_only)?;
+					Some((machine.name().to_string(), initial_loads))
+				})
+				.ok_or_else(|| ThisError::NoMachineForSoftware(software_list.clone(), software.clone()).into())
+		}
+	}
+}
+
+/// Loads the InfoDb, building it anew if it is missing or corrupt (e.g. - a previous build was interrupted); the new
+/// InfoDb is only saved if preferences are writable
+fn load_or_rebuild_info_db(
+	prefs_path: Option<&Path>,
+	read_only_prefs: bool,
+	mame_executable_path: &str,
+) -> Result<InfoDb> {
+	InfoDb::load(prefs_path, mame_executable_path).or_else(|e| {
+		event!(LOG, "load_or_rebuild_info_db(): load failed; rebuilding: {e:?}");
+		let info_db = InfoDb::from_child_process(mame_executable_path, |_| false)?
+			.expect("InfoDb build cancelled without being asked to");
+		if !read_only_prefs {
+			let _ = info_db.save(prefs_path, mame_executable_path);
+		}
+		Ok(info_db)
+	})
+}
+
+#[cfg(test)]
+mod test {
+	use test_case::test_case;
+
+	#[test_case(0, "Favorites:3", Some(("Favorites", 3)))]
+	#[test_case(1, "Disk: Games:0", Some(("Disk: Games", 0)))]
+	#[test_case(2, "Favorites", None)]
+	#[test_case(3, "Favorites:x", None)]
+	pub fn parse_run_item(_index: usize, s: &str, expected: Option<(&str, usize)>) {
+		let actual = super::parse_run_item(s).ok();
+		let expected = expected.map(|(folder_name, index)| (folder_name.to_string(), index));
+		assert_eq!(expected, actual);
+	}
+
+	#[test_case(0, "coco_cart:mpak", Some(("coco_cart", "mpak")))]
+	#[test_case(1, "coco_cart", None)]
+	pub fn parse_run_software(_index: usize, s: &str, expected: Option<(&str, &str)>) {
+		let actual = super::parse_run_software(s).ok();
+		let expected = expected.map(|(software_list, software)| (software_list.to_string(), software.to_string()));
+		assert_eq!(expected, actual);
+	}
+}
@@ -3,6 +3,7 @@ use std::collections::HashSet;
 use std::mem::take;
 use std::rc::Rc;
 
+use crate::collections::reparent_folders;
 use crate::prefs::HistoryEntry;
 use crate::prefs::Preferences;
 use crate::prefs::PrefsCollection;
@@ -16,7 +17,9 @@ pub trait History {
 	fn current_collection(&self) -> (Rc<PrefsCollection>, Option<usize>);
 	fn current_history_entry(&self) -> &HistoryEntry;
 	fn current_history_entry_mut(&mut self) -> &mut HistoryEntry;
+	fn history_entry_at(&self, delta: isize) -> Option<&HistoryEntry>;
 	fn rename_folder(&mut self, collection_index: usize, new_folder_name: String);
+	fn rename_folder_entries(&mut self, old_folder_name: &str, new_folder_name: &str);
 	fn purge_stray_entries(&mut self);
 }
 
@@ -37,6 +40,7 @@ where
 		let history_entry: HistoryEntry = HistoryEntry {
 			collection: sanitize_collection(collection),
 			search: "".into(),
+			filters: Vec::default(),
 			sort_suppressed: false,
 			selection: Vec::default(),
 		};
@@ -86,24 +90,48 @@ where
 		&mut history[history_len - *position - 1]
 	}
 
+	fn history_entry_at(&self, delta: isize) -> Option<&HistoryEntry> {
+		let (history, position) = self.entries();
+		let position = advance_position(position, history.len(), delta)?;
+		Some(&history[history.len() - position - 1])
+	}
+
 	fn rename_folder(&mut self, collection_index: usize, new_folder_name: String) {
 		// its weird that this is on "history", but it requires simultaneous changes to history and collections
 		let collections = self.collections_mut();
-		let PrefsCollection::Folder { items, name: old_name } = collections[collection_index].as_ref() else {
+		let PrefsCollection::Folder {
+			items,
+			parent,
+			name: old_name,
+		} = collections[collection_index].as_ref()
+		else {
 			panic!("Expected PrefsCollection::Folder")
 		};
 		let old_name = old_name.to_string();
 		let new_collection = PrefsCollection::Folder {
-			name: new_folder_name,
+			name: new_folder_name.clone(),
+			parent: parent.clone(),
 			items: items.clone(),
 		};
 		let new_collection = Rc::new(new_collection);
-		collections[collection_index] = new_collection.clone();
+		collections[collection_index] = new_collection;
+		reparent_folders(collections, &old_name, Some(&new_folder_name));
+
+		self.rename_folder_entries(&old_name, &new_folder_name);
+	}
+
+	fn rename_folder_entries(&mut self, old_folder_name: &str, new_folder_name: &str) {
+		let new_collection = PrefsCollection::Folder {
+			name: new_folder_name.to_string(),
+			parent: None,
+			items: Vec::default(),
+		};
+		let new_collection = Rc::new(new_collection);
 
 		let (entries, _) = self.entries_mut();
 		for entry in entries.iter_mut() {
-			if matches!(entry.collection.as_ref(), PrefsCollection::Folder { name, .. } if name == &old_name) {
-				entry.collection = sanitize_collection(new_collection.clone());
+			if matches!(entry.collection.as_ref(), PrefsCollection::Folder { name, .. } if name == old_folder_name) {
+				entry.collection = new_collection.clone();
 			}
 		}
 	}
@@ -138,11 +166,13 @@ fn advance_position(position: usize, length: usize, delta: isize) -> Option<usiz
 	(position < length).then_some(position)
 }
 
+/// History entries refer to folders by name alone, so that they survive the folder's contents (or nesting) changing
 fn sanitize_collection(collection: Rc<PrefsCollection>) -> Rc<PrefsCollection> {
-	if let PrefsCollection::Folder { name, items: _ } = collection.as_ref() {
+	if let PrefsCollection::Folder { name, .. } = collection.as_ref() {
 		let name = name.clone();
 		let collection = PrefsCollection::Folder {
 			name,
+			parent: None,
 			items: Vec::default(),
 		};
 		Rc::new(collection)
@@ -152,7 +182,7 @@ fn sanitize_collection(collection: Rc<PrefsCollection>) -> Rc<PrefsCollection> {
 }
 
 fn collection_folder_name(collection: &PrefsCollection) -> Option<&str> {
-	if let PrefsCollection::Folder { name, items: _ } = collection {
+	if let PrefsCollection::Folder { name, .. } = collection {
 		Some(name)
 	} else {
 		None
@@ -0,0 +1,213 @@
+//! Support for MAME's history.xml (the "History" file from arcade-history.com), which carries
+//! free-form historical notes for machines and software
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::path::Path;
+
+use anyhow::Error;
+use anyhow::Result;
+
+use crate::xml::XmlElement;
+use crate::xml::XmlEvent;
+use crate::xml::XmlReader;
+
+#[derive(Debug, Default)]
+pub struct HistoryXml {
+	texts: Vec<String>,
+	machines: HashMap<String, usize>,
+	software: HashMap<(String, String), usize>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Phase {
+	Root,
+	History,
+	Entry,
+	Systems,
+	Software,
+	Text,
+}
+
+#[derive(Default)]
+struct State {
+	phase_stack: Vec<Phase>,
+	result: HistoryXml,
+	current_systems: Vec<String>,
+	current_software: Vec<(String, String)>,
+}
+
+impl State {
+	fn handle_start(&mut self, evt: XmlElement<'_>) -> Result<Option<Phase>> {
+		let phase = self.phase_stack.last().unwrap_or(&Phase::Root);
+		let new_phase = match (phase, evt.name().as_ref()) {
+			(Phase::Root, b"history") => Some(Phase::History),
+			(Phase::History, b"entry") => {
+				self.current_systems.clear();
+				self.current_software.clear();
+				Some(Phase::Entry)
+			}
+			(Phase::Entry, b"systems") => Some(Phase::Systems),
+			(Phase::Entry, b"software") => Some(Phase::Software),
+			(Phase::Entry, b"text") => Some(Phase::Text),
+			(Phase::Systems, b"system") => {
+				let [name] = evt.find_attributes([b"name"])?;
+				if let Some(name) = name {
+					self.current_systems.push(name.into());
+				}
+				None
+			}
+			(Phase::Software, b"item") => {
+				let [list, name] = evt.find_attributes([b"list", b"name"])?;
+				if let Some((list, name)) = Option::zip(list, name) {
+					self.current_software.push((list.into(), name.into()));
+				}
+				None
+			}
+			_ => None,
+		};
+		Ok(new_phase)
+	}
+
+	fn handle_end(&mut self, text: Option<String>) {
+		if self.phase_stack.last() == Some(&Phase::Text) {
+			let index = self.result.texts.len();
+			self.result.texts.push(text.unwrap_or_default().trim().to_string());
+			for name in self.current_systems.drain(..) {
+				self.result.machines.insert(name, index);
+			}
+			for key in self.current_software.drain(..) {
+				self.result.software.insert(key, index);
+			}
+		}
+	}
+}
+
+impl HistoryXml {
+	pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+		let file = File::open(path)?;
+		Self::from_reader(BufReader::new(file))
+	}
+
+	pub fn from_reader(reader: impl BufRead) -> Result<Self> {
+		let mut state = State::default();
+		let mut reader = XmlReader::from_reader(reader, true);
+		let mut buf = Vec::with_capacity(1024);
+
+		while let Some(evt) = reader.next(&mut buf).map_err(|e| historyxml_err(&reader, e))? {
+			match evt {
+				XmlEvent::Start(evt) => {
+					let new_phase = state.handle_start(evt).map_err(|e| historyxml_err(&reader, e))?;
+					if let Some(new_phase) = new_phase {
+						state.phase_stack.push(new_phase);
+						if new_phase == Phase::Text {
+							reader.start_text_capture();
+						}
+					} else {
+						reader.start_unknown_tag();
+					}
+				}
+				XmlEvent::End(s) => {
+					state.handle_end(s);
+					state.phase_stack.pop().unwrap();
+				}
+				XmlEvent::Null => {}
+			}
+		}
+		Ok(state.result)
+	}
+
+	pub fn machine_text(&self, machine_name: &str) -> Option<&str> {
+		let index = *self.machines.get(machine_name)?;
+		Some(self.texts[index].as_str())
+	}
+
+	pub fn software_text(&self, software_list: &str, software: &str) -> Option<&str> {
+		let key = (software_list.to_string(), software.to_string());
+		let index = *self.software.get(&key)?;
+		Some(self.texts[index].as_str())
+	}
+}
+
+fn historyxml_err(reader: &XmlReader<impl BufRead>, e: impl Into<Error>) -> Error {
+	let message = format!("Error parsing history XML at position {}", reader.buffer_position());
+	e.into().context(message)
+}
+
+/// Finds all http/https URLs in a block of text, so that they can be presented as links
+pub fn find_links(text: &str) -> Vec<&str> {
+	text.match_indices("http")
+		.filter(|(index, _)| {
+			// only consider URLs at the start of a word
+			text[..*index]
+				.chars()
+				.next_back()
+				.is_none_or(|ch| ch.is_whitespace() || "([<\"'".contains(ch))
+		})
+		.filter_map(|(index, _)| {
+			let rest = &text[index..];
+			let end = rest
+				.find(|ch: char| ch.is_whitespace() || "<>\"".contains(ch))
+				.unwrap_or(rest.len());
+			let url = rest[..end].trim_end_matches(['.', ',', ';', ':', '!', '?', ')', ']', '\'']);
+			(url.starts_with("http://") || url.starts_with("https://"))
+				.then_some(url)
+				.filter(|url| !url.ends_with("//"))
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod test {
+	use std::io::BufReader;
+
+	use test_case::test_case;
+
+	use super::find_links;
+	use super::HistoryXml;
+
+	const XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<history version="2.70" date="2024-01-01">
+	<entry>
+		<systems>
+			<system name="pacman" />
+			<system name="puckman" />
+		</systems>
+		<text>Pac-Man &amp; friends.
+See https://www.example.com/pacman for more.</text>
+	</entry>
+	<entry>
+		<software>
+			<item list="coco_cart" name="clowns" />
+		</software>
+		<text>Clowns &amp; Balloons</text>
+	</entry>
+</history>"#;
+
+	#[test_case(0, "pacman", Some("Pac-Man & friends.\nSee https://www.example.com/pacman for more."))]
+	#[test_case(1, "puckman", Some("Pac-Man & friends.\nSee https://www.example.com/pacman for more."))]
+	#[test_case(2, "galaga", None)]
+	pub fn machine_text(_index: usize, machine_name: &str, expected: Option<&str>) {
+		let history = HistoryXml::from_reader(BufReader::new(XML.as_bytes())).unwrap();
+		let actual = history.machine_text(machine_name);
+		assert_eq!(expected, actual);
+	}
+
+	#[test_case(0, "coco_cart", "clowns", Some("Clowns & Balloons"))]
+	#[test_case(1, "coco_cart", "pacman", None)]
+	pub fn software_text(_index: usize, software_list: &str, software: &str, expected: Option<&str>) {
+		let history = HistoryXml::from_reader(BufReader::new(XML.as_bytes())).unwrap();
+		let actual = history.software_text(software_list, software);
+		assert_eq!(expected, actual);
+	}
+
+	#[test_case(0, "No links here", &[])]
+	#[test_case(1, "See https://www.example.com/pacman for more.", &["https://www.example.com/pacman"])]
+	#[test_case(2, "(http://a.com/x), and https://b.org.", &["http://a.com/x", "https://b.org"])]
+	#[test_case(3, "xhttp://nope.com http://", &[])]
+	pub fn links(_index: usize, text: &str, expected: &[&str]) {
+		let actual = find_links(text);
+		assert_eq!(expected, actual.as_slice());
+	}
+}
@@ -0,0 +1,255 @@
+//! Minimal translation layer for UI strings
+//!
+//! Catalogs are gettext style `.po` files keyed by the English source text, loaded from the `i18n` directory
+//! alongside the preferences (e.g. - `i18n/de.po`).  Strings without a translation are passed through untouched.
+//! Strings in `.slint` files go through the same catalog by way of the `I18n` global.
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs::read_dir;
+use std::fs::read_to_string;
+use std::mem::take;
+use std::path::Path;
+
+use anyhow::Error;
+use anyhow::Result;
+use slint::ComponentHandle;
+use slint::Global;
+use tracing::event;
+use tracing::Level;
+
+use crate::ui::I18n;
+
+const LOG: Level = Level::DEBUG;
+
+thread_local! {
+	static CATALOG: RefCell<HashMap<String, String>> = RefCell::new(HashMap::new());
+	static LANGUAGE: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+#[derive(thiserror::Error, Debug)]
+enum ThisError {
+	#[error("Unterminated string on line {0}")]
+	UnterminatedString(usize),
+	#[error("Unexpected text on line {0}")]
+	UnexpectedText(usize),
+}
+
+/// Translates a UI string using the current catalog
+pub fn tr(text: &str) -> String {
+	CATALOG
+		.with_borrow(|catalog| catalog.get(text).cloned())
+		.unwrap_or_else(|| text.to_string())
+}
+
+/// Translates a UI string template, and then substitutes `{name}` placeholders with the specified arguments;
+/// translators are free to reorder placeholders
+pub fn tr_args(text: &str, args: &[(&str, &dyn Display)]) -> String {
+	substitute(&tr(text), args)
+}
+
+fn substitute(template: &str, args: &[(&str, &dyn Display)]) -> String {
+	let mut result = String::with_capacity(template.len());
+	let mut rest = template;
+	while let Some(start) = rest.find('{') {
+		result.push_str(&rest[..start]);
+		let after = &rest[start + 1..];
+		let arg = after
+			.find('}')
+			.and_then(|end| args.iter().find(|(name, _)| *name == &after[..end]).map(|x| (end, x.1)));
+		if let Some((end, value)) = arg {
+			result.push_str(&value.to_string());
+			rest = &after[end + 1..];
+		} else {
+			result.push('{');
+			rest = after;
+		}
+	}
+	result.push_str(rest);
+	result
+}
+
+/// Loads the catalog for the specified language; `None` reverts to the (untranslated) English strings
+pub fn load_language(prefs_path: Option<&Path>, language: Option<&str>) -> Result<()> {
+	let catalog = if let Some(language) = language {
+		let path = prefs_path
+			.unwrap_or(Path::new("."))
+			.join("i18n")
+			.join(format!("{language}.po"));
+		let text = read_to_string(&path)
+			.map_err(|e| Error::new(e).context(format!("Error loading translations from {}", path.display())))?;
+		parse_po(&text)?
+	} else {
+		HashMap::new()
+	};
+
+	event!(
+		LOG,
+		"load_language(): language={:?} entries={}",
+		language,
+		catalog.len()
+	);
+	CATALOG.set(catalog);
+	LANGUAGE.set(language.unwrap_or_default().to_string());
+	Ok(())
+}
+
+/// Hooks up the `I18n` global of a window to the current catalog; calling this again after `load_language()`
+/// retranslates the window
+pub fn apply_translations<C>(component: &C)
+where
+	C: ComponentHandle,
+	for<'a> I18n<'a>: Global<'a, C>,
+{
+	let global = component.global::<I18n>();
+	global.on_translate(|text, _language| tr(&text).into());
+	global.on_translate_with(|text, _language, name, value| tr_args(&text, &[(&name, &value)]).into());
+	global.set_language(LANGUAGE.with_borrow(|x| x.clone()).into());
+}
+
+/// Lists the languages with catalogs in the `i18n` directory
+pub fn available_languages(prefs_path: Option<&Path>) -> Vec<String> {
+	let path = prefs_path.unwrap_or(Path::new(".")).join("i18n");
+	let mut results = read_dir(path)
+		.into_iter()
+		.flatten()
+		.filter_map(|entry| {
+			let path = entry.ok()?.path();
+			if !path.extension().is_some_and(|x| x.eq_ignore_ascii_case("po")) {
+				return None;
+			}
+			path.file_stem()?.to_str().map(str::to_string)
+		})
+		.collect::<Vec<_>>();
+	results.sort();
+	results
+}
+
+/// Parses the subset of the `.po` format we care about (`msgid`/`msgstr` pairs, with continuation lines)
+fn parse_po(text: &str) -> Result<HashMap<String, String>> {
+	enum Target {
+		None,
+		MsgId,
+		MsgStr,
+	}
+
+	let mut result = HashMap::new();
+	let mut msgid = String::new();
+	let mut msgstr = String::new();
+	let mut target = Target::None;
+
+	let mut flush = |msgid: &mut String, msgstr: &mut String| {
+		let (msgid, msgstr) = (take(msgid), take(msgstr));
+		if !msgid.is_empty() && !msgstr.is_empty() {
+			result.insert(msgid, msgstr);
+		}
+	};
+
+	for (line_number, line) in text.lines().enumerate().map(|(index, line)| (index + 1, line.trim())) {
+		if line.is_empty() || line.starts_with('#') {
+			continue;
+		}
+
+		let (new_target, rest) = if let Some(rest) = line.strip_prefix("msgid ") {
+			flush(&mut msgid, &mut msgstr);
+			(Target::MsgId, rest)
+		} else if let Some(rest) = line.strip_prefix("msgstr ") {
+			(Target::MsgStr, rest)
+		} else if line.starts_with('"') {
+			(target, line)
+		} else {
+			return Err(ThisError::UnexpectedText(line_number).into());
+		};
+		target = new_target;
+
+		let s = parse_po_string(rest).ok_or(ThisError::UnterminatedString(line_number))?;
+		match target {
+			Target::MsgId => msgid.push_str(&s),
+			Target::MsgStr => msgstr.push_str(&s),
+			Target::None => return Err(ThisError::UnexpectedText(line_number).into()),
+		}
+	}
+	flush(&mut msgid, &mut msgstr);
+	Ok(result)
+}
+
+fn parse_po_string(s: &str) -> Option<String> {
+	let s = s.trim().strip_prefix('"')?.strip_suffix('"')?;
+	let mut result = String::with_capacity(s.len());
+	let mut chars = s.chars();
+	while let Some(ch) = chars.next() {
+		let ch = if ch == '\\' {
+			match chars.next()? {
+				'n' => '\n',
+				't' => '\t',
+				other => other,
+			}
+		} else {
+			ch
+		};
+		result.push(ch);
+	}
+	Some(result)
+}
+
+#[cfg(test)]
+mod test {
+	use std::fmt::Display;
+	use std::path::Path;
+
+	use test_case::test_case;
+
+	#[test_case(0, "Hello", &[], "Hello")]
+	#[test_case(1, "Run {item}", &[("item", &"Galaxian")], "Run Galaxian")]
+	#[test_case(2, "{a}{b}{a}", &[("a", &1), ("b", &"-")], "1-1")]
+	#[test_case(3, "Keep {unknown} and {", &[("item", &"x")], "Keep {unknown} and {")]
+	#[test_case(4, "{item}", &[("item", &"{item}")], "{item}")]
+	fn substitute(_index: usize, template: &str, args: &[(&str, &dyn Display)], expected: &str) {
+		let actual = super::substitute(template, args);
+		assert_eq!(expected, actual);
+	}
+
+	/// Loads the sample catalog that ships in the repository
+	#[test]
+	fn load_sample_language() {
+		let prefs_path = Path::new(env!("CARGO_MANIFEST_DIR"));
+		assert!(super::available_languages(Some(prefs_path)).contains(&"de".to_string()));
+
+		super::load_language(Some(prefs_path), Some("de")).unwrap();
+		let safe_mode = "BletchMAME is in safe mode; are you sure you want to run \"{machine}\"?";
+		let actual = (
+			super::tr("Cancel"),
+			super::tr("Not In The Catalog"),
+			super::tr_args(safe_mode, &[("machine", &"pacman")]),
+			super::tr_args(
+				"Link {machine} with another instance of MAME:",
+				&[("machine", &"Pac-Man")],
+			),
+		);
+		super::load_language(Some(prefs_path), None).unwrap();
+
+		let expected = (
+			"Abbrechen".to_string(),
+			"Not In The Catalog".to_string(),
+			"BletchMAME ist im abgesicherten Modus; soll \"pacman\" wirklich gestartet werden?".to_string(),
+			"Pac-Man mit einer anderen MAME-Instanz verbinden:".to_string(),
+		);
+		assert_eq!(expected, actual);
+	}
+
+	#[test_case(0, "", &[])]
+	#[test_case(1, "msgid \"File\"\nmsgstr \"Datei\"", &[("File", "Datei")])]
+	#[test_case(2, "# comment\nmsgid \"Exit\"\nmsgstr \"Beenden\"\n\nmsgid \"Sound\"\nmsgstr \"Ton\"", &[("Exit", "Beenden"), ("Sound", "Ton")])]
+	#[test_case(3, "msgid \"\"\n\"Paths...\"\nmsgstr \"\"\n\"Pfade...\"", &[("Paths...", "Pfade...")])]
+	#[test_case(4, "msgid \"Say \\\"Hi\\\"\"\nmsgstr \"Sag \\\"Hallo\\\"\"", &[("Say \"Hi\"", "Sag \"Hallo\"")])]
+	#[test_case(5, "msgid \"Untranslated\"\nmsgstr \"\"", &[])]
+	fn parse_po(_index: usize, text: &str, expected: &[(&str, &str)]) {
+		let mut actual = super::parse_po(text).unwrap().into_iter().collect::<Vec<_>>();
+		actual.sort();
+		let expected = expected
+			.iter()
+			.map(|(id, s)| (id.to_string(), s.to_string()))
+			.collect::<Vec<_>>();
+		assert_eq!(expected, actual);
+	}
+}
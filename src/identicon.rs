@@ -0,0 +1,132 @@
+//! Placeholder images for items without snapshots; a symmetric pattern of blocks in a color derived from the item's
+//! name, so that items remain easy to tell apart at a glance
+use crate::prefs::PrefsItem;
+
+/// The width and height of identicons, in pixels
+pub const IDENTICON_SIZE: u32 = 60;
+
+/// The number of blocks across and down the pattern
+const CELLS: u32 = 5;
+
+/// The name identicons are derived from; this is stable, unlike descriptions which can vary between MAME versions
+pub fn identicon_name(item: &PrefsItem) -> String {
+	match item {
+		PrefsItem::Machine { machine_name } => machine_name.clone(),
+		PrefsItem::Software {
+			software_list,
+			software,
+		} => format!("{software_list}:{software}"),
+	}
+}
+
+/// The initials shown over an identicon (e.g. - "Pac-Man" becomes "PM")
+pub fn identicon_initials(caption: &str) -> String {
+	caption
+		.split(|ch: char| !ch.is_alphanumeric())
+		.filter_map(|word| word.chars().next())
+		.take(2)
+		.flat_map(char::to_uppercase)
+		.collect()
+}
+
+/// Renders the identicon for a name as RGBA pixels
+pub fn identicon_pixels(name: &str) -> Vec<u8> {
+	let hash = fnv1a_hash(name);
+	let color = hue_color((hash % 360) as u16);
+	let background = blend(color, 0.15);
+	let foreground = blend(color, 0.55);
+
+	// the low bits picked the hue, so the pattern comes from the remaining ones; only the left half (plus the middle
+	// column) is chosen, and mirrored onto the right
+	let cell_size = IDENTICON_SIZE / CELLS;
+	let is_filled = |x: u32, y: u32| {
+		let x = x.min(CELLS - 1 - x);
+		let bit = 16 + y * CELLS.div_ceil(2) + x;
+		(hash >> bit) & 1 != 0
+	};
+
+	(0..IDENTICON_SIZE)
+		.flat_map(|y| (0..IDENTICON_SIZE).map(move |x| (x, y)))
+		.flat_map(|(x, y)| {
+			let [r, g, b] = if is_filled(x / cell_size, y / cell_size) {
+				foreground
+			} else {
+				background
+			};
+			[r, g, b, 0xFF]
+		})
+		.collect()
+}
+
+/// A stable hash; `DefaultHasher` makes no promises across releases, which would make identicons shift around
+fn fnv1a_hash(s: &str) -> u64 {
+	s.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+		(hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+	})
+}
+
+/// A fully saturated color with the specified hue (in degrees)
+fn hue_color(hue: u16) -> [u8; 3] {
+	let sector = hue / 60;
+	let fraction = u8::try_from(u32::from(hue % 60) * 255 / 60).unwrap();
+	match sector {
+		0 => [0xFF, fraction, 0x00],
+		1 => [0xFF - fraction, 0xFF, 0x00],
+		2 => [0x00, 0xFF, fraction],
+		3 => [0x00, 0xFF - fraction, 0xFF],
+		4 => [fraction, 0x00, 0xFF],
+		_ => [0xFF, 0x00, 0xFF - fraction],
+	}
+}
+
+/// Blends a color with white; `amount` is how much of the color is kept
+fn blend(color: [u8; 3], amount: f32) -> [u8; 3] {
+	color.map(|x| (f32::from(x) * amount + 255.0 * (1.0 - amount)).round() as u8)
+}
+
+#[cfg(test)]
+mod test {
+	use test_case::test_case;
+
+	use super::IDENTICON_SIZE;
+
+	#[test_case(0, "Pac-Man", "PM")]
+	#[test_case(1, "Color Computer 2B", "CC")]
+	#[test_case(2, "Galaga", "G")]
+	#[test_case(3, "1942 (Revision B)", "1R")]
+	#[test_case(4, "", "")]
+	pub fn identicon_initials(_index: usize, caption: &str, expected: &str) {
+		let actual = super::identicon_initials(caption);
+		assert_eq!(expected, actual);
+	}
+
+	#[test_case(0, 0, [0xFF, 0x00, 0x00])]
+	#[test_case(1, 60, [0xFF, 0xFF, 0x00])]
+	#[test_case(2, 180, [0x00, 0xFF, 0xFF])]
+	#[test_case(3, 270, [0x7F, 0x00, 0xFF])]
+	pub fn hue_color(_index: usize, hue: u16, expected: [u8; 3]) {
+		let actual = super::hue_color(hue);
+		assert_eq!(expected, actual);
+	}
+
+	#[test_case(0, "coco2b")]
+	#[test_case(1, "pacman")]
+	pub fn identicon_pixels(_index: usize, name: &str) {
+		let pixels = super::identicon_pixels(name);
+		assert_eq!(
+			usize::try_from(IDENTICON_SIZE * IDENTICON_SIZE * 4).unwrap(),
+			pixels.len()
+		);
+
+		// the pattern is mirrored left to right
+		let pixel = |x: u32, y: u32| {
+			let index = usize::try_from((y * IDENTICON_SIZE + x) * 4).unwrap();
+			&pixels[index..index + 4]
+		};
+		for y in 0..IDENTICON_SIZE {
+			for x in 0..IDENTICON_SIZE {
+				assert_eq!(pixel(x, y), pixel(IDENTICON_SIZE - 1 - x, y));
+			}
+		}
+	}
+}
@@ -25,6 +25,7 @@ pub struct Header {
 	pub software_list_machine_count: u32,
 	pub machine_software_lists_count: u32,
 	pub ram_option_count: u32,
+	pub bios_set_count: u32,
 }
 
 #[derive(Clone, Copy, Debug, Default, BinarySerde)]
@@ -48,7 +49,13 @@ pub struct Machine {
 	pub machine_software_lists_end: u32,
 	pub ram_options_start: u32,
 	pub ram_options_end: u32,
+	pub bios_sets_start: u32,
+	pub bios_sets_end: u32,
 	pub runnable: bool,
+	pub is_bios: bool,
+	pub is_device: bool,
+	pub is_mechanical: bool,
+	pub requires_disks: bool,
 }
 
 impl Fixup for Machine {
@@ -111,6 +118,13 @@ pub struct RamOption {
 	pub is_default: bool,
 }
 
+#[derive(Clone, Copy, Debug, BinarySerde)]
+pub struct BiosSet {
+	pub name_strindex: u32,
+	pub description_strindex: u32,
+	pub is_default: bool,
+}
+
 impl Fixup for MachineSoftwareList {
 	fn identify_software_list_indexes(&mut self) -> impl IntoIterator<Item = &mut u32> {
 		[&mut self.software_list_index]
@@ -29,6 +29,13 @@ use crate::xml::XmlReader;
 
 const LOG: Level = Level::TRACE;
 
+/// Progress reported as each machine in `-listxml` output is processed
+#[derive(Clone, Copy, Debug)]
+pub struct ListXmlProgress<'a> {
+	pub machine_description: &'a str,
+	pub machines_processed: usize,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum Phase {
 	Root,
@@ -60,6 +67,7 @@ struct State {
 	strings: StringTableBuilder,
 	software_lists: BTreeMap<String, SoftwareListBuild>,
 	ram_options: BinBuilder<binary::RamOption>,
+	bios_sets: BinBuilder<binary::BiosSet>,
 	build_strindex: u32,
 	phase_specific: Option<PhaseSpecificState>,
 }
@@ -99,6 +107,7 @@ impl State {
 			slot_options: BinBuilder::new(1000),           // ??? slot options
 			machine_software_lists: BinBuilder::new(6800), // 6337 software lists
 			ram_options: BinBuilder::new(6800),            // 6383 ram options
+			bios_sets: BinBuilder::new(1000),              // ??? bios sets
 			software_lists: BTreeMap::new(),
 			strings,
 			build_strindex,
@@ -118,8 +127,17 @@ impl State {
 				Some(Phase::Mame)
 			}
 			(Phase::Mame, b"machine") => {
-				let [name, source_file, clone_of, rom_of, runnable] =
-					evt.find_attributes([b"name", b"sourcefile", b"cloneof", b"romof", b"runnable"])?;
+				let [name, source_file, clone_of, rom_of, runnable, is_bios, is_device, is_mechanical] = evt
+					.find_attributes([
+						b"name",
+						b"sourcefile",
+						b"cloneof",
+						b"romof",
+						b"runnable",
+						b"isbios",
+						b"isdevice",
+						b"ismechanical",
+					])?;
 
 				event!(
 					LOG,
@@ -135,6 +153,9 @@ impl State {
 				let clone_of_machine_index = self.strings.lookup(&clone_of.unwrap_or_default());
 				let rom_of_machine_index = self.strings.lookup(&rom_of.unwrap_or_default());
 				let runnable = runnable.map(parse_mame_bool).transpose()?.unwrap_or(true);
+				let is_bios = is_bios.map(parse_mame_bool).transpose()?.unwrap_or(false);
+				let is_device = is_device.map(parse_mame_bool).transpose()?.unwrap_or(false);
+				let is_mechanical = is_mechanical.map(parse_mame_bool).transpose()?.unwrap_or(false);
 				let machine = binary::Machine {
 					name_strindex,
 					source_file_strindex,
@@ -150,7 +171,12 @@ impl State {
 					machine_software_lists_end: self.machine_software_lists.len(),
 					ram_options_start: self.ram_options.len(),
 					ram_options_end: self.ram_options.len(),
+					bios_sets_start: self.bios_sets.len(),
+					bios_sets_end: self.bios_sets.len(),
 					runnable,
+					is_bios,
+					is_device,
+					is_mechanical,
 					..Default::default()
 				};
 				self.machines.push(machine);
@@ -253,6 +279,31 @@ impl State {
 				self.phase_specific = Some(PhaseSpecificState::RamOption(is_default));
 				Some(Phase::MachineRamOption)
 			}
+			(Phase::Machine, b"biosset") => {
+				let [name, description, is_default] = evt.find_attributes([b"name", b"description", b"default"])?;
+				let name = name.ok_or(ThisError::MissingMandatoryAttribute("name"))?;
+				let name_strindex = self.strings.lookup(&name);
+				let description_strindex = self.strings.lookup(&description.unwrap_or_default());
+				let is_default = is_default.map(parse_mame_bool).transpose()?.unwrap_or_default();
+				let bios_set = binary::BiosSet {
+					name_strindex,
+					description_strindex,
+					is_default,
+				};
+				self.bios_sets.push(bios_set);
+				self.machines.increment(|x| &mut x.bios_sets_end)?;
+				None
+			}
+			(Phase::Machine, b"disk") => {
+				// disks that were never dumped, or are optional, are not needed to run the machine
+				let [status, optional] = evt.find_attributes([b"status", b"optional"])?;
+				let is_nodump = status.is_some_and(|x| x.as_ref() == "nodump");
+				let optional = optional.map(parse_mame_bool).transpose()?.unwrap_or(false);
+				if !is_nodump && !optional {
+					self.machines.tweak(|x| x.requires_disks = true);
+				}
+				None
+			}
 			(Phase::MachineDevice, b"extension") => {
 				let [name] = evt.find_attributes([b"name"])?;
 				if let Some(name) = name {
@@ -290,14 +341,24 @@ impl State {
 		Ok(new_phase)
 	}
 
-	pub fn handle_end(&mut self, callback: &mut impl FnMut(&str) -> bool, text: Option<String>) -> Result<Option<()>> {
+	pub fn handle_end(
+		&mut self,
+		callback: &mut impl FnMut(ListXmlProgress<'_>) -> bool,
+		text: Option<String>,
+	) -> Result<Option<()>> {
 		event!(LOG, "handle_end(): self={:?}", self);
 
 		match self.phase_stack.last().unwrap_or(&Phase::Root) {
 			Phase::MachineDescription => {
 				let description = text.unwrap();
-				if !description.is_empty() && callback(&description) {
-					return Ok(None);
+				if !description.is_empty() {
+					let progress = ListXmlProgress {
+						machine_description: &description,
+						machines_processed: self.machines.len().try_into().unwrap(),
+					};
+					if callback(progress) {
+						return Ok(None);
+					}
 				}
 				let description_strindex = self.strings.lookup(&description);
 				self.machines.tweak(|x| x.description_strindex = description_strindex);
@@ -455,6 +516,7 @@ impl State {
 			software_list_machine_count: software_list_machine_indexes.len(),
 			machine_software_lists_count: self.machine_software_lists.len(),
 			ram_option_count: self.ram_options.len(),
+			bios_set_count: self.bios_sets.len(),
 		};
 		let mut header_bytes = [0u8; binary::Header::SERIALIZED_SIZE];
 		header.binary_serialize(&mut header_bytes, ENDIANNESS);
@@ -471,6 +533,7 @@ impl State {
 			.chain(software_list_machine_indexes.into_iter())
 			.chain(self.machine_software_lists.into_iter())
 			.chain(self.ram_options.into_iter())
+			.chain(self.bios_sets.into_iter())
 			.chain(self.strings.into_iter())
 			.collect();
 		Ok(bytes)
@@ -525,7 +588,7 @@ fn listxml_err(reader: &XmlReader<impl BufRead>, e: impl Into<Error>) -> Error {
 
 pub fn data_from_listxml_output(
 	reader: impl BufRead,
-	mut callback: impl FnMut(&str) -> bool,
+	mut callback: impl FnMut(ListXmlProgress<'_>) -> bool,
 ) -> Result<Option<Box<[u8]>>> {
 	let mut state = State::new();
 	let mut reader = XmlReader::from_reader(reader, true);
@@ -702,6 +765,7 @@ pub fn calculate_sizes_hash() -> u64 {
 		binary::SoftwareList::SERIALIZED_SIZE,
 		binary::MachineSoftwareList::SERIALIZED_SIZE,
 		binary::RamOption::SERIALIZED_SIZE,
+		binary::BiosSet::SERIALIZED_SIZE,
 	]
 	.into_iter()
 	.fold(0, |value, item| {
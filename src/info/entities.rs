@@ -6,6 +6,7 @@ use crate::info::ChipType;
 use crate::info::IndirectView;
 use crate::info::Object;
 use crate::info::SimpleView;
+use crate::info::SoftwareListStatus;
 use crate::info::View;
 
 pub type Machine<'a> = Object<'a, binary::Machine>;
@@ -17,6 +18,8 @@ pub type SlotOption<'a> = Object<'a, binary::SlotOption>;
 pub type SoftwareList<'a> = Object<'a, binary::SoftwareList>;
 pub type SoftwareListsView<'a> = SimpleView<'a, binary::SoftwareList>;
 pub type MachineSoftwareList<'a> = Object<'a, binary::MachineSoftwareList>;
+pub type RamOption<'a> = Object<'a, binary::RamOption>;
+pub type BiosSet<'a> = Object<'a, binary::BiosSet>;
 
 impl<'a> Machine<'a> {
 	pub fn name(&self) -> &'a str {
@@ -44,6 +47,10 @@ impl<'a> Machine<'a> {
 		self.db.machines().get(clone_of_machine_index)
 	}
 
+	pub fn clone_count(&self) -> usize {
+		self.db.clone_counts()[self.index()].try_into().unwrap()
+	}
+
 	pub fn rom_of(&self) -> Option<Machine<'a>> {
 		let rom_of_machine_index = self.obj().rom_of_machine_index.try_into().unwrap();
 		self.db.machines().get(rom_of_machine_index)
@@ -53,10 +60,38 @@ impl<'a> Machine<'a> {
 		self.obj().runnable
 	}
 
+	pub fn is_bios(&self) -> bool {
+		self.obj().is_bios
+	}
+
+	pub fn is_device(&self) -> bool {
+		self.obj().is_device
+	}
+
+	pub fn is_mechanical(&self) -> bool {
+		self.obj().is_mechanical
+	}
+
+	/// Does this machine need CHDs (and not just ROMs) to run?
+	pub fn requires_disks(&self) -> bool {
+		self.obj().requires_disks
+	}
+
 	pub fn chips(&self) -> impl View<'a, Chip<'a>> {
 		self.db.chips().sub_view(self.obj().chips_start..self.obj().chips_end)
 	}
 
+	/// The most prominent chip of a particular type; this is the first one with a clock, if any
+	pub fn primary_chip(&self, chip_type: ChipType) -> Option<Chip<'a>> {
+		let mut chips = self
+			.chips()
+			.iter()
+			.filter(|chip| chip.chip_type() == chip_type)
+			.peekable();
+		let first = chips.peek().cloned();
+		chips.find(|chip| chip.clock() > 0).or(first)
+	}
+
 	pub fn devices(&self) -> impl View<'a, Device<'a>> {
 		self.db
 			.devices()
@@ -72,6 +107,30 @@ impl<'a> Machine<'a> {
 			.machine_software_lists()
 			.sub_view(self.obj().machine_software_lists_start..self.obj().machine_software_lists_end)
 	}
+
+	pub fn ram_options(&self) -> impl View<'a, RamOption<'a>> {
+		self.db
+			.ram_options()
+			.sub_view(self.obj().ram_options_start..self.obj().ram_options_end)
+	}
+
+	/// The RAM size (in bytes) the machine runs with unless configured otherwise, if it has configurable RAM
+	pub fn default_ram_size(&self) -> Option<u64> {
+		let ram_options = self.ram_options();
+		let default = ram_options.iter().find(|x| x.is_default());
+		default.or_else(|| ram_options.get(0)).map(|x| x.size())
+	}
+
+	/// The largest RAM size (in bytes) the machine can be configured with
+	pub fn maximum_ram_size(&self) -> Option<u64> {
+		self.ram_options().iter().map(|x| x.size()).max()
+	}
+
+	pub fn bios_sets(&self) -> impl View<'a, BiosSet<'a>> {
+		self.db
+			.bios_sets()
+			.sub_view(self.obj().bios_sets_start..self.obj().bios_sets_end)
+	}
 }
 
 impl<'a> MachinesView<'a> {
@@ -108,6 +167,34 @@ impl<'a> Chip<'a> {
 	pub fn chip_type(&self) -> ChipType {
 		self.obj().chip_type
 	}
+
+	pub fn clock(&self) -> u64 {
+		self.obj().clock
+	}
+}
+
+impl RamOption<'_> {
+	pub fn size(&self) -> u64 {
+		self.obj().size
+	}
+
+	pub fn is_default(&self) -> bool {
+		self.obj().is_default
+	}
+}
+
+impl<'a> BiosSet<'a> {
+	pub fn name(&self) -> &'a str {
+		self.string(|x| x.name_strindex)
+	}
+
+	pub fn description(&self) -> &'a str {
+		self.string(|x| x.description_strindex)
+	}
+
+	pub fn is_default(&self) -> bool {
+		self.obj().is_default
+	}
 }
 
 impl<'a> Device<'a> {
@@ -202,6 +289,14 @@ impl<'a> MachineSoftwareList<'a> {
 		let software_list_index = self.obj().software_list_index.try_into().unwrap();
 		self.db.software_lists().get(software_list_index).unwrap()
 	}
+
+	pub fn status(&self) -> SoftwareListStatus {
+		self.obj().status
+	}
+
+	pub fn filter(&self) -> &'a str {
+		self.string(|x| x.filter_strindex)
+	}
 }
 
 #[cfg(test)]
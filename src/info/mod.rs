@@ -19,6 +19,7 @@ use std::path::Path;
 use std::path::PathBuf;
 use std::process::Command;
 use std::process::Stdio;
+use std::sync::OnceLock;
 
 use anyhow::Error;
 use anyhow::Result;
@@ -34,19 +35,23 @@ use crate::version::MameVersion;
 
 pub use self::binary::ChipType;
 pub use self::binary::SoftwareListStatus;
+pub use self::build::ListXmlProgress;
+pub use self::entities::BiosSet;
 pub use self::entities::Chip;
 pub use self::entities::Device;
 pub use self::entities::Machine;
 pub use self::entities::MachineSoftwareList;
 pub use self::entities::MachinesView;
+pub use self::entities::RamOption;
 pub use self::entities::Slot;
 pub use self::entities::SlotOption;
 pub use self::entities::SoftwareList;
+pub use self::strings::read_string;
+pub use self::strings::validate_string_table;
+pub use self::strings::StringTableBuilder;
 
 use self::build::calculate_sizes_hash;
 use self::build::data_from_listxml_output;
-use self::strings::read_string;
-use self::strings::validate_string_table;
 
 const MAGIC_HDR: &[u8; 8] = b"MAMEINFO";
 const ENDIANNESS: Endianness = Endianness::Little;
@@ -61,9 +66,12 @@ pub struct InfoDb {
 	software_lists: RootView<binary::SoftwareList>,
 	software_list_machine_indexes: RootView<u32>,
 	machine_software_lists: RootView<binary::MachineSoftwareList>,
+	ram_options: RootView<binary::RamOption>,
+	bios_sets: RootView<binary::BiosSet>,
 	strings_offset: usize,
 	strings_arena: Arena<str>,
 	build: MameVersion,
+	clone_counts: OnceLock<Box<[u32]>>,
 }
 
 impl InfoDb {
@@ -85,7 +93,8 @@ impl InfoDb {
 		let software_lists = next_root_view(&mut cursor, hdr.software_list_count)?;
 		let software_list_machine_indexes = next_root_view(&mut cursor, hdr.software_list_machine_count)?;
 		let machine_software_lists = next_root_view(&mut cursor, hdr.machine_software_lists_count)?;
-		let _ram_options: RootView<binary::RamOption> = next_root_view(&mut cursor, hdr.ram_option_count)?;
+		let ram_options = next_root_view(&mut cursor, hdr.ram_option_count)?;
+		let bios_sets = next_root_view(&mut cursor, hdr.bios_set_count)?;
 
 		// validations we want to skip if we're creating things ourselves
 		if !skip_validations {
@@ -107,9 +116,12 @@ impl InfoDb {
 			software_lists,
 			software_list_machine_indexes,
 			machine_software_lists,
+			ram_options,
+			bios_sets,
 			strings_offset: cursor.start,
 			strings_arena: Arena::new(),
 			build,
+			clone_counts: OnceLock::new(),
 		};
 
 		// more validations
@@ -134,14 +146,39 @@ impl InfoDb {
 		Self::new(data.into())
 	}
 
+	/// Loads the InfoDb that was replaced by the most recent `save()`, so that changes can be reviewed
+	pub fn load_previous(prefs_path: Option<impl AsRef<Path>>, mame_executable_path: &str) -> Result<Self> {
+		let filename = infodb_filename(prefs_path, mame_executable_path).map_err(infodb_load_error)?;
+		let file = File::open(previous_infodb_filename(&filename)).map_err(infodb_load_error)?;
+		let mut reader = BufReader::new(file);
+		let mut data = Vec::new();
+		reader.read_to_end(&mut data).map_err(infodb_load_error)?;
+		Self::new(data.into())
+	}
+
 	pub fn save(&self, prefs_path: Option<impl AsRef<Path>>, mame_executable_path: &str) -> Result<()> {
 		let filename = infodb_filename(prefs_path, mame_executable_path).map_err(infodb_save_error)?;
-		let mut file = File::create(filename).map_err(infodb_save_error)?;
+
+		// write to a temporary file first; crashing midway through a write should not leave a corrupt InfoDb
+		let temporary_filename = temporary_infodb_filename(&filename);
+		let mut file = File::create(&temporary_filename).map_err(infodb_save_error)?;
 		file.write_all(&self.data).map_err(infodb_save_error)?;
+		file.sync_all().map_err(infodb_save_error)?;
+		drop(file);
+
+		// keep the InfoDb we're replacing around, so that the user can see what changed
+		if filename.exists() {
+			std::fs::rename(&filename, previous_infodb_filename(&filename)).map_err(infodb_save_error)?;
+		}
+
+		std::fs::rename(&temporary_filename, &filename).map_err(infodb_save_error)?;
 		Ok(())
 	}
 
-	pub fn from_listxml_output(reader: impl BufRead, callback: impl FnMut(&str) -> bool) -> Result<Option<Self>> {
+	pub fn from_listxml_output(
+		reader: impl BufRead,
+		callback: impl FnMut(ListXmlProgress<'_>) -> bool,
+	) -> Result<Option<Self>> {
 		// process 'mame -listxml' output
 		let data = data_from_listxml_output(reader, callback)?;
 
@@ -155,7 +192,21 @@ impl InfoDb {
 		Ok(Some(info_db))
 	}
 
-	pub fn from_child_process(mame_executable_path: &str, callback: impl FnMut(&str) -> bool) -> Result<Option<Self>> {
+	pub fn from_listxml_file(
+		path: impl AsRef<Path>,
+		callback: impl FnMut(ListXmlProgress<'_>) -> bool,
+	) -> Result<Option<Self>> {
+		let path = path.as_ref();
+		let file = File::open(path)
+			.map_err(|e| Error::new(e).context(format!("Error opening listxml file {}", path.display())))?;
+		let reader = BufReader::new(file);
+		InfoDb::from_listxml_output(reader, callback)
+	}
+
+	pub fn from_child_process(
+		mame_executable_path: &str,
+		callback: impl FnMut(ListXmlProgress<'_>) -> bool,
+	) -> Result<Option<Self>> {
 		// launch the process
 		let mut process = Command::new(mame_executable_path)
 			.arg("-listxml")
@@ -215,10 +266,31 @@ impl InfoDb {
 		self.make_view(&self.machine_software_lists)
 	}
 
+	pub fn ram_options(&self) -> impl View<'_, RamOption<'_>> {
+		self.make_view(&self.ram_options)
+	}
+
+	pub fn bios_sets(&self) -> impl View<'_, BiosSet<'_>> {
+		self.make_view(&self.bios_sets)
+	}
+
 	pub fn software_list_machine_indexes(&self) -> impl View<'_, Object<'_, u32>> {
 		self.make_view(&self.software_list_machine_indexes)
 	}
 
+	/// The number of clones of each machine; computed on first use because few callers need it
+	fn clone_counts(&self) -> &[u32] {
+		self.clone_counts.get_or_init(|| {
+			let mut counts = vec![0u32; self.machines().len()];
+			for machine in self.machines().iter() {
+				if let Some(clone_of) = machine.clone_of() {
+					counts[clone_of.index()] += 1;
+				}
+			}
+			counts.into()
+		})
+	}
+
 	fn string(&self, offset: u32) -> &'_ str {
 		match read_string(&self.data[self.strings_offset..], offset).unwrap_or_default() {
 			Cow::Borrowed(s) => s,
@@ -296,6 +368,14 @@ fn infodb_filename(prefs_path: Option<impl AsRef<Path>>, mame_executable_path: &
 	prefs_filename(prefs_path, Some(&file_name.as_path().to_string_lossy()))
 }
 
+fn previous_infodb_filename(filename: &Path) -> PathBuf {
+	filename.with_extension("previous.infodb")
+}
+
+fn temporary_infodb_filename(filename: &Path) -> PathBuf {
+	filename.with_extension("infodb.tmp")
+}
+
 fn infodb_load_error(error: impl Into<Error>) -> Error {
 	error.into().context("Error loading InfoDB")
 }
@@ -491,7 +571,9 @@ where
 mod test {
 	use std::cmp::max;
 
+	use assert_matches::assert_matches;
 	use itertools::Itertools;
+	use tempdir::TempDir;
 	use test_case::test_case;
 
 	use super::ChipType;
@@ -575,6 +657,30 @@ mod test {
 		assert_eq!(expected, actual);
 	}
 
+	#[test_case(0, include_str!("test_data/listxml_alienar.xml"), (0, 12, 0))]
+	#[test_case(1, include_str!("test_data/listxml_coco.xml"), (0, 111, 0))]
+	#[test_case(2, include_str!("test_data/listxml_machine_kinds.xml"), (1, 1, 1))]
+	pub fn machine_kinds(_index: usize, xml: &str, expected: (usize, usize, usize)) {
+		let db = InfoDb::from_listxml_output(xml.as_bytes(), |_| false).unwrap().unwrap();
+		let machines = db.machines();
+		let actual = (
+			machines.iter().filter(|m| m.is_bios()).count(),
+			machines.iter().filter(|m| m.is_device()).count(),
+			machines.iter().filter(|m| m.is_mechanical()).count(),
+		);
+		assert_eq!(expected, actual);
+	}
+
+	#[test_case(0, include_str!("test_data/listxml_coco.xml"), "coco", 6)]
+	#[test_case(1, include_str!("test_data/listxml_coco.xml"), "coco2b", 0)]
+	#[test_case(2, include_str!("test_data/listxml_coco.xml"), "cocoloco", 2)]
+	#[test_case(3, include_str!("test_data/listxml_alienar.xml"), "alienar", 0)]
+	pub fn clone_count(_index: usize, xml: &str, machine: &str, expected: usize) {
+		let db = InfoDb::from_listxml_output(xml.as_bytes(), |_| false).unwrap().unwrap();
+		let actual = db.machines().find(machine).unwrap().clone_count();
+		assert_eq!(expected, actual);
+	}
+
 	#[test_case(0, include_str!("test_data/listxml_alienar.xml"), 0, Some(("alienar", "1985")))]
 	#[test_case(1, include_str!("test_data/listxml_alienar.xml"), 5, Some(("mc6809e", "")))]
 	#[test_case(2, include_str!("test_data/listxml_alienar.xml"), 4242, None)]
@@ -634,6 +740,43 @@ mod test {
 		assert_eq!(expected, actual);
 	}
 
+	#[test_case(0, include_str!("test_data/listxml_alienar.xml"), "alienar", ChipType::Cpu, Some(("maincpu", 1000000)))]
+	#[test_case(1, include_str!("test_data/listxml_alienar.xml"), "alienar", ChipType::Audio, Some(("speaker", 0)))]
+	#[test_case(2, include_str!("test_data/listxml_c64.xml"), "c64", ChipType::Audio, Some(("u18", 1022727)))]
+	pub fn primary_chip(_index: usize, xml: &str, machine: &str, chip_type: ChipType, expected: Option<(&str, u64)>) {
+		let db = InfoDb::from_listxml_output(xml.as_bytes(), |_| false).unwrap().unwrap();
+		let chip = db.machines().find(machine).unwrap().primary_chip(chip_type);
+		let actual = chip.as_ref().map(|chip| (chip.tag(), chip.clock()));
+		assert_eq!(expected, actual);
+	}
+
+	#[test_case(0, include_str!("test_data/listxml_coco.xml"), "coco", Some(65536), Some(65536))]
+	#[test_case(1, include_str!("test_data/listxml_alienar.xml"), "alienar", None, None)]
+	pub fn ram_sizes(
+		_index: usize,
+		xml: &str,
+		machine: &str,
+		expected_default: Option<u64>,
+		expected_max: Option<u64>,
+	) {
+		let db = InfoDb::from_listxml_output(xml.as_bytes(), |_| false).unwrap().unwrap();
+		let machine = db.machines().find(machine).unwrap();
+		let actual = (machine.default_ram_size(), machine.maximum_ram_size());
+		assert_eq!((expected_default, expected_max), actual);
+	}
+
+	#[test_case(0, include_str!("test_data/listxml_coco.xml"), "coco", 9, Some("b12e11"))]
+	#[test_case(1, include_str!("test_data/listxml_coco.xml"), "cocoh", 9, Some("b12e11"))]
+	#[test_case(2, include_str!("test_data/listxml_coco.xml"), "coco2b", 0, None)]
+	#[test_case(3, include_str!("test_data/listxml_alienar.xml"), "alienar", 0, None)]
+	pub fn bios_sets(_index: usize, xml: &str, machine: &str, expected_count: usize, expected_default: Option<&str>) {
+		let db = InfoDb::from_listxml_output(xml.as_bytes(), |_| false).unwrap().unwrap();
+		let machine = db.machines().find(machine).unwrap();
+		let bios_sets = machine.bios_sets();
+		let default = bios_sets.iter().find(|x| x.is_default()).map(|x| x.name());
+		assert_eq!((expected_count, expected_default), (bios_sets.len(), default));
+	}
+
 	#[test_case(0, include_str!("test_data/listxml_coco.xml"), "coco2b", "ext:fdc:wd17xx:0:525dd", "floppydisk", "floppy_5_25",
 		&["1dd", "86f", "cqi", "cqm", "d77", "d88", "dfi", "dmk", "dsk", "imd", "jvc", "mfi", "mfm", "os9", "sdf", "td0", "vdk"])]
 	pub fn devices(
@@ -779,4 +922,26 @@ mod test {
 			(actual_originals, actual_compatibles)
 		);
 	}
+
+	#[test]
+	pub fn save_and_load() {
+		let xml = include_str!("test_data/listxml_coco.xml");
+		let db = InfoDb::from_listxml_output(xml.as_bytes(), |_| false).unwrap().unwrap();
+		let tmp_dir = TempDir::new("temp").unwrap();
+		let prefs_path = Some(tmp_dir.path());
+		db.save(prefs_path, "mame").unwrap();
+		db.save(prefs_path, "mame").unwrap();
+
+		// the temporary file should be gone, and the previous InfoDb kept around
+		let filename = super::infodb_filename(prefs_path, "mame").unwrap();
+		assert!(!super::temporary_infodb_filename(&filename).exists());
+		assert!(super::previous_infodb_filename(&filename).exists());
+		let loaded = InfoDb::load(prefs_path, "mame").unwrap();
+		assert_eq!(db.machines().len(), loaded.machines().len());
+
+		// a truncated InfoDb (e.g. - from an older crash) should fail to load, so that we rebuild
+		let data = std::fs::read(&filename).unwrap();
+		std::fs::write(&filename, &data[..data.len() / 2]).unwrap();
+		assert_matches!(InfoDb::load(prefs_path, "mame"), Err(_));
+	}
 }
@@ -0,0 +1,189 @@
+//! Comparing the InfoDb of the previous MAME version with the current one, so that users updating their ROM
+//! collections can see what changed
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use itertools::Itertools;
+
+use crate::info::InfoDb;
+use crate::info::Machine;
+use crate::renames::MachineRename;
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct InfoDbDiff {
+	pub old_build: String,
+	pub new_build: String,
+	pub added: Vec<String>,
+	pub removed: Vec<String>,
+	pub renamed: Vec<MachineRename>,
+	pub changed: Vec<MachineChange>,
+}
+
+/// A machine whose description, manufacturer or year changed between versions
+#[derive(Debug, PartialEq, Eq)]
+pub struct MachineChange {
+	pub name: String,
+	pub field: &'static str,
+	pub old_value: String,
+	pub new_value: String,
+}
+
+impl InfoDbDiff {
+	pub fn new(old_info_db: &InfoDb, new_info_db: &InfoDb) -> Self {
+		let old_machines = machines_map(old_info_db);
+		let new_machines = machines_map(new_info_db);
+
+		// find the machines that are only on one side
+		let mut removed = old_machines
+			.keys()
+			.filter(|name| !new_machines.contains_key(*name))
+			.copied()
+			.collect::<Vec<_>>();
+		let mut added = new_machines
+			.keys()
+			.filter(|name| !old_machines.contains_key(*name))
+			.copied()
+			.collect::<Vec<_>>();
+
+		// a removed machine is considered renamed if exactly one added machine has the same description,
+		// manufacturer and year
+		let renamed = removed
+			.iter()
+			.filter_map(|old_name| {
+				let old_machine = old_machines[old_name];
+				let (new_name,) = added
+					.iter()
+					.filter(|new_name| same_identity(old_machine, new_machines[*new_name]))
+					.collect_tuple()?;
+				let rename = MachineRename {
+					old_name: old_name.to_string(),
+					new_name: new_name.to_string(),
+				};
+				Some(rename)
+			})
+			.collect::<Vec<_>>();
+		removed.retain(|name| !renamed.iter().any(|x| x.old_name == *name));
+		added.retain(|name| !renamed.iter().any(|x| x.new_name == *name));
+
+		// and the machines whose details changed
+		let changed = old_machines
+			.iter()
+			.filter_map(|(name, old_machine)| Some((name, old_machine, new_machines.get(name)?)))
+			.flat_map(|(name, old_machine, new_machine)| {
+				[
+					("Description", old_machine.description(), new_machine.description()),
+					("Manufacturer", old_machine.manufacturer(), new_machine.manufacturer()),
+					("Year", old_machine.year(), new_machine.year()),
+				]
+				.into_iter()
+				.filter(|(_, old_value, new_value)| old_value != new_value)
+				.map(|(field, old_value, new_value)| MachineChange {
+					name: name.to_string(),
+					field,
+					old_value: old_value.to_string(),
+					new_value: new_value.to_string(),
+				})
+			})
+			.collect();
+
+		Self {
+			old_build: old_info_db.build().to_string(),
+			new_build: new_info_db.build().to_string(),
+			added: added.into_iter().map(str::to_string).collect(),
+			removed: removed.into_iter().map(str::to_string).collect(),
+			renamed,
+			changed,
+		}
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.added.is_empty() && self.removed.is_empty() && self.renamed.is_empty() && self.changed.is_empty()
+	}
+
+	/// A plain text report, suitable for showing to the user or copying to the clipboard
+	pub fn text(&self) -> String {
+		let mut text = format!("{} \u{2192} {}\n", self.old_build, self.new_build);
+		if self.is_empty() {
+			text.push_str("\nNo machines were added, removed, renamed or changed\n");
+			return text;
+		}
+
+		let mut section = |title: &str, lines: Vec<String>| {
+			if !lines.is_empty() {
+				writeln!(text, "\n{title} ({}):", lines.len()).unwrap();
+				for line in lines {
+					writeln!(text, "  {line}").unwrap();
+				}
+			}
+		};
+		section("New machines", self.added.clone());
+		section("Removed machines", self.removed.clone());
+		section("Renamed machines", self.renamed.iter().map(|x| x.to_string()).collect());
+		section(
+			"Changed machines",
+			self.changed
+				.iter()
+				.map(|x| {
+					format!(
+						"{}: {} \"{}\" \u{2192} \"{}\"",
+						x.name, x.field, x.old_value, x.new_value
+					)
+				})
+				.collect(),
+		);
+		text
+	}
+}
+
+fn machines_map(info_db: &InfoDb) -> BTreeMap<&'_ str, Machine<'_>> {
+	info_db.machines().iter().map(|x| (x.name(), x)).collect()
+}
+
+fn same_identity(a: Machine<'_>, b: Machine<'_>) -> bool {
+	a.description() == b.description() && a.manufacturer() == b.manufacturer() && a.year() == b.year()
+}
+
+#[cfg(test)]
+mod test {
+	use crate::info::InfoDb;
+	use crate::renames::MachineRename;
+
+	use super::InfoDbDiff;
+	use super::MachineChange;
+
+	fn info_db(xml: &str) -> InfoDb {
+		InfoDb::from_listxml_output(xml.as_bytes(), |_| false).unwrap().unwrap()
+	}
+
+	#[test]
+	pub fn no_changes() {
+		let xml = include_str!("info/test_data/listxml_coco.xml");
+		let diff = InfoDbDiff::new(&info_db(xml), &info_db(xml));
+		assert!(diff.is_empty());
+	}
+
+	#[test]
+	pub fn changes() {
+		let old_xml = include_str!("info/test_data/listxml_coco.xml");
+		let new_xml = old_xml
+			.replace("\"coco2b\"", "\"coco2bus\"")
+			.replace("<year>1980</year>", "<year>1981</year>");
+		let diff = InfoDbDiff::new(&info_db(old_xml), &info_db(&new_xml));
+
+		let expected_renamed = vec![MachineRename {
+			old_name: "coco2b".into(),
+			new_name: "coco2bus".into(),
+		}];
+		assert_eq!(
+			(Vec::<String>::new(), Vec::<String>::new(), expected_renamed),
+			(diff.added, diff.removed, diff.renamed)
+		);
+		let expected_changed = vec![MachineChange {
+			name: "coco".into(),
+			field: "Year",
+			old_value: "1980".into(),
+			new_value: "1981".into(),
+		}];
+		assert_eq!(expected_changed, diff.changed);
+	}
+}
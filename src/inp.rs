@@ -0,0 +1,58 @@
+//! MAME input recording (`.inp`) files
+use std::io::Read;
+
+use anyhow::Error;
+use anyhow::Result;
+
+const INP_MAGIC: &[u8; 8] = b"MAMEINP\0";
+const SYSNAME_OFFSET: usize = 0x14;
+const SYSNAME_LENGTH: usize = 12;
+
+#[derive(thiserror::Error, Debug)]
+enum ThisError {
+	#[error("Not a MAME input file")]
+	NotInpFile,
+	#[error("MAME input file does not identify a machine")]
+	NoMachineName,
+}
+
+/// Reads the name of the machine an input file was recorded with from its header
+pub fn inp_machine_name(mut reader: impl Read) -> Result<String> {
+	let mut header = [0u8; SYSNAME_OFFSET + SYSNAME_LENGTH];
+	reader
+		.read_exact(&mut header)
+		.map_err(|e| Error::new(e).context("Error reading MAME input file"))?;
+	if !header.starts_with(INP_MAGIC) {
+		return Err(ThisError::NotInpFile.into());
+	}
+
+	let sysname = &header[SYSNAME_OFFSET..];
+	let sysname = sysname.split(|&b| b == 0).next().unwrap_or_default();
+	let sysname = String::from_utf8_lossy(sysname).trim().to_string();
+	if sysname.is_empty() {
+		return Err(ThisError::NoMachineName.into());
+	}
+	Ok(sysname)
+}
+
+#[cfg(test)]
+mod test {
+	use test_case::test_case;
+
+	fn header(magic: &[u8; 8], sysname: &str) -> Vec<u8> {
+		let mut result = vec![0u8; 0x40];
+		result[..8].copy_from_slice(magic);
+		result[0x14..(0x14 + sysname.len())].copy_from_slice(sysname.as_bytes());
+		result
+	}
+
+	#[test_case(0, header(b"MAMEINP\0", "coco2b"), Some("coco2b"))]
+	#[test_case(1, header(b"MAMEINP\0", "pacmanxx1234"), Some("pacmanxx1234"))]
+	#[test_case(2, header(b"MAMEINP\0", ""), None)]
+	#[test_case(3, header(b"NOTINP\0\0", "coco2b"), None)]
+	#[test_case(4, b"MAMEINP\0".to_vec(), None)]
+	pub fn inp_machine_name(_index: usize, bytes: Vec<u8>, expected: Option<&str>) {
+		let actual = super::inp_machine_name(bytes.as_slice()).ok();
+		assert_eq!(expected, actual.as_deref());
+	}
+}
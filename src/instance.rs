@@ -0,0 +1,211 @@
+//! Handing launch requests (e.g. - from the taskbar jump list) to the instance of BletchMAME that is already running
+//!
+//! The running instance listens on a localhost port, and advertises that port in `instance.json` alongside the
+//! preferences, together with a token so that only somebody who can read the preferences directory can talk to it.
+//! When no instance is running (or it cannot be reached), the request is instead handled by the new instance once it
+//! is ready.
+use std::fs::remove_file;
+use std::fs::write;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Read;
+use std::io::Write;
+use std::net::Ipv4Addr;
+use std::net::SocketAddr;
+use std::net::TcpListener;
+use std::net::TcpStream;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::thread::sleep;
+use std::thread::spawn;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use anyhow::Error;
+use anyhow::Result;
+use serde::Deserialize;
+use serde::Serialize;
+use tracing::event;
+use tracing::Level;
+
+use crate::remote::constant_time_eq;
+use crate::remote::generate_token;
+
+const LOG: Level = Level::DEBUG;
+
+const INSTANCE_FILE_NAME: &str = "instance.json";
+
+/// How often the listener checks whether it has been asked to shut down
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long either side waits on the other
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Longest message we are willing to read; requests are tiny
+const MAX_MESSAGE_LENGTH: u64 = 4096;
+
+const REPLY_OK: &str = "OK";
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LaunchRequest {
+	/// Run a machine, as if it was chosen in the GUI
+	RunMachine(String),
+	/// Run the machine that was last run, with the same images
+	ResumeLastSession,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct InstanceInfo {
+	port: u16,
+	token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Message {
+	token: String,
+	request: LaunchRequest,
+}
+
+pub struct InstanceListener {
+	shutdown: Arc<AtomicBool>,
+	handle: Option<JoinHandle<()>>,
+	info_path: PathBuf,
+}
+
+impl InstanceListener {
+	/// Starts listening for requests from other instances; `callback` is invoked on the listener thread
+	pub fn start(prefs_path: &Path, callback: impl Fn(LaunchRequest) + Send + 'static) -> Result<Self> {
+		let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+		listener.set_nonblocking(true)?;
+		let info = InstanceInfo {
+			port: listener.local_addr()?.port(),
+			token: generate_token(),
+		};
+		let info_path = prefs_path.join(INSTANCE_FILE_NAME);
+		write(&info_path, serde_json::to_vec(&info)?)?;
+		event!(LOG, "InstanceListener::start(): listening on port {}", info.port);
+
+		let shutdown = Arc::new(AtomicBool::new(false));
+		let shutdown_clone = shutdown.clone();
+		let handle = spawn(move || {
+			while !shutdown_clone.load(Ordering::Relaxed) {
+				match listener.accept() {
+					Ok((stream, _)) => {
+						// requests are tiny and rare, so there is no need to serve them on threads of their own
+						if let Err(e) = handle_connection(stream, &info.token, &callback) {
+							event!(LOG, "InstanceListener: error handling connection: {e:?}");
+						}
+					}
+					Err(_) => sleep(POLL_INTERVAL),
+				}
+			}
+		});
+
+		let result = Self {
+			shutdown,
+			handle: Some(handle),
+			info_path,
+		};
+		Ok(result)
+	}
+}
+
+impl Drop for InstanceListener {
+	fn drop(&mut self) {
+		self.shutdown.store(true, Ordering::Relaxed);
+		if let Some(handle) = self.handle.take() {
+			let _ = handle.join();
+		}
+		let _ = remove_file(&self.info_path);
+	}
+}
+
+fn handle_connection(stream: TcpStream, token: &str, callback: &impl Fn(LaunchRequest)) -> Result<()> {
+	stream.set_nonblocking(false)?;
+	stream.set_read_timeout(Some(TIMEOUT))?;
+	let mut line = String::new();
+	BufReader::new((&stream).take(MAX_MESSAGE_LENGTH)).read_line(&mut line)?;
+	let message = serde_json::from_str::<Message>(&line)?;
+	if !constant_time_eq(message.token.as_bytes(), token.as_bytes()) {
+		return Err(Error::msg("Bad token"));
+	}
+
+	event!(LOG, "InstanceListener: request={:?}", message.request);
+	callback(message.request);
+	writeln!(&stream, "{REPLY_OK}")?;
+	Ok(())
+}
+
+/// Hands a request to the running instance; fails if there is none (or it cannot be reached)
+pub fn forward_to_running_instance(prefs_path: &Path, request: &LaunchRequest) -> Result<()> {
+	let info = std::fs::read(prefs_path.join(INSTANCE_FILE_NAME))?;
+	let info = serde_json::from_slice::<InstanceInfo>(&info)?;
+	let address = SocketAddr::from((Ipv4Addr::LOCALHOST, info.port));
+	let stream = TcpStream::connect_timeout(&address, TIMEOUT)?;
+	stream.set_read_timeout(Some(TIMEOUT))?;
+
+	let message = Message {
+		token: info.token,
+		request: request.clone(),
+	};
+	writeln!(&stream, "{}", serde_json::to_string(&message)?)?;
+
+	let mut reply = String::new();
+	BufReader::new((&stream).take(MAX_MESSAGE_LENGTH)).read_line(&mut reply)?;
+	if reply.trim() != REPLY_OK {
+		return Err(Error::msg("Running instance did not accept the request"));
+	}
+	Ok(())
+}
+
+/// The command line arguments that make a request (e.g. - for jump list entries)
+pub fn launch_args(request: &LaunchRequest, prefs_path: Option<&Path>) -> Vec<String> {
+	let prefs_args = prefs_path
+		.into_iter()
+		.flat_map(|path| ["--prefs-path".to_string(), path.to_string_lossy().into_owned()]);
+	let request_args = match request {
+		LaunchRequest::RunMachine(machine_name) => vec!["--launch".to_string(), machine_name.clone()],
+		LaunchRequest::ResumeLastSession => vec!["--resume-last-session".to_string()],
+	};
+	prefs_args.chain(request_args).collect()
+}
+
+#[cfg(test)]
+mod test {
+	use std::path::Path;
+	use std::sync::mpsc::channel;
+	use std::time::Duration;
+
+	use tempdir::TempDir;
+	use test_case::test_case;
+
+	use super::InstanceListener;
+	use super::LaunchRequest;
+
+	#[test]
+	pub fn forward_to_running_instance() {
+		let prefs_dir = TempDir::new("bletchmame_instance").unwrap();
+		let (sender, receiver) = channel();
+		let listener = InstanceListener::start(prefs_dir.path(), move |request| sender.send(request).unwrap()).unwrap();
+
+		let request = LaunchRequest::RunMachine("coco2b".into());
+		super::forward_to_running_instance(prefs_dir.path(), &request).unwrap();
+		let actual = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+		assert_eq!(request, actual);
+
+		// once the instance goes away, there is nobody to forward to
+		drop(listener);
+		let result = super::forward_to_running_instance(prefs_dir.path(), &LaunchRequest::ResumeLastSession);
+		assert!(result.is_err());
+	}
+
+	#[test_case(0, LaunchRequest::RunMachine("coco2b".into()), &["--prefs-path", "/prefs", "--launch", "coco2b"])]
+	#[test_case(1, LaunchRequest::ResumeLastSession, &["--prefs-path", "/prefs", "--resume-last-session"])]
+	pub fn launch_args(_index: usize, request: LaunchRequest, expected: &[&str]) {
+		let actual = super::launch_args(&request, Some(Path::new("/prefs")));
+		assert_eq!(expected, actual.as_slice());
+	}
+}
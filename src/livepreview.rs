@@ -0,0 +1,93 @@
+//! Periodic snapshots of a detached session, so that it can be watched from the main window
+//!
+//! Slint caches images loaded from files by path, so each snapshot is saved under a new name and the previous ones
+//! are deleted once they are no longer needed.
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::fs::create_dir_all;
+use std::fs::remove_dir_all;
+use std::fs::remove_file;
+use std::path::PathBuf;
+
+use slint::Image;
+
+pub struct LivePreview {
+	directory: PathBuf,
+	sequence: Cell<u64>,
+	pending: RefCell<Option<PathBuf>>,
+}
+
+impl LivePreview {
+	fn with_directory(directory: PathBuf) -> Self {
+		Self {
+			directory,
+			sequence: Cell::new(0),
+			pending: RefCell::new(None),
+		}
+	}
+
+	/// Chooses the path for the next snapshot to be saved to; the previous snapshot is discarded
+	pub fn next_path(&self) -> Option<String> {
+		create_dir_all(&self.directory).ok()?;
+		let sequence = self.sequence.get();
+		self.sequence.set(sequence + 1);
+		let path = self.directory.join(format!("{sequence}.png"));
+		if let Some(old_path) = self.pending.replace(Some(path.clone())) {
+			let _ = remove_file(old_path);
+		}
+		path.into_os_string().into_string().ok()
+	}
+
+	/// Loads the most recently requested snapshot, if MAME has saved it yet
+	pub fn load_image(&self) -> Option<Image> {
+		let pending = self.pending.borrow();
+		Image::load_from_path(pending.as_ref()?).ok()
+	}
+
+	/// Discards any outstanding snapshot (e.g. - because the session is no longer active)
+	pub fn reset(&self) {
+		if let Some(old_path) = self.pending.take() {
+			let _ = remove_file(old_path);
+		}
+	}
+}
+
+impl Default for LivePreview {
+	fn default() -> Self {
+		let directory = std::env::temp_dir().join(format!("bletchmame-preview-{}", std::process::id()));
+		Self::with_directory(directory)
+	}
+}
+
+impl Drop for LivePreview {
+	fn drop(&mut self) {
+		let _ = remove_dir_all(&self.directory);
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::path::Path;
+
+	use tempdir::TempDir;
+
+	use super::LivePreview;
+
+	#[test]
+	pub fn next_path() {
+		let tmp_dir = TempDir::new("livepreview").unwrap();
+		let directory = tmp_dir.path().join("preview");
+		let live_preview = LivePreview::with_directory(directory.clone());
+
+		// each snapshot gets a new name, and previous ones are cleaned up
+		let first = live_preview.next_path().unwrap();
+		std::fs::write(&first, b"").unwrap();
+		let second = live_preview.next_path().unwrap();
+		assert_ne!(first, second);
+		assert!(!Path::new(&first).exists());
+
+		// the directory goes away with the preview
+		drop(live_preview);
+		assert!(!directory.exists());
+	}
+}
@@ -0,0 +1,213 @@
+//! Frontend macros; sequences of commands issued to a running session that can be recorded and replayed with timing
+use std::cell::RefCell;
+use std::io::Read;
+use std::io::Write;
+use std::time::Duration;
+use std::time::Instant;
+
+use anyhow::Error;
+use anyhow::Result;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::runtime::MameCommand;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MacroCommand {
+	SoftReset,
+	HardReset,
+	Pause,
+	Resume,
+	LoadImage(Vec<(String, String)>),
+	UnloadImage(String),
+	ChangeSlots(Vec<(String, String)>),
+	StateLoad(String),
+	Paste(String),
+}
+
+impl MacroCommand {
+	/// Returns the macro equivalent of a command, or `None` if the command should not be recorded
+	pub fn from_mame_command(command: &MameCommand<'_>) -> Option<Self> {
+		let result = match command {
+			MameCommand::SoftReset => Self::SoftReset,
+			MameCommand::HardReset => Self::HardReset,
+			MameCommand::Pause => Self::Pause,
+			MameCommand::Resume => Self::Resume,
+			MameCommand::LoadImage(loads) => Self::LoadImage(owned_pairs(loads)),
+			MameCommand::UnloadImage(tag) => Self::UnloadImage(tag.to_string()),
+			MameCommand::ChangeSlots(changes) => Self::ChangeSlots(owned_pairs(changes)),
+			MameCommand::StateLoad(path) => Self::StateLoad(path.to_string()),
+			MameCommand::Paste(text) => Self::Paste(text.to_string()),
+			_ => return None,
+		};
+		Some(result)
+	}
+
+	/// Invokes `func` with the `MameCommand` equivalent of this macro command
+	pub fn with_mame_command<R>(&self, func: impl FnOnce(MameCommand<'_>) -> R) -> R {
+		match self {
+			Self::SoftReset => func(MameCommand::SoftReset),
+			Self::HardReset => func(MameCommand::HardReset),
+			Self::Pause => func(MameCommand::Pause),
+			Self::Resume => func(MameCommand::Resume),
+			Self::LoadImage(loads) => func(MameCommand::LoadImage(&borrowed_pairs(loads))),
+			Self::UnloadImage(tag) => func(MameCommand::UnloadImage(tag)),
+			Self::ChangeSlots(changes) => func(MameCommand::ChangeSlots(&borrowed_pairs(changes))),
+			Self::StateLoad(path) => func(MameCommand::StateLoad(path)),
+			Self::Paste(text) => func(MameCommand::Paste(text)),
+		}
+	}
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MacroStep {
+	/// Milliseconds to wait after the previous step before issuing this command
+	pub delay: u64,
+	pub command: MacroCommand,
+}
+
+impl MacroStep {
+	pub fn delay(&self) -> Duration {
+		Duration::from_millis(self.delay)
+	}
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Macro {
+	pub steps: Vec<MacroStep>,
+}
+
+impl Macro {
+	pub fn load(reader: impl Read) -> Result<Self> {
+		serde_json::from_reader(reader).map_err(|e| Error::new(e).context("Error reading macro file"))
+	}
+
+	pub fn save(&self, writer: impl Write) -> Result<()> {
+		serde_json::to_writer_pretty(writer, self).map_err(|e| Error::new(e).context("Error writing macro file"))
+	}
+}
+
+/// Records commands issued to a session, along with the time between them
+#[derive(Debug, Default)]
+pub struct MacroRecorder(RefCell<Option<(Instant, Vec<MacroStep>)>>);
+
+impl MacroRecorder {
+	pub fn start(&self) {
+		self.0.replace(Some((Instant::now(), Vec::new())));
+	}
+
+	pub fn stop(&self) -> Option<Macro> {
+		self.0.take().map(|(_, steps)| Macro { steps })
+	}
+
+	pub fn is_recording(&self) -> bool {
+		self.0.borrow().is_some()
+	}
+
+	pub fn record(&self, command: &MameCommand<'_>) {
+		self.record_at(command, Instant::now());
+	}
+
+	fn record_at(&self, command: &MameCommand<'_>, now: Instant) {
+		let mut state = self.0.borrow_mut();
+		let Some((last_instant, steps)) = state.as_mut() else {
+			return;
+		};
+		let Some(command) = MacroCommand::from_mame_command(command) else {
+			return;
+		};
+		let delay = now.saturating_duration_since(*last_instant).as_millis();
+		let delay = delay.try_into().unwrap_or(u64::MAX);
+		steps.push(MacroStep { delay, command });
+		*last_instant = now;
+	}
+}
+
+fn owned_pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+	pairs.iter().map(|(a, b)| (a.to_string(), b.to_string())).collect()
+}
+
+fn borrowed_pairs(pairs: &[(String, String)]) -> Vec<(&str, &str)> {
+	pairs.iter().map(|(a, b)| (a.as_str(), b.as_str())).collect()
+}
+
+#[cfg(test)]
+mod test {
+	use std::time::Duration;
+	use std::time::Instant;
+
+	use crate::runtime::MameCommand;
+
+	use super::Macro;
+	use super::MacroCommand;
+	use super::MacroRecorder;
+	use super::MacroStep;
+
+	#[test]
+	fn record() {
+		let recorder = MacroRecorder::default();
+		recorder.record(&MameCommand::SoftReset);
+		assert!(!recorder.is_recording());
+
+		recorder.start();
+		let start = recorder.0.borrow().as_ref().unwrap().0;
+		recorder.record_at(&MameCommand::LoadImage(&[("ext:fdc:wd17xx:0", "/disk.dsk")]), start);
+		recorder.record_at(&MameCommand::Ping, start + Duration::from_millis(100));
+		recorder.record_at(&MameCommand::HardReset, start + Duration::from_millis(1500));
+		let actual = recorder.stop().unwrap();
+		assert!(!recorder.is_recording());
+
+		let expected = Macro {
+			steps: vec![
+				MacroStep {
+					delay: 0,
+					command: MacroCommand::LoadImage(vec![("ext:fdc:wd17xx:0".into(), "/disk.dsk".into())]),
+				},
+				MacroStep {
+					delay: 1500,
+					command: MacroCommand::HardReset,
+				},
+			],
+		};
+		assert_eq!(expected, actual);
+	}
+
+	#[test]
+	fn stale_recorder_instant() {
+		let recorder = MacroRecorder::default();
+		recorder.start();
+		let past = Instant::now() - Duration::from_secs(1);
+		recorder.record_at(&MameCommand::Pause, past);
+		assert_eq!(0, recorder.stop().unwrap().steps[0].delay);
+	}
+
+	#[test]
+	fn save_and_load() {
+		let mac = Macro {
+			steps: vec![
+				MacroStep {
+					delay: 250,
+					command: MacroCommand::Paste("RUN\n".into()),
+				},
+				MacroStep {
+					delay: 1000,
+					command: MacroCommand::ChangeSlots(vec![("ext".into(), "fdc".into())]),
+				},
+			],
+		};
+		let mut buf = Vec::new();
+		mac.save(&mut buf).unwrap();
+		let actual = Macro::load(buf.as_slice()).unwrap();
+		assert_eq!(mac, actual);
+	}
+
+	#[test]
+	fn round_trip_mame_command() {
+		let command = MacroCommand::LoadImage(vec![("cart".into(), "/foo.ccc".into())]);
+		let actual = command.with_mame_command(|x| MacroCommand::from_mame_command(&x));
+		assert_eq!(Some(command), actual);
+	}
+}
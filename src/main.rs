@@ -1,60 +1,154 @@
 #![cfg_attr(not(test), windows_subsystem = "windows")]
 mod appcommand;
+mod appearance;
 mod appstate;
 mod appwindow;
+mod attract;
+mod bios;
+mod catver;
 mod channel;
 mod childwindow;
+mod collation;
+mod collectionfile;
 mod collections;
 mod debugstr;
+mod desktopshortcut;
+mod details;
 mod devimageconfig;
 mod diagnostics;
 mod dialogs;
+mod discord;
+mod earlyexit;
 mod guiutils;
+#[cfg(feature = "test-harness")]
+mod harness;
+mod headless;
 mod history;
+mod historyxml;
+mod i18n;
 mod icon;
+mod identicon;
 mod info;
+mod infodiff;
+mod inp;
+mod instance;
+mod livepreview;
+mod macros;
+mod mameini;
+mod mameupdate;
 mod mconfig;
 mod models;
 mod parse;
 mod platform;
 mod prefs;
+mod prefsync;
+mod regions;
+mod remote;
+mod renames;
 mod runtime;
+mod screenshot;
 mod selection;
+mod sessionsummary;
+mod shortcuts;
+mod snapcompare;
 mod software;
 mod status;
 mod threadlocalbubble;
 mod version;
 mod xml;
 
+use std::env::current_exe;
+use std::env::var_os;
+use std::path::Path;
 use std::path::PathBuf;
 
 use dirs::config_local_dir;
 use guiutils::MenuingType;
 use muda::Menu;
+use rfd::MessageDialog;
+use rfd::MessageLevel;
 use slint::ComponentHandle;
 use structopt::StructOpt;
+use tracing::event;
 use tracing::Level;
+use tracing_subscriber::fmt::writer::MakeWriterExt;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
 
 use crate::appwindow::AppArgs;
 use crate::diagnostics::info_db_from_xml_file;
+use crate::diagnostics::status_from_xml_file;
+use crate::diagnostics::LogFilter;
+use crate::diagnostics::LogRing;
 use crate::guiutils::init_gui_utils;
 use crate::guiutils::menuing::MenuExt;
+use crate::headless::parse_run_item;
+use crate::headless::parse_run_software;
+use crate::headless::run_headless;
+use crate::headless::RunTarget;
+use crate::instance::forward_to_running_instance;
+use crate::instance::LaunchRequest;
 use crate::platform::platform_init;
+use crate::prefs::PrefsItem;
 use crate::runtime::MameStderr;
+use crate::software::set_software_list_cache_dir;
+use crate::status::status_schema_dtd;
 
 mod ui {
 	slint::include_modules!();
 }
 
+/// Setting this environment variable (to anything other than "0") is the same as `--read-only-prefs`
+const READ_ONLY_PREFS_ENV: &str = "BLETCHMAME_READ_ONLY_PREFS";
+
 #[derive(StructOpt, Debug)]
 #[structopt(name = "basic")]
 struct Opt {
 	#[structopt(long, parse(from_os_str))]
 	prefs_path: Option<PathBuf>,
 
+	/// Store preferences alongside the executable, rather than in the local configuration directory
+	#[structopt(long, conflicts_with = "prefs-path")]
+	portable: bool,
+
+	/// Never save changes to preferences (e.g. - for shared kiosks); they are discarded on exit
+	#[structopt(long)]
+	read_only_prefs: bool,
+
+	/// Recover from problems by ignoring saved window and appearance settings, using Slint menus, not loading
+	/// the machine list, and confirming before running anything
+	#[structopt(long)]
+	safe_mode: bool,
+
+	/// Run a machine without the GUI
+	#[structopt(long, conflicts_with_all = &["run-item", "run-software"])]
+	run: Option<String>,
+
+	/// Run software (e.g. - "coco_cart:mpak") without the GUI
+	#[structopt(long, conflicts_with = "run-item", parse(try_from_str = parse_run_software))]
+	run_software: Option<(String, String)>,
+
+	/// Run an item in a folder (e.g. - "Favorites:0") without the GUI
+	#[structopt(long, parse(try_from_str = parse_run_item))]
+	run_item: Option<(String, usize)>,
+
+	/// Run a machine in the GUI, handing it to BletchMAME if it is already running (used by the taskbar jump list)
+	#[structopt(long, conflicts_with_all = &["run", "run-item", "run-software", "resume-last-session"])]
+	launch: Option<String>,
+
+	/// Rerun the last machine run (with the same images) in the GUI, like `--launch`
+	#[structopt(long, conflicts_with_all = &["run", "run-item", "run-software"])]
+	resume_last_session: bool,
+
 	#[cfg_attr(feature = "diagnostics", structopt(long, parse(from_os_str)))]
 	process_xml: Option<PathBuf>,
 
+	#[cfg_attr(feature = "diagnostics", structopt(long, parse(from_os_str)))]
+	process_status_xml: Option<PathBuf>,
+
+	#[cfg_attr(feature = "diagnostics", structopt(long))]
+	status_schema: bool,
+
 	#[cfg_attr(feature = "diagnostics", structopt(long))]
 	log_level: Option<Level>,
 
@@ -72,10 +166,16 @@ fn main() {
 	// get the command line arguments
 	let opts = Opt::from_args();
 
-	// set up logging
-	tracing_subscriber::fmt()
-		.with_max_level(opts.log_level.unwrap_or(Level::INFO))
+	// set up logging; recent output is also retained for diagnostic reports, and the filter can be changed later
+	let log_ring = LogRing::default();
+	let log_ring_clone = log_ring.clone();
+	let (log_filter, log_filter_layer) = LogFilter::new(opts.log_level.unwrap_or(Level::INFO));
+	let fmt_layer = tracing_subscriber::fmt::layer()
 		.with_target(false)
+		.with_writer(std::io::stdout.and(move || log_ring_clone.clone()));
+	tracing_subscriber::registry()
+		.with(log_filter_layer)
+		.with(fmt_layer)
 		.init();
 
 	// are we doing diagnostics
@@ -83,15 +183,36 @@ fn main() {
 		info_db_from_xml_file(path);
 		return;
 	}
+	if let Some(path) = opts.process_status_xml {
+		status_from_xml_file(path);
+		return;
+	}
+	if opts.status_schema {
+		print!("{}", status_schema_dtd());
+		return;
+	}
 
 	// identify the preferences directory
-	let prefs_path = opts.prefs_path.or_else(|| {
-		let mut path = config_local_dir();
-		if let Some(path) = &mut path {
-			path.push("BletchMAME");
-		}
-		path
-	});
+	let prefs_path = if opts.portable {
+		current_exe().ok().and_then(|x| x.parent().map(Path::to_path_buf))
+	} else {
+		opts.prefs_path.or_else(|| {
+			let mut path = config_local_dir();
+			if let Some(path) = &mut path {
+				path.push("BletchMAME");
+			}
+			path
+		})
+	};
+	let read_only_prefs = opts.read_only_prefs || var_os(READ_ONLY_PREFS_ENV).is_some_and(|x| x != "0");
+
+	// parsed software lists are cached alongside our preferences, unless those are read-only
+	set_software_list_cache_dir(
+		prefs_path
+			.as_ref()
+			.filter(|_| !read_only_prefs)
+			.map(|x| x.join("softlists")),
+	);
 
 	// are we supposed to capture MAME's stderr? we almost always do, except when debugging
 	let mame_stderr = if opts.no_capture_mame_stderr {
@@ -100,6 +221,45 @@ fn main() {
 		MameStderr::Capture
 	};
 
+	// are we running something without the GUI?
+	let run_target = match (opts.run, opts.run_software, opts.run_item) {
+		(Some(machine_name), _, _) => Some(RunTarget::Item(PrefsItem::Machine { machine_name })),
+		(None, Some((software_list, software)), _) => Some(RunTarget::Item(PrefsItem::Software {
+			software_list,
+			software,
+		})),
+		(None, None, Some((folder_name, index))) => Some(RunTarget::FolderItem { folder_name, index }),
+		(None, None, None) => None,
+	};
+	if let Some(run_target) = run_target {
+		// launchers need to know how MAME fared, so we exit with its exit code
+		let exit_code =
+			run_headless(prefs_path.as_deref(), read_only_prefs, mame_stderr, &run_target).unwrap_or_else(|e| {
+				// on Windows we have no console, so stderr alone would swallow the error
+				eprintln!("{e}");
+				MessageDialog::new()
+					.set_level(MessageLevel::Error)
+					.set_title("BletchMAME")
+					.set_description(e.to_string())
+					.show();
+				1
+			});
+		std::process::exit(exit_code);
+	}
+
+	// launch requests (e.g. - from the taskbar jump list) are handed to the running instance, if there is one
+	let launch_request = match (opts.launch, opts.resume_last_session) {
+		(Some(machine_name), _) => Some(LaunchRequest::RunMachine(machine_name)),
+		(None, true) => Some(LaunchRequest::ResumeLastSession),
+		(None, false) => None,
+	};
+	if let (Some(request), Some(prefs_path)) = (&launch_request, &prefs_path) {
+		match forward_to_running_instance(prefs_path, request) {
+			Ok(()) => return,
+			Err(e) => event!(Level::DEBUG, "Could not forward launch request: {e:?}"),
+		}
+	}
+
 	// set up the tokio runtime
 	let tokio_runtime = tokio::runtime::Builder::new_multi_thread()
 		.enable_time()
@@ -110,9 +270,10 @@ fn main() {
 	// initialize our GUI utility code that will hopefully go away as Slint improves
 	init_gui_utils();
 
-	// what types of menus will we be using?
+	// what types of menus will we be using?  safe mode always uses Slint menus, in case
+	// native menuing is what is broken
 	let menuing_type = opts.menuing.unwrap_or_else(|| {
-		if Menu::is_natively_supported() {
+		if Menu::is_natively_supported() && !opts.safe_mode {
 			MenuingType::Native
 		} else {
 			MenuingType::Slint
@@ -124,6 +285,11 @@ fn main() {
 		prefs_path,
 		mame_stderr,
 		menuing_type,
+		safe_mode: opts.safe_mode,
+		read_only_prefs,
+		log_ring,
+		log_filter,
+		launch_request,
 	};
 	let app_window = appwindow::create(args);
 
@@ -144,4 +310,25 @@ mod test {
 		let attrs = Opt::from_iter_safe(empty_args.iter());
 		assert_matches!(attrs, Ok(_));
 	}
+
+	#[test]
+	fn opts_run_item() {
+		let args = ["bletchmame", "--run-item", "Favorites:2"];
+		let opts = Opt::from_iter_safe(args.iter()).unwrap();
+		assert_eq!(Some(("Favorites".to_string(), 2)), opts.run_item);
+	}
+
+	#[test]
+	fn opts_launch_conflicts_with_run() {
+		let args = ["bletchmame", "--launch", "coco2b", "--run", "coco2b"];
+		let opts = Opt::from_iter_safe(args.iter());
+		assert_matches!(opts, Err(_));
+	}
+
+	#[test]
+	fn opts_portable_conflicts_with_prefs_path() {
+		let args = ["bletchmame", "--portable", "--prefs-path", "/prefs"];
+		let opts = Opt::from_iter_safe(args.iter());
+		assert_matches!(opts, Err(_));
+	}
 }
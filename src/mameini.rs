@@ -0,0 +1,85 @@
+//! Minimal reader for MAME's own `mame.ini`, used to suggest where to browse for paths
+//!
+//! Each option is a line of the form `name value`; `#` starts a comment and values may be quoted
+use std::collections::HashMap;
+use std::fs::read_to_string;
+use std::path::Path;
+use std::path::PathBuf;
+
+#[derive(Debug, Default)]
+pub struct MameIni {
+	options: HashMap<String, String>,
+}
+
+impl MameIni {
+	/// Loads `mame.ini` from alongside the MAME executable (or its `ini/` subdirectory)
+	pub fn load(mame_executable: impl AsRef<Path>) -> Option<Self> {
+		let mame_dir = mame_executable.as_ref().parent()?;
+		[mame_dir.join("mame.ini"), mame_dir.join("ini").join("mame.ini")]
+			.iter()
+			.find_map(|path| read_to_string(path).ok())
+			.map(|text| Self::from_text(&text))
+	}
+
+	pub fn from_text(text: &str) -> Self {
+		let options = text
+			.lines()
+			.map(str::trim)
+			.filter(|line| !line.is_empty() && !line.starts_with('#'))
+			.filter_map(|line| {
+				let (name, value) = line.split_once(char::is_whitespace)?;
+				let value = value.trim();
+				let value = value
+					.strip_prefix('"')
+					.and_then(|x| x.strip_suffix('"'))
+					.unwrap_or(value);
+				Some((name.to_string(), value.to_string()))
+			})
+			.collect();
+		Self { options }
+	}
+
+	pub fn get(&self, name: &str) -> Option<&str> {
+		self.options.get(name).map(String::as_str)
+	}
+
+	/// The first directory listed for a (possibly `;` separated) path option, resolved against the MAME directory
+	pub fn first_path(&self, name: &str, mame_executable: impl AsRef<Path>) -> Option<PathBuf> {
+		let path = self.get(name)?.split(';').map(str::trim).find(|x| !x.is_empty())?;
+		let path = Path::new(path);
+		if path.is_absolute() {
+			Some(path.to_path_buf())
+		} else {
+			mame_executable.as_ref().parent().map(|mame_dir| mame_dir.join(path))
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::path::Path;
+
+	use test_case::test_case;
+
+	use super::MameIni;
+
+	const TEXT: &str = "#\n# CORE SEARCH PATH OPTIONS\n#\nhomepath                  .\nrompath                   \"roms;/mnt/roms\"\nhashpath                  hash;ahash\nsamplepath                /abs/samples\ncfg_directory\n";
+
+	#[test_case(0, "rompath", Some("roms;/mnt/roms"))]
+	#[test_case(1, "hashpath", Some("hash;ahash"))]
+	#[test_case(2, "cfg_directory", None)]
+	#[test_case(3, "artpath", None)]
+	pub fn get(_index: usize, name: &str, expected: Option<&str>) {
+		let ini = MameIni::from_text(TEXT);
+		assert_eq!(expected, ini.get(name));
+	}
+
+	#[test_case(0, "rompath", Some("/mame/roms"))]
+	#[test_case(1, "samplepath", Some("/abs/samples"))]
+	#[test_case(2, "artpath", None)]
+	pub fn first_path(_index: usize, name: &str, expected: Option<&str>) {
+		let ini = MameIni::from_text(TEXT);
+		let actual = ini.first_path(name, "/mame/mame");
+		assert_eq!(expected.map(Path::new), actual.as_deref());
+	}
+}
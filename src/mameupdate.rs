@@ -0,0 +1,178 @@
+//! Keeping MAME up to date from the official releases on GitHub
+//!
+//! The MAME project only publishes Windows builds, as 7-Zip self-extracting executables; these understand the usual
+//! `-o<dir>` and `-y` switches, so extracting one is a matter of running it.  Releases are installed into their own
+//! directories alongside the preferences, so earlier releases remain available as other MAME executables.
+use std::fs::create_dir_all;
+use std::fs::remove_file;
+use std::fs::File;
+use std::io::copy;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+
+use anyhow::Error;
+use anyhow::Result;
+use serde::Deserialize;
+use tracing::event;
+use tracing::Level;
+
+use crate::platform::CommandExt;
+use crate::version::MameVersion;
+
+const LOG: Level = Level::DEBUG;
+
+const LATEST_RELEASE_URL: &str = "https://api.github.com/repos/mamedev/mame/releases/latest";
+
+/// GitHub refuses API requests without a user agent
+const USER_AGENT: &str = concat!("BletchMAME/", env!("CARGO_PKG_VERSION"));
+
+/// How the builds for this platform are named (e.g. - "mame0274b_x64.exe"); older releases used "_64bit"
+const ASSET_SUFFIXES: &[&str] = if cfg!(all(target_os = "windows", target_arch = "x86_64")) {
+	&["b_x64.exe", "b_64bit.exe"]
+} else if cfg!(all(target_os = "windows", target_arch = "aarch64")) {
+	&["b_arm64.exe"]
+} else {
+	&[]
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MameRelease {
+	pub version: MameVersion,
+	pub tag: String,
+	pub file_name: String,
+	pub download_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubRelease {
+	tag_name: String,
+	assets: Vec<GithubAsset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubAsset {
+	name: String,
+	browser_download_url: String,
+}
+
+/// Asks GitHub for the latest release of MAME; returns `None` if there is no build for this platform
+pub fn latest_release() -> Result<Option<MameRelease>> {
+	let release = ureq::get(LATEST_RELEASE_URL)
+		.set("User-Agent", USER_AGENT)
+		.call()?
+		.into_json::<GithubRelease>()?;
+	event!(LOG, "latest_release(): tag_name={:?}", release.tag_name);
+	Ok(release_for_platform(release, ASSET_SUFFIXES))
+}
+
+fn release_for_platform(release: GithubRelease, asset_suffixes: &[&str]) -> Option<MameRelease> {
+	// tags look like "mame0274"
+	let digits = release.tag_name.strip_prefix("mame")?;
+	let (major, minor) = (digits.get(..1)?, digits.get(1..)?);
+	let version = MameVersion::new(major.parse().ok()?, minor.parse().ok()?);
+
+	let asset = release
+		.assets
+		.into_iter()
+		.find(|asset| asset_suffixes.iter().any(|suffix| asset.name.ends_with(suffix)))?;
+	let result = MameRelease {
+		version,
+		tag: release.tag_name,
+		file_name: asset.name,
+		download_url: asset.browser_download_url,
+	};
+	Some(result)
+}
+
+/// Is this release an update to the MAME we have (if any)?
+pub fn is_update(release: &MameRelease, current: Option<&MameVersion>) -> bool {
+	current.is_none_or(|current| release.version > *current)
+}
+
+/// Downloads and extracts a release into `<prefs_path>/mame/<tag>`, returning the path to the MAME executable
+pub fn install_release(release: &MameRelease, prefs_path: &Path) -> Result<PathBuf> {
+	let dir = prefs_path.join("mame").join(&release.tag);
+	create_dir_all(&dir)?;
+
+	// download the self-extracting archive...
+	let archive_path = dir.join(&release.file_name);
+	event!(
+		LOG,
+		"install_release(): downloading {:?} to {:?}",
+		release.download_url,
+		archive_path
+	);
+	let mut reader = ureq::get(&release.download_url)
+		.set("User-Agent", USER_AGENT)
+		.call()?
+		.into_reader();
+	copy(&mut reader, &mut File::create(&archive_path)?)?;
+
+	// ...and run it
+	let status = Command::new(&archive_path)
+		.arg(format!("-o{}", dir.display()))
+		.arg("-y")
+		.create_no_window(true)
+		.status();
+	let _ = remove_file(&archive_path);
+	if !status?.success() {
+		return Err(Error::msg(format!("Could not extract {}", release.file_name)));
+	}
+
+	let mame_executable = dir.join(if cfg!(target_os = "windows") {
+		"mame.exe"
+	} else {
+		"mame"
+	});
+	if !mame_executable.is_file() {
+		return Err(Error::msg(format!("{} did not contain MAME", release.file_name)));
+	}
+	Ok(mame_executable)
+}
+
+#[cfg(test)]
+mod test {
+	use test_case::test_case;
+
+	use super::GithubRelease;
+	use crate::version::MameVersion;
+
+	const RELEASE_JSON: &str = r#"{
+		"tag_name": "mame0274",
+		"assets": [
+			{ "name": "mame0274s.exe", "browser_download_url": "https://example.com/mame0274s.exe" },
+			{ "name": "mame0274b_arm64.exe", "browser_download_url": "https://example.com/mame0274b_arm64.exe" },
+			{ "name": "mame0274b_x64.exe", "browser_download_url": "https://example.com/mame0274b_x64.exe" }
+		]
+	}"#;
+
+	#[test_case(0, &["b_x64.exe", "b_64bit.exe"], Some("mame0274b_x64.exe"))]
+	#[test_case(1, &["b_arm64.exe"], Some("mame0274b_arm64.exe"))]
+	#[test_case(2, &[], None)]
+	pub fn release_for_platform(_index: usize, asset_suffixes: &[&str], expected: Option<&str>) {
+		let release = serde_json::from_str::<GithubRelease>(RELEASE_JSON).unwrap();
+		let actual = super::release_for_platform(release, asset_suffixes);
+		assert_eq!(expected, actual.as_ref().map(|x| x.file_name.as_str()));
+		if let Some(actual) = actual {
+			assert_eq!(MameVersion::new(0, 274), actual.version);
+			assert_eq!("mame0274", actual.tag);
+		}
+	}
+
+	#[test_case(0, None, true)]
+	#[test_case(1, Some((0, 273)), true)]
+	#[test_case(2, Some((0, 274)), false)]
+	#[test_case(3, Some((0, 275)), false)]
+	pub fn is_update(_index: usize, current: Option<(u16, u16)>, expected: bool) {
+		let release = super::MameRelease {
+			version: MameVersion::new(0, 274),
+			tag: "mame0274".into(),
+			file_name: "mame0274b_x64.exe".into(),
+			download_url: "https://example.com/mame0274b_x64.exe".into(),
+		};
+		let current = current.map(|(major, minor)| MameVersion::new(major, minor));
+		let actual = super::is_update(&release, current.as_ref());
+		assert_eq!(expected, actual);
+	}
+}
@@ -1,6 +1,8 @@
 use std::any::Any;
 use std::cell::Cell;
 use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::future::Future;
 use std::pin::Pin;
 use std::rc::Rc;
@@ -13,16 +15,32 @@ use slint::ModelTracker;
 use slint::Weak;
 
 use crate::appcommand::AppCommand;
+use crate::collections::collection_tree_rows;
+use crate::collections::folder_move_targets;
+use crate::collections::CollectionRow;
 use crate::guiutils::menuing::MenuDesc;
+use crate::i18n::tr;
 use crate::info::InfoDb;
 use crate::prefs::PrefsCollection;
 use crate::ui::AppWindow;
 use crate::ui::MagicListViewItem;
 
+/// Prefixes for folders, which can have other folders nested beneath them
+const FOLDER_EXPANDED_PREFIX: &str = "\u{25BE} ";
+const FOLDER_COLLAPSED_PREFIX: &str = "\u{25B8} ";
+const FOLDER_NESTED_PREFIX: &str = "    ";
+
 pub struct CollectionsViewModel {
 	app_window_weak: Weak<AppWindow>,
 	info_db: RefCell<Option<Rc<InfoDb>>>,
 	items: RefCell<Vec<Rc<PrefsCollection>>>,
+
+	/// How `items` are laid out, with folders nested beneath their parents
+	rows: RefCell<Vec<CollectionRow>>,
+	collapsed: RefCell<BTreeSet<String>>,
+
+	/// Collections generated from the category file; listed after `items` but never persisted
+	category_items: RefCell<Vec<Rc<PrefsCollection>>>,
 	after_refresh_callback: Cell<Option<Box<dyn Future<Output = ()> + 'static>>>,
 	notify: ModelNotify,
 }
@@ -33,14 +51,31 @@ impl CollectionsViewModel {
 			app_window_weak,
 			info_db: RefCell::new(None),
 			items: RefCell::new(Vec::new()),
+			rows: RefCell::new(Vec::new()),
+			collapsed: RefCell::new(BTreeSet::new()),
+			category_items: RefCell::new(Vec::new()),
 			after_refresh_callback: Cell::new(None),
 			notify: ModelNotify::default(),
 		}
 	}
 
-	pub fn update(&self, info_db: Option<Rc<InfoDb>>, items: &[Rc<PrefsCollection>]) {
+	pub fn update(&self, info_db: Option<Rc<InfoDb>>, items: &[Rc<PrefsCollection>], collapsed: &BTreeSet<String>) {
 		self.info_db.replace(info_db);
 		self.items.replace(items.to_vec());
+		self.rows.replace(collection_tree_rows(items, collapsed));
+		self.collapsed.replace(collapsed.clone());
+		self.notify.reset();
+	}
+
+	pub fn set_categories(&self, categories: &[&str]) {
+		let category_items = categories
+			.iter()
+			.map(|category| {
+				let category = category.to_string();
+				Rc::new(PrefsCollection::Category { category })
+			})
+			.collect();
+		self.category_items.replace(category_items);
 		self.notify.reset();
 	}
 
@@ -49,9 +84,40 @@ impl CollectionsViewModel {
 		items.clone()
 	}
 
-	pub fn get(&self, index: usize) -> Option<Rc<PrefsCollection>> {
+	pub fn get(&self, row: usize) -> Option<Rc<PrefsCollection>> {
+		let rows_len = self.rows.borrow().len();
+		if let Some(index) = self.collection_index(row) {
+			self.items.borrow().get(index).cloned()
+		} else {
+			self.category_items.borrow().get(row.checked_sub(rows_len)?).cloned()
+		}
+	}
+
+	/// The index within the collections in preferences shown on a row; generated category collections have none
+	pub fn collection_index(&self, row: usize) -> Option<usize> {
+		self.rows.borrow().get(row).map(|x| x.index)
+	}
+
+	/// The row showing a collection from preferences; collections nested within collapsed folders have none
+	pub fn row(&self, collection_index: usize) -> Option<usize> {
+		self.rows.borrow().iter().position(|x| x.index == collection_index)
+	}
+
+	/// If a row is a folder, returns its index within the collections in preferences
+	pub fn folder_index(&self, row: usize) -> Option<usize> {
+		let index = self.collection_index(row)?;
 		let items = self.items.borrow();
-		items.get(index).cloned()
+		matches!(items.get(index)?.as_ref(), PrefsCollection::Folder { .. }).then_some(index)
+	}
+
+	/// Finds a generated category collection, returning its row in the view
+	pub fn category_index(&self, collection: &PrefsCollection) -> Option<usize> {
+		let position = self
+			.category_items
+			.borrow()
+			.iter()
+			.position(|x| x.as_ref() == collection)?;
+		Some(self.rows.borrow().len() + position)
 	}
 
 	pub fn callback_after_refresh(&self, callback: impl Future<Output = ()> + 'static) {
@@ -59,40 +125,95 @@ impl CollectionsViewModel {
 		self.after_refresh_callback.set(Some(callback));
 	}
 
-	pub fn context_commands(&self, index: Option<usize>) -> Option<Menu> {
+	pub fn context_commands(
+		&self,
+		row: Option<usize>,
+		folder_capture_directories: &BTreeMap<String, String>,
+	) -> Option<Menu> {
 		let mut menu_items = Vec::new();
 
-		// menu items pertaining to selected collections
-		if let Some(old_index) = index {
+		// menu items pertaining to selected collections (generated category collections cannot be changed)
+		if let Some(old_index) = row.and_then(|row| self.collection_index(row)) {
 			let items = self.items.borrow();
 			if old_index > 0 {
 				let new_index = Some(old_index - 1);
 				let command = AppCommand::MoveCollection { old_index, new_index };
-				menu_items.push(MenuDesc::Item("Move Up".into(), Some(command.into())));
+				menu_items.push(MenuDesc::Item(tr("Move Up"), Some(command.into())));
 			}
 			if old_index < items.len() - 1 {
 				let new_index = Some(old_index + 1);
 				let command = AppCommand::MoveCollection { old_index, new_index };
-				menu_items.push(MenuDesc::Item("Move Down".into(), Some(command.into())));
+				menu_items.push(MenuDesc::Item(tr("Move Down"), Some(command.into())));
 			}
 			if items.len() > 1 {
 				let command = AppCommand::DeleteCollectionDialog { index: old_index };
-				menu_items.push(MenuDesc::Item("Delete".into(), Some(command.into())));
+				menu_items.push(MenuDesc::Item(tr("Delete"), Some(command.into())));
 			}
-			if items
-				.get(old_index)
-				.map(|x| matches!(x.as_ref(), PrefsCollection::Folder { .. }))
-				.unwrap_or_default()
-			{
+			if let Some(PrefsCollection::Folder { name, parent, .. }) = items.get(old_index).map(|x| x.as_ref()) {
 				let command = AppCommand::RenameCollectionDialog { index: old_index };
-				menu_items.push(MenuDesc::Item("Rename...".into(), Some(command.into())));
+				menu_items.push(MenuDesc::Item(tr("Rename..."), Some(command.into())));
+				let command = AppCommand::ExportCollectionDialog { index: old_index };
+				menu_items.push(MenuDesc::Item(tr("Export Collection..."), Some(command.into())));
+
+				// nesting folders
+				let has_children = self
+					.row(old_index)
+					.is_some_and(|row| self.rows.borrow()[row].has_children);
+				if has_children {
+					let text = if self.collapsed.borrow().contains(name) {
+						"Expand"
+					} else {
+						"Collapse"
+					};
+					let command = AppCommand::ToggleFolderExpanded { name: name.clone() };
+					menu_items.push(MenuDesc::Item(tr(text), Some(command.into())));
+				}
+				let top_level = parent.is_some().then(|| {
+					let command = AppCommand::MoveFolder {
+						index: old_index,
+						parent: None,
+					};
+					MenuDesc::Item(tr("Top Level"), Some(command.into()))
+				});
+				let targets = folder_move_targets(&items, old_index);
+				let target_items = targets
+					.into_iter()
+					.filter(|x| parent.as_ref() != Some(x))
+					.map(|target| {
+						let text = target.clone().into();
+						let command = AppCommand::MoveFolder {
+							index: old_index,
+							parent: Some(target),
+						};
+						MenuDesc::Item(text, Some(command.into()))
+					});
+				let move_items = top_level.into_iter().chain(target_items).collect::<Vec<_>>();
+				if !move_items.is_empty() {
+					menu_items.push(MenuDesc::SubMenu(tr("Move Into"), true, move_items));
+				}
+
+				let command = AppCommand::FolderCaptureDirectoryDialog {
+					folder_name: name.clone(),
+				};
+				menu_items.push(MenuDesc::Item(tr("Set Capture Directory..."), Some(command.into())));
+				let command = folder_capture_directories.contains_key(name).then(|| {
+					let folder_name = name.clone();
+					AppCommand::FolderCaptureDirectorySet {
+						folder_name,
+						directory: None,
+					}
+				});
+				let text = "Clear Capture Directory".into();
+				menu_items.push(MenuDesc::Item(text, command.map(|x| x.into())));
 			}
 			menu_items.push(MenuDesc::Separator);
 		}
 
 		// new collection
 		let command = AppCommand::AddToNewFolderDialog([].into());
-		menu_items.push(MenuDesc::Item("New Collection".into(), Some(command.into())));
+		menu_items.push(MenuDesc::Item(tr("New Collection"), Some(command.into())));
+		let command = AppCommand::ImportCollectionDialog;
+		menu_items.push(MenuDesc::Item(tr("Import Collection..."), Some(command.into())));
 
 		// make the popup menu
 		Some(MenuDesc::make_popup_menu(menu_items))
@@ -105,7 +226,7 @@ impl Model for CollectionsViewModel {
 	fn row_count(&self) -> usize {
 		invoke_after_refresh_callback(&self.after_refresh_callback);
 		if self.info_db.borrow().is_some() {
-			self.items.borrow().len()
+			self.rows.borrow().len() + self.category_items.borrow().len()
 		} else {
 			0
 		}
@@ -114,9 +235,22 @@ impl Model for CollectionsViewModel {
 	fn row_data(&self, row: usize) -> Option<Self::Data> {
 		let info_db = self.info_db.borrow();
 		let info_db = info_db.as_ref()?.as_ref();
+		let tree_row = self.rows.borrow().get(row).copied();
 		self.get(row).map(|item| {
 			let prefix_icon = item.icon().slint_icon(&self.app_window_weak.unwrap());
-			let text = item.description(info_db).as_ref().into();
+			let text = item.description(info_db);
+			let text = if let Some(tree_row) = tree_row {
+				let expander = match (&*item, tree_row.has_children) {
+					(PrefsCollection::Folder { name, .. }, true) if self.collapsed.borrow().contains(name) => {
+						FOLDER_COLLAPSED_PREFIX
+					}
+					(_, true) => FOLDER_EXPANDED_PREFIX,
+					(_, false) => "",
+				};
+				format!("{}{expander}{text}", FOLDER_NESTED_PREFIX.repeat(tree_row.depth)).into()
+			} else {
+				text.as_ref().into()
+			};
 			MagicListViewItem {
 				prefix_icon,
 				text,
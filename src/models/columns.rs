@@ -0,0 +1,430 @@
+//! The columns of the items view; each column comes from a `ColumnProvider`, and optional subsystems can register
+//! providers of their own with `register_column_provider()`
+use std::borrow::Cow;
+use std::sync::Arc;
+use std::sync::RwLock;
+
+use itertools::Itertools;
+
+use crate::catver::Catver;
+use crate::info::Chip;
+use crate::info::ChipType;
+use crate::info::InfoDb;
+use crate::info::Machine;
+use crate::info::View;
+use crate::prefs::ram_text;
+use crate::prefs::ColumnAlignment;
+use crate::prefs::ColumnType;
+use crate::regions::machine_region;
+use crate::regions::Languages;
+use crate::software::Software;
+use crate::software::SoftwareList;
+
+/// Machine information from files other than MAME itself
+#[derive(Clone, Default)]
+pub struct ExternalInfo {
+	pub catver: Option<Arc<Catver>>,
+	pub languages: Option<Arc<Languages>>,
+}
+
+impl ExternalInfo {
+	pub fn machine_category(&self, machine_name: &str) -> Option<&str> {
+		self.catver.as_deref()?.machine_category(machine_name)
+	}
+
+	/// The language from languages.ini if we have it, otherwise our best guess at the region
+	pub fn machine_region<'a>(&'a self, machine: Machine<'a>) -> Option<&'a str> {
+		if let Some(languages) = self.languages.as_deref() {
+			languages.machine_language(machine.name())
+		} else {
+			let clone_of = machine.clone_of().map(|x| x.name());
+			machine_region(machine.description(), machine.name(), clone_of)
+		}
+	}
+}
+
+/// What a row of the items view is about
+#[derive(Clone, Copy)]
+pub enum ColumnSubject<'a> {
+	Machine(Machine<'a>),
+	Software {
+		software_list: &'a SoftwareList,
+		software: &'a Software,
+		info_db: &'a InfoDb,
+		machine_indexes: &'a [usize],
+	},
+	UnrecognizedSoftware {
+		software_list_name: &'a str,
+		software_name: &'a str,
+	},
+}
+
+#[derive(Clone, Copy)]
+pub struct ColumnProvider {
+	pub column_type: ColumnType,
+	pub title: &'static str,
+	pub default_alignment: ColumnAlignment,
+
+	/// The relative share of the items table that this column gets when widths are reset
+	pub width_weight: f32,
+
+	pub text: for<'a> fn(&'a ExternalInfo, ColumnSubject<'a>) -> Cow<'a, str>,
+
+	/// Numeric columns sort by this rather than by their text
+	pub sort_key: Option<fn(ColumnSubject<'_>) -> Option<u64>>,
+}
+
+const BUILTIN_PROVIDERS: &[ColumnProvider] = &[
+	ColumnProvider {
+		column_type: ColumnType::NAME,
+		title: "Name",
+		default_alignment: ColumnAlignment::Left,
+		width_weight: 10.0,
+		text: name_text,
+		sort_key: None,
+	},
+	ColumnProvider {
+		column_type: ColumnType::SOURCE_FILE,
+		title: "Source File",
+		default_alignment: ColumnAlignment::Left,
+		width_weight: 15.0,
+		text: source_file_text,
+		sort_key: None,
+	},
+	ColumnProvider {
+		column_type: ColumnType::DESCRIPTION,
+		title: "Description",
+		default_alignment: ColumnAlignment::Left,
+		width_weight: 40.0,
+		text: description_text,
+		sort_key: None,
+	},
+	ColumnProvider {
+		column_type: ColumnType::YEAR,
+		title: "Year",
+		default_alignment: ColumnAlignment::Right,
+		width_weight: 8.0,
+		text: year_text,
+		sort_key: None,
+	},
+	ColumnProvider {
+		column_type: ColumnType::PROVIDER,
+		title: "Provider",
+		default_alignment: ColumnAlignment::Left,
+		width_weight: 18.0,
+		text: provider_text,
+		sort_key: None,
+	},
+	ColumnProvider {
+		column_type: ColumnType::CPU,
+		title: "CPU",
+		default_alignment: ColumnAlignment::Left,
+		width_weight: 15.0,
+		text: cpu_text,
+		sort_key: None,
+	},
+	ColumnProvider {
+		column_type: ColumnType::SOUND,
+		title: "Sound",
+		default_alignment: ColumnAlignment::Left,
+		width_weight: 15.0,
+		text: sound_text,
+		sort_key: None,
+	},
+	ColumnProvider {
+		column_type: ColumnType::RAM,
+		title: "RAM",
+		default_alignment: ColumnAlignment::Right,
+		width_weight: 10.0,
+		text: ram_column_text,
+		sort_key: Some(ram_sort_key),
+	},
+	ColumnProvider {
+		column_type: ColumnType::MAXIMUM_RAM,
+		title: "Maximum RAM",
+		default_alignment: ColumnAlignment::Right,
+		width_weight: 12.0,
+		text: maximum_ram_text,
+		sort_key: Some(maximum_ram_sort_key),
+	},
+	ColumnProvider {
+		column_type: ColumnType::CATEGORY,
+		title: "Category",
+		default_alignment: ColumnAlignment::Left,
+		width_weight: 15.0,
+		text: category_text,
+		sort_key: None,
+	},
+	ColumnProvider {
+		column_type: ColumnType::REGION,
+		title: "Region",
+		default_alignment: ColumnAlignment::Left,
+		width_weight: 10.0,
+		text: region_text,
+		sort_key: None,
+	},
+	ColumnProvider {
+		column_type: ColumnType::REQUIREMENTS,
+		title: "Requirements",
+		default_alignment: ColumnAlignment::Left,
+		width_weight: 10.0,
+		text: requirements_column_text,
+		sort_key: None,
+	},
+	ColumnProvider {
+		column_type: ColumnType::RUNS_ON,
+		title: "Runs On",
+		default_alignment: ColumnAlignment::Left,
+		width_weight: 20.0,
+		text: runs_on_text,
+		sort_key: None,
+	},
+];
+
+/// How many compatible machines the "Runs On" column names before summarizing the rest
+const RUNS_ON_COUNT: usize = 3;
+
+static REGISTERED_PROVIDERS: RwLock<Vec<ColumnProvider>> = RwLock::new(Vec::new());
+
+/// Adds a column beyond the builtin ones; registering a column that is already present replaces it
+pub fn register_column_provider(provider: ColumnProvider) {
+	let mut providers = REGISTERED_PROVIDERS.write().unwrap();
+	providers.retain(|x| x.column_type != provider.column_type);
+	providers.push(provider);
+}
+
+/// All available columns, builtin ones first
+pub fn column_providers() -> Vec<ColumnProvider> {
+	let registered = REGISTERED_PROVIDERS.read().unwrap();
+	BUILTIN_PROVIDERS
+		.iter()
+		.filter(|x| !registered.iter().any(|y| y.column_type == x.column_type))
+		.chain(registered.iter())
+		.copied()
+		.collect()
+}
+
+/// The provider for a column; columns whose provider is missing (e.g. - persisted by a subsystem that is not present)
+/// are shown blank, using their id as the title
+pub fn column_provider(column_type: ColumnType) -> ColumnProvider {
+	let registered = REGISTERED_PROVIDERS.read().unwrap();
+	registered
+		.iter()
+		.chain(BUILTIN_PROVIDERS)
+		.find(|x| x.column_type == column_type)
+		.copied()
+		.unwrap_or(ColumnProvider {
+			column_type,
+			title: column_type.id(),
+			default_alignment: ColumnAlignment::Left,
+			width_weight: 10.0,
+			text: missing_text,
+			sort_key: None,
+		})
+}
+
+fn name_text<'a>(_external_info: &'a ExternalInfo, subject: ColumnSubject<'a>) -> Cow<'a, str> {
+	match subject {
+		ColumnSubject::Machine(machine) => machine.name().into(),
+		ColumnSubject::Software { software, .. } => software.name.as_ref().into(),
+		ColumnSubject::UnrecognizedSoftware { software_name, .. } => software_name.into(),
+	}
+}
+
+fn source_file_text<'a>(_external_info: &'a ExternalInfo, subject: ColumnSubject<'a>) -> Cow<'a, str> {
+	match subject {
+		ColumnSubject::Machine(machine) => machine.source_file().into(),
+		ColumnSubject::Software { software_list, .. } => format!("{}.xml", &software_list.name).into(),
+		ColumnSubject::UnrecognizedSoftware { software_list_name, .. } => format!("{}.xml", software_list_name).into(),
+	}
+}
+
+fn description_text<'a>(_external_info: &'a ExternalInfo, subject: ColumnSubject<'a>) -> Cow<'a, str> {
+	match subject {
+		ColumnSubject::Machine(machine) => machine.description().into(),
+		ColumnSubject::Software { software, .. } => software.description.as_ref().into(),
+		ColumnSubject::UnrecognizedSoftware { .. } => "".into(),
+	}
+}
+
+fn year_text<'a>(_external_info: &'a ExternalInfo, subject: ColumnSubject<'a>) -> Cow<'a, str> {
+	match subject {
+		ColumnSubject::Machine(machine) => machine.year().into(),
+		ColumnSubject::Software { software, .. } => software.year.as_ref().into(),
+		ColumnSubject::UnrecognizedSoftware { .. } => "".into(),
+	}
+}
+
+fn provider_text<'a>(_external_info: &'a ExternalInfo, subject: ColumnSubject<'a>) -> Cow<'a, str> {
+	match subject {
+		ColumnSubject::Machine(machine) => machine.manufacturer().into(),
+		ColumnSubject::Software { software, .. } => software.publisher.as_ref().into(),
+		ColumnSubject::UnrecognizedSoftware { .. } => "".into(),
+	}
+}
+
+fn cpu_text<'a>(_external_info: &'a ExternalInfo, subject: ColumnSubject<'a>) -> Cow<'a, str> {
+	machine_text(subject, |machine| chip_text(machine.primary_chip(ChipType::Cpu)))
+}
+
+fn sound_text<'a>(_external_info: &'a ExternalInfo, subject: ColumnSubject<'a>) -> Cow<'a, str> {
+	machine_text(subject, |machine| chip_text(machine.primary_chip(ChipType::Audio)))
+}
+
+fn ram_column_text<'a>(_external_info: &'a ExternalInfo, subject: ColumnSubject<'a>) -> Cow<'a, str> {
+	let size = ram_sort_key(subject);
+	size.map(ram_text).unwrap_or_default().into()
+}
+
+fn ram_sort_key(subject: ColumnSubject<'_>) -> Option<u64> {
+	let ColumnSubject::Machine(machine) = subject else {
+		return None;
+	};
+	machine.default_ram_size()
+}
+
+fn maximum_ram_text<'a>(_external_info: &'a ExternalInfo, subject: ColumnSubject<'a>) -> Cow<'a, str> {
+	let size = maximum_ram_sort_key(subject);
+	size.map(ram_text).unwrap_or_default().into()
+}
+
+fn maximum_ram_sort_key(subject: ColumnSubject<'_>) -> Option<u64> {
+	let ColumnSubject::Machine(machine) = subject else {
+		return None;
+	};
+	machine.maximum_ram_size()
+}
+
+fn category_text<'a>(external_info: &'a ExternalInfo, subject: ColumnSubject<'a>) -> Cow<'a, str> {
+	machine_text(subject, |machine| {
+		external_info
+			.machine_category(machine.name())
+			.unwrap_or_default()
+			.into()
+	})
+}
+
+fn region_text<'a>(external_info: &'a ExternalInfo, subject: ColumnSubject<'a>) -> Cow<'a, str> {
+	machine_text(subject, |machine| {
+		external_info.machine_region(machine).unwrap_or_default().into()
+	})
+}
+
+fn requirements_column_text<'a>(_external_info: &'a ExternalInfo, subject: ColumnSubject<'a>) -> Cow<'a, str> {
+	machine_text(subject, |machine| requirements_text(machine).into())
+}
+
+fn runs_on_text<'a>(_external_info: &'a ExternalInfo, subject: ColumnSubject<'a>) -> Cow<'a, str> {
+	let ColumnSubject::Software {
+		info_db,
+		machine_indexes,
+		..
+	} = subject
+	else {
+		return "".into();
+	};
+	let machines = machine_indexes
+		.iter()
+		.map(|&index| info_db.machines().get(index).unwrap());
+	let machines = rank_software_machines(machines, None);
+	let text = machines.iter().take(RUNS_ON_COUNT).map(|x| x.description()).join(", ");
+	match machines.len().saturating_sub(RUNS_ON_COUNT) {
+		0 => text.into(),
+		rest => format!("{text} (+{rest} more)").into(),
+	}
+}
+
+fn missing_text<'a>(_external_info: &'a ExternalInfo, _subject: ColumnSubject<'a>) -> Cow<'a, str> {
+	"".into()
+}
+
+/// Most columns only apply to machines
+fn machine_text<'a>(subject: ColumnSubject<'a>, func: impl FnOnce(Machine<'a>) -> Cow<'a, str>) -> Cow<'a, str> {
+	match subject {
+		ColumnSubject::Machine(machine) => func(machine),
+		ColumnSubject::Software { .. } | ColumnSubject::UnrecognizedSoftware { .. } => "".into(),
+	}
+}
+
+fn chip_text(chip: Option<Chip<'_>>) -> Cow<'_, str> {
+	match chip {
+		Some(chip) if chip.clock() > 0 => format!("{} @ {}", chip.name(), clock_text(chip.clock())).into(),
+		Some(chip) => chip.name().into(),
+		None => "".into(),
+	}
+}
+
+/// Orders the machines that can run a piece of software, best first: the one last chosen, then parents ahead of clones
+pub fn rank_software_machines<'a>(
+	machines: impl IntoIterator<Item = Machine<'a>>,
+	last_choice: Option<&str>,
+) -> Vec<Machine<'a>> {
+	machines
+		.into_iter()
+		.sorted_by_key(|machine| (Some(machine.name()) != last_choice, machine.clone_of().is_some()))
+		.collect()
+}
+
+/// What a machine needs beyond its ROMs (e.g. - "CHD, cartridge"), so users know before trying to launch it
+pub fn requirements_text(machine: Machine<'_>) -> String {
+	let disks = machine.requires_disks().then_some("CHD");
+	let media = machine
+		.devices()
+		.iter()
+		.filter(|x| x.mandatory())
+		.map(|x| x.device_type())
+		.unique();
+	disks.into_iter().chain(media).join(", ")
+}
+
+pub fn clock_text(clock: u64) -> String {
+	let (value, units) = match clock {
+		1_000_000.. => (clock as f64 / 1_000_000.0, "MHz"),
+		1_000.. => (clock as f64 / 1_000.0, "kHz"),
+		_ => (clock as f64, "Hz"),
+	};
+
+	// trim insignificant zeroes
+	let text = format!("{value:.3}");
+	let text = text.trim_end_matches('0').trim_end_matches('.');
+	format!("{text} {units}")
+}
+
+#[cfg(test)]
+mod test {
+	use test_case::test_case;
+
+	use crate::info::InfoDb;
+	use crate::prefs::ColumnType;
+
+	#[test_case(0, 60, "60 Hz")]
+	#[test_case(1, 894886, "894.886 kHz")]
+	#[test_case(2, 1000000, "1 MHz")]
+	#[test_case(3, 3579545, "3.58 MHz")]
+	pub fn clock_text(_index: usize, clock: u64, expected: &str) {
+		let actual = super::clock_text(clock);
+		assert_eq!(expected, actual);
+	}
+
+	#[test_case(0, &["coco3", "coco", "coco2b"], None, &["coco", "coco3", "coco2b"])]
+	#[test_case(1, &["coco3", "coco", "coco2b"], Some("coco2b"), &["coco2b", "coco", "coco3"])]
+	#[test_case(2, &["coco3", "coco", "coco2b"], Some("cocoloco"), &["coco", "coco3", "coco2b"])]
+	pub fn rank_software_machines(_index: usize, machine_names: &[&str], last_choice: Option<&str>, expected: &[&str]) {
+		let info_xml = include_str!("../info/test_data/listxml_coco.xml");
+		let info_db = InfoDb::from_listxml_output(info_xml.as_bytes(), |_| false)
+			.unwrap()
+			.unwrap();
+		let machines = machine_names.iter().map(|name| info_db.machines().find(name).unwrap());
+
+		let actual = super::rank_software_machines(machines, last_choice);
+		let actual = actual.iter().map(|x| x.name()).collect::<Vec<_>>();
+		assert_eq!(expected, actual.as_slice());
+	}
+
+	#[test_case(0, ColumnType::MAXIMUM_RAM, "Maximum RAM")]
+	#[test_case(1, ColumnType::new("bogus"), "bogus")]
+	pub fn column_provider_title(_index: usize, column_type: ColumnType, expected: &str) {
+		let actual = super::column_provider(column_type).title;
+		assert_eq!(expected, actual);
+	}
+}
@@ -0,0 +1,231 @@
+//! Model for the grid (cover art) presentation of the items view; this piggybacks on `ItemsTableModel`
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::rc::Weak;
+
+use png::ColorType;
+use png::Decoder;
+use png::Transformations;
+use slint::spawn_local;
+use slint::Image;
+use slint::Model;
+use slint::ModelNotify;
+use slint::ModelTracker;
+use slint::Rgba8Pixel;
+use slint::SharedPixelBuffer;
+use tokio::task::spawn_blocking;
+use tracing::event;
+use tracing::Level;
+
+use crate::identicon::identicon_initials;
+use crate::identicon::identicon_name;
+use crate::identicon::identicon_pixels;
+use crate::identicon::IDENTICON_SIZE;
+use crate::models::itemstable::ItemsTableModel;
+use crate::prefs::PrefsItem;
+use crate::ui::ItemsGridEntry;
+
+const LOG: Level = Level::TRACE;
+
+pub struct ItemsGridModel {
+	model_weak: Weak<ItemsGridModel>,
+	items_model: Rc<ItemsTableModel>,
+	snapshot_paths: RefCell<Rc<[String]>>,
+	thumbnails: RefCell<HashMap<PrefsItem, Option<Thumbnail>>>,
+	notify: ModelNotify,
+}
+
+#[derive(Clone)]
+struct Thumbnail {
+	image: Image,
+
+	/// Is this an identicon generated because there is no snapshot?
+	is_identicon: bool,
+}
+
+impl ItemsGridModel {
+	pub fn new(items_model: Rc<ItemsTableModel>, snapshot_paths: Vec<String>) -> Rc<Self> {
+		Rc::new_cyclic(|model_weak| Self {
+			model_weak: model_weak.clone(),
+			items_model,
+			snapshot_paths: RefCell::new(snapshot_paths.into()),
+			thumbnails: RefCell::new(HashMap::new()),
+			notify: ModelNotify::default(),
+		})
+	}
+
+	pub fn set_snapshot_paths(&self, snapshot_paths: Vec<String>) {
+		self.snapshot_paths.replace(snapshot_paths.into());
+		self.thumbnails.borrow_mut().clear();
+		self.notify.reset();
+	}
+
+	pub fn items_map_changed(&self) {
+		self.notify.reset();
+	}
+
+	fn load_thumbnail(&self, row: usize, item: PrefsItem) {
+		// mark this thumbnail as pending, so we don't try to load it twice
+		self.thumbnails.borrow_mut().insert(item.clone(), None);
+
+		// find the snapshot (or failing that, render an identicon) on a worker thread, and load the image back on the
+		// UI thread
+		let snapshot_paths = self.snapshot_paths.borrow().clone();
+		let model_weak = self.model_weak.clone();
+		let fut = async move {
+			let item_clone = item.clone();
+			let (path, pixels) = spawn_blocking(move || {
+				let path = find_snapshot(&snapshot_paths, &item_clone);
+				let pixels = path.is_none().then(|| identicon_pixels(&identicon_name(&item_clone)));
+				(path, pixels)
+			})
+			.await
+			.unwrap_or_default();
+			event!(LOG, "ItemsGridModel::load_thumbnail(): item={:?} path={:?}", item, path);
+
+			let Some(model) = model_weak.upgrade() else {
+				return;
+			};
+			let thumbnail = if let Some(pixels) = pixels {
+				let buffer = SharedPixelBuffer::<Rgba8Pixel>::clone_from_slice(&pixels, IDENTICON_SIZE, IDENTICON_SIZE);
+				let image = Image::from_rgba8(buffer);
+				Some(Thumbnail {
+					image,
+					is_identicon: true,
+				})
+			} else {
+				let image = path.and_then(|path| Image::load_from_path(&path).ok());
+				image.map(|image| Thumbnail {
+					image,
+					is_identicon: false,
+				})
+			};
+			if let Some(thumbnail) = thumbnail {
+				model.thumbnails.borrow_mut().insert(item, Some(thumbnail));
+				if row < model.row_count() {
+					model.notify.row_changed(row);
+				}
+			}
+		};
+		spawn_local(fut).unwrap();
+	}
+}
+
+impl Model for ItemsGridModel {
+	type Data = ItemsGridEntry;
+
+	fn row_count(&self) -> usize {
+		self.items_model.row_count()
+	}
+
+	fn row_data(&self, row: usize) -> Option<Self::Data> {
+		let (item, caption) = self.items_model.row_item(row)?;
+		let thumbnail = self.thumbnails.borrow().get(&item).cloned();
+		let thumbnail = match thumbnail {
+			Some(thumbnail) => thumbnail,
+			None => {
+				self.load_thumbnail(row, item);
+				None
+			}
+		};
+		let (image, initials) = match thumbnail {
+			Some(Thumbnail {
+				image,
+				is_identicon: true,
+			}) => (image, identicon_initials(&caption)),
+			Some(Thumbnail { image, .. }) => (image, String::new()),
+			None => (Image::default(), String::new()),
+		};
+		let caption = caption.into();
+		let initials = initials.into();
+		Some(ItemsGridEntry {
+			image,
+			caption,
+			initials,
+		})
+	}
+
+	fn model_tracker(&self) -> &dyn ModelTracker {
+		&self.notify
+	}
+
+	fn as_any(&self) -> &dyn Any {
+		self
+	}
+}
+
+pub fn find_snapshot(snapshot_paths: &[String], item: &PrefsItem) -> Option<PathBuf> {
+	let candidates = match item {
+		PrefsItem::Machine { machine_name } => vec![
+			PathBuf::from(format!("{machine_name}.png")),
+			[machine_name.as_str(), "0000.png"].iter().collect(),
+		],
+		PrefsItem::Software {
+			software_list,
+			software,
+		} => vec![[software_list.as_str(), &format!("{software}.png")].iter().collect()],
+	};
+
+	snapshot_paths
+		.iter()
+		.flat_map(|path| candidates.iter().map(move |candidate| Path::new(path).join(candidate)))
+		.find(|path| path.is_file())
+}
+
+/// Decodes a snapshot; unlike `Image::load_from_path()` this can be called from a worker thread
+pub fn load_snapshot_pixels(path: &Path) -> Option<SharedPixelBuffer<Rgba8Pixel>> {
+	let mut decoder = Decoder::new(BufReader::new(File::open(path).ok()?));
+	decoder.set_transformations(Transformations::normalize_to_color8());
+	let mut reader = decoder.read_info().ok()?;
+	let mut buffer = vec![0; reader.output_buffer_size()];
+	let info = reader.next_frame(&mut buffer).ok()?;
+	let pixels = &buffer[..info.buffer_size()];
+	let pixels = match info.color_type {
+		ColorType::Rgba => pixels.to_vec(),
+		ColorType::Rgb => pixels.chunks_exact(3).flat_map(|x| [x[0], x[1], x[2], 0xFF]).collect(),
+		ColorType::GrayscaleAlpha => pixels.chunks_exact(2).flat_map(|x| [x[0], x[0], x[0], x[1]]).collect(),
+		ColorType::Grayscale => pixels.iter().flat_map(|&x| [x, x, x, 0xFF]).collect(),
+		ColorType::Indexed => return None,
+	};
+	let pixels = SharedPixelBuffer::<Rgba8Pixel>::clone_from_slice(&pixels, info.width, info.height);
+	Some(pixels)
+}
+
+#[cfg(test)]
+mod test {
+	use std::fs::create_dir_all;
+	use std::fs::File;
+
+	use tempdir::TempDir;
+	use test_case::test_case;
+
+	use crate::prefs::PrefsItem;
+
+	#[test_case(0, &["coco2b.png"], "coco2b", Some("coco2b.png"))]
+	#[test_case(1, &["coco2b/0000.png"], "coco2b", Some("coco2b/0000.png"))]
+	#[test_case(2, &["coco2b.png", "coco2b/0000.png"], "coco2b", Some("coco2b.png"))]
+	#[test_case(3, &["coco3.png"], "coco2b", None)]
+	pub fn find_snapshot(_index: usize, files: &[&str], machine_name: &str, expected: Option<&str>) {
+		let tmp_dir = TempDir::new("temp").unwrap();
+		for file in files {
+			let path = tmp_dir.path().join(file);
+			create_dir_all(path.parent().unwrap()).unwrap();
+			File::create(path).unwrap();
+		}
+
+		let snapshot_paths = [tmp_dir.path().to_str().unwrap().to_string()];
+		let item = PrefsItem::Machine {
+			machine_name: machine_name.to_string(),
+		};
+		let actual = super::find_snapshot(&snapshot_paths, &item);
+
+		let expected = expected.map(|x| tmp_dir.path().join(x));
+		assert_eq!(expected, actual);
+	}
+}
@@ -3,8 +3,21 @@ use std::borrow::Cow;
 use std::cell::Cell;
 use std::cell::RefCell;
 use std::cmp::Reverse;
+use std::collections::hash_map::RandomState;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::hash::BuildHasher;
+use std::hash::Hasher;
+use std::iter::once;
+use std::path::Path;
 use std::rc::Rc;
+use std::rc::Weak;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use std::time::Instant;
 
 use anyhow::Error;
 use anyhow::Result;
@@ -12,48 +25,126 @@ use itertools::Either;
 use itertools::Itertools;
 use levenshtein::levenshtein;
 use muda::Menu;
+use slint::spawn_local;
 use slint::Model;
 use slint::ModelNotify;
 use slint::ModelRc;
 use slint::ModelTracker;
 use slint::SharedString;
 use slint::StandardListViewItem;
+use tokio::sync::mpsc::unbounded_channel;
+use tokio::task::spawn_blocking;
 use tracing::event;
 use tracing::Level;
-use unicase::UniCase;
 
 use crate::appcommand::AppCommand;
+use crate::bios::family_parent_name;
+use crate::catver::category_genre;
+use crate::catver::Catver;
+use crate::channel::Channel;
+use crate::collation::fold_text;
+use crate::collation::CollatedText;
 use crate::guiutils::menuing::MenuDesc;
+use crate::i18n::tr;
+use crate::i18n::tr_args;
 use crate::info;
+use crate::info::ChipType;
 use crate::info::InfoDb;
+use crate::info::Machine;
 use crate::info::View;
+use crate::models::columns::column_provider;
+use crate::models::columns::rank_software_machines;
+use crate::models::columns::ColumnSubject;
+use crate::models::columns::ExternalInfo;
+use crate::prefs::ram_range_description;
+use crate::prefs::software_key;
+use crate::prefs::year_decade;
 use crate::prefs::BuiltinCollection;
+use crate::prefs::CloneDisplay;
+use crate::prefs::ColumnAlignment;
+use crate::prefs::ColumnEllipsis;
 use crate::prefs::ColumnType;
+use crate::prefs::ItemAction;
+use crate::prefs::MachineKind;
 use crate::prefs::PrefsCollection;
 use crate::prefs::PrefsColumn;
+use crate::prefs::PrefsFilter;
+use crate::prefs::PrefsInputFile;
 use crate::prefs::PrefsItem;
+use crate::prefs::PrefsLaunchOutcome;
 use crate::prefs::SortOrder;
+use crate::regions::Languages;
+use crate::selection::SelectionClick;
 use crate::selection::SelectionManager;
+use crate::selection::TypeAhead;
+use crate::software::load_software_lists;
+use crate::software::software_initial_loads;
+use crate::software::software_part_choices;
 use crate::software::Software;
 use crate::software::SoftwareList;
 use crate::software::SoftwareListDispenser;
+use crate::software::SoftwareListProgress;
+use crate::software::SoftwareSupport;
 
 const LOG: Level = Level::TRACE;
 
+/// Approximate width of a character in the items table, used when fitting text into columns
+const APPROX_CHAR_WIDTH: f32 = 7.0;
+
+/// Shown alongside machines whose last launch failed
+const LAUNCH_FAILED_BADGE: &str = "\u{26A0}";
+
+/// Shown alongside items that are part of a multiple selection
+const MULTI_SELECTED_BADGE: &str = "\u{2714}";
+
+/// Prefixes for the first column when clones are grouped beneath their parents
+const GROUP_EXPANDED_PREFIX: &str = "\u{25BE} ";
+const GROUP_COLLAPSED_PREFIX: &str = "\u{25B8} ";
+const GROUP_CLONE_PREFIX: &str = "    ";
+
 pub struct ItemsTableModel {
+	model_weak: Weak<ItemsTableModel>,
 	info_db: RefCell<Option<Rc<InfoDb>>>,
 	software_list_paths: RefCell<Vec<String>>,
-	columns: RefCell<Rc<[ColumnType]>>,
+	columns: RefCell<Rc<[PrefsColumn]>>,
 	sorting: Cell<Option<(ColumnType, SortOrder)>>,
 	search: RefCell<String>,
+	filters: RefCell<Vec<PrefsFilter>>,
 	items: RefCell<Rc<[Item]>>,
 	items_map: RefCell<Box<[u32]>>,
 
+	/// How clones are presented, and (when grouping) the role of each row in `items_map`
+	clone_display: Cell<CloneDisplay>,
+	clone_groupings: RefCell<Box<[CloneGrouping]>>,
+	collapsed_parents: RefCell<HashSet<usize>>,
+
+	/// Kinds of machines left out of "All Systems"
+	hidden_machine_kinds: RefCell<BTreeSet<MachineKind>>,
+
+	/// Machine information from files other than MAME itself (catver.ini, languages.ini)
+	external_info: RefCell<Rc<ExternalInfo>>,
+
 	current_collection: RefCell<Rc<PrefsCollection>>,
 	selected_index: Cell<Option<u32>>,
 
+	/// All software lists, loaded in the background the first time "All Software" is shown
+	all_software: RefCell<Option<Rc<[(String, Arc<SoftwareList>)]>>>,
+	software_list_scan: RefCell<Option<SoftwareListScan>>,
+	software_list_progress: Channel<Option<SoftwareListProgress>>,
+
+	/// Machines whose last launch failed get a badge
+	launch_outcomes: RefCell<Rc<BTreeMap<String, PrefsLaunchOutcome>>>,
+
+	/// Whether running multi-part software mounts every part, or just the first
+	mount_all_software_parts: Cell<bool>,
+
+	/// The machine that software was last run on, which its "Run" submenu offers first
+	software_machines: RefCell<Rc<BTreeMap<String, String>>>,
+
 	selection: SelectionManager,
+	type_ahead: TypeAhead,
 	empty_callback: Box<dyn Fn(Option<EmptyReason>) + 'static>,
+	items_map_changed_callback: RefCell<Box<dyn Fn() + 'static>>,
 	notify: ModelNotify,
 }
 
@@ -64,26 +155,66 @@ impl ItemsTableModel {
 		selection: SelectionManager,
 		empty_callback: impl Fn(Option<EmptyReason>) + 'static,
 	) -> Rc<Self> {
-		let result = Self {
+		Rc::new_cyclic(|model_weak| Self {
+			model_weak: model_weak.clone(),
 			info_db: RefCell::new(None),
 			software_list_paths: RefCell::new(software_list_paths),
 			columns: RefCell::new([].into()),
 			sorting: Cell::new(None),
 			search: RefCell::new("".into()),
+			filters: RefCell::new(Vec::new()),
 			items: RefCell::new([].into()),
 			items_map: RefCell::new([].into()),
+			clone_display: Cell::new(CloneDisplay::default()),
+			clone_groupings: RefCell::new([].into()),
+			collapsed_parents: RefCell::new(HashSet::new()),
+			hidden_machine_kinds: RefCell::new(BTreeSet::new()),
+			external_info: RefCell::new(Rc::new(ExternalInfo::default())),
 			current_collection: RefCell::new(current_collection),
 			selected_index: Cell::new(None),
+			all_software: RefCell::new(None),
+			software_list_scan: RefCell::new(None),
+			software_list_progress: Channel::default(),
+			launch_outcomes: RefCell::new(Rc::new(BTreeMap::new())),
+			mount_all_software_parts: Cell::new(true),
+			software_machines: RefCell::new(Rc::new(BTreeMap::new())),
 
 			selection,
+			type_ahead: TypeAhead::default(),
 			empty_callback: Box::new(empty_callback),
+			items_map_changed_callback: RefCell::new(Box::new(|| {})),
 			notify: ModelNotify::default(),
-		};
-		Rc::new(result)
+		})
+	}
+
+	/// Progress of loading software lists for "All Software"; `None` when no scan is in progress
+	pub fn software_list_progress_channel(&self) -> Channel<Option<SoftwareListProgress>> {
+		self.software_list_progress.clone()
+	}
+
+	/// Sets a callback invoked whenever the rows change; used by the grid view
+	pub fn set_items_map_changed_callback(&self, callback: impl Fn() + 'static) {
+		self.items_map_changed_callback.replace(Box::new(callback));
+	}
+
+	pub fn set_launch_outcomes(&self, launch_outcomes: BTreeMap<String, PrefsLaunchOutcome>) {
+		self.launch_outcomes.replace(Rc::new(launch_outcomes));
+		self.notify.reset();
+	}
+
+	pub fn set_mount_all_software_parts(&self, mount_all_software_parts: bool) {
+		self.mount_all_software_parts.set(mount_all_software_parts);
+	}
+
+	pub fn set_software_machines(&self, software_machines: BTreeMap<String, String>) {
+		self.software_machines.replace(Rc::new(software_machines));
 	}
 
 	pub fn info_db_changed(&self, info_db: Option<Rc<InfoDb>>) {
 		self.info_db.replace(info_db);
+		self.collapsed_parents.borrow_mut().clear();
+		self.all_software.replace(None);
+		self.cancel_software_list_scan();
 		self.refresh(&[]);
 	}
 
@@ -96,6 +227,24 @@ impl ItemsTableModel {
 	pub fn set_software_list_paths(&self, software_list_paths: Vec<String>) {
 		let selection = self.current_selection();
 		self.software_list_paths.replace(software_list_paths);
+		self.all_software.replace(None);
+		self.cancel_software_list_scan();
+		self.refresh(&selection);
+	}
+
+	pub fn set_catver(&self, catver: Option<Arc<Catver>>) {
+		self.modify_external_info(|x| x.catver = catver);
+	}
+
+	pub fn set_languages(&self, languages: Option<Arc<Languages>>) {
+		self.modify_external_info(|x| x.languages = languages);
+	}
+
+	fn modify_external_info(&self, func: impl FnOnce(&mut ExternalInfo)) {
+		let selection = self.current_selection();
+		let mut external_info = ExternalInfo::clone(&self.external_info.borrow());
+		func(&mut external_info);
+		self.external_info.replace(Rc::new(external_info));
 		self.refresh(&selection);
 	}
 
@@ -104,6 +253,15 @@ impl ItemsTableModel {
 		let info_db = self.info_db.borrow();
 		let collection = self.current_collection.borrow().clone();
 
+		// navigating away from "All Software" abandons any scan in progress
+		let is_all_software = matches!(
+			collection.as_ref(),
+			PrefsCollection::Builtin(BuiltinCollection::AllSoftware)
+		);
+		if !is_all_software {
+			self.cancel_software_list_scan();
+		}
+
 		let (items, dispenser_is_empty) = info_db
 			.as_ref()
 			.map(|info_db: &Rc<InfoDb>| {
@@ -112,14 +270,25 @@ impl ItemsTableModel {
 
 				let items = match collection.as_ref() {
 					PrefsCollection::Builtin(BuiltinCollection::All) => {
-						let machine_count = info_db.machines().len();
-						(0..machine_count)
-							.map(|machine_index| Item::Machine { machine_index })
+						let hidden_machine_kinds = self.hidden_machine_kinds.borrow();
+						info_db
+							.machines()
+							.iter()
+							.enumerate()
+							.filter(|(_, machine)| {
+								!hidden_machine_kinds.iter().any(|kind| match kind {
+									MachineKind::Bios => machine.is_bios(),
+									MachineKind::Device => machine.is_device(),
+									MachineKind::Mechanical => machine.is_mechanical(),
+								})
+							})
+							.map(|(machine_index, _)| Item::Machine { machine_index })
 							.collect::<Rc<[_]>>()
 					}
-					PrefsCollection::Builtin(BuiltinCollection::AllSoftware) => dispenser
-						.get_all()
-						.into_iter()
+					PrefsCollection::Builtin(BuiltinCollection::AllSoftware) => self
+						.all_software(info_db, &software_list_paths, selection)
+						.iter()
+						.filter_map(|(name, list)| Some((info_db.software_lists().find(name)?, list)))
 						.flat_map(|(info, list)| {
 							list.software
 								.iter()
@@ -146,22 +315,70 @@ impl ItemsTableModel {
 						.machines()
 						.find(machine_name)
 						.into_iter()
-						.flat_map(|x| x.machine_software_lists().iter().collect::<Vec<_>>())
-						.filter_map(|x| dispenser.get(x.software_list().name()).ok())
-						.flat_map(|(_, list)| {
+						.flat_map(|x| {
+							x.machine_software_lists()
+								.iter()
+								.map(|list| (x.index(), list))
+								.collect::<Vec<_>>()
+						})
+						.filter_map(|(machine_index, x)| {
+							let (_, list) = dispenser.get(x.software_list().name()).ok()?;
+							Some((machine_index, list))
+						})
+						.flat_map(|(machine_index, list)| {
 							list.software
 								.iter()
-								.map(|s| (list.clone(), s.clone()))
+								.map(|s| (machine_index, list.clone(), s.clone()))
 								.collect::<Vec<_>>()
 						})
-						.map(|(software_list, software)| Item::Software {
+						.map(|(machine_index, software_list, software)| Item::Software {
 							software_list,
 							software,
-							machine_indexes: Vec::default(),
+							machine_indexes: vec![machine_index],
+						})
+						.collect::<Rc<[_]>>(),
+
+					PrefsCollection::MachinesWithCpu { cpu } => info_db
+						.machines()
+						.iter()
+						.enumerate()
+						.filter(|(_, machine)| {
+							machine
+								.primary_chip(ChipType::Cpu)
+								.is_some_and(|chip| chip.name() == cpu)
+						})
+						.map(|(machine_index, _)| Item::Machine { machine_index })
+						.collect::<Rc<[_]>>(),
+
+					PrefsCollection::MachinesWithRam { minimum, maximum } => info_db
+						.machines()
+						.iter()
+						.enumerate()
+						.filter(|(_, machine)| {
+							machine.ram_options().iter().any(|ram_option| {
+								let size = ram_option.size();
+								minimum.is_none_or(|x| size >= x) && maximum.is_none_or(|x| size <= x)
+							})
 						})
+						.map(|(machine_index, _)| Item::Machine { machine_index })
 						.collect::<Rc<[_]>>(),
 
-					PrefsCollection::Folder { name: _, items } => items
+					PrefsCollection::Category { category } => {
+						let external_info = self.external_info.borrow();
+						info_db
+							.machines()
+							.iter()
+							.enumerate()
+							.filter(|(_, machine)| {
+								external_info
+									.machine_category(machine.name())
+									.is_some_and(|x| category_genre(x) == category)
+							})
+							.map(|(machine_index, _)| Item::Machine { machine_index })
+							.collect::<Rc<[_]>>()
+					}
+
+					PrefsCollection::Folder { items, .. } => items
 						.iter()
 						.filter_map(|item| match item {
 							PrefsItem::Machine { machine_name } => info_db
@@ -187,13 +404,14 @@ impl ItemsTableModel {
 			})
 			.unwrap_or_else(|| (Rc::new([]), true));
 
-		// if we're empty, try to gauge why and broadcast the result
-		let empty_reason = items.is_empty().then(|| {
+		// if we're empty, try to gauge why and broadcast the result (unless we're still loading)
+		let is_scanning = self.software_list_scan.borrow().is_some();
+		let empty_reason = (items.is_empty() && !is_scanning).then(|| {
 			if info_db.is_none() {
 				EmptyReason::NoInfoDb
 			} else if dispenser_is_empty || self.software_list_paths.borrow().is_empty() {
 				EmptyReason::NoSoftwareLists
-			} else if matches!(collection.as_ref(), PrefsCollection::Folder { name: _, items } if items.is_empty() ) {
+			} else if matches!(collection.as_ref(), PrefsCollection::Folder { items, .. } if items.is_empty() ) {
 				EmptyReason::Folder
 			} else {
 				EmptyReason::Unknown
@@ -209,11 +427,203 @@ impl ItemsTableModel {
 		self.set_current_selection(selection);
 	}
 
+	/// Returns all software lists if we have them, otherwise kicks off a background scan and returns nothing
+	fn all_software(
+		&self,
+		info_db: &InfoDb,
+		software_list_paths: &[String],
+		selection: &[PrefsItem],
+	) -> Rc<[(String, Arc<SoftwareList>)]> {
+		if let Some(all_software) = self.all_software.borrow().as_ref() {
+			return all_software.clone();
+		}
+
+		// is a scan already underway?  if so, just remember the selection for when it finishes
+		if let Some(scan) = self.software_list_scan.borrow_mut().as_mut() {
+			scan.selection = selection.to_vec();
+			return Rc::new([]);
+		}
+
+		let names = info_db
+			.software_lists()
+			.iter()
+			.map(|x| x.name().to_string())
+			.collect::<Vec<_>>();
+		let software_list_paths = software_list_paths.to_vec();
+		let total = names.len();
+		event!(LOG, "ItemsTableModel::all_software(): scanning {total} software lists");
+
+		let cancelled = Arc::new(AtomicBool::new(false));
+		let scan = SoftwareListScan {
+			cancelled: cancelled.clone(),
+			selection: selection.to_vec(),
+		};
+		self.software_list_scan.replace(Some(scan));
+		self.software_list_progress
+			.publish(&Some(SoftwareListProgress { loaded: 0, total }));
+
+		// load the software lists on worker threads, relaying progress back to the UI thread
+		let model_weak = self.model_weak.clone();
+		let fut = async move {
+			let (progress_tx, mut progress_rx) = unbounded_channel();
+			let cancelled_clone = cancelled.clone();
+			let handle = spawn_blocking(move || {
+				load_software_lists(&software_list_paths, &names, &cancelled_clone, |progress| {
+					let _ = progress_tx.send(progress);
+				})
+			});
+			while let Some(progress) = progress_rx.recv().await {
+				if let Some(model) = model_weak.upgrade().filter(|_| !cancelled.load(Ordering::Relaxed)) {
+					model.software_list_progress.publish(&Some(progress));
+				}
+			}
+			let all_software = handle.await.ok().flatten();
+			if let Some(model) = model_weak.upgrade() {
+				model.software_list_scan_finished(&cancelled, all_software);
+			}
+		};
+		spawn_local(fut).unwrap();
+		Rc::new([])
+	}
+
+	fn software_list_scan_finished(
+		&self,
+		cancelled: &Arc<AtomicBool>,
+		all_software: Option<Vec<(String, Arc<SoftwareList>)>>,
+	) {
+		// ignore scans that have been superseded or cancelled
+		let scan = self.software_list_scan.borrow().as_ref().map(|x| x.cancelled.clone());
+		if !scan.is_some_and(|x| Arc::ptr_eq(&x, cancelled)) {
+			return;
+		}
+		let Some(all_software) = all_software else {
+			return;
+		};
+		event!(
+			LOG,
+			"ItemsTableModel::software_list_scan_finished(): {} software lists loaded",
+			all_software.len()
+		);
+
+		let scan = self.software_list_scan.take().unwrap();
+		self.software_list_progress.publish(&None);
+		self.all_software.replace(Some(all_software.into()));
+		self.refresh(&scan.selection);
+	}
+
+	fn cancel_software_list_scan(&self) {
+		let Some(scan) = self.software_list_scan.take() else {
+			return;
+		};
+		event!(LOG, "ItemsTableModel::cancel_software_list_scan()");
+		scan.cancelled.store(true, Ordering::Relaxed);
+		self.software_list_progress.publish(&None);
+	}
+
+	/// What double clicking a row (or pressing Enter on it) does; running software runs it on its preferred machine
+	pub fn action_command(&self, index: usize, action: ItemAction, has_mame_initialized: bool) -> Option<AppCommand> {
+		let info_db = self.info_db.borrow();
+		let info_db = info_db.as_ref()?;
+		let items = self.items.borrow();
+		let index = *self.items_map.borrow().get(index)?;
+		let item = items.get(usize::try_from(index).unwrap())?;
+
+		match item {
+			Item::Machine { machine_index } => {
+				let machine = info_db.machines().get(*machine_index).unwrap();
+				let machine_name = machine.name().to_string();
+				match action {
+					ItemAction::Configure => has_mame_initialized.then(|| AppCommand::RunMameConfigure {
+						machine_name,
+						initial_loads: vec![],
+					}),
+					ItemAction::BrowseSoftware if !machine.machine_software_lists().is_empty() => {
+						Some(AppCommand::Browse(PrefsCollection::MachineSoftware { machine_name }))
+					}
+					ItemAction::ShowDetails => Some(AppCommand::MachineDetailsDialog { machine_name }),
+					ItemAction::Run | ItemAction::BrowseSoftware => has_mame_initialized.then(|| AppCommand::RunMame {
+						machine_name,
+						initial_loads: vec![],
+					}),
+				}
+			}
+			Item::Software {
+				software_list,
+				software,
+				machine_indexes,
+			} => match action {
+				ItemAction::ShowDetails => Some(AppCommand::SoftwareDetailsDialog {
+					software_list: software_list.name.to_string(),
+					software: software.name.to_string(),
+				}),
+				ItemAction::Run | ItemAction::Configure | ItemAction::BrowseSoftware => self
+					.software_run_commands(info_db, software_list, software, machine_indexes)
+					.into_iter()
+					.next()
+					.map(|(_, command)| command),
+			},
+			Item::UnrecognizedSoftware { .. } => None,
+		}
+	}
+
+	/// Commands to run software on each machine that can run it, with the preferred machine (the one last chosen)
+	/// first; running software through one of these makes that machine the preferred one
+	fn software_run_commands<'a>(
+		&self,
+		info_db: &'a InfoDb,
+		software_list: &SoftwareList,
+		software: &Software,
+		machine_indexes: &[usize],
+	) -> Vec<(Machine<'a>, AppCommand)> {
+		let mount_all_parts = self.mount_all_software_parts.get();
+		let preferred_machine = self
+			.software_machines
+			.borrow()
+			.get(&software_key(&software_list.name, &software.name))
+			.cloned();
+		let machines = machine_indexes
+			.iter()
+			.map(|&index| info_db.machines().get(index).unwrap());
+		rank_software_machines(machines, preferred_machine.as_deref())
+			.into_iter()
+			.filter_map(|machine| {
+				// identify all parts of the software
+				let initial_loads = software_initial_loads(machine, software, mount_all_parts)?;
+
+				// software that MAME does not fully support gets a warning first
+				let machine_name = machine.name().to_string();
+				let command = if software.supported == SoftwareSupport::Yes {
+					AppCommand::RunMame {
+						machine_name: machine_name.clone(),
+						initial_loads,
+					}
+				} else {
+					AppCommand::RunMameUnsupportedSoftware {
+						machine_name: machine_name.clone(),
+						initial_loads,
+						software_description: software.description.to_string(),
+						supported: software.supported,
+					}
+				};
+				let command = AppCommand::RunSoftwareOn {
+					software_list: software_list.name.to_string(),
+					software: software.name.to_string(),
+					machine_name,
+					command: Box::new(command),
+				};
+				Some((machine, command))
+			})
+			.collect()
+	}
+
 	pub fn context_commands(
 		&self,
 		index: usize,
 		folder_info: &[(usize, Rc<PrefsCollection>)],
 		has_mame_initialized: bool,
+		recent_input_files: &[PrefsInputFile],
+		reference_snapshots: &BTreeMap<String, String>,
+		machine_capture_directories: &BTreeMap<String, String>,
 	) -> Option<Menu> {
 		// access the InfoDB
 		let info_db = self.info_db.borrow();
@@ -226,15 +636,22 @@ impl ItemsTableModel {
 			None
 		};
 
-		// access the selection
+		// access the selection; if the clicked item is part of a multiple selection, folder operations apply to all of it
+		let selection = self.current_selection();
 		let items = self.items.borrow();
+		let clone_grouping = self.clone_groupings.borrow().get(index).copied().unwrap_or_default();
 		let index = *self.items_map.borrow().get(index).unwrap();
 		let index = usize::try_from(index).unwrap();
 		let item = items.get(index)?;
-		let items = vec![make_prefs_item(info_db, item)];
+		let clicked_item = make_prefs_item(info_db, item);
+		let items = if selection.contains(&clicked_item) {
+			selection
+		} else {
+			vec![clicked_item]
+		};
 
 		// get the critical information - the description and where (if anyplace) "Browse" would go to
-		let (run_menu_item, browse_target) = match item {
+		let (run_menu_item, run_detached_menu_item, extra_menu_items, browse_menu_items) = match item {
 			Item::Machine { machine_index } => {
 				let machine = info_db.machines().get(*machine_index).unwrap();
 				let command = has_mame_initialized.then(|| AppCommand::RunMame {
@@ -243,66 +660,213 @@ impl ItemsTableModel {
 				});
 				let text = run_item_text(machine.description());
 				let run_menu_item = MenuDesc::Item(text, command.map(|x| x.into()));
-				let browse_target =
-					(!machine.machine_software_lists().is_empty()).then(|| PrefsCollection::MachineSoftware {
-						machine_name: machine.name().to_string(),
-					});
-				(run_menu_item, browse_target)
+				let command = has_mame_initialized.then(|| AppCommand::RunMameDetached {
+					machine_name: machine.name().to_string(),
+					initial_loads: vec![],
+				});
+				let run_detached_menu_item = MenuDesc::Item(tr("Run In New Window"), command.map(|x| x.into()));
+				let command = has_mame_initialized.then(|| AppCommand::NetplayDialog {
+					machine_name: machine.name().to_string(),
+				});
+				let netplay_menu_item = MenuDesc::Item(tr("Run With Network Play..."), command.map(|x| x.into()));
+				let command = AppCommand::MachineDetailsDialog {
+					machine_name: machine.name().to_string(),
+				};
+				let details_menu_item = MenuDesc::Item(tr("Details..."), Some(command.into()));
+				let playback_menu_items = recent_input_files
+					.iter()
+					.filter(|input_file| input_file.machine_name == machine.name())
+					.map(|input_file| {
+						let text = Path::new(&input_file.path)
+							.file_name()
+							.map(|x| x.to_string_lossy().into_owned())
+							.unwrap_or_else(|| input_file.path.clone());
+						let command = has_mame_initialized.then(|| AppCommand::PlaybackInput {
+							machine_name: input_file.machine_name.clone(),
+							path: input_file.path.clone(),
+						});
+						MenuDesc::Item(text, command.map(|x| x.into()))
+					})
+					.collect::<Vec<_>>();
+				let playback_menu_item = (!playback_menu_items.is_empty())
+					.then(|| MenuDesc::SubMenu(tr("Play Back Input"), true, playback_menu_items));
+				let machine_name = machine.name().to_string();
+				let has_reference_snapshot = reference_snapshots.contains_key(&machine_name);
+				let compare_command = AppCommand::ReferenceSnapshotCompare {
+					machine_name: machine_name.clone(),
+				};
+				let use_latest_command = AppCommand::ReferenceSnapshotUseLatest {
+					machine_name: machine_name.clone(),
+				};
+				let choose_command = AppCommand::ReferenceSnapshotDialog {
+					machine_name: machine_name.clone(),
+				};
+				let clear_command = AppCommand::ReferenceSnapshotSet {
+					machine_name: machine_name.clone(),
+					path: None,
+				};
+				let reference_menu_items = vec![
+					MenuDesc::Item(
+						tr("Compare Latest Snapshot"),
+						has_reference_snapshot.then(|| compare_command.into()),
+					),
+					MenuDesc::Separator,
+					MenuDesc::Item(tr("Use Latest Snapshot As Reference"), Some(use_latest_command.into())),
+					MenuDesc::Item(tr("Choose Reference Snapshot..."), Some(choose_command.into())),
+					MenuDesc::Item(
+						tr("Clear Reference Snapshot"),
+						has_reference_snapshot.then(|| clear_command.into()),
+					),
+				];
+				let reference_menu_item = MenuDesc::SubMenu(tr("Reference Snapshot"), true, reference_menu_items);
+				let has_capture_directory = machine_capture_directories.contains_key(&machine_name);
+				let choose_command = AppCommand::MachineCaptureDirectoryDialog {
+					machine_name: machine_name.clone(),
+				};
+				let clear_command = AppCommand::MachineCaptureDirectorySet {
+					machine_name: machine_name.clone(),
+					directory: None,
+				};
+				let capture_menu_items = vec![
+					MenuDesc::Item(tr("Set Capture Directory..."), Some(choose_command.into())),
+					MenuDesc::Item(
+						tr("Clear Capture Directory"),
+						has_capture_directory.then(|| clear_command.into()),
+					),
+				];
+				let capture_menu_item = MenuDesc::SubMenu(tr("Capture Directory"), true, capture_menu_items);
+				let bios_menu_item = folder_name
+					.as_ref()
+					.and_then(|folder_name| family_bios_menu_item(info_db, machine, folder_name));
+				let clone_group_menu_item = match clone_grouping {
+					CloneGrouping::Parent { expanded } => {
+						let text = if expanded { "Collapse Clones" } else { "Expand Clones" };
+						let command = AppCommand::ItemsToggleCloneGroup {
+							machine_name: machine_name.clone(),
+						};
+						Some(MenuDesc::Item(tr(text), Some(command.into())))
+					}
+					_ => None,
+				};
+				let extra_menu_items = once(netplay_menu_item)
+					.chain(once(details_menu_item))
+					.chain(playback_menu_item)
+					.chain(once(reference_menu_item))
+					.chain(once(capture_menu_item))
+					.chain(bios_menu_item)
+					.chain(clone_group_menu_item);
+				let extra_menu_items = extra_menu_items.collect::<Vec<_>>();
+
+				// the various places we might browse to from this machine
+				let mut browse_menu_items = Vec::new();
+				if !machine.machine_software_lists().is_empty() {
+					let id = AppCommand::Browse(PrefsCollection::MachineSoftware { machine_name }).into();
+					browse_menu_items.push(MenuDesc::Item(tr("Browse Software"), Some(id)));
+				}
+				if let Some(chip) = machine.primary_chip(ChipType::Cpu) {
+					let text = tr_args("Browse Machines With {cpu}", &[("cpu", &chip.name())]);
+					let cpu = chip.name().to_string();
+					let id = AppCommand::Browse(PrefsCollection::MachinesWithCpu { cpu }).into();
+					browse_menu_items.push(MenuDesc::Item(text, Some(id)));
+				}
+				if let Some(ram_size) = machine.default_ram_size() {
+					let ram_ranges = [
+						(Some(ram_size), None),
+						(None, Some(ram_size)),
+						(Some(ram_size), Some(ram_size)),
+					];
+					let ram_menu_items = ram_ranges
+						.into_iter()
+						.map(|(minimum, maximum)| {
+							let text = ram_range_description(minimum, maximum);
+							let id = AppCommand::Browse(PrefsCollection::MachinesWithRam { minimum, maximum }).into();
+							MenuDesc::Item(text, Some(id))
+						})
+						.collect::<Vec<_>>();
+					browse_menu_items.push(MenuDesc::SubMenu(tr("Browse Machines By RAM"), true, ram_menu_items));
+				}
+				let category = self
+					.external_info
+					.borrow()
+					.machine_category(machine.name())
+					.map(|x| category_genre(x).to_string());
+				if let Some(category) = category {
+					let text = tr_args("Browse {category} Machines", &[("category", &category)]);
+					let id = AppCommand::Browse(PrefsCollection::Category { category }).into();
+					browse_menu_items.push(MenuDesc::Item(text, Some(id)));
+				}
+				(
+					run_menu_item,
+					Some(run_detached_menu_item),
+					extra_menu_items,
+					browse_menu_items,
+				)
 			}
 			Item::Software {
+				software_list,
 				software,
 				machine_indexes,
-				..
 			} => {
-				let sub_items = machine_indexes
-					.iter()
-					.filter_map(|&index| {
-						// get the machine out of the InfoDB
-						let machine = info_db.machines().get(index).unwrap();
-
-						// identify all parts of the software
-						let parts_with_devices = software
-							.parts
-							.iter()
-							.map(|part| {
-								machine
-									.devices()
-									.iter()
-									.find(|dev| part.interface.as_ref() == dev.interface())
-									.map(|dev| (Arc::<str>::from(dev.tag()), software.name.clone()))
-									.ok_or(())
-							})
-							.collect::<std::result::Result<Vec<_>, ()>>();
+				let sub_items = self
+					.software_run_commands(info_db, software_list, software, machine_indexes)
+					.into_iter()
+					.map(|(machine, command)| MenuDesc::Item(machine.description().to_string(), Some(command.into())))
+					.collect::<Vec<_>>();
+				let text = run_item_text(&software.description);
+				let run_menu_item = MenuDesc::SubMenu(text, true, sub_items);
+				let command = AppCommand::SoftwareDetailsDialog {
+					software_list: software_list.name.to_string(),
+					software: software.name.to_string(),
+				};
+				let details_menu_item = MenuDesc::Item(tr("Details..."), Some(command.into()));
 
-						parts_with_devices.ok().map(|initial_loads| {
-							// running is not yet supported!
-							let command = AppCommand::RunMame {
+				// multi-part software (e.g. - multi-disk games) lets the user choose which part goes where
+				let parts_menu_item = (software.parts.len() > 1).then(|| {
+					let sub_items = machine_indexes
+						.iter()
+						.map(|&index| info_db.machines().get(index).unwrap())
+						.filter(|&machine| !software_part_choices(machine, software).is_empty())
+						.map(|machine| {
+							let command = AppCommand::SoftwarePartsDialog {
 								machine_name: machine.name().to_string(),
-								initial_loads,
+								software_list: software_list.name.to_string(),
+								software: software.name.to_string(),
 							};
-							MenuDesc::Item(machine.description().to_string(), Some(command.into()))
+							MenuDesc::Item(format!("{}...", machine.description()), Some(command.into()))
 						})
-					})
-					.collect::<Vec<_>>();
-				let text = run_item_text(&software.description);
-				let run_menu_item = MenuDesc::SubMenu(text, true, sub_items);
-				(run_menu_item, None)
+						.collect::<Vec<_>>();
+					MenuDesc::SubMenu(tr("Run With Parts"), !sub_items.is_empty(), sub_items)
+				});
+				let extra_menu_items = parts_menu_item.into_iter().chain([details_menu_item]).collect();
+				(run_menu_item, None, extra_menu_items, Vec::new())
 			}
 			Item::UnrecognizedSoftware { error, .. } => {
 				let message = format!("{}", error);
 				let run_menu_item = MenuDesc::Item(message, None);
-				(run_menu_item, None)
+				(run_menu_item, None, Vec::new(), Vec::new())
 			}
 		};
 
 		// now actually build the context menu
 		let mut menu_items = Vec::new();
 		menu_items.push(run_menu_item);
+		menu_items.extend(run_detached_menu_item);
+		menu_items.extend(extra_menu_items);
 		menu_items.push(MenuDesc::Separator);
-
-		if let Some(browse_target) = browse_target {
-			let id = AppCommand::Browse(browse_target).into();
-			menu_items.push(MenuDesc::Item("Browse Software".to_string(), Some(id)));
+		menu_items.extend(browse_menu_items);
+
+		// filter down to items like this one
+		let filter_menu_items = item_filters(info_db, &self.external_info.borrow(), item)
+			.into_iter()
+			.map(|filter| {
+				let text = filter.description();
+				let is_active = self.filters.borrow().contains(&filter);
+				let command = (!is_active).then(|| AppCommand::ItemsAddFilter(filter).into());
+				MenuDesc::Item(text, command)
+			})
+			.collect::<Vec<_>>();
+		if !filter_menu_items.is_empty() {
+			menu_items.push(MenuDesc::SubMenu(tr("Filter By"), true, filter_menu_items));
 		}
 
 		// add to folder
@@ -312,6 +876,7 @@ impl ItemsTableModel {
 				let PrefsCollection::Folder {
 					name,
 					items: folder_items,
+					..
 				} = &**col
 				else {
 					panic!("Expected PrefsCollection::Folder");
@@ -328,25 +893,41 @@ impl ItemsTableModel {
 			folder_menu_items.push(MenuDesc::Separator);
 		}
 		folder_menu_items.push(MenuDesc::Item(
-			"New Folder...".into(),
+			tr("New Folder..."),
 			Some(AppCommand::AddToNewFolderDialog(items.clone()).into()),
 		));
-		menu_items.push(MenuDesc::SubMenu("Add To Folder".into(), true, folder_menu_items));
+		menu_items.push(MenuDesc::SubMenu(tr("Add To Folder"), true, folder_menu_items));
 
 		// remove from this folder
 		if let Some(folder_name) = folder_name {
-			let text = format!("Remove From \"{}\"", folder_name);
+			let text = tr_args("Remove From \"{folder}\"", &[("folder", &folder_name)]);
 			let command = AppCommand::RemoveFromFolder(folder_name, items.clone());
 			menu_items.push(MenuDesc::Item(text, Some(command.into())));
 		};
 
+		// desktop shortcut (which uses the command line run mode)
+		if !matches!(item, Item::UnrecognizedSoftware { .. }) {
+			let command = AppCommand::CreateDesktopShortcut {
+				item: make_prefs_item(info_db, item),
+				name: column_text(info_db, &ExternalInfo::default(), item, ColumnType::DESCRIPTION).into_owned(),
+			};
+			menu_items.push(MenuDesc::Separator);
+			menu_items.push(MenuDesc::Item(tr("Create Desktop Shortcut"), Some(command.into())));
+		}
+
 		// and return!
 		Some(MenuDesc::make_popup_menu(menu_items))
 	}
 
-	pub fn set_columns_and_search(&self, columns: &[PrefsColumn], search: &str, sort_suppressed: bool) {
+	pub fn set_columns_and_search(
+		&self,
+		columns: &[PrefsColumn],
+		search: &str,
+		filters: &[PrefsFilter],
+		sort_suppressed: bool,
+	) {
 		// update columns
-		self.columns.replace(columns.iter().map(|x| x.column_type).collect());
+		self.columns.replace(columns.into());
 
 		// update search if it has changed
 		let search_changed = search != *self.search.borrow();
@@ -354,6 +935,12 @@ impl ItemsTableModel {
 			self.search.replace(search.to_string());
 		}
 
+		// likewise filters
+		let filters_changed = filters != self.filters.borrow().as_slice();
+		if filters_changed {
+			self.filters.replace(filters.to_vec());
+		}
+
 		// determine the new sorting
 		let sorting = (!sort_suppressed)
 			.then(|| {
@@ -370,44 +957,142 @@ impl ItemsTableModel {
 
 		event!(
 			LOG,
-			"set_columns_and_search(): search={:?} sorting={:?} search_changed={} sorting_changed={:?}",
+			"set_columns_and_search(): search={:?} filters={:?} sorting={:?} search_changed={} filters_changed={} sorting_changed={:?}",
 			search,
+			filters,
 			sorting,
 			search_changed,
+			filters_changed,
 			sorting_changed
 		);
 
 		// if anything changed, update our map
-		if search_changed || sorting_changed {
-			// get the selected index, because we're about to mess up all of the rows
-			let selected_index = self.current_selected_index();
+		if search_changed || filters_changed || sorting_changed {
+			// get the selected indices, because we're about to mess up all of the rows
+			let selected_indices = self.current_selected_indices();
 
 			self.update_items_map();
 
 			// restore the selection
-			let index = selected_index.and_then(|index| self.items_map.borrow().iter().position(|&x| index == x));
-			self.selection.set_selected_index(index);
+			self.restore_selected_indices(&selected_indices);
+		}
+	}
+
+	pub fn set_hidden_machine_kinds(&self, hidden_machine_kinds: BTreeSet<MachineKind>) {
+		if hidden_machine_kinds != *self.hidden_machine_kinds.borrow() {
+			self.hidden_machine_kinds.replace(hidden_machine_kinds);
+			let selection = self.current_selection();
+			self.refresh(&selection);
 		}
 	}
 
+	pub fn set_clone_display(&self, clone_display: CloneDisplay) {
+		if clone_display != self.clone_display.replace(clone_display) {
+			self.update_items_map_keeping_selection();
+		}
+	}
+
+	/// Collapses or expands the clones grouped beneath a parent machine
+	pub fn toggle_clone_group(&self, machine_name: &str) {
+		let Some(machine_index) = self
+			.info_db
+			.borrow()
+			.as_ref()
+			.and_then(|info_db| info_db.machines().find_index(machine_name))
+		else {
+			return;
+		};
+		{
+			let mut collapsed_parents = self.collapsed_parents.borrow_mut();
+			if !collapsed_parents.remove(&machine_index) {
+				collapsed_parents.insert(machine_index);
+			}
+		}
+		self.update_items_map_keeping_selection();
+	}
+
+	fn update_items_map_keeping_selection(&self) {
+		let selected_indices = self.current_selected_indices();
+		self.update_items_map();
+		self.restore_selected_indices(&selected_indices);
+	}
+
 	fn update_items_map(&self) {
 		// borrow all the things
 		let info_db = self.info_db.borrow();
 		let info_db = info_db.as_ref().map(|x| x.as_ref());
+		let external_info = self.external_info.borrow();
 		let items = self.items.borrow();
 
 		// build the new items map
 		let new_items_map = build_items_map(
 			info_db,
-			&self.columns.borrow(),
+			&external_info,
+			&self.columns.borrow().iter().map(|x| x.column_type).collect::<Vec<_>>(),
 			&items,
 			self.sorting.get(),
 			&self.search.borrow(),
+			&self.filters.borrow(),
 		);
+		let (new_items_map, new_clone_groupings) = info_db
+			.map(|info_db| {
+				apply_clone_display(
+					info_db,
+					&items,
+					new_items_map,
+					self.clone_display.get(),
+					&self.collapsed_parents.borrow(),
+				)
+			})
+			.unwrap_or_default();
 		self.items_map.replace(new_items_map);
+		self.clone_groupings.replace(new_clone_groupings);
 
 		// and notify
 		self.notify.reset();
+		(self.items_map_changed_callback.borrow())();
+	}
+
+	/// Returns the `PrefsItem` and description for a particular row
+	pub fn row_item(&self, row: usize) -> Option<(PrefsItem, String)> {
+		let info_db = self.info_db.borrow();
+		let info_db = info_db.as_ref()?;
+		let index = *self.items_map.borrow().get(row)?;
+		let items = self.items.borrow();
+		let item = &items[usize::try_from(index).unwrap()];
+		let description = column_text(info_db, &ExternalInfo::default(), item, ColumnType::DESCRIPTION).into_owned();
+		Some((make_prefs_item(info_db, item), description))
+	}
+
+	/// Picks a random item out of the current (searched/filtered) view, returning its `PrefsItem` and description
+	pub fn random_item(&self) -> Option<(PrefsItem, String)> {
+		let row = random_index(self.items_map.borrow().len())?;
+		self.row_item(row)
+	}
+
+	/// Picks a random runnable machine out of the current (searched/filtered) view, returning
+	/// the row index and the machine name
+	pub fn random_runnable_machine(&self) -> Option<(usize, String)> {
+		let info_db = self.info_db.borrow();
+		let info_db = info_db.as_ref()?;
+		let items = self.items.borrow();
+
+		let candidates = self
+			.items_map
+			.borrow()
+			.iter()
+			.enumerate()
+			.filter_map(|(row, &index)| {
+				let Item::Machine { machine_index } = &items[usize::try_from(index).unwrap()] else {
+					return None;
+				};
+				let machine = info_db.machines().get(*machine_index).unwrap();
+				machine.runnable().then(|| (row, machine.name().to_string()))
+			})
+			.collect::<Vec<_>>();
+
+		let index = random_index(candidates.len())?;
+		candidates.into_iter().nth(index)
 	}
 
 	pub fn current_selection(&self) -> Vec<PrefsItem> {
@@ -417,20 +1102,40 @@ impl ItemsTableModel {
 			return [].into();
 		};
 
-		let result = self.current_selected_index().map(|index| {
-			let items = self.items.borrow();
-			let index = usize::try_from(index).unwrap();
-			make_prefs_item(info_db, &items[index])
-		});
+		let items = self.items.borrow();
+		self.current_selected_indices()
+			.into_iter()
+			.map(|index| {
+				let index = usize::try_from(index).unwrap();
+				make_prefs_item(info_db, &items[index])
+			})
+			.collect()
+	}
 
-		result.into_iter().collect()
+	/// The indexes (into `items`) of all selected items, with the current one first
+	fn current_selected_indices(&self) -> Vec<u32> {
+		let items_map = self.items_map.borrow();
+		let indices = self
+			.selection
+			.selected_indices()
+			.into_iter()
+			.filter_map(|x| items_map.get(x).cloned())
+			.collect::<Vec<_>>();
+		if indices.is_empty() {
+			self.selected_index.get().into_iter().collect()
+		} else {
+			indices
+		}
 	}
 
-	fn current_selected_index(&self) -> Option<u32> {
-		self.selection
-			.selected_index()
-			.and_then(|x| self.items_map.borrow().get(x).cloned())
-			.or_else(|| self.selected_index.get())
+	/// Selects the rows showing the specified indexes (into `items`)
+	fn restore_selected_indices(&self, selected_indices: &[u32]) {
+		let items_map = self.items_map.borrow();
+		let indices = selected_indices
+			.iter()
+			.filter_map(|index| items_map.iter().position(|x| index == x))
+			.collect::<Vec<_>>();
+		self.selection.set_selected_indices(&indices);
 	}
 
 	fn set_current_selection(&self, selection: &[PrefsItem]) {
@@ -439,25 +1144,63 @@ impl ItemsTableModel {
 			return;
 		};
 
-		// we only support single selection now
-		let selection = selection.iter().next();
-
-		let selected_index = selection.and_then(|selection| {
+		let selected_indices = {
 			let items = self.items.borrow();
 			let items_map = self.items_map.borrow();
-
-			items_map
+			selection
 				.iter()
-				.enumerate()
-				.find(|(_, map_index)| {
-					let map_index = usize::try_from(**map_index).unwrap();
-					let item = &items[map_index];
-					is_item_match(info_db, selection, item)
+				.filter_map(|selection| {
+					items_map.iter().position(|map_index| {
+						let map_index = usize::try_from(*map_index).unwrap();
+						let item = &items[map_index];
+						is_item_match(info_db, selection, item)
+					})
 				})
-				.map(|(index, _)| index)
-		});
+				.collect::<Vec<_>>()
+		};
+
+		self.selection.set_selected_indices(&selected_indices);
+	}
+
+	/// Responds to a click on a row, which might select multiple items with ctrl or shift
+	pub fn row_clicked(&self, row: usize, click: SelectionClick) {
+		for row in self.selection.row_clicked(row, click) {
+			self.notify.row_changed(row);
+		}
+	}
+
+	/// Responds to text typed into the items table by jumping to the first row whose name or description starts with
+	/// what was recently typed; returns whether the text was consumed
+	pub fn type_ahead(&self, text: &str) -> bool {
+		let Some(prefix) = self.type_ahead.push(text, Instant::now()) else {
+			return false;
+		};
+		if let Some(row) = self.find_row_with_prefix(&prefix) {
+			self.selection.jump_to(row);
+		}
+		true
+	}
+
+	fn find_row_with_prefix(&self, prefix: &str) -> Option<usize> {
+		let info_db = self.info_db.borrow();
+		let info_db = info_db.as_ref()?;
+		let items = self.items.borrow();
+		let external_info = ExternalInfo::default();
+		let prefix = fold_text(prefix);
+		self.items_map.borrow().iter().position(|&index| {
+			let item = &items[usize::try_from(index).unwrap()];
+			[ColumnType::NAME, ColumnType::DESCRIPTION].into_iter().any(|column| {
+				let text = column_text(info_db, &external_info, item, column);
+				fold_text(&text).starts_with(&prefix)
+			})
+		})
+	}
 
-		self.selection.set_selected_index(selected_index);
+	/// Responds to the current row changing, which collapses multiple selections unless caused by a click
+	pub fn current_row_changed(&self) {
+		for row in self.selection.current_index_changed() {
+			self.notify.row_changed(row);
+		}
 	}
 }
 
@@ -469,13 +1212,30 @@ impl Model for ItemsTableModel {
 		self.items_map.borrow().len()
 	}
 
-	fn row_data(&self, row: usize) -> Option<Self::Data> {
+	fn row_data(&self, view_row: usize) -> Option<Self::Data> {
 		let info_db = self.info_db.borrow().as_ref().unwrap().clone();
-		let row = *self.items_map.borrow().get(row)?;
+		let clone_grouping = self.clone_groupings.borrow().get(view_row).copied().unwrap_or_default();
+		let row = *self.items_map.borrow().get(view_row)?;
 		let row = row.try_into().unwrap();
 		let columns = self.columns.borrow().clone();
 		let items = self.items.borrow().clone();
-		let row_model = RowModel::new(info_db, columns, items, row);
+		let launch_failed = match &items[row] {
+			Item::Machine { machine_index } => {
+				let machine_name = info_db.machines().get(*machine_index).unwrap().name();
+				self.launch_outcomes.borrow().contains_key(machine_name)
+			}
+			_ => false,
+		};
+		let external_info = self.external_info.borrow().clone();
+		let badges = [
+			(self.selection.is_multi_selected(view_row), MULTI_SELECTED_BADGE),
+			(launch_failed, LAUNCH_FAILED_BADGE),
+		];
+		let badges = badges
+			.into_iter()
+			.filter_map(|(applies, badge)| applies.then_some(badge))
+			.collect();
+		let row_model = RowModel::new(info_db, external_info, columns, items, row, badges, clone_grouping);
 		Some(ModelRc::from(row_model))
 	}
 
@@ -522,6 +1282,23 @@ fn software_item(info: info::SoftwareList<'_>, software_list: Arc<SoftwareList>,
 	}
 }
 
+/// The role of a row when clones are grouped beneath their parents
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum CloneGrouping {
+	#[default]
+	None,
+	Parent {
+		expanded: bool,
+	},
+	Clone,
+}
+
+/// A background load of all software lists, in support of "All Software"
+struct SoftwareListScan {
+	cancelled: Arc<AtomicBool>,
+	selection: Vec<PrefsItem>,
+}
+
 /// Sometimes, the items view is empty - we can (try to) report why
 #[derive(Clone, Copy, Debug, strum_macros::Display)]
 pub enum EmptyReason {
@@ -579,18 +1356,32 @@ fn make_prefs_item(info_db: &InfoDb, item: &Item) -> PrefsItem {
 
 struct RowModel {
 	info_db: Rc<InfoDb>,
-	columns: Rc<[ColumnType]>,
+	external_info: Rc<ExternalInfo>,
+	columns: Rc<[PrefsColumn]>,
 	items: Rc<[Item]>,
 	row: usize,
+	badges: Vec<&'static str>,
+	clone_grouping: CloneGrouping,
 }
 
 impl RowModel {
-	pub fn new(info_db: Rc<InfoDb>, columns: Rc<[ColumnType]>, items: Rc<[Item]>, row: usize) -> Rc<Self> {
+	pub fn new(
+		info_db: Rc<InfoDb>,
+		external_info: Rc<ExternalInfo>,
+		columns: Rc<[PrefsColumn]>,
+		items: Rc<[Item]>,
+		row: usize,
+		badges: Vec<&'static str>,
+		clone_grouping: CloneGrouping,
+	) -> Rc<Self> {
 		Rc::new(Self {
 			info_db,
+			external_info,
 			columns,
 			items,
 			row,
+			badges,
+			clone_grouping,
 		})
 	}
 }
@@ -602,12 +1393,33 @@ impl Model for RowModel {
 		self.columns.len()
 	}
 
-	fn row_data(&self, column: usize) -> Option<Self::Data> {
-		let column = *self.columns.get(column)?;
+	fn row_data(&self, column_index: usize) -> Option<Self::Data> {
+		let column = *self.columns.get(column_index)?;
 		let item = self.items.get(self.row).unwrap();
-		let text = column_text(&self.info_db, item, column);
-		let text = String::from(text.as_ref());
-		Some(SharedString::from(text).into())
+		let text = column_text(&self.info_db, &self.external_info, item, column.column_type);
+		let text = format_cell_text(text.as_ref(), &column);
+
+		// badge the first column if selected among others, or if the last launch failed
+		let text = if !self.badges.is_empty() && column_index == 0 {
+			Cow::Owned(format!("{} {text}", self.badges.join(" ")))
+		} else {
+			text
+		};
+
+		// and indicate the parent/clone relationships when grouping
+		let prefix = match self.clone_grouping {
+			_ if column_index != 0 => "",
+			CloneGrouping::None => "",
+			CloneGrouping::Parent { expanded: true } => GROUP_EXPANDED_PREFIX,
+			CloneGrouping::Parent { expanded: false } => GROUP_COLLAPSED_PREFIX,
+			CloneGrouping::Clone => GROUP_CLONE_PREFIX,
+		};
+		let text = if prefix.is_empty() {
+			text
+		} else {
+			Cow::Owned(format!("{prefix}{text}"))
+		};
+		Some(SharedString::from(text.as_ref()).into())
 	}
 
 	fn model_tracker(&self) -> &dyn ModelTracker {
@@ -617,18 +1429,23 @@ impl Model for RowModel {
 
 fn build_items_map(
 	info_db: Option<&InfoDb>,
+	external_info: &ExternalInfo,
 	column_types: &[ColumnType],
 	items: &[Item],
 	sorting: Option<(ColumnType, SortOrder)>,
 	search: &str,
+	filters: &[PrefsFilter],
 ) -> Box<[u32]> {
 	// if we have no InfoDB, we have no rows
 	let Some(info_db) = info_db else {
 		return [].into();
 	};
 
-	// start iterating
-	let iter = items.iter().enumerate();
+	// start iterating, applying any structured filters
+	let iter = items
+		.iter()
+		.enumerate()
+		.filter(|(_, item)| is_filters_match(info_db, external_info, item, filters));
 
 	// apply searching if appropriate
 	let iter = if !search.is_empty() {
@@ -637,7 +1454,7 @@ fn build_items_map(
 				let distance = column_types
 					.iter()
 					.filter_map(|&column| {
-						let text = column_text(info_db, item, column);
+						let text = column_text(info_db, external_info, item, column);
 						contains_and_distance(text.as_ref(), search)
 					})
 					.min();
@@ -651,9 +1468,13 @@ fn build_items_map(
 		Either::Right(iter)
 	};
 
-	// now apply sorting
+	// now apply sorting; numeric columns sort by their values, not their text
 	let iter = if let Some((column_type, sort_order)) = sorting {
-		let func = |item| UniCase::new(column_text(info_db, item, column_type));
+		let func = |item| {
+			let number = column_number(info_db, item, column_type);
+			let text = column_text(info_db, external_info, item, column_type);
+			(number, CollatedText(text))
+		};
 		let iter = match sort_order {
 			SortOrder::Ascending => Either::Left(iter.sorted_by_cached_key(|(_, item)| func(item))),
 			SortOrder::Descending => Either::Right(iter.sorted_by_cached_key(|(_, item)| Reverse(func(item)))),
@@ -667,52 +1488,331 @@ fn build_items_map(
 	iter.map(|(index, _)| u32::try_from(index).unwrap()).collect()
 }
 
+/// Applies the clone display preference to an already searched and sorted items map, returning
+/// the new map along with the grouping role of each row (empty unless grouping)
+fn apply_clone_display(
+	info_db: &InfoDb,
+	items: &[Item],
+	items_map: Box<[u32]>,
+	clone_display: CloneDisplay,
+	collapsed_parents: &HashSet<usize>,
+) -> (Box<[u32]>, Box<[CloneGrouping]>) {
+	let machine_index = |index: u32| match &items[usize::try_from(index).unwrap()] {
+		Item::Machine { machine_index } => Some(*machine_index),
+		_ => None,
+	};
+	let clone_of = |index: u32| {
+		let machine = info_db.machines().get(machine_index(index)?).unwrap();
+		machine.clone_of().map(|x| x.index())
+	};
+
+	match clone_display {
+		CloneDisplay::Show => (items_map, [].into()),
+		CloneDisplay::Hide => {
+			let items_map = items_map.iter().copied().filter(|&index| clone_of(index).is_none());
+			(items_map.collect(), [].into())
+		}
+		CloneDisplay::Group => {
+			// clones can only be grouped beneath parents that are actually present
+			let present = items_map
+				.iter()
+				.filter_map(|&index| machine_index(index))
+				.collect::<HashSet<_>>();
+			let grouped_parent = |index: u32| clone_of(index).filter(|parent| present.contains(parent));
+			let mut clones = HashMap::<usize, Vec<u32>>::new();
+			for &index in items_map.iter() {
+				if let Some(parent) = grouped_parent(index) {
+					clones.entry(parent).or_default().push(index);
+				}
+			}
+
+			// and emit each parent followed by its clones (preserving the existing order)
+			let mut new_items_map = Vec::with_capacity(items_map.len());
+			let mut clone_groupings = Vec::with_capacity(items_map.len());
+			for &index in items_map.iter().filter(|&&index| grouped_parent(index).is_none()) {
+				let group = machine_index(index).and_then(|x| Some((x, clones.get(&x)?)));
+				if let Some((machine_index, group)) = group {
+					let expanded = !collapsed_parents.contains(&machine_index);
+					new_items_map.push(index);
+					clone_groupings.push(CloneGrouping::Parent { expanded });
+					if expanded {
+						new_items_map.extend(group.iter().copied());
+						clone_groupings.extend(group.iter().map(|_| CloneGrouping::Clone));
+					}
+				} else {
+					new_items_map.push(index);
+					clone_groupings.push(CloneGrouping::None);
+				}
+			}
+			(new_items_map.into(), clone_groupings.into())
+		}
+	}
+}
+
 fn contains_and_distance(text: &str, target: &str) -> Option<usize> {
-	text.to_lowercase()
-		.contains(&target.to_lowercase())
+	fold_text(text)
+		.contains(&fold_text(target))
 		.then(|| levenshtein(text, target))
 }
 
-fn column_text<'a>(info_db: &'a InfoDb, item: &'a Item, column: ColumnType) -> Cow<'a, str> {
+fn column_subject<'a>(info_db: &'a InfoDb, item: &'a Item) -> ColumnSubject<'a> {
 	match item {
-		Item::Machine { machine_index } => {
-			let machine = info_db.machines().get(*machine_index).unwrap();
-			let text = match column {
-				ColumnType::Name => machine.name(),
-				ColumnType::SourceFile => machine.source_file(),
-				ColumnType::Description => machine.description(),
-				ColumnType::Year => machine.year(),
-				ColumnType::Provider => machine.manufacturer(),
-			};
-			text.into()
-		}
+		Item::Machine { machine_index } => ColumnSubject::Machine(info_db.machines().get(*machine_index).unwrap()),
 		Item::Software {
 			software_list,
 			software,
-			..
-		} => match column {
-			ColumnType::Name => software.name.as_ref().into(),
-			ColumnType::SourceFile => format!("{}.xml", &software_list.name).into(),
-			ColumnType::Description => software.description.as_ref().into(),
-			ColumnType::Year => software.year.as_ref().into(),
-			ColumnType::Provider => software.publisher.as_ref().into(),
+			machine_indexes,
+		} => ColumnSubject::Software {
+			software_list,
+			software,
+			info_db,
+			machine_indexes,
 		},
 		Item::UnrecognizedSoftware {
 			software_list_name,
 			software_name,
 			..
-		} => match column {
-			ColumnType::Name => software_name.into(),
-			ColumnType::SourceFile => format!("{}.xml", software_list_name).into(),
-			_ => "".into(),
+		} => ColumnSubject::UnrecognizedSoftware {
+			software_list_name,
+			software_name,
 		},
 	}
 }
 
+fn column_text<'a>(
+	info_db: &'a InfoDb,
+	external_info: &'a ExternalInfo,
+	item: &'a Item,
+	column: ColumnType,
+) -> Cow<'a, str> {
+	(column_provider(column).text)(external_info, column_subject(info_db, item))
+}
+
+fn column_number(info_db: &InfoDb, item: &Item, column: ColumnType) -> Option<u64> {
+	column_provider(column).sort_key?(column_subject(info_db, item))
+}
+
+/// Filters of the same kind are alternatives to each other, but each kind of filter must match
+fn is_filters_match(info_db: &InfoDb, external_info: &ExternalInfo, item: &Item, filters: &[PrefsFilter]) -> bool {
+	filters.iter().all(|filter| {
+		filters
+			.iter()
+			.filter(|x| x.is_same_kind(filter))
+			.any(|x| is_filter_match(info_db, external_info, item, x))
+	})
+}
+
+fn is_filter_match(info_db: &InfoDb, external_info: &ExternalInfo, item: &Item, filter: &PrefsFilter) -> bool {
+	let text = |column| column_text(info_db, external_info, item, column);
+	match filter {
+		PrefsFilter::Decade { decade } => year_decade(&text(ColumnType::YEAR)) == Some(*decade),
+		PrefsFilter::Manufacturer { manufacturer } => text(ColumnType::PROVIDER) == *manufacturer,
+		PrefsFilter::Region { region } => text(ColumnType::REGION) == *region,
+		PrefsFilter::SoftwareSupport { supported } => {
+			matches!(item, Item::Software { software, .. } if software.supported == *supported)
+		}
+	}
+}
+
+/// The filters that would narrow the view down to items like this one
+fn item_filters(info_db: &InfoDb, external_info: &ExternalInfo, item: &Item) -> Vec<PrefsFilter> {
+	let year = column_text(info_db, external_info, item, ColumnType::YEAR);
+	let manufacturer = column_text(info_db, external_info, item, ColumnType::PROVIDER);
+	let region = column_text(info_db, external_info, item, ColumnType::REGION);
+	let decade_filter = year_decade(&year).map(|decade| PrefsFilter::Decade { decade });
+	let manufacturer_filter = (!manufacturer.is_empty()).then(|| PrefsFilter::Manufacturer {
+		manufacturer: manufacturer.into_owned(),
+	});
+	let region_filter = (!region.is_empty()).then(|| PrefsFilter::Region {
+		region: region.into_owned(),
+	});
+	let software_support_filter = if let Item::Software { software, .. } = item {
+		Some(PrefsFilter::SoftwareSupport {
+			supported: software.supported,
+		})
+	} else {
+		None
+	};
+	decade_filter
+		.into_iter()
+		.chain(manufacturer_filter)
+		.chain(region_filter)
+		.chain(software_support_filter)
+		.collect()
+}
+
 fn is_item_match(info_db: &InfoDb, prefs_item: &PrefsItem, item: &Item) -> bool {
 	make_prefs_item(info_db, item) == *prefs_item
 }
 
+fn random_index(len: usize) -> Option<usize> {
+	// we don't need anything fancy; each `RandomState` is seeded differently
+	let len = u64::try_from(len).ok().filter(|&len| len > 0)?;
+	let value = RandomState::new().build_hasher().finish();
+	Some(usize::try_from(value % len).unwrap())
+}
+
+/// The alignment of a column's cells, which is applied by the items table itself
+pub fn column_alignment(column: &PrefsColumn) -> ColumnAlignment {
+	column
+		.alignment
+		.unwrap_or_else(|| column_provider(column.column_type).default_alignment)
+}
+
+/// Applies a column's ellipsis mode to cell text
+///
+/// Text can only be elided at the end, so we approximate middle ellipsis based on the column width
+fn format_cell_text<'a>(text: &'a str, column: &PrefsColumn) -> Cow<'a, str> {
+	let capacity = (column.width / APPROX_CHAR_WIDTH) as usize;
+	match column.ellipsis {
+		ColumnEllipsis::End => Cow::Borrowed(text),
+		ColumnEllipsis::Middle => middle_ellipsis(text, capacity),
+	}
+}
+
+fn middle_ellipsis(text: &str, capacity: usize) -> Cow<'_, str> {
+	let length = text.chars().count();
+	if length <= capacity || capacity < 3 {
+		return Cow::Borrowed(text);
+	}
+	let tail_length = (capacity - 1) / 2;
+	let head_length = capacity - 1 - tail_length;
+	let head = text.chars().take(head_length);
+	let tail = text.chars().skip(length - tail_length);
+	Cow::Owned(head.chain(once('…')).chain(tail).collect())
+}
+
+/// Menu for applying a BIOS set to every machine in this machine's family (its parent and clones) within a folder
+fn family_bios_menu_item(info_db: &InfoDb, machine: Machine<'_>, folder_name: &str) -> Option<MenuDesc> {
+	let parent_name = family_parent_name(&machine);
+	let parent = info_db.machines().find(parent_name).unwrap_or(machine);
+	let bios_sets = if parent.bios_sets().is_empty() {
+		machine.bios_sets()
+	} else {
+		parent.bios_sets()
+	};
+	if bios_sets.is_empty() {
+		return None;
+	}
+
+	let bios_menu_item = |text: String, bios: Option<&str>| {
+		let command = AppCommand::FolderFamilyBiosSet {
+			folder_name: folder_name.to_string(),
+			parent_name: parent_name.to_string(),
+			bios: bios.map(str::to_string),
+		};
+		MenuDesc::Item(text, Some(command.into()))
+	};
+	let bios_set_menu_items = bios_sets
+		.iter()
+		.map(|bios_set| bios_menu_item(bios_set.description().to_string(), Some(bios_set.name())));
+	let menu_items = once(bios_menu_item(tr("Default BIOS"), None))
+		.chain(once(MenuDesc::Separator))
+		.chain(bios_set_menu_items)
+		.collect::<Vec<_>>();
+	let text = tr_args(
+		"Set BIOS For \"{family}\" Family In Folder",
+		&[("family", &parent.description())],
+	);
+	Some(MenuDesc::SubMenu(text, true, menu_items))
+}
+
 fn run_item_text(text: &str) -> String {
-	format!("Run {}", text)
+	tr_args("Run {item}", &[("item", &text)])
+}
+
+#[cfg(test)]
+mod test {
+	use std::collections::HashSet;
+
+	use test_case::test_case;
+
+	use crate::info::InfoDb;
+	use crate::prefs::CloneDisplay;
+	use crate::prefs::ColumnAlignment;
+	use crate::prefs::ColumnEllipsis;
+	use crate::prefs::ColumnType;
+	use crate::prefs::PrefsColumn;
+
+	#[test_case(0, "Pac-Man", 10, "Pac-Man")]
+	#[test_case(1, "Pac-Man", 7, "Pac-Man")]
+	#[test_case(2, "Ms. Pac-Man", 7, "Ms.…Man")]
+	#[test_case(3, "Ms. Pac-Man", 6, "Ms.…an")]
+	#[test_case(4, "Ms. Pac-Man", 2, "Ms. Pac-Man")]
+	pub fn middle_ellipsis(_index: usize, text: &str, capacity: usize, expected: &str) {
+		let actual = super::middle_ellipsis(text, capacity);
+		assert_eq!(expected, actual.as_ref());
+	}
+
+	#[test_case(0, &["coco3", "coco", "cocoloco", "cocolocoa", "coco2b"], CloneDisplay::Show, &[], &["coco3", "coco", "cocoloco", "cocolocoa", "coco2b"])]
+	#[test_case(1, &["coco3", "coco", "cocoloco", "cocolocoa", "coco2b"], CloneDisplay::Hide, &[], &["coco", "cocoloco"])]
+	#[test_case(2, &["coco3", "coco", "cocoloco", "cocolocoa", "coco2b"], CloneDisplay::Group, &[], &["coco", "coco3", "coco2b", "cocoloco", "cocolocoa"])]
+	#[test_case(3, &["coco3", "coco", "cocoloco", "cocolocoa", "coco2b"], CloneDisplay::Group, &["coco"], &["coco", "cocoloco", "cocolocoa"])]
+	#[test_case(4, &["coco3", "cocolocoa"], CloneDisplay::Group, &[], &["coco3", "cocolocoa"])]
+	pub fn apply_clone_display(
+		_index: usize,
+		machine_names: &[&str],
+		clone_display: CloneDisplay,
+		collapsed: &[&str],
+		expected: &[&str],
+	) {
+		let info_xml = include_str!("../info/test_data/listxml_coco.xml");
+		let info_db = InfoDb::from_listxml_output(info_xml.as_bytes(), |_| false)
+			.unwrap()
+			.unwrap();
+		let items = machine_names
+			.iter()
+			.map(|name| super::Item::Machine {
+				machine_index: info_db.machines().find_index(name).unwrap(),
+			})
+			.collect::<Vec<_>>();
+		let items_map = (0..u32::try_from(items.len()).unwrap()).collect();
+		let collapsed = collapsed
+			.iter()
+			.map(|name| info_db.machines().find_index(name).unwrap())
+			.collect::<HashSet<_>>();
+
+		let (items_map, _) = super::apply_clone_display(&info_db, &items, items_map, clone_display, &collapsed);
+		let actual = items_map
+			.iter()
+			.map(|&index| machine_names[usize::try_from(index).unwrap()])
+			.collect::<Vec<_>>();
+		assert_eq!(expected, actual.as_slice());
+	}
+
+	#[test_case(0, ColumnType::DESCRIPTION, None, ColumnAlignment::Left)]
+	#[test_case(1, ColumnType::YEAR, None, ColumnAlignment::Right)]
+	#[test_case(2, ColumnType::YEAR, Some(ColumnAlignment::Left), ColumnAlignment::Left)]
+	#[test_case(3, ColumnType::DESCRIPTION, Some(ColumnAlignment::Right), ColumnAlignment::Right)]
+	pub fn column_alignment(
+		_index: usize,
+		column_type: ColumnType,
+		alignment: Option<ColumnAlignment>,
+		expected: ColumnAlignment,
+	) {
+		let column = PrefsColumn {
+			column_type,
+			sort: None,
+			width: 56.0,
+			alignment,
+			ellipsis: ColumnEllipsis::End,
+		};
+		let actual = super::column_alignment(&column);
+		assert_eq!(expected, actual);
+	}
+
+	#[test_case(0, ColumnEllipsis::End, "Super Street Fighter II", "Super Street Fighter II")]
+	#[test_case(1, ColumnEllipsis::Middle, "1980", "1980")]
+	#[test_case(2, ColumnEllipsis::Middle, "Super Street Fighter II", "Supe… II")]
+	pub fn format_cell_text(_index: usize, ellipsis: ColumnEllipsis, text: &str, expected: &str) {
+		let column = PrefsColumn {
+			column_type: ColumnType::DESCRIPTION,
+			sort: None,
+			width: 56.0,
+			alignment: None,
+			ellipsis,
+		};
+		let actual = super::format_cell_text(text, &column);
+		assert_eq!(expected, actual.as_ref());
+	}
 }
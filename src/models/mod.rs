@@ -1,2 +1,4 @@
 pub mod collectionsview;
+pub mod columns;
+pub mod itemsgrid;
 pub mod itemstable;
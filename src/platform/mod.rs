@@ -3,11 +3,15 @@ mod other;
 #[cfg(target_os = "windows")]
 mod windows;
 
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+mod xdgrecent;
+
 // declarations for Windows platform
 #[cfg(target_os = "windows")]
 #[rustfmt::skip]
 pub use {
     windows::win_platform_init as platform_init,
+    windows::jumplist::update_jump_list as update_recent_items,
     windows::WinCommandExt as CommandExt,
     windows::WinWindowAttributesExt as WindowAttributesExt,
     windows::WinWindowExt as WindowExt
@@ -18,7 +22,16 @@ pub use {
 #[rustfmt::skip]
 pub use {
     other::other_platform_init as platform_init,
+    other::other_update_recent_items as update_recent_items,
     other::OtherCommandExt as CommandExt,
     other::OtherWindowAttributesExt as WindowAttributesExt,
     other::OtherWindowExt as WindowExt
 };
+
+/// An item offered by the OS as something recently used, or as a task (e.g. - in the Windows taskbar jump list)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecentItem {
+	pub title: String,
+	pub program: String,
+	pub args: Vec<String>,
+}
@@ -9,10 +9,24 @@ use slint::LogicalPosition;
 use slint::Window;
 use winit::window::WindowAttributes;
 
+use crate::platform::RecentItem;
+
 pub fn other_platform_init() -> Result<impl Any> {
 	Ok(())
 }
 
+/// XDG recent files have no notion of tasks, so only the items are offered
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+pub fn other_update_recent_items(items: &[RecentItem], _tasks: &[RecentItem]) -> Result<()> {
+	super::xdgrecent::update_xdg_recent_items(items)
+}
+
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+pub fn other_update_recent_items(_items: &[RecentItem], _tasks: &[RecentItem]) -> Result<()> {
+	// macOS only tracks recent documents opened through NSDocumentController
+	Ok(())
+}
+
 pub trait OtherCommandExt {
 	fn create_no_window(&mut self, flag: bool) -> &mut Self;
 }
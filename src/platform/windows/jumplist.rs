@@ -0,0 +1,193 @@
+//! The taskbar jump list; each entry launches BletchMAME with a request that is handed to the running instance (see
+//! `instance.rs`)
+use std::ffi::c_void;
+use std::ops::Deref;
+use std::ptr::null_mut;
+
+use anyhow::Error;
+use anyhow::Result;
+use itertools::Itertools;
+use winapi::shared::guiddef::GUID;
+use winapi::shared::minwindef::UINT;
+use winapi::shared::winerror::HRESULT;
+use winapi::shared::winerror::SUCCEEDED;
+use winapi::shared::wtypes::VT_LPWSTR;
+use winapi::um::combaseapi::CoCreateInstance;
+use winapi::um::combaseapi::CLSCTX_INPROC_SERVER;
+use winapi::um::objectarray::IObjectArray;
+use winapi::um::objectarray::IObjectCollection;
+use winapi::um::propidl::PROPVARIANT;
+use winapi::um::propkey::PKEY_Title;
+use winapi::um::propsys::IPropertyStore;
+use winapi::um::shobjidl_core::ICustomDestinationList;
+use winapi::um::shobjidl_core::IShellLinkW;
+use winapi::um::unknwnbase::IUnknown;
+use winapi::Interface;
+
+use crate::platform::RecentItem;
+
+const CLSID_DESTINATION_LIST: GUID = GUID {
+	Data1: 0x77f10cf0,
+	Data2: 0x3db5,
+	Data3: 0x4966,
+	Data4: [0xb5, 0x20, 0xb7, 0xc5, 0x4f, 0xd3, 0x5e, 0xd6],
+};
+
+const CLSID_ENUMERABLE_OBJECT_COLLECTION: GUID = GUID {
+	Data1: 0x2d3468c1,
+	Data2: 0x36a7,
+	Data3: 0x43b6,
+	Data4: [0xac, 0x24, 0xd3, 0xf0, 0x2f, 0xd9, 0x60, 0x7a],
+};
+
+const CLSID_SHELL_LINK: GUID = GUID {
+	Data1: 0x00021401,
+	Data2: 0x0000,
+	Data3: 0x0000,
+	Data4: [0xc0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x46],
+};
+
+/// Replaces the "Recent" category and the tasks of our jump list; COM is already initialized on the UI thread by winit
+pub fn update_jump_list(items: &[RecentItem], tasks: &[RecentItem]) -> Result<()> {
+	unsafe {
+		let list = ComPtr::<ICustomDestinationList>::create(&CLSID_DESTINATION_LIST)?;
+		let mut min_slots: UINT = 0;
+		let mut removed = null_mut();
+		check(list.BeginList(&mut min_slots, &IObjectArray::uuidof(), &mut removed))?;
+		let removed = ComPtr(removed as *mut IObjectArray);
+
+		let result = populate_jump_list(&list, &removed, items, tasks, min_slots.try_into().unwrap());
+		if result.is_err() {
+			list.AbortList();
+		}
+		result
+	}
+}
+
+unsafe fn populate_jump_list(
+	list: &ComPtr<ICustomDestinationList>,
+	removed: &ComPtr<IObjectArray>,
+	items: &[RecentItem],
+	tasks: &[RecentItem],
+	max_items: usize,
+) -> Result<()> {
+	// the shell refuses lists containing entries the user removed
+	let removed_args = removed_arguments(removed)?;
+
+	let collection = ComPtr::<IObjectCollection>::create(&CLSID_ENUMERABLE_OBJECT_COLLECTION)?;
+	let items = items
+		.iter()
+		.filter(|item| !removed_args.contains(&command_line_args(&item.args)))
+		.take(max_items);
+	for item in items {
+		let link = shell_link(item)?;
+		check(collection.AddObject(link.0 as *mut IUnknown))?;
+	}
+
+	let category = wide("Recent");
+	check(list.AppendCategory(category.as_ptr(), collection.0 as *mut IObjectArray))?;
+
+	// tasks cannot be removed by the user, so they are not filtered
+	if !tasks.is_empty() {
+		let task_collection = ComPtr::<IObjectCollection>::create(&CLSID_ENUMERABLE_OBJECT_COLLECTION)?;
+		for task in tasks {
+			let link = shell_link(task)?;
+			check(task_collection.AddObject(link.0 as *mut IUnknown))?;
+		}
+		check(list.AddUserTasks(task_collection.0 as *mut IObjectArray))?;
+	}
+	check(list.CommitList())?;
+	Ok(())
+}
+
+unsafe fn shell_link(item: &RecentItem) -> Result<ComPtr<IShellLinkW>> {
+	let link = ComPtr::<IShellLinkW>::create(&CLSID_SHELL_LINK)?;
+	let program = wide(&item.program);
+	check(link.SetPath(program.as_ptr()))?;
+	check(link.SetArguments(wide(&command_line_args(&item.args)).as_ptr()))?;
+	check(link.SetIconLocation(program.as_ptr(), 0))?;
+
+	// jump list entries are titled through the property store
+	let mut store = null_mut();
+	check(link.QueryInterface(&IPropertyStore::uuidof(), &mut store))?;
+	let store = ComPtr(store as *mut IPropertyStore);
+	let mut title = wide(&item.title);
+	let mut value: PROPVARIANT = std::mem::zeroed();
+	let value_s = value.u.s_mut();
+	value_s.vt = VT_LPWSTR.try_into().unwrap();
+	*value_s.u.pwszVal_mut() = title.as_mut_ptr();
+	check(store.SetValue(&PKEY_Title, &value))?;
+	check(store.Commit())?;
+	Ok(link)
+}
+
+unsafe fn removed_arguments(removed: &ComPtr<IObjectArray>) -> Result<Vec<String>> {
+	let mut count: UINT = 0;
+	check(removed.GetCount(&mut count))?;
+	let mut result = Vec::new();
+	for index in 0..count {
+		let mut link = null_mut();
+		if SUCCEEDED(removed.GetAt(index, &IShellLinkW::uuidof(), &mut link)) {
+			let link = ComPtr(link as *mut IShellLinkW);
+			let mut buf = [0u16; 1024];
+			check(link.GetArguments(buf.as_mut_ptr(), buf.len().try_into().unwrap()))?;
+			let len = buf.iter().position(|&c| c == 0).unwrap_or(buf.len());
+			result.push(String::from_utf16_lossy(&buf[..len]));
+		}
+	}
+	Ok(result)
+}
+
+fn command_line_args(args: &[String]) -> String {
+	args.iter()
+		.map(|x| if x.contains(' ') { format!("\"{x}\"") } else { x.clone() })
+		.join(" ")
+}
+
+fn wide(s: &str) -> Vec<u16> {
+	s.encode_utf16().chain([0]).collect()
+}
+
+fn check(hr: HRESULT) -> Result<()> {
+	if SUCCEEDED(hr) {
+		Ok(())
+	} else {
+		let message = format!("COM call failed (HRESULT 0x{hr:08X})");
+		Err(Error::msg(message))
+	}
+}
+
+/// Minimal owning pointer to a COM interface
+struct ComPtr<T: Interface>(*mut T);
+
+impl<T: Interface> ComPtr<T> {
+	unsafe fn create(clsid: &GUID) -> Result<Self> {
+		let mut ptr: *mut c_void = null_mut();
+		check(CoCreateInstance(
+			clsid,
+			null_mut(),
+			CLSCTX_INPROC_SERVER,
+			&T::uuidof(),
+			&mut ptr,
+		))?;
+		Ok(Self(ptr as *mut T))
+	}
+}
+
+impl<T: Interface> Deref for ComPtr<T> {
+	type Target = T;
+
+	fn deref(&self) -> &Self::Target {
+		unsafe { &*self.0 }
+	}
+}
+
+impl<T: Interface> Drop for ComPtr<T> {
+	fn drop(&mut self) {
+		if !self.0.is_null() {
+			unsafe {
+				(*(self.0 as *mut IUnknown)).Release();
+			}
+		}
+	}
+}
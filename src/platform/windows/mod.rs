@@ -1,4 +1,5 @@
 #![allow(dead_code)]
+pub mod jumplist;
 pub mod menuing;
 
 use std::any::Any;
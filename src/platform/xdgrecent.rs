@@ -0,0 +1,193 @@
+//! Recent items exported through the XDG recent files spec (`recently-used.xbel`); each item is a desktop entry that
+//! runs the item through the command line run mode
+use std::io::ErrorKind;
+use std::io::Write;
+use std::path::Path;
+use std::time::SystemTime;
+
+use anyhow::Error;
+use anyhow::Result;
+use quick_xml::escape::escape;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use quick_xml::Writer;
+use tracing::event;
+use tracing::Level;
+
+use crate::desktopshortcut::desktop_entry;
+use crate::desktopshortcut::shortcut_file_name;
+use crate::platform::RecentItem;
+use crate::screenshot::civil_date;
+
+const LOG: Level = Level::DEBUG;
+
+/// Bookmarks are opened by launching the desktop entry itself; BletchMAME does not accept `file://` URIs
+const XBEL_EXEC: &str = "gio launch %f";
+
+const EMPTY_XBEL: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<xbel version=\"1.0\" xmlns:bookmark=\"http://www.freedesktop.org/standards/desktop-bookmarks\" xmlns:mime=\"http://www.freedesktop.org/standards/shared-mime-info\">\n</xbel>\n";
+
+pub fn update_xdg_recent_items(items: &[RecentItem]) -> Result<()> {
+	let data_dir = dirs::data_dir().ok_or_else(|| Error::msg("Could not find the data directory"))?;
+
+	// our desktop entries live in their own directory, so we know which bookmarks are ours
+	let entries_dir = data_dir.join("bletchmame").join("recent");
+	if entries_dir.exists() {
+		std::fs::remove_dir_all(&entries_dir)?;
+	}
+	std::fs::create_dir_all(&entries_dir)?;
+	let entry_paths = items
+		.iter()
+		.map(|item| {
+			let path = entries_dir.join(format!("{}.desktop", shortcut_file_name(&item.title)));
+			std::fs::write(&path, desktop_entry(&item.title, &item.program, &item.args, None))?;
+			Ok(path)
+		})
+		.collect::<Result<Vec<_>>>()?;
+
+	// and rewrite the bookmarks; this file belongs to the whole desktop, so if we cannot make sense of it we leave it be
+	let xbel_path = data_dir.join("recently-used.xbel");
+	let xbel = match std::fs::read_to_string(&xbel_path) {
+		Ok(xbel) => xbel,
+		Err(e) if e.kind() == ErrorKind::NotFound => EMPTY_XBEL.to_string(),
+		Err(e) => return Err(e.into()),
+	};
+	let entry_uris = entry_paths.iter().map(|x| file_uri(x)).collect::<Vec<_>>();
+	let timestamp = xbel_timestamp(SystemTime::now());
+	let xbel = rewrite_xbel(&xbel, &file_uri(&entries_dir), &entry_uris, &timestamp)?;
+	event!(
+		LOG,
+		"update_xdg_recent_items(): xbel_path={xbel_path:?} entry_uris={entry_uris:?}"
+	);
+
+	// write to a temporary file first, so that other programs never see a partially written file
+	let temporary_path = xbel_path.with_extension("xbel.tmp");
+	std::fs::write(&temporary_path, xbel)?;
+	std::fs::rename(&temporary_path, &xbel_path)?;
+	Ok(())
+}
+
+/// Replaces all bookmarks under `uri_prefix` in an XBEL document with bookmarks for `uris`
+fn rewrite_xbel(xbel: &str, uri_prefix: &str, uris: &[String], timestamp: &str) -> Result<String> {
+	let mut reader = Reader::from_str(xbel);
+	let mut writer = Writer::new(Vec::new());
+	let mut skip_depth = 0;
+	loop {
+		let event = reader.read_event()?;
+		if skip_depth > 0 {
+			match event {
+				Event::Start(_) => skip_depth += 1,
+				Event::End(_) => skip_depth -= 1,
+				_ => {}
+			};
+			continue;
+		}
+
+		match &event {
+			Event::Start(e) | Event::Empty(e) if e.name().as_ref() == b"bookmark" => {
+				let href = e
+					.try_get_attribute("href")?
+					.map(|x| x.unescape_value().map(|x| x.into_owned()))
+					.transpose()?
+					.unwrap_or_default();
+				if href.starts_with(uri_prefix) {
+					if matches!(event, Event::Start(_)) {
+						skip_depth = 1;
+					}
+					continue;
+				}
+			}
+			Event::End(e) if e.name().as_ref() == b"xbel" => {
+				for uri in uris {
+					writer.get_mut().write_all(xbel_bookmark(uri, timestamp).as_bytes())?;
+				}
+			}
+			Event::Eof => break,
+			_ => {}
+		}
+		writer.write_event(event)?;
+	}
+	Ok(String::from_utf8(writer.into_inner())?)
+}
+
+fn xbel_bookmark(uri: &str, timestamp: &str) -> String {
+	let uri = escape(uri);
+	let exec = escape(XBEL_EXEC);
+	format!(
+		"  <bookmark href=\"{uri}\" added=\"{timestamp}\" modified=\"{timestamp}\" visited=\"{timestamp}\">\n    <info>\n      <metadata owner=\"http://freedesktop.org\">\n        <mime:mime-type type=\"application/x-desktop\"/>\n        <bookmark:applications>\n          <bookmark:application name=\"BletchMAME\" exec=\"{exec}\" modified=\"{timestamp}\" count=\"1\"/>\n        </bookmark:applications>\n      </metadata>\n    </info>\n  </bookmark>\n"
+	)
+}
+
+/// Percent encodes a path as a `file://` URI
+fn file_uri(path: &Path) -> String {
+	let path = path.to_string_lossy();
+	let path = path
+		.bytes()
+		.map(|b| {
+			if b.is_ascii_alphanumeric() || b"/-_.~".contains(&b) {
+				char::from(b).to_string()
+			} else {
+				format!("%{b:02X}")
+			}
+		})
+		.collect::<String>();
+	format!("file://{path}")
+}
+
+/// ISO 8601 timestamp in UTC, as used by XBEL
+fn xbel_timestamp(time: SystemTime) -> String {
+	let (year, month, day) = civil_date(time);
+	let secs = time
+		.duration_since(SystemTime::UNIX_EPOCH)
+		.unwrap_or_default()
+		.as_secs()
+		% 86400;
+	let (hour, minute, second) = (secs / 3600, secs / 60 % 60, secs % 60);
+	format!("{year:04}-{month:02}-{day:02}T{hour:02}:{minute:02}:{second:02}Z")
+}
+
+#[cfg(test)]
+mod test {
+	use std::path::Path;
+	use std::time::Duration;
+	use std::time::UNIX_EPOCH;
+
+	use test_case::test_case;
+
+	#[test_case(0, "/home/me/.local/share/bletchmame/recent", "file:///home/me/.local/share/bletchmame/recent")]
+	#[test_case(1, "/home/me/Pac-Man (Midway).desktop", "file:///home/me/Pac-Man%20%28Midway%29.desktop")]
+	pub fn file_uri(_index: usize, path: &str, expected: &str) {
+		let actual = super::file_uri(Path::new(path));
+		assert_eq!(expected, actual);
+	}
+
+	#[test]
+	pub fn xbel_timestamp() {
+		let time = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+		let actual = super::xbel_timestamp(time);
+		assert_eq!("2023-11-14T22:13:20Z", actual);
+	}
+
+	#[test]
+	pub fn rewrite_xbel() {
+		let xbel = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0" xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks" xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info">
+  <bookmark href="file:///home/me/notes.txt" added="2024-01-01T00:00:00Z"/>
+  <bookmark href="file:///recent/Old.desktop" added="2024-01-01T00:00:00Z"><info><metadata/></info></bookmark>
+</xbel>
+"#;
+		let uris = ["file:///recent/New.desktop".to_string()];
+		let actual = super::rewrite_xbel(xbel, "file:///recent", &uris, "2024-02-02T00:00:00Z").unwrap();
+		assert!(actual.contains("file:///home/me/notes.txt"));
+		assert!(!actual.contains("Old.desktop"));
+		assert!(actual.contains("<bookmark href=\"file:///recent/New.desktop\""));
+		assert!(actual.contains("exec=\"gio launch %f\""));
+		assert!(actual.trim_end().ends_with("</xbel>"));
+	}
+
+	#[test]
+	pub fn rewrite_empty_xbel() {
+		let uris = ["file:///recent/New.desktop".to_string()];
+		let actual = super::rewrite_xbel(super::EMPTY_XBEL, "file:///recent", &uris, "").unwrap();
+		assert_eq!(1, actual.matches("<bookmark href=").count());
+	}
+}
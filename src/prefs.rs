@@ -1,4 +1,8 @@
 use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::fmt::Debug;
+use std::fmt::Display;
 use std::fs::create_dir_all;
 use std::fs::rename;
 use std::fs::File;
@@ -6,23 +10,34 @@ use std::io::BufReader;
 use std::io::ErrorKind;
 use std::io::Read;
 use std::io::Write;
+use std::mem::discriminant;
+use std::mem::replace;
 use std::path::Path;
 use std::path::PathBuf;
 use std::rc::Rc;
+use std::sync::Arc;
+use std::sync::Mutex;
 
 use anyhow::Error;
 use anyhow::Result;
 use derive_enum_all_values::AllValues;
 use num::clamp;
 use serde::Deserialize;
+use serde::Deserializer;
 use serde::Serialize;
+use serde::Serializer;
 use slint::LogicalSize;
 use tracing::event;
 use tracing::Level;
 
+use crate::appcommand::AppCommand;
 use crate::history::History;
+use crate::i18n::tr;
+use crate::i18n::tr_args;
 use crate::icon::Icon;
 use crate::info::InfoDb;
+use crate::models::columns::column_provider;
+use crate::software::SoftwareSupport;
 
 const LOG: Level = Level::DEBUG;
 
@@ -35,20 +50,135 @@ pub struct Preferences {
 	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
 	pub paths: Rc<PrefsPaths>,
 
+	/// Other registered MAME executables that are not currently in use, each with the paths used alongside it
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub other_mame_executables: Vec<Rc<PrefsPaths>>,
+
 	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
 	pub window_size: Option<PrefsSize>,
 
+	/// MAME window sizes (in pixels) for machines run in their own windows, keyed by machine name
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub machine_window_sizes: BTreeMap<String, (u32, u32)>,
+
+	/// Last position and size of dialogs, keyed by dialog type (e.g. - "PathsDialog")
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub dialog_geometry: BTreeMap<String, PrefsDialogGeometry>,
+
 	#[serde(default)]
 	pub items_columns: Vec<PrefsColumn>,
 
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub items_view_mode: ItemsViewMode,
+
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub items_grid_columns: Option<u32>,
+
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub clone_display: CloneDisplay,
+
+	/// What double clicking an item (or pressing Enter on it) does
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub item_action: ItemAction,
+
+	/// Kinds of machines left out of "All Systems"
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub hidden_machine_kinds: BTreeSet<MachineKind>,
+
 	#[serde(default)]
 	pub collections: Vec<Rc<PrefsCollection>>,
 
+	/// Folders (by name) whose nested folders are hidden in the collections view
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub collapsed_folders: BTreeSet<String>,
+
 	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
 	pub history: Vec<HistoryEntry>,
 
 	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
 	pub history_position: usize,
+
+	/// Keyboard shortcuts (accelerator text to command); `None` for the defaults
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub shortcuts: Option<BTreeMap<String, AppCommand>>,
+
+	/// Recently recorded or played back MAME input files, most recent first
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub recent_input_files: Vec<PrefsInputFile>,
+
+	/// Recently run machines, most recent first; these are offered by the OS (e.g. - the taskbar jump list)
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub recent_machines: Vec<String>,
+
+	/// The machine (and images) last run, for "Resume Last Session" in the taskbar jump list
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub last_session: Option<PrefsSession>,
+
+	/// Recently used network play settings, most recent first
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub netplay_presets: Vec<PrefsNetplay>,
+
+	/// How screenshots saved from running machines are named
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub screenshots: PrefsScreenshots,
+
+	/// How movies recorded from running machines are saved
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub recording: PrefsRecording,
+
+	/// Directories that screenshots and recordings are saved into rather than the first snapshot path, keyed by machine
+	/// name; these can contain tokens (e.g. - "{machine}") and path variables (e.g. - `$(MAMEPATH)`)
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub machine_capture_directories: BTreeMap<String, String>,
+
+	/// Like `machine_capture_directories`, but keyed by folder name and applying to each machine within the folder
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub folder_capture_directories: BTreeMap<String, String>,
+
+	/// BIOS sets that machines are started with, keyed by machine name; machines using their default BIOS are absent
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub machine_bios_sets: BTreeMap<String, String>,
+
+	/// Reference snapshots that captured snapshots can be compared against, keyed by machine name
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub reference_snapshots: BTreeMap<String, String>,
+
+	/// How the last launch of each machine went, keyed by machine name; machines whose last launch succeeded are absent
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub launch_outcomes: BTreeMap<String, PrefsLaunchOutcome>,
+
+	/// Font used throughout the UI
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub appearance: PrefsAppearance,
+
+	/// Local HTTP API for controlling the running emulation (see `remote.rs`)
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub remote_control: PrefsRemoteControl,
+
+	/// Synchronizing preferences with other computers through a shared directory (see `prefsync.rs`)
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub sync: PrefsSync,
+
+	/// Show the running machine on Discord (see `discord.rs`)
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub discord_rich_presence: bool,
+
+	/// Snapshots shown full screen after a period of inactivity (see `attract.rs`)
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub attract_mode: PrefsAttractMode,
+
+	/// Language for UI strings (e.g. - "de"); `None` for the untranslated English strings
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub language: Option<String>,
+
+	/// Only mount the first part of multi-part software, rather than mounting each part into a matching device
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub mount_first_software_part_only: bool,
+
+	/// The preferred machine for running software, keyed by `software_key()`; this is the machine the software was last
+	/// run on, which is offered first in its "Run" submenu and used when it is double clicked in any collection
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub software_machines: BTreeMap<String, String>,
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
@@ -63,9 +193,18 @@ pub struct PrefsPaths {
 	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
 	pub samples: Vec<String>,
 
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub artwork: Vec<String>,
+
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub cheats: Vec<String>,
+
 	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
 	pub plugins: Vec<String>,
 
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub snapshots: Vec<String>,
+
 	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
 	pub software_lists: Vec<String>,
 
@@ -74,6 +213,220 @@ pub struct PrefsPaths {
 
 	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
 	pub nvram: Option<String>,
+
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub ini: Vec<String>,
+
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub history: Option<String>,
+
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub category: Option<String>,
+
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub languages: Option<String>,
+
+	/// User-defined path variables (e.g. - "ROMROOT"), which paths can start with (e.g. - `$(ROMROOT)/nes`)
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub variables: BTreeMap<String, String>,
+}
+
+/// What "Reset Settings To Default" keeps
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ResetOptions {
+	pub keep_paths: bool,
+	pub keep_collections: bool,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PrefsScreenshots {
+	/// Prompt for a filename every time, rather than naming screenshots automatically
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub ask: bool,
+
+	/// Naming template (e.g. - "{machine}-{sequence}"); `None` for the default
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub template: Option<String>,
+
+	/// Save screenshots into a subdirectory for each machine
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub per_machine_directories: bool,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PrefsRecording {
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub movie_format: MovieFormat,
+}
+
+/// The movie formats supported by MAME's recording; neither is compressed, so there is no bitrate to choose
+#[derive(AllValues, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, strum_macros::Display)]
+#[serde(rename_all = "camelCase")]
+pub enum MovieFormat {
+	#[default]
+	#[strum(to_string = "AVI")]
+	Avi,
+	#[strum(to_string = "MNG")]
+	Mng,
+}
+
+impl MovieFormat {
+	/// The format name understood by MAME, which is also the file extension
+	pub fn extension(&self) -> &'static str {
+		match self {
+			Self::Avi => "avi",
+			Self::Mng => "mng",
+		}
+	}
+
+	/// MNG movies are video only; only AVI movies capture sound
+	pub fn has_sound(&self) -> bool {
+		matches!(self, Self::Avi)
+	}
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PrefsAppearance {
+	/// Font family (e.g. - "Noto Sans CJK JP"); `None` for the platform default
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub font_family: Option<String>,
+
+	/// Font size in logical pixels; `None` for the platform default
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub font_size: Option<u32>,
+}
+
+/// A launch of a machine that did not go well
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", tag = "outcome")]
+pub enum PrefsLaunchOutcome {
+	/// The machine exited shortly after starting
+	Crashed { reason: Option<String> },
+	/// The machine could not be run because BletchMAME or MAME is not set up properly
+	PreflightFailure { reason: String },
+}
+
+impl PrefsLaunchOutcome {
+	pub fn description(&self) -> String {
+		match self {
+			PrefsLaunchOutcome::Crashed { reason: None } => tr("Last launch exited shortly after starting"),
+			PrefsLaunchOutcome::Crashed { reason: Some(reason) } => tr_args(
+				"Last launch exited shortly after starting: {reason}",
+				&[("reason", reason)],
+			),
+			PrefsLaunchOutcome::PreflightFailure { reason } => {
+				tr_args("Last launch could not be started: {reason}", &[("reason", reason)])
+			}
+		}
+	}
+}
+
+/// A facet that narrows down the items view
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", tag = "filter")]
+pub enum PrefsFilter {
+	/// Items from a particular decade (e.g. - 1980 for the 1980s)
+	Decade { decade: u16 },
+	/// Items from a particular manufacturer (or publisher, for software)
+	Manufacturer { manufacturer: String },
+	/// Machines for a particular region or language (e.g. - "USA" or "Japan")
+	Region { region: String },
+	/// Software with a particular level of support in MAME
+	SoftwareSupport { supported: SoftwareSupport },
+}
+
+impl PrefsFilter {
+	pub fn description(&self) -> String {
+		match self {
+			PrefsFilter::Decade { decade } => tr_args("Year: {decade}s", &[("decade", decade)]),
+			PrefsFilter::Manufacturer { manufacturer } => {
+				tr_args("Manufacturer: {manufacturer}", &[("manufacturer", manufacturer)])
+			}
+			PrefsFilter::Region { region } => tr_args("Region: {region}", &[("region", region)]),
+			PrefsFilter::SoftwareSupport { supported } => {
+				tr_args("Support: {supported}", &[("supported", &tr(&supported.to_string()))])
+			}
+		}
+	}
+
+	/// Filters of the same kind are alternatives to each other (e.g. - "USA" or "Europe")
+	pub fn is_same_kind(&self, other: &Self) -> bool {
+		discriminant(self) == discriminant(other)
+	}
+}
+
+/// The decade of a MAME year (e.g. - "1982" or "198?"), if known
+pub fn year_decade(year: &str) -> Option<u16> {
+	let century_and_decade = year.get(..3).filter(|x| x.bytes().all(|ch| ch.is_ascii_digit()))?;
+	century_and_decade.parse::<u16>().ok().map(|x| x * 10)
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PrefsAttractMode {
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub enabled: bool,
+
+	/// Eventually run a random machine from the current collection, rather than just showing snapshots
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub auto_launch: bool,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PrefsRemoteControl {
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub enabled: bool,
+
+	/// TCP port to listen on; `None` for the default
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub port: Option<u16>,
+
+	/// Accept connections from other computers, rather than just localhost
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub allow_lan: bool,
+
+	/// Access token that connections from other computers have to present
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub token: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PrefsSync {
+	/// Directory (e.g. - a Dropbox folder) holding the synchronized preferences; `None` if not synchronizing
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub directory: Option<String>,
+
+	/// WebDAV collection holding the synchronized preferences, used instead of `directory`
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub url: Option<String>,
+
+	/// Modification time (in seconds since the Unix epoch) of the synchronized preferences when last synchronized
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub last_synced: Option<u64>,
+
+	/// Whether preferences have changed since they were last synchronized
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub dirty: bool,
+}
+
+impl PrefsSync {
+	pub fn is_enabled(&self) -> bool {
+		self.directory.is_some() || self.url.is_some()
+	}
+}
+
+/// Position is in physical pixels (as reported by the windowing system); size is logical
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PrefsDialogGeometry {
+	pub x: i32,
+	pub y: i32,
+	pub size: PrefsSize,
 }
 
 #[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq)]
@@ -111,6 +464,70 @@ pub struct PrefsColumn {
 	pub sort: Option<SortOrder>,
 
 	pub width: f32,
+
+	/// Text alignment; `None` for the default for this column type
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub alignment: Option<ColumnAlignment>,
+
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub ellipsis: ColumnEllipsis,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ColumnAlignment {
+	#[default]
+	Left,
+	Right,
+}
+
+/// How text that is too long for a column is shortened
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ColumnEllipsis {
+	#[default]
+	End,
+	Middle,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ItemsViewMode {
+	#[default]
+	Table,
+	Grid,
+}
+
+/// Kinds of machines that are not really systems in their own right
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MachineKind {
+	Bios,
+	Device,
+	Mechanical,
+}
+
+/// What double clicking an item (or pressing Enter on it) does; items that the action does not apply to (e.g. -
+/// browsing the software of a machine without software lists) are run instead
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ItemAction {
+	#[default]
+	Run,
+	Configure,
+	BrowseSoftware,
+	ShowDetails,
+}
+
+/// How clones of other machines are presented in the items view
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CloneDisplay {
+	#[default]
+	Show,
+	Hide,
+	/// Clones are nested beneath their parent machine
+	Group,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -120,19 +537,73 @@ pub enum SortOrder {
 	Descending,
 }
 
-#[derive(AllValues, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, strum_macros::Display)]
-#[serde(rename_all = "camelCase")]
-pub enum ColumnType {
-	#[strum(to_string = "Name")]
-	Name,
-	#[strum(to_string = "Source File")]
-	SourceFile,
-	#[strum(to_string = "Description")]
-	Description,
-	#[strum(to_string = "Year")]
-	Year,
-	#[strum(to_string = "Provider")]
-	Provider,
+/// Identifies a column in the items view, and is persisted as its id (e.g. - "maximumRam"); everything else about a
+/// column (title, text, sorting) comes from its provider in `models::columns`, so that adding a column does not
+/// involve touching serialization
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ColumnType(&'static str);
+
+impl ColumnType {
+	pub const NAME: Self = Self("name");
+	pub const SOURCE_FILE: Self = Self("sourceFile");
+	pub const DESCRIPTION: Self = Self("description");
+	pub const YEAR: Self = Self("year");
+	pub const PROVIDER: Self = Self("provider");
+	pub const CPU: Self = Self("cpu");
+	pub const SOUND: Self = Self("sound");
+	pub const RAM: Self = Self("ram");
+	pub const MAXIMUM_RAM: Self = Self("maximumRam");
+	pub const CATEGORY: Self = Self("category");
+	pub const REGION: Self = Self("region");
+	pub const REQUIREMENTS: Self = Self("requirements");
+	pub const RUNS_ON: Self = Self("runsOn");
+
+	pub const fn new(id: &'static str) -> Self {
+		Self(id)
+	}
+
+	/// Columns persisted by a provider that is not (or no longer) present are kept, so the ids have to be interned
+	pub fn from_id(id: &str) -> Self {
+		static INTERNED: Mutex<BTreeSet<&'static str>> = Mutex::new(BTreeSet::new());
+		let mut interned = INTERNED.lock().unwrap();
+		let id = if let Some(&id) = interned.get(id) {
+			id
+		} else {
+			let id: &'static str = Box::leak(Box::<str>::from(id));
+			interned.insert(id);
+			id
+		};
+		Self(id)
+	}
+
+	pub fn id(&self) -> &'static str {
+		self.0
+	}
+}
+
+impl Debug for ColumnType {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(self.0)
+	}
+}
+
+impl Serialize for ColumnType {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+	{
+		serializer.serialize_str(self.0)
+	}
+}
+
+impl<'de> Deserialize<'de> for ColumnType {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		let s: String = Deserialize::deserialize(deserializer)?;
+		Ok(Self::from_id(&s))
+	}
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
@@ -143,9 +614,28 @@ pub enum PrefsCollection {
 		#[serde(rename = "machine")]
 		machine_name: String,
 	},
+	/// All machines whose primary CPU is a particular chip
+	MachinesWithCpu {
+		cpu: String,
+	},
+	/// All machines that can be configured with an amount of RAM (in bytes) within a range
+	MachinesWithRam {
+		#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+		minimum: Option<u64>,
+		#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+		maximum: Option<u64>,
+	},
+	/// All machines in a top level category genre (e.g. - "Shooter"), as assigned by the category file
+	Category {
+		category: String,
+	},
 	Folder {
 		name: String,
 
+		/// The folder (by name) that this folder is nested within; `None` for top level folders
+		#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+		parent: Option<String>,
+
 		#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
 		items: Vec<PrefsItem>,
 	},
@@ -154,19 +644,26 @@ pub enum PrefsCollection {
 impl PrefsCollection {
 	pub fn icon(&self) -> Icon {
 		match self {
-			PrefsCollection::Builtin(_) | PrefsCollection::MachineSoftware { .. } => Icon::Search,
+			PrefsCollection::Builtin(_)
+			| PrefsCollection::MachineSoftware { .. }
+			| PrefsCollection::MachinesWithCpu { .. }
+			| PrefsCollection::MachinesWithRam { .. }
+			| PrefsCollection::Category { .. } => Icon::Search,
 			PrefsCollection::Folder { .. } => Icon::Folder,
 		}
 	}
 
 	pub fn description(&self, info_db: &InfoDb) -> Cow<'_, str> {
 		match self {
-			PrefsCollection::Builtin(x) => format!("{x}").into(),
+			PrefsCollection::Builtin(x) => tr(&x.to_string()).into(),
 			PrefsCollection::MachineSoftware { machine_name } => {
 				let machine_desc = info_db.machines().find(machine_name).unwrap().description();
-				format!("Software for \"{}\"", machine_desc).into()
+				tr_args("Software for \"{machine}\"", &[("machine", &machine_desc)]).into()
 			}
-			PrefsCollection::Folder { name, items: _ } => Cow::Borrowed(name),
+			PrefsCollection::MachinesWithCpu { cpu } => tr_args("Machines With {cpu}", &[("cpu", cpu)]).into(),
+			PrefsCollection::MachinesWithRam { minimum, maximum } => ram_range_description(*minimum, *maximum).into(),
+			PrefsCollection::Category { category } => Cow::Borrowed(category),
+			PrefsCollection::Folder { name, .. } => Cow::Borrowed(name),
 		}
 	}
 }
@@ -188,6 +685,10 @@ pub struct HistoryEntry {
 	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
 	pub search: String,
 
+	/// Structured filters applied on top of the search (shown as chips under the search box)
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub filters: Vec<PrefsFilter>,
+
 	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
 	pub sort_suppressed: bool,
 
@@ -196,18 +697,81 @@ pub struct HistoryEntry {
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PrefsInputFile {
+	#[serde(rename = "machine")]
+	pub machine_name: String,
+	pub path: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PrefsSession {
+	#[serde(rename = "machine")]
+	pub machine_name: String,
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub initial_loads: Vec<(Arc<str>, Arc<str>)>,
+}
+
+/// Settings for MAME's `-comm_*` options, which link two machines over the network
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PrefsNetplay {
+	pub local_host: String,
+	pub local_port: u16,
+	pub remote_host: String,
+	pub remote_port: u16,
+
+	#[serde(default, skip_serializing_if = "default_ext::DefaultExt::is_default")]
+	pub frame_sync: bool,
+}
+
+impl PrefsNetplay {
+	pub fn description(&self) -> String {
+		format!(
+			"{}:{} (from {}:{})",
+			self.remote_host, self.remote_port, self.local_host, self.local_port
+		)
+	}
+}
+
+impl Default for PrefsNetplay {
+	/// MAME's own defaults
+	fn default() -> Self {
+		Self {
+			local_host: "0.0.0.0".into(),
+			local_port: 15112,
+			remote_host: "127.0.0.1".into(),
+			remote_port: 15112,
+			frame_sync: false,
+		}
+	}
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash, strum_macros::Display)]
 #[serde(rename_all = "camelCase", tag = "type")]
 pub enum PrefsItem {
+	#[strum(to_string = "Machine \"{machine_name}\"")]
 	Machine {
 		#[serde(rename = "machine")]
 		machine_name: String,
 	},
-	Software {
-		software_list: String,
-		software: String,
-	},
+	#[strum(to_string = "Software \"{software_list}:{software}\"")]
+	Software { software_list: String, software: String },
 }
 
+pub const DEFAULT_GRID_COLUMNS: u32 = 5;
+
+const DEFAULT_COLUMN_WIDTH: f32 = 150.0;
+
+const MIN_COLUMN_WIDTH: f32 = 40.0;
+
+const MAX_RECENT_INPUT_FILES: usize = 10;
+
+const MAX_RECENT_MACHINES: usize = 10;
+
+const MAX_NETPLAY_PRESETS: usize = 10;
+
 const PREFS: Option<&str> = Some("BletchMAME.json");
 const PREFS_BACKUP: Option<&str> = Some("BletchMAME.backup.json");
 
@@ -244,6 +808,16 @@ impl Preferences {
 		save_prefs(self, &path)
 	}
 
+	/// Deserializes preferences from JSON (e.g. - preferences synchronized from another computer), with the same
+	/// treatments as when loading them
+	pub fn from_json(value: serde_json::Value, prefs_path: Option<PathBuf>) -> Result<Self> {
+		let mut prefs: Preferences =
+			serde_json::from_value(value).map_err(|e| Error::new(e).context("Error loading preferences"))?;
+		prefs_treatments(&mut prefs);
+		prefs.prefs_path = prefs_path;
+		Ok(prefs)
+	}
+
 	pub fn fresh(prefs_path: Option<PathBuf>) -> Self {
 		let json = include_str!("prefs_fresh.json");
 		let mut result = load_prefs_from_reader(json.as_bytes()).unwrap();
@@ -256,6 +830,191 @@ impl Preferences {
 		result.prefs_path = prefs_path;
 		result
 	}
+
+	/// Fresh preferences that carry over whatever `options` asks to keep from these preferences
+	pub fn fresh_keeping(&self, options: ResetOptions) -> Self {
+		let mut result = Self::fresh(self.prefs_path.clone());
+		if options.keep_paths {
+			result.paths = self.paths.clone();
+			result.other_mame_executables = self.other_mame_executables.clone();
+		}
+		if options.keep_collections {
+			result.collections = self.collections.clone();
+			result.collapsed_folders = self.collapsed_folders.clone();
+		}
+		result
+	}
+
+	/// Describes what resetting these preferences would throw away, given what `options` keeps
+	pub fn reset_losses(&self, options: ResetOptions) -> Vec<String> {
+		let mut result = Vec::new();
+		if !options.keep_collections {
+			let folder_item_count = self
+				.collections
+				.iter()
+				.filter_map(|x| match x.as_ref() {
+					PrefsCollection::Folder { items, .. } => Some(items.len()),
+					_ => None,
+				})
+				.sum::<usize>();
+			let count = self.collections.len();
+			let args: &[(&str, &dyn Display)] = &[("count", &count), ("items", &folder_item_count)];
+			result.push(tr_args("{count} collections ({items} items in folders)", args));
+		}
+		if !options.keep_paths {
+			if let Some(mame_executable) = &self.paths.mame_executable {
+				result.push(tr_args("MAME executable: {path}", &[("path", mame_executable)]));
+			}
+			let paths = [
+				&self.paths.roms,
+				&self.paths.samples,
+				&self.paths.artwork,
+				&self.paths.snapshots,
+				&self.paths.software_lists,
+			];
+			let count = paths.iter().map(|x| x.len()).sum::<usize>();
+			let text = tr_args(
+				"{count} ROM, sample, artwork, snapshot and software list paths",
+				&[("count", &count)],
+			);
+			result.push(text);
+			if !self.other_mame_executables.is_empty() {
+				let count = self.other_mame_executables.len();
+				result.push(tr_args(
+					"{count} other MAME executables and their paths",
+					&[("count", &count)],
+				));
+			}
+		}
+		result.push(tr("Columns, shortcuts, history and all other settings"));
+		result
+	}
+
+	/// All registered MAME executables (including the current one), sorted
+	pub fn mame_executables(&self) -> Vec<&str> {
+		let mut result = self
+			.paths
+			.mame_executable
+			.iter()
+			.chain(
+				self.other_mame_executables
+					.iter()
+					.flat_map(|x| x.mame_executable.iter()),
+			)
+			.map(String::as_str)
+			.collect::<Vec<_>>();
+		result.sort();
+		result
+	}
+
+	/// Switches to a different MAME executable; if it has not been used before, it is registered with our
+	/// current paths as a starting point
+	pub fn switch_mame_executable(&mut self, mame_executable: String) {
+		if self.paths.mame_executable.as_ref() == Some(&mame_executable) {
+			return;
+		}
+
+		let index = self
+			.other_mame_executables
+			.iter()
+			.position(|x| x.mame_executable.as_ref() == Some(&mame_executable));
+		let new_paths = if let Some(index) = index {
+			self.other_mame_executables.remove(index)
+		} else {
+			let mut paths = (*self.paths).clone();
+			paths.mame_executable = Some(mame_executable);
+			Rc::new(paths)
+		};
+
+		let old_paths = replace(&mut self.paths, new_paths);
+		if old_paths.mame_executable.is_some() {
+			self.other_mame_executables.push(old_paths);
+		}
+	}
+
+	/// Shows or hides a column in the items table; the last column cannot be hidden
+	pub fn toggle_items_column(&mut self, column_type: ColumnType) {
+		let position = self.items_columns.iter().position(|x| x.column_type == column_type);
+		if let Some(position) = position {
+			if self.items_columns.len() > 1 {
+				self.items_columns.remove(position);
+			}
+		} else {
+			let column = PrefsColumn {
+				column_type,
+				sort: None,
+				width: DEFAULT_COLUMN_WIDTH,
+				alignment: None,
+				ellipsis: ColumnEllipsis::default(),
+			};
+			self.items_columns.push(column);
+		}
+	}
+
+	/// Sizes the items columns to share the specified width (e.g. - that of the items table) in proportion to their
+	/// weights
+	pub fn reset_items_column_widths(&mut self, total_width: f32) {
+		let total_weight = self
+			.items_columns
+			.iter()
+			.map(|x| column_provider(x.column_type).width_weight)
+			.sum::<f32>();
+		for column in self.items_columns.iter_mut() {
+			let width = total_width * column_provider(column.column_type).width_weight / total_weight;
+			column.width = width.floor().max(MIN_COLUMN_WIDTH);
+		}
+	}
+
+	/// Notes an input file that was recorded or played back, moving it to the front of the recent list
+	pub fn add_recent_input_file(&mut self, machine_name: String, path: String) {
+		self.recent_input_files.retain(|x| x.path != path);
+		self.recent_input_files.insert(0, PrefsInputFile { machine_name, path });
+		self.recent_input_files.truncate(MAX_RECENT_INPUT_FILES);
+	}
+
+	/// Notes a machine that was run, moving it to the front of the recent list
+	pub fn add_recent_machine(&mut self, machine_name: String) {
+		self.recent_machines.retain(|x| *x != machine_name);
+		self.recent_machines.insert(0, machine_name);
+		self.recent_machines.truncate(MAX_RECENT_MACHINES);
+	}
+
+	/// Notes network play settings that were used, moving them to the front of the presets
+	pub fn add_netplay_preset(&mut self, netplay: PrefsNetplay) {
+		self.netplay_presets.retain(|x| *x != netplay);
+		self.netplay_presets.insert(0, netplay);
+		self.netplay_presets.truncate(MAX_NETPLAY_PRESETS);
+	}
+}
+
+pub fn ram_range_description(minimum: Option<u64>, maximum: Option<u64>) -> String {
+	match (minimum, maximum) {
+		(Some(minimum), Some(maximum)) if minimum == maximum => {
+			tr_args("Machines With {ram} RAM", &[("ram", &ram_text(minimum))])
+		}
+		(Some(minimum), Some(maximum)) => {
+			let args: &[(&str, &dyn Display)] = &[("minimum", &ram_text(minimum)), ("maximum", &ram_text(maximum))];
+			tr_args("Machines With {minimum} - {maximum} RAM", args)
+		}
+		(Some(minimum), None) => tr_args("Machines With At Least {ram} RAM", &[("ram", &ram_text(minimum))]),
+		(None, Some(maximum)) => tr_args("Machines With At Most {ram} RAM", &[("ram", &ram_text(maximum))]),
+		(None, None) => tr("Machines With RAM Options"),
+	}
+}
+
+/// Identifies software across software lists (e.g. - "coco_cart:pacman"), in the same form MAME accepts
+pub fn software_key(software_list: &str, software: &str) -> String {
+	format!("{software_list}:{software}")
+}
+
+/// Formats a RAM size the way MAME names RAM options (e.g. - "64K")
+pub fn ram_text(size: u64) -> String {
+	const UNITS: [(u64, &str); 3] = [(1 << 30, "G"), (1 << 20, "M"), (1 << 10, "K")];
+	UNITS
+		.iter()
+		.find(|(unit, _)| size >= *unit && size % unit == 0)
+		.map(|(unit, suffix)| format!("{}{suffix}", size / unit))
+		.unwrap_or_else(|| size.to_string())
 }
 
 pub fn prefs_filename(prefs_path: Option<impl AsRef<Path>>, filename: Option<&str>) -> Result<PathBuf> {
@@ -351,7 +1110,13 @@ mod test {
 
 	use super::load_prefs_from_reader;
 	use super::save_prefs_to_string;
+	use super::ColumnType;
 	use super::Preferences;
+	use super::PrefsCollection;
+	use super::PrefsItem;
+	use super::PrefsNetplay;
+	use super::PrefsPaths;
+	use super::ResetOptions;
 
 	#[test]
 	pub fn test() {
@@ -365,6 +1130,187 @@ mod test {
 		assert_eq!(prefs, new_prefs);
 	}
 
+	#[test_case(0, "\"maximumRam\"", ColumnType::MAXIMUM_RAM)]
+	#[test_case(1, "\"auditStatus\"", ColumnType::new("auditStatus"))]
+	pub fn column_type_serde(_index: usize, json: &str, expected: ColumnType) {
+		let actual = serde_json::from_str::<ColumnType>(json).unwrap();
+		assert_eq!(expected, actual);
+		assert_eq!(json, serde_json::to_string(&actual).unwrap());
+	}
+
+	#[test_case(0, None, &[], "/alpha/mame", &["/alpha/mame"])]
+	#[test_case(1, Some("/alpha/mame"), &[], "/alpha/mame", &["/alpha/mame"])]
+	#[test_case(2, Some("/alpha/mame"), &[], "/bravo/mame", &["/alpha/mame", "/bravo/mame"])]
+	#[test_case(3, Some("/bravo/mame"), &["/alpha/mame"], "/alpha/mame", &["/alpha/mame", "/bravo/mame"])]
+	pub fn switch_mame_executable(
+		_index: usize,
+		current: Option<&str>,
+		others: &[&str],
+		new_mame_executable: &str,
+		expected: &[&str],
+	) {
+		let make_paths = |mame_executable: Option<&str>| PrefsPaths {
+			mame_executable: mame_executable.map(str::to_string),
+			roms: vec![format!("{}/roms", mame_executable.unwrap_or_default())],
+			..Default::default()
+		};
+		let mut prefs = Preferences::fresh(None);
+		prefs.paths = make_paths(current).into();
+		prefs.other_mame_executables = others.iter().map(|x| make_paths(Some(x)).into()).collect();
+
+		prefs.switch_mame_executable(new_mame_executable.to_string());
+		assert_eq!(Some(new_mame_executable), prefs.paths.mame_executable.as_deref());
+		assert_eq!(expected, prefs.mame_executables().as_slice());
+
+		// paths registered with an executable follow it around
+		let source = if current == Some(new_mame_executable) || others.contains(&new_mame_executable) {
+			Some(new_mame_executable)
+		} else {
+			current
+		};
+		let expected_roms = format!("{}/roms", source.unwrap_or_default());
+		assert_eq!(Some(&expected_roms), prefs.paths.roms.first());
+	}
+
+	#[test_case(0, false, false, &["2 collections (1 items in folders)", "MAME executable: /mame", "2 ROM, sample, artwork, snapshot and software list paths"])]
+	#[test_case(1, true, false, &["2 collections (1 items in folders)"])]
+	#[test_case(2, false, true, &["MAME executable: /mame", "2 ROM, sample, artwork, snapshot and software list paths"])]
+	#[test_case(3, true, true, &[])]
+	pub fn reset(_index: usize, keep_paths: bool, keep_collections: bool, expected_losses: &[&str]) {
+		let mut prefs = Preferences::fresh(None);
+		prefs.paths = PrefsPaths {
+			mame_executable: Some("/mame".into()),
+			roms: vec!["/roms".into()],
+			samples: vec!["/samples".into()],
+			..Default::default()
+		}
+		.into();
+		prefs.collections = vec![
+			PrefsCollection::Folder {
+				name: "Favorites".into(),
+				parent: None,
+				items: vec![PrefsItem::Machine {
+					machine_name: "coco2b".into(),
+				}],
+			}
+			.into(),
+			PrefsCollection::Folder {
+				name: "Empty".into(),
+				parent: None,
+				items: Vec::new(),
+			}
+			.into(),
+		];
+		let options = ResetOptions {
+			keep_paths,
+			keep_collections,
+		};
+
+		let mut expected_losses = expected_losses.to_vec();
+		expected_losses.push("Columns, shortcuts, history and all other settings");
+		assert_eq!(expected_losses, prefs.reset_losses(options));
+
+		let fresh = Preferences::fresh(None);
+		let reset = prefs.fresh_keeping(options);
+		assert_eq!(keep_paths, reset.paths == prefs.paths);
+		assert_eq!(keep_collections, reset.collections == prefs.collections);
+		assert_eq!(fresh.items_columns, reset.items_columns);
+	}
+
+	#[test]
+	pub fn add_recent_input_file() {
+		let mut prefs = Preferences::fresh(None);
+		for i in 0..12 {
+			prefs.add_recent_input_file("coco2b".into(), format!("/inp/{i}.inp"));
+		}
+		prefs.add_recent_input_file("coco2b".into(), "/inp/5.inp".into());
+
+		let actual = prefs
+			.recent_input_files
+			.iter()
+			.map(|x| x.path.as_str())
+			.collect::<Vec<_>>();
+		let expected = [
+			"/inp/5.inp",
+			"/inp/11.inp",
+			"/inp/10.inp",
+			"/inp/9.inp",
+			"/inp/8.inp",
+			"/inp/7.inp",
+			"/inp/6.inp",
+			"/inp/4.inp",
+			"/inp/3.inp",
+			"/inp/2.inp",
+		];
+		assert_eq!(expected.as_slice(), actual.as_slice());
+	}
+
+	#[test]
+	pub fn add_recent_machine() {
+		let mut prefs = Preferences::fresh(None);
+		for machine_name in ["coco", "coco2", "coco2b", "coco3"] {
+			prefs.add_recent_machine(machine_name.into());
+		}
+		prefs.add_recent_machine("coco2".into());
+		assert_eq!(vec!["coco2", "coco3", "coco2b", "coco"], prefs.recent_machines);
+	}
+
+	#[test]
+	pub fn add_netplay_preset() {
+		let netplay = |remote_port| PrefsNetplay {
+			remote_port,
+			..Default::default()
+		};
+		let mut prefs = Preferences::fresh(None);
+		for port in 1000..1012 {
+			prefs.add_netplay_preset(netplay(port));
+		}
+		prefs.add_netplay_preset(netplay(1005));
+
+		let actual = prefs.netplay_presets.iter().map(|x| x.remote_port).collect::<Vec<_>>();
+		let expected = [1005, 1011, 1010, 1009, 1008, 1007, 1006, 1004, 1003, 1002];
+		assert_eq!(expected.as_slice(), actual.as_slice());
+	}
+
+	#[test_case(0, "1982", Some(1980))]
+	#[test_case(1, "198?", Some(1980))]
+	#[test_case(2, "19??", None)]
+	#[test_case(3, "", None)]
+	#[test_case(4, "2001", Some(2000))]
+	pub fn year_decade(_index: usize, year: &str, expected: Option<u16>) {
+		let actual = super::year_decade(year);
+		assert_eq!(expected, actual);
+	}
+
+	#[test_case(0, ColumnType::CPU, true)]
+	#[test_case(1, ColumnType::YEAR, false)]
+	pub fn toggle_items_column(_index: usize, column_type: ColumnType, expected: bool) {
+		let mut prefs = Preferences::fresh(None);
+		prefs.toggle_items_column(column_type);
+		let actual = prefs.items_columns.iter().any(|x| x.column_type == column_type);
+		assert_eq!(expected, actual);
+	}
+
+	#[test_case(0, 910.0, &[100.0, 150.0, 400.0, 80.0, 180.0])]
+	#[test_case(1, 1820.0, &[200.0, 300.0, 800.0, 160.0, 360.0])]
+	#[test_case(2, 300.0, &[40.0, 49.0, 131.0, 40.0, 59.0])]
+	pub fn reset_items_column_widths(_index: usize, total_width: f32, expected: &[f32]) {
+		let mut prefs = Preferences::fresh(None);
+		prefs.reset_items_column_widths(total_width);
+		let actual = prefs.items_columns.iter().map(|x| x.width).collect::<Vec<_>>();
+		assert_eq!(expected, actual.as_slice());
+	}
+
+	#[test_case(0, 4096, "4K")]
+	#[test_case(1, 65536, "64K")]
+	#[test_case(2, 1 << 20, "1M")]
+	#[test_case(3, 1536 * 1024, "1536K")]
+	#[test_case(4, 1000, "1000")]
+	pub fn ram_text(_index: usize, size: u64, expected: &str) {
+		let actual = super::ram_text(size);
+		assert_eq!(expected, actual);
+	}
+
 	#[test_case(0, &["foo"])]
 	#[test_case(1, &["foo", "bar"])]
 	pub fn ensure_directory(_index: usize, path_parts: &[&str]) {
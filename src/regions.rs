@@ -0,0 +1,162 @@
+//! Determining the region (or language) of a machine, either with heuristics based on its description and clone
+//! naming, or from a folder style `languages.ini` file (one section per language, listing machine names)
+use std::collections::HashMap;
+use std::fs::read_to_string;
+use std::path::Path;
+
+use anyhow::Result;
+
+use crate::catver::ini_entries;
+
+/// Regions, along with how they appear in the parenthesized qualifiers of machine descriptions
+const REGIONS: &[(&str, &[&str])] = &[
+	("World", &["World"]),
+	("USA", &["USA", "US"]),
+	("Europe", &["Europe", "Euro"]),
+	("Japan", &["Japan"]),
+	("Asia", &["Asia"]),
+	("Korea", &["Korea"]),
+	("Taiwan", &["Taiwan"]),
+	("Hong Kong", &["Hong Kong"]),
+	("China", &["China"]),
+	("Brazil", &["Brazil"]),
+	("Hispanic", &["Hispanic", "Latin America"]),
+	("Spain", &["Spain"]),
+	("France", &["France"]),
+	("Germany", &["Germany"]),
+	("Italy", &["Italy"]),
+	("UK", &["UK"]),
+	("Australia", &["Australia"]),
+];
+
+/// Single letter clone name suffixes conventionally used for regional variants (e.g. - "sf2j")
+const CLONE_SUFFIXES: &[(&str, &str)] = &[
+	("j", "Japan"),
+	("u", "USA"),
+	("e", "Europe"),
+	("w", "World"),
+	("a", "Asia"),
+	("k", "Korea"),
+	("t", "Taiwan"),
+	("h", "Hong Kong"),
+	("b", "Brazil"),
+];
+
+#[derive(Debug, Default)]
+pub struct Languages {
+	machines: HashMap<String, String>,
+}
+
+impl Languages {
+	pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+		let text = read_to_string(path)?;
+		Ok(Self::from_text(&text))
+	}
+
+	pub fn from_text(text: &str) -> Self {
+		let mut machines = HashMap::new();
+		for (language, machine_name) in ini_entries(text) {
+			machines
+				.entry(machine_name.to_string())
+				.or_insert_with(|| language.to_string());
+		}
+		Self { machines }
+	}
+
+	pub fn machine_language(&self, machine_name: &str) -> Option<&str> {
+		self.machines.get(machine_name).map(String::as_str)
+	}
+}
+
+/// Guesses the region of a machine, first from its description and then from how its name relates to its parent
+pub fn machine_region(description: &str, name: &str, clone_of: Option<&str>) -> Option<&'static str> {
+	description_region(description).or_else(|| {
+		let suffix = name.strip_prefix(clone_of?)?;
+		CLONE_SUFFIXES
+			.iter()
+			.find(|(x, _)| *x == suffix)
+			.map(|(_, region)| *region)
+	})
+}
+
+fn description_region(description: &str) -> Option<&'static str> {
+	// regions appear within parenthesized qualifiers, e.g. - "Street Fighter II: The World Warrior (World 910522)"
+	description
+		.split('(')
+		.skip(1)
+		.filter_map(|x| x.split_once(')').map(|(qualifier, _)| qualifier))
+		.find_map(|qualifier| {
+			REGIONS
+				.iter()
+				.flat_map(|(region, aliases)| aliases.iter().map(move |alias| (*region, *alias)))
+				.filter_map(|(region, alias)| find_word(qualifier, alias).map(|position| (position, region)))
+				.min_by_key(|(position, _)| *position)
+				.map(|(_, region)| region)
+		})
+}
+
+/// Finds a word (or phrase) within text, provided that it is not part of a larger word
+fn find_word(text: &str, word: &str) -> Option<usize> {
+	text.match_indices(word)
+		.map(|(position, _)| position)
+		.find(|&position| {
+			let before = text[..position].chars().next_back();
+			let after = text[position + word.len()..].chars().next();
+			!before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+		})
+}
+
+#[cfg(test)]
+mod test {
+	use test_case::test_case;
+
+	use super::Languages;
+
+	#[test_case(0, "Pac-Man", "pacman", None, None)]
+	#[test_case(1, "Street Fighter II: The World Warrior (World 910522)", "sf2", None, Some("World"))]
+	#[test_case(2, "Street Fighter II: The World Warrior (USA 910522)", "sf2ua", Some("sf2"), Some("USA"))]
+	#[test_case(3, "Street Fighter II: The World Warrior (Japan 911210)", "sf2j", Some("sf2"), Some("Japan"))]
+	#[test_case(4, "Galaga (Namco rev. B)", "galaga", None, None)]
+	#[test_case(5, "Galaga (Midway set 1)", "galagamw", Some("galaga"), None)]
+	#[test_case(6, "Final Fight (Hong Kong, bootleg)", "ffightbl", Some("ffight"), Some("Hong Kong"))]
+	#[test_case(7, "Something (Europe, US)", "something", None, Some("Europe"))]
+	#[test_case(8, "Some Clone (bootleg)", "somej", Some("some"), Some("Japan"))]
+	#[test_case(9, "Contra (US / Asia)", "contra", None, Some("USA"))]
+	#[test_case(10, "Blaster (USSR)", "blaster", None, None)]
+	pub fn machine_region(
+		_index: usize,
+		description: &str,
+		name: &str,
+		clone_of: Option<&str>,
+		expected: Option<&str>,
+	) {
+		let actual = super::machine_region(description, name, clone_of);
+		assert_eq!(expected, actual);
+	}
+
+	const LANGUAGES_INI: &str = "[FOLDER_SETTINGS]
+RootFolderIcon mame
+SubFolderIcon folder
+
+[ROOT_FOLDER]
+
+[English]
+pacman
+galaga
+
+[Japanese]
+puckman
+galaga
+";
+
+	#[test_case(0, "pacman", Some("English"))]
+	#[test_case(1, "puckman", Some("Japanese"))]
+	#[test_case(2, "galaga", Some("English"))]
+	#[test_case(3, "dkong", None)]
+	#[test_case(4, "RootFolderIcon mame", None)]
+	pub fn machine_language(_index: usize, machine_name: &str, expected: Option<&str>) {
+		let languages = Languages::from_text(LANGUAGES_INI);
+		let actual = languages.machine_language(machine_name);
+		assert_eq!(expected, actual);
+	}
+}
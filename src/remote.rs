@@ -0,0 +1,338 @@
+//! Optional remote control of the running emulation over HTTP, for tools like stream decks or home automation
+//!
+//! Requests are simple `POST`s (e.g. - `curl -X POST http://localhost:8089/pause`):
+//!
+//! - `/pause`, `/resume`, `/stop`, `/reset/soft`, `/reset/hard`
+//! - `/run/<machine>` to switch to another machine
+//! - `/state/load?path=<path>` to load a save state
+//!
+//! The server only listens on localhost unless connections from other computers are explicitly allowed, in which case
+//! every request has to carry an `Authorization: Bearer <token>` header with the access token shown in the UI.
+//! Requests from web browsers (which always carry an `Origin` header) are refused, so that web pages cannot drive the
+//! emulation.
+//!
+//! Commands are one shot and nothing is pushed back to clients, so this is plain HTTP/1.1 over a blocking listener; a
+//! WebSocket (and the async runtime to serve it) would add nothing for the fire-and-forget clients this is meant for.
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Read;
+use std::io::Write;
+use std::net::Ipv4Addr;
+use std::net::TcpListener;
+use std::net::TcpStream;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::thread::sleep;
+use std::thread::spawn;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use anyhow::Result;
+use serde::Deserialize;
+use serde::Serialize;
+use tracing::event;
+use tracing::Level;
+
+const LOG: Level = Level::DEBUG;
+
+pub const DEFAULT_REMOTE_CONTROL_PORT: u16 = 8089;
+
+/// How often the listener checks whether it has been asked to shut down
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long a client has to send its request
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Longest request line and headers we are willing to read; anything legitimate is far smaller
+const MAX_REQUEST_HEAD_LENGTH: u64 = 8192;
+
+/// Most connections served at once; further connections are turned away until one finishes
+const MAX_CONNECTIONS: usize = 4;
+
+pub const STATUS_OK: &str = "200 OK";
+pub const STATUS_BAD_REQUEST: &str = "400 Bad Request";
+pub const STATUS_UNAUTHORIZED: &str = "401 Unauthorized";
+pub const STATUS_FORBIDDEN: &str = "403 Forbidden";
+pub const STATUS_NOT_FOUND: &str = "404 Not Found";
+pub const STATUS_METHOD_NOT_ALLOWED: &str = "405 Method Not Allowed";
+pub const STATUS_SERVICE_UNAVAILABLE: &str = "503 Service Unavailable";
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RemoteCommand {
+	Pause,
+	Resume,
+	Stop,
+	SoftReset,
+	HardReset,
+	Run(String),
+	LoadState(String),
+}
+
+pub struct RemoteServer {
+	shutdown: Arc<AtomicBool>,
+	handle: Option<JoinHandle<()>>,
+}
+
+impl RemoteServer {
+	/// Starts listening; `callback` is invoked on a connection thread for each command received, and returns the
+	/// HTTP status to respond with if the command was refused
+	///
+	/// Connections from other computers are only accepted when there is a `lan_token`, which every request then has
+	/// to present
+	pub fn start(
+		port: u16,
+		lan_token: Option<String>,
+		callback: impl Fn(RemoteCommand) -> std::result::Result<(), &'static str> + Send + Sync + 'static,
+	) -> Result<Self> {
+		let address = if lan_token.is_some() {
+			Ipv4Addr::UNSPECIFIED
+		} else {
+			Ipv4Addr::LOCALHOST
+		};
+		let listener = TcpListener::bind((address, port))?;
+		listener.set_nonblocking(true)?;
+		event!(LOG, "RemoteServer::start(): listening on {:?}", listener.local_addr());
+
+		let shutdown = Arc::new(AtomicBool::new(false));
+		let shutdown_clone = shutdown.clone();
+		let callback = Arc::new(callback);
+		let lan_token = Arc::new(lan_token);
+		let connection_count = Arc::new(AtomicUsize::new(0));
+		let handle = spawn(move || {
+			while !shutdown_clone.load(Ordering::Relaxed) {
+				match listener.accept() {
+					Ok((stream, _)) if connection_count.load(Ordering::Relaxed) >= MAX_CONNECTIONS => {
+						event!(LOG, "RemoteServer: too many connections; refusing");
+						let _ = write_response(&stream, STATUS_SERVICE_UNAVAILABLE);
+					}
+					Ok((stream, _)) => {
+						// connections get their own threads (up to a limit), so that a slow client cannot hold up
+						// anybody else
+						let callback = callback.clone();
+						let lan_token = lan_token.clone();
+						let connection_count = connection_count.clone();
+						connection_count.fetch_add(1, Ordering::Relaxed);
+						spawn(move || {
+							let lan_token = lan_token.as_deref();
+							if let Err(e) = handle_connection(stream, lan_token, callback.as_ref()) {
+								event!(LOG, "RemoteServer: error handling connection: {e:?}");
+							}
+							connection_count.fetch_sub(1, Ordering::Relaxed);
+						});
+					}
+					Err(_) => sleep(POLL_INTERVAL),
+				}
+			}
+		});
+
+		let result = Self {
+			shutdown,
+			handle: Some(handle),
+		};
+		Ok(result)
+	}
+}
+
+impl Drop for RemoteServer {
+	fn drop(&mut self) {
+		self.shutdown.store(true, Ordering::Relaxed);
+		if let Some(handle) = self.handle.take() {
+			let _ = handle.join();
+		}
+	}
+}
+
+fn handle_connection(
+	stream: TcpStream,
+	lan_token: Option<&str>,
+	callback: &impl Fn(RemoteCommand) -> std::result::Result<(), &'static str>,
+) -> Result<()> {
+	stream.set_nonblocking(false)?;
+	stream.set_read_timeout(Some(READ_TIMEOUT))?;
+	let mut reader = BufReader::new((&stream).take(MAX_REQUEST_HEAD_LENGTH));
+
+	// read the request line and the headers; the only headers we care about are `Origin` and `Authorization` (and we
+	// ignore any body)
+	let mut request_line = String::new();
+	reader.read_line(&mut request_line)?;
+	let mut has_origin = false;
+	let mut authorization = None;
+	let mut header = String::new();
+	let mut is_complete = false;
+	while reader.read_line(&mut header)? > 0 {
+		if header.trim().is_empty() {
+			is_complete = true;
+			break;
+		}
+		if let Some((name, value)) = header.split_once(':') {
+			let name = name.trim();
+			has_origin |= name.eq_ignore_ascii_case("origin");
+			if name.eq_ignore_ascii_case("authorization") {
+				authorization = Some(value.trim().to_string());
+			}
+		}
+		header.clear();
+	}
+
+	// parse and dispatch the request
+	let mut parts = request_line.split_whitespace();
+	let method = parts.next().unwrap_or_default();
+	let target = parts.next().unwrap_or_default();
+	event!(LOG, "RemoteServer: {} {} has_origin={}", method, target, has_origin);
+	let status = if is_complete {
+		match parse_request(method, target, has_origin, authorization.as_deref(), lan_token) {
+			Ok(command) => callback(command).err().unwrap_or(STATUS_OK),
+			Err(status) => status,
+		}
+	} else {
+		// the request was cut off, or too long for us to read
+		STATUS_BAD_REQUEST
+	};
+
+	// and respond
+	write_response(&stream, status)?;
+	Ok(())
+}
+
+fn write_response(stream: &TcpStream, status: &str) -> Result<()> {
+	let body = format!("{{\"status\":\"{status}\"}}");
+	write!(
+		&*stream,
+		"HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+		body.len()
+	)?;
+	Ok(())
+}
+
+/// Interprets a request; on failure returns the HTTP status to respond with
+fn parse_request(
+	method: &str,
+	target: &str,
+	has_origin: bool,
+	authorization: Option<&str>,
+	lan_token: Option<&str>,
+) -> std::result::Result<RemoteCommand, &'static str> {
+	// web browsers send `Origin` with cross-origin requests; a web page has no business controlling MAME
+	if has_origin {
+		return Err(STATUS_FORBIDDEN);
+	}
+
+	// when we are reachable from other computers, clients have to prove that they were given the token
+	if let Some(lan_token) = lan_token {
+		let bearer = authorization.and_then(|x| x.strip_prefix("Bearer ")).map(str::trim);
+		if !bearer.is_some_and(|x| constant_time_eq(x.as_bytes(), lan_token.as_bytes())) {
+			return Err(STATUS_UNAUTHORIZED);
+		}
+	}
+
+	if method != "POST" {
+		return Err(STATUS_METHOD_NOT_ALLOWED);
+	}
+	let (path, query) = target.split_once('?').unwrap_or((target, ""));
+	let query_param = |name| {
+		query
+			.split('&')
+			.filter_map(|x| x.split_once('='))
+			.find(|(key, _)| *key == name)
+			.and_then(|(_, value)| percent_decode(value))
+	};
+
+	let command = match path.trim_end_matches('/') {
+		"/pause" => RemoteCommand::Pause,
+		"/resume" => RemoteCommand::Resume,
+		"/stop" => RemoteCommand::Stop,
+		"/reset/soft" => RemoteCommand::SoftReset,
+		"/reset/hard" => RemoteCommand::HardReset,
+		"/state/load" => RemoteCommand::LoadState(query_param("path").ok_or(STATUS_BAD_REQUEST)?),
+		path => {
+			let machine_name = path.strip_prefix("/run/").ok_or(STATUS_NOT_FOUND)?;
+			let machine_name = percent_decode(machine_name).ok_or(STATUS_BAD_REQUEST)?;
+			if machine_name.is_empty() {
+				return Err(STATUS_NOT_FOUND);
+			}
+			RemoteCommand::Run(machine_name)
+		}
+	};
+	Ok(command)
+}
+
+/// Compares tokens without bailing out at the first difference, so that response times give nothing away
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+	a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Creates a new random access token for connections from other computers
+pub fn generate_token() -> String {
+	// `RandomState` is seeded by the operating system's random number generator
+	(0..2)
+		.map(|_| format!("{:016x}", RandomState::new().hash_one(0u8)))
+		.collect()
+}
+
+/// Decodes a URL component; control characters (e.g. - newlines, which delimit commands to the worker_ui plugin) are
+/// never legitimate, so anything containing them is rejected
+fn percent_decode(s: &str) -> Option<String> {
+	let mut bytes = Vec::with_capacity(s.len());
+	let mut iter = s.bytes();
+	while let Some(b) = iter.next() {
+		let b = match b {
+			b'%' => {
+				let hex = [iter.next()?, iter.next()?];
+				u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?
+			}
+			b'+' => b' ',
+			b => b,
+		};
+		bytes.push(b);
+	}
+	String::from_utf8(bytes)
+		.ok()
+		.filter(|x| !x.chars().any(char::is_control))
+}
+
+#[cfg(test)]
+mod test {
+	use test_case::test_case;
+
+	use super::RemoteCommand;
+
+	#[test_case(0, "POST", "/pause", false, None, None, Ok(RemoteCommand::Pause))]
+	#[test_case(1, "POST", "/reset/hard/", false, None, None, Ok(RemoteCommand::HardReset))]
+	#[test_case(2, "POST", "/run/coco2b", false, None, None, Ok(RemoteCommand::Run("coco2b".into())))]
+	#[test_case(3, "POST", "/state/load?path=C%3A%5Cmy+states%5Ccoco.sta", false, None, None, Ok(RemoteCommand::LoadState("C:\\my states\\coco.sta".into())))]
+	#[test_case(4, "POST", "/state/load", false, None, None, Err("400 Bad Request"))]
+	#[test_case(5, "GET", "/pause", false, None, None, Err("405 Method Not Allowed"))]
+	#[test_case(6, "POST", "/bogus", false, None, None, Err("404 Not Found"))]
+	#[test_case(7, "POST", "/run/", false, None, None, Err("404 Not Found"))]
+	#[test_case(8, "POST", "/pause", true, None, None, Err("403 Forbidden"))]
+	#[test_case(9, "POST", "/run/coco2b%0APAUSE", false, None, None, Err("400 Bad Request"))]
+	#[test_case(10, "POST", "/state/load?path=coco.sta%0D%0AEXIT", false, None, None, Err("400 Bad Request"))]
+	#[test_case(11, "POST", "/pause", false, None, Some("s3cret"), Err("401 Unauthorized"))]
+	#[test_case(12, "POST", "/pause", false, Some("Bearer wrong"), Some("s3cret"), Err("401 Unauthorized"))]
+	#[test_case(13, "POST", "/pause", false, Some("Bearer s3cret"), Some("s3cret"), Ok(RemoteCommand::Pause))]
+	#[test_case(14, "POST", "/pause", false, Some("Bearer s3cret"), None, Ok(RemoteCommand::Pause))]
+	#[test_case(15, "POST", "/pause", true, Some("Bearer s3cret"), Some("s3cret"), Err("403 Forbidden"))]
+	pub fn parse_request(
+		_index: usize,
+		method: &str,
+		target: &str,
+		has_origin: bool,
+		authorization: Option<&str>,
+		lan_token: Option<&str>,
+		expected: Result<RemoteCommand, &'static str>,
+	) {
+		let actual = super::parse_request(method, target, has_origin, authorization, lan_token);
+		assert_eq!(expected, actual);
+	}
+
+	#[test]
+	pub fn generate_token() {
+		let token = super::generate_token();
+		assert_eq!(32, token.len());
+		assert_ne!(token, super::generate_token());
+	}
+}
@@ -0,0 +1,207 @@
+//! Following machines that MAME has renamed, so that folders and history keep working
+use std::collections::BTreeSet;
+use std::rc::Rc;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::info::InfoDb;
+use crate::prefs::Preferences;
+use crate::prefs::PrefsCollection;
+use crate::prefs::PrefsItem;
+
+/// Machines known to have been renamed by MAME (old name, new name); these are applied when the old InfoDb is not
+/// available to match descriptions against
+const KNOWN_MACHINE_RENAMES: &[(&str, &str)] = &[];
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, strum_macros::Display)]
+#[strum(to_string = "{old_name} \u{2192} {new_name}")]
+pub struct MachineRename {
+	pub old_name: String,
+	pub new_name: String,
+}
+
+/// Finds machines referred to by preferences that are missing from `new_info_db`, but appear to have been renamed;
+/// a machine is considered renamed if it is in the known renames, or if exactly one machine in `new_info_db` has the
+/// same description, manufacturer and year as it did in `old_info_db`
+pub fn find_machine_renames(
+	prefs: &Preferences,
+	old_info_db: Option<&InfoDb>,
+	new_info_db: &InfoDb,
+) -> Vec<MachineRename> {
+	referenced_machine_names(prefs)
+		.into_iter()
+		.filter(|old_name| new_info_db.machines().find(old_name).is_none())
+		.filter_map(|old_name| {
+			let known = KNOWN_MACHINE_RENAMES
+				.iter()
+				.find(|(x, _)| *x == old_name)
+				.map(|(_, new_name)| *new_name)
+				.filter(|new_name| new_info_db.machines().find(new_name).is_some());
+			let new_name = known.or_else(|| {
+				let old_machine = old_info_db?.machines().find(old_name)?;
+				let mut candidates = new_info_db.machines().iter().filter(|x| {
+					x.description() == old_machine.description()
+						&& x.manufacturer() == old_machine.manufacturer()
+						&& x.year() == old_machine.year()
+				});
+				let candidate = candidates.next()?;
+				candidates.next().is_none().then(|| candidate.name())
+			})?;
+			let rename = MachineRename {
+				old_name: old_name.to_string(),
+				new_name: new_name.to_string(),
+			};
+			Some(rename)
+		})
+		.collect()
+}
+
+/// Updates all references to renamed machines in preferences
+pub fn apply_machine_renames(prefs: &mut Preferences, renames: &[MachineRename]) {
+	let new_name = |old_name: &str| {
+		renames
+			.iter()
+			.find(|x| x.old_name == old_name)
+			.map(|x| x.new_name.clone())
+	};
+	let rename_item = |item: &mut PrefsItem| {
+		if let PrefsItem::Machine { machine_name } = item {
+			if let Some(new_name) = new_name(machine_name) {
+				*machine_name = new_name;
+			}
+		}
+	};
+	let rename_collection = |collection: &mut Rc<PrefsCollection>| match Rc::make_mut(collection) {
+		PrefsCollection::MachineSoftware { machine_name } => {
+			if let Some(new_name) = new_name(machine_name) {
+				*machine_name = new_name;
+			}
+		}
+		PrefsCollection::Folder { items, .. } => items.iter_mut().for_each(rename_item),
+		_ => {}
+	};
+
+	prefs.collections.iter_mut().for_each(rename_collection);
+	for entry in prefs.history.iter_mut() {
+		rename_collection(&mut entry.collection);
+		entry.selection.iter_mut().for_each(rename_item);
+	}
+	for input_file in prefs.recent_input_files.iter_mut() {
+		if let Some(new_name) = new_name(&input_file.machine_name) {
+			input_file.machine_name = new_name;
+		}
+	}
+	for rename in renames {
+		if let Some(size) = prefs.machine_window_sizes.remove(&rename.old_name) {
+			prefs
+				.machine_window_sizes
+				.entry(rename.new_name.clone())
+				.or_insert(size);
+		}
+		if let Some(path) = prefs.reference_snapshots.remove(&rename.old_name) {
+			prefs.reference_snapshots.entry(rename.new_name.clone()).or_insert(path);
+		}
+		if let Some(directory) = prefs.machine_capture_directories.remove(&rename.old_name) {
+			prefs
+				.machine_capture_directories
+				.entry(rename.new_name.clone())
+				.or_insert(directory);
+		}
+		if let Some(bios) = prefs.machine_bios_sets.remove(&rename.old_name) {
+			prefs.machine_bios_sets.entry(rename.new_name.clone()).or_insert(bios);
+		}
+	}
+}
+
+fn referenced_machine_names(prefs: &Preferences) -> BTreeSet<&str> {
+	let item_machine_name = |item: &PrefsItem| match item {
+		PrefsItem::Machine { machine_name } => Some(machine_name.as_str()),
+		PrefsItem::Software { .. } => None,
+	};
+	let collection_machine_names = |collection: &Rc<PrefsCollection>| match collection.as_ref() {
+		PrefsCollection::MachineSoftware { machine_name } => vec![machine_name.as_str()],
+		PrefsCollection::Folder { items, .. } => items.iter().filter_map(item_machine_name).collect(),
+		_ => Vec::new(),
+	};
+
+	let collections = prefs.collections.iter().flat_map(collection_machine_names);
+	let history = prefs.history.iter().flat_map(|entry| {
+		let selection = entry.selection.iter().filter_map(item_machine_name);
+		collection_machine_names(&entry.collection).into_iter().chain(selection)
+	});
+	let others = prefs
+		.recent_input_files
+		.iter()
+		.map(|x| x.machine_name.as_str())
+		.chain(prefs.machine_window_sizes.keys().map(|x| x.as_str()))
+		.chain(prefs.reference_snapshots.keys().map(|x| x.as_str()))
+		.chain(prefs.machine_capture_directories.keys().map(|x| x.as_str()))
+		.chain(prefs.machine_bios_sets.keys().map(|x| x.as_str()));
+	collections.chain(history).chain(others).collect()
+}
+
+#[cfg(test)]
+mod test {
+	use std::rc::Rc;
+
+	use crate::info::InfoDb;
+	use crate::prefs::Preferences;
+	use crate::prefs::PrefsCollection;
+	use crate::prefs::PrefsItem;
+
+	use super::MachineRename;
+
+	fn machine(machine_name: &str) -> PrefsItem {
+		let machine_name = machine_name.to_string();
+		PrefsItem::Machine { machine_name }
+	}
+
+	#[test]
+	fn renames() {
+		let old_xml = include_str!("info/test_data/listxml_coco.xml");
+		let new_xml = old_xml.replace("\"coco2b\"", "\"coco2bus\"");
+		let old_info_db = InfoDb::from_listxml_output(old_xml.as_bytes(), |_| false)
+			.unwrap()
+			.unwrap();
+		let new_info_db = InfoDb::from_listxml_output(new_xml.as_bytes(), |_| false)
+			.unwrap()
+			.unwrap();
+
+		let mut prefs = Preferences::fresh(None);
+		let folder = PrefsCollection::Folder {
+			name: "Favorites".into(),
+			parent: None,
+			items: vec![machine("coco"), machine("coco2b"), machine("gone")],
+		};
+		prefs.collections.push(Rc::new(folder));
+		prefs.reference_snapshots.insert("coco2b".into(), "coco2b.png".into());
+
+		// without the old InfoDb there is nothing to go on
+		assert_eq!(
+			Vec::<MachineRename>::new(),
+			super::find_machine_renames(&prefs, None, &new_info_db)
+		);
+
+		// with it, the description match finds the rename
+		let renames = super::find_machine_renames(&prefs, Some(&old_info_db), &new_info_db);
+		let expected = vec![MachineRename {
+			old_name: "coco2b".into(),
+			new_name: "coco2bus".into(),
+		}];
+		assert_eq!(expected, renames);
+
+		// and applying it updates references
+		super::apply_machine_renames(&mut prefs, &renames);
+		let expected = PrefsCollection::Folder {
+			name: "Favorites".into(),
+			parent: None,
+			items: vec![machine("coco"), machine("coco2bus"), machine("gone")],
+		};
+		assert_eq!(&expected, prefs.collections.last().unwrap().as_ref());
+		assert_eq!(
+			vec!["coco2bus"],
+			prefs.reference_snapshots.keys().map(|x| x.as_str()).collect::<Vec<_>>()
+		);
+	}
+}
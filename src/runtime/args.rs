@@ -1,6 +1,9 @@
 use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::env::current_exe;
 use std::fs::metadata;
+#[cfg(unix)]
+use std::fs::set_permissions;
 use std::path::Path;
 use std::path::PathBuf;
 
@@ -15,6 +18,9 @@ use crate::runtime::MameWindowing;
 
 const LOG: Level = Level::DEBUG;
 
+/// Variables that are always available; user-defined path variables cannot use these names
+pub const BUILTIN_VARIABLES: &[&str] = &["MAMEPATH", "BLETCHMAMEPATH"];
+
 #[derive(Copy, Clone, Debug, strum_macros::Display)]
 pub enum PreflightProblem {
 	#[strum(to_string = "No MAME executable path specified")]
@@ -41,38 +47,58 @@ enum ThisError {
 pub struct MameArgumentsSource<'a> {
 	windowing: &'a MameWindowing,
 	mame_executable_path: Option<&'a str>,
+	variables: &'a BTreeMap<String, String>,
 	roms_paths: &'a [String],
 	samples_paths: &'a [String],
+	artwork_paths: &'a [String],
+	cheats_paths: &'a [String],
 	plugins_paths: &'a [String],
+	snapshots_paths: &'a [String],
 	software_lists_paths: &'a [String],
 	cfg_path: &'a [String],
 	nvram_path: &'a [String],
+	ini_paths: &'a [String],
 }
 
 impl<'a> MameArgumentsSource<'a> {
 	pub fn new(prefs_paths: &'a PrefsPaths, windowing: &'a MameWindowing) -> Result<Self> {
 		let mame_executable_path = prefs_paths.mame_executable.as_deref();
+		let variables = &prefs_paths.variables;
 		let roms_paths = prefs_paths.roms.as_slice();
 		let samples_paths = prefs_paths.samples.as_slice();
+		let artwork_paths = prefs_paths.artwork.as_slice();
+		let cheats_paths = prefs_paths.cheats.as_slice();
 		let plugins_paths = prefs_paths.plugins.as_slice();
+		let snapshots_paths = prefs_paths.snapshots.as_slice();
 		let software_lists_paths = prefs_paths.software_lists.as_slice();
 		let cfg_path: &[String] = prefs_paths.cfg.as_slice();
 		let nvram_path = prefs_paths.nvram.as_slice();
+		let ini_paths = prefs_paths.ini.as_slice();
 		let result: MameArgumentsSource<'a> = Self {
 			windowing,
 			roms_paths,
 			mame_executable_path,
+			variables,
 			samples_paths,
+			artwork_paths,
+			cheats_paths,
 			plugins_paths,
+			snapshots_paths,
 			software_lists_paths,
 			cfg_path,
 			nvram_path,
+			ini_paths,
 		};
 		Ok(result)
 	}
 
 	pub fn preflight(&self) -> Result<()> {
-		let results = preflight_checks(self.mame_executable_path, self.plugins_paths, current_exe_lookup);
+		let results = preflight_checks(
+			self.mame_executable_path,
+			self.variables,
+			self.plugins_paths,
+			current_exe_lookup,
+		);
 		if results.is_empty() {
 			Ok(())
 		} else {
@@ -118,16 +144,29 @@ fn platform_specific_args() -> Vec<&'static str> {
 	}
 }
 
+/// Fixes `PreflightProblem::MameExecutableIsNotExecutable` by setting the executable bits
+#[cfg(unix)]
+pub fn make_executable(path: impl AsRef<Path>) -> Result<()> {
+	use std::os::unix::fs::PermissionsExt;
+
+	let mut permissions = metadata(&path)?.permissions();
+	permissions.set_mode(permissions.mode() | 0o111);
+	set_permissions(&path, permissions)?;
+	Ok(())
+}
+
 /// FIXME
 pub fn preflight_checks_public(
 	mame_executable_path: Option<&str>,
+	variables: &BTreeMap<String, String>,
 	plugins_paths: &[impl AsRef<str>],
 ) -> Vec<PreflightProblem> {
-	preflight_checks(mame_executable_path, plugins_paths, current_exe_lookup)
+	preflight_checks(mame_executable_path, variables, plugins_paths, current_exe_lookup)
 }
 
 pub fn preflight_checks(
 	mame_executable_path: Option<&str>,
+	variables: &BTreeMap<String, String>,
 	plugins_paths: &[impl AsRef<str>],
 	current_exe_lookup: impl Fn() -> Option<PathBuf>,
 ) -> Vec<PreflightProblem> {
@@ -152,7 +191,7 @@ pub fn preflight_checks(
 		.flat_map(|path| {
 			let path = path.as_ref();
 			if let Some((var_name, rest)) = get_var_name(path) {
-				let var_value = env_lookup(var_name, mame_executable_path, &current_exe_lookup);
+				let var_value = env_lookup(var_name, mame_executable_path, variables, &current_exe_lookup);
 				let result = var_value.map(|x| PathBuf::from(format!("{x}{rest}")));
 				result.map(Cow::Owned)
 			} else {
@@ -202,16 +241,24 @@ fn mame_args_from_source(
 ) -> MameArguments {
 	// lambda that looks up variables
 	let mame_executable_path = source.mame_executable_path;
-	let lookup_var = move |var_name: &str| env_lookup(var_name, mame_executable_path, &current_exe_lookup);
+	let variables = source.variables;
+	let lookup_var = move |var_name: &str| env_lookup(var_name, mame_executable_path, variables, &current_exe_lookup);
+
+	// MAME only takes a single snapshot directory; the first one is where new snapshots go
+	let snapshot_directory = source.snapshots_paths.get(..1).unwrap_or_default();
 
-	// convert all path vec's to the appropriate MAME arguments
+	// convert all path vec's to the appropriate MAME arguments, so that our paths take precedence over `mame.ini`
 	let paths = [
 		("-rompath", source.roms_paths),
 		("-samplepath", source.samples_paths),
+		("-artpath", source.artwork_paths),
+		("-cheatpath", source.cheats_paths),
 		("-pluginspath", source.plugins_paths),
 		("-hashpath", source.software_lists_paths),
+		("-snapshot_directory", snapshot_directory),
 		("-cfg_directory", source.cfg_path),
 		("-nvram_directory", source.nvram_path),
+		("-inipath", source.ini_paths),
 	]
 	.into_iter()
 	.filter(|(_, paths)| !paths.is_empty())
@@ -225,6 +272,12 @@ fn mame_args_from_source(
 	let windowing_args = match source.windowing {
 		MameWindowing::Attached(window) => vec!["-attach_window".into(), Cow::Owned(window.to_string())],
 		MameWindowing::Windowed => vec!["-w".into(), "-nomax".into()],
+		MameWindowing::WindowedSized(width, height) => vec![
+			"-w".into(),
+			"-nomax".into(),
+			"-resolution".into(),
+			Cow::Owned(format!("{width}x{height}")),
+		],
 		MameWindowing::WindowedMaximized => vec!["-w".into(), "-max".into()],
 		MameWindowing::Fullscreen => vec!["-now".into()],
 	};
@@ -265,6 +318,29 @@ fn get_full_path(paths: &[impl AsRef<str>], lookup_var: impl Fn(&str) -> Option<
 		.join(";")
 }
 
+/// Expands a path that might be prefixed with a variable (e.g. - `$(MAMEPATH)`, or a user-defined variable)
+pub fn expand_path(path: &str, prefs_paths: &PrefsPaths) -> Option<String> {
+	if let Some((var_name, rest)) = get_var_name(path) {
+		let mame_executable_path = prefs_paths.mame_executable.as_deref();
+		let var_value = env_lookup(
+			var_name,
+			mame_executable_path,
+			&prefs_paths.variables,
+			current_exe_lookup,
+		);
+		var_value.map(|x| format!("{x}{rest}"))
+	} else {
+		Some(path.to_string())
+	}
+}
+
+/// Whether `name` can be used for a user-defined path variable
+pub fn is_valid_variable_name(name: &str) -> bool {
+	!name.is_empty()
+		&& name.chars().all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+		&& !BUILTIN_VARIABLES.contains(&name)
+}
+
 fn get_var_name(s: &str) -> Option<(&str, &str)> {
 	let s = s.strip_prefix("$(")?;
 	let idx = s.find(')')?;
@@ -274,6 +350,27 @@ fn get_var_name(s: &str) -> Option<(&str, &str)> {
 }
 
 fn env_lookup(
+	var_name: &str,
+	mame_executable_path: Option<&str>,
+	variables: &BTreeMap<String, String>,
+	current_exe_lookup: impl Fn() -> Option<PathBuf>,
+) -> Option<String> {
+	// user-defined variables can themselves start with a builtin variable (e.g. - `$(MAMEPATH)/roms`)
+	if let Some(value) = variables
+		.get(var_name)
+		.filter(|_| !BUILTIN_VARIABLES.contains(&var_name))
+	{
+		return match get_var_name(value) {
+			Some((var_name, rest)) => {
+				builtin_lookup(var_name, mame_executable_path, current_exe_lookup).map(|x| format!("{x}{rest}"))
+			}
+			None => Some(value.clone()),
+		};
+	}
+	builtin_lookup(var_name, mame_executable_path, current_exe_lookup)
+}
+
+fn builtin_lookup(
 	var_name: &str,
 	mame_executable_path: Option<&str>,
 	current_exe_lookup: impl Fn() -> Option<PathBuf>,
@@ -288,6 +385,9 @@ fn env_lookup(
 
 #[cfg(test)]
 mod test {
+	use std::collections::BTreeMap;
+	use std::path::PathBuf;
+
 	use test_case::test_case;
 
 	use crate::runtime::MameWindowing;
@@ -319,21 +419,58 @@ mod test {
 		assert_eq!(expected, actual)
 	}
 
+	#[test_case(0, "MAMEPATH", Some("/mydir/mame"))]
+	#[test_case(1, "BLETCHMAMEPATH", Some("/bmdir"))]
+	#[test_case(2, "ROMROOT", Some("/roms"))]
+	#[test_case(3, "MAMEROMS", Some("/mydir/mame/roms"))]
+	#[test_case(4, "NESTED", None)]
+	#[test_case(5, "INVALID", None)]
+	pub fn env_lookup(_index: usize, var_name: &str, expected: Option<&str>) {
+		let variables = [
+			("ROMROOT", "/roms"),
+			("MAMEROMS", "$(MAMEPATH)/roms"),
+			("NESTED", "$(ROMROOT)/nes"),
+			("MAMEPATH", "/ignored"),
+		]
+		.into_iter()
+		.map(|(name, value)| (name.to_string(), value.to_string()))
+		.collect::<BTreeMap<_, _>>();
+		let actual = super::env_lookup(var_name, Some("/mydir/mame/mame.exe"), &variables, || {
+			Some(PathBuf::from("/bmdir/bletchmame"))
+		});
+		assert_eq!(expected, actual.as_deref());
+	}
+
+	#[test_case(0, "ROMROOT", true)]
+	#[test_case(1, "rom_root2", true)]
+	#[test_case(2, "", false)]
+	#[test_case(3, "ROM ROOT", false)]
+	#[test_case(4, "MAMEPATH", false)]
+	pub fn is_valid_variable_name(_index: usize, name: &str, expected: bool) {
+		let actual = super::is_valid_variable_name(name);
+		assert_eq!(expected, actual);
+	}
+
 	#[test]
 	pub fn mame_args_from_source() {
 		let windowing = MameWindowing::Attached("1234".to_string());
 		let source = MameArgumentsSource {
 			windowing: &windowing,
 			mame_executable_path: Some("/mydir/mame/mame.exe"),
+			variables: &BTreeMap::new(),
 			roms_paths: &["/mydir/mame/roms1".to_string(), "/mydir/mame/roms2".to_string()],
 			samples_paths: &["/mydir/mame/samples1".to_string(), "/mydir/mame/samples2".to_string()],
+			artwork_paths: &["/mydir/mame/artwork".to_string()],
+			cheats_paths: &["$(MAMEPATH)/cheat".to_string()],
 			plugins_paths: &[
 				"$(MAMEPATH)/plugins".to_string(),
 				"$(BLETCHMAMEPATH)/plugins".to_string(),
 			],
+			snapshots_paths: &["/mydir/mame/snap".to_string(), "/mydir/snap".to_string()],
 			software_lists_paths: &["/mydir/mame/hash".to_string()],
 			cfg_path: &["/mydir/mame/cfg".to_string()],
 			nvram_path: &["/mydir/mame/nvram".to_string()],
+			ini_paths: &["/mydir/mame/ini".to_string(), "/mydir/ini".to_string()],
 		};
 		let result = super::mame_args_from_source(source, || Some(std::path::PathBuf::from("/bmdir/bletchmame")));
 
@@ -348,21 +485,55 @@ mod test {
 			find_arg(&result.args, "-attach_window"),
 			find_arg(&result.args, "-rompath"),
 			find_arg(&result.args, "-samplepath"),
+			find_arg(&result.args, "-artpath"),
+			find_arg(&result.args, "-cheatpath"),
 			find_arg(&result.args, "-pluginspath"),
 			find_arg(&result.args, "-hashpath"),
+			find_arg(&result.args, "-snapshot_directory"),
 			find_arg(&result.args, "-cfg_directory"),
 			find_arg(&result.args, "-nvram_directory"),
+			find_arg(&result.args, "-inipath"),
 		);
 		let expected = (
 			"/mydir/mame/mame.exe",
 			Some("1234"),
 			Some("/mydir/mame/roms1;/mydir/mame/roms2"),
 			Some("/mydir/mame/samples1;/mydir/mame/samples2"),
+			Some("/mydir/mame/artwork"),
+			Some("/mydir/mame/cheat"),
 			Some("/mydir/mame/plugins;/bmdir/plugins"),
 			Some("/mydir/mame/hash"),
+			Some("/mydir/mame/snap"),
 			Some("/mydir/mame/cfg"),
 			Some("/mydir/mame/nvram"),
+			Some("/mydir/mame/ini;/mydir/ini"),
 		);
 		assert_eq!(expected, actual);
 	}
+
+	#[test]
+	pub fn windowed_sized() {
+		let windowing = MameWindowing::WindowedSized(640, 480);
+		let source = MameArgumentsSource {
+			windowing: &windowing,
+			mame_executable_path: Some("/mydir/mame/mame.exe"),
+			variables: &BTreeMap::new(),
+			roms_paths: &[],
+			samples_paths: &[],
+			artwork_paths: &[],
+			cheats_paths: &[],
+			plugins_paths: &[],
+			snapshots_paths: &[],
+			software_lists_paths: &[],
+			cfg_path: &[],
+			nvram_path: &[],
+			ini_paths: &[],
+		};
+		let result = super::mame_args_from_source(source, || None);
+
+		let position = result.args.iter().position(|x| x == "-resolution");
+		let actual = position.map(|idx| result.args[idx + 1].as_ref());
+		assert_eq!(Some("640x480"), actual);
+		assert!(result.args.iter().any(|x| x == "-nomax"));
+	}
 }
@@ -0,0 +1,230 @@
+//! Rendering of `MameCommand`s to the line oriented protocol spoken by the `worker_ui` plugin
+//!
+//! The wire form of every command is pinned by golden transcripts in `test_data`, as is which commands are available
+//! at each MAME version gate; changes to either show up as test failures rather than as confused MAME sessions.
+use std::borrow::Cow;
+
+use itertools::Itertools;
+
+use crate::runtime::MameCommand;
+use crate::version::MameVersion;
+
+impl MameCommand<'_> {
+	/// The oldest MAME that understands this command, if not all supported versions do
+	pub fn minimum_mame_version(&self) -> Option<MameVersion> {
+		match self {
+			MameCommand::ClassicMenu => Some(MameVersion::new(0, 274)),
+			_ => None,
+		}
+	}
+}
+
+pub fn command_text(command: &MameCommand<'_>) -> Cow<'static, str> {
+	match command {
+		MameCommand::Exit => "EXIT".into(),
+		MameCommand::Start {
+			machine_name,
+			initial_loads,
+		} => pairs_command_text(&["START", machine_name], initial_loads),
+		MameCommand::Stop => "STOP".into(),
+		MameCommand::SoftReset => "SOFT_RESET".into(),
+		MameCommand::HardReset => "HARD_RESET".into(),
+		MameCommand::Pause => "PAUSE".into(),
+		MameCommand::Resume => "RESUME".into(),
+		MameCommand::Ping => "PING".into(),
+		MameCommand::ClassicMenu => "CLASSIC_MENU".into(),
+		MameCommand::Throttled(throttled) => format!("THROTTLED {}", bool_str(*throttled)).into(),
+		MameCommand::ThrottleRate(throttle) => format!("THROTTLE_RATE {}", throttle).into(),
+		MameCommand::SetAttenuation(attenuation) => format!("SET_ATTENUATION {}", attenuation).into(),
+		MameCommand::LoadImage(loads) => pairs_command_text(&["LOAD"], loads),
+		MameCommand::UnloadImage(tag) => format!("UNLOAD {}", tag).into(),
+		MameCommand::ChangeSlots(changes) => pairs_command_text(&["CHANGE_SLOTS"], changes),
+		MameCommand::RecordInput(path) => pairs_command_text(&["INPUT_FILE"], &[("RECORD", path)]),
+		MameCommand::PlaybackInput(path) => pairs_command_text(&["INPUT_FILE"], &[("PLAYBACK", path)]),
+		MameCommand::SaveSnapshot(path) => pairs_command_text(&["SAVE_SNAPSHOT"], &[("0", path)]),
+		MameCommand::StateLoad(path) => format!("STATE_LOAD {}", quoted_arg(path)).into(),
+		MameCommand::Paste(text) => format!("PASTE {}", paste_arg(text)).into(),
+		MameCommand::BeginRecording(path, format) => {
+			format!("BEGIN_RECORDING {} {}", quoted_arg(path), format.extension()).into()
+		}
+		MameCommand::EndRecording => "END_RECORDING".into(),
+		MameCommand::RecordAudio(path) => format!("WAV_FILE {}", quoted_arg(path)).into(),
+		MameCommand::CommLink(netplay) => format!(
+			"COMM_LINK {} {} {} {} {}",
+			quoted_arg(&netplay.local_host),
+			netplay.local_port,
+			quoted_arg(&netplay.remote_host),
+			netplay.remote_port,
+			bool_str(netplay.frame_sync)
+		)
+		.into(),
+		MameCommand::Bios(bios) => format!("BIOS {}", quoted_arg(bios)).into(),
+	}
+}
+
+fn bool_str(b: bool) -> &'static str {
+	if b {
+		"true"
+	} else {
+		"false"
+	}
+}
+
+fn pairs_command_text(base: &[&str], args: &[(&str, &str)]) -> Cow<'static, str> {
+	base.iter()
+		.copied()
+		.map(Cow::Borrowed)
+		.chain(args.iter().flat_map(|(name, value)| {
+			let name = Cow::Borrowed(*name);
+			let value = quoted_arg(value);
+			[name, value]
+		}))
+		.join(" ")
+		.into()
+}
+
+fn quoted_arg(value: &str) -> Cow<'_, str> {
+	if value.contains(' ') {
+		Cow::Owned(format!("\"{}\"", value))
+	} else {
+		Cow::Borrowed(value)
+	}
+}
+
+/// Quotes text to be pasted; newlines are escaped because commands are delimited by them
+fn paste_arg(text: &str) -> String {
+	let text = text.replace("\r\n", "\n").replace('\n', "\\n");
+	if text.contains('"') {
+		format!("'{}'", text)
+	} else {
+		format!("\"{}\"", text)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::collections::HashSet;
+	use std::iter::once;
+	use std::sync::LazyLock;
+
+	use itertools::Itertools;
+	use strum::EnumCount;
+	use test_case::test_case;
+
+	use crate::prefs::MovieFormat;
+	use crate::prefs::PrefsNetplay;
+	use crate::runtime::MameCommand;
+	use crate::version::MameVersion;
+
+	#[test_case(0, MameCommand::Exit, "EXIT")]
+	#[test_case(1, MameCommand::Start { machine_name: "coco2b", initial_loads: &[("ext:fdc:wd17xx:0", "foo.dsk")]}, "START coco2b ext:fdc:wd17xx:0 foo.dsk")]
+	#[test_case(2, MameCommand::LoadImage(&[("ext:fdc:wd17xx:0", "foo bar.dsk")]), "LOAD ext:fdc:wd17xx:0 \"foo bar.dsk\"")]
+	#[test_case(3, MameCommand::PlaybackInput("/my inp/coco2b.inp"), "INPUT_FILE PLAYBACK \"/my inp/coco2b.inp\"")]
+	#[test_case(4, MameCommand::SaveSnapshot("/my snap/coco2b.png"), "SAVE_SNAPSHOT 0 \"/my snap/coco2b.png\"")]
+	#[test_case(
+		5,
+		MameCommand::BeginRecording("/my snap/a.mng", MovieFormat::Mng),
+		"BEGIN_RECORDING \"/my snap/a.mng\" mng"
+	)]
+	#[test_case(6, MameCommand::RecordAudio("/snap/coco2b.wav"), "WAV_FILE /snap/coco2b.wav")]
+	#[test_case(7, MameCommand::CommLink(&PrefsNetplay::default()), "COMM_LINK 0.0.0.0 15112 127.0.0.1 15112 false")]
+	#[test_case(8, MameCommand::StateLoad("/my sta/coco2b.sta"), "STATE_LOAD \"/my sta/coco2b.sta\"")]
+	#[test_case(9, MameCommand::Paste("PRINT \"HI\"\n"), "PASTE 'PRINT \"HI\"\\n'")]
+	#[test_case(10, MameCommand::Paste("RUN"), "PASTE \"RUN\"")]
+	#[test_case(11, MameCommand::Bios("b11e10"), "BIOS b11e10")]
+	fn command_test(_index: usize, command: MameCommand<'_>, expected: &str) {
+		let actual = super::command_text(&command);
+		assert_eq!(expected, actual);
+	}
+
+	/// One of each command; the transcripts below have a line for each of these
+	fn sample_commands() -> Vec<MameCommand<'static>> {
+		static NETPLAY: LazyLock<PrefsNetplay> = LazyLock::new(PrefsNetplay::default);
+		vec![
+			MameCommand::Exit,
+			MameCommand::Start {
+				machine_name: "coco2b",
+				initial_loads: &[("ext:fdc:wd17xx:0", "foo.dsk")],
+			},
+			MameCommand::Stop,
+			MameCommand::SoftReset,
+			MameCommand::HardReset,
+			MameCommand::Pause,
+			MameCommand::Resume,
+			MameCommand::Ping,
+			MameCommand::ClassicMenu,
+			MameCommand::Throttled(false),
+			MameCommand::ThrottleRate(0.5),
+			MameCommand::SetAttenuation(-12),
+			MameCommand::LoadImage(&[("cart", "/my roms/pacman.ccc")]),
+			MameCommand::UnloadImage("cart"),
+			MameCommand::ChangeSlots(&[("ext", "multi")]),
+			MameCommand::RecordInput("/inp/coco2b.inp"),
+			MameCommand::PlaybackInput("/my inp/coco2b.inp"),
+			MameCommand::SaveSnapshot("/snap/coco2b.png"),
+			MameCommand::StateLoad("/sta/coco2b.sta"),
+			MameCommand::Paste("10 PRINT \"HI\"\n20 GOTO 10\n"),
+			MameCommand::BeginRecording("/snap/coco2b.avi", MovieFormat::Avi),
+			MameCommand::EndRecording,
+			MameCommand::RecordAudio("/snap/coco2b.wav"),
+			MameCommand::CommLink(&NETPLAY),
+			MameCommand::Bios("b11e10"),
+		]
+	}
+
+	/// The MAME versions that make up the columns of the compatibility matrix; every gate must appear here
+	const MATRIX_VERSIONS: &[(u16, u16)] = &[(0, 273), (0, 274)];
+
+	#[test]
+	fn samples_cover_every_command() {
+		let names = sample_commands()
+			.iter()
+			.map(<&'static str>::from)
+			.collect::<HashSet<_>>();
+		assert_eq!(MameCommand::COUNT, names.len());
+	}
+
+	#[test]
+	fn transcript() {
+		let actual = sample_commands()
+			.iter()
+			.map(|command| format!("{} => {}", <&'static str>::from(command), super::command_text(command)))
+			.join("\n");
+		let expected = include_str!("test_data/command_transcript.txt");
+		assert_eq!(expected.replace('\r', "").trim_end(), actual);
+	}
+
+	#[test]
+	fn compatibility_matrix() {
+		let versions = MATRIX_VERSIONS
+			.iter()
+			.map(|&(major, minor)| MameVersion::new(major, minor))
+			.collect::<Vec<_>>();
+		let commands = sample_commands();
+		for minimum in commands.iter().filter_map(MameCommand::minimum_mame_version) {
+			assert!(versions.contains(&minimum), "{minimum} is not in MATRIX_VERSIONS");
+		}
+
+		let header = MATRIX_VERSIONS
+			.iter()
+			.map(|(major, minor)| format!("{major}.{minor}"))
+			.join(" ");
+		let rows = commands.iter().map(|command| {
+			let available = versions
+				.iter()
+				.map(|version| {
+					let is_available = command.minimum_mame_version().is_none_or(|minimum| version >= &minimum);
+					if is_available {
+						"yes"
+					} else {
+						"no"
+					}
+				})
+				.join(" ");
+			format!("{} {}", <&'static str>::from(command), available)
+		});
+		let actual = once(header).chain(rows).join("\n");
+		let expected = include_str!("test_data/command_compatibility.txt");
+		assert_eq!(expected.replace('\r', "").trim_end(), actual);
+	}
+}
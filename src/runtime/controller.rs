@@ -7,7 +7,10 @@ use tracing::Level;
 use crate::debugstr::DebugString;
 use crate::prefs::PrefsPaths;
 use crate::runtime::args::MameArgumentsSource;
+#[cfg(feature = "test-harness")]
+use crate::runtime::mock::MockMame;
 use crate::runtime::session::MameSession;
+use crate::runtime::sessionlog::SessionLog;
 use crate::runtime::MameCommand;
 use crate::runtime::MameEvent;
 use crate::runtime::MameStderr;
@@ -19,14 +22,29 @@ pub struct MameController {
 	session: RefCell<Option<MameSession>>,
 	event_callback: RefCell<Arc<dyn Fn(MameEvent) + Send + Sync + 'static>>,
 	mame_stderr: MameStderr,
+	log: SessionLog,
+	#[cfg(feature = "test-harness")]
+	mock: Option<Arc<MockMame>>,
 }
 
 impl MameController {
-	pub fn new(mame_stderr: MameStderr) -> Self {
+	pub fn new(mame_stderr: MameStderr, log: SessionLog) -> Self {
 		Self {
 			session: RefCell::new(None),
 			event_callback: RefCell::new(Arc::new(|_| {})),
 			mame_stderr,
+			log,
+			#[cfg(feature = "test-harness")]
+			mock: None,
+		}
+	}
+
+	/// Creates a controller whose sessions talk to a mock instead of launching MAME
+	#[cfg(feature = "test-harness")]
+	pub fn new_mock(mock: Arc<MockMame>, log: SessionLog) -> Self {
+		Self {
+			mock: Some(mock),
+			..Self::new(MameStderr::Inherit, log)
 		}
 	}
 
@@ -65,12 +83,24 @@ impl MameController {
 			session.shutdown();
 		}
 
+		// sessions against a mock only need to know whether they should be running
+		#[cfg(feature = "test-harness")]
+		if let Some(mock) = &self.mock {
+			if prefs_paths.is_some() {
+				let event_callback = self.event_callback.borrow().clone();
+				let event_callback = move |evt| event_callback(evt);
+				let session = MameSession::new_mock(mock.clone(), event_callback);
+				self.session.replace(Some(session));
+			}
+			return;
+		}
+
 		// are we starting up a new session?
 		if let Some(mame_args) = mame_args {
 			// we are - start the session
 			let event_callback = self.event_callback.borrow().clone();
 			let event_callback = move |evt| event_callback(evt);
-			let session = MameSession::new(mame_args.into(), event_callback, self.mame_stderr);
+			let session = MameSession::new(mame_args.into(), event_callback, self.mame_stderr, self.log.clone());
 			self.session.replace(Some(session));
 		}
 	}
@@ -0,0 +1,97 @@
+//! A stand-in for MAME that answers commands with canned status XML, so that the UI can be driven without MAME
+use std::borrow::Cow;
+use std::sync::Mutex;
+
+use anyhow::Result;
+
+use crate::runtime::MameEvent;
+use crate::status::Update;
+
+pub struct MockMame {
+	initial_status: Vec<u8>,
+	responses: Vec<(String, Vec<u8>)>,
+	commands: Mutex<Vec<String>>,
+}
+
+impl MockMame {
+	/// Creates a mock that reports `initial_status` when the session starts
+	pub fn new(initial_status: impl Into<Vec<u8>>) -> Self {
+		Self {
+			initial_status: initial_status.into(),
+			responses: Vec::new(),
+			commands: Mutex::new(Vec::new()),
+		}
+	}
+
+	/// Reports `status` whenever a command starting with `verb` (e.g. - "START") is issued
+	pub fn respond_to(mut self, verb: &str, status: impl Into<Vec<u8>>) -> Self {
+		self.responses.push((verb.to_string(), status.into()));
+		self
+	}
+
+	/// The text of all commands issued so far
+	pub fn commands(&self) -> Vec<String> {
+		self.commands.lock().unwrap().clone()
+	}
+
+	/// Plays the role of MAME in a session; `next_command` blocks until the front end issues a command, and
+	/// returns its text and whether it is an exit command
+	pub fn interact(
+		&self,
+		mut next_command: impl FnMut() -> (Cow<'static, str>, bool),
+		event_callback: &impl Fn(MameEvent),
+	) -> Result<()> {
+		event_callback(MameEvent::StatusUpdate(Update::parse(self.initial_status.as_slice())?));
+		loop {
+			let (text, is_exit) = next_command();
+			self.commands.lock().unwrap().push(text.to_string());
+			if is_exit {
+				break Ok(());
+			}
+
+			let verb = text.split_whitespace().next().unwrap_or_default();
+			let status = self
+				.responses
+				.iter()
+				.find(|(x, _)| x.eq_ignore_ascii_case(verb))
+				.map(|(_, status)| status.as_slice());
+			if let Some(status) = status {
+				event_callback(MameEvent::StatusUpdate(Update::parse(status)?));
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::cell::RefCell;
+
+	use crate::runtime::MameEvent;
+	use crate::status::Status;
+
+	use super::MockMame;
+
+	#[test]
+	pub fn interact() {
+		let mock = MockMame::new(include_str!("../status/test_data/status_mame0270_1.xml")).respond_to(
+			"START",
+			include_str!("../status/test_data/status_mame0270_coco2b_1.xml"),
+		);
+		let mut commands = ["START coco2b", "PING", "EXIT"].into_iter();
+		let next_command = || {
+			let text = commands.next().unwrap();
+			(text.into(), text == "EXIT")
+		};
+		let machine_names = RefCell::new(Vec::new());
+		let event_callback = |event| {
+			if let MameEvent::StatusUpdate(update) = event {
+				let machine_name = Status::default().merge(update).running.map(|x| x.machine_name);
+				machine_names.borrow_mut().push(machine_name);
+			}
+		};
+		mock.interact(next_command, &event_callback).unwrap();
+
+		assert_eq!(vec![None, Some("coco2b".to_string())], machine_names.into_inner());
+		assert_eq!(vec!["START coco2b", "PING", "EXIT"], mock.commands());
+	}
+}
@@ -1,23 +1,36 @@
 pub mod args;
+mod command;
 pub mod controller;
+#[cfg(feature = "test-harness")]
+pub mod mock;
+pub mod plugin;
 mod session;
+pub mod sessionlog;
+pub mod sessions;
+
+use std::process::ExitStatus;
 
 use anyhow::Error;
+use strum::EnumCount;
 use strum::EnumString;
+use strum::IntoStaticStr;
 
+use crate::prefs::MovieFormat;
+use crate::prefs::PrefsNetplay;
 use crate::status::Update;
 
 #[derive(Debug)]
 pub enum MameWindowing {
 	Attached(String),
 	Windowed,
+	WindowedSized(u32, u32),
 	#[allow(dead_code)]
 	WindowedMaximized,
 	#[allow(dead_code)]
 	Fullscreen,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, EnumCount, IntoStaticStr)]
 pub enum MameCommand<'a> {
 	Exit,
 	Start {
@@ -37,12 +50,23 @@ pub enum MameCommand<'a> {
 	LoadImage(&'a [(&'a str, &'a str)]),
 	UnloadImage(&'a str),
 	ChangeSlots(&'a [(&'a str, &'a str)]),
+	RecordInput(&'a str),
+	PlaybackInput(&'a str),
+	SaveSnapshot(&'a str),
+	StateLoad(&'a str),
+	Paste(&'a str),
+	BeginRecording(&'a str, MovieFormat),
+	EndRecording,
+	RecordAudio(&'a str),
+	CommLink(&'a PrefsNetplay),
+	Bios(&'a str),
 }
 
 #[derive(Debug)]
 pub enum MameEvent {
 	SessionStarted,
-	SessionEnded,
+	/// The session is over; carries MAME's exit status when it ran as a process and exited cleanly
+	SessionEnded(Option<ExitStatus>),
 	Error(Error),
 	StatusUpdate(Update),
 }
@@ -0,0 +1,80 @@
+//! Management of the `worker_ui` Lua plugin that BletchMAME uses to communicate with MAME
+//!
+//! A copy of the plugin is bundled into the executable, and can be installed into a managed plugins directory
+//! under the preferences path.  Installed copies are rewritten whenever they differ from the bundled copy, so
+//! that the plugin always matches what this build of BletchMAME expects.
+use std::fs::create_dir_all;
+use std::fs::read;
+use std::fs::write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use tracing::event;
+use tracing::Level;
+
+const LOG: Level = Level::DEBUG;
+
+const PLUGIN_FILES: &[(&str, &[u8])] = &[
+	("init.lua", include_bytes!("../../plugins/worker_ui/init.lua")),
+	("plugin.json", include_bytes!("../../plugins/worker_ui/plugin.json")),
+];
+
+/// The plugins directory managed by BletchMAME
+pub fn managed_plugins_path(prefs_path: &Path) -> PathBuf {
+	prefs_path.join("plugins")
+}
+
+/// Has the plugin ever been installed into the managed plugins directory?
+pub fn is_worker_ui_installed(prefs_path: &Path) -> bool {
+	managed_plugins_path(prefs_path)
+		.join("worker_ui")
+		.join("plugin.json")
+		.is_file()
+}
+
+/// Installs the plugin into the managed plugins directory, or brings an existing installation up to date;
+/// returns whether anything needed to be written
+pub fn install_worker_ui(prefs_path: &Path) -> Result<bool> {
+	let dir = managed_plugins_path(prefs_path).join("worker_ui");
+	create_dir_all(&dir)?;
+
+	let mut changed = false;
+	for (filename, contents) in PLUGIN_FILES {
+		let path = dir.join(filename);
+		if read(&path).ok().as_deref() != Some(*contents) {
+			write(&path, contents)?;
+			changed = true;
+		}
+	}
+
+	event!(LOG, "install_worker_ui(): dir={:?} changed={}", dir, changed);
+	Ok(changed)
+}
+
+#[cfg(test)]
+mod test {
+	use std::fs::write;
+
+	use tempdir::TempDir;
+
+	#[test]
+	pub fn install_worker_ui() {
+		let tmp_dir = TempDir::new("temp").unwrap();
+		assert!(!super::is_worker_ui_installed(tmp_dir.path()));
+
+		// fresh install
+		assert!(super::install_worker_ui(tmp_dir.path()).unwrap());
+		assert!(super::is_worker_ui_installed(tmp_dir.path()));
+
+		// already up to date
+		assert!(!super::install_worker_ui(tmp_dir.path()).unwrap());
+
+		// stale install
+		let init_lua = super::managed_plugins_path(tmp_dir.path())
+			.join("worker_ui")
+			.join("init.lua");
+		write(init_lua, "-- stale").unwrap();
+		assert!(super::install_worker_ui(tmp_dir.path()).unwrap());
+	}
+}
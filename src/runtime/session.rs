@@ -7,6 +7,7 @@ use std::io::Read;
 use std::io::Write;
 use std::process::Child;
 use std::process::Command;
+use std::process::ExitStatus;
 use std::process::Stdio;
 use std::sync::atomic::AtomicU64;
 use std::sync::atomic::Ordering;
@@ -17,12 +18,16 @@ use std::thread::JoinHandle;
 use anyhow::Error;
 use anyhow::Result;
 use blockingqueue::BlockingQueue;
-use itertools::Itertools;
 use tracing::event;
 use tracing::Level;
 
 use crate::platform::CommandExt;
 use crate::runtime::args::MameArguments;
+use crate::runtime::command::command_text;
+#[cfg(feature = "test-harness")]
+use crate::runtime::mock::MockMame;
+use crate::runtime::sessionlog::SessionLog;
+use crate::runtime::sessionlog::SessionLogSource;
 use crate::runtime::MameCommand;
 use crate::runtime::MameEvent;
 use crate::runtime::MameStderr;
@@ -63,18 +68,14 @@ impl MameSession {
 		mame_args: MameArguments,
 		event_callback: impl Fn(MameEvent) + Send + 'static,
 		mame_stderr: MameStderr,
+		log: SessionLog,
 	) -> Self {
 		// prepare communication with the child
-		let comm = SessionCommunication {
-			message_queue: BlockingQueue::new(),
-			mame_pid: (!0).into(),
-			message_queue_len: 0.into(),
-		};
-		let comm = Arc::new(comm);
+		let comm = Arc::new(SessionCommunication::new());
 
 		// and start the thread
 		let comm_clone = comm.clone();
-		let handle = spawn(move || thread_proc(&mame_args, &comm_clone, event_callback, mame_stderr));
+		let handle = spawn(move || thread_proc(&mame_args, &comm_clone, event_callback, mame_stderr, &log));
 
 		// and set ourselves up
 		Self {
@@ -84,6 +85,31 @@ impl MameSession {
 		}
 	}
 
+	/// Creates a session that talks to a mock instead of launching MAME
+	#[cfg(feature = "test-harness")]
+	pub fn new_mock(mock: Arc<MockMame>, event_callback: impl Fn(MameEvent) + Send + 'static) -> Self {
+		let comm = Arc::new(SessionCommunication::new());
+		let comm_clone = comm.clone();
+		let handle = spawn(move || {
+			let next_command = || {
+				let command = comm_clone.message_queue.pop();
+				comm_clone.message_queue_len.fetch_sub(1, Ordering::Relaxed);
+				(command.text, command.is_exit)
+			};
+			event_callback(MameEvent::SessionStarted);
+			if let Err(e) = mock.interact(next_command, &event_callback) {
+				event_callback(MameEvent::Error(e));
+			}
+			event_callback(MameEvent::SessionEnded(None));
+		});
+
+		Self {
+			handle,
+			comm,
+			exit_issued: Cell::new(false),
+		}
+	}
+
 	pub fn has_pending_commands(&self) -> bool {
 		self.comm.message_queue_len.load(Ordering::Relaxed) > 0
 	}
@@ -103,6 +129,17 @@ impl MameSession {
 		self.handle.join().unwrap()
 	}
 }
+
+impl SessionCommunication {
+	fn new() -> Self {
+		Self {
+			message_queue: BlockingQueue::new(),
+			mame_pid: (!0).into(),
+			message_queue_len: 0.into(),
+		}
+	}
+}
+
 impl From<MameCommand<'_>> for ProcessedCommand {
 	fn from(value: MameCommand<'_>) -> Self {
 		let text = command_text(&value);
@@ -116,12 +153,17 @@ fn thread_proc(
 	comm: &SessionCommunication,
 	event_callback: impl Fn(MameEvent),
 	mame_stderr: MameStderr,
+	log: &SessionLog,
 ) {
 	event_callback(MameEvent::SessionStarted);
-	if let Err(e) = execute_mame(mame_args, comm, &event_callback, mame_stderr) {
-		event_callback(MameEvent::Error(e));
-	}
-	event_callback(MameEvent::SessionEnded);
+	let exit_status = match execute_mame(mame_args, comm, &event_callback, mame_stderr, log) {
+		Ok(exit_status) => Some(exit_status),
+		Err(e) => {
+			event_callback(MameEvent::Error(e));
+			None
+		}
+	};
+	event_callback(MameEvent::SessionEnded(exit_status));
 }
 
 fn execute_mame(
@@ -129,7 +171,8 @@ fn execute_mame(
 	comm: &SessionCommunication,
 	event_callback: &impl Fn(MameEvent),
 	mame_stderr: MameStderr,
-) -> Result<()> {
+	log: &SessionLog,
+) -> Result<ExitStatus> {
 	// launch MAME, launch!
 	event!(LOG, "execute_mame(): Launching MAME: mame_args={mame_args:?}");
 	let args = mame_args.args.iter().map(|x| x.as_ref());
@@ -150,7 +193,7 @@ fn execute_mame(
 	comm.mame_pid.store(child.id().into(), Ordering::Relaxed);
 
 	// interact with MAME, do our thing
-	let mame_result = interact_with_mame(&mut child, comm, &event_callback);
+	let mame_result = interact_with_mame(&mut child, comm, &event_callback, log);
 
 	// await the exit status
 	let exit_status = child.wait();
@@ -158,24 +201,29 @@ fn execute_mame(
 
 	// and we're done
 	comm.mame_pid.store(!0, Ordering::Relaxed);
-	mame_result
+	mame_result?;
+	Ok(exit_status?)
 }
 
 fn interact_with_mame(
 	child: &mut Child,
 	comm: &SessionCommunication,
 	event_callback: &impl Fn(MameEvent),
+	log: &SessionLog,
 ) -> Result<()> {
 	// set up what we need to interact with MAME as a child process
 	let mut mame_stdin = BufWriter::new(child.stdin.take().unwrap());
-	let mut mame_stderr = child.stderr.take().map(BufReader::new);
+	let mut mame_stderr = child
+		.stderr
+		.take()
+		.map(|stderr| spawn_stderr_reader(stderr, log.clone()));
 	let mut mame_stdout = BufReader::new(child.stdout.take().unwrap());
 	let mut line = String::new();
 	let mut is_exiting = false;
 
 	loop {
 		event!(LOG, "interact_with_mame(): calling read_line_from_mame()");
-		let (update, is_signal) = read_response_from_mame(&mut mame_stdout, &mut mame_stderr, &mut line)?;
+		let (update, is_signal) = read_response_from_mame(&mut mame_stdout, &mut mame_stderr, &mut line, log)?;
 
 		if let Some(update) = update {
 			event_callback(MameEvent::StatusUpdate(update))
@@ -185,7 +233,7 @@ fn interact_with_mame(
 			if is_exiting {
 				break Ok(());
 			}
-			is_exiting = match process_event_from_front_end(comm, &mut mame_stdin) {
+			is_exiting = match process_event_from_front_end(comm, &mut mame_stdin, log) {
 				Ok(x) => x,
 				Err(e) => break Err(e),
 			};
@@ -193,10 +241,28 @@ fn interact_with_mame(
 	}
 }
 
+/// MAME's stderr is read on its own thread, so that it can be logged as it arrives; the thread yields all of the
+/// text when MAME closes stderr
+fn spawn_stderr_reader(stderr: impl Read + Send + 'static, log: SessionLog) -> JoinHandle<String> {
+	spawn(move || {
+		let mut reader = BufReader::new(stderr);
+		let mut buf = Vec::new();
+		let mut text = String::new();
+		while reader.read_until(b'\n', &mut buf).is_ok_and(|len| len > 0) {
+			let line = String::from_utf8_lossy(&buf);
+			log.push(SessionLogSource::Stderr, line.trim_end());
+			text.push_str(&line);
+			buf.clear();
+		}
+		text
+	})
+}
+
 fn read_response_from_mame(
 	mame_stdout: &mut impl BufRead,
-	mame_stderr: &mut Option<impl BufRead>,
+	mame_stderr: &mut Option<JoinHandle<String>>,
 	line: &mut String,
+	log: &SessionLog,
 ) -> Result<(Option<Update>, bool)> {
 	#[derive(Debug, Clone, Copy, PartialEq)]
 	enum ResponseLine {
@@ -208,6 +274,7 @@ fn read_response_from_mame(
 
 	let (resp, comment) = match read_line_from_mame(mame_stdout, mame_stderr, line) {
 		Ok(()) => {
+			log.push(SessionLogSource::Response, line.trim_end());
 			if let Some(status_line) = line.strip_prefix("@") {
 				let (msg, comment) = if let Some((msg, comment)) = status_line.split_once("###") {
 					(msg.trim_end(), Some(comment.trim()))
@@ -235,9 +302,11 @@ fn read_response_from_mame(
 	let resp = resp?;
 
 	let update = if resp == ResponseLine::OkStatus {
-		// read the status XML from MAME
+		// read the status XML from MAME, retaining a copy for diagnostic reports
 		event!(LOG, "thread_proc(): starting to parse update");
-		let update = Update::parse(&mut *mame_stdout);
+		let mut capture = CaptureReader::new(&mut *mame_stdout);
+		let update = Update::parse(&mut capture);
+		log.set_last_status_xml(String::from_utf8_lossy(&capture.captured));
 		event!(LOG, "thread_proc(): parsed update: {:?}", update.as_ref().map(|_| ()));
 
 		// read until end of line
@@ -268,15 +337,55 @@ fn read_response_from_mame(
 	Ok((update, is_signal))
 }
 
+/// Reads through to another reader, keeping a copy of everything consumed
+struct CaptureReader<'a, R> {
+	inner: &'a mut R,
+	captured: Vec<u8>,
+}
+
+impl<'a, R> CaptureReader<'a, R> {
+	pub fn new(inner: &'a mut R) -> Self {
+		Self {
+			inner,
+			captured: Vec::new(),
+		}
+	}
+}
+
+impl<R: BufRead> Read for CaptureReader<'_, R> {
+	fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+		let len = self.inner.read(buf)?;
+		self.captured.extend_from_slice(&buf[..len]);
+		Ok(len)
+	}
+}
+
+impl<R: BufRead> BufRead for CaptureReader<'_, R> {
+	fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+		self.inner.fill_buf()
+	}
+
+	fn consume(&mut self, amt: usize) {
+		// the buffer is still filled, so this does not read anything further
+		if let Ok(buf) = self.inner.fill_buf() {
+			self.captured.extend_from_slice(&buf[..amt.min(buf.len())]);
+		}
+		self.inner.consume(amt);
+	}
+}
+
 fn read_line_from_mame(
 	mame_stdout: &mut impl BufRead,
-	mame_stderr: &mut Option<impl BufRead>,
+	mame_stderr: &mut Option<JoinHandle<String>>,
 	line: &mut String,
 ) -> Result<()> {
 	line.clear();
 	match mame_stdout.read_line(line) {
 		Ok(0) => {
-			let mame_stderr_text = mame_stderr.as_mut().map(read_text_from_reader).unwrap_or_default();
+			let mame_stderr_text = mame_stderr
+				.take()
+				.and_then(|handle| handle.join().ok())
+				.unwrap_or_default();
 			Err(ThisError::EofFromMame(mame_stderr_text).into())
 		}
 		Ok(_) => Ok(()),
@@ -284,18 +393,15 @@ fn read_line_from_mame(
 	}
 }
 
-fn read_text_from_reader(read: &mut impl Read) -> String {
-	let mut buf = Vec::new();
-	if read.read_to_end(&mut buf).is_err() {
-		buf.clear();
-	}
-	String::from_utf8(buf).unwrap_or_else(|e| String::from_utf8_lossy(e.as_bytes()).to_string())
-}
-
-fn process_event_from_front_end(comm: &SessionCommunication, mame_stdin: &mut BufWriter<impl Write>) -> Result<bool> {
+fn process_event_from_front_end(
+	comm: &SessionCommunication,
+	mame_stdin: &mut BufWriter<impl Write>,
+	log: &SessionLog,
+) -> Result<bool> {
 	let command = comm.message_queue.pop();
 	comm.message_queue_len.fetch_sub(1, Ordering::Relaxed);
 	event!(LOG, "process_event_from_front_end(): command=\"{:?}\"", command);
+	log.push(SessionLogSource::Command, command.text.as_ref());
 
 	fn mame_write_err(e: impl Into<Error>) -> Error {
 		e.into().context("Error writing to MAME")
@@ -306,66 +412,3 @@ fn process_event_from_front_end(comm: &SessionCommunication, mame_stdin: &mut Bu
 
 	Ok(command.is_exit)
 }
-
-fn command_text(command: &MameCommand<'_>) -> Cow<'static, str> {
-	match command {
-		MameCommand::Exit => "EXIT".into(),
-		MameCommand::Start {
-			machine_name,
-			initial_loads,
-		} => pairs_command_text(&["START", machine_name], initial_loads),
-		MameCommand::Stop => "STOP".into(),
-		MameCommand::SoftReset => "SOFT_RESET".into(),
-		MameCommand::HardReset => "HARD_RESET".into(),
-		MameCommand::Pause => "PAUSE".into(),
-		MameCommand::Resume => "RESUME".into(),
-		MameCommand::Ping => "PING".into(),
-		MameCommand::ClassicMenu => "CLASSIC_MENU".into(),
-		MameCommand::Throttled(throttled) => format!("THROTTLED {}", bool_str(*throttled)).into(),
-		MameCommand::ThrottleRate(throttle) => format!("THROTTLE_RATE {}", throttle).into(),
-		MameCommand::SetAttenuation(attenuation) => format!("SET_ATTENUATION {}", attenuation).into(),
-		MameCommand::LoadImage(loads) => pairs_command_text(&["LOAD"], loads),
-		MameCommand::UnloadImage(tag) => format!("UNLOAD {}", tag).into(),
-		MameCommand::ChangeSlots(changes) => pairs_command_text(&["CHANGE_SLOTS"], changes),
-	}
-}
-
-fn bool_str(b: bool) -> &'static str {
-	if b {
-		"true"
-	} else {
-		"false"
-	}
-}
-
-fn pairs_command_text(base: &[&str], args: &[(&str, &str)]) -> Cow<'static, str> {
-	base.iter()
-		.copied()
-		.map(Cow::Borrowed)
-		.chain(args.iter().flat_map(|(name, value)| {
-			let name = Cow::Borrowed(*name);
-			let value = if value.contains(' ') {
-				Cow::Owned(format!("\"{}\"", value))
-			} else {
-				Cow::Borrowed(*value)
-			};
-			[name, value]
-		}))
-		.join(" ")
-		.into()
-}
-
-#[cfg(test)]
-mod test {
-	use test_case::test_case;
-
-	use crate::runtime::MameCommand;
-
-	#[test_case(0, MameCommand::Exit, "EXIT")]
-	#[test_case(1, MameCommand::Start { machine_name: "coco2b", initial_loads: &[("ext:fdc:wd17xx:0", "foo.dsk")]}, "START coco2b ext:fdc:wd17xx:0 foo.dsk")]
-	#[test_case(2, MameCommand::LoadImage(&[("ext:fdc:wd17xx:0", "foo bar.dsk")]), "LOAD ext:fdc:wd17xx:0 \"foo bar.dsk\"")]
-	fn command_test(_index: usize, command: MameCommand<'_>, expected: &str) {
-		let actual = super::command_text(&command);
-		assert_eq!(expected, actual);
-	}
-}
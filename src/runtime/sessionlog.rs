@@ -0,0 +1,102 @@
+//! Captured MAME stderr output and `worker_ui` traffic, for display in the log viewer
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+/// The number of entries retained
+const SESSION_LOG_CAPACITY: usize = 5000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SessionLogSource {
+	/// Output from MAME's stderr
+	Stderr,
+	/// A command sent to the `worker_ui` plugin
+	Command,
+	/// A response line from the `worker_ui` plugin
+	Response,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SessionLogEntry {
+	pub source: SessionLogSource,
+	pub text: String,
+}
+
+/// A log shared by all sessions; cloning yields another handle onto the same log
+#[derive(Clone, Default)]
+pub struct SessionLog(Arc<Mutex<SessionLogInner>>);
+
+#[derive(Default)]
+struct SessionLogInner {
+	entries: VecDeque<SessionLogEntry>,
+	generation: u64,
+	last_status_xml: Option<String>,
+}
+
+impl SessionLog {
+	pub fn push(&self, source: SessionLogSource, text: impl Into<String>) {
+		let mut inner = self.0.lock().unwrap();
+		if inner.entries.len() >= SESSION_LOG_CAPACITY {
+			inner.entries.pop_front();
+		}
+		let text = text.into();
+		inner.entries.push_back(SessionLogEntry { source, text });
+		inner.generation += 1;
+	}
+
+	pub fn clear(&self) {
+		let mut inner = self.0.lock().unwrap();
+		inner.entries.clear();
+		inner.generation += 1;
+	}
+
+	/// A counter that changes whenever the log changes, so that viewers can cheaply determine whether to refresh
+	pub fn generation(&self) -> u64 {
+		self.0.lock().unwrap().generation
+	}
+
+	pub fn entries(&self) -> Vec<SessionLogEntry> {
+		self.0.lock().unwrap().entries.iter().cloned().collect()
+	}
+
+	/// Retains the most recent status XML from the `worker_ui` plugin (for diagnostic reports); unlike other traffic
+	/// this is not logged line by line
+	pub fn set_last_status_xml(&self, xml: impl Into<String>) {
+		self.0.lock().unwrap().last_status_xml = Some(xml.into());
+	}
+
+	pub fn last_status_xml(&self) -> Option<String> {
+		self.0.lock().unwrap().last_status_xml.clone()
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::SessionLog;
+	use super::SessionLogSource;
+
+	#[test]
+	pub fn capacity() {
+		let log = SessionLog::default();
+		for i in 0..(super::SESSION_LOG_CAPACITY + 10) {
+			log.push(SessionLogSource::Stderr, format!("line {i}"));
+		}
+		let entries = log.entries();
+		assert_eq!(super::SESSION_LOG_CAPACITY, entries.len());
+		assert_eq!("line 10", entries[0].text);
+
+		let generation = log.generation();
+		log.clear();
+		assert!(log.entries().is_empty());
+		assert_ne!(generation, log.generation());
+	}
+
+	#[test]
+	pub fn last_status_xml() {
+		let log = SessionLog::default();
+		assert_eq!(None, log.last_status_xml());
+		log.set_last_status_xml("<status/>");
+		log.set_last_status_xml("<status romname=\"pacman\"/>");
+		assert_eq!(Some("<status romname=\"pacman\"/>"), log.last_status_xml().as_deref());
+	}
+}
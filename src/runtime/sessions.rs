@@ -0,0 +1,174 @@
+//! Additional MAME sessions that run in their own windows, alongside the main (attached) session
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+use tracing::event;
+use tracing::Level;
+
+use crate::runtime::controller::MameController;
+use crate::runtime::MameCommand;
+use crate::runtime::MameWindowing;
+use crate::status::Status;
+use crate::status::Update;
+
+const LOG: Level = Level::DEBUG;
+
+pub type SessionId = usize;
+
+pub struct DetachedSession {
+	pub machine_description: String,
+	pub controller: MameController,
+	pub status: Status,
+}
+
+/// Tracks detached sessions, and which session (if any) is "active" and should receive commands; an active
+/// session of `None` refers to the main session
+#[derive(Default)]
+pub struct DetachedSessions {
+	sessions: RefCell<BTreeMap<SessionId, DetachedSession>>,
+	next_id: Cell<SessionId>,
+	active: Cell<Option<SessionId>>,
+}
+
+impl DetachedSessions {
+	/// Reserves an id for a session about to be started
+	pub fn allocate_id(&self) -> SessionId {
+		let id = self.next_id.get();
+		self.next_id.set(id + 1);
+		id
+	}
+
+	/// Adds a newly started session, and makes it active
+	pub fn insert(&self, id: SessionId, session: DetachedSession) {
+		event!(
+			LOG,
+			"DetachedSessions::insert(): id={} desc={:?}",
+			id,
+			session.machine_description
+		);
+		self.sessions.borrow_mut().insert(id, session);
+		self.active.set(Some(id));
+	}
+
+	/// Removes a session; if it was active, we fall back to the main session
+	pub fn remove(&self, id: SessionId) -> Option<DetachedSession> {
+		event!(LOG, "DetachedSessions::remove(): id={}", id);
+		if self.active.get() == Some(id) {
+			self.active.set(None);
+		}
+		self.sessions.borrow_mut().remove(&id)
+	}
+
+	pub fn active(&self) -> Option<SessionId> {
+		self.active.get()
+	}
+
+	/// Sets the active session; returns false if there is no such session
+	pub fn set_active(&self, id: Option<SessionId>) -> bool {
+		let result = id.is_none_or(|id| self.sessions.borrow().contains_key(&id));
+		if result {
+			self.active.set(id);
+		}
+		result
+	}
+
+	/// The status of the active session, if the active session is a detached session
+	pub fn active_status(&self) -> Option<Status> {
+		let id = self.active.get()?;
+		self.sessions.borrow().get(&id).map(|session| session.status.clone())
+	}
+
+	pub fn status(&self, id: SessionId) -> Option<Status> {
+		self.sessions.borrow().get(&id).map(|session| session.status.clone())
+	}
+
+	pub fn issue_command(&self, id: SessionId, command: MameCommand<'_>) {
+		if let Some(session) = self.sessions.borrow().get(&id) {
+			session.controller.issue_command(command);
+		}
+	}
+
+	/// Applies a status update; returns whether the session is still running a machine
+	pub fn status_update(&self, id: SessionId, update: Update) -> bool {
+		let mut sessions = self.sessions.borrow_mut();
+		let Some(session) = sessions.get_mut(&id) else {
+			return false;
+		};
+		let was_running = session.status.running.is_some();
+		session.status = session.status.merge(update);
+		!was_running || session.status.running.is_some()
+	}
+
+	/// Is this session running a machine and not busy with other commands?
+	pub fn is_idle(&self, id: SessionId) -> bool {
+		self.sessions
+			.borrow()
+			.get(&id)
+			.is_some_and(|session| session.status.running.is_some() && session.controller.is_queue_empty())
+	}
+
+	/// Pings all sessions that are running and are not busy
+	pub fn ping(&self) {
+		for session in self.sessions.borrow().values() {
+			if session.status.running.is_some() && session.controller.is_queue_empty() {
+				session.controller.issue_command(MameCommand::Ping);
+			}
+		}
+	}
+
+	/// The descriptions of all sessions, in order
+	pub fn entries(&self) -> Vec<(SessionId, String)> {
+		self.sessions
+			.borrow()
+			.iter()
+			.map(|(id, session)| (*id, session.machine_description.clone()))
+			.collect()
+	}
+
+	/// Shuts down all sessions
+	pub fn shutdown_all(&self) {
+		let sessions = self.sessions.take();
+		self.active.set(None);
+		for session in sessions.into_values() {
+			session.controller.reset(None, &MameWindowing::Windowed);
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use crate::runtime::controller::MameController;
+	use crate::runtime::sessionlog::SessionLog;
+	use crate::runtime::MameStderr;
+	use crate::status::Status;
+
+	use super::DetachedSession;
+	use super::DetachedSessions;
+
+	fn session(desc: &str) -> DetachedSession {
+		DetachedSession {
+			machine_description: desc.to_string(),
+			controller: MameController::new(MameStderr::default(), SessionLog::default()),
+			status: Status::default(),
+		}
+	}
+
+	#[test]
+	pub fn active_session() {
+		let sessions = DetachedSessions::default();
+		let alpha = sessions.allocate_id();
+		sessions.insert(alpha, session("Alpha"));
+		let bravo = sessions.allocate_id();
+		sessions.insert(bravo, session("Bravo"));
+		assert_eq!(Some(bravo), sessions.active());
+
+		assert!(sessions.set_active(Some(alpha)));
+		assert!(!sessions.set_active(Some(bravo + 1)));
+		assert_eq!(Some(alpha), sessions.active());
+
+		sessions.remove(alpha);
+		assert_eq!(None, sessions.active());
+		assert_eq!(vec![(bravo, "Bravo".to_string())], sessions.entries());
+	}
+}
@@ -0,0 +1,189 @@
+//! Automatic naming of screenshots saved from running machines
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use crate::prefs::PrefsCollection;
+use crate::prefs::PrefsItem;
+
+/// The naming template used if none is specified
+pub const DEFAULT_SCREENSHOT_TEMPLATE: &str = "{machine}-{sequence}";
+
+/// The naming templates offered in the menus, along with their descriptions
+pub const SCREENSHOT_TEMPLATES: &[(&str, &str)] = &[
+	("{machine}-{sequence}", "Machine and Sequence Number"),
+	("{machine}-{date}", "Machine and Date"),
+	("{machine}-{date}-{sequence}", "Machine, Date and Sequence Number"),
+];
+
+/// Chooses the path of a new screenshot; the sequence number is the first one not already taken
+pub fn next_screenshot_path(
+	directory: &Path,
+	template: &str,
+	per_machine_directory: bool,
+	machine_name: &str,
+	now: SystemTime,
+) -> PathBuf {
+	let directory = if per_machine_directory {
+		directory.join(machine_name)
+	} else {
+		directory.to_path_buf()
+	};
+	let date = civil_date(now);
+	let path = |sequence| directory.join(screenshot_filename(template, machine_name, date, sequence));
+
+	// without a sequence number in the template, there is nothing we can do about existing files
+	if !template.contains("{sequence}") {
+		return path(0);
+	}
+	(0..).map(path).find(|path| !path.exists()).unwrap()
+}
+
+/// Finds the capture directory override for a machine, with tokens (e.g. - "{machine}") expanded; overrides for the
+/// machine itself win over those of folders containing it, which are consulted in collection order
+pub fn capture_directory_override(
+	machine_overrides: &BTreeMap<String, String>,
+	folder_overrides: &BTreeMap<String, String>,
+	collections: &[Rc<PrefsCollection>],
+	machine_name: &str,
+	now: SystemTime,
+) -> Option<String> {
+	let folder_override = || {
+		collections.iter().find_map(|collection| match collection.as_ref() {
+			PrefsCollection::Folder { name, items, .. } if items.iter().any(|item| is_machine(item, machine_name)) => {
+				folder_overrides.get(name)
+			}
+			_ => None,
+		})
+	};
+	let directory = machine_overrides.get(machine_name).or_else(folder_override)?;
+	let (year, month, day) = civil_date(now);
+	let directory = directory
+		.replace("{machine}", machine_name)
+		.replace("{date}", &format!("{year:04}-{month:02}-{day:02}"));
+	Some(directory)
+}
+
+fn is_machine(item: &PrefsItem, machine_name: &str) -> bool {
+	matches!(item, PrefsItem::Machine { machine_name: x } if x == machine_name)
+}
+
+fn screenshot_filename(
+	template: &str,
+	machine_name: &str,
+	(year, month, day): (i64, u32, u32),
+	sequence: u32,
+) -> String {
+	let filename = template
+		.replace("{machine}", machine_name)
+		.replace("{date}", &format!("{year:04}-{month:02}-{day:02}"))
+		.replace("{sequence}", &format!("{sequence:04}"));
+	format!("{filename}.png")
+}
+
+/// Converts a time into a (year, month, day) triple in UTC
+pub fn civil_date(time: SystemTime) -> (i64, u32, u32) {
+	let secs = time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+	let days = i64::try_from(secs / 86400).unwrap();
+
+	// see http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+	let z = days + 719468;
+	let era = z.div_euclid(146097);
+	let doe = z.rem_euclid(146097);
+	let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+	let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+	let mp = (5 * doy + 2) / 153;
+	let day = u32::try_from(doy - (153 * mp + 2) / 5 + 1).unwrap();
+	let month = u32::try_from(if mp < 10 { mp + 3 } else { mp - 9 }).unwrap();
+	let year = yoe + era * 400 + i64::from(month <= 2);
+	(year, month, day)
+}
+
+#[cfg(test)]
+mod test {
+	use std::collections::BTreeMap;
+	use std::fs::File;
+	use std::rc::Rc;
+	use std::time::Duration;
+	use std::time::UNIX_EPOCH;
+
+	use tempdir::TempDir;
+	use test_case::test_case;
+
+	use crate::prefs::PrefsCollection;
+	use crate::prefs::PrefsItem;
+
+	#[test_case(0, 0, (1970, 1, 1))]
+	#[test_case(1, 951782400, (2000, 2, 29))]
+	#[test_case(2, 1792022400, (2026, 10, 15))]
+	pub fn civil_date(_index: usize, secs: u64, expected: (i64, u32, u32)) {
+		let actual = super::civil_date(UNIX_EPOCH + Duration::from_secs(secs));
+		assert_eq!(expected, actual);
+	}
+
+	#[test_case(0, &[], &[], "coco2b", None)]
+	#[test_case(1, &[("coco2b", "C:\\Captures")], &[], "coco2b", Some("C:\\Captures"))]
+	#[test_case(2, &[("coco2b", "C:\\Captures\\{machine}")], &[], "coco2b", Some("C:\\Captures\\coco2b"))]
+	#[test_case(3, &[("coco2b", "C:\\Captures")], &[], "coco3", None)]
+	#[test_case(4, &[], &[("CoCo", "D:\\{machine}-{date}")], "coco2b", Some("D:\\coco2b-2026-10-15"))]
+	#[test_case(5, &[], &[("CoCo", "D:\\{machine}")], "pacman", None)]
+	#[test_case(6, &[("coco2b", "C:\\Captures")], &[("CoCo", "D:\\")], "coco2b", Some("C:\\Captures"))]
+	pub fn capture_directory_override(
+		_index: usize,
+		machine_overrides: &[(&str, &str)],
+		folder_overrides: &[(&str, &str)],
+		machine_name: &str,
+		expected: Option<&str>,
+	) {
+		let to_map = |overrides: &[(&str, &str)]| {
+			overrides
+				.iter()
+				.map(|(key, value)| (key.to_string(), value.to_string()))
+				.collect::<BTreeMap<_, _>>()
+		};
+		let item = |machine_name: &str| PrefsItem::Machine {
+			machine_name: machine_name.to_string(),
+		};
+		let collections = [Rc::new(PrefsCollection::Folder {
+			name: "CoCo".into(),
+			parent: None,
+			items: vec![item("coco2b"), item("coco3")],
+		})];
+
+		let now = UNIX_EPOCH + Duration::from_secs(1792022400);
+		let actual = super::capture_directory_override(
+			&to_map(machine_overrides),
+			&to_map(folder_overrides),
+			&collections,
+			machine_name,
+			now,
+		);
+		assert_eq!(expected, actual.as_deref());
+	}
+
+	#[test_case(0, "{machine}-{sequence}", false, &[], "coco2b-0000.png")]
+	#[test_case(1, "{machine}-{sequence}", false, &["coco2b-0000.png", "coco2b-0001.png"], "coco2b-0002.png")]
+	#[test_case(2, "{machine}-{sequence}", true, &[], "coco2b/coco2b-0000.png")]
+	#[test_case(3, "{machine}-{date}", false, &[], "coco2b-2026-10-15.png")]
+	#[test_case(4, "{machine}-{date}", false, &["coco2b-2026-10-15.png"], "coco2b-2026-10-15.png")]
+	#[test_case(5, "{machine}-{date}-{sequence}", false, &["coco2b-2026-10-15-0000.png"], "coco2b-2026-10-15-0001.png")]
+	pub fn next_screenshot_path(
+		_index: usize,
+		template: &str,
+		per_machine_directory: bool,
+		existing_files: &[&str],
+		expected: &str,
+	) {
+		let tmp_dir = TempDir::new("temp").unwrap();
+		for file in existing_files {
+			File::create(tmp_dir.path().join(file)).unwrap();
+		}
+
+		let now = UNIX_EPOCH + Duration::from_secs(1792022400);
+		let actual = super::next_screenshot_path(tmp_dir.path(), template, per_machine_directory, "coco2b", now);
+		assert_eq!(tmp_dir.path().join(expected), actual);
+	}
+}
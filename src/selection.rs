@@ -1,13 +1,58 @@
 use std::cell::Cell;
+use std::cell::RefCell;
+use std::collections::BTreeSet;
 use std::rc::Rc;
+use std::time::Duration;
+use std::time::Instant;
 
+use i_slint_core::items::PointerEvent;
+use i_slint_core::items::PointerEventKind;
+use slint::platform::PointerEventButton;
 use slint::spawn_local;
 use slint::ComponentHandle;
 
+/// How long a pause in typing has to be before type-ahead starts over with a new prefix
+const TYPE_AHEAD_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// How a click on a row affects the selection
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SelectionClick {
+	/// A plain click; the clicked row becomes the entire selection
+	Replace,
+	/// A ctrl click; the clicked row is added to or removed from the selection
+	Toggle,
+	/// A shift click; the selection becomes the rows between the anchor and the clicked row
+	Extend,
+}
+
+impl SelectionClick {
+	pub fn from_pointer_event(evt: &PointerEvent) -> Option<Self> {
+		if evt.button != PointerEventButton::Left || evt.kind != PointerEventKind::Down {
+			None
+		} else if evt.modifiers.shift {
+			Some(Self::Extend)
+		} else if evt.modifiers.control || evt.modifiers.meta {
+			Some(Self::Toggle)
+		} else {
+			Some(Self::Replace)
+		}
+	}
+}
+
 pub struct SelectionManager {
 	getter: Box<dyn Fn() -> i32 + 'static>,
 	setter: Rc<dyn Fn(i32) + 'static>,
 	index_to_select: Cell<Option<i32>>,
+
+	/// Rows selected with ctrl/shift clicks; when not empty, this is the selection rather than just the current row
+	multi_indices: RefCell<BTreeSet<usize>>,
+
+	/// The row that shift clicks extend the selection from
+	anchor_index: Cell<Option<usize>>,
+
+	/// The row we expect the current row to change to; any other change (e.g. - from the keyboard) collapses the
+	/// selection back to the current row
+	expected_index: Cell<Option<usize>>,
 }
 
 impl SelectionManager {
@@ -23,6 +68,9 @@ impl SelectionManager {
 			getter,
 			setter,
 			index_to_select: Cell::new(None),
+			multi_indices: RefCell::new(BTreeSet::new()),
+			anchor_index: Cell::new(None),
+			expected_index: Cell::new(None),
 		}
 	}
 
@@ -30,11 +78,72 @@ impl SelectionManager {
 		(self.getter)().try_into().ok()
 	}
 
-	pub fn set_selected_index(&self, index: Option<usize>) {
+	/// All selected rows; the current row comes first if it is selected
+	pub fn selected_indices(&self) -> Vec<usize> {
+		let current = self.selected_index();
+		let multi_indices = self.multi_indices.borrow();
+		if multi_indices.is_empty() {
+			current.into_iter().collect()
+		} else {
+			let current = current.filter(|x| multi_indices.contains(x));
+			let others = multi_indices.iter().copied().filter(|&x| Some(x) != current);
+			current.into_iter().chain(others).collect()
+		}
+	}
+
+	/// Is this row part of a multiple selection?
+	pub fn is_multi_selected(&self, index: usize) -> bool {
+		self.multi_indices.borrow().contains(&index)
+	}
+
+	/// Selects rows; the first one becomes the current row
+	pub fn set_selected_indices(&self, indices: &[usize]) {
+		let index = indices.first().copied();
+		let multi_indices = if indices.len() > 1 {
+			indices.iter().copied().collect()
+		} else {
+			BTreeSet::new()
+		};
+		self.multi_indices.replace(multi_indices);
+		self.anchor_index.set(index);
+		self.expected_index.set(index);
+
 		let index = index.map(|x| x.try_into().unwrap()).unwrap_or(-1);
 		self.index_to_select.set(Some(index));
 	}
 
+	/// Responds to a click on a row, before the current row follows it; returns the rows whose selection changed
+	pub fn row_clicked(&self, index: usize, click: SelectionClick) -> Vec<usize> {
+		let old_indices = self.multi_indices.take();
+		let (new_indices, anchor_index) = apply_click(
+			&old_indices,
+			self.anchor_index.get(),
+			self.selected_index(),
+			index,
+			click,
+		);
+		let changed = old_indices.symmetric_difference(&new_indices).copied().collect();
+		self.multi_indices.replace(new_indices);
+		self.anchor_index.set(anchor_index);
+		self.expected_index.set(Some(index));
+		changed
+	}
+
+	/// Responds to the current row changing; returns the rows whose selection changed
+	pub fn current_index_changed(&self) -> Vec<usize> {
+		let current = self.selected_index();
+		if self.expected_index.take() == current {
+			return Vec::new();
+		}
+		self.anchor_index.set(current);
+		self.multi_indices.take().into_iter().collect()
+	}
+
+	/// Makes a row the current row, as if it had been navigated to with the keyboard
+	pub fn jump_to(&self, index: usize) {
+		(self.setter)(index.try_into().unwrap());
+	}
+
 	pub fn model_accessed(&self) {
 		if let Some(index) = self.index_to_select.take() {
 			let setter = self.setter.clone();
@@ -45,3 +154,117 @@ impl SelectionManager {
 		}
 	}
 }
+
+/// Accumulates characters typed into a list into a prefix to jump to
+#[derive(Default)]
+pub struct TypeAhead {
+	prefix: RefCell<String>,
+	last_keystroke: Cell<Option<Instant>>,
+}
+
+impl TypeAhead {
+	/// Adds typed text to the prefix (starting over if the user paused), returning the prefix to jump to; returns
+	/// `None` for keys that are not text (e.g. - Enter or the arrow keys)
+	pub fn push(&self, text: &str, now: Instant) -> Option<String> {
+		if text.is_empty() || text.chars().any(|ch| ch.is_control() || is_private_use(ch)) {
+			return None;
+		}
+		let timed_out = self
+			.last_keystroke
+			.replace(Some(now))
+			.is_none_or(|last| now.saturating_duration_since(last) > TYPE_AHEAD_TIMEOUT);
+		let mut prefix = self.prefix.borrow_mut();
+		if timed_out {
+			prefix.clear();
+		}
+		prefix.push_str(text);
+		Some(prefix.clone())
+	}
+}
+
+/// Slint reports special keys (arrows, function keys etc) as characters in the private use area
+fn is_private_use(ch: char) -> bool {
+	('\u{E000}'..='\u{F8FF}').contains(&ch)
+}
+
+/// Determines the new multiple selection and anchor after a click
+fn apply_click(
+	multi_indices: &BTreeSet<usize>,
+	anchor_index: Option<usize>,
+	current_index: Option<usize>,
+	index: usize,
+	click: SelectionClick,
+) -> (BTreeSet<usize>, Option<usize>) {
+	match click {
+		SelectionClick::Replace => (BTreeSet::new(), Some(index)),
+		SelectionClick::Toggle => {
+			let mut multi_indices = multi_indices.clone();
+			if multi_indices.is_empty() {
+				multi_indices.extend(current_index);
+			}
+			if !multi_indices.remove(&index) {
+				multi_indices.insert(index);
+			}
+			(multi_indices, Some(index))
+		}
+		SelectionClick::Extend => {
+			let anchor_index = anchor_index.or(current_index).unwrap_or(index);
+			let multi_indices = (anchor_index.min(index)..=anchor_index.max(index)).collect();
+			(multi_indices, Some(anchor_index))
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::collections::BTreeSet;
+	use std::time::Duration;
+	use std::time::Instant;
+
+	use test_case::test_case;
+
+	use super::SelectionClick;
+	use super::TypeAhead;
+
+	#[test_case(0, &[], None, Some(2), 5, SelectionClick::Replace, &[], Some(5))]
+	#[test_case(1, &[1, 2], Some(1), Some(2), 5, SelectionClick::Replace, &[], Some(5))]
+	#[test_case(2, &[], None, Some(2), 5, SelectionClick::Toggle, &[2, 5], Some(5))]
+	#[test_case(3, &[2, 5], Some(5), Some(5), 2, SelectionClick::Toggle, &[5], Some(2))]
+	#[test_case(4, &[], None, None, 5, SelectionClick::Toggle, &[5], Some(5))]
+	#[test_case(5, &[], Some(2), Some(2), 5, SelectionClick::Extend, &[2, 3, 4, 5], Some(2))]
+	#[test_case(6, &[2, 3, 4, 5], Some(2), Some(5), 0, SelectionClick::Extend, &[0, 1, 2], Some(2))]
+	#[test_case(7, &[], None, None, 3, SelectionClick::Extend, &[3], Some(3))]
+	#[allow(clippy::too_many_arguments)]
+	pub fn apply_click(
+		_index: usize,
+		multi_indices: &[usize],
+		anchor_index: Option<usize>,
+		current_index: Option<usize>,
+		index: usize,
+		click: SelectionClick,
+		expected_indices: &[usize],
+		expected_anchor: Option<usize>,
+	) {
+		let multi_indices = multi_indices.iter().copied().collect::<BTreeSet<_>>();
+		let actual = super::apply_click(&multi_indices, anchor_index, current_index, index, click);
+		let expected = (expected_indices.iter().copied().collect(), expected_anchor);
+		assert_eq!(expected, actual);
+	}
+
+	#[test_case(0, &[(0, "g")], Some("g"))]
+	#[test_case(1, &[(0, "g"), (200, "a")], Some("ga"))]
+	#[test_case(2, &[(0, "g"), (200, "a"), (2000, "p")], Some("p"))]
+	#[test_case(3, &[(0, "g"), (200, "\n")], None)]
+	#[test_case(4, &[(0, "g"), (200, "\u{F700}")], None)]
+	#[test_case(5, &[(0, "g"), (200, "\n"), (400, "a")], Some("ga"))]
+	pub fn type_ahead(_index: usize, keystrokes: &[(u64, &str)], expected: Option<&str>) {
+		let start = Instant::now();
+		let type_ahead = TypeAhead::default();
+		let actual = keystrokes
+			.iter()
+			.map(|&(millis, text)| type_ahead.push(text, start + Duration::from_millis(millis)))
+			.last()
+			.unwrap();
+		assert_eq!(expected, actual.as_deref());
+	}
+}
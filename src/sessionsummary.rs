@@ -0,0 +1,124 @@
+//! Tracking of what happened while a machine was running, so that a brief summary can be shown when it stops
+use std::fs::read_dir;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+
+/// A machine that has been started by the user, and what has happened since
+#[derive(Debug)]
+pub struct SessionStats {
+	machine_name: String,
+	initial_loads: Vec<(Arc<str>, Arc<str>)>,
+	started_at: Instant,
+	started_at_system: SystemTime,
+	has_run: bool,
+	screenshots: usize,
+}
+
+/// The summary of a session that has ended
+#[derive(Debug)]
+pub struct SessionSummary {
+	pub machine_name: String,
+	pub initial_loads: Vec<(Arc<str>, Arc<str>)>,
+	pub duration: Duration,
+	pub state_saved: bool,
+	pub screenshots: usize,
+}
+
+impl SessionStats {
+	pub fn new(machine_name: impl Into<String>, initial_loads: Vec<(Arc<str>, Arc<str>)>) -> Self {
+		Self {
+			machine_name: machine_name.into(),
+			initial_loads,
+			started_at: Instant::now(),
+			started_at_system: SystemTime::now(),
+			has_run: false,
+			screenshots: 0,
+		}
+	}
+
+	pub fn set_running(&mut self) {
+		self.has_run = true;
+	}
+
+	pub fn has_run(&self) -> bool {
+		self.has_run
+	}
+
+	pub fn note_screenshot(&mut self) {
+		self.screenshots += 1;
+	}
+
+	/// Wraps up the session; MAME writes save states to `state_dir` (one subdirectory per machine)
+	pub fn finish(self, now: Instant, state_dir: Option<&Path>) -> SessionSummary {
+		let state_saved = state_dir.is_some_and(|state_dir| {
+			has_file_modified_since(&state_dir.join(&self.machine_name), self.started_at_system)
+		});
+		SessionSummary {
+			machine_name: self.machine_name,
+			initial_loads: self.initial_loads,
+			duration: now.saturating_duration_since(self.started_at),
+			state_saved,
+			screenshots: self.screenshots,
+		}
+	}
+}
+
+impl SessionSummary {
+	pub fn text(&self, machine_description: &str) -> String {
+		let mut text = format!("Played \"{machine_description}\" for {}", play_time_text(self.duration));
+		if self.state_saved {
+			text.push_str("; state saved");
+		}
+		match self.screenshots {
+			0 => {}
+			1 => text.push_str("; 1 screenshot taken"),
+			count => text.push_str(&format!("; {count} screenshots taken")),
+		}
+		text
+	}
+}
+
+fn play_time_text(duration: Duration) -> String {
+	let secs = duration.as_secs();
+	match secs {
+		3600.. => format!("{}h {:02}m", secs / 3600, (secs % 3600) / 60),
+		60.. => format!("{}m {:02}s", secs / 60, secs % 60),
+		_ => format!("{secs}s"),
+	}
+}
+
+fn has_file_modified_since(dir: &Path, since: SystemTime) -> bool {
+	read_dir(dir).is_ok_and(|entries| {
+		entries
+			.filter_map(|entry| entry.ok()?.metadata().ok()?.modified().ok())
+			.any(|modified| modified >= since)
+	})
+}
+
+#[cfg(test)]
+mod test {
+	use std::time::Duration;
+
+	use test_case::test_case;
+
+	use super::SessionSummary;
+
+	#[test_case(0, 5, false, 0, "Played \"Pac-Man\" for 5s")]
+	#[test_case(1, 125, true, 0, "Played \"Pac-Man\" for 2m 05s; state saved")]
+	#[test_case(2, 3900, false, 1, "Played \"Pac-Man\" for 1h 05m; 1 screenshot taken")]
+	#[test_case(3, 60, true, 3, "Played \"Pac-Man\" for 1m 00s; state saved; 3 screenshots taken")]
+	pub fn summary_text(_index: usize, secs: u64, state_saved: bool, screenshots: usize, expected: &str) {
+		let summary = SessionSummary {
+			machine_name: "pacman".into(),
+			initial_loads: Vec::new(),
+			duration: Duration::from_secs(secs),
+			state_saved,
+			screenshots,
+		};
+		let actual = summary.text("Pac-Man");
+		assert_eq!(expected, actual);
+	}
+}
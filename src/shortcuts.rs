@@ -0,0 +1,97 @@
+//! Configurable keyboard shortcuts for frontend commands
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+
+use muda::accelerator::Accelerator;
+
+use crate::appcommand::AppCommand;
+use crate::guiutils::menuing::parse_accel;
+use crate::prefs::Preferences;
+
+/// Commands that can be bound to a keyboard shortcut, and their descriptions
+pub fn bindable_commands() -> Vec<(AppCommand, &'static str)> {
+	vec![
+		(AppCommand::FileStop, "Stop"),
+		(AppCommand::FilePause, "Pause"),
+		(AppCommand::FileDevicesAndImages, "Devices and Images..."),
+		(AppCommand::ItemsSurpriseMe { run: true }, "Run Random Machine"),
+		(AppCommand::FileResetSoft, "Soft Reset"),
+		(AppCommand::FileResetHard, "Hard Reset"),
+		(AppCommand::FileExit, "Exit"),
+		(AppCommand::ViewToggleGrid, "Grid View"),
+		(AppCommand::ViewCommandPalette, "Command Palette..."),
+		(AppCommand::OptionsToggleWarp, "Warp mode"),
+		(AppCommand::OptionsToggleSound, "Sound"),
+		(AppCommand::OptionsClassic, "Classic MAME Menu"),
+		(AppCommand::SettingsPaths, "Paths..."),
+	]
+}
+
+/// The shortcuts used when the user has not customized them
+pub fn default_shortcuts() -> BTreeMap<String, AppCommand> {
+	[
+		("Pause", AppCommand::FilePause),
+		("Ctrl+Alt+X", AppCommand::FileExit),
+		("F10", AppCommand::OptionsToggleWarp),
+		("Ctrl+Shift+P", AppCommand::ViewCommandPalette),
+	]
+	.into_iter()
+	.map(|(accel, command)| (accel.to_string(), command))
+	.collect()
+}
+
+/// The shortcuts currently in effect
+pub fn effective_shortcuts(prefs: &Preferences) -> BTreeMap<String, AppCommand> {
+	prefs.shortcuts.clone().unwrap_or_else(default_shortcuts)
+}
+
+/// Finds the accelerator bound to a particular command, if any
+pub fn accelerator_for_command(shortcuts: &BTreeMap<String, AppCommand>, command: &AppCommand) -> Option<Accelerator> {
+	shortcuts
+		.iter()
+		.find(|(_, x)| *x == command)
+		.and_then(|(accel, _)| parse_accel(accel))
+}
+
+/// Identifies problems with proposed shortcuts (one entry per shortcut; `None` if there is no problem)
+pub fn shortcut_problems(shortcuts: &[(&str, &str)]) -> Vec<Option<String>> {
+	let mut usage = HashMap::<u32, Vec<usize>>::new();
+	let parsed = shortcuts
+		.iter()
+		.map(|(accel, _)| (!accel.is_empty()).then(|| parse_accel(accel)))
+		.collect::<Vec<_>>();
+	for (index, accel) in parsed.iter().enumerate() {
+		if let Some(Some(accel)) = accel {
+			usage.entry(accel.id()).or_default().push(index);
+		}
+	}
+
+	parsed
+		.iter()
+		.enumerate()
+		.map(|(index, accel)| match accel {
+			None => None,
+			Some(None) => Some("Invalid shortcut".to_string()),
+			Some(Some(accel)) => usage[&accel.id()]
+				.iter()
+				.find(|&&other| other != index)
+				.map(|&other| format!("Conflicts with \"{}\"", shortcuts[other].1)),
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod test {
+	use test_case::test_case;
+
+	#[test_case(0, &[("F7", "Alpha"), ("F8", "Bravo")], &[None, None])]
+	#[test_case(1, &[("F7", "Alpha"), ("", "Bravo")], &[None, None])]
+	#[test_case(2, &[("F7", "Alpha"), ("Bogus", "Bravo")], &[None, Some("Invalid shortcut")])]
+	#[test_case(3, &[("F7", "Alpha"), ("F7", "Bravo")], &[Some("Conflicts with \"Bravo\""), Some("Conflicts with \"Alpha\"")])]
+	#[test_case(4, &[("Ctrl+F7", "Alpha"), ("F7", "Bravo")], &[None, None])]
+	pub fn shortcut_problems(_index: usize, shortcuts: &[(&str, &str)], expected: &[Option<&str>]) {
+		let actual = super::shortcut_problems(shortcuts);
+		let expected = expected.iter().map(|x| x.map(|x| x.to_string())).collect::<Vec<_>>();
+		assert_eq!(expected, actual);
+	}
+}
@@ -0,0 +1,116 @@
+//! Comparison of captured snapshots against per-machine reference images, to spot machines whose output changed
+use std::fs::read_dir;
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use slint::Image;
+use slint::Rgba8Pixel;
+
+#[derive(thiserror::Error, Debug)]
+enum ThisError {
+	#[error("Could not load image \"{0}\"")]
+	CannotLoadImage(String),
+}
+
+/// The result of comparing a snapshot against its reference
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SnapshotDifference {
+	/// The images have different dimensions, so a pixel comparison is meaningless
+	SizeMismatch((u32, u32), (u32, u32)),
+	/// Percentage (0.0 - 100.0) of pixels that differ
+	Pixels(f32),
+}
+
+impl SnapshotDifference {
+	pub fn is_changed(&self) -> bool {
+		!matches!(self, Self::Pixels(percent) if *percent == 0.0)
+	}
+}
+
+/// Finds the most recently captured snapshot for a machine; MAME saves these either as `<machine>.png` or as
+/// numbered files within a `<machine>` subdirectory
+pub fn find_latest_snapshot(snapshot_paths: &[String], machine_name: &str) -> Option<PathBuf> {
+	snapshot_paths
+		.iter()
+		.flat_map(|path| {
+			let path = Path::new(path);
+			let single = path.join(format!("{machine_name}.png"));
+			let numbered = read_dir(path.join(machine_name))
+				.into_iter()
+				.flatten()
+				.filter_map(|entry| entry.ok().map(|entry| entry.path()))
+				.filter(|path| path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("png")));
+			std::iter::once(single).chain(numbered)
+		})
+		.filter_map(|path| {
+			let modified = path.metadata().ok().filter(|x| x.is_file())?.modified().ok()?;
+			Some((modified, path))
+		})
+		.max_by(|(a, _), (b, _)| a.cmp(b))
+		.map(|(_, path)| path)
+}
+
+/// Loads two images and compares them pixel by pixel
+pub fn compare_snapshot_files(snapshot: &Path, reference: &Path) -> Result<SnapshotDifference> {
+	let load = |path: &Path| {
+		Image::load_from_path(path)
+			.ok()
+			.and_then(|image| image.to_rgba8())
+			.ok_or_else(|| ThisError::CannotLoadImage(path.to_string_lossy().into_owned()))
+	};
+	let snapshot = load(snapshot)?;
+	let reference = load(reference)?;
+	let result = compare_pixels(
+		(snapshot.width(), snapshot.height()),
+		snapshot.as_slice(),
+		(reference.width(), reference.height()),
+		reference.as_slice(),
+	);
+	Ok(result)
+}
+
+fn compare_pixels(
+	size_a: (u32, u32),
+	pixels_a: &[Rgba8Pixel],
+	size_b: (u32, u32),
+	pixels_b: &[Rgba8Pixel],
+) -> SnapshotDifference {
+	if size_a != size_b {
+		return SnapshotDifference::SizeMismatch(size_a, size_b);
+	}
+	if pixels_a.is_empty() {
+		return SnapshotDifference::Pixels(0.0);
+	}
+
+	let different_count = pixels_a.iter().zip(pixels_b).filter(|(a, b)| a != b).count();
+	let percent = (different_count as f64 * 100.0 / pixels_a.len() as f64) as f32;
+	SnapshotDifference::Pixels(percent)
+}
+
+#[cfg(test)]
+mod test {
+	use slint::Rgba8Pixel;
+	use test_case::test_case;
+
+	use super::SnapshotDifference;
+
+	const BLACK: Rgba8Pixel = Rgba8Pixel::new(0, 0, 0, 255);
+	const WHITE: Rgba8Pixel = Rgba8Pixel::new(255, 255, 255, 255);
+
+	#[test_case(0, (2, 2), &[BLACK, BLACK, BLACK, BLACK], (2, 2), &[BLACK, BLACK, BLACK, BLACK], SnapshotDifference::Pixels(0.0))]
+	#[test_case(1, (2, 2), &[BLACK, BLACK, BLACK, BLACK], (2, 2), &[BLACK, WHITE, BLACK, BLACK], SnapshotDifference::Pixels(25.0))]
+	#[test_case(2, (2, 2), &[BLACK, BLACK, BLACK, BLACK], (2, 2), &[WHITE, WHITE, WHITE, WHITE], SnapshotDifference::Pixels(100.0))]
+	#[test_case(3, (2, 2), &[BLACK, BLACK, BLACK, BLACK], (4, 1), &[BLACK, BLACK, BLACK, BLACK], SnapshotDifference::SizeMismatch((2, 2), (4, 1)))]
+	pub fn compare_pixels(
+		_index: usize,
+		size_a: (u32, u32),
+		pixels_a: &[Rgba8Pixel],
+		size_b: (u32, u32),
+		pixels_b: &[Rgba8Pixel],
+		expected: SnapshotDifference,
+	) {
+		let actual = super::compare_pixels(size_a, pixels_a, size_b, pixels_b);
+		assert_eq!(expected, actual);
+	}
+}
@@ -0,0 +1,490 @@
+//! Binary cache of parsed software lists, so that large hash files do not need to be parsed every time they are used
+//!
+//! Each hash file gets its own cache file, which records the hash file's path, size, modification time and a hash of
+//! its contents; a cache file is ignored (and eventually replaced) when any of these differ.  The layout follows the
+//! InfoDb:  a header, arrays of fixed size records and a string table.
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::fs::read;
+use std::fs::read_dir;
+use std::fs::remove_file;
+use std::fs::File;
+use std::fs::Metadata;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::RwLock;
+use std::time::UNIX_EPOCH;
+
+use anyhow::Result;
+use binary_serde::BinarySerde;
+use binary_serde::Endianness;
+use tracing::event;
+use tracing::Level;
+
+use crate::info::read_string;
+use crate::info::validate_string_table;
+use crate::info::StringTableBuilder;
+use crate::software::Software;
+use crate::software::SoftwareList;
+use crate::software::SoftwarePart;
+use crate::software::SoftwareSupport;
+
+const LOG: Level = Level::DEBUG;
+
+const MAGIC_HDR: &[u8; 8] = b"MAMESOFT";
+const ENDIANNESS: Endianness = Endianness::Little;
+const EXTENSION: &str = "softlist";
+
+static CACHE_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Hash of the cache directory and hash files that the cache was last pruned for, so that creating dispensers over
+/// and over does not keep rescanning the cache directory
+static LAST_PRUNED: Mutex<Option<u64>> = Mutex::new(None);
+
+/// Sets where cached software lists are kept; caching is disabled if there is no directory
+pub fn set_software_list_cache_dir(cache_dir: Option<PathBuf>) {
+	*CACHE_DIR.write().unwrap() = cache_dir;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CacheKey {
+	size: u64,
+	modified: u64,
+	contents_hash: u64,
+}
+
+impl CacheKey {
+	/// Identifies a hash file; the contents are hashed because modification times can be unreliable (e.g. - files
+	/// restored from archives)
+	pub fn new(metadata: &Metadata, contents: &[u8]) -> Option<Self> {
+		let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+		let modified = u64::try_from(modified.as_nanos()).ok()?;
+		let size = metadata.len();
+		let mut hasher = DefaultHasher::new();
+		hasher.write(contents);
+		let contents_hash = hasher.finish();
+		Some(Self {
+			size,
+			modified,
+			contents_hash,
+		})
+	}
+}
+
+mod binary {
+	use binary_serde::BinarySerde;
+
+	#[derive(Clone, Copy, Debug, Default, BinarySerde)]
+	pub struct Header {
+		pub magic: [u8; 8],
+		pub sizes_hash: u64,
+		pub source_size: u64,
+		pub source_modified: u64,
+		pub source_contents_hash: u64,
+		pub source_strindex: u32,
+		pub name_strindex: u32,
+		pub description_strindex: u32,
+		pub software_count: u32,
+		pub part_count: u32,
+		pub pair_count: u32,
+	}
+
+	#[derive(Clone, Copy, Debug, BinarySerde)]
+	pub struct Software {
+		pub name_strindex: u32,
+		pub description_strindex: u32,
+		pub year_strindex: u32,
+		pub publisher_strindex: u32,
+		pub parts_start: u32,
+		pub parts_end: u32,
+		pub infos_start: u32,
+		pub infos_end: u32,
+		pub shared_features_start: u32,
+		pub shared_features_end: u32,
+		pub supported: u8,
+	}
+
+	#[derive(Clone, Copy, Debug, BinarySerde)]
+	pub struct Part {
+		pub name_strindex: u32,
+		pub interface_strindex: u32,
+		pub features_start: u32,
+		pub features_end: u32,
+	}
+
+	#[derive(Clone, Copy, Debug, BinarySerde)]
+	pub struct Pair {
+		pub name_strindex: u32,
+		pub value_strindex: u32,
+	}
+}
+
+/// Retrieves a software list from the cache, if it is there and still matches the hash file
+pub fn load(source: &Path, key: CacheKey) -> Option<SoftwareList> {
+	let filename = cache_filename(source)?;
+	let data = read(&filename).ok()?;
+	let result = decode(&data, source, key);
+	let hit = result.is_some();
+	event!(LOG, "software::cache::load(): filename={filename:?} hit={hit}");
+	result
+}
+
+/// Stores a freshly parsed software list in the cache; failures only cost us the ability to skip parsing next time
+pub fn save(source: &Path, key: CacheKey, software_list: &SoftwareList) {
+	let Some(filename) = cache_filename(source) else {
+		return;
+	};
+	let result = save_file(&filename, &encode(source, key, software_list));
+	event!(LOG, "software::cache::save(): filename={filename:?} result={result:?}");
+}
+
+/// Removes cache files that do not belong to any of these hash files (e.g. - after software list paths change)
+pub fn prune(sources: impl IntoIterator<Item = PathBuf>) {
+	let Some(cache_dir) = CACHE_DIR.read().unwrap().clone() else {
+		return;
+	};
+	let expected = sources
+		.into_iter()
+		.filter_map(|source| cache_filename_in(&cache_dir, &source))
+		.collect::<BTreeSet<_>>();
+
+	// have we already done this?
+	let mut hasher = DefaultHasher::new();
+	cache_dir.hash(&mut hasher);
+	expected.hash(&mut hasher);
+	let prune_hash = hasher.finish();
+	if LAST_PRUNED.lock().unwrap().replace(prune_hash) != Some(prune_hash) {
+		prune_cache_dir(&cache_dir, &expected);
+	}
+}
+
+fn prune_cache_dir(cache_dir: &Path, expected: &BTreeSet<PathBuf>) {
+	let stale = read_dir(cache_dir)
+		.into_iter()
+		.flatten()
+		.filter_map(|entry| Some(entry.ok()?.path()))
+		.filter(|path| path.extension().is_some_and(|x| x == EXTENSION) && !expected.contains(path))
+		.collect::<Vec<_>>();
+	for path in stale {
+		let result = remove_file(&path);
+		event!(
+			LOG,
+			"software::cache::prune_cache_dir(): path={path:?} result={result:?}"
+		);
+	}
+}
+
+fn save_file(filename: &Path, data: &[u8]) -> Result<()> {
+	if let Some(parent) = filename.parent() {
+		std::fs::create_dir_all(parent)?;
+	}
+
+	// write to a temporary file first, so that concurrent readers never see a partial file
+	let temporary_filename = filename.with_extension("softlist.tmp");
+	let mut file = File::create(&temporary_filename)?;
+	file.write_all(data)?;
+	drop(file);
+	std::fs::rename(&temporary_filename, filename)?;
+	Ok(())
+}
+
+fn cache_filename(source: &Path) -> Option<PathBuf> {
+	let cache_dir = CACHE_DIR.read().unwrap().clone()?;
+	cache_filename_in(&cache_dir, source)
+}
+
+fn cache_filename_in(cache_dir: &Path, source: &Path) -> Option<PathBuf> {
+	let stem = source.file_stem()?.to_str()?;
+	let mut hasher = DefaultHasher::new();
+	source.hash(&mut hasher);
+	let file_name = format!("{stem}-{:016x}.{EXTENSION}", hasher.finish());
+	Some(cache_dir.join(file_name))
+}
+
+fn calculate_sizes_hash() -> u64 {
+	let multiplicand = 4729; // arbitrary prime number
+	[
+		binary::Header::SERIALIZED_SIZE,
+		binary::Software::SERIALIZED_SIZE,
+		binary::Part::SERIALIZED_SIZE,
+		binary::Pair::SERIALIZED_SIZE,
+	]
+	.into_iter()
+	.fold(0, |value, item| {
+		u64::overflowing_mul(value, multiplicand).0 ^ (item as u64)
+	})
+}
+
+fn encode(source: &Path, key: CacheKey, software_list: &SoftwareList) -> Vec<u8> {
+	let mut strings = StringTableBuilder::new(software_list.software.len() * 64);
+	let mut software_records = Vec::new();
+	let mut part_records = Vec::new();
+	let mut pair_records = Vec::new();
+
+	for software in &software_list.software {
+		let parts_start = record_count::<binary::Part>(&part_records);
+		for part in &software.parts {
+			let (features_start, features_end) = push_pairs(&mut pair_records, &mut strings, &part.features);
+			let part = binary::Part {
+				name_strindex: strings.lookup(&part.name),
+				interface_strindex: strings.lookup(&part.interface),
+				features_start,
+				features_end,
+			};
+			push_record(&mut part_records, part);
+		}
+		let parts_end = record_count::<binary::Part>(&part_records);
+		let (infos_start, infos_end) = push_pairs(&mut pair_records, &mut strings, &software.infos);
+		let (shared_features_start, shared_features_end) =
+			push_pairs(&mut pair_records, &mut strings, &software.shared_features);
+		let supported = match software.supported {
+			SoftwareSupport::Yes => 0,
+			SoftwareSupport::Partial => 1,
+			SoftwareSupport::No => 2,
+		};
+		let software = binary::Software {
+			name_strindex: strings.lookup(&software.name),
+			description_strindex: strings.lookup(&software.description),
+			year_strindex: strings.lookup(&software.year),
+			publisher_strindex: strings.lookup(&software.publisher),
+			parts_start,
+			parts_end,
+			infos_start,
+			infos_end,
+			shared_features_start,
+			shared_features_end,
+			supported,
+		};
+		push_record(&mut software_records, software);
+	}
+
+	let header = binary::Header {
+		magic: *MAGIC_HDR,
+		sizes_hash: calculate_sizes_hash(),
+		source_size: key.size,
+		source_modified: key.modified,
+		source_contents_hash: key.contents_hash,
+		source_strindex: strings.lookup(&source.to_string_lossy()),
+		name_strindex: strings.lookup(&software_list.name),
+		description_strindex: strings.lookup(&software_list.description),
+		software_count: record_count::<binary::Software>(&software_records),
+		part_count: record_count::<binary::Part>(&part_records),
+		pair_count: record_count::<binary::Pair>(&pair_records),
+	};
+	let mut header_bytes = [0u8; binary::Header::SERIALIZED_SIZE];
+	header.binary_serialize(&mut header_bytes, ENDIANNESS);
+
+	header_bytes
+		.into_iter()
+		.chain(software_records)
+		.chain(part_records)
+		.chain(pair_records)
+		.chain(strings.into_iter())
+		.collect()
+}
+
+/// Appends name/value pairs, returning their range
+fn push_pairs(
+	pair_records: &mut Vec<u8>,
+	strings: &mut StringTableBuilder,
+	pairs: &[(Arc<str>, Arc<str>)],
+) -> (u32, u32) {
+	let start = record_count::<binary::Pair>(pair_records);
+	for (name, value) in pairs {
+		let pair = binary::Pair {
+			name_strindex: strings.lookup(name),
+			value_strindex: strings.lookup(value),
+		};
+		push_record(pair_records, pair);
+	}
+	(start, record_count::<binary::Pair>(pair_records))
+}
+
+fn push_record<T: BinarySerde>(records: &mut Vec<u8>, obj: T) {
+	let pos = records.len();
+	records.resize(pos + T::SERIALIZED_SIZE, 0x00);
+	obj.binary_serialize(&mut records[pos..], ENDIANNESS);
+}
+
+fn record_count<T: BinarySerde>(records: &[u8]) -> u32 {
+	(records.len() / T::SERIALIZED_SIZE).try_into().unwrap()
+}
+
+fn decode(data: &[u8], source: &Path, key: CacheKey) -> Option<SoftwareList> {
+	// check the header
+	let header_data = data.get(..binary::Header::SERIALIZED_SIZE)?;
+	let header = binary::Header::binary_deserialize(header_data, ENDIANNESS).ok()?;
+	let is_match = &header.magic == MAGIC_HDR
+		&& header.sizes_hash == calculate_sizes_hash()
+		&& header.source_size == key.size
+		&& header.source_modified == key.modified
+		&& header.source_contents_hash == key.contents_hash;
+	is_match.then_some(())?;
+
+	// walk the records
+	let mut cursor = &data[binary::Header::SERIALIZED_SIZE..];
+	let software_records = next_records::<binary::Software>(&mut cursor, header.software_count)?;
+	let part_records = next_records::<binary::Part>(&mut cursor, header.part_count)?;
+	let pair_records = next_records::<binary::Pair>(&mut cursor, header.pair_count)?;
+
+	// and what's left is the string table (which has to at least have its beginning and ending magic bytes)
+	let string_table = cursor;
+	(string_table.len() >= 4).then_some(())?;
+	validate_string_table(string_table).ok()?;
+
+	// like the XML parser, identical strings share the same allocation
+	let string_cache = RefCell::new(HashMap::new());
+	let string = |strindex: u32| -> Option<Arc<str>> {
+		if let Some(result) = string_cache.borrow().get(&strindex) {
+			return Some(Arc::clone(result));
+		}
+		let result = Arc::<str>::from(read_string(string_table, strindex).ok()?.as_ref());
+		string_cache.borrow_mut().insert(strindex, result.clone());
+		Some(result)
+	};
+	(*string(header.source_strindex)? == *source.to_string_lossy()).then_some(())?;
+
+	let pairs = |start: u32, end: u32| -> Option<Vec<(Arc<str>, Arc<str>)>> {
+		pair_records
+			.get(usize::try_from(start).ok()?..usize::try_from(end).ok()?)?
+			.iter()
+			.map(|pair| Some((string(pair.name_strindex)?, string(pair.value_strindex)?)))
+			.collect()
+	};
+	let software = software_records
+		.iter()
+		.map(|software| {
+			let supported = match software.supported {
+				0 => SoftwareSupport::Yes,
+				1 => SoftwareSupport::Partial,
+				2 => SoftwareSupport::No,
+				_ => return None,
+			};
+			let parts = part_records
+				.get(usize::try_from(software.parts_start).ok()?..usize::try_from(software.parts_end).ok()?)?
+				.iter()
+				.map(|part| {
+					let part = SoftwarePart {
+						name: string(part.name_strindex)?,
+						interface: string(part.interface_strindex)?,
+						features: pairs(part.features_start, part.features_end)?,
+					};
+					Some(part)
+				})
+				.collect::<Option<Vec<_>>>()?;
+			let software = Software {
+				name: string(software.name_strindex)?,
+				description: string(software.description_strindex)?,
+				year: string(software.year_strindex)?,
+				publisher: string(software.publisher_strindex)?,
+				supported,
+				parts,
+				infos: pairs(software.infos_start, software.infos_end)?,
+				shared_features: pairs(software.shared_features_start, software.shared_features_end)?,
+			};
+			Some(Arc::new(software))
+		})
+		.collect::<Option<Vec<_>>>()?;
+
+	let software_list = SoftwareList {
+		name: string(header.name_strindex)?,
+		description: string(header.description_strindex)?,
+		software,
+		source: Some(source.to_path_buf()),
+		shadowed: Vec::new(),
+	};
+	Some(software_list)
+}
+
+fn next_records<T: BinarySerde>(cursor: &mut &[u8], count: u32) -> Option<Vec<T>> {
+	let len = usize::try_from(count).ok()?.checked_mul(T::SERIALIZED_SIZE)?;
+	let (records, rest) = (len <= cursor.len()).then(|| cursor.split_at(len))?;
+	*cursor = rest;
+	records
+		.chunks_exact(T::SERIALIZED_SIZE)
+		.map(|slice| T::binary_deserialize(slice, ENDIANNESS).ok())
+		.collect()
+}
+
+#[cfg(test)]
+mod test {
+	use std::collections::BTreeSet;
+	use std::fs::read_dir;
+	use std::fs::File;
+	use std::path::Path;
+
+	use tempdir::TempDir;
+	use test_case::test_case;
+
+	use crate::software::SoftwareList;
+
+	use super::CacheKey;
+
+	const KEY: CacheKey = CacheKey {
+		size: 1234,
+		modified: 5678,
+		contents_hash: 9012,
+	};
+
+	#[test_case(0, "/hash/coco_cart.xml", KEY, true)]
+	#[test_case(1, "/hash/coco_flop.xml", KEY, false)]
+	#[test_case(2, "/hash/coco_cart.xml", CacheKey { size: 1235, ..KEY }, false)]
+	#[test_case(3, "/hash/coco_cart.xml", CacheKey { modified: 5679, ..KEY }, false)]
+	#[test_case(4, "/hash/coco_cart.xml", CacheKey { contents_hash: 9013, ..KEY }, false)]
+	pub fn decode(_index: usize, source: &str, key: CacheKey, expected: bool) {
+		let xml = include_str!("test_data/softlist_coco_cart.xml");
+		let software_list = SoftwareList::from_reader(xml.as_bytes()).unwrap();
+		let data = super::encode(Path::new("/hash/coco_cart.xml"), KEY, &software_list);
+		let actual = super::decode(&data, Path::new(source), key);
+		assert_eq!(expected, actual.is_some());
+	}
+
+	#[test]
+	pub fn encode_decode_round_trip() {
+		let xml = include_str!("test_data/softlist_coco_cart.xml");
+		let expected = SoftwareList::from_reader(xml.as_bytes()).unwrap();
+		let data = super::encode(Path::new("/hash/coco_cart.xml"), KEY, &expected);
+		let actual = super::decode(&data, Path::new("/hash/coco_cart.xml"), KEY).unwrap();
+		assert_eq!(expected.name, actual.name);
+		assert_eq!(expected.description, actual.description);
+		assert_eq!(format!("{:?}", expected.software), format!("{:?}", actual.software));
+	}
+
+	#[test]
+	pub fn decode_truncated() {
+		let xml = include_str!("test_data/softlist_coco_cart.xml");
+		let software_list = SoftwareList::from_reader(xml.as_bytes()).unwrap();
+		let data = super::encode(Path::new("/hash/coco_cart.xml"), KEY, &software_list);
+		let actual = super::decode(&data[..data.len() - 1], Path::new("/hash/coco_cart.xml"), KEY);
+		assert!(actual.is_none());
+	}
+
+	#[test]
+	pub fn prune_cache_dir() {
+		let temp_dir = TempDir::new("bletchmame_softlist_cache").unwrap();
+		let cache_dir = temp_dir.path();
+		let kept = super::cache_filename_in(cache_dir, Path::new("/hash/coco_cart.xml")).unwrap();
+		let stale = super::cache_filename_in(cache_dir, Path::new("/old/hash/coco_cart.xml")).unwrap();
+		let unrelated = cache_dir.join("readme.txt");
+		for path in [&kept, &stale, &unrelated] {
+			File::create(path).unwrap();
+		}
+
+		super::prune_cache_dir(cache_dir, &BTreeSet::from([kept.clone()]));
+		let mut actual = read_dir(cache_dir)
+			.unwrap()
+			.map(|entry| entry.unwrap().path())
+			.collect::<Vec<_>>();
+		actual.sort();
+		let mut expected = vec![kept, unrelated];
+		expected.sort();
+		assert_eq!(expected, actual);
+	}
+}
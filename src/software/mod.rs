@@ -1,28 +1,48 @@
 use std::collections::hash_map::Entry;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::fmt::Debug;
-use std::fs::File;
+use std::fs::metadata;
+use std::fs::read;
+use std::fs::read_dir;
 use std::io::BufRead;
-use std::io::BufReader;
+use std::num::NonZeroUsize;
 use std::path::Path;
 use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use std::thread::available_parallelism;
 use std::thread::scope;
 
 use anyhow::Error;
 use anyhow::Result;
+use itertools::Itertools;
 use process::process_xml;
+use serde::Deserialize;
+use serde::Serialize;
+use strum::EnumString;
 
 use crate::info;
 use crate::info::InfoDb;
 use crate::info::View;
 
+mod cache;
 mod process;
 
+pub use self::cache::set_software_list_cache_dir;
+
 pub struct SoftwareList {
 	pub name: Arc<str>,
 	pub description: Arc<str>,
 	pub software: Vec<Arc<Software>>,
+
+	/// The hash file this list was loaded from
+	pub source: Option<PathBuf>,
+
+	/// Hash files for this list in later software list paths, which are hidden by `source`
+	pub shadowed: Vec<PathBuf>,
 }
 
 #[derive(Debug)]
@@ -31,22 +51,96 @@ pub struct Software {
 	pub description: Arc<str>,
 	pub year: Arc<str>,
 	pub publisher: Arc<str>,
+	pub supported: SoftwareSupport,
 	pub parts: Vec<SoftwarePart>,
+
+	/// Free form information (e.g. - "serial" or "usage"), as name/value pairs
+	pub infos: Vec<(Arc<str>, Arc<str>)>,
+
+	/// Features shared by all parts (e.g. - "compatibility" or "requirement"), as name/value pairs
+	pub shared_features: Vec<(Arc<str>, Arc<str>)>,
 }
 
 #[derive(Debug)]
 pub struct SoftwarePart {
-	#[allow(dead_code)]
 	pub name: Arc<str>,
-
 	pub interface: Arc<str>,
+
+	/// Features of this part (e.g. - "slot" or "part_id"), as name/value pairs
+	pub features: Vec<(Arc<str>, Arc<str>)>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, EnumString, strum_macros::Display)]
+#[serde(rename_all = "camelCase")]
+pub enum SoftwareSupport {
+	#[default]
+	#[strum(serialize = "yes", to_string = "Supported")]
+	Yes,
+	#[strum(serialize = "partial", to_string = "Partially Supported")]
+	Partial,
+	#[strum(serialize = "no", to_string = "Not Supported")]
+	No,
+}
+
+impl Software {
+	pub fn shared_feature(&self, name: &str) -> Option<&str> {
+		self.shared_features
+			.iter()
+			.find(|(x, _)| x.as_ref() == name)
+			.map(|(_, value)| value.as_ref())
+	}
+
+	/// Whether a machine whose software list has the specified filter can run this software, following MAME's rules;
+	/// the filter and the "compatibility" and "incompatibility" shared features are comma separated lists
+	pub fn is_compatible(&self, filter: &str) -> bool {
+		if filter.is_empty() {
+			return true;
+		}
+		let filter = filter.split(',').collect::<Vec<_>>();
+		let matches = |feature| {
+			self.shared_feature(feature)
+				.is_some_and(|x| x.split(',').any(|x| filter.contains(&x)))
+		};
+		if matches("incompatibility") {
+			return false;
+		}
+		self.shared_feature("compatibility").is_none() || matches("compatibility")
+	}
+}
+
+impl SoftwarePart {
+	pub fn feature(&self, name: &str) -> Option<&str> {
+		self.features
+			.iter()
+			.find(|(x, _)| x.as_ref() == name)
+			.map(|(_, value)| value.as_ref())
+	}
+
+	/// The part's name, along with its "part_id" (e.g. - "Disk 1") if it has one
+	pub fn display_name(&self) -> String {
+		match self.feature("part_id") {
+			Some(part_id) => format!("{part_id} ({})", self.name),
+			None => self.name.to_string(),
+		}
+	}
 }
 
 impl SoftwareList {
+	/// Loads a software list from a hash file, going through the binary cache when possible
 	pub fn load(path: impl AsRef<Path>) -> Result<Self> {
-		let file = File::open(path)?;
-		let file = BufReader::new(file);
-		Self::from_reader(file)
+		let path = path.as_ref();
+		let contents = read(path)?;
+		let cache_key = cache::CacheKey::new(&metadata(path)?, &contents);
+		if let Some(result) = cache_key.and_then(|key| cache::load(path, key)) {
+			return Ok(result);
+		}
+
+		let mut result = Self::from_reader(contents.as_slice())?;
+		result.source = Some(path.to_path_buf());
+		if let Some(key) = cache_key {
+			cache::save(path, key, &result);
+		}
+		Ok(result)
 	}
 
 	pub fn from_reader(reader: impl BufRead) -> Result<Self> {
@@ -60,6 +154,7 @@ impl Debug for SoftwareList {
 			.field("name", &self.name)
 			.field("description", &self.description)
 			.field("software.len()", &self.software.len())
+			.field("source", &self.source)
 			.finish_non_exhaustive()
 	}
 }
@@ -72,6 +167,15 @@ pub struct SoftwareListDispenser<'a> {
 
 impl<'a> SoftwareListDispenser<'a> {
 	pub fn new(info_db: &'a InfoDb, software_list_paths: &'a [String]) -> Self {
+		// cached software lists for hash files we would never load are just taking up space
+		let names = info_db.software_lists().iter().map(|x| x.name()).collect::<Vec<_>>();
+		let sources = software_list_paths
+			.iter()
+			.filter(|path| !path.is_empty())
+			.cartesian_product(names)
+			.map(|(path, name)| Path::new(path).join(name).with_extension("xml"));
+		cache::prune(sources);
+
 		Self {
 			info_db,
 			software_list_paths,
@@ -98,50 +202,266 @@ impl<'a> SoftwareListDispenser<'a> {
 		Ok((info_db_software_list, software_list))
 	}
 
-	pub fn get_all(&mut self) -> Vec<(info::SoftwareList<'a>, Arc<SoftwareList>)> {
-		scope(|scope| {
-			let info_db = self.info_db;
-			let paths: &[String] = self.software_list_paths;
-			let threads = info_db
-				.software_lists()
-				.iter()
-				.map(|info_db_software_list| {
-					scope.spawn(move || {
-						load_software_list(paths, info_db_software_list.name())
-							.map(|software_list| (info_db_software_list, software_list))
-					})
+	pub fn is_empty(&self) -> bool {
+		self.map.is_empty()
+	}
+}
+
+/// How far along we are in loading all software lists
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SoftwareListProgress {
+	pub loaded: usize,
+	pub total: usize,
+}
+
+/// Loads the named software lists on a pool of worker threads (no more than there are CPUs), invoking `progress` as
+/// each list completes; returns `None` if `cancelled` was set before everything was loaded
+pub fn load_software_lists(
+	software_list_paths: &[String],
+	names: &[String],
+	cancelled: &AtomicBool,
+	progress: impl Fn(SoftwareListProgress) + Sync,
+) -> Option<Vec<(String, Arc<SoftwareList>)>> {
+	let total = names.len();
+	let worker_count = total.min(available_parallelism().map_or(1, NonZeroUsize::get));
+	let next_index = AtomicUsize::new(0);
+	let loaded = AtomicUsize::new(0);
+	let mut results = scope(|scope| {
+		let next_index = &next_index;
+		let loaded = &loaded;
+		let progress = &progress;
+		let threads = (0..worker_count)
+			.map(|_| {
+				scope.spawn(move || {
+					// each worker keeps taking the next list until there are none left (or we are cancelled)
+					let mut results = Vec::new();
+					while !cancelled.load(Ordering::Relaxed) {
+						let index = next_index.fetch_add(1, Ordering::Relaxed);
+						let Some(name) = names.get(index) else {
+							break;
+						};
+						let software_list = load_software_list(software_list_paths, name).ok();
+						let loaded = loaded.fetch_add(1, Ordering::Relaxed) + 1;
+						progress(SoftwareListProgress { loaded, total });
+						if let Some(software_list) = software_list {
+							results.push((index, name.clone(), software_list));
+						}
+					}
+					results
 				})
-				.collect::<Vec<_>>();
+			})
+			.collect::<Vec<_>>();
+
+		threads
+			.into_iter()
+			.flat_map(|handle| handle.join().unwrap())
+			.collect::<Vec<_>>()
+	});
+	results.sort_by_key(|(index, _, _)| *index);
+	let results = results.into_iter().map(|(_, name, x)| (name, x));
+	(!cancelled.load(Ordering::Relaxed)).then(|| results.collect())
+}
+
+/// The images (device tag and software name) to load for `machine` to run `software`, with each part going into its
+/// own device (e.g. - "flop1" and "flop2" for a two disk game); parts beyond the first are only mounted when
+/// `mount_all_parts` is set.  Returns `None` if the machine lacks a device for the first part
+pub fn software_initial_loads(
+	machine: info::Machine<'_>,
+	software: &Software,
+	mount_all_parts: bool,
+) -> Option<Vec<(Arc<str>, Arc<str>)>> {
+	let choices = software_part_choices(machine, software);
+	let part_count = if mount_all_parts {
+		software.parts.len()
+	} else {
+		software.parts.len().min(1)
+	};
+	let assignments = default_part_assignments(&choices, part_count);
+	assignments
+		.contains(&Some(0))
+		.then(|| software_part_loads(software, &choices, &assignments))
+}
 
-			threads
-				.into_iter()
-				.filter_map(|handle| handle.join().unwrap().ok())
-				.collect::<Vec<_>>()
+/// For each device in `machine` that can load at least one of `software`'s parts, the device tag and the indexes of
+/// those parts
+pub fn software_part_choices(machine: info::Machine<'_>, software: &Software) -> Vec<(Arc<str>, Vec<usize>)> {
+	machine
+		.devices()
+		.iter()
+		.filter_map(|dev| {
+			let part_indexes = software
+				.parts
+				.iter()
+				.positions(|part| part.interface.as_ref() == dev.interface())
+				.collect::<Vec<_>>();
+			(!part_indexes.is_empty()).then(|| (Arc::<str>::from(dev.tag()), part_indexes))
 		})
+		.collect()
+}
+
+/// Assigns each part (in order) to the first device that can load it and that has not already been taken by an
+/// earlier part; the result is the chosen part index (if any) for each entry in `choices`
+pub fn default_part_assignments(choices: &[(Arc<str>, Vec<usize>)], part_count: usize) -> Vec<Option<usize>> {
+	let mut assignments = vec![None; choices.len()];
+	for part_index in 0..part_count {
+		let device_index =
+			(0..choices.len()).find(|&index| assignments[index].is_none() && choices[index].1.contains(&part_index));
+		if let Some(device_index) = device_index {
+			assignments[device_index] = Some(part_index);
+		}
 	}
+	assignments
+}
 
-	pub fn is_empty(&self) -> bool {
-		self.map.is_empty()
+/// The images (device tag and "software:part") to load for the chosen part (if any) of each entry in `choices`
+pub fn software_part_loads(
+	software: &Software,
+	choices: &[(Arc<str>, Vec<usize>)],
+	assignments: &[Option<usize>],
+) -> Vec<(Arc<str>, Arc<str>)> {
+	choices
+		.iter()
+		.zip(assignments)
+		.filter_map(|((tag, _), part_index)| {
+			// single part software does not need the part to be spelled out
+			let part = software.parts.get((*part_index)?)?;
+			let image = if software.parts.len() > 1 {
+				format!("{}:{}", software.name, part.name).into()
+			} else {
+				software.name.clone()
+			};
+			Some((tag.clone(), image))
+		})
+		.collect()
+}
+
+/// Software lists with hash files in more than one software list path, along with those files; only the first of
+/// each is ever loaded
+pub fn shadowed_software_lists(paths: &[String]) -> Vec<(String, Vec<PathBuf>)> {
+	let mut map = BTreeMap::<String, Vec<PathBuf>>::new();
+	for entries in paths.iter().filter(|&path| !path.is_empty()).flat_map(read_dir) {
+		for path in entries.flatten().map(|entry| entry.path()) {
+			if let Some(name) = path
+				.file_stem()
+				.and_then(|x| x.to_str())
+				.filter(|_| path.extension().is_some_and(|x| x == "xml"))
+			{
+				map.entry(name.to_string()).or_default().push(path);
+			}
+		}
 	}
+	map.into_iter().filter(|(_, files)| files.len() > 1).collect()
 }
 
 fn load_software_list(paths: &[String], name: &str) -> Result<Arc<SoftwareList>> {
 	let mut err = Error::msg("Error loading software list: No paths specified");
-	paths
-		.iter()
-		.filter(|&path| !path.is_empty())
-		.filter_map(|path| {
-			let mut path = PathBuf::from(path);
-			path.push(name);
-			path.set_extension("xml");
+	let mut software_list = None;
+	let mut shadowed = Vec::new();
+	for path in paths.iter().filter(|&path| !path.is_empty()) {
+		let mut path = PathBuf::from(path);
+		path.push(name);
+		path.set_extension("xml");
+		if software_list.is_some() {
+			if path.is_file() {
+				shadowed.push(path);
+			}
+		} else {
 			match SoftwareList::load(&path) {
-				Ok(x) => Some(x.into()),
-				Err(e) => {
-					err = e;
-					None
-				}
+				Ok(x) => software_list = Some(x),
+				Err(e) => err = e,
 			}
-		})
-		.next()
-		.ok_or(err)
+		}
+	}
+	let mut software_list = software_list.ok_or(err)?;
+	software_list.shadowed = shadowed;
+	Ok(software_list.into())
+}
+
+#[cfg(test)]
+mod test {
+	use std::sync::atomic::AtomicBool;
+	use std::sync::Arc;
+	use std::sync::Mutex;
+
+	use test_case::test_case;
+
+	use crate::info::InfoDb;
+
+	use super::load_software_lists;
+	use super::SoftwareList;
+	use super::SoftwareListProgress;
+
+	#[test_case(0, false, Some(0), &[1, 2, 3])]
+	#[test_case(1, true, None, &[])]
+	pub fn load_software_lists_progress(
+		_index: usize,
+		cancelled: bool,
+		expected_count: Option<usize>,
+		expected_loaded: &[usize],
+	) {
+		let names = ["coco_cart", "coco_flop", "msx1_cart"].map(String::from);
+		let cancelled = AtomicBool::new(cancelled);
+		let reports = Mutex::new(Vec::new());
+		let result = load_software_lists(&[], &names, &cancelled, |x| reports.lock().unwrap().push(x));
+		let actual_count = result.map(|x| x.len());
+
+		let mut reports = reports.into_inner().unwrap();
+		reports.sort_by_key(|x| x.loaded);
+		let expected_reports = expected_loaded
+			.iter()
+			.map(|&loaded| SoftwareListProgress { loaded, total: 3 })
+			.collect::<Vec<_>>();
+		assert_eq!((expected_count, expected_reports), (actual_count, reports));
+	}
+
+	#[test_case(0, "clowns", "COCO", true)]
+	#[test_case(1, "clowns", "", true)]
+	#[test_case(2, "amazing", "COCO", false)]
+	#[test_case(3, "amazing", "COCO3", true)]
+	#[test_case(4, "amazing", "COCO,COCO3", true)]
+	#[test_case(5, "amazing", "", true)]
+	pub fn is_compatible(_index: usize, software_name: &str, filter: &str, expected: bool) {
+		let xml = include_str!("test_data/softlist_coco_cart.xml");
+		let software_list = SoftwareList::from_reader(xml.as_bytes()).unwrap();
+		let software = software_list.software.iter().find(|x| x.name.as_ref() == software_name);
+		let actual = software.unwrap().is_compatible(filter);
+		assert_eq!(expected, actual);
+	}
+
+	#[test_case(0, &[&[0, 1], &[0, 1]], 2, &[Some(0), Some(1)])]
+	#[test_case(1, &[&[0, 1], &[0, 1]], 1, &[Some(0), None])]
+	#[test_case(2, &[&[0, 1]], 2, &[Some(0)])]
+	#[test_case(3, &[&[1], &[0, 1]], 2, &[Some(1), Some(0)])]
+	#[test_case(4, &[&[0], &[0]], 2, &[Some(0), None])]
+	pub fn default_part_assignments(
+		_index: usize,
+		choices: &[&[usize]],
+		part_count: usize,
+		expected: &[Option<usize>],
+	) {
+		let choices = choices
+			.iter()
+			.map(|x| (Arc::<str>::from(""), x.to_vec()))
+			.collect::<Vec<_>>();
+		let actual = super::default_part_assignments(&choices, part_count);
+		assert_eq!(expected, actual.as_slice());
+	}
+
+	#[test_case(0, true, &[("ext:fdc:wd17xx:0:525dd", "multi:flop1"), ("ext:fdc:wd17xx:1:525dd", "multi:flop2")])]
+	#[test_case(1, false, &[("ext:fdc:wd17xx:0:525dd", "multi:flop1")])]
+	pub fn software_initial_loads(_index: usize, mount_all_parts: bool, expected: &[(&str, &str)]) {
+		let xml = include_str!("../info/test_data/listxml_coco.xml");
+		let info_db = InfoDb::from_listxml_output(xml.as_bytes(), |_| false).unwrap().unwrap();
+		let machine = info_db.machines().find("coco").unwrap();
+		let xml = r#"<softwarelist name="coco_flop"><software name="multi">
+			<part name="flop1" interface="floppy_5_25"/><part name="flop2" interface="floppy_5_25"/>
+			</software></softwarelist>"#;
+		let software_list = SoftwareList::from_reader(xml.as_bytes()).unwrap();
+		let actual = super::software_initial_loads(machine, &software_list.software[0], mount_all_parts).unwrap();
+		let actual = actual
+			.iter()
+			.map(|(tag, image)| (tag.as_ref(), image.as_ref()))
+			.collect::<Vec<_>>();
+		assert_eq!(expected, actual.as_slice());
+	}
 }
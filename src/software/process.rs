@@ -20,6 +20,7 @@ enum Phase {
 	SoftwareDescription,
 	SoftwareYear,
 	SoftwarePublisher,
+	SoftwarePart,
 }
 
 const TEXT_CAPTURE_PHASES: &[Phase] = &[
@@ -50,6 +51,8 @@ impl State {
 				name: empty_str.clone(),
 				description: empty_str.clone(),
 				software: Vec::new(),
+				source: None,
+				shadowed: Vec::new(),
 			},
 			current_software: None,
 		}
@@ -65,15 +68,19 @@ impl State {
 				Some(Phase::SoftwareList)
 			}
 			(Phase::SoftwareList, b"software") => {
-				let [name] = evt.find_attributes([b"name"])?;
+				let [name, supported] = evt.find_attributes([b"name", b"supported"])?;
 				let name = self.string(&name.unwrap_or_default());
+				let supported = supported.and_then(|x| x.parse().ok()).unwrap_or_default();
 
 				let software = Software {
 					name,
 					description: self.empty_str.clone(),
 					year: self.empty_str.clone(),
 					publisher: self.empty_str.clone(),
+					supported,
 					parts: Vec::new(),
+					infos: Vec::new(),
+					shared_features: Vec::new(),
 				};
 				self.current_software = Some(software);
 				Some(Phase::Software)
@@ -81,12 +88,38 @@ impl State {
 			(Phase::Software, b"description") => Some(Phase::SoftwareDescription),
 			(Phase::Software, b"year") => Some(Phase::SoftwareYear),
 			(Phase::Software, b"publisher") => Some(Phase::SoftwarePublisher),
+			(Phase::Software, b"info") => {
+				if let Some(info) = self.name_value_pair(&evt)? {
+					self.current_software.as_mut().unwrap().infos.push(info);
+				}
+				None
+			}
+			(Phase::Software, b"sharedfeat") => {
+				if let Some(feature) = self.name_value_pair(&evt)? {
+					self.current_software.as_mut().unwrap().shared_features.push(feature);
+				}
+				None
+			}
 			(Phase::Software, b"part") => {
 				let [name, interface] = evt.find_attributes([b"name", b"interface"])?;
 				if let Some((name, interface)) = Option::zip(name, interface) {
 					let (name, interface) = (name.into(), interface.into());
-					let part = SoftwarePart { name, interface };
+					let features = Vec::new();
+					let part = SoftwarePart {
+						name,
+						interface,
+						features,
+					};
 					self.current_software.as_mut().unwrap().parts.push(part);
+					Some(Phase::SoftwarePart)
+				} else {
+					None
+				}
+			}
+			(Phase::SoftwarePart, b"feature") => {
+				if let Some(feature) = self.name_value_pair(&evt)? {
+					let part = self.current_software.as_mut().unwrap().parts.last_mut().unwrap();
+					part.features.push(feature);
 				}
 				None
 			}
@@ -119,6 +152,12 @@ impl State {
 		Ok(())
 	}
 
+	fn name_value_pair(&mut self, evt: &XmlElement<'_>) -> Result<Option<(Arc<str>, Arc<str>)>> {
+		let [name, value] = evt.find_attributes([b"name", b"value"])?;
+		let result = Option::zip(name, value).map(|(name, value)| (self.string(&name), self.string(&value)));
+		Ok(result)
+	}
+
 	fn string(&mut self, s: &str) -> Arc<str> {
 		self.strings.get(s).cloned().unwrap_or_else(|| {
 			let result = Arc::<str>::from(s);
@@ -173,9 +212,12 @@ pub fn process_xml(reader: impl BufRead) -> Result<SoftwareList> {
 #[cfg(test)]
 mod test {
 	use std::io::BufReader;
+	use std::sync::Arc;
 
 	use test_case::test_case;
 
+	use crate::software::SoftwareSupport;
+
 	use super::process_xml;
 
 	#[test_case(0, include_str!("test_data/softlist_coco_cart.xml"), ("coco_cart", "Tandy Radio Shack Color Computer cartridges", 112))]
@@ -207,4 +249,40 @@ mod test {
 		);
 		assert_eq!(expected, actual);
 	}
+
+	#[test_case(0, include_str!("test_data/softlist_coco_cart.xml"), "clowns", SoftwareSupport::Yes, &[("developer", "Datasoft"), ("author", "Steve Bjork"), ("serial", "26-3087")], &[], &[])]
+	#[test_case(1, include_str!("test_data/softlist_coco_cart.xml"), "amazing", SoftwareSupport::Yes, &[("developer", "ZCT Systems"), ("author", "Greg L. Zumwalt & Mark W. Easter"), ("serial", "26-3160")], &[("compatibility", "COCO3")], &[])]
+	#[test_case(2, include_str!("test_data/softlist_msx1_cart.xml"), "hydlide2", SoftwareSupport::Partial, &[("serial", "TEX-70"), ("alt_title", "ハイドライドII")], &[], &[("pcb", "TA-621M"), ("slot", "ascii8_sram"), ("mapper", "M60002-0125SP"), ("sram", "HM6116LP-3")])]
+	pub fn software_details(
+		_index: usize,
+		xml: &str,
+		name: &str,
+		expected_supported: SoftwareSupport,
+		expected_infos: &[(&str, &str)],
+		expected_shared_features: &[(&str, &str)],
+		expected_part_features: &[(&str, &str)],
+	) {
+		let reader = BufReader::new(xml.as_bytes());
+		let software_list = process_xml(reader).unwrap();
+		let software = software_list.software.iter().find(|x| x.name.as_ref() == name).unwrap();
+		fn pairs(pairs: &[(Arc<str>, Arc<str>)]) -> Vec<(&str, &str)> {
+			pairs
+				.iter()
+				.map(|(name, value)| (name.as_ref(), value.as_ref()))
+				.collect()
+		}
+		let actual = (
+			software.supported,
+			pairs(&software.infos),
+			pairs(&software.shared_features),
+			pairs(&software.parts[0].features),
+		);
+		let expected = (
+			expected_supported,
+			expected_infos.to_vec(),
+			expected_shared_features.to_vec(),
+			expected_part_features.to_vec(),
+		);
+		assert_eq!(expected, actual);
+	}
 }
@@ -1,4 +1,5 @@
 mod parse;
+mod schema;
 
 use std::borrow::Cow;
 use std::fmt::Debug;
@@ -14,8 +15,11 @@ use tracing::Level;
 
 use crate::debugstr::DebugString;
 use crate::status::parse::parse_update;
+use crate::status::parse::parse_update_strict;
 use crate::version::MameVersion;
 
+pub use self::schema::status_schema_dtd;
+
 const LOG: Level = Level::TRACE;
 
 #[derive(Clone, Default)]
@@ -39,7 +43,9 @@ impl Status {
 			let is_paused = running.is_paused.unwrap_or(status_running.is_paused);
 			let is_throttled = running.is_throttled.unwrap_or(status_running.is_throttled);
 			let throttle_rate = running.throttle_rate.unwrap_or(status_running.throttle_rate);
+			let is_recording = running.is_recording.unwrap_or(status_running.is_recording);
 			let sound_attenuation = running.sound_attenuation.unwrap_or(status_running.sound_attenuation);
+			let window_size = running.window_size.or(status_running.window_size);
 			let images = if let Some(images) = running.images {
 				images
 					.into_iter()
@@ -73,7 +79,9 @@ impl Status {
 				is_paused,
 				is_throttled,
 				throttle_rate,
+				is_recording,
 				sound_attenuation,
+				window_size,
 				images,
 				slots,
 			}
@@ -103,7 +111,9 @@ pub struct Running {
 	pub is_paused: bool,
 	pub is_throttled: bool,
 	pub throttle_rate: f32,
+	pub is_recording: bool,
 	pub sound_attenuation: i32,
+	pub window_size: Option<(u32, u32)>,
 	pub images: Arc<[Image]>,
 	pub slots: Arc<[Slot]>,
 }
@@ -142,6 +152,11 @@ impl Update {
 	pub fn parse(reader: impl BufRead) -> Result<Self> {
 		parse_update(reader)
 	}
+
+	/// Like `parse()`, but rejects anything the plugin is not expected to emit (see `schema.rs`)
+	pub fn parse_strict(reader: impl BufRead) -> Result<Self> {
+		parse_update_strict(reader)
+	}
 }
 
 impl Debug for Update {
@@ -159,7 +174,9 @@ struct RunningUpdate {
 	pub is_paused: Option<bool>,
 	pub is_throttled: Option<bool>,
 	pub throttle_rate: Option<f32>,
+	pub is_recording: Option<bool>,
 	pub sound_attenuation: Option<i32>,
+	pub window_size: Option<(u32, u32)>,
 	pub images: Option<Vec<ImageUpdate>>,
 	pub slots: Option<Vec<Slot>>,
 }
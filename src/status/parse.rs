@@ -1,5 +1,6 @@
 use std::collections::HashSet;
 use std::io::BufRead;
+use std::io::Read;
 use std::sync::Arc;
 
 use anyhow::Error;
@@ -9,6 +10,7 @@ use tracing::Level;
 
 use crate::parse::normalize_tag;
 use crate::parse::parse_mame_bool;
+use crate::status::schema::validate_status_xml;
 use crate::status::ImageDetails;
 use crate::status::ImageFormat;
 use crate::status::ImageUpdate;
@@ -80,19 +82,32 @@ impl State {
 				Some(Phase::Status)
 			}
 			(Phase::Status, b"video") => {
-				let [throttled, throttle_rate] = evt.find_attributes([b"throttled", b"throttle_rate"])?;
+				let [throttled, throttle_rate, is_recording, window_width, window_height] = evt.find_attributes([
+					b"throttled",
+					b"throttle_rate",
+					b"is_recording",
+					b"window_width",
+					b"window_height",
+				])?;
 				let throttled = throttled.map(parse_mame_bool).transpose()?;
+				let is_recording = is_recording.map(parse_mame_bool).transpose()?;
 				let throttle_rate = throttle_rate.map(|x| x.parse::<f32>()).transpose()?;
+				let window_width = window_width.map(|x| x.parse::<u32>()).transpose()?;
+				let window_height = window_height.map(|x| x.parse::<u32>()).transpose()?;
+				let window_size = window_width.zip(window_height);
 
 				event!(
 					LOG,
-					"status State::handle_start(): throttled={:?} throttle_rate={:?}",
+					"status State::handle_start(): throttled={:?} throttle_rate={:?} window_size={:?}",
 					throttled,
-					throttle_rate
+					throttle_rate,
+					window_size
 				);
 
 				self.running.is_throttled = throttled.or(self.running.is_throttled);
 				self.running.throttle_rate = throttle_rate.or(self.running.throttle_rate);
+				self.running.is_recording = is_recording.or(self.running.is_recording);
+				self.running.window_size = window_size.or(self.running.window_size);
 				None
 			}
 			(Phase::Status, b"sound") => {
@@ -298,6 +313,15 @@ pub fn parse_update(reader: impl BufRead) -> Result<Update> {
 	Ok(result)
 }
 
+/// Parses an update, first checking that it contains nothing unexpected; this is for tests and diagnostics, as
+/// ordinarily we want to tolerate newer plugins
+pub fn parse_update_strict(mut reader: impl BufRead) -> Result<Update> {
+	let mut xml = Vec::new();
+	reader.read_to_end(&mut xml)?;
+	validate_status_xml(&xml)?;
+	parse_update(xml.as_slice())
+}
+
 fn statusxml_err(reader: &XmlReader<impl BufRead>, e: impl Into<Error>) -> Error {
 	let message = format!("Error parsing status XML at position {}", reader.buffer_position());
 	e.into().context(message)
@@ -311,6 +335,7 @@ mod test {
 	use test_case::test_case;
 
 	use super::parse_update;
+	use super::parse_update_strict;
 
 	#[test_case(0, include_str!("test_data/status_mame0226_coco2b_1.xml"))]
 	#[test_case(1, include_str!("test_data/status_mame0227_coco2b_1.xml"))]
@@ -325,6 +350,10 @@ mod test {
 		let reader = BufReader::new(xml.as_bytes());
 		let result = parse_update(reader);
 		assert_matches!(result, Ok(_));
+
+		let reader = BufReader::new(xml.as_bytes());
+		let result = parse_update_strict(reader);
+		assert_matches!(result, Ok(_));
 	}
 
 	#[test_case(0, include_str!("test_data/status_mame0226_coco2b_1.xml"), Some(true), Some(1.0))]
@@ -341,4 +370,12 @@ mod test {
 		let actual = (running.is_throttled, running.throttle_rate);
 		assert_eq!(expected, actual);
 	}
+
+	#[test_case(0, include_str!("test_data/status_mame0226_coco2b_1.xml"), Some(false))]
+	#[test_case(1, include_str!("test_data/status_mame0270_coco2b_3.xml"), None)]
+	fn recording(_index: usize, xml: &str, expected: Option<bool>) {
+		let reader = BufReader::new(xml.as_bytes());
+		let running = parse_update(reader).unwrap().running.unwrap();
+		assert_eq!(expected, running.is_recording);
+	}
 }
@@ -0,0 +1,214 @@
+//! The elements and attributes the `worker_ui` plugin may emit in status XML
+//!
+//! Much of what the plugin emits is not (yet) used by the parser, but it is all described here so that strict parsing
+//! can reject anything unexpected, catching drift between the plugin and the parser early.
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+use anyhow::Result;
+use itertools::Itertools;
+use quick_xml::events::BytesStart;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+
+pub struct SchemaElement {
+	/// Slash separated path from the root (e.g. - "status/images/image")
+	pub path: &'static str,
+	pub attributes: &'static [&'static str],
+	pub has_text: bool,
+
+	/// Whether the parser does anything with this element, as opposed to skipping it
+	pub parsed: bool,
+}
+
+#[rustfmt::skip]
+pub const STATUS_SCHEMA: &[SchemaElement] = &[
+	element("status", &["app_name", "app_version", "app_build", "gamename", "romname", "softname", "time", "pid", "polling_input_seq", "natural_keyboard_in_use", "paused", "startup_text", "debugger_present", "show_profiler", "has_input_using_mouse", "has_mouse_enabled_problem", "phase"], true),
+	element("status/video", &["speed_percent", "frameskip", "effective_frameskip", "throttled", "throttle_rate", "is_recording", "window_width", "window_height"], true),
+	element("status/sound", &["attenuation"], true),
+	element("status/cheats", &[], false),
+	element("status/cheats/cheat", &["id", "enabled", "description", "has_run_script", "has_on_script", "has_off_script", "has_change_script", "comment"], false),
+	element("status/cheats/cheat/parameter", &["value", "minimum", "maximum", "step"], false),
+	element("status/cheats/cheat/parameter/item", &["value", "text"], false),
+	element("status/images", &[], true),
+	element("status/images/image", &["tag", "filename", "instance_name", "is_readable", "is_writeable", "is_creatable", "must_be_loaded"], true),
+	element("status/images/image/details", &["instance_name", "is_readable", "is_writeable", "is_creatable", "must_be_loaded"], true),
+	element("status/images/image/details/format", &["name", "description", "option_spec"], true),
+	text_element("status/images/image/details/format/extension", true),
+	element("status/images/image/formats", &[], false),
+	element("status/images/image/formats/format", &["name", "description", "optspec"], false),
+	text_element("status/images/image/formats/format/extension", false),
+	element("status/cassettes", &[], false),
+	element("status/cassettes/cassette", &["tag", "is_stopped", "is_playing", "is_recording", "motor_state", "speaker_state", "position", "length"], false),
+	element("status/slots", &[], true),
+	element("status/slots/slot", &["name", "fixed", "has_selectable_options", "current_option"], true),
+	element("status/slots/slot/option", &["name", "selectable"], true),
+	element("status/inputs", &[], false),
+	element("status/inputs/input", &["port_tag", "mask", "class", "group", "type", "player", "is_analog", "name", "first_keyboard_code", "value"], false),
+	element("status/inputs/input/seq", &["type", "tokens"], false),
+	element("status/input_devices", &[], false),
+	element("status/input_devices/class", &["name", "enabled", "multi"], false),
+	element("status/input_devices/class/device", &["name", "id", "devindex"], false),
+	element("status/input_devices/class/device/item", &["name", "token", "code"], false),
+];
+
+const fn element(path: &'static str, attributes: &'static [&'static str], parsed: bool) -> SchemaElement {
+	SchemaElement {
+		path,
+		attributes,
+		has_text: false,
+		parsed,
+	}
+}
+
+const fn text_element(path: &'static str, parsed: bool) -> SchemaElement {
+	SchemaElement {
+		path,
+		attributes: &[],
+		has_text: true,
+		parsed,
+	}
+}
+
+#[derive(thiserror::Error, Debug)]
+enum ThisError {
+	#[error("Unknown element {0} in status XML")]
+	UnknownElement(String),
+	#[error("Unknown attribute {1} on element {0} in status XML")]
+	UnknownAttribute(String, String),
+	#[error("Unexpected text in element {0} in status XML")]
+	UnexpectedText(String),
+}
+
+/// Checks that status XML only contains elements and attributes described by `STATUS_SCHEMA`
+pub fn validate_status_xml(xml: &[u8]) -> Result<()> {
+	let mut reader = Reader::from_reader(xml);
+	let mut buf = Vec::new();
+	let mut stack = Vec::<&SchemaElement>::new();
+	loop {
+		match reader.read_event_into(&mut buf)? {
+			Event::Start(evt) => {
+				let element = validate_element(stack.last().copied(), &evt)?;
+				stack.push(element);
+			}
+			Event::Empty(evt) => {
+				validate_element(stack.last().copied(), &evt)?;
+			}
+			Event::End(_) => {
+				stack.pop();
+			}
+			Event::Text(text) => {
+				if let Some(element) = stack.last().filter(|x| !x.has_text) {
+					if !text.iter().all(u8::is_ascii_whitespace) {
+						return Err(ThisError::UnexpectedText(element.path.to_string()).into());
+					}
+				}
+			}
+			Event::Eof => break,
+			_ => {}
+		}
+		buf.clear();
+	}
+	Ok(())
+}
+
+fn validate_element(parent: Option<&SchemaElement>, evt: &BytesStart<'_>) -> Result<&'static SchemaElement> {
+	let name = String::from_utf8_lossy(evt.name().as_ref()).into_owned();
+	let path = match parent {
+		Some(parent) => format!("{}/{}", parent.path, name),
+		None => name,
+	};
+	let element = STATUS_SCHEMA
+		.iter()
+		.find(|x| x.path == path)
+		.ok_or_else(|| ThisError::UnknownElement(path.clone()))?;
+	for attribute in evt.attributes() {
+		let attribute = attribute?;
+		let key = String::from_utf8_lossy(attribute.key.as_ref());
+		if !element.attributes.contains(&key.as_ref()) {
+			return Err(ThisError::UnknownAttribute(path, key.into_owned()).into());
+		}
+	}
+	Ok(element)
+}
+
+/// Describes `STATUS_SCHEMA` as a DTD; elements of the same name in different places are merged, as DTDs require
+pub fn status_schema_dtd() -> String {
+	#[derive(Default)]
+	struct DtdElement<'a> {
+		children: BTreeSet<&'a str>,
+		attributes: BTreeSet<&'a str>,
+		has_text: bool,
+		parsed: bool,
+	}
+
+	let mut elements = BTreeMap::<&str, DtdElement<'_>>::new();
+	for element in STATUS_SCHEMA {
+		let (parent_path, name) = element.path.rsplit_once('/').unwrap_or(("", element.path));
+		let entry = elements.entry(name).or_default();
+		entry.attributes.extend(element.attributes);
+		entry.has_text |= element.has_text;
+		entry.parsed |= element.parsed;
+		if let Some(parent_name) = parent_path.rsplit('/').next().filter(|x| !x.is_empty()) {
+			elements.entry(parent_name).or_default().children.insert(name);
+		}
+	}
+
+	let mut result = String::new();
+	let root = STATUS_SCHEMA[0].path;
+	writeln!(result, "<!DOCTYPE {root} [").unwrap();
+	for (name, element) in &elements {
+		let children = element.children.iter().join("|");
+		let content = match (children.is_empty(), element.has_text) {
+			(true, false) => "EMPTY".to_string(),
+			(true, true) => "(#PCDATA)".to_string(),
+			(false, false) => format!("({children})*"),
+			(false, true) => format!("(#PCDATA|{children})*"),
+		};
+		let comment = if element.parsed { "" } else { " <!-- not parsed -->" };
+		writeln!(result, "\t<!ELEMENT {name} {content}>{comment}").unwrap();
+		for attribute in &element.attributes {
+			writeln!(result, "\t\t<!ATTLIST {name} {attribute} CDATA #IMPLIED>").unwrap();
+		}
+	}
+	writeln!(result, "]>").unwrap();
+	result
+}
+
+#[cfg(test)]
+mod test {
+	use assert_matches::assert_matches;
+	use test_case::test_case;
+
+	use super::STATUS_SCHEMA;
+
+	#[test_case(0, "<status romname=\"\"/>", true)]
+	#[test_case(1, "<status romname=\"coco2b\"><video throttled=\"true\"/></status>", true)]
+	#[test_case(2, "<status bogus=\"1\"/>", false)]
+	#[test_case(3, "<status><bogus/></status>", false)]
+	#[test_case(4, "<status><images><image tag=\"ext\">text</image></images></status>", false)]
+	#[test_case(5, "<bogus/>", false)]
+	pub fn validate_status_xml(_index: usize, xml: &str, expected: bool) {
+		let actual = super::validate_status_xml(xml.as_bytes()).is_ok();
+		assert_eq!(expected, actual);
+	}
+
+	#[test]
+	pub fn parents_are_described() {
+		for element in STATUS_SCHEMA {
+			if let Some((parent_path, _)) = element.path.rsplit_once('/') {
+				let parent = STATUS_SCHEMA.iter().find(|x| x.path == parent_path);
+				assert_matches!(parent, Some(_), "parent of {}", element.path);
+			}
+		}
+	}
+
+	#[test]
+	pub fn status_schema_dtd() {
+		let dtd = super::status_schema_dtd();
+		assert!(dtd.contains("<!ELEMENT slot (option)*>"));
+		assert!(dtd.contains("<!ELEMENT extension (#PCDATA)>"));
+		assert!(dtd.contains("<!ATTLIST status romname CDATA #IMPLIED>"));
+	}
+}